@@ -1,16 +1,45 @@
-use crossterm::event::{KeyCode, KeyModifiers};
+use crossterm::event::{KeyCode, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::Rect;
+use std::path::Path;
+use std::time::{Duration, Instant};
 
-use crate::lib::config::StockConfig;
+use crate::data::{ChartWindow, TimeRange};
+use crate::lib::{
+    browser,
+    config::{StockConfig, SymbolNote},
+    fuzzy::fuzzy_score,
+    import::read_symbol_file,
+    instrument::format_price,
+    paper::{PaperAccount, Side},
+    portfolio::Position,
+};
 
-use super::state::{App, View};
+use crate::ui::selector::time_range_at;
+
+use super::state::{App, DetailTab, MainMode, MainSort, NoteEdit, OrderTicket, PositionForm, SignalSort, SymbolCheck, TableSort, View};
+
+/// Longest analysis period the edit view accepts, about ten years.
+const MAX_PERIOD_DAYS: i64 = 3650;
+
+/// Two clicks on the same symbol within this long open it.
+const DOUBLE_CLICK: Duration = Duration::from_millis(400);
 
 impl App {
     // ── main view ──────────────────────────────────────────────
 
+    // An arm that checks its condition inside still takes the key, rather
+    // than letting it fall through to a later arm
+    #[allow(clippy::collapsible_match)]
     pub(super) fn handle_main_key(&mut self, code: KeyCode, modifiers: KeyModifiers) -> Option<()> {
+        if self.vim && !modifiers.contains(KeyModifiers::CONTROL) && self.handle_vim_key(code) {
+            return None;
+        }
         match code {
             KeyCode::Char('q') => return Some(()),
             KeyCode::Char('c') if modifiers == KeyModifiers::CONTROL => return Some(()),
+            // Sectors regroup the symbols, so ←→ follow the grouping
+            KeyCode::Left if self.main_mode == MainMode::Sectors => self.step_sector_selection(-1),
+            KeyCode::Right if self.main_mode == MainMode::Sectors => self.step_sector_selection(1),
             KeyCode::Left => {
                 if self.selected_index > 0 {
                     self.selected_index -= 1;
@@ -23,26 +52,333 @@ impl App {
                     self.selected_index += 1;
                 }
             }
+            // Heatmap cells all share one layout, so ↑↓ move between rows
+            KeyCode::Up if self.main_mode == MainMode::Heatmap => {
+                self.selected_index = self.selected_index.saturating_sub(self.heatmap_columns);
+            }
+            KeyCode::Down if self.main_mode == MainMode::Heatmap => {
+                let last = self.analyses.len().saturating_sub(1);
+                self.selected_index = (self.selected_index + self.heatmap_columns).min(last);
+            }
+            // Table rows follow the sort order rather than the watchlist
+            KeyCode::Up if self.main_mode == MainMode::Table => self.step_table_selection(-1),
+            KeyCode::Down if self.main_mode == MainMode::Table => self.step_table_selection(1),
+            KeyCode::Char(c @ '1'..='6') if self.main_mode == MainMode::Table => {
+                let column = [
+                    TableSort::Symbol, TableSort::Price, TableSort::Change,
+                    TableSort::Trend, TableSort::Volatility, TableSort::Volume,
+                ][c as usize - '1' as usize];
+                self.sort_table_by(column);
+            }
             KeyCode::Up => self.cycle_time_range(-1),
             KeyCode::Down => self.cycle_time_range(1),
-            KeyCode::Enter => {
-                self.crosshair_index = None;
-                // Lazy-load: fetch data for this stock on first entry
-                if self.analyses.get(self.selected_index)
-                    .is_none_or(|a| a.stock_data.is_empty())
+            KeyCode::Char('v') => self.set_main_mode(self.main_mode.next()),
+            KeyCode::Enter => self.open_detail(),
+            KeyCode::Esc => return Some(()),
+            KeyCode::Char('e') => self.enter_edit_mode(),
+            KeyCode::Char('L') => self.cycle_grid(),
+            KeyCode::Char('c') => self.toggle_compare_mark(),
+            KeyCode::Char('S') => self.open_signals(),
+            KeyCode::Char('$') => self.open_paper(),
+            KeyCode::Char('P') => self.open_portfolio(),
+            KeyCode::Char('/') => self.open_search(),
+            KeyCode::Char('s') => self.set_main_sort(self.main_sort.next()),
+            KeyCode::Char('r') => self.refetch(self.selected_index),
+            KeyCode::Char('R') => self.refresh_all(),
+            KeyCode::Tab => self.cycle_watchlist(1),
+            KeyCode::BackTab => self.cycle_watchlist(-1),
+            _ => {}
+        }
+        None
+    }
+
+    pub(super) fn set_main_mode(&mut self, mode: MainMode) {
+        self.main_mode = mode;
+        // Heatmap and table summarise every symbol, so load them all
+        if self.main_mode != MainMode::Tiles {
+            self.fetch_missing();
+        }
+        if self.main_mode == MainMode::Sectors {
+            self.fetch_profiles();
+        }
+    }
+
+    pub(super) fn set_main_sort(&mut self, sort: MainSort) {
+        self.main_sort = sort;
+        self.sort_analyses();
+    }
+
+    pub(super) fn open_signals(&mut self) {
+        self.fetch_missing();
+        self.signals_selected = 0;
+        self.current_view = View::Signals;
+    }
+
+    pub(super) fn open_detail(&mut self) {
+        self.crosshair_index = None;
+        self.chart_window = None;
+        // Lazy-load: fetch data for this stock on first entry
+        if self.analyses.get(self.selected_index)
+            .is_none_or(|a| a.stock_data.is_empty())
+        {
+            let tr = self.analyses[self.selected_index].time_range;
+            self.fetch_single_stock(self.selected_index, tr);
+        }
+        // For the fundamentals tab and the ETF/index label
+        self.fetch_profile(self.selected_index);
+        self.fetch_fundamentals(self.selected_index);
+        self.fetch_dividends(self.selected_index);
+        self.fetch_ratings(self.selected_index);
+        self.fetch_insider_trades(self.selected_index);
+        self.fetch_news(self.selected_index);
+        self.fetch_filings(self.selected_index);
+        self.list_selected = 0;
+        self.current_view = View::Detail;
+    }
+
+    /// Click selects a symbol (or a range on its selector), double-click
+    /// opens it, and the wheel scrolls a page at a time.
+    pub(super) fn handle_main_mouse(&mut self, mouse: MouseEvent) {
+        let last = self.analyses.len().saturating_sub(1);
+        match mouse.kind {
+            MouseEventKind::ScrollDown | MouseEventKind::ScrollUp => {
+                let down = mouse.kind == MouseEventKind::ScrollDown;
+                let per_page = self.main_hits.per_page;
+                if self.main_mode == MainMode::Table {
+                    // The table scrolls itself to keep the selection visible
+                    self.step_table_selection(if down { 1 } else { -1 });
+                } else if per_page == 0 {
+                    // Nothing drawn yet
+                } else if down {
+                    let next_page = (self.selected_index / per_page + 1) * per_page;
+                    if next_page <= last {
+                        self.selected_index = next_page;
+                    }
+                } else {
+                    let page = self.selected_index / per_page;
+                    self.selected_index = page.saturating_sub(1) * per_page;
+                }
+            }
+            MouseEventKind::Down(MouseButton::Left) => {
+                let (x, y) = (mouse.column, mouse.row);
+                let contains = |r: &Rect| x >= r.x && x < r.x + r.width && y >= r.y && y < r.y + r.height;
+                let Some(hit) = self.main_hits.tiles.iter().find(|t| contains(&t.area)) else { return };
+                let (index, selector) = (hit.index, hit.selector);
+                if let Some(sel) = selector.filter(|s| contains(s))
+                    && let Some(data) = self.analyses.get(index)
+                    && let Some(range) = time_range_at(sel, data.time_range, index == self.selected_index, x)
                 {
-                    let tr = self.analyses[self.selected_index].time_range;
-                    self.fetch_single_stock(self.selected_index, tr);
+                    self.selected_index = index;
+                    if range != data.time_range {
+                        self.set_time_range(range);
+                    }
+                    self.last_click = None;
+                    return;
                 }
+                let now = Instant::now();
+                let double = self.last_click
+                    .is_some_and(|(t, i)| i == index && now.duration_since(t) < DOUBLE_CLICK);
+                self.selected_index = index;
+                if double {
+                    self.last_click = None;
+                    self.open_detail();
+                } else {
+                    self.last_click = Some((now, index));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Vim motions: `h`/`l` move by symbol, `j`/`k` by row, `gg`/`G` jump
+    /// to the first/last symbol (or the Nth with a count), and a count
+    /// prefix repeats the motion. Digits sort the table there, so counts
+    /// only apply to tiles and the heatmap. Returns false for other keys.
+    fn handle_vim_key(&mut self, code: KeyCode) -> bool {
+        if let KeyCode::Char(c @ '0'..='9') = code
+            && (c != '0' || self.vim_count.is_some())
+            && self.main_mode != MainMode::Table
+        {
+            let digit = c as usize - '0' as usize;
+            self.vim_count = Some((self.vim_count.unwrap_or(0) * 10 + digit).min(9999));
+            return true;
+        }
+        let count = self.vim_count.take();
+        let pending_g = std::mem::take(&mut self.vim_pending_g);
+        let n = count.unwrap_or(1);
+        let last = self.analyses.len().saturating_sub(1);
+        match code {
+            KeyCode::Char(c @ ('h' | 'l')) if self.main_mode == MainMode::Sectors => {
+                for _ in 0..n {
+                    self.step_sector_selection(if c == 'l' { 1 } else { -1 });
+                }
+            }
+            KeyCode::Char('h') => self.selected_index = self.selected_index.saturating_sub(n),
+            KeyCode::Char('l') => self.selected_index = (self.selected_index + n).min(last),
+            KeyCode::Char(c @ ('j' | 'k')) => {
+                let down = c == 'j';
+                if self.main_mode == MainMode::Table {
+                    for _ in 0..n {
+                        self.step_table_selection(if down { 1 } else { -1 });
+                    }
+                } else if self.main_mode == MainMode::Sectors {
+                    let step = (self.heatmap_columns * n) as isize;
+                    self.step_sector_selection(if down { step } else { -step });
+                } else {
+                    let row = match self.main_mode {
+                        MainMode::Heatmap => self.heatmap_columns,
+                        _ => self.grid.cols as usize,
+                    };
+                    self.selected_index = if down {
+                        (self.selected_index + row * n).min(last)
+                    } else {
+                        self.selected_index.saturating_sub(row * n)
+                    };
+                }
+            }
+            KeyCode::Char('g') if pending_g => self.jump_to(count.map_or(0, |c| c.max(1) - 1)),
+            KeyCode::Char('g') => {
+                self.vim_pending_g = true;
+                self.vim_count = count;
+            }
+            KeyCode::Char('G') => self.jump_to(count.map_or(last, |c| c.max(1) - 1)),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Select the symbol at `pos` in display order (the sort order in the
+    /// table, the grouping in the sector view).
+    fn jump_to(&mut self, pos: usize) {
+        if self.analyses.is_empty() {
+            return;
+        }
+        let pos = pos.min(self.analyses.len() - 1);
+        self.selected_index = match self.main_mode {
+            MainMode::Table => self.table_order()[pos],
+            MainMode::Sectors => self.sector_order()[pos],
+            _ => pos,
+        };
+    }
+
+    /// Switch to the next grid preset and remember it.
+    pub(super) fn cycle_grid(&mut self) {
+        self.grid = self.grid.next_preset();
+        let mut ui = self.persistence_manager.get_ui_settings();
+        ui.grid = self.grid;
+        let _ = self.persistence_manager.save_ui_settings(&ui);
+    }
+
+    /// Move the selection to the previous/next row of the sorted table.
+    fn step_table_selection(&mut self, direction: isize) {
+        let order = self.table_order();
+        let Some(pos) = order.iter().position(|&i| i == self.selected_index) else { return };
+        let next = pos.saturating_add_signed(direction).min(order.len() - 1);
+        self.selected_index = order[next];
+    }
+
+    /// Move the selection to the previous/next symbol of the sector grouping.
+    fn step_sector_selection(&mut self, direction: isize) {
+        let order = self.sector_order();
+        let Some(pos) = order.iter().position(|&i| i == self.selected_index) else { return };
+        let next = pos.saturating_add_signed(direction).min(order.len() - 1);
+        self.selected_index = order[next];
+    }
+
+    fn sort_table_by(&mut self, column: TableSort) {
+        if self.table_sort == column {
+            self.table_sort_desc = !self.table_sort_desc;
+        } else {
+            self.table_sort = column;
+            // Names read best A→Z; numbers biggest first
+            self.table_sort_desc = column != TableSort::Symbol;
+        }
+    }
+
+    /// First press marks the selected symbol; pressing again on another
+    /// symbol opens the comparison, pressing on the marked one clears it.
+    fn toggle_compare_mark(&mut self) {
+        let Some(selected) = self
+            .analyses
+            .get(self.selected_index)
+            .map(|a| a.analysis.symbol.clone())
+        else {
+            return;
+        };
+        match self.compare_mark.take() {
+            Some(mark) if mark == selected => {}
+            Some(mark) if self.index_of(&mark).is_some() => self.open_compare(mark, selected),
+            _ => self.compare_mark = Some(selected),
+        }
+    }
+
+    /// Open the comparison view, aligning both symbols on the second one's range.
+    pub(super) fn open_compare(&mut self, first: String, second: String) {
+        let Some(second_idx) = self.index_of(&second) else { return };
+        let range = self.analyses[second_idx].time_range;
+        for sym in [&first, &second] {
+            if let Some(i) = self.index_of(sym) {
+                self.ensure_loaded(i, range);
+            }
+        }
+        self.compare_pair = Some((first, second));
+        self.current_view = View::Compare;
+    }
+
+    // ── compare view ───────────────────────────────────────────
+
+    pub(super) fn handle_compare_key(&mut self, code: KeyCode, modifiers: KeyModifiers) -> Option<()> {
+        match code {
+            KeyCode::Char('q') => return Some(()),
+            KeyCode::Char('c') if modifiers == KeyModifiers::CONTROL => return Some(()),
+            KeyCode::Up => self.cycle_compare_range(-1),
+            KeyCode::Down => self.cycle_compare_range(1),
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Char('c') => {
+                self.compare_pair = None;
+                self.current_view = View::Main;
+            }
+            _ => {}
+        }
+        None
+    }
+
+    // ── prior-period view ──────────────────────────────────────
+
+    pub(super) fn handle_prior_key(&mut self, code: KeyCode, modifiers: KeyModifiers) -> Option<()> {
+        match code {
+            KeyCode::Char('q') => return Some(()),
+            KeyCode::Char('c') if modifiers == KeyModifiers::CONTROL => return Some(()),
+            KeyCode::Up | KeyCode::Down => {
+                self.cycle_time_range(if code == KeyCode::Up { -1 } else { 1 });
+                self.fetch_prior_history();
+            }
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Char('p') => {
+                self.prior_history = None;
                 self.current_view = View::Detail;
             }
-            KeyCode::Esc => return Some(()),
-            KeyCode::Char('e') => self.enter_edit_mode(),
             _ => {}
         }
         None
     }
 
+    /// Move both compared symbols to the next/previous range together.
+    fn cycle_compare_range(&mut self, direction: i8) {
+        let Some((first, _)) = &self.compare_pair else { return };
+        let Some(first_idx) = self.index_of(first) else { return };
+        let range = self.analyses[first_idx].time_range.cycled(direction);
+        self.set_compare_range(range);
+    }
+
+    /// Move both compared symbols to `range`.
+    pub(super) fn set_compare_range(&mut self, range: TimeRange) {
+        let Some((first, second)) = self.compare_pair.clone() else { return };
+        for sym in [&first, &second] {
+            if let Some(i) = self.index_of(sym) {
+                self.ensure_loaded(i, range);
+            }
+        }
+    }
+
     // ── detail view ────────────────────────────────────────────
 
     pub(super) fn handle_detail_key(&mut self, code: KeyCode, modifiers: KeyModifiers) -> Option<()> {
@@ -66,62 +402,355 @@ impl App {
                     );
                 }
             }
-            KeyCode::Up => {
+            // The news, filings and strategies tabs' lists take the arrows,
+            // and the first two Enter
+            KeyCode::Up if self.detail_tab_rows().is_some() => {
+                self.list_selected = self.list_selected.saturating_sub(1);
+            }
+            KeyCode::Down if self.detail_tab_rows().is_some() => {
+                let last = self.detail_tab_rows().unwrap_or(0).saturating_sub(1);
+                self.list_selected = (self.list_selected + 1).min(last);
+            }
+            KeyCode::Enter | KeyCode::Char('o') if self.detail_tab_links().is_some() => self.open_selected_link(),
+            KeyCode::Up if !self.read_only => {
                 self.crosshair_index = None;
                 self.cycle_time_range(-1);
             }
-            KeyCode::Down => {
+            KeyCode::Down if !self.read_only => {
                 self.crosshair_index = None;
                 self.cycle_time_range(1);
             }
+            KeyCode::Char('w') if !self.read_only => self.export_snapshot(),
+            KeyCode::Char('n') if !self.read_only => self.open_note_editor(),
+            KeyCode::Char('b') if !self.read_only => self.open_order(Side::Buy),
+            KeyCode::Char('s') if !self.read_only => self.open_order(Side::Sell),
+            KeyCode::Char('r') if !self.read_only => self.refetch(self.selected_index),
+            KeyCode::Char('E') => self.export_csv(),
+            KeyCode::Char('P') => self.export_chart_image(),
+            KeyCode::Char('p') if !self.read_only => {
+                self.fetch_prior_history();
+                self.current_view = View::PriorPeriod;
+            }
+            KeyCode::Char('+') | KeyCode::Char('=') => self.zoom_chart(2.0 / 3.0),
+            KeyCode::Char('-') => self.zoom_chart(1.5),
+            KeyCode::Char('h') => self.pan_chart(-1),
+            KeyCode::Char('l') => self.pan_chart(1),
+            KeyCode::Char('0') => {
+                self.chart_window = None;
+                self.crosshair_index = None;
+            }
+            KeyCode::Char('g') => {
+                self.show_gridlines = !self.show_gridlines;
+            }
+            KeyCode::Tab => self.set_detail_tab(self.detail_tab.cycled(1)),
+            KeyCode::BackTab => self.set_detail_tab(self.detail_tab.cycled(-1)),
+            KeyCode::Char(c @ '1'..='8') => self.set_detail_tab(DetailTab::ALL[c as usize - '1' as usize]),
             KeyCode::Esc => {
                 if self.crosshair_index.is_some() {
                     self.crosshair_index = None;
+                } else if self.read_only {
+                    // A snapshot has no watchlist to go back to
+                    return Some(());
                 } else {
-                    self.current_view = View::Main;
+                    self.leave_detail();
                 }
             }
-            KeyCode::Enter => {
+            KeyCode::Enter if !self.read_only => {
                 self.crosshair_index = None;
-                self.current_view = View::Main;
+                self.leave_detail();
             }
             _ => {}
         }
         None
     }
 
+    pub(super) fn set_detail_tab(&mut self, tab: DetailTab) {
+        self.detail_tab = tab;
+        self.list_selected = 0;
+    }
+
+    /// Rows of the open tab, for the tabs that are lists.
+    fn detail_tab_rows(&self) -> Option<usize> {
+        match self.detail_tab {
+            DetailTab::Strategies => Some(self.strategies().len()),
+            _ => self.detail_tab_links().map(|links| links.len()),
+        }
+    }
+
+    /// Links of the rows of the open tab, for the tabs that are lists of
+    /// articles or documents.
+    fn detail_tab_links(&self) -> Option<Vec<&str>> {
+        let symbol = &self.analyses.get(self.selected_index)?.analysis.symbol;
+        match self.detail_tab {
+            DetailTab::News => Some(self.news.get(symbol).map_or_else(Vec::new, |n| n.iter().map(|n| n.link.as_str()).collect())),
+            DetailTab::Filings => Some(self.filings.get(symbol).map_or_else(Vec::new, |f| f.iter().map(|f| f.link.as_str()).collect())),
+            _ => None,
+        }
+    }
+
+    /// Open the highlighted row's link in the browser.
+    fn open_selected_link(&mut self) {
+        let Some(link) = self.detail_tab_links().and_then(|links| links.get(self.list_selected).map(|l| l.to_string())) else { return };
+        self.status_message = Some(match browser::open(&link) {
+            Ok(()) => format!("Opened {}", link),
+            Err(e) => format!("Could not open the link: {}", e),
+        });
+    }
+
+    fn leave_detail(&mut self) {
+        self.status_message = None;
+        self.current_view = View::Main;
+    }
+
+    /// First visible bar's index into the full series.
+    fn window_start(&self) -> usize {
+        self.chart_window.map_or(0, |w| w.start)
+    }
+
+    /// Re-point the crosshair at absolute bar `abs` after the window moved,
+    /// clamping it to the visible range.
+    fn keep_crosshair_on(&mut self, abs: Option<usize>) {
+        if let Some(abs) = abs {
+            let n = self.visible_bar_count();
+            let idx = abs.saturating_sub(self.window_start());
+            self.crosshair_index = Some(idx.min(n.saturating_sub(1)));
+        }
+    }
+
+    /// Zoom around the crosshair (or the latest bar when there is none).
+    fn zoom_chart(&mut self, factor: f64) {
+        let total = self.total_bar_count();
+        if total == 0 {
+            return;
+        }
+        let abs = self.crosshair_index.map(|i| self.window_start() + i);
+        let anchor = abs.unwrap_or(total - 1);
+        self.chart_window = ChartWindow::zoom(self.chart_window, total, factor, anchor);
+        self.keep_crosshair_on(abs);
+    }
+
+    /// Pan by a quarter of the visible window in `direction`.
+    fn pan_chart(&mut self, direction: isize) {
+        let total = self.total_bar_count();
+        let Some(window) = self.chart_window else { return };
+        let abs = self.crosshair_index.map(|i| self.window_start() + i);
+        let step = (window.len() / 4).max(1) as isize;
+        self.chart_window = Some(window.pan(total, direction * step));
+        self.keep_crosshair_on(abs);
+    }
+
+    /// Hovering, clicking or dragging over the price chart moves the crosshair
+    /// to the bar under the pointer; leaving the chart keeps the last position.
+    /// Clicking a label on the range selector switches to that range.
+    pub(super) fn handle_detail_mouse(&mut self, mouse: MouseEvent) {
+        if mouse.kind == MouseEventKind::Down(MouseButton::Left)
+            && let Some(sel) = self.detail_selector_area
+            && mouse.row == sel.y
+            && let Some(data) = self.analyses.get(self.selected_index)
+            && let Some(range) = time_range_at(sel, data.time_range, true, mouse.column)
+        {
+            if range != data.time_range {
+                self.crosshair_index = None;
+                self.set_time_range(range);
+            }
+            return;
+        }
+        if !matches!(
+            mouse.kind,
+            MouseEventKind::Moved | MouseEventKind::Down(_) | MouseEventKind::Drag(_)
+        ) {
+            return;
+        }
+        let Some(area) = self.detail_chart_area else { return };
+        // Canvas content sits inside a one-cell border
+        let left = area.x + 1;
+        let right = (area.x + area.width).saturating_sub(2);
+        let top = area.y + 1;
+        let bottom = (area.y + area.height).saturating_sub(2);
+        if mouse.column < left || mouse.column > right || mouse.row < top || mouse.row > bottom {
+            return;
+        }
+
+        let n = self.visible_bar_count();
+        if n == 0 {
+            return;
+        }
+        // Predictions are only drawn when the view reaches the latest bar
+        let at_tail = self.window_start() + n == self.total_bar_count();
+        let n_preds = self
+            .analyses
+            .get(self.selected_index)
+            .filter(|_| at_tail)
+            .map_or(0, |a| a.analysis.predictions.len());
+        let x_max = crate::ui::chart::price_x_max(n, n_preds);
+        let frac = (mouse.column - left) as f64 / (right - left).max(1) as f64;
+        let idx = (frac * x_max).round() as usize;
+        self.crosshair_index = Some(idx.min(n - 1));
+    }
+
+    // ── signals view ───────────────────────────────────────────
+
+    pub(super) fn handle_signals_key(&mut self, code: KeyCode, modifiers: KeyModifiers) -> Option<()> {
+        match code {
+            KeyCode::Char('q') => return Some(()),
+            KeyCode::Char('c') if modifiers == KeyModifiers::CONTROL => return Some(()),
+            KeyCode::Esc => self.current_view = View::Main,
+            KeyCode::Up => self.signals_selected = self.signals_selected.saturating_sub(1),
+            KeyCode::Down => {
+                self.signals_selected = (self.signals_selected + 1).min(self.collect_signals().len().saturating_sub(1));
+            }
+            KeyCode::Char('1') => self.sort_signals_by(SignalSort::Time),
+            KeyCode::Char('2') => self.sort_signals_by(SignalSort::Symbol),
+            KeyCode::Char('3') => self.sort_signals_by(SignalSort::Kind),
+            KeyCode::Enter => {
+                let signals = self.collect_signals();
+                if let Some(sig) = signals.get(self.signals_selected)
+                    && let Some(idx) = self.index_of(&sig.symbol)
+                {
+                    self.selected_index = idx;
+                    self.crosshair_index = None;
+                    self.current_view = View::Detail;
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+
+    /// Sort by `column`; choosing the current column again flips direction.
+    fn sort_signals_by(&mut self, column: SignalSort) {
+        if self.signals_sort == column {
+            self.signals_sort_desc = !self.signals_sort_desc;
+        } else {
+            self.signals_sort = column;
+            self.signals_sort_desc = column == SignalSort::Time;
+        }
+        self.signals_selected = 0;
+    }
+
     // ── edit view ──────────────────────────────────────────────
 
-    fn enter_edit_mode(&mut self) {
+    pub(super) fn enter_edit_mode(&mut self) {
         self.current_view = View::Edit;
-        self.editing_symbols = self
-            .analyses
-            .iter()
-            .map(|a| a.analysis.symbol.clone())
-            .collect();
+        // The saved order, which a metric sort of the main view does not change
+        let saved = self.persistence_manager.get_stock_config();
+        self.editing_period = saved.as_ref().map_or(90, |c| c.analysis_period_days).to_string();
+        self.editing_period_focus = false;
+        self.editing_symbols = match saved {
+            Ok(config) => config.symbols,
+            Err(_) => self.analyses.iter().map(|a| a.analysis.symbol.clone()).collect(),
+        };
         self.editing_selected_index = 0;
         self.new_symbol_input = String::new();
+        self.clear_suggestions();
+        self.symbol_checks.clear();
+        self.save_warned = false;
+        self.edit_message = None;
+    }
+
+    /// Ctrl+O: append the tickers in the file named by the input, checking
+    /// each new one like a typed symbol.
+    fn import_into_edit(&mut self) {
+        let path = self.new_symbol_input.trim().to_string();
+        if path.is_empty() {
+            self.edit_message = Some("Type the path of a text or CSV file, then Ctrl+O".into());
+            return;
+        }
+        match read_symbol_file(Path::new(&path)) {
+            Ok(symbols) => {
+                let total = symbols.len();
+                let new: Vec<String> = symbols.into_iter().filter(|s| !self.editing_symbols.contains(s)).collect();
+                self.edit_message = Some(format!(
+                    "Imported {} of {} symbols from {} ({} already listed)",
+                    new.len(), total, path, total - new.len(),
+                ));
+                for symbol in new {
+                    self.check_symbol(symbol.clone());
+                    self.editing_symbols.push(symbol);
+                }
+                self.save_warned = false;
+                self.new_symbol_input.clear();
+                self.clear_suggestions();
+            }
+            Err(e) => self.edit_message = Some(format!("Could not import {}: {}", path, e)),
+        }
     }
 
+    fn clear_suggestions(&mut self) {
+        self.suggestions.clear();
+        self.suggestion_selected = None;
+        self.suggest_query = self.new_symbol_input.trim().to_string();
+    }
+
+    // An arm that checks its condition inside still takes the key, rather
+    // than letting it fall through to a later arm
+    #[allow(clippy::collapsible_match)]
     pub(super) fn handle_edit_key(&mut self, code: KeyCode, modifiers: KeyModifiers) {
         match code {
+            // Esc closes the suggestion dropdown first
+            KeyCode::Esc if !self.suggestions.is_empty() => self.clear_suggestions(),
             KeyCode::Esc => self.current_view = View::Main,
 
+            KeyCode::Tab | KeyCode::BackTab => {
+                self.editing_period_focus = !self.editing_period_focus;
+                self.clear_suggestions();
+            }
+            KeyCode::Char(c @ '0'..='9') if self.editing_period_focus && self.editing_period.len() < 5 => {
+                self.editing_period.push(c);
+            }
+            KeyCode::Backspace if self.editing_period_focus => {
+                self.editing_period.pop();
+            }
+
             KeyCode::Enter => {
-                if !self.new_symbol_input.trim().is_empty() {
-                    let sym = self.new_symbol_input.trim().to_uppercase();
+                if !self.editing_period_focus && !self.new_symbol_input.trim().is_empty() {
+                    let picked = self.suggestion_selected.and_then(|i| self.suggestions.get(i));
+                    let sym = match picked {
+                        Some(m) => m.symbol.clone(),
+                        None => self.new_symbol_input.trim().to_uppercase(),
+                    };
                     if !self.editing_symbols.contains(&sym) {
+                        // A search match is known to exist; typed text gets checked
+                        if picked.is_some() {
+                            self.symbol_checks.insert(sym.clone(), SymbolCheck::Valid);
+                        } else {
+                            self.check_symbol(sym.clone());
+                        }
                         self.editing_symbols.push(sym);
+                        self.save_warned = false;
                     }
                     self.new_symbol_input.clear();
+                    self.clear_suggestions();
                 }
             }
 
+            // ↑↓ move through the dropdown while it is open; above its
+            // first entry the typed text is used as is
+            KeyCode::Up if !self.suggestions.is_empty() && !modifiers.contains(KeyModifiers::SHIFT) => {
+                self.suggestion_selected = self.suggestion_selected.and_then(|i| i.checked_sub(1));
+            }
+            KeyCode::Down if !self.suggestions.is_empty() && !modifiers.contains(KeyModifiers::SHIFT) => {
+                let last = self.suggestions.len() - 1;
+                self.suggestion_selected = Some(self.suggestion_selected.map_or(0, |i| (i + 1).min(last)));
+            }
+
             KeyCode::Char(c) => {
-                if c == 's' && modifiers.contains(KeyModifiers::CONTROL) {
+                let unverified = self.editing_symbols.iter()
+                    .any(|s| matches!(self.symbol_checks.get(s), Some(SymbolCheck::Checking | SymbolCheck::Unknown(_))));
+                if c == 'o' && modifiers.contains(KeyModifiers::CONTROL) {
+                    self.import_into_edit();
+                } else if c == 's' && modifiers.contains(KeyModifiers::CONTROL) && unverified && !self.save_warned {
+                    // The list shows which; a second Ctrl+S saves anyway
+                    self.save_warned = true;
+                } else if c == 's' && modifiers.contains(KeyModifiers::CONTROL) {
+                    let Some(period) = self.editing_period.parse::<i64>().ok().filter(|p| (1..=MAX_PERIOD_DAYS).contains(p)) else {
+                        self.edit_message = Some(format!("The analysis period must be 1-{} days", MAX_PERIOD_DAYS));
+                        self.editing_period_focus = true;
+                        return;
+                    };
                     let config = StockConfig {
                         symbols: self.editing_symbols.clone(),
-                        analysis_period_days: 90,
+                        analysis_period_days: period,
                     };
                     if let Err(e) = self.persistence_manager.save_stock_config(&config) {
                         eprintln!("Error saving config: {}", e);
@@ -129,26 +758,43 @@ impl App {
                         self.current_view = View::Main;
                         self.refresh_analyses(&config);
                     }
-                } else {
+                } else if !self.editing_period_focus {
                     self.new_symbol_input.push(c);
+                    self.input_changed_at = Instant::now();
                 }
             }
 
             KeyCode::Backspace => {
                 self.new_symbol_input.pop();
+                self.input_changed_at = Instant::now();
             }
 
             KeyCode::Delete => {
                 if !self.editing_symbols.is_empty()
                     && self.editing_selected_index < self.editing_symbols.len()
                 {
-                    self.editing_symbols.remove(self.editing_selected_index);
+                    let removed = self.editing_symbols.remove(self.editing_selected_index);
+                    self.symbol_checks.remove(&removed);
+                    self.save_warned = false;
                     if self.editing_selected_index > 0 {
                         self.editing_selected_index -= 1;
                     }
                 }
             }
 
+            // Shift+↑↓ carry the selected symbol with the selection
+            KeyCode::Up if modifiers.contains(KeyModifiers::SHIFT) && self.editing_selected_index > 0 => {
+                self.editing_symbols.swap(self.editing_selected_index, self.editing_selected_index - 1);
+                self.editing_selected_index -= 1;
+            }
+            KeyCode::Down
+                if modifiers.contains(KeyModifiers::SHIFT)
+                    && self.editing_selected_index + 1 < self.editing_symbols.len() =>
+            {
+                self.editing_symbols.swap(self.editing_selected_index, self.editing_selected_index + 1);
+                self.editing_selected_index += 1;
+            }
+
             KeyCode::Up => {
                 if self.editing_selected_index > 0 {
                     self.editing_selected_index -= 1;
@@ -166,4 +812,354 @@ impl App {
             _ => {}
         }
     }
+
+    // ── help overlay ───────────────────────────────────────────
+
+    pub(super) fn handle_help_key(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Char('?') | KeyCode::Esc | KeyCode::Char('q') | KeyCode::Enter => self.show_help = false,
+            KeyCode::Up | KeyCode::Char('k') => self.help_scroll = self.help_scroll.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => self.help_scroll = self.help_scroll.saturating_add(1),
+            KeyCode::PageUp => self.help_scroll = self.help_scroll.saturating_sub(10),
+            KeyCode::PageDown => self.help_scroll = self.help_scroll.saturating_add(10),
+            _ => {}
+        }
+    }
+
+    // ── error panel ────────────────────────────────────────────
+
+    pub(super) fn handle_errors_key(&mut self, code: KeyCode) {
+        let last = self.error_log.len().saturating_sub(1);
+        match code {
+            KeyCode::Char('x') | KeyCode::Esc => self.show_errors = false,
+            KeyCode::Up => self.errors_selected = self.errors_selected.saturating_sub(1),
+            KeyCode::Down => self.errors_selected = (self.errors_selected + 1).min(last),
+            KeyCode::Char('r') | KeyCode::Enter => self.retry_error(self.errors_selected),
+            KeyCode::Char('R') => {
+                while !self.error_log.is_empty() {
+                    self.retry_error(0);
+                }
+            }
+            KeyCode::Delete | KeyCode::Char('d') if self.errors_selected < self.error_log.len() => {
+                self.error_log.remove(self.errors_selected);
+            }
+            _ => {}
+        }
+        self.errors_selected = self.errors_selected.min(self.error_log.len().saturating_sub(1));
+    }
+
+    // ── note editor ────────────────────────────────────────────
+
+    pub(super) fn open_note_editor(&mut self) {
+        let Some(data) = self.analyses.get(self.selected_index) else { return };
+        let symbol = data.analysis.symbol.clone();
+        let saved = self.notes.get(&symbol).cloned().unwrap_or_default();
+        self.note_edit = Some(NoteEdit {
+            symbol,
+            alias: saved.alias.unwrap_or_default(),
+            note: saved.note.unwrap_or_default(),
+            on_note: false,
+        });
+    }
+
+    /// Tab or ↑↓ move between the alias and note fields, Enter saves both
+    /// and Esc discards the edit.
+    pub(super) fn handle_note_key(&mut self, code: KeyCode) {
+        let Some(edit) = self.note_edit.as_mut() else { return };
+        let field = if edit.on_note { &mut edit.note } else { &mut edit.alias };
+        match code {
+            KeyCode::Esc => self.note_edit = None,
+            KeyCode::Tab | KeyCode::BackTab | KeyCode::Up | KeyCode::Down => edit.on_note = !edit.on_note,
+            KeyCode::Backspace => {
+                field.pop();
+            }
+            KeyCode::Char(c) => field.push(c),
+            KeyCode::Enter => {
+                let Some(edit) = self.note_edit.take() else { return };
+                let trimmed = |s: &str| Some(s.trim().to_string()).filter(|s| !s.is_empty());
+                let note = SymbolNote { alias: trimmed(&edit.alias), note: trimmed(&edit.note) };
+                if let Err(e) = self.persistence_manager.save_note(&edit.symbol, &note) {
+                    self.status_message = Some(format!("Note not saved: {}", e));
+                }
+                if note.is_empty() {
+                    self.notes.remove(&edit.symbol);
+                } else {
+                    self.notes.insert(edit.symbol, note);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // ── paper trading ──────────────────────────────────────────
+
+    pub(super) fn open_paper(&mut self) {
+        self.paper_selected = 0;
+        self.current_view = View::Paper;
+    }
+
+    /// A ticket for the selected symbol: empty for a buy, the whole holding
+    /// for a sell.
+    pub(super) fn open_order(&mut self, side: Side) {
+        let Some(data) = self.analyses.get(self.selected_index) else { return };
+        let symbol = data.analysis.symbol.clone();
+        self.open_order_for(symbol, side);
+    }
+
+    fn open_order_for(&mut self, symbol: String, side: Side) {
+        let held = self.paper.held(&symbol);
+        if side == Side::Sell && held <= 0.0 {
+            self.status_message = Some(format!("No {} held in the paper account", symbol));
+            return;
+        }
+        let shares = if side == Side::Sell { held.to_string() } else { String::new() };
+        self.order = Some(OrderTicket { symbol, side, shares, error: None });
+    }
+
+    /// Digits type the shares, Tab fills in as many as the cash buys or
+    /// all that are held, Enter fills at the latest price and Esc cancels.
+    pub(super) fn handle_order_key(&mut self, code: KeyCode) {
+        let Some(ticket) = self.order.as_mut() else { return };
+        match code {
+            KeyCode::Esc => self.order = None,
+            KeyCode::Char(c) if c.is_ascii_digit() || c == '.' => ticket.shares.push(c),
+            KeyCode::Backspace => {
+                ticket.shares.pop();
+            }
+            KeyCode::Tab => {
+                let (symbol, side) = (ticket.symbol.clone(), ticket.side);
+                let max = match side {
+                    Side::Buy => self.live_price(&symbol)
+                        .map_or(0.0, |price| self.paper.affordable(price, self.strategy.commission)),
+                    Side::Sell => self.paper.held(&symbol),
+                };
+                if let Some(ticket) = self.order.as_mut() {
+                    ticket.shares = max.to_string();
+                }
+            }
+            KeyCode::Enter => self.place_order(),
+            _ => {}
+        }
+    }
+
+    fn place_order(&mut self) {
+        let Some(mut ticket) = self.order.take() else { return };
+        let Some(price) = self.live_price(&ticket.symbol) else {
+            ticket.error = Some(format!("{} has no price loaded", ticket.symbol));
+            self.order = Some(ticket);
+            return;
+        };
+        let shares = ticket.shares.trim().parse::<f64>().unwrap_or(0.0);
+        let now = chrono::Utc::now().timestamp();
+        let commission = self.strategy.commission;
+        let filled = match ticket.side {
+            Side::Buy => self.paper.buy(&ticket.symbol, shares, price, commission, now),
+            Side::Sell => self.paper.sell(&ticket.symbol, shares, price, commission, now),
+        };
+        match filled {
+            Ok(fill) => {
+                let message = format!(
+                    "Paper: {} {} {} at {}",
+                    if fill.side == Side::Buy { "bought" } else { "sold" },
+                    fill.shares,
+                    fill.symbol,
+                    format_price(&fill.symbol, fill.price),
+                );
+                self.status_message = Some(match self.persistence_manager.save_paper_account(&self.paper) {
+                    Ok(()) => message,
+                    Err(e) => format!("{}; not saved: {}", message, e),
+                });
+            }
+            Err(e) => {
+                ticket.error = Some(e);
+                self.order = Some(ticket);
+            }
+        }
+    }
+
+    /// Start the paper account again with the strategy capital.
+    pub(super) fn reset_paper(&mut self) {
+        self.paper = PaperAccount::new(self.strategy.capital);
+        self.paper_selected = 0;
+        self.status_message = Some(match self.persistence_manager.save_paper_account(&self.paper) {
+            Ok(()) => format!("Paper account reset to ${:.2}", self.paper.cash),
+            Err(e) => format!("Paper account not saved: {}", e),
+        });
+    }
+
+    pub(super) fn handle_paper_key(&mut self, code: KeyCode, modifiers: KeyModifiers) -> Option<()> {
+        let selected = self.paper.positions.keys().nth(self.paper_selected).cloned();
+        match code {
+            KeyCode::Char('q') => return Some(()),
+            KeyCode::Char('c') if modifiers == KeyModifiers::CONTROL => return Some(()),
+            KeyCode::Esc => self.current_view = View::Main,
+            KeyCode::Up => self.paper_selected = self.paper_selected.saturating_sub(1),
+            KeyCode::Down => {
+                self.paper_selected = (self.paper_selected + 1).min(self.paper.positions.len().saturating_sub(1));
+            }
+            KeyCode::Char(c @ ('b' | 's')) => {
+                if let Some(symbol) = selected {
+                    self.open_order_for(symbol, if c == 'b' { Side::Buy } else { Side::Sell });
+                }
+            }
+            KeyCode::Enter => {
+                if let Some(idx) = selected.and_then(|s| self.index_of(&s)) {
+                    self.selected_index = idx;
+                    self.open_detail();
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+
+    // ── portfolio ──────────────────────────────────────────────
+
+    pub(super) fn open_portfolio(&mut self) {
+        self.portfolio_selected = 0;
+        self.fetch_portfolio_history();
+        self.current_view = View::Portfolio;
+    }
+
+    /// The form for a new lot, bought today, or for lot `editing`.
+    pub(super) fn open_position_form(&mut self, editing: Option<usize>) {
+        let fields = match editing.and_then(|i| self.portfolio.positions.get(i)) {
+            Some(p) => [p.symbol.clone(), p.quantity.to_string(), p.cost_basis.to_string(), p.purchased.format("%Y-%m-%d").to_string()],
+            None => [String::new(), String::new(), String::new(), chrono::Local::now().format("%Y-%m-%d").to_string()],
+        };
+        self.position_form = Some(PositionForm { editing, fields, field: 0, error: None });
+    }
+
+    /// Typing fills the field, Tab and ↑↓ move between the fields, Enter
+    /// saves the lot and Esc cancels.
+    pub(super) fn handle_position_form_key(&mut self, code: KeyCode) {
+        let Some(form) = self.position_form.as_mut() else { return };
+        match code {
+            KeyCode::Esc => self.position_form = None,
+            KeyCode::Tab | KeyCode::Down => form.field = (form.field + 1) % form.fields.len(),
+            KeyCode::BackTab | KeyCode::Up => form.field = (form.field + form.fields.len() - 1) % form.fields.len(),
+            KeyCode::Backspace => {
+                form.fields[form.field].pop();
+            }
+            KeyCode::Char(c) if form.field == 0 => form.fields[0].push(c.to_ascii_uppercase()),
+            KeyCode::Char(c) if c.is_ascii_digit() || c == '.' || c == '-' => form.fields[form.field].push(c),
+            KeyCode::Enter => self.save_position(),
+            _ => {}
+        }
+    }
+
+    fn save_position(&mut self) {
+        let Some(mut form) = self.position_form.take() else { return };
+        let [symbol, quantity, cost_basis, purchased] = &form.fields;
+        let position = match Position::parse(symbol, quantity, cost_basis, purchased) {
+            Ok(position) => position,
+            Err(e) => {
+                form.error = Some(e);
+                self.position_form = Some(form);
+                return;
+            }
+        };
+        let message = format!("{} {} {}", if form.editing.is_some() { "Updated" } else { "Added" }, position.quantity, position.symbol);
+        match form.editing.filter(|&i| i < self.portfolio.positions.len()) {
+            Some(i) => self.portfolio.positions[i] = position.clone(),
+            None => self.portfolio.positions.push(position.clone()),
+        }
+        self.portfolio.sort();
+        self.portfolio_selected = self.portfolio.positions.iter().position(|p| *p == position).unwrap_or(0);
+        self.status_message = Some(match self.persistence_manager.save_portfolio(&self.portfolio) {
+            Ok(()) => message,
+            Err(e) => format!("{}; not saved: {}", message, e),
+        });
+        self.fetch_portfolio_history();
+    }
+
+    fn remove_position(&mut self) {
+        if self.portfolio_selected >= self.portfolio.positions.len() {
+            return;
+        }
+        let removed = self.portfolio.positions.remove(self.portfolio_selected);
+        self.portfolio_selected = self.portfolio_selected.min(self.portfolio.positions.len().saturating_sub(1));
+        let message = format!("Removed {} {} bought {}", removed.quantity, removed.symbol, removed.purchased.format("%Y-%m-%d"));
+        self.status_message = Some(match self.persistence_manager.save_portfolio(&self.portfolio) {
+            Ok(()) => message,
+            Err(e) => format!("{}; not saved: {}", message, e),
+        });
+    }
+
+    pub(super) fn handle_portfolio_key(&mut self, code: KeyCode, modifiers: KeyModifiers) -> Option<()> {
+        match code {
+            KeyCode::Char('q') => return Some(()),
+            KeyCode::Char('c') if modifiers == KeyModifiers::CONTROL => return Some(()),
+            KeyCode::Esc => self.current_view = View::Main,
+            KeyCode::Up => self.portfolio_selected = self.portfolio_selected.saturating_sub(1),
+            KeyCode::Down => {
+                self.portfolio_selected = (self.portfolio_selected + 1).min(self.portfolio.positions.len().saturating_sub(1));
+            }
+            KeyCode::Char('a') => self.open_position_form(None),
+            KeyCode::Char('e') if self.portfolio_selected < self.portfolio.positions.len() => {
+                self.open_position_form(Some(self.portfolio_selected));
+            }
+            KeyCode::Char('d') | KeyCode::Delete => self.remove_position(),
+            KeyCode::Enter => {
+                let symbol = self.portfolio.positions.get(self.portfolio_selected).map(|p| p.symbol.clone());
+                if let Some(idx) = symbol.and_then(|s| self.index_of(&s)) {
+                    self.selected_index = idx;
+                    self.open_detail();
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+
+    // ── search ─────────────────────────────────────────────────
+
+    pub(super) fn open_search(&mut self) {
+        self.search = Some(String::new());
+        self.search_origin = self.selected_index;
+    }
+
+    /// Typing narrows the main view to matching symbols and selects the
+    /// best one; arrows step through the matches, Enter keeps the
+    /// selection and Esc restores it.
+    pub(super) fn handle_search_key(&mut self, code: KeyCode) {
+        let Some(query) = self.search.as_mut() else { return };
+        match code {
+            KeyCode::Esc => {
+                self.search = None;
+                self.selected_index = self.search_origin;
+            }
+            KeyCode::Enter => self.search = None,
+            KeyCode::Char(c) => {
+                query.push(c);
+                self.select_best_match();
+            }
+            KeyCode::Backspace => {
+                query.pop();
+                self.select_best_match();
+            }
+            KeyCode::Left | KeyCode::Up | KeyCode::Right | KeyCode::Down => {
+                let order = self.main_order();
+                let Some(pos) = order.iter().position(|&i| i == self.selected_index) else { return };
+                let next = if matches!(code, KeyCode::Left | KeyCode::Up) {
+                    pos.saturating_sub(1)
+                } else {
+                    (pos + 1).min(order.len() - 1)
+                };
+                self.selected_index = order[next];
+            }
+            _ => {}
+        }
+    }
+
+    fn select_best_match(&mut self) {
+        let Some(query) = &self.search else { return };
+        let best = (0..self.analyses.len())
+            .filter_map(|i| fuzzy_score(query, &self.analyses[i].analysis.symbol).map(|s| (s, i)))
+            // Highest score; the earliest symbol on ties
+            .max_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)));
+        if let Some((_, i)) = best {
+            self.selected_index = i;
+        }
+    }
 }
@@ -0,0 +1,298 @@
+//! Every key binding, grouped by view. The help overlay ('?') is generated
+//! from this table, with the `ui.keys` remaps applied, so a binding added to
+//! a handler belongs here too.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crossterm::event::KeyCode;
+
+pub struct Binding {
+    pub keys: &'static str,
+    pub description: &'static str,
+}
+
+pub struct Section {
+    pub title: &'static str,
+    /// Only shown when vim-style keys are on.
+    pub vim_only: bool,
+    pub bindings: &'static [Binding],
+}
+
+impl Binding {
+    /// The keys as help shows them under `remap`: a key that another is
+    /// remapped to also lists that one, and a key remapped to act as
+    /// something else is left out of what it used to do. With `J` remapped
+    /// to Down, "↑ ↓" reads "↑ ↓/J".
+    pub fn keys_with(&self, remap: &HashMap<KeyCode, KeyCode>) -> String {
+        let tokens: Vec<&str> = self.keys.split(' ').collect();
+        // A lone "/" between keys separates them; on its own it is the key
+        let is_separator = |token: &str| token == "/" && tokens.len() > 1;
+        let shown: Vec<String> = tokens
+            .iter()
+            .filter_map(|&token| {
+                let Some(key) = help_key(token).filter(|_| !is_separator(token)) else {
+                    return Some(token.to_string());
+                };
+                let mut names: Vec<String> = remap.iter()
+                    .filter(|&(from, to)| *to == key && *from != key)
+                    .map(|(&from, _)| key_name(from))
+                    .collect();
+                names.sort();
+                if !remap.get(&key).is_some_and(|to| *to != key) {
+                    names.insert(0, token.to_string());
+                }
+                (!names.is_empty()).then(|| names.join("/"))
+            })
+            .collect();
+        // Drop the separators a left-out key leaves dangling
+        let mut out: Vec<&str> = Vec::new();
+        for token in &shown {
+            if is_separator(token) && out.last().is_none_or(|last| is_separator(last)) {
+                continue;
+            }
+            out.push(token);
+        }
+        if out.last().is_some_and(|last| is_separator(last)) {
+            out.pop();
+        }
+        out.join(" ")
+    }
+}
+
+/// The key a token of a binding's keys stands for, if it is a single key.
+fn help_key(token: &str) -> Option<KeyCode> {
+    match token {
+        "↑" => Some(KeyCode::Up),
+        "↓" => Some(KeyCode::Down),
+        "←" => Some(KeyCode::Left),
+        "→" => Some(KeyCode::Right),
+        "…" => None,
+        _ => parse_key(token),
+    }
+}
+
+/// How help writes `key`.
+fn key_name(key: KeyCode) -> String {
+    match key {
+        KeyCode::Char(' ') => "Space".into(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::Up => "↑".into(),
+        KeyCode::Down => "↓".into(),
+        KeyCode::Left => "←".into(),
+        KeyCode::Right => "→".into(),
+        KeyCode::PageUp => "PageUp".into(),
+        KeyCode::PageDown => "PageDown".into(),
+        other => format!("{:?}", other),
+    }
+}
+
+const fn b(keys: &'static str, description: &'static str) -> Binding {
+    Binding { keys, description }
+}
+
+pub const KEYMAP: &[Section] = &[
+    Section {
+        title: "Everywhere",
+        vim_only: false,
+        bindings: &[
+            b("?", "toggle this help (not while editing)"),
+            b(":", "command palette (not while editing)"),
+            b("x", "fetch errors, with retry (not while editing)"),
+            b("q / Ctrl+C", "quit (not while editing)"),
+            b("Esc", "back / close"),
+        ],
+    },
+    Section {
+        title: "Main view",
+        vim_only: false,
+        bindings: &[
+            b("← →", "select symbol"),
+            b("↑ ↓", "change time range (tiles, sectors); move by row (heatmap, table)"),
+            b("Enter", "open details"),
+            b("v", "switch view: tiles, heatmap, table, sectors"),
+            b("L", "cycle tile layout"),
+            b("1-6", "sort table by column (again to reverse)"),
+            b("c", "mark for comparison / compare with marked"),
+            b("S", "signals across the watchlist"),
+            b("$", "paper trading account"),
+            b("P", "portfolio of lots held"),
+            b("e", "edit watchlist"),
+            b("s", "sort: watchlist, symbol, % change, volatility, volume, upside"),
+            b("/", "search: type to filter, ←→ step, Enter select, Esc cancel"),
+            b("Tab / Shift+Tab", "switch to the next / previous watchlist"),
+            b("r / R", "refresh the selected symbol / the whole watchlist"),
+        ],
+    },
+    Section {
+        title: "Main view (vim keys)",
+        vim_only: true,
+        bindings: &[
+            b("h l", "previous / next symbol"),
+            b("j k", "down / up a row"),
+            b("gg G", "first / last symbol (5G: the fifth)"),
+            b("3l …", "count prefix repeats a motion"),
+        ],
+    },
+    Section {
+        title: "Detail view",
+        vim_only: false,
+        bindings: &[
+            b("Tab / 1-8", "switch tab: chart, indicators, fundamentals, news, filings, insiders, backtest, strategies"),
+            b("← →", "move crosshair"),
+            b("↑ ↓", "change time range"),
+            b("+ -", "zoom in / out"),
+            b("h l", "pan"),
+            b("0", "reset zoom"),
+            b("g", "toggle gridlines"),
+            b("p", "compare with the prior period"),
+            b("w", "save a snapshot"),
+            b("n", "edit the symbol's alias and note"),
+            b("b / s", "paper buy / sell the symbol at its latest price"),
+            b("r", "refresh the symbol"),
+            b("E", "export bars and indicators to CSV"),
+            b("P", "save the chart as a PNG image"),
+            b("↑ ↓ Enter / o", "news and filings tabs: select a row, open it in the browser"),
+            b("↑ ↓", "strategies tab: select the strategy whose equity curve is shown"),
+            b("Enter", "back"),
+        ],
+    },
+    Section {
+        title: "Compare / prior period",
+        vim_only: false,
+        bindings: &[
+            b("↑ ↓", "change time range"),
+            b("Enter", "back"),
+        ],
+    },
+    Section {
+        title: "Signals",
+        vim_only: false,
+        bindings: &[
+            b("↑ ↓", "select signal"),
+            b("1 2 3", "sort by time / symbol / signal"),
+            b("Enter", "open the symbol's details"),
+        ],
+    },
+    Section {
+        title: "Paper trading",
+        vim_only: false,
+        bindings: &[
+            b("↑ ↓", "select position"),
+            b("b / s", "buy more / sell the selected position"),
+            b("Enter", "open the symbol's details"),
+            b("digits, Tab", "order: type the shares; Tab for as many as the cash buys, or all held"),
+            b("Enter / Esc", "order: fill at the latest price / cancel"),
+        ],
+    },
+    Section {
+        title: "Portfolio",
+        vim_only: false,
+        bindings: &[
+            b("↑ ↓", "select lot"),
+            b("a / e", "add a lot / edit the selected lot"),
+            b("d / Delete", "remove the selected lot"),
+            b("Enter", "open the symbol's details"),
+            b("Tab / ↑ ↓", "form: next / previous field"),
+            b("Enter / Esc", "form: save the lot / cancel"),
+        ],
+    },
+    Section {
+        title: "Edit watchlist",
+        vim_only: false,
+        bindings: &[
+            b("type, Enter", "add symbol; names search for matching tickers"),
+            b("↑ ↓ (matches open)", "choose a match; Esc closes the list"),
+            b("Delete", "remove selected"),
+            b("↑ ↓", "select"),
+            b("Shift+↑ ↓", "move selected up / down the tile order"),
+            b("Tab", "switch between the symbol input and the analysis period"),
+            b("Ctrl+O", "import the tickers in the file whose path is typed"),
+            b("Ctrl+S", "save and exit (twice to save unknown symbols)"),
+        ],
+    },
+    Section {
+        title: "Command palette",
+        vim_only: false,
+        bindings: &[
+            b("type", "filter commands (fuzzy)"),
+            b("↑ ↓", "select"),
+            b("Enter", "run"),
+            b("Esc", "close"),
+        ],
+    },
+    Section {
+        title: "Fetch errors",
+        vim_only: false,
+        bindings: &[
+            b("↑ ↓", "select"),
+            b("r / Enter", "retry the selected symbol"),
+            b("R", "retry all"),
+            b("d / Delete", "dismiss"),
+            b("x / Esc", "close"),
+        ],
+    },
+    Section {
+        title: "Mouse",
+        vim_only: false,
+        bindings: &[
+            b("click", "select tile; pick a range on a selector; move crosshair"),
+            b("double-click", "open details"),
+            b("wheel", "page through the watchlist"),
+        ],
+    },
+];
+
+/// A key by its config name: a single character, or one of `Up`, `Down`,
+/// `Left`, `Right`, `Enter`, `Esc`, `Tab`, `Space`, `Home`, `End`,
+/// `PageUp`, `PageDown`, `Delete`, `Backspace` (case-insensitive).
+pub fn parse_key(name: &str) -> Option<KeyCode> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(KeyCode::Char(c));
+    }
+    Some(match name.to_ascii_lowercase().as_str() {
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "enter" => KeyCode::Enter,
+        "esc" => KeyCode::Esc,
+        "tab" => KeyCode::Tab,
+        "space" => KeyCode::Char(' '),
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        "delete" => KeyCode::Delete,
+        "backspace" => KeyCode::Backspace,
+        _ => return None,
+    })
+}
+
+/// The `ui.keys` remaps, or the first key name that is not recognised.
+pub fn parse_remaps(keys: &BTreeMap<String, String>) -> Result<HashMap<KeyCode, KeyCode>, String> {
+    keys.iter()
+        .map(|(from, to)| {
+            let parse = |name: &str| parse_key(name).ok_or_else(|| format!("Unknown key '{}'", name));
+            Ok((parse(from)?, parse(to)?))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_help_shows_remapped_keys() {
+        let keys = BTreeMap::from([("J".to_string(), "Down".to_string()), ("q".to_string(), "Esc".to_string())]);
+        let remap = parse_remaps(&keys).unwrap();
+        let find = |keys: &str| KEYMAP.iter().flat_map(|s| s.bindings).find(|b| b.keys == keys).unwrap();
+        assert_eq!(find("↑ ↓").keys_with(&remap), "↑ ↓/J");
+        assert_eq!(find("Esc").keys_with(&remap), "Esc/q");
+        // q no longer quits; Ctrl+C still does
+        assert_eq!(find("q / Ctrl+C").keys_with(&remap), "Ctrl+C");
+        assert_eq!(find("/").keys_with(&remap), "/");
+        assert_eq!(find("1-6").keys_with(&HashMap::new()), "1-6");
+    }
+}
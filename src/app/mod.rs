@@ -1,5 +1,7 @@
 mod handlers;
+pub mod keymap;
+pub mod palette;
 mod run;
 mod state;
 
-pub use state::{AnalysisWithChartData, App};
+pub use state::{sma_spread, AnalysisWithChartData, App, DetailTab, LoadState, MainMode, MainSort, SignalSort, SymbolCheck, TableSort, UNCLASSIFIED_SECTOR};
@@ -0,0 +1,336 @@
+//! ':' command palette: fuzzy search over actions, so features that have no
+//! key of their own (or whose key is hard to remember) are still reachable.
+
+use crossterm::event::{KeyCode, KeyModifiers};
+
+use crate::data::TimeRange;
+use crate::lib::{forecast::Forecaster, fuzzy::fuzzy_score, paper::Side, provider::KEYED_PROVIDERS, report::ReportFormat};
+
+use super::state::{App, DetailTab, MainMode, MainSort, View};
+
+/// Something the palette can run.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Command {
+    /// Select a symbol by its watchlist index.
+    GoTo(usize),
+    TimeRange(TimeRange),
+    MainMode(MainMode),
+    Sort(MainSort),
+    /// Prompts for the symbol, then adds it to the saved watchlist.
+    AddSymbol,
+    /// Show the watchlist at this index of `App::watchlists`.
+    SwitchWatchlist(usize),
+    /// Prompts for a name, then creates and shows that watchlist.
+    NewWatchlist,
+    DeleteWatchlist(usize),
+    /// Load the profile at this index of `App::profile_names`.
+    SwitchProfile(usize),
+    /// Prompts for a name, then creates and loads that profile.
+    NewProfile,
+    /// Prompts for the named provider's API key, then stores it.
+    SetApiKey(&'static str),
+    /// Compare the selected symbol with another one.
+    Compare(usize),
+    Refresh,
+    RefreshAll,
+    ToggleStream,
+    ToggleAdjusted,
+    ToggleSentimentForecast,
+    /// Forecast the selected symbol with this model.
+    SetForecaster(Forecaster),
+    CycleLayout,
+    Signals,
+    EditWatchlist,
+    DetailTab(DetailTab),
+    ToggleGridlines,
+    PriorPeriod,
+    Snapshot,
+    ExportCsv,
+    ExportChart,
+    Report(ReportFormat),
+    EditNote,
+    /// Open a paper order for the selected symbol.
+    PaperOrder(Side),
+    Paper,
+    ResetPaper,
+    Portfolio,
+    Errors,
+    Help,
+    Quit,
+}
+
+impl Command {
+    /// What the palette asks for before running, for commands that take text.
+    pub fn prompt(self) -> Option<&'static str> {
+        match self {
+            Command::AddSymbol => Some("Symbol to add"),
+            Command::NewWatchlist => Some("Name of the new watchlist"),
+            Command::NewProfile => Some("Name of the new profile"),
+            Command::SetApiKey(_) => Some("API key"),
+            _ => None,
+        }
+    }
+
+    /// Whether the typed argument is hidden as it is entered.
+    pub fn is_secret(self) -> bool {
+        matches!(self, Command::SetApiKey(_))
+    }
+}
+
+#[derive(Default)]
+pub struct Palette {
+    pub query: String,
+    /// Row of the highlighted match.
+    pub selected: usize,
+    /// Command waiting for its text argument; the query holds the argument.
+    pub pending: Option<Command>,
+}
+
+impl App {
+    /// Every command that makes sense in the current view, labelled.
+    fn palette_commands(&self) -> Vec<(String, Command)> {
+        let mut commands = Vec::new();
+        let in_detail = matches!(self.current_view, View::Detail);
+        let selected = self.analyses.get(self.selected_index).map(|a| a.analysis.symbol.as_str());
+
+        if !self.read_only {
+            for (i, a) in self.analyses.iter().enumerate() {
+                commands.push((format!("Go to {}", self.symbol_label(&a.analysis.symbol)), Command::GoTo(i)));
+            }
+            for &tr in TimeRange::all() {
+                commands.push((format!("Time range: {}", tr.as_str()), Command::TimeRange(tr)));
+            }
+            for (label, mode) in [("tiles", MainMode::Tiles), ("heatmap", MainMode::Heatmap), ("table", MainMode::Table), ("sectors", MainMode::Sectors)] {
+                commands.push((format!("View: {}", label), Command::MainMode(mode)));
+            }
+            for sort in MainSort::ALL {
+                commands.push((format!("Sort by {}", sort.label()), Command::Sort(sort)));
+            }
+            commands.push(("Add symbol to watchlist…".into(), Command::AddSymbol));
+            for (i, name) in self.watchlists.iter().enumerate().filter(|(_, w)| **w != self.active_watchlist) {
+                commands.push((format!("Switch watchlist: {}", name), Command::SwitchWatchlist(i)));
+            }
+            commands.push(("New watchlist…".into(), Command::NewWatchlist));
+            for (i, name) in self.watchlists.iter().enumerate().filter(|(_, w)| **w != self.active_watchlist) {
+                commands.push((format!("Delete watchlist: {}", name), Command::DeleteWatchlist(i)));
+            }
+            if let Some(sel) = selected {
+                for (i, a) in self.analyses.iter().enumerate().filter(|&(i, _)| i != self.selected_index) {
+                    commands.push((format!("Compare {} with {}", sel, a.analysis.symbol), Command::Compare(i)));
+                }
+            }
+            for (i, name) in self.profile_names.iter().enumerate().filter(|(_, p)| *p != self.profile()) {
+                commands.push((format!("Switch profile: {}", name), Command::SwitchProfile(i)));
+            }
+            commands.push(("New profile…".into(), Command::NewProfile));
+            for &name in KEYED_PROVIDERS {
+                commands.push((format!("Set API key: {}…", name), Command::SetApiKey(name)));
+            }
+            if selected.is_some() {
+                commands.push(("Refresh selected symbol".into(), Command::Refresh));
+            }
+            commands.push(("Refresh watchlist".into(), Command::RefreshAll));
+            let stream = if self.refresh.stream { "Stop live quotes" } else { "Stream live quotes" };
+            commands.push((stream.into(), Command::ToggleStream));
+            let adjusted = if self.adjusted { "Show prices as traded" } else { "Adjust prices for splits and dividends" };
+            commands.push((adjusted.into(), Command::ToggleAdjusted));
+            let sentiment = if self.sentiment_forecast { "Forecast from the price trend only" } else { "Tilt forecasts by news sentiment" };
+            commands.push((sentiment.into(), Command::ToggleSentimentForecast));
+            if let Some(sel) = selected {
+                let current = self.forecast.for_symbol(sel);
+                for forecaster in Forecaster::ALL.iter().copied().filter(|&f| f != current) {
+                    commands.push((format!("Forecast {} with {}", sel, forecaster.label()), Command::SetForecaster(forecaster)));
+                }
+            }
+            commands.push(("Cycle tile layout".into(), Command::CycleLayout));
+            commands.push(("Signals across the watchlist".into(), Command::Signals));
+            commands.push(("Paper trading account".into(), Command::Paper));
+            commands.push(("Reset the paper trading account".into(), Command::ResetPaper));
+            commands.push(("Portfolio".into(), Command::Portfolio));
+            commands.push(("Edit watchlist".into(), Command::EditWatchlist));
+        }
+        if in_detail {
+            for tab in DetailTab::ALL {
+                commands.push((format!("Tab: {}", tab.title()), Command::DetailTab(tab)));
+            }
+            commands.push(("Toggle gridlines".into(), Command::ToggleGridlines));
+            commands.push(("Export data to CSV".into(), Command::ExportCsv));
+            commands.push(("Save the chart as PNG".into(), Command::ExportChart));
+            if !self.read_only {
+                commands.push(("Compare with the prior period".into(), Command::PriorPeriod));
+                commands.push(("Save a snapshot".into(), Command::Snapshot));
+                commands.push(("Edit alias and note".into(), Command::EditNote));
+                if let Some(sel) = selected {
+                    commands.push((format!("Paper buy {}…", sel), Command::PaperOrder(Side::Buy)));
+                    commands.push((format!("Paper sell {}…", sel), Command::PaperOrder(Side::Sell)));
+                }
+            }
+        }
+        if !in_detail {
+            commands.push(("Write report (Markdown)".into(), Command::Report(ReportFormat::Markdown)));
+            commands.push(("Write report (HTML)".into(), Command::Report(ReportFormat::Html)));
+        }
+        commands.push(("Show fetch errors".into(), Command::Errors));
+        commands.push(("Show key bindings".into(), Command::Help));
+        commands.push(("Quit".into(), Command::Quit));
+        commands
+    }
+
+    /// Commands matching the palette query, best first. Ties keep the
+    /// listing order, so an empty query shows everything as listed.
+    pub fn palette_matches(&self) -> Vec<(String, Command)> {
+        let Some(palette) = &self.palette else { return Vec::new() };
+        if palette.pending.is_some() {
+            return Vec::new();
+        }
+        let mut scored: Vec<(i32, (String, Command))> = self.palette_commands()
+            .into_iter()
+            .filter_map(|(label, cmd)| fuzzy_score(&palette.query, &label).map(|s| (s, (label, cmd))))
+            .collect();
+        scored.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+        scored.into_iter().map(|(_, m)| m).collect()
+    }
+
+    pub(super) fn open_palette(&mut self) {
+        self.palette = Some(Palette::default());
+    }
+
+    pub(super) fn handle_palette_key(&mut self, code: KeyCode, modifiers: KeyModifiers) -> Option<()> {
+        let palette = self.palette.as_mut()?;
+        match code {
+            KeyCode::Esc => self.palette = None,
+            KeyCode::Char('c') if modifiers == KeyModifiers::CONTROL => return Some(()),
+            KeyCode::Char(c) => {
+                palette.query.push(c);
+                palette.selected = 0;
+            }
+            KeyCode::Backspace => {
+                palette.query.pop();
+                palette.selected = 0;
+            }
+            KeyCode::Up => palette.selected = palette.selected.saturating_sub(1),
+            KeyCode::Down => {
+                let last = self.palette_matches().len().saturating_sub(1);
+                if let Some(palette) = self.palette.as_mut() {
+                    palette.selected = (palette.selected + 1).min(last);
+                }
+            }
+            KeyCode::Enter => {
+                if let Some(cmd) = palette.pending {
+                    let arg = palette.query.trim().to_string();
+                    self.palette = None;
+                    if !arg.is_empty() {
+                        self.run_command_with(cmd, &arg);
+                    }
+                    return None;
+                }
+                let matches = self.palette_matches();
+                let palette = self.palette.as_mut()?;
+                let &(_, cmd) = matches.get(palette.selected)?;
+                if cmd.prompt().is_some() {
+                    *palette = Palette { pending: Some(cmd), ..Palette::default() };
+                    return None;
+                }
+                self.palette = None;
+                return self.run_command(cmd);
+            }
+            _ => {}
+        }
+        None
+    }
+
+    /// Run a command that needs no argument. `Some(())` means quit.
+    fn run_command(&mut self, cmd: Command) -> Option<()> {
+        match cmd {
+            Command::GoTo(i) => {
+                self.selected_index = i;
+                match self.current_view {
+                    View::Detail => self.open_detail(),
+                    _ => self.current_view = View::Main,
+                }
+            }
+            Command::TimeRange(tr) => match self.current_view {
+                View::Compare => self.set_compare_range(tr),
+                View::PriorPeriod => {
+                    self.set_time_range(tr);
+                    self.fetch_prior_history();
+                }
+                _ => {
+                    self.crosshair_index = None;
+                    self.set_time_range(tr);
+                }
+            },
+            Command::MainMode(mode) => {
+                self.current_view = View::Main;
+                self.set_main_mode(mode);
+            }
+            Command::Sort(sort) => self.set_main_sort(sort),
+            Command::SwitchWatchlist(i) => {
+                let name = self.watchlists[i].clone();
+                self.current_view = View::Main;
+                self.switch_watchlist(&name);
+            }
+            Command::DeleteWatchlist(i) => {
+                let name = self.watchlists[i].clone();
+                self.delete_watchlist(&name);
+            }
+            Command::SwitchProfile(i) => {
+                let name = self.profile_names[i].clone();
+                self.switch_profile(&name);
+            }
+            Command::Compare(i) => {
+                let first = self.analyses[self.selected_index].analysis.symbol.clone();
+                let second = self.analyses[i].analysis.symbol.clone();
+                self.open_compare(first, second);
+            }
+            Command::Refresh => self.refetch(self.selected_index),
+            Command::RefreshAll => self.refresh_all(),
+            Command::ToggleStream => self.toggle_stream(),
+            Command::ToggleAdjusted => self.toggle_adjusted(),
+            Command::ToggleSentimentForecast => self.toggle_sentiment_forecast(),
+            Command::SetForecaster(forecaster) => self.set_forecaster(forecaster),
+            Command::CycleLayout => self.cycle_grid(),
+            Command::Signals => self.open_signals(),
+            Command::EditWatchlist => self.enter_edit_mode(),
+            Command::DetailTab(tab) => self.set_detail_tab(tab),
+            Command::ToggleGridlines => self.show_gridlines = !self.show_gridlines,
+            Command::PriorPeriod => {
+                self.fetch_prior_history();
+                self.current_view = View::PriorPeriod;
+            }
+            Command::Snapshot => self.export_snapshot(),
+            Command::ExportCsv => self.export_csv(),
+            Command::ExportChart => self.export_chart_image(),
+            Command::Report(format) => self.write_report(format),
+            Command::EditNote => self.open_note_editor(),
+            Command::PaperOrder(side) => self.open_order(side),
+            Command::Paper => self.open_paper(),
+            Command::ResetPaper => self.reset_paper(),
+            Command::Portfolio => self.open_portfolio(),
+            Command::Errors => {
+                self.show_errors = true;
+                self.errors_selected = 0;
+            }
+            Command::Help => {
+                self.show_help = true;
+                self.help_scroll = 0;
+            }
+            Command::Quit => return Some(()),
+            Command::AddSymbol | Command::NewWatchlist | Command::NewProfile | Command::SetApiKey(_) => {}
+        }
+        None
+    }
+
+    fn run_command_with(&mut self, cmd: Command, arg: &str) {
+        match cmd {
+            Command::AddSymbol => self.add_symbol(arg),
+            Command::NewWatchlist => {
+                self.current_view = View::Main;
+                self.switch_watchlist(arg);
+            }
+            Command::NewProfile => self.switch_profile(arg),
+            Command::SetApiKey(name) => self.set_api_key(name, arg),
+            _ => {}
+        }
+    }
+}
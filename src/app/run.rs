@@ -1,13 +1,30 @@
 use anyhow::Result;
-use crossterm::event::{self, Event};
+use crossterm::event::{self, Event, KeyCode, KeyModifiers};
 use ratatui::prelude::*;
-use std::io;
+use std::io::{self, Write};
 use std::time::Duration;
 
 use crate::lib::config::StockConfig;
-use crate::ui::{detail::draw_detail_ui, layout::draw_ui};
+use crate::ui::{
+    compare::{draw_compare_ui, draw_prior_period_ui},
+    detail::{detail_chart_image, draw_detail_view},
+    errors::draw_error_panel,
+    graphics::{self, Protocol},
+    help::draw_help_overlay,
+    note::draw_note_editor,
+    paper::draw_order_ticket,
+    portfolio::draw_position_form,
+    palette::draw_palette,
+    heatmap::draw_heatmap_ui,
+    layout::{draw_ui, MainHits},
+    plain::{draw_plain_compare, draw_plain_detail, draw_plain_main, draw_plain_prior},
+    sectors::draw_sectors_ui,
+    status::draw_status_bar,
+    table::draw_table_ui,
+    watchlists::draw_watchlist_tabs,
+};
 
-use super::state::{App, View};
+use super::state::{App, MainMode, View};
 
 impl App {
     pub fn run(
@@ -18,58 +35,275 @@ impl App {
     ) -> Result<()> {
         self.config_file_path = config_file_path.to_string();
         self.initialize_placeholders(config);
+        self.restore_session();
+        self.restart_stream();
+        let result = self.event_loop(terminal);
+        self.save_session();
+        result
+    }
+
+    /// Show a snapshot built with [`App::from_snapshot`] until the user quits.
+    pub fn view_snapshot(&mut self, terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> Result<()> {
+        self.event_loop(terminal)
+    }
+
+    /// Send the raster detail chart after a frame, or take it down when
+    /// `area` is `None`. Images are large, so one is only re-sent when
+    /// something it shows has changed.
+    fn place_raster(&mut self, terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, area: Option<Rect>) -> Result<()> {
+        let Some(protocol) = self.graphics else { return Ok(()) };
+        let data = area.and(self.analyses.get(self.selected_index)).filter(|d| !d.stock_data.is_empty());
+        let key = data.zip(area).map(|(d, area)| format!(
+            "{} {} {} {} {:?} {:?} {} {} {} {:?}",
+            d.analysis.symbol, d.time_range.as_str(), d.stock_data.len(), d.analysis.current_price,
+            self.chart_window, self.crosshair_index, self.show_gridlines, self.adjusted,
+            self.dividends_of(d).len(), area,
+        ));
+        if key == self.raster_shown {
+            return Ok(());
+        }
+
+        let backend = terminal.backend_mut();
+        if self.raster_shown.is_some() && protocol == Protocol::Kitty {
+            write!(backend, "{}", graphics::KITTY_DELETE_ALL)?;
+        }
+        if let (Some(data), Some(area)) = (data, area) {
+            let (cw, ch) = graphics::cell_pixels();
+            let (w, h) = (area.width as usize * cw as usize, area.height as usize * ch as usize);
+            let dividends = self.dividends_of(data);
+            if let Some(image) = detail_chart_image(data, self.chart_window, self.crosshair_index, self.show_gridlines, &dividends, w, h) {
+                crossterm::queue!(backend, crossterm::cursor::MoveTo(area.x, area.y))?;
+                match protocol {
+                    Protocol::Kitty => write!(backend, "{}", graphics::kitty_sequence(&image.to_png(), area.width, area.height))?,
+                    Protocol::Sixel => write!(backend, "{}", graphics::sixel_sequence(&image))?,
+                }
+            }
+        } else if protocol == Protocol::Sixel {
+            // Sixel pixels live in the cells; repaint everything to drop them
+            terminal.clear()?;
+        }
+        Write::flush(terminal.backend_mut())?;
+        self.raster_shown = key;
+        Ok(())
+    }
 
+    fn event_loop(&mut self, terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> Result<()> {
         loop {
             self.check_refresh();
+            self.check_auto_refresh();
             self.drain_events();
+            if matches!(self.current_view, View::Edit) {
+                self.poll_symbol_search();
+            }
 
             // ── render ───────────────────────────────────────
-            match self.current_view {
-                View::Main => {
-                    terminal.draw(|f| draw_ui(
-                        f,
-                        &self.analyses,
-                        self.selected_index,
-                        self.loading_total,
-                        self.loading_done,
-                        &self.loading_errors,
-                    ))?;
+            let mut main_hits = None;
+            let mut heatmap_columns = None;
+            let mut detail_areas = None;
+            terminal.draw(|f| {
+                let full = f.size();
+                let rows = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Min(0), Constraint::Length(1)])
+                    .split(full);
+                let mut area = rows[0];
+                if matches!(self.current_view, View::Main) && !self.plain && self.watchlists.len() > 1 {
+                    let split = Layout::default()
+                        .direction(Direction::Vertical)
+                        .constraints([Constraint::Length(1), Constraint::Min(0)])
+                        .split(area);
+                    draw_watchlist_tabs(f, self, split[0]);
+                    area = split[1];
                 }
-                View::Detail => {
-                    terminal.draw(|f| {
+                match self.current_view {
+                    View::Main if self.plain => draw_plain_main(f, self, area),
+                    View::Main => match self.main_mode {
+                        MainMode::Tiles => main_hits = Some(draw_ui(f, self, area)),
+                        MainMode::Heatmap => {
+                            let (cols, hits) = draw_heatmap_ui(f, self, area);
+                            heatmap_columns = Some(cols);
+                            main_hits = Some(hits);
+                        }
+                        MainMode::Sectors => {
+                            let (cols, hits) = draw_sectors_ui(f, self, area);
+                            heatmap_columns = Some(cols);
+                            main_hits = Some(hits);
+                        }
+                        MainMode::Table => {
+                            draw_table_ui(f, self, area);
+                            main_hits = Some(MainHits::default());
+                        }
+                    },
+                    View::Detail => {
                         if let Some(data) = self.analyses.get(self.selected_index) {
-                            draw_detail_ui(
-                                f, data, f.size(), self.crosshair_index,
-                                self.loading_total, self.loading_done,
-                            );
+                            if self.plain {
+                                draw_plain_detail(f, self, data, area);
+                            } else {
+                                detail_areas = draw_detail_view(f, self, data, area);
+                            }
                         }
-                    })?;
+                    }
+                    View::Edit => crate::ui::edit::draw_edit_ui(f, self, area),
+                    View::Signals => crate::ui::signals::draw_signals_ui(f, self, area),
+                    View::Paper => crate::ui::paper::draw_paper_ui(f, self, area),
+                    View::Portfolio => crate::ui::portfolio::draw_portfolio_ui(f, self, area),
+                    View::PriorPeriod => {
+                        if let Some(data) = self.analyses.get(self.selected_index) {
+                            let history = self.prior_history.as_ref()
+                                .filter(|(sym, tr, _)| *sym == data.analysis.symbol && *tr == data.time_range)
+                                .map(|(_, _, h)| h);
+                            let loading = self.loading_total > 0 && self.loading_done < self.loading_total;
+                            if self.plain {
+                                draw_plain_prior(f, self, data, history, area);
+                            } else {
+                                draw_prior_period_ui(f, data, history, area, loading, self.zone, &self.theme);
+                            }
+                        }
+                    }
+                    View::Compare => {
+                        if let Some((a, b)) = &self.compare_pair
+                            && let (Some(a), Some(b)) = (self.index_of(a), self.index_of(b))
+                        {
+                            if self.plain {
+                                draw_plain_compare(f, self, &self.analyses[a], &self.analyses[b], area);
+                            } else {
+                                draw_compare_ui(
+                                    f, &self.analyses[a], &self.analyses[b], area,
+                                    self.loading_total, self.loading_done, self.zone, &self.theme,
+                                );
+                            }
+                        }
+                    }
+                }
+                draw_status_bar(f, self, rows[1]);
+                if self.show_errors {
+                    draw_error_panel(f, self, full);
                 }
-                View::Edit => {
-                    terminal.draw(|f| {
-                        crate::ui::edit::draw_edit_ui(f, self, f.size());
-                    })?;
+                if self.show_help {
+                    draw_help_overlay(f, self, full);
                 }
+                if self.palette.is_some() {
+                    draw_palette(f, self, full);
+                }
+                if self.note_edit.is_some() {
+                    draw_note_editor(f, self, full);
+                }
+                if self.order.is_some() {
+                    draw_order_ticket(f, self, full);
+                }
+                if self.position_form.is_some() {
+                    draw_position_form(f, self, full);
+                }
+            })?;
+            if let Some(hits) = main_hits {
+                self.main_hits = hits;
+                // Tiles load as they come into view
+                if !self.read_only && self.main_mode == MainMode::Tiles {
+                    self.fetch_visible();
+                }
+            }
+            if let Some(cols) = heatmap_columns {
+                self.heatmap_columns = cols;
             }
+            let chart_area = detail_areas.as_ref().map(|a| a.chart);
+            self.detail_chart_area = chart_area;
+            self.detail_selector_area = detail_areas.and_then(|a| a.selector);
+            // Inside the chart's border; taken down while an overlay covers it
+            let overlay = self.show_help || self.show_errors || self.palette.is_some() || self.note_edit.is_some()
+                || self.order.is_some() || self.position_form.is_some();
+            let raster_area = chart_area.filter(|_| !overlay).map(|a| Rect::new(
+                a.x + 1, a.y + 1, a.width.saturating_sub(2), a.height.saturating_sub(2),
+            ));
+            self.place_raster(terminal, raster_area)?;
 
             // ── input ────────────────────────────────────────
-            if event::poll(Duration::from_millis(100))?
-                && let Event::Key(key) = event::read()?
-            {
-                let code = key.code;
-                let mods = key.modifiers;
+            if event::poll(Duration::from_millis(100))? {
+                match event::read()? {
+                    Event::Key(key) => {
+                        let mut code = key.code;
+                        let mods = key.modifiers;
+                        // Remaps only apply where keys are commands, not typed text
+                        let typing = self.note_edit.is_some()
+                            || self.order.is_some()
+                            || self.position_form.is_some()
+                            || self.search.is_some()
+                            || self.palette.is_some()
+                            || matches!(self.current_view, View::Edit);
+                        if !typing && !mods.contains(KeyModifiers::CONTROL) {
+                            code = self.key_remap.get(&code).copied().unwrap_or(code);
+                        }
 
-                let quit = match self.current_view {
-                    View::Main => self.handle_main_key(code, mods),
-                    View::Detail => self.handle_detail_key(code, mods),
-                    View::Edit => {
-                        self.handle_edit_key(code, mods);
-                        None
-                    }
-                };
+                        if self.note_edit.is_some() {
+                            self.handle_note_key(code);
+                            continue;
+                        }
+                        if self.order.is_some() {
+                            self.handle_order_key(code);
+                            continue;
+                        }
+                        if self.position_form.is_some() {
+                            self.handle_position_form_key(code);
+                            continue;
+                        }
+                        if self.search.is_some() {
+                            self.handle_search_key(code);
+                            continue;
+                        }
+                        if self.palette.is_some() {
+                            if self.handle_palette_key(code, mods).is_some() {
+                                return Ok(());
+                            }
+                            continue;
+                        }
+                        if self.show_help {
+                            self.handle_help_key(code);
+                            continue;
+                        }
+                        if self.show_errors {
+                            self.handle_errors_key(code);
+                            continue;
+                        }
+                        if code == KeyCode::Char('?') && !matches!(self.current_view, View::Edit) {
+                            self.show_help = true;
+                            self.help_scroll = 0;
+                            continue;
+                        }
+                        if code == KeyCode::Char(':') && !matches!(self.current_view, View::Edit) {
+                            self.open_palette();
+                            continue;
+                        }
+                        if code == KeyCode::Char('x') && !matches!(self.current_view, View::Edit) {
+                            self.show_errors = true;
+                            self.errors_selected = 0;
+                            continue;
+                        }
+
+                        let quit = match self.current_view {
+                            View::Main => self.handle_main_key(code, mods),
+                            View::Detail => self.handle_detail_key(code, mods),
+                            View::Compare => self.handle_compare_key(code, mods),
+                            View::Signals => self.handle_signals_key(code, mods),
+                            View::Paper => self.handle_paper_key(code, mods),
+                            View::Portfolio => self.handle_portfolio_key(code, mods),
+                            View::PriorPeriod => self.handle_prior_key(code, mods),
+                            View::Edit => {
+                                self.handle_edit_key(code, mods);
+                                None
+                            }
+                        };
 
-                if quit.is_some() {
-                    return Ok(());
+                        if quit.is_some() {
+                            return Ok(());
+                        }
+                    }
+                    Event::Mouse(_) if self.show_help || self.show_errors || self.palette.is_some() || self.note_edit.is_some()
+                        || self.order.is_some() || self.position_form.is_some() => {}
+                    Event::Mouse(mouse) => match self.current_view {
+                        View::Detail => self.handle_detail_mouse(mouse),
+                        View::Main if !self.plain => self.handle_main_mouse(mouse),
+                        _ => {}
+                    },
+                    _ => {}
                 }
             }
         }
@@ -1,28 +1,353 @@
 use anyhow::Result;
+use ratatui::layout::Rect;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+use crossterm::event::KeyCode;
 use tokio::runtime::Runtime;
 
 use crate::lib::{
-    analysis::{analyze_stock, StockAnalysis},
-    config::StockConfig,
+    accuracy::{self, Accuracy, PredictionLog},
+    backtest::{self, Backtest, DEFAULT_WINDOW},
+    analysis::{analyze_stock, apply_sentiment, prediction_upside, StockAnalysis},
+    fuzzy::fuzzy_score,
+    insiders::{self, InsiderTrade, NetActivity},
+    instrument::AssetClass,
+    calendar,
+    market::{follows_us_sessions, Session},
+    config::{ForecastSettings, GridSize, PortfolioSettings, StrategySettings, RefreshSettings, StockConfig, SymbolNote, SymbolProfile},
+    dividends::{self, Dividend, DividendSummary},
+    edgar::{self, Filing},
+    error::AppError,
+    export,
+    forecast::Forecaster,
+    paper::{PaperAccount, Side},
+    portfolio::Portfolio,
     persistence::PersistenceManager,
+    report::{write_report, Report, ReportEntry, ReportFormat},
+    sentiment::{self, Sentiment},
+    signals::{self, detect_signals, Signal, SignalHistory},
+    snapshot::{Snapshot, SNAPSHOT_VERSION},
     stock_data::StockData,
-    yahooapi::fetch_stock_data,
+    strategy::{self, Strategy},
+    stream::{apply_tick, stream_quotes, Tick},
+    timezone::DisplayZone,
+    provider::{provider_for_watchlist, provider_named, AnalystRatings, DataProvider, Fundamentals, NewsItem, Quote, SymbolMatch},
+    yahooapi::YahooProvider,
 };
-use crate::data::TimeRange;
+use crate::data::{calculate_volatility, trailing_range, ChartWindow, Interval, TimeRange};
 use crate::event::AppEvent;
 
+use super::keymap::parse_remaps;
+use super::palette::Palette;
+use crate::ui::{
+    detail::detail_chart_image,
+    graphics::{self, Protocol},
+    layout::MainHits,
+    theme::Theme,
+};
+
 // ── public types ───────────────────────────────────────────────
 
+/// Sector group for symbols the provider gives no sector, e.g. funds.
+pub const OTHER_SECTOR: &str = "Other";
+/// Sector group for symbols not looked up yet, or whose lookup failed.
+pub const UNCLASSIFIED_SECTOR: &str = "Unclassified";
+/// Width and height in pixels of charts saved with `P`.
+const CHART_IMAGE_SIZE: (usize, usize) = (1200, 600);
+
 pub enum View {
     Main,
     Detail,
     Edit,
+    Compare,
+    Signals,
+    PriorPeriod,
+    Paper,
+    Portfolio,
+}
+
+/// How the main view lays out the watchlist, cycled with 'v'.
+#[derive(Clone, Copy, PartialEq)]
+pub enum MainMode {
+    Tiles,
+    Heatmap,
+    Table,
+    Sectors,
+}
+
+impl MainMode {
+    pub fn next(self) -> MainMode {
+        match self {
+            MainMode::Tiles => MainMode::Heatmap,
+            MainMode::Heatmap => MainMode::Table,
+            MainMode::Table => MainMode::Sectors,
+            MainMode::Sectors => MainMode::Tiles,
+        }
+    }
+
+    /// Name in the saved session.
+    pub fn name(self) -> &'static str {
+        match self {
+            MainMode::Tiles => "tiles",
+            MainMode::Heatmap => "heatmap",
+            MainMode::Table => "table",
+            MainMode::Sectors => "sectors",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<MainMode> {
+        [MainMode::Tiles, MainMode::Heatmap, MainMode::Table, MainMode::Sectors]
+            .into_iter()
+            .find(|m| m.name() == name)
+    }
+}
+
+/// Tab of the detail view, switched with Tab or 1-8.
+#[derive(Clone, Copy, PartialEq)]
+pub enum DetailTab {
+    Chart,
+    Indicators,
+    Fundamentals,
+    News,
+    Filings,
+    Insiders,
+    Backtest,
+    Strategies,
+}
+
+impl DetailTab {
+    pub const ALL: [DetailTab; 8] = [
+        DetailTab::Chart,
+        DetailTab::Indicators,
+        DetailTab::Fundamentals,
+        DetailTab::News,
+        DetailTab::Filings,
+        DetailTab::Insiders,
+        DetailTab::Backtest,
+        DetailTab::Strategies,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            DetailTab::Chart => "Chart",
+            DetailTab::Indicators => "Indicators",
+            DetailTab::Fundamentals => "Fundamentals",
+            DetailTab::News => "News",
+            DetailTab::Filings => "Filings",
+            DetailTab::Insiders => "Insiders",
+            DetailTab::Backtest => "Backtest",
+            DetailTab::Strategies => "Strategies",
+        }
+    }
+
+    /// Next (`direction > 0`) or previous tab, wrapping around.
+    pub fn cycled(self, direction: i8) -> DetailTab {
+        let i = DetailTab::ALL.iter().position(|&t| t == self).unwrap_or(0);
+        let n = DetailTab::ALL.len();
+        DetailTab::ALL[if direction > 0 { (i + 1) % n } else { (i + n - 1) % n }]
+    }
+}
+
+/// Order of the watchlist in the main view, cycled with 's'.
+#[derive(Clone, Copy, PartialEq)]
+pub enum MainSort {
+    /// As saved in the config.
+    Watchlist,
+    Symbol,
+    Change,
+    Volatility,
+    Volume,
+    /// Last forecast relative to the current price.
+    Upside,
+}
+
+impl MainSort {
+    pub const ALL: [MainSort; 6] = [
+        MainSort::Watchlist, MainSort::Symbol, MainSort::Change,
+        MainSort::Volatility, MainSort::Volume, MainSort::Upside,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            MainSort::Watchlist => "watchlist order",
+            MainSort::Symbol => "symbol",
+            MainSort::Change => "% change",
+            MainSort::Volatility => "volatility",
+            MainSort::Volume => "volume",
+            MainSort::Upside => "prediction upside",
+        }
+    }
+
+    /// Name in the saved session.
+    pub fn name(self) -> &'static str {
+        match self {
+            MainSort::Watchlist => "watchlist",
+            MainSort::Symbol => "symbol",
+            MainSort::Change => "change",
+            MainSort::Volatility => "volatility",
+            MainSort::Volume => "volume",
+            MainSort::Upside => "upside",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<MainSort> {
+        MainSort::ALL.into_iter().find(|s| s.name() == name)
+    }
+
+    pub fn next(self) -> MainSort {
+        let i = MainSort::ALL.iter().position(|&s| s == self).unwrap_or(0);
+        MainSort::ALL[(i + 1) % MainSort::ALL.len()]
+    }
+}
+
+/// Column the main-view table is ordered by.
+#[derive(Clone, Copy, PartialEq)]
+pub enum TableSort {
+    Symbol,
+    Price,
+    Change,
+    /// SMA-10 vs SMA-50 spread.
+    Trend,
+    Volatility,
+    Volume,
+}
+
+/// Column the signals table is ordered by.
+#[derive(Clone, Copy, PartialEq)]
+pub enum SignalSort {
+    Time,
+    Symbol,
+    Kind,
+}
+
+/// Where an entry's data stands, so tiles can say why they are empty.
+#[derive(Clone, Copy, PartialEq)]
+pub enum LoadState<'a> {
+    Loaded,
+    Fetching,
+    /// The last fetch failed with this message.
+    Failed(&'a str),
+    /// Never requested.
+    NotLoaded,
+}
+
+/// A failed fetch, kept in the error panel until retried or dismissed.
+pub struct FetchError {
+    pub symbol: String,
+    pub time_range: TimeRange,
+    pub message: String,
+    pub at: chrono::DateTime<chrono::Local>,
+}
+
+/// Result of checking a symbol added in the edit view against the provider.
+pub enum SymbolCheck {
+    Checking,
+    /// Known to exist without asking the provider: picked from the search
+    /// matches, or imported.
+    Valid,
+    /// The provider quoted it.
+    Quoted(Quote),
+    /// The provider did not return data; holds its message.
+    Unknown(String),
+}
+
+/// Open alias/note editor for a symbol, opened with 'n' in the detail view.
+pub struct NoteEdit {
+    pub symbol: String,
+    pub alias: String,
+    pub note: String,
+    /// Typing goes to the note rather than the alias.
+    pub on_note: bool,
+}
+
+/// Open paper order for a symbol, opened with 'b' or 's'.
+pub struct OrderTicket {
+    pub symbol: String,
+    pub side: Side,
+    /// Shares as typed.
+    pub shares: String,
+    /// Why the last Enter did not fill.
+    pub error: Option<String>,
+}
+
+/// Open portfolio form for a lot, opened with 'a' or 'e' in the portfolio
+/// view.
+pub struct PositionForm {
+    /// The lot being edited; `None` adds one.
+    pub editing: Option<usize>,
+    /// Symbol, quantity, cost basis and purchase date, as typed.
+    pub fields: [String; 4],
+    /// Field typing goes to.
+    pub field: usize,
+    /// Why the last Enter did not save.
+    pub error: Option<String>,
 }
 
 pub struct AnalysisWithChartData {
     pub analysis: StockAnalysis,
     pub stock_data: StockData,
     pub time_range: TimeRange,
+    /// When `stock_data` arrived from the provider.
+    pub fetched_at: Option<chrono::DateTime<chrono::Local>>,
+    /// `stock_data` came from the on-disk cache and has not been refreshed.
+    pub cached: bool,
+    /// Provider that served `stock_data`; `None` for cached, imported or
+    /// snapshot bars.
+    pub source: Option<&'static str>,
+}
+
+/// SMA-10 relative to SMA-50, in percent; positive while the short average
+/// is above the long one.
+pub fn sma_spread(analysis: &StockAnalysis) -> Option<f64> {
+    let (fast, slow) = (analysis.sma_10?, analysis.sma_50?);
+    (slow != 0.0).then(|| (fast / slow - 1.0) * 100.0)
+}
+
+/// Empty entry for a symbol whose data has not been fetched yet.
+fn placeholder(symbol: &str) -> AnalysisWithChartData {
+    AnalysisWithChartData {
+        analysis: StockAnalysis {
+            symbol: symbol.to_string(),
+            current_price: 0.0,
+            sma_10: None,
+            sma_50: None,
+            ema_20: None,
+            sma10_values: vec![],
+            sma50_values: vec![],
+            ema20_values: vec![],
+            kalman_values: vec![],
+            kalman_slope: None,
+            predictions: vec![],
+            forecaster: Forecaster::default(),
+            garch_volatility: None,
+            prediction_bands: vec![],
+            recent_change: None,
+        },
+        stock_data: StockData::new(),
+        time_range: TimeRange::ThreeMonths,
+        fetched_at: None,
+        cached: false,
+        source: None,
+    }
+}
+
+/// `loaded` bars with those since its last one appended, or the whole
+/// range again if that request fails.
+async fn top_up(provider: &dyn DataProvider, symbol: &str, time_range: TimeRange, mut loaded: StockData) -> Result<StockData, AppError> {
+    let since = loaded.timestamps.last().copied().unwrap_or_default();
+    match provider.fetch_history_since(symbol, time_range, since).await {
+        Ok(newer) => {
+            loaded.merge_newer(&newer);
+            Ok(loaded)
+        }
+        Err(_) => provider.fetch_history(symbol, time_range).await,
+    }
+}
+
+/// Bars as charts and analysis use them: adjusted for splits and
+/// dividends, or as traded.
+fn prices(stock_data: StockData, adjusted: bool) -> StockData {
+    if adjusted { stock_data.adjusted() } else { stock_data }
 }
 
 // ── App state ──────────────────────────────────────────────────
@@ -30,46 +355,439 @@ pub struct AnalysisWithChartData {
 pub struct App {
     pub analyses: Vec<AnalysisWithChartData>,
     pub selected_index: usize,
-    pub selected_time_range_index: usize,
     pub(super) rt: Runtime,
+    /// Source of quotes, history, search and profiles.
+    pub provider: Arc<dyn DataProvider>,
     pub current_view: View,
+    pub main_mode: MainMode,
+    pub main_sort: MainSort,
+    /// Columns of the heatmap or sector view from the last render, so keys can move by a row.
+    pub(super) heatmap_columns: usize,
     pub config_file_path: String,
+    /// Name of the watchlist being shown.
+    pub active_watchlist: String,
+    /// Every watchlist name, sorted, for the tabs over the main view.
+    pub watchlists: Vec<String>,
+    /// Every profile name, the default first, for the palette.
+    pub(super) profile_names: Vec<String>,
     pub editing_symbols: Vec<String>,
     pub editing_selected_index: usize,
     pub new_symbol_input: String,
+    /// Analysis period field of the edit view, in days, as typed.
+    pub editing_period: String,
+    /// Typing goes to the period field rather than the symbol input.
+    pub editing_period_focus: bool,
+    /// Tickers matching the typed input, for the edit view's dropdown.
+    pub suggestions: Vec<SymbolMatch>,
+    pub suggestion_selected: Option<usize>,
+    /// Checks of symbols added in this edit session, by symbol.
+    pub symbol_checks: HashMap<String, SymbolCheck>,
+    /// Outcome of the last Ctrl+O import, under the symbol list.
+    pub edit_message: Option<String>,
+    /// Ctrl+S was refused once because of unknown symbols; again saves anyway.
+    pub save_warned: bool,
+    /// Input last sent to the symbol search, and when the input last changed.
+    pub(super) suggest_query: String,
+    pub(super) input_changed_at: std::time::Instant,
     pub(super) should_refresh_after_save: bool,
-    pub(super) channel_rx: Option<std::sync::mpsc::Receiver<AppEvent>>,
+    pub(super) channel_tx: std::sync::mpsc::Sender<AppEvent>,
+    pub(super) channel_rx: std::sync::mpsc::Receiver<AppEvent>,
     pub(super) persistence_manager: PersistenceManager,
     pub crosshair_index: Option<usize>,
+    pub detail_tab: DetailTab,
+    /// Zoomed/panned slice of the detail chart; `None` shows every bar.
+    pub chart_window: Option<ChartWindow>,
+    /// Dotted gridlines on price charts, toggled with 'g' in the detail view.
+    pub show_gridlines: bool,
+    /// Symbol marked with 'c' as the first leg of a quick comparison.
+    pub compare_mark: Option<String>,
+    /// Symbols shown in the comparison view (marked, selected).
+    pub compare_pair: Option<(String, String)>,
+    /// Selected row in the signals table.
+    pub signals_selected: usize,
+    /// Selected position in the paper trading view.
+    pub paper_selected: usize,
+    /// Selected lot in the portfolio view.
+    pub portfolio_selected: usize,
+    pub signals_sort: SignalSort,
+    pub signals_sort_desc: bool,
+    pub table_sort: TableSort,
+    pub table_sort_desc: bool,
+    /// Price chart area from the last detail-view render, for mouse hit-testing.
+    pub(super) detail_chart_area: Option<Rect>,
+    /// Time range selector from the last detail-view render.
+    pub(super) detail_selector_area: Option<Rect>,
     /// How many stocks are being fetched in the current batch.
     pub loading_total: usize,
     /// How many have completed (success or error) so far.
     pub loading_done: usize,
     /// Error messages collected during the current load batch.
     pub loading_errors: Vec<String>,
+    /// Showing a snapshot: nothing is fetched and the watchlist is untouched.
+    pub read_only: bool,
+    /// One-line message for the detail header (e.g. where a snapshot went).
+    pub status_message: Option<String>,
+    /// Screen-reader friendly text panels instead of charts.
+    pub plain: bool,
+    /// Tile layout of the main view, cycled with 'L'.
+    pub grid: GridSize,
+    /// History backing the prior-period overlay: symbol, range, bars.
+    pub prior_history: Option<(String, TimeRange, StockData)>,
+    /// Terminal graphics protocol for the detail chart; `None` draws braille.
+    pub graphics: Option<Protocol>,
+    /// What the raster chart on screen shows, so it is only re-sent on change.
+    pub(super) raster_shown: Option<String>,
+    /// Colours for every view; an invalid configured theme falls back to the default.
+    pub theme: Theme,
+    /// Vim-style motions in the main view.
+    pub vim: bool,
+    /// Zone dates and times are shown in.
+    pub zone: DisplayZone,
+    /// Prices adjusted for splits and dividends rather than as traded.
+    pub adjusted: bool,
+    /// Forecasts tilted by the sentiment of the symbol's headlines.
+    pub sentiment_forecast: bool,
+    /// Which model forecasts each symbol.
+    pub forecast: ForecastSettings,
+    /// The account the strategies tab trades with.
+    pub strategy: StrategySettings,
+    /// Keys from `ui.keys` and the keys they act as, outside text input.
+    pub key_remap: HashMap<KeyCode, KeyCode>,
+    /// Count typed before a vim motion, e.g. the 3 of `3l`.
+    pub vim_count: Option<usize>,
+    /// First `g` of `gg` typed.
+    pub vim_pending_g: bool,
+    /// Symbol areas from the last main-view render, for mouse hit-testing.
+    pub(super) main_hits: MainHits,
+    /// Time and symbol of the last click, to spot double-clicks.
+    pub(super) last_click: Option<(std::time::Instant, usize)>,
+    /// Key binding overlay, toggled with '?'.
+    pub show_help: bool,
+    pub help_scroll: u16,
+    /// Open ':' command palette.
+    pub palette: Option<Palette>,
+    /// '/' search query while the search box is open; the main view only
+    /// shows matching symbols.
+    pub search: Option<String>,
+    /// Selection before the search started, restored by Esc.
+    pub(super) search_origin: usize,
+    /// Provider requests still running: symbol and range.
+    pub in_flight: Vec<(String, TimeRange)>,
+    /// When data last arrived, for the status bar.
+    pub last_refresh: Option<chrono::DateTime<chrono::Local>>,
+    /// Show only cached history and send no provider requests.
+    pub offline: bool,
+    /// Background refresh interval and whether it waits for market hours.
+    pub refresh: RefreshSettings,
+    /// When loaded quotes were last re-fetched in the background.
+    pub(super) last_auto_refresh: std::time::Instant,
+    /// Task feeding live prices from the quote stream into the channel.
+    pub(super) stream_task: Option<tokio::task::JoinHandle<()>>,
+    /// The quote stream is connected, for the status bar.
+    pub stream_live: bool,
+    /// Symbols whose bars are being re-analysed in the background after
+    /// streamed prices, and whether more arrived since it started.
+    pub(super) reanalyzing: HashMap<String, bool>,
+    /// Failed fetches not yet retried or dismissed, oldest first.
+    pub error_log: Vec<FetchError>,
+    /// Error panel, toggled with 'x'.
+    pub show_errors: bool,
+    pub errors_selected: usize,
+    /// Aliases and notes by symbol.
+    pub notes: BTreeMap<String, SymbolNote>,
+    pub note_edit: Option<NoteEdit>,
+    /// Sector metadata by symbol, saved so each is looked up once.
+    pub profiles: BTreeMap<String, SymbolProfile>,
+    /// Symbols whose profile has been asked for this session.
+    pub(super) profiles_requested: HashSet<String>,
+    /// Dividend history by symbol, fetched once a session when a symbol
+    /// is opened; empty for symbols that pay none.
+    pub dividends: HashMap<String, Vec<Dividend>>,
+    /// Valuation and short-interest figures by symbol, fetched once a
+    /// session when a stock loads.
+    pub fundamentals: HashMap<String, Fundamentals>,
+    /// Analyst recommendations and targets by symbol, fetched once a
+    /// session when a stock loads.
+    pub ratings: HashMap<String, AnalystRatings>,
+    /// Headlines by symbol, newest first, fetched like `dividends`.
+    pub news: HashMap<String, Vec<NewsItem>>,
+    /// Insider trades by symbol, newest first, fetched once a session when
+    /// a stock loads.
+    pub insider_trades: HashMap<String, Vec<InsiderTrade>>,
+    /// SEC filings of US stocks by symbol, newest first, fetched like
+    /// `dividends`.
+    pub filings: HashMap<String, Vec<Filing>>,
+    /// Highlighted row of the news or filings tab.
+    pub list_selected: usize,
+    /// Forecasts made from daily bars and how they turned out, by symbol.
+    pub prediction_log: PredictionLog,
+    /// Active signals by symbol, evaluated whenever bars arrive or change.
+    pub signals: HashMap<String, Vec<Signal>>,
+    /// Every signal that has fired, by symbol.
+    pub signal_history: SignalHistory,
+    /// Backtests of each symbol's forecast models on its loaded bars, run
+    /// when the backtest tab opens; `None` while running. New bars drop
+    /// them.
+    pub backtests: HashMap<String, Option<Vec<Backtest>>>,
+    /// The paper trading account, opened with the strategy capital.
+    pub paper: PaperAccount,
+    pub order: Option<OrderTicket>,
+    /// The lots held, opened with 'P'.
+    pub portfolio: Portfolio,
+    /// Where the portfolio's allocation counts as concentrated.
+    pub portfolio_settings: PortfolioSettings,
+    pub position_form: Option<PositionForm>,
+    /// Bars of each symbol held, reaching back to the first purchase, for
+    /// prices of symbols off the watchlist and for the risk figures. Empty
+    /// while fetching.
+    pub portfolio_history: HashMap<String, (TimeRange, StockData)>,
 }
 
 impl App {
-    pub fn new() -> Result<Self> {
-        let persistence_manager = PersistenceManager::new()?;
-        Ok(Self {
+    pub fn new(persistence_manager: PersistenceManager) -> Result<Self> {
+        let ui = persistence_manager.get_ui_settings();
+        let (active_watchlist, watchlists) = persistence_manager.watchlist_names();
+        let profiles = persistence_manager.get_profiles();
+        let notes = persistence_manager.get_notes();
+        let profile_names = persistence_manager.profile_names();
+        let refresh = persistence_manager.get_refresh_settings();
+        let forecast = persistence_manager.get_forecast_settings();
+        let strategy = persistence_manager.get_strategy_settings();
+        let prediction_log = persistence_manager.prediction_log()?;
+        let signal_history = persistence_manager.signal_history()?;
+        let paper = persistence_manager.paper_account()?.unwrap_or_else(|| PaperAccount::new(strategy.capital));
+        let portfolio = persistence_manager.portfolio()?;
+        let portfolio_settings = persistence_manager.get_portfolio_settings();
+        let (channel_tx, channel_rx) = std::sync::mpsc::channel();
+        let mut app = Self {
             analyses: Vec::new(),
             selected_index: 0,
-            selected_time_range_index: 0,
             rt: Runtime::new()?,
+            provider: Arc::new(YahooProvider),
             current_view: View::Main,
+            main_mode: MainMode::Tiles,
+            main_sort: MainSort::Watchlist,
+            heatmap_columns: 1,
             config_file_path: String::from("persistent_config"),
+            active_watchlist,
+            watchlists,
+            profile_names,
             editing_symbols: Vec::new(),
             editing_selected_index: 0,
             new_symbol_input: String::new(),
+            editing_period: String::new(),
+            editing_period_focus: false,
+            suggestions: Vec::new(),
+            suggestion_selected: None,
+            symbol_checks: HashMap::new(),
+            save_warned: false,
+            edit_message: None,
+            suggest_query: String::new(),
+            input_changed_at: std::time::Instant::now(),
             should_refresh_after_save: false,
-            channel_rx: None,
+            channel_tx,
+            channel_rx,
             persistence_manager,
             crosshair_index: None,
+            detail_tab: DetailTab::Chart,
+            chart_window: None,
+            show_gridlines: true,
+            compare_mark: None,
+            compare_pair: None,
+            signals_selected: 0,
+            paper_selected: 0,
+            portfolio_selected: 0,
+            signals_sort: SignalSort::Time,
+            signals_sort_desc: true,
+            table_sort: TableSort::Symbol,
+            table_sort_desc: false,
+            detail_chart_area: None,
+            detail_selector_area: None,
             loading_total: 0,
             loading_done: 0,
             loading_errors: Vec::new(),
-        })
+            read_only: false,
+            status_message: None,
+            plain: ui.plain,
+            grid: ui.grid,
+            prior_history: None,
+            graphics: graphics::detect(ui.graphics),
+            raster_shown: None,
+            theme: Theme::from_settings(&ui.theme).unwrap_or_default(),
+            vim: ui.vim,
+            zone: ui.display_zone(),
+            adjusted: ui.adjusted,
+            sentiment_forecast: ui.sentiment_forecast,
+            forecast,
+            strategy,
+            key_remap: parse_remaps(&ui.keys).unwrap_or_default(),
+            vim_count: None,
+            vim_pending_g: false,
+            main_hits: MainHits::default(),
+            last_click: None,
+            show_help: false,
+            help_scroll: 0,
+            palette: None,
+            search: None,
+            search_origin: 0,
+            in_flight: Vec::new(),
+            last_refresh: None,
+            offline: false,
+            refresh,
+            last_auto_refresh: std::time::Instant::now(),
+            stream_task: None,
+            stream_live: false,
+            reanalyzing: HashMap::new(),
+            error_log: Vec::new(),
+            show_errors: false,
+            errors_selected: 0,
+            notes,
+            note_edit: None,
+            profiles,
+            profiles_requested: HashSet::new(),
+            dividends: HashMap::new(),
+            fundamentals: HashMap::new(),
+            prediction_log,
+            signals: HashMap::new(),
+            signal_history,
+            ratings: HashMap::new(),
+            news: HashMap::new(),
+            insider_trades: HashMap::new(),
+            filings: HashMap::new(),
+            list_selected: 0,
+            backtests: HashMap::new(),
+            paper,
+            order: None,
+            portfolio,
+            portfolio_settings,
+            position_form: None,
+            portfolio_history: HashMap::new(),
+        };
+        app.select_provider();
+        Ok(app)
+    }
+
+    /// App showing a single saved snapshot in a read-only detail view.
+    pub fn from_snapshot(persistence_manager: PersistenceManager, snapshot: Snapshot) -> Result<Self> {
+        let mut app = Self::new(persistence_manager)?;
+        let taken = chrono::DateTime::from_timestamp(snapshot.created_at, 0)
+            .map(|dt| app.zone.instant(&dt).format("%Y-%m-%d %H:%M %:z").to_string())
+            .unwrap_or_else(|| "?".into());
+        let mut status = format!("read-only snapshot taken {}", taken);
+        if !snapshot.notes.is_empty() {
+            status.push_str(&format!(" — {}", snapshot.notes.join("; ")));
+        }
+        let total = snapshot.stock_data.len();
+        app.chart_window = snapshot.window
+            .filter(|&(start, end)| start < end && end <= total)
+            .map(|(start, end)| ChartWindow { start, end });
+        app.analyses.push(AnalysisWithChartData {
+            analysis: snapshot.analysis,
+            stock_data: snapshot.stock_data,
+            time_range: TimeRange::from_label(&snapshot.time_range).unwrap_or(TimeRange::ThreeMonths),
+            fetched_at: chrono::DateTime::from_timestamp(snapshot.created_at, 0).map(|t| t.with_timezone(&chrono::Local)),
+            cached: false,
+            source: None,
+        });
+        app.current_view = View::Detail;
+        app.read_only = true;
+        app.status_message = Some(status);
+        Ok(app)
+    }
+
+    /// Save the selected symbol's detail view as a JSON snapshot.
+    pub(super) fn export_snapshot(&mut self) {
+        let Some(data) = self.analyses.get(self.selected_index) else { return };
+        if data.stock_data.is_empty() {
+            self.status_message = Some("Nothing to snapshot yet".into());
+            return;
+        }
+        let symbol = &data.analysis.symbol;
+        let snapshot = Snapshot {
+            version: SNAPSHOT_VERSION,
+            created_at: chrono::Utc::now().timestamp(),
+            symbol: symbol.clone(),
+            time_range: data.time_range.as_str().to_string(),
+            window: self.chart_window.map(|w| (w.start, w.end)),
+            stock_data: data.stock_data.clone(),
+            analysis: data.analysis.clone(),
+            signals: detect_signals(symbol, &data.stock_data, &data.analysis),
+            notes: Vec::new(),
+        };
+        self.status_message = Some(match snapshot.save(&self.persistence_manager.snapshot_dir()) {
+            Ok(path) => format!("Snapshot saved to {}", path.display()),
+            Err(e) => format!("Snapshot failed: {}", e),
+        });
+    }
+
+    /// Write the selected symbol's bars and indicators to a CSV file.
+    pub(super) fn export_csv(&mut self) {
+        let Some(data) = self.analyses.get(self.selected_index) else { return };
+        if data.stock_data.is_empty() {
+            self.status_message = Some("Nothing to export yet".into());
+            return;
+        }
+        let file_name = format!(
+            "{}-{}-{}.csv",
+            data.analysis.symbol, data.time_range.as_str(), chrono::Local::now().format("%Y%m%d-%H%M%S"),
+        );
+        let path = self.persistence_manager.export_dir().join(file_name);
+        self.status_message = Some(match export::write_csv(&path, &data.stock_data, &data.analysis, self.zone) {
+            Ok(()) => format!("Exported to {}", path.display()),
+            Err(e) => format!("Export failed: {}", e),
+        });
+    }
+
+    /// Saves the detail chart as it is shown (window, overlays and
+    /// gridlines, without the crosshair) as a PNG under the export dir.
+    pub(super) fn export_chart_image(&mut self) {
+        let Some(data) = self.analyses.get(self.selected_index) else { return };
+        let Some(image) = detail_chart_image(data, self.chart_window, None, self.show_gridlines, &self.dividends_of(data), CHART_IMAGE_SIZE.0, CHART_IMAGE_SIZE.1) else {
+            self.status_message = Some("Nothing to chart yet".into());
+            return;
+        };
+        let file_name = format!(
+            "{}-{}-{}.png",
+            data.analysis.symbol, data.time_range.as_str(), chrono::Local::now().format("%Y%m%d-%H%M%S"),
+        );
+        let dir = self.persistence_manager.export_dir();
+        let path = dir.join(file_name);
+        let written = std::fs::create_dir_all(&dir).and_then(|()| std::fs::write(&path, image.to_png()));
+        self.status_message = Some(match written {
+            Ok(()) => format!("Saved chart to {}", path.display()),
+            Err(e) => format!("Saving the chart failed: {}", e),
+        });
+    }
+
+    /// Writes the daily report of the loaded symbols under the report dir.
+    /// Symbols still loading are left out.
+    pub(super) fn write_report(&mut self, format: ReportFormat) {
+        let entries: Vec<ReportEntry> = self.analyses.iter()
+            .filter(|a| !a.stock_data.is_empty())
+            .map(|a| ReportEntry { stock_data: &a.stock_data, analysis: &a.analysis })
+            .collect();
+        if entries.is_empty() {
+            self.status_message = Some("Nothing to report yet".into());
+            return;
+        }
+        let skipped = self.analyses.len() - entries.len();
+        let report = Report {
+            title: self.active_watchlist.clone(),
+            range: self.analyses[self.selected_index.min(self.analyses.len() - 1)].time_range.as_str().to_string(),
+            generated: self.zone.now(),
+            entries,
+        };
+        let file_name = format!(
+            "{}-{}.{}",
+            self.active_watchlist, report.generated.format("%Y%m%d-%H%M%S"), format.extension(),
+        );
+        let path = self.persistence_manager.report_dir().join(file_name);
+        let message = match write_report(&path, format, &report) {
+            Ok(()) if skipped > 0 => format!("Report written to {} ({} symbols not loaded yet)", path.display(), skipped),
+            Ok(()) => format!("Report written to {}", path.display()),
+            Err(e) => format!("Report failed: {}", e),
+        };
+        self.status_message = Some(message);
     }
 
     /// Set flag to refresh analyses after saving config.
@@ -83,6 +801,10 @@ impl App {
             self.should_refresh_after_save = false;
             if let Ok(config) = self.persistence_manager.get_stock_config() {
                 self.initialize_placeholders(&config);
+                if self.main_sort != MainSort::Watchlist {
+                    self.sort_analyses();
+                }
+                self.restart_stream();
             }
         }
     }
@@ -90,39 +812,222 @@ impl App {
     /// Drain async events from the channel into analyses.
     pub(super) fn drain_events(&mut self) {
         // Drain all available events (not just one per frame)
-        loop {
-            let event = if let Some(ref rx) = self.channel_rx {
-                match rx.try_recv() {
-                    Ok(e) => e,
-                    Err(_) => break, // channel empty or disconnected
-                }
-            } else {
-                break;
-            };
-
-            self.loading_done += 1;
+        let mut updated = false;
+        while let Ok(event) = self.channel_rx.try_recv() {
+            if !matches!(
+                event,
+                AppEvent::Started(..) | AppEvent::Finished(..) | AppEvent::Profile(..) | AppEvent::Suggestions(..)
+                    | AppEvent::SymbolChecked(..) | AppEvent::Cached(..) | AppEvent::Tick(..) | AppEvent::Reanalyzed(..)
+                    | AppEvent::StreamState(..) | AppEvent::Dividends(..) | AppEvent::Fundamentals(..)
+                    | AppEvent::News(..) | AppEvent::Ratings(..) | AppEvent::Filings(..)
+                    | AppEvent::InsiderTrades(..) | AppEvent::Backtest(..) | AppEvent::PortfolioHistory(..)
+            ) {
+                self.loading_done += 1;
+            }
 
             match event {
-                AppEvent::Update(analysis, stock_data, time_range) => {
+                AppEvent::Update(analysis, stock_data, time_range, source) => {
+                    let symbol = analysis.symbol.clone();
                     // Replace existing entry for this symbol (re-fetch), or push new
                     if let Some(existing) = self.analyses.iter_mut()
                         .find(|a| a.analysis.symbol == analysis.symbol)
                     {
+                        // A newer request for another range superseded this one
+                        if existing.time_range != time_range {
+                            continue;
+                        }
                         existing.analysis = analysis;
                         existing.stock_data = stock_data;
                         existing.time_range = time_range;
+                        existing.fetched_at = Some(chrono::Local::now());
+                        existing.cached = false;
+                        existing.source = Some(source);
                     } else {
                         self.analyses.push(AnalysisWithChartData {
                             analysis,
                             stock_data,
                             time_range,
+                            fetched_at: Some(chrono::Local::now()),
+                            cached: false,
+                            source: Some(source),
                         });
                     }
+                    self.last_refresh = Some(chrono::Local::now());
+                    updated = true;
+                    self.track_predictions(&symbol);
+                    self.backtests.remove(&symbol);
+                    // For the target upside, short-interest flag and insider
+                    // activity on its tile
+                    if let Some(index) = self.index_of(&symbol) {
+                        self.fetch_ratings(index);
+                        self.fetch_fundamentals(index);
+                        self.fetch_insider_trades(index);
+                    }
+                }
+                AppEvent::Cached(analysis, stock_data, time_range, saved_at) => {
+                    let Some(existing) = self.analyses.iter_mut()
+                        .find(|a| a.analysis.symbol == analysis.symbol && a.time_range == time_range)
+                    else {
+                        continue;
+                    };
+                    // The fetch it was read for may already have finished
+                    if existing.stock_data.is_empty() {
+                        existing.analysis = analysis;
+                        existing.stock_data = stock_data;
+                        existing.fetched_at = chrono::DateTime::from_timestamp(saved_at, 0)
+                            .map(|t| t.with_timezone(&chrono::Local));
+                        existing.cached = true;
+                        existing.source = None;
+                        updated = true;
+                    }
+                }
+                AppEvent::History(symbol, time_range, history) => {
+                    // Only keep it if it still matches what the view shows
+                    let current = self.analyses.get(self.selected_index)
+                        .is_some_and(|a| a.analysis.symbol == symbol && a.time_range == time_range);
+                    if current {
+                        self.prior_history = Some((symbol, time_range, history));
+                    }
+                }
+                AppEvent::Error(symbol, time_range, message) => {
+                    self.log_error(symbol, time_range, message);
+                }
+                // Results for an older input are stale
+                AppEvent::Suggestions(query, matches) => {
+                    if query == self.new_symbol_input.trim() {
+                        self.suggestions = matches;
+                        self.suggestion_selected = None;
+                    }
+                }
+                AppEvent::SymbolChecked(symbol, result) => {
+                    // Only if it is still in the list being edited
+                    if let Some(check) = self.symbol_checks.get_mut(&symbol) {
+                        *check = match result {
+                            Ok(quote) => SymbolCheck::Quoted(quote),
+                            Err(message) => SymbolCheck::Unknown(message),
+                        };
+                    }
+                }
+                AppEvent::Profile(symbol, Ok(profile)) => {
+                    let _ = self.persistence_manager.save_profile(&symbol, &profile);
+                    self.profiles.insert(symbol, profile);
+                }
+                // Left unclassified; entering the sector view again retries
+                AppEvent::Profile(symbol, Err(_)) => {
+                    self.profiles_requested.remove(&symbol);
+                }
+                AppEvent::Fundamentals(symbol, Ok(figures)) => {
+                    self.fundamentals.insert(symbol, figures);
+                }
+                AppEvent::Fundamentals(symbol, Err(_)) => {
+                    self.fundamentals.remove(&symbol);
+                }
+                AppEvent::InsiderTrades(symbol, Ok(trades)) => {
+                    self.insider_trades.insert(symbol, trades);
+                }
+                AppEvent::InsiderTrades(symbol, Err(_)) => {
+                    self.insider_trades.remove(&symbol);
+                }
+                AppEvent::Filings(symbol, Ok(filings)) => {
+                    self.filings.insert(symbol, filings);
+                }
+                AppEvent::Filings(symbol, Err(_)) => {
+                    self.filings.remove(&symbol);
+                }
+                // Unless the bars have changed since
+                AppEvent::Backtest(symbol, last_bar, results) => {
+                    let current = self.analyses.iter()
+                        .any(|a| a.analysis.symbol == symbol && a.stock_data.timestamps.last() == Some(&last_bar));
+                    if current && self.backtests.contains_key(&symbol) {
+                        self.backtests.insert(symbol, Some(results));
+                    }
+                }
+                AppEvent::Ratings(symbol, Ok(ratings)) => {
+                    self.ratings.insert(symbol, ratings);
+                }
+                AppEvent::Ratings(symbol, Err(_)) => {
+                    self.ratings.remove(&symbol);
                 }
-                AppEvent::Error(err) => {
-                    self.loading_errors.push(err);
+                AppEvent::News(symbol, Ok(headlines)) => {
+                    self.news.insert(symbol, headlines);
+                    // Its sentiment moves the forecast
+                    updated |= self.sentiment_forecast;
                 }
+                AppEvent::News(symbol, Err(_)) => {
+                    self.news.remove(&symbol);
+                }
+                AppEvent::Dividends(symbol, Ok(history)) => {
+                    self.dividends.insert(symbol, history);
+                }
+                // Opening the symbol again retries
+                AppEvent::Dividends(symbol, Err(_)) => {
+                    self.dividends.remove(&symbol);
+                }
+                AppEvent::PortfolioHistory(symbol, time_range, Ok(history)) => {
+                    self.portfolio_history.insert(symbol, (time_range, history));
+                }
+                // Opening the portfolio again retries
+                AppEvent::PortfolioHistory(symbol, _, Err(_)) => {
+                    self.portfolio_history.remove(&symbol);
+                }
+                AppEvent::Tick(tick) => updated |= self.apply_tick(&tick),
+                AppEvent::Reanalyzed(analysis, time_range) => {
+                    let symbol = analysis.symbol.clone();
+                    let stale = self.reanalyzing.remove(&symbol).unwrap_or(false);
+                    let Some(index) = self.index_of(&symbol) else { continue };
+                    // A newer request for another range superseded this one
+                    if self.analyses[index].time_range != time_range {
+                        continue;
+                    }
+                    let price = self.analyses[index].analysis.current_price;
+                    self.analyses[index].analysis = StockAnalysis { current_price: price, ..analysis };
+                    updated = true;
+                    if stale {
+                        self.reanalyze(index);
+                    }
+                }
+                AppEvent::StreamState(live) => self.stream_live = live,
+                AppEvent::Started(symbol, time_range) => self.in_flight.push((symbol, time_range)),
+                AppEvent::Finished(symbol, time_range) => {
+                    if let Some(i) = self.in_flight.iter().position(|(s, tr)| *s == symbol && *tr == time_range) {
+                        self.in_flight.remove(i);
+                    }
+                }
+            }
+        }
+        if updated && self.sentiment_forecast {
+            self.tilt_forecasts();
+        }
+        if updated {
+            self.evaluate_signals();
+        }
+        if matches!(self.current_view, View::Detail) && self.detail_tab == DetailTab::Backtest {
+            self.run_backtest(self.selected_index);
+        }
+        // New values can move a symbol in a metric order
+        if updated && self.main_sort != MainSort::Watchlist {
+            self.sort_analyses();
+        }
+    }
+
+    /// Record a failed fetch for this batch and the error panel.
+    fn log_error(&mut self, symbol: String, time_range: TimeRange, message: String) {
+        self.loading_errors.push(format!("{symbol}: {message}"));
+        self.error_log.push(FetchError { symbol, time_range, message, at: chrono::Local::now() });
+    }
+
+    /// Fetch the symbol of error-log entry `i` again, dropping every entry
+    /// for that symbol.
+    pub(super) fn retry_error(&mut self, i: usize) {
+        let Some(entry) = self.error_log.get(i) else { return };
+        let (symbol, time_range) = (entry.symbol.clone(), entry.time_range);
+        self.error_log.retain(|e| e.symbol != symbol);
+        match self.index_of(&symbol) {
+            Some(idx) => {
+                self.analyses[idx].time_range = time_range;
+                self.fetch_single_stock(idx, time_range);
             }
+            None => self.status_message = Some(format!("{} is no longer in the watchlist", symbol)),
         }
     }
 
@@ -134,25 +1039,202 @@ impl App {
         self.loading_done = 0;
         self.loading_errors.clear();
 
-        let default_time_range = TimeRange::ThreeMonths;
         for symbol in &config.symbols {
-            self.analyses.push(AnalysisWithChartData {
-                analysis: StockAnalysis {
-                    symbol: symbol.clone(),
-                    current_price: 0.0,
-                    sma_10: None,
-                    sma_50: None,
-                    ema_20: None,
-                    sma10_values: vec![],
-                    sma50_values: vec![],
-                    ema20_values: vec![],
-                    predictions: vec![],
-                    recent_change: None,
-                },
-                stock_data: StockData::new(),
-                time_range: default_time_range,
-            });
+            self.analyses.push(placeholder(symbol));
+        }
+    }
+
+    /// Put back the selection, time ranges, layout and sort saved on the
+    /// last exit. Entries for symbols no longer in the watchlist are ignored.
+    pub(super) fn restore_session(&mut self) {
+        let session = self.persistence_manager.get_session();
+        for a in &mut self.analyses {
+            if let Some(tr) = session.time_ranges.get(&a.analysis.symbol).and_then(|l| TimeRange::from_label(l)) {
+                a.time_range = tr;
+            }
+        }
+        if let Some(sort) = session.sort.as_deref().and_then(MainSort::from_name) {
+            self.set_main_sort(sort);
+        }
+        if let Some(mode) = session.main_mode.as_deref().and_then(MainMode::from_name) {
+            self.set_main_mode(mode);
+        }
+        if let Some(i) = session.selected.as_deref().and_then(|s| self.index_of(s)) {
+            self.selected_index = i;
+            if session.detail {
+                self.open_detail();
+            }
+        }
+    }
+
+    /// Save where the TUI is for [`App::restore_session`]. Time ranges of
+    /// symbols in other watchlists are kept.
+    pub(super) fn save_session(&self) {
+        let mut session = self.persistence_manager.get_session();
+        for a in &self.analyses {
+            if a.time_range == TimeRange::ThreeMonths {
+                session.time_ranges.remove(&a.analysis.symbol);
+            } else {
+                session.time_ranges.insert(a.analysis.symbol.clone(), a.time_range.as_str().to_string());
+            }
+        }
+        session.selected = self.analyses.get(self.selected_index).map(|a| a.analysis.symbol.clone());
+        session.main_mode = Some(self.main_mode.name().to_string());
+        session.sort = Some(self.main_sort.name().to_string());
+        session.detail = matches!(self.current_view, View::Detail);
+        let _ = self.persistence_manager.save_session(&session);
+    }
+
+    /// Fetch through the provider the config names for the active
+    /// watchlist, or Yahoo when that one cannot be set up.
+    fn select_provider(&mut self) {
+        let settings = self.persistence_manager.get_provider_settings();
+        match provider_for_watchlist(&settings, &self.active_watchlist) {
+            Ok(provider) => self.provider = provider,
+            Err(e) => {
+                self.provider = provider_named("yahoo", &settings).unwrap_or_else(|_| Arc::new(YahooProvider));
+                self.status_message = Some(format!("{}; using Yahoo Finance", e));
+            }
+        }
+    }
+
+    /// Store `provider`'s API key and start using it if the watchlist
+    /// fetches from that provider.
+    pub(super) fn set_api_key(&mut self, provider: &str, key: &str) {
+        let saved = match self.persistence_manager.save_api_key(provider, key) {
+            Ok(None) => format!("Saved the {} key in the OS credential store", provider),
+            Ok(Some(e)) => format!("{}; saved the {} key in the config file instead", e, provider),
+            Err(e) => e.to_string(),
+        };
+        self.status_message = None;
+        self.select_provider();
+        // A provider that still fails to set up says why instead
+        self.status_message.get_or_insert(saved);
+    }
+
+    /// Show watchlist `name`, creating it empty if it does not exist.
+    pub(super) fn switch_watchlist(&mut self, name: &str) {
+        match self.persistence_manager.switch_watchlist(name) {
+            Ok(config) => {
+                (self.active_watchlist, self.watchlists) = self.persistence_manager.watchlist_names();
+                self.select_provider();
+                self.show_watchlist(&config);
+            }
+            Err(e) => self.status_message = Some(e.to_string()),
+        }
+    }
+
+    /// Replace the shown symbols with `config`'s and fetch what the
+    /// current main mode needs.
+    fn show_watchlist(&mut self, config: &StockConfig) {
+        self.initialize_placeholders(config);
+        self.selected_index = 0;
+        self.compare_mark = None;
+        if self.main_sort != MainSort::Watchlist {
+            self.sort_analyses();
+        }
+        if self.main_mode != MainMode::Tiles {
+            self.fetch_missing();
+        }
+        if self.main_mode == MainMode::Sectors {
+            self.fetch_profiles();
         }
+        self.restart_stream();
+    }
+
+    /// Name of the profile in use.
+    pub fn profile(&self) -> &str {
+        self.persistence_manager.profile()
+    }
+
+    /// Load profile `name`, creating it if new. Its settings, watchlists
+    /// and notes replace the current ones; the graphics protocol is kept,
+    /// as it depends on the terminal rather than the profile.
+    pub(super) fn switch_profile(&mut self, name: &str) {
+        let loaded = self.persistence_manager.open_profile(name)
+            .and_then(|pm| pm.get_stock_config().map(|config| (pm, config)));
+        let (pm, config) = match loaded {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                self.status_message = Some(e.to_string());
+                return;
+            }
+        };
+        self.save_session();
+        let ui = pm.get_ui_settings();
+        self.plain = ui.plain;
+        self.grid = ui.grid;
+        self.theme = Theme::from_settings(&ui.theme).unwrap_or_default();
+        self.vim = ui.vim;
+        self.zone = ui.display_zone();
+        self.adjusted = ui.adjusted;
+        self.sentiment_forecast = ui.sentiment_forecast;
+        self.forecast = pm.get_forecast_settings();
+        self.strategy = pm.get_strategy_settings();
+        self.portfolio_settings = pm.get_portfolio_settings();
+        self.key_remap = parse_remaps(&ui.keys).unwrap_or_default();
+        self.refresh = pm.get_refresh_settings();
+        (self.active_watchlist, self.watchlists) = pm.watchlist_names();
+        self.notes = pm.get_notes();
+        self.profiles = pm.get_profiles();
+        self.profiles_requested.clear();
+        self.dividends.clear();
+        self.fundamentals.clear();
+        self.ratings.clear();
+        self.news.clear();
+        self.filings.clear();
+        self.insider_trades.clear();
+        self.backtests.clear();
+        self.profile_names = pm.profile_names();
+        self.persistence_manager = pm;
+        self.select_provider();
+        self.current_view = View::Main;
+        self.show_watchlist(&config);
+        self.restore_session();
+        self.status_message = Some(format!("Profile {}", name));
+    }
+
+    /// Switch to the next (`direction > 0`) or previous watchlist.
+    pub(super) fn cycle_watchlist(&mut self, direction: i8) {
+        let n = self.watchlists.len();
+        let Some(i) = self.watchlists.iter().position(|w| *w == self.active_watchlist) else { return };
+        if n > 1 {
+            let next = if direction > 0 { (i + 1) % n } else { (i + n - 1) % n };
+            self.switch_watchlist(&self.watchlists[next].clone());
+        }
+    }
+
+    /// Delete a watchlist other than the shown one.
+    pub(super) fn delete_watchlist(&mut self, name: &str) {
+        match self.persistence_manager.delete_watchlist(name) {
+            Ok(()) => self.watchlists.retain(|w| w != name),
+            Err(e) => self.status_message = Some(e.to_string()),
+        }
+    }
+
+    /// Add `symbol` to the saved watchlist, select it and start fetching it.
+    pub(super) fn add_symbol(&mut self, symbol: &str) {
+        let symbol = symbol.to_uppercase();
+        if let Some(i) = self.index_of(&symbol) {
+            self.selected_index = i;
+            return;
+        }
+        let mut config = match self.persistence_manager.get_stock_config() {
+            Ok(config) => config,
+            Err(e) => {
+                self.status_message = Some(format!("Could not add {}: {}", symbol, e));
+                return;
+            }
+        };
+        config.symbols.push(symbol.clone());
+        if let Err(e) = self.persistence_manager.save_stock_config(&config) {
+            self.status_message = Some(format!("Could not add {}: {}", symbol, e));
+            return;
+        }
+        self.analyses.push(placeholder(&symbol));
+        self.selected_index = self.analyses.len() - 1;
+        self.fetch_single_stock(self.selected_index, TimeRange::ThreeMonths);
+        self.restart_stream();
     }
 
     /// Fetch data for a single stock (called on Enter or time-range change).
@@ -163,37 +1245,368 @@ impl App {
         }
         // Clear old data immediately — chart shows empty until new data arrives
         self.analyses[index].stock_data = StockData::new();
+        self.analyses[index].fetched_at = None;
         self.analyses[index].analysis = StockAnalysis {
             symbol: self.analyses[index].analysis.symbol.clone(),
             current_price: 0.0,
             sma_10: None, sma_50: None, ema_20: None,
-            sma10_values: vec![], sma50_values: vec![], ema20_values: vec![],
-            predictions: vec![], recent_change: None,
+            sma10_values: vec![], sma50_values: vec![], ema20_values: vec![], kalman_values: vec![], kalman_slope: None,
+            predictions: vec![], forecaster: Forecaster::default(), garch_volatility: None, prediction_bands: vec![],
+            recent_change: None,
         };
+        if self.load_imported(index, time_range) {
+            return;
+        }
+        if self.offline {
+            self.load_cached(index, time_range);
+        } else {
+            self.spawn_fetch(index, time_range, None);
+        }
+    }
 
+    /// Show entry `index` from prices imported with `import-prices`, if it
+    /// has any; such symbols are never fetched.
+    fn load_imported(&mut self, index: usize, time_range: TimeRange) -> bool {
         let symbol = self.analyses[index].analysis.symbol.clone();
-        let (tx, rx) = std::sync::mpsc::channel();
-        self.channel_rx = Some(rx);
-        self.loading_total = 1;
-        self.loading_done = 0;
-        self.loading_errors.clear();
+        let Some((history, imported_at)) = self.persistence_manager.imported_prices(&symbol) else { return false };
+        let stock_data = trailing_range(&history, time_range);
+        let entry = &mut self.analyses[index];
+        entry.analysis = analyze_stock(&stock_data, &symbol, self.forecast.for_symbol(&symbol));
+        entry.stock_data = stock_data;
+        entry.time_range = time_range;
+        entry.fetched_at = chrono::DateTime::from_timestamp(imported_at, 0).map(|t| t.with_timezone(&chrono::Local));
+        entry.cached = false;
+        entry.source = None;
+        true
+    }
+
+    /// Show entry `index`'s cached bars for `time_range`, for offline mode.
+    fn load_cached(&mut self, index: usize, time_range: TimeRange) {
+        let symbol = self.analyses[index].analysis.symbol.clone();
+        match self.persistence_manager.history_store().load(&symbol, time_range.as_str()) {
+            Some((stock_data, saved_at)) => {
+                let stock_data = prices(stock_data, self.adjusted);
+                let entry = &mut self.analyses[index];
+                entry.analysis = analyze_stock(&stock_data, &symbol, self.forecast.for_symbol(&symbol));
+                entry.stock_data = stock_data;
+                entry.time_range = time_range;
+                entry.fetched_at = chrono::DateTime::from_timestamp(saved_at, 0).map(|t| t.with_timezone(&chrono::Local));
+                entry.cached = true;
+                entry.source = None;
+            }
+            None => self.log_error(symbol, time_range, "not in the cache (offline)".into()),
+        }
+    }
+
+    /// Re-fetch entry `index` for its current range, keeping what it shows
+    /// until the new data replaces it.
+    pub(super) fn refetch(&mut self, index: usize) {
+        let Some(entry) = self.analyses.get(index) else { return };
+        if self.in_flight.iter().any(|(s, _)| *s == entry.analysis.symbol) {
+            return;
+        }
+        let time_range = entry.time_range;
+        if self.load_imported(index, time_range) {
+            return;
+        }
+        let entry = &self.analyses[index];
+        if self.offline {
+            if entry.stock_data.is_empty() {
+                self.load_cached(index, time_range);
+            }
+            return;
+        }
+        // Adjusted bars cannot be topped up; the cache has them as traded
+        let as_traded = !self.adjusted || entry.stock_data.actions.is_empty();
+        let loaded = (as_traded && !entry.stock_data.is_empty()).then(|| entry.stock_data.clone());
+        self.spawn_fetch(index, time_range, loaded);
+    }
+
+    /// Fetch entry `index` for `time_range` on the runtime. Bars already
+    /// `loaded`, or else cached on disk, are only topped up with the bars
+    /// since their last one; cached bars are shown while that runs.
+    fn spawn_fetch(&mut self, index: usize, time_range: TimeRange, loaded: Option<StockData>) {
+        let symbol = self.analyses[index].analysis.symbol.clone();
+        let store = self.persistence_manager.history_store();
+        let provider = self.provider.clone();
+        let tx = self.channel_tx.clone();
+        let adjusted = self.adjusted;
+        let forecaster = self.forecast.for_symbol(&symbol);
+        self.join_batch();
         self.rt.spawn(async move {
-            match fetch_stock_data(&symbol, time_range).await {
-                Ok(stock_data) => {
-                    if !stock_data.is_empty() {
-                        let analysis = analyze_stock(&stock_data, &symbol);
-                        let _ = tx.send(AppEvent::Update(analysis, stock_data, time_range));
-                    } else {
-                        let _ = tx.send(AppEvent::Error(format!("No data for {symbol}")));
+            let _ = tx.send(AppEvent::Started(symbol.clone(), time_range));
+            let range = time_range.as_str();
+            let loaded = loaded.or_else(|| {
+                let (cached, saved_at) = store.load(&symbol, range)?;
+                let shown = prices(cached.clone(), adjusted);
+                let analysis = analyze_stock(&shown, &symbol, forecaster);
+                let _ = tx.send(AppEvent::Cached(analysis, shown, time_range, saved_at));
+                Some(cached)
+            });
+            let result = match loaded {
+                Some(loaded) => top_up(provider.as_ref(), &symbol, time_range, loaded).await,
+                None => provider.fetch_history(&symbol, time_range).await,
+            };
+            match result {
+                Ok(stock_data) if !stock_data.is_empty() => {
+                    let _ = store.save(&symbol, range, &stock_data);
+                    let stock_data = prices(stock_data, adjusted);
+                    let analysis = analyze_stock(&stock_data, &symbol, forecaster);
+                    let source = provider.served_by(&symbol);
+                    let _ = tx.send(AppEvent::Update(analysis, stock_data, time_range, source));
+                }
+                Ok(_) => {
+                    let _ = tx.send(AppEvent::Error(symbol.clone(), time_range, "no data returned".into()));
+                }
+                Err(e) => {
+                    let _ = tx.send(AppEvent::Error(symbol.clone(), time_range, e.to_string()));
+                }
+            }
+            let _ = tx.send(AppEvent::Finished(symbol, time_range));
+        });
+    }
+
+    /// Re-fetch the whole watchlist in place, loading entries that never were.
+    pub(super) fn refresh_all(&mut self) {
+        self.last_auto_refresh = std::time::Instant::now();
+        for i in 0..self.analyses.len() {
+            self.refetch(i);
+        }
+    }
+
+    /// Whether `symbol` may be refreshed in the background now: always,
+    /// unless refresh is limited to market hours and its market is closed.
+    /// Crypto markets never close.
+    fn refreshable_now(&self, symbol: &str) -> bool {
+        !self.refresh.market_hours_only || calendar::session(symbol, chrono::Utc::now()) == Session::Regular
+    }
+
+    /// Whether background refresh is on and may run now for some entry.
+    pub fn auto_refresh_active(&self) -> bool {
+        self.auto_refresh_enabled() && self.analyses.iter().any(|a| self.refreshable_now(&a.analysis.symbol))
+    }
+
+    /// Whether background refresh is on at all, whether or not a market is
+    /// open for it.
+    pub fn auto_refresh_enabled(&self) -> bool {
+        self.refresh.interval_minutes > 0 && !self.read_only && !self.offline
+    }
+
+    /// Re-fetch every loaded entry whose market is open in place once the
+    /// refresh interval has passed. Entries never loaded stay lazy.
+    pub(super) fn check_auto_refresh(&mut self) {
+        let interval = std::time::Duration::from_secs(self.refresh.interval_minutes * 60);
+        if self.last_auto_refresh.elapsed() < interval || !self.auto_refresh_active() {
+            return;
+        }
+        self.last_auto_refresh = std::time::Instant::now();
+        for i in 0..self.analyses.len() {
+            if !self.analyses[i].stock_data.is_empty() && self.refreshable_now(&self.analyses[i].analysis.symbol) {
+                self.refetch(i);
+            }
+        }
+    }
+
+    /// (Re)subscribe the quote stream to the watchlist, or stop it when
+    /// streaming is off, nothing is fetched or the watchlist is empty. The
+    /// task reconnects after a drop, waiting longer each time it fails.
+    pub(super) fn restart_stream(&mut self) {
+        if let Some(task) = self.stream_task.take() {
+            task.abort();
+        }
+        self.stream_live = false;
+        let symbols: Vec<String> = self.analyses.iter().map(|a| a.analysis.symbol.clone()).collect();
+        if !self.refresh.stream || self.read_only || self.offline || symbols.is_empty() {
+            return;
+        }
+        let tx = self.channel_tx.clone();
+        self.stream_task = Some(self.rt.spawn(async move {
+            let mut backoff = std::time::Duration::from_secs(5);
+            loop {
+                let started = std::time::Instant::now();
+                let mut live = false;
+                // Every way out is a dropped connection, retried below
+                let _ = stream_quotes(&symbols, |tick| {
+                    if !live {
+                        live = true;
+                        let _ = tx.send(AppEvent::StreamState(true));
                     }
+                    let _ = tx.send(AppEvent::Tick(tick));
+                }).await;
+                let _ = tx.send(AppEvent::StreamState(false));
+                // A connection that lasted is worth retrying promptly
+                if started.elapsed() > std::time::Duration::from_secs(60) {
+                    backoff = std::time::Duration::from_secs(5);
+                }
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(std::time::Duration::from_secs(300));
+            }
+        }));
+    }
+
+    /// Turn live quotes on or off and remember the choice.
+    pub(super) fn toggle_stream(&mut self) {
+        self.refresh.stream = !self.refresh.stream;
+        if let Err(e) = self.persistence_manager.save_refresh_settings(self.refresh) {
+            self.status_message = Some(e.to_string());
+        }
+        self.restart_stream();
+    }
+
+    /// Switch between adjusted and as-traded prices and remember the
+    /// choice. Fetched entries are re-read from the history cache, which
+    /// keeps bars as traded.
+    pub(super) fn toggle_adjusted(&mut self) {
+        self.adjusted = !self.adjusted;
+        let mut ui = self.persistence_manager.get_ui_settings();
+        ui.adjusted = self.adjusted;
+        let _ = self.persistence_manager.save_ui_settings(&ui);
+        let store = self.persistence_manager.history_store();
+        for entry in self.analyses.iter_mut().filter(|a| a.source.is_some() || a.cached) {
+            let symbol = &entry.analysis.symbol;
+            if let Some((stock_data, _)) = store.load(symbol, entry.time_range.as_str()) {
+                entry.stock_data = prices(stock_data, self.adjusted);
+                entry.analysis = analyze_stock(&entry.stock_data, symbol, self.forecast.for_symbol(symbol));
+            }
+        }
+        if self.sentiment_forecast {
+            self.tilt_forecasts();
+        }
+        self.status_message = Some(if self.adjusted {
+            "Prices adjusted for splits and dividends".into()
+        } else {
+            "Prices as traded".into()
+        });
+    }
+
+    /// Fold a streamed price into its symbol's loaded bars and price, and
+    /// have them re-analysed in the background. Entries not loaded, or
+    /// showing imported or snapshot bars, are left alone. Returns whether
+    /// anything changed.
+    fn apply_tick(&mut self, tick: &Tick) -> bool {
+        let Some(index) = self.index_of(&tick.symbol) else { return false };
+        let entry = &mut self.analyses[index];
+        if entry.stock_data.is_empty() || (entry.source.is_none() && !entry.cached) {
+            return false;
+        }
+        apply_tick(&mut entry.stock_data, tick, entry.time_range.interval().seconds());
+        entry.analysis.current_price = tick.price;
+        entry.fetched_at = Some(chrono::Local::now());
+        self.last_refresh = entry.fetched_at;
+        self.reanalyze(index);
+        true
+    }
+
+    /// Analyse entry `index`'s bars again off the UI thread, since a
+    /// forecast may train a model. One run per symbol at a time: prices
+    /// streamed in meanwhile only mark it for another once it is done.
+    fn reanalyze(&mut self, index: usize) {
+        let entry = &self.analyses[index];
+        let symbol = entry.analysis.symbol.clone();
+        if let Some(stale) = self.reanalyzing.get_mut(&symbol) {
+            *stale = true;
+            return;
+        }
+        self.reanalyzing.insert(symbol.clone(), false);
+        let stock_data = entry.stock_data.clone();
+        let time_range = entry.time_range;
+        let forecaster = self.forecast.for_symbol(&symbol);
+        let tx = self.channel_tx.clone();
+        self.rt.spawn_blocking(move || {
+            let analysis = analyze_stock(&stock_data, &symbol, forecaster);
+            let _ = tx.send(AppEvent::Reanalyzed(analysis, time_range));
+        });
+    }
+
+    /// Count one more pending fetch. Start a new batch once the previous one
+    /// has finished, otherwise join the in-flight batch so progress counts
+    /// every pending fetch.
+    fn join_batch(&mut self) {
+        if self.loading_done >= self.loading_total {
+            self.loading_total = 0;
+            self.loading_done = 0;
+            self.loading_errors.clear();
+        }
+        self.loading_total += 1;
+    }
+
+    /// Fetch the selected symbol's range together with the period before it,
+    /// for the prior-period overlay.
+    pub(super) fn fetch_prior_history(&mut self) {
+        let Some(entry) = self.analyses.get(self.selected_index) else { return };
+        let symbol = entry.analysis.symbol.clone();
+        let time_range = entry.time_range;
+        self.prior_history = None;
+        if let Some((history, _)) = self.persistence_manager.imported_prices(&symbol) {
+            self.prior_history = Some((symbol, time_range, history));
+            return;
+        }
+        if self.offline {
+            self.log_error(symbol, time_range, "prior period not available offline".into());
+            return;
+        }
+        let provider = self.provider.clone();
+        let tx = self.channel_tx.clone();
+        self.join_batch();
+        self.rt.spawn(async move {
+            let _ = tx.send(AppEvent::Started(symbol.clone(), time_range));
+            match provider.fetch_doubled_history(&symbol, time_range).await {
+                Ok(history) => {
+                    let _ = tx.send(AppEvent::History(symbol.clone(), time_range, history));
                 }
                 Err(e) => {
-                    let _ = tx.send(AppEvent::Error(format!("{symbol}: {e}")));
+                    let _ = tx.send(AppEvent::Error(symbol.clone(), time_range, e.to_string()));
                 }
             }
+            let _ = tx.send(AppEvent::Finished(symbol, time_range));
         });
     }
 
+    /// Shortest range reaching back to the first purchase.
+    fn portfolio_range(&self) -> TimeRange {
+        let days = self.portfolio.since()
+            .map_or(0, |since| (chrono::Local::now().date_naive() - since).num_days());
+        match days {
+            ..=365 => TimeRange::OneYear,
+            366..=730 => TimeRange::TwoYears,
+            731..=1826 => TimeRange::FiveYears,
+            1827..=3652 => TimeRange::TenYears,
+            _ => TimeRange::All,
+        }
+    }
+
+    /// Fetch the history of every symbol held, and of the benchmark, that
+    /// has none for the range back to the first purchase; imported prices
+    /// are used as they are. Sectors are looked up too, for the allocation.
+    pub(super) fn fetch_portfolio_history(&mut self) {
+        let time_range = self.portfolio_range();
+        let held = self.portfolio.symbols();
+        for symbol in &held {
+            self.fetch_symbol_profile(symbol.clone());
+        }
+        let benchmark = self.portfolio_settings.benchmark.trim().to_uppercase();
+        let benchmark = (!held.is_empty() && !benchmark.is_empty()).then_some(benchmark);
+        for symbol in held.into_iter().chain(benchmark) {
+            if self.portfolio_history.get(&symbol).is_some_and(|(tr, _)| *tr == time_range) {
+                continue;
+            }
+            if let Some((history, _)) = self.persistence_manager.imported_prices(&symbol) {
+                self.portfolio_history.insert(symbol, (time_range, trailing_range(&history, time_range)));
+                continue;
+            }
+            if self.offline {
+                continue;
+            }
+            self.portfolio_history.insert(symbol.clone(), (time_range, StockData::new()));
+            let provider = self.provider.clone();
+            let tx = self.channel_tx.clone();
+            self.rt.spawn(async move {
+                let history = provider.fetch_history(&symbol, time_range).await.map_err(|e| e.to_string());
+                let _ = tx.send(AppEvent::PortfolioHistory(symbol, time_range, history));
+            });
+        }
+    }
+
     // ── shared helpers ─────────────────────────────────────────
 
     /// Cycle the time range and re-fetch with the new range/interval.
@@ -201,32 +1614,553 @@ impl App {
         if self.analyses.is_empty() || self.selected_index >= self.analyses.len() {
             return;
         }
-        let ranges = TimeRange::all();
-        let len = ranges.len();
-        if direction > 0 {
-            if self.selected_time_range_index < len - 1 {
-                self.selected_time_range_index += 1;
-            } else {
-                self.selected_time_range_index = 0;
+        let new_range = self.analyses[self.selected_index].time_range.cycled(direction);
+        self.set_time_range(new_range);
+    }
+
+    /// Switch the selected symbol to `range` and fetch it.
+    pub(super) fn set_time_range(&mut self, range: TimeRange) {
+        if self.selected_index >= self.analyses.len() {
+            return;
+        }
+        self.chart_window = None;
+        self.analyses[self.selected_index].time_range = range;
+        self.fetch_single_stock(self.selected_index, range);
+    }
+
+    /// Fetch every entry that has no data yet, each with its own range.
+    pub(super) fn fetch_missing(&mut self) {
+        for i in 0..self.analyses.len() {
+            if self.analyses[i].stock_data.is_empty() {
+                let tr = self.analyses[i].time_range;
+                self.fetch_single_stock(i, tr);
             }
+        }
+    }
+
+    /// `symbol` with its alias, e.g. "Nvidia (NVDA)", or just the symbol.
+    pub fn symbol_label(&self, symbol: &str) -> String {
+        match self.notes.get(symbol).and_then(|n| n.alias.as_deref()) {
+            Some(alias) => format!("{} ({})", alias, symbol),
+            None => symbol.to_string(),
+        }
+    }
+
+    /// `symbol`'s free-text note, if it has one.
+    pub fn note_of(&self, symbol: &str) -> Option<&str> {
+        self.notes.get(symbol).and_then(|n| n.note.as_deref())
+    }
+
+    /// Check in the background that a newly added `symbol` resolves, by
+    /// fetching its latest quote.
+    pub(super) fn check_symbol(&mut self, symbol: String) {
+        if self.persistence_manager.imported_prices(&symbol).is_some() {
+            self.symbol_checks.insert(symbol, SymbolCheck::Valid);
+            return;
+        }
+        if self.offline {
+            self.symbol_checks.insert(symbol, SymbolCheck::Unknown("not checked while offline".into()));
+            return;
+        }
+        self.symbol_checks.insert(symbol.clone(), SymbolCheck::Checking);
+        let provider = self.provider.clone();
+        let tx = self.channel_tx.clone();
+        self.rt.spawn(async move {
+            let result = provider.fetch_quote(&symbol).await.map_err(|e| e.to_string());
+            let _ = tx.send(AppEvent::SymbolChecked(symbol, result));
+        });
+    }
+
+    /// Search for the edit view's input once it has been still for a
+    /// moment, so each keystroke does not send a request.
+    pub(super) fn poll_symbol_search(&mut self) {
+        const SETTLE: std::time::Duration = std::time::Duration::from_millis(300);
+        let query = self.new_symbol_input.trim().to_string();
+        if query == self.suggest_query || self.input_changed_at.elapsed() < SETTLE {
+            return;
+        }
+        self.suggest_query = query.clone();
+        if query.is_empty() || self.offline {
+            self.suggestions.clear();
+            self.suggestion_selected = None;
+            return;
+        }
+        let provider = self.provider.clone();
+        let tx = self.channel_tx.clone();
+        self.rt.spawn(async move {
+            // A failed search just leaves the dropdown empty
+            let matches = provider.search_symbols(&query).await.unwrap_or_default();
+            let _ = tx.send(AppEvent::Suggestions(query, matches));
+        });
+    }
+
+    /// Look up the sector of every symbol without a saved one.
+    pub(super) fn fetch_profiles(&mut self) {
+        for i in 0..self.analyses.len() {
+            self.fetch_profile(i);
+        }
+    }
+
+    /// Look up entry `index`'s profile unless it is saved or on its way.
+    pub(super) fn fetch_profile(&mut self, index: usize) {
+        let Some(a) = self.analyses.get(index) else { return };
+        self.fetch_symbol_profile(a.analysis.symbol.clone());
+    }
+
+    /// Look up `symbol`'s profile unless it is saved or on its way.
+    pub(super) fn fetch_symbol_profile(&mut self, symbol: String) {
+        if self.offline || self.profiles.contains_key(&symbol) || !self.profiles_requested.insert(symbol.clone()) {
+            return;
+        }
+        let provider = self.provider.clone();
+        let tx = self.channel_tx.clone();
+        self.rt.spawn(async move {
+            let profile = provider.fetch_profile(&symbol).await.map_err(|e| e.to_string());
+            let _ = tx.send(AppEvent::Profile(symbol, profile));
+        });
+    }
+
+    /// Fetch entry `index`'s dividend history unless it has been this
+    /// session. Only stocks and funds pay dividends. An empty history
+    /// stands in while the request runs.
+    pub(super) fn fetch_dividends(&mut self, index: usize) {
+        let Some(a) = self.analyses.get(index) else { return };
+        let symbol = a.analysis.symbol.clone();
+        if self.offline || AssetClass::of(&symbol) != AssetClass::Equity || self.dividends.contains_key(&symbol) {
+            return;
+        }
+        self.dividends.insert(symbol.clone(), Vec::new());
+        let provider = self.provider.clone();
+        let tx = self.channel_tx.clone();
+        self.rt.spawn(async move {
+            let history = provider.fetch_dividends(&symbol).await.map_err(|e| e.to_string());
+            let _ = tx.send(AppEvent::Dividends(symbol, history));
+        });
+    }
+
+    /// Fetch entry `index`'s valuation figures unless they have been this
+    /// session; only stocks and funds have them.
+    pub(super) fn fetch_fundamentals(&mut self, index: usize) {
+        let Some(a) = self.analyses.get(index) else { return };
+        let symbol = a.analysis.symbol.clone();
+        if self.offline || AssetClass::of(&symbol) != AssetClass::Equity || self.fundamentals.contains_key(&symbol) {
+            return;
+        }
+        self.fundamentals.insert(symbol.clone(), Fundamentals::default());
+        let provider = self.provider.clone();
+        let tx = self.channel_tx.clone();
+        self.rt.spawn(async move {
+            let figures = provider.fetch_fundamentals(&symbol).await.map_err(|e| e.to_string());
+            let _ = tx.send(AppEvent::Fundamentals(symbol, figures));
+        });
+    }
+
+    /// Fetch entry `index`'s analyst ratings unless they have been this
+    /// session; only stocks have them.
+    pub(super) fn fetch_ratings(&mut self, index: usize) {
+        let Some(a) = self.analyses.get(index) else { return };
+        let symbol = a.analysis.symbol.clone();
+        if self.offline || AssetClass::of(&symbol) != AssetClass::Equity || self.ratings.contains_key(&symbol) {
+            return;
+        }
+        self.ratings.insert(symbol.clone(), AnalystRatings::default());
+        let provider = self.provider.clone();
+        let tx = self.channel_tx.clone();
+        self.rt.spawn(async move {
+            let ratings = provider.fetch_ratings(&symbol).await.map_err(|e| e.to_string());
+            let _ = tx.send(AppEvent::Ratings(symbol, ratings));
+        });
+    }
+
+    /// Fetch entry `index`'s insider trades unless they have been this
+    /// session; only stocks have them.
+    pub(super) fn fetch_insider_trades(&mut self, index: usize) {
+        let Some(a) = self.analyses.get(index) else { return };
+        let symbol = a.analysis.symbol.clone();
+        if self.offline || AssetClass::of(&symbol) != AssetClass::Equity || self.insider_trades.contains_key(&symbol) {
+            return;
+        }
+        self.insider_trades.insert(symbol.clone(), Vec::new());
+        let provider = self.provider.clone();
+        let tx = self.channel_tx.clone();
+        self.rt.spawn(async move {
+            let trades = provider.fetch_insider_trades(&symbol).await.map_err(|e| e.to_string());
+            let _ = tx.send(AppEvent::InsiderTrades(symbol, trades));
+        });
+    }
+
+    /// Log the forecast from `symbol`'s freshly fetched daily bars and
+    /// score earlier ones against those bars.
+    fn track_predictions(&mut self, symbol: &str) {
+        let Some(entry) = self.analyses.iter().find(|a| a.analysis.symbol == symbol) else { return };
+        if entry.time_range.interval() != Interval::OneDay {
+            return;
+        }
+        let log = self.prediction_log.entry(symbol.to_string()).or_default();
+        let resolved = accuracy::resolve(log, symbol, &entry.stock_data);
+        if accuracy::record(log, &entry.analysis, &entry.stock_data) || resolved {
+            let _ = self.persistence_manager.save_prediction_log(&self.prediction_log);
+        }
+    }
+
+    /// How each model's past forecasts for `symbol` have done.
+    pub fn forecast_accuracy(&self, symbol: &str) -> Vec<(Forecaster, Accuracy)> {
+        self.prediction_log.get(symbol).map_or_else(Vec::new, |log| accuracy::accuracy(log))
+    }
+
+    /// Every strategy the strategies tab can backtest.
+    pub fn strategies(&self) -> Vec<Strategy> {
+        let mut strategies = strategy::builtin();
+        strategies.extend(strategy::parse_definitions(&self.strategy.definitions).unwrap_or_default());
+        strategies
+    }
+
+    /// Insider buying less selling in `symbol` over the last three months.
+    pub fn insider_activity(&self, symbol: &str) -> Option<NetActivity> {
+        insiders::net_activity(self.insider_trades.get(symbol)?, chrono::Utc::now().timestamp())
+    }
+
+    /// Backtest every forecast model on entry `index`'s bars in the
+    /// background, unless that has been done since they last changed.
+    pub(super) fn run_backtest(&mut self, index: usize) {
+        let Some(a) = self.analyses.get(index) else { return };
+        let symbol = a.analysis.symbol.clone();
+        let Some(&last_bar) = a.stock_data.timestamps.last() else { return };
+        if self.backtests.contains_key(&symbol) {
+            return;
+        }
+        self.backtests.insert(symbol.clone(), None);
+        let stock_data = a.stock_data.clone();
+        let window = DEFAULT_WINDOW.min(stock_data.len() / 2);
+        let tx = self.channel_tx.clone();
+        self.rt.spawn_blocking(move || {
+            let results = backtest::backtest_all(&stock_data, window);
+            let _ = tx.send(AppEvent::Backtest(symbol, last_bar, results));
+        });
+    }
+
+    /// Fetch entry `index`'s SEC filings unless they have been this
+    /// session; only US stocks file with EDGAR.
+    pub(super) fn fetch_filings(&mut self, index: usize) {
+        let Some(a) = self.analyses.get(index) else { return };
+        let symbol = a.analysis.symbol.clone();
+        if self.offline || !follows_us_sessions(&symbol) || self.filings.contains_key(&symbol) {
+            return;
+        }
+        self.filings.insert(symbol.clone(), Vec::new());
+        let tx = self.channel_tx.clone();
+        self.rt.spawn(async move {
+            let filings = edgar::fetch_filings(&symbol).await.map_err(|e| e.to_string());
+            let _ = tx.send(AppEvent::Filings(symbol, filings));
+        });
+    }
+
+    /// Fetch headlines about entry `index` unless they have been this
+    /// session.
+    pub(super) fn fetch_news(&mut self, index: usize) {
+        let Some(a) = self.analyses.get(index) else { return };
+        let symbol = a.analysis.symbol.clone();
+        if self.offline || self.news.contains_key(&symbol) {
+            return;
+        }
+        self.news.insert(symbol.clone(), Vec::new());
+        let provider = self.provider.clone();
+        let tx = self.channel_tx.clone();
+        self.rt.spawn(async move {
+            let headlines = provider.fetch_news(&symbol).await.map_err(|e| e.to_string());
+            let _ = tx.send(AppEvent::News(symbol, headlines));
+        });
+    }
+
+    /// Whether `symbol` is flagged as heavily shorted.
+    pub fn heavily_shorted(&self, symbol: &str) -> bool {
+        self.fundamentals.get(symbol).is_some_and(Fundamentals::heavily_shorted)
+    }
+
+    /// Aggregate sentiment of the headlines fetched for `symbol`.
+    pub fn sentiment(&self, symbol: &str) -> Option<Sentiment> {
+        sentiment::aggregate(self.news.get(symbol)?, chrono::Utc::now().timestamp())
+    }
+
+    /// Tilt the forecast of every entry with headlines by their sentiment,
+    /// or refit it untilted when sentiment is not a forecast input.
+    fn tilt_forecasts(&mut self) {
+        for i in 0..self.analyses.len() {
+            let symbol = &self.analyses[i].analysis.symbol;
+            if self.analyses[i].stock_data.is_empty() || !self.news.contains_key(symbol) {
+                continue;
+            }
+            let tilt = if self.sentiment_forecast { self.sentiment(symbol).map_or(0.0, |s| s.score) } else { 0.0 };
+            let entry = &mut self.analyses[i];
+            apply_sentiment(&mut entry.analysis, &entry.stock_data, tilt);
+        }
+    }
+
+    /// Switch news sentiment as a forecast input on or off and remember
+    /// the choice.
+    pub(super) fn toggle_sentiment_forecast(&mut self) {
+        self.sentiment_forecast = !self.sentiment_forecast;
+        let mut ui = self.persistence_manager.get_ui_settings();
+        ui.sentiment_forecast = self.sentiment_forecast;
+        let _ = self.persistence_manager.save_ui_settings(&ui);
+        self.tilt_forecasts();
+        if self.main_sort != MainSort::Watchlist {
+            self.sort_analyses();
+        }
+        self.status_message = Some(if self.sentiment_forecast {
+            "Forecasts tilted by news sentiment".into()
         } else {
-            if self.selected_time_range_index > 0 {
-                self.selected_time_range_index -= 1;
-            } else {
-                self.selected_time_range_index = len - 1;
+            "Forecasts from the price trend only".into()
+        });
+    }
+
+    /// Forecast the selected symbol with `forecaster` and remember the
+    /// choice for it.
+    pub(super) fn set_forecaster(&mut self, forecaster: Forecaster) {
+        let Some(entry) = self.analyses.get_mut(self.selected_index) else { return };
+        let symbol = entry.analysis.symbol.clone();
+        if forecaster == self.forecast.default {
+            self.forecast.symbols.remove(&symbol);
+        } else {
+            self.forecast.symbols.insert(symbol.clone(), forecaster);
+        }
+        if let Err(e) = self.persistence_manager.save_forecast_settings(&self.forecast) {
+            self.status_message = Some(e.to_string());
+            return;
+        }
+        entry.analysis.forecaster = forecaster;
+        if !entry.stock_data.is_empty() {
+            let tilt = if self.sentiment_forecast { self.sentiment(&symbol).map_or(0.0, |s| s.score) } else { 0.0 };
+            let entry = &mut self.analyses[self.selected_index];
+            apply_sentiment(&mut entry.analysis, &entry.stock_data, tilt);
+        }
+        if self.main_sort != MainSort::Watchlist {
+            self.sort_analyses();
+        }
+        self.status_message = Some(format!("{} forecast with {}", symbol, forecaster.label()));
+    }
+
+    /// Every dividend known for `data`'s symbol: its fetched history and
+    /// any in its bars' corporate actions.
+    pub fn dividends_of(&self, data: &AnalysisWithChartData) -> Vec<Dividend> {
+        let history = self.dividends.get(&data.analysis.symbol).map_or(&[][..], Vec::as_slice);
+        dividends::merge(history, &data.stock_data)
+    }
+
+    /// Trailing yield and ex-dates of `data`'s symbol at its current price,
+    /// if it pays dividends.
+    pub fn dividend_summary(&self, data: &AnalysisWithChartData) -> Option<DividendSummary> {
+        dividends::summarize(&self.dividends_of(data), data.analysis.current_price, chrono::Utc::now().timestamp())
+    }
+
+    /// Sector heading entry `index` is grouped under.
+    pub fn sector_of(&self, index: usize) -> &str {
+        self.sector_of_symbol(&self.analyses[index].analysis.symbol)
+    }
+
+    pub fn sector_of_symbol(&self, symbol: &str) -> &str {
+        match self.profiles.get(symbol) {
+            Some(SymbolProfile { sector: Some(sector), .. }) => sector,
+            Some(_) => OTHER_SECTOR,
+            None => UNCLASSIFIED_SECTOR,
+        }
+    }
+
+    /// `main_order` grouped by sector, sectors A→Z with funds and the like,
+    /// then symbols not looked up yet, last. Within a sector the main order
+    /// is kept.
+    pub fn sector_order(&self) -> Vec<usize> {
+        let rank = |s: &str| match s {
+            OTHER_SECTOR => 1,
+            UNCLASSIFIED_SECTOR => 2,
+            _ => 0,
+        };
+        let mut order = self.main_order();
+        order.sort_by(|&i, &j| {
+            let (a, b) = (self.sector_of(i), self.sector_of(j));
+            rank(a).cmp(&rank(b)).then(a.cmp(b))
+        });
+        order
+    }
+
+    /// Run every signal rule over each loaded symbol's bars, adding the
+    /// ones that fired to the history.
+    fn evaluate_signals(&mut self) {
+        self.signals.clear();
+        let mut recorded = false;
+        for a in self.analyses.iter().filter(|a| !a.stock_data.is_empty()) {
+            let symbol = &a.analysis.symbol;
+            let active = detect_signals(symbol, &a.stock_data, &a.analysis);
+            let history = self.signal_history.entry(symbol.clone()).or_default();
+            recorded |= signals::record_history(history, &active, &a.stock_data);
+            self.signals.insert(symbol.clone(), active);
+        }
+        if recorded {
+            let _ = self.persistence_manager.save_signal_history(&self.signal_history);
+        }
+    }
+
+    /// Active signals across all loaded symbols, in the table's sort order.
+    pub fn collect_signals(&self) -> Vec<Signal> {
+        let mut signals: Vec<Signal> = self.analyses.iter()
+            .flat_map(|a| self.signals.get(&a.analysis.symbol).into_iter().flatten().cloned())
+            .collect();
+        signals.sort_by(|a, b| {
+            let ord = match self.signals_sort {
+                SignalSort::Time => a.timestamp.cmp(&b.timestamp),
+                SignalSort::Symbol => a.symbol.cmp(&b.symbol),
+                SignalSort::Kind => a.kind.cmp(&b.kind),
+            };
+            if self.signals_sort_desc { ord.reverse() } else { ord }
+        });
+        signals
+    }
+
+    /// Reorder `analyses` by `main_sort`, keeping the same symbol selected.
+    /// Symbols without data sort last in the metric orders.
+    pub(super) fn sort_analyses(&mut self) {
+        let selected = self.analyses.get(self.selected_index).map(|a| a.analysis.symbol.clone());
+        match self.main_sort {
+            MainSort::Watchlist => {
+                let saved = self.persistence_manager.get_stock_config().map(|c| c.symbols).unwrap_or_default();
+                self.analyses.sort_by_key(|a| saved.iter().position(|s| *s == a.analysis.symbol).unwrap_or(usize::MAX));
+            }
+            MainSort::Symbol => self.analyses.sort_by(|a, b| a.analysis.symbol.cmp(&b.analysis.symbol)),
+            sort => {
+                let key = |a: &AnalysisWithChartData| -> Option<f64> {
+                    if a.stock_data.is_empty() {
+                        return None;
+                    }
+                    match sort {
+                        MainSort::Change => a.analysis.recent_change,
+                        MainSort::Volatility => Some(calculate_volatility(&a.stock_data.closes)),
+                        MainSort::Volume => a.stock_data.volumes.last().map(|&v| v as f64),
+                        _ => prediction_upside(&a.analysis),
+                    }
+                };
+                // Biggest first, missing values last
+                self.analyses.sort_by(|a, b| match (key(a), key(b)) {
+                    (Some(x), Some(y)) => y.total_cmp(&x),
+                    (a, b) => b.is_some().cmp(&a.is_some()),
+                });
             }
         }
-        let new_range = ranges[self.selected_time_range_index];
-        self.analyses[self.selected_index].time_range = new_range;
-        self.fetch_single_stock(self.selected_index, new_range);
+        if let Some(i) = selected.and_then(|s| self.index_of(&s)) {
+            self.selected_index = i;
+        }
+    }
+
+    /// Whether entry `index` matches the open search, if any.
+    fn matches_search(&self, index: usize) -> bool {
+        match &self.search {
+            Some(query) => fuzzy_score(query, &self.analyses[index].analysis.symbol).is_some(),
+            None => true,
+        }
+    }
+
+    /// Indices into `analyses` the main view shows, in watchlist order.
+    pub fn main_order(&self) -> Vec<usize> {
+        (0..self.analyses.len()).filter(|&i| self.matches_search(i)).collect()
+    }
+
+    /// Indices into `analyses` in the table's sort order. Symbols without
+    /// data always sort last.
+    pub fn table_order(&self) -> Vec<usize> {
+        let key = |a: &AnalysisWithChartData| -> f64 {
+            match self.table_sort {
+                TableSort::Symbol => 0.0,
+                TableSort::Price => a.analysis.current_price,
+                TableSort::Change => a.analysis.recent_change.unwrap_or(0.0),
+                TableSort::Trend => sma_spread(&a.analysis).unwrap_or(0.0),
+                TableSort::Volatility => calculate_volatility(&a.stock_data.closes),
+                TableSort::Volume => a.stock_data.volumes.last().copied().unwrap_or(0) as f64,
+            }
+        };
+        let mut order = self.main_order();
+        order.sort_by(|&i, &j| {
+            let (a, b) = (&self.analyses[i], &self.analyses[j]);
+            let ord = match self.table_sort {
+                TableSort::Symbol => a.analysis.symbol.cmp(&b.analysis.symbol),
+                _ => key(a).total_cmp(&key(b)),
+            };
+            let ord = if self.table_sort_desc { ord.reverse() } else { ord };
+            a.stock_data.is_empty().cmp(&b.stock_data.is_empty()).then(ord)
+        });
+        order
+    }
+
+    /// Whether entry `index` has data, is being fetched or failed.
+    pub fn load_state(&self, index: usize) -> LoadState<'_> {
+        let Some(entry) = self.analyses.get(index) else { return LoadState::NotLoaded };
+        let symbol = &entry.analysis.symbol;
+        if self.in_flight.iter().any(|(s, _)| s == symbol) {
+            LoadState::Fetching
+        } else if !entry.stock_data.is_empty() {
+            LoadState::Loaded
+        } else if let Some(err) = self.error_log.iter().rev().find(|e| &e.symbol == symbol) {
+            LoadState::Failed(&err.message)
+        } else {
+            LoadState::NotLoaded
+        }
+    }
+
+    /// Fetch the symbols on screen in the main view that were never requested.
+    pub(super) fn fetch_visible(&mut self) {
+        let indices: Vec<usize> = self.main_hits.tiles.iter().map(|t| t.index).collect();
+        for i in indices {
+            if self.load_state(i) == LoadState::NotLoaded {
+                let tr = self.analyses[i].time_range;
+                self.fetch_single_stock(i, tr);
+            }
+        }
+    }
+
+    /// The latest price of `symbol`, if it is in the watchlist and loaded.
+    pub fn live_price(&self, symbol: &str) -> Option<f64> {
+        self.index_of(symbol)
+            .map(|i| &self.analyses[i])
+            .filter(|a| !a.stock_data.is_empty() && a.analysis.current_price > 0.0)
+            .map(|a| a.analysis.current_price)
+    }
+
+    /// `symbol`'s latest price on the watchlist, or else its last close in
+    /// the portfolio history.
+    pub fn portfolio_price(&self, symbol: &str) -> Option<f64> {
+        self.live_price(symbol).or_else(|| self.portfolio_bars(symbol)?.closes.last().copied())
+    }
+
+    /// Bars fetched for the portfolio view: of a symbol held, or of the
+    /// benchmark.
+    pub fn portfolio_bars(&self, symbol: &str) -> Option<&StockData> {
+        self.portfolio_history.get(symbol).map(|(_, sd)| sd).filter(|sd| !sd.is_empty())
+    }
+
+    /// Index of the entry for `symbol`, if it is in the watchlist.
+    pub(super) fn index_of(&self, symbol: &str) -> Option<usize> {
+        self.analyses.iter().position(|a| a.analysis.symbol == symbol)
+    }
+
+    /// Make sure `index` has data for `time_range`, fetching only when the
+    /// entry is empty or currently holds a different range.
+    pub(super) fn ensure_loaded(&mut self, index: usize, time_range: TimeRange) {
+        let Some(entry) = self.analyses.get(index) else { return };
+        if entry.stock_data.is_empty() || entry.time_range != time_range {
+            self.analyses[index].time_range = time_range;
+            self.fetch_single_stock(index, time_range);
+        }
     }
 
     /// Get the number of visible bars for the currently selected stock.
     pub(super) fn visible_bar_count(&self) -> usize {
         if let Some(data) = self.analyses.get(self.selected_index) {
-            crate::data::filter_bars(&data.stock_data, data.time_range).len()
+            crate::data::window_bars(&data.stock_data, data.time_range, self.chart_window).1.len()
         } else {
             0
         }
     }
+
+    /// Total bars loaded for the currently selected stock.
+    pub(super) fn total_bar_count(&self) -> usize {
+        self.analyses.get(self.selected_index).map_or(0, |a| a.stock_data.len())
+    }
 }
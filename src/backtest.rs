@@ -0,0 +1,198 @@
+use anyhow::Result;
+use std::sync::Arc;
+
+use crate::data::TimeRange;
+use crate::lib::{
+    backtest::{backtest, backtest_all, Backtest, BacktestRow, MIN_WINDOW},
+    forecast::Forecaster,
+    provider::DataProvider,
+    walk_forward::{walk_models, ModelFoldRow, ModelWalk},
+};
+use crate::optimize::fetch_symbols;
+
+/// Output format of `bstock backtest`.
+#[derive(Clone, Copy, Debug, clap::ValueEnum)]
+pub enum BacktestFormat {
+    Table,
+    Json,
+    Csv,
+}
+
+/// Fetch every symbol concurrently and backtest `models` (all of them when
+/// empty) on its bars, split- and dividend-`adjusted` or as traded. Symbols
+/// that fail to load, or are too short for the window, are reported on
+/// stderr and left out.
+async fn backtest_symbols(
+    provider: Arc<dyn DataProvider>,
+    symbols: &[String],
+    time_range: TimeRange,
+    models: &[Forecaster],
+    window: usize,
+    adjusted: bool,
+) -> Vec<(String, Vec<Backtest>)> {
+    let mut tasks = tokio::task::JoinSet::new();
+    for symbol in symbols {
+        let symbol = symbol.clone();
+        let provider = provider.clone();
+        tasks.spawn(async move {
+            let data = provider.fetch_history(&symbol, time_range).await;
+            (symbol, data)
+        });
+    }
+
+    let mut results = Vec::new();
+    while let Some(joined) = tasks.join_next().await {
+        let Ok((symbol, data)) = joined else { continue };
+        match data {
+            Ok(sd) if !sd.is_empty() => {
+                let sd = if adjusted { sd.adjusted() } else { sd };
+                let tests = if models.is_empty() {
+                    backtest_all(&sd, window)
+                } else {
+                    models.iter().filter_map(|&m| backtest(&sd, m, window)).collect()
+                };
+                if tests.is_empty() {
+                    eprintln!("{symbol}: {} bars are too few for a {window}-bar window", sd.len());
+                } else {
+                    results.push((symbol, tests));
+                }
+            }
+            Ok(_) => eprintln!("{symbol}: no data"),
+            Err(e) => eprintln!("{symbol}: {e}"),
+        }
+    }
+    results.sort_by(|a, b| a.0.cmp(&b.0));
+    results
+}
+
+fn print_table(rows: &[BacktestRow]) {
+    println!(
+        "{:<10} {:<14} {:>9} {:>10} {:>7} {:>6} {:>8} {:>8}",
+        "Symbol", "Model", "Forecasts", "MAE", "MAPE", "Hit", "Step 1", "Last",
+    );
+    for r in rows {
+        println!(
+            "{:<10} {:<14} {:>9} {:>10.2} {:>6.2}% {:>5.0}% {:>7.2}% {:>7.2}%",
+            r.symbol, r.model, r.forecasts, r.mae, r.mape, r.hit_rate * 100.0, r.first_step_mape, r.last_step_mape,
+        );
+    }
+}
+
+fn print_csv(rows: &[BacktestRow]) {
+    println!("symbol,model,window,forecasts,mae,mape,hit_rate,first_step_mape,last_step_mape");
+    for r in rows {
+        println!(
+            "{},{},{},{},{:.4},{:.4},{:.4},{:.4},{:.4}",
+            r.symbol, r.model, r.window, r.forecasts, r.mae, r.mape, r.hit_rate, r.first_step_mape, r.last_step_mape,
+        );
+    }
+}
+
+fn print_walk_table(symbol: &str, walk: &ModelWalk, rows: &[ModelFoldRow]) {
+    let (train, test) = walk.folds.first().map_or((0, 0), |f| (f.fold.train.len(), f.fold.test.len()));
+    println!("{symbol}: models walked forward over {} folds, picked on {train} bars and scored on the next {test}", walk.folds.len());
+    println!("{:>4} {:<23} {:<14} {:>8} {:>8}  Best after", "Fold", "Test bars", "Picked", "Picking", "Testing");
+    for r in rows {
+        println!(
+            "{:>4} {:<23} {:<14} {:>7.2}% {:>7.2}%  {}",
+            r.fold, format!("{}–{}", r.test_from, r.test_to), r.picked, r.train_mape, r.test_mape, r.best,
+        );
+    }
+    println!(
+        "Picked model's MAPE: {:.2}% where picked, {:.2}% after; it stayed the best in {} of {} folds",
+        walk.train_mape,
+        walk.test_mape,
+        walk.folds.iter().filter(|f| f.picked == f.best).count(),
+        walk.folds.len(),
+    );
+    println!();
+}
+
+fn print_walk_csv(rows: &[ModelFoldRow]) {
+    println!("symbol,fold,test_from,test_to,picked,train_mape,test_mape,best");
+    for r in rows {
+        println!(
+            "{},{},{},{},{},{:.4},{:.4},{}",
+            r.symbol, r.fold, r.test_from, r.test_to, r.picked, r.train_mape, r.test_mape, r.best,
+        );
+    }
+}
+
+/// Runs `bstock backtest --walk-forward`: on each fold the most accurate
+/// of `models` on `train` bars is scored on the `test` bars after.
+#[allow(clippy::too_many_arguments)]
+fn run_walk_forward(
+    rt: &tokio::runtime::Runtime,
+    provider: Arc<dyn DataProvider>,
+    symbols: &[String],
+    time_range: TimeRange,
+    models: &[Forecaster],
+    window: usize,
+    (train, test): (usize, usize),
+    format: BacktestFormat,
+    adjusted: bool,
+) -> Result<()> {
+    let models = if models.is_empty() { Forecaster::ALL } else { models };
+    let data = rt.block_on(fetch_symbols(provider, symbols, time_range, adjusted));
+    let mut walks = Vec::new();
+    for (symbol, sd) in &data {
+        match walk_models(sd, models, window, train, test) {
+            Ok(walk) => walks.push((symbol, sd, walk)),
+            Err(e) => eprintln!("{symbol}: {e}"),
+        }
+    }
+    match format {
+        BacktestFormat::Table => {
+            for (symbol, sd, walk) in &walks {
+                print_walk_table(symbol, walk, &walk.rows(symbol, sd));
+            }
+        }
+        BacktestFormat::Json => {
+            let rows: Vec<ModelFoldRow> = walks.iter().flat_map(|(symbol, sd, walk)| walk.rows(symbol, sd)).collect();
+            println!("{}", serde_json::to_string_pretty(&rows)?);
+        }
+        BacktestFormat::Csv => {
+            let rows: Vec<ModelFoldRow> = walks.iter().flat_map(|(symbol, sd, walk)| walk.rows(symbol, sd)).collect();
+            print_walk_csv(&rows);
+        }
+    }
+    if walks.is_empty() && !symbols.is_empty() {
+        anyhow::bail!("no symbols could be walked forward");
+    }
+    Ok(())
+}
+
+/// Runs `bstock backtest` without starting the TUI, or with `walk_forward`
+/// the model picked on each fold and how it did after.
+#[allow(clippy::too_many_arguments)]
+pub fn run_backtest(
+    provider: Arc<dyn DataProvider>,
+    symbols: &[String],
+    time_range: TimeRange,
+    models: &[Forecaster],
+    window: usize,
+    walk_forward: Option<(usize, usize)>,
+    format: BacktestFormat,
+    adjusted: bool,
+) -> Result<()> {
+    if window < MIN_WINDOW {
+        anyhow::bail!("the window must be at least {MIN_WINDOW} bars");
+    }
+    let rt = tokio::runtime::Runtime::new()?;
+    if let Some(split) = walk_forward {
+        return run_walk_forward(&rt, provider, symbols, time_range, models, window, split, format, adjusted);
+    }
+    let results = rt.block_on(backtest_symbols(provider, symbols, time_range, models, window, adjusted));
+    let rows: Vec<BacktestRow> = results.iter()
+        .flat_map(|(symbol, tests)| tests.iter().map(|t| t.row(symbol)))
+        .collect();
+    match format {
+        BacktestFormat::Table => print_table(&rows),
+        BacktestFormat::Json => println!("{}", serde_json::to_string_pretty(&rows)?),
+        BacktestFormat::Csv => print_csv(&rows),
+    }
+    if rows.is_empty() && !symbols.is_empty() {
+        anyhow::bail!("no symbols could be backtested");
+    }
+    Ok(())
+}
@@ -1,4 +1,47 @@
-use crate::lib::stock_data::StockData;
+use crate::lib::{stock_data::StockData, timezone::bar_date};
+use chrono::Datelike;
+
+/// Bar size a range is charted with.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Interval {
+    OneMinute,
+    FiveMinutes,
+    OneHour,
+    OneDay,
+    OneWeek,
+    OneMonth,
+}
+
+impl Interval {
+    /// Yahoo Finance v8 API interval.
+    pub fn yahoo(&self) -> &'static str {
+        match self {
+            Interval::OneMinute => "1m",
+            Interval::FiveMinutes => "5m",
+            Interval::OneHour => "1h",
+            Interval::OneDay => "1d",
+            Interval::OneWeek => "1wk",
+            Interval::OneMonth => "1mo",
+        }
+    }
+
+    /// Whether bars are shorter than a trading day.
+    pub fn is_intraday(&self) -> bool {
+        matches!(self, Interval::OneMinute | Interval::FiveMinutes | Interval::OneHour)
+    }
+
+    /// Length of one bar in seconds; a month counts as 31 days.
+    pub fn seconds(&self) -> i64 {
+        match self {
+            Interval::OneMinute => 60,
+            Interval::FiveMinutes => 300,
+            Interval::OneHour => 3_600,
+            Interval::OneDay => 86_400,
+            Interval::OneWeek => 7 * 86_400,
+            Interval::OneMonth => 31 * 86_400,
+        }
+    }
+}
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 #[allow(dead_code)]
@@ -20,6 +63,8 @@ impl TimeRange {
     pub fn all() -> &'static [TimeRange] {
         &[
             TimeRange::OneDay,
+            TimeRange::OneWeek,
+            TimeRange::OneMonth,
             TimeRange::ThreeMonths,
             TimeRange::SixMonths,
             TimeRange::YearToDate,
@@ -31,6 +76,18 @@ impl TimeRange {
         ]
     }
 
+    /// Next (`direction > 0`) or previous range in [`TimeRange::all`], wrapping
+    /// around at either end. Ranges outside the cycle restart from the first.
+    pub fn cycled(&self, direction: i8) -> TimeRange {
+        let ranges = TimeRange::all();
+        let len = ranges.len();
+        match ranges.iter().position(|tr| tr == self) {
+            Some(i) if direction > 0 => ranges[(i + 1) % len],
+            Some(i) => ranges[(i + len - 1) % len],
+            None => ranges[0],
+        }
+    }
+
     pub fn as_str(&self) -> &str {
         match self {
             TimeRange::OneDay => "1D",
@@ -47,27 +104,97 @@ impl TimeRange {
         }
     }
 
+    /// Inverse of [`TimeRange::as_str`].
+    pub fn from_label(label: &str) -> Option<TimeRange> {
+        [
+            TimeRange::OneDay, TimeRange::OneWeek, TimeRange::OneMonth,
+            TimeRange::ThreeMonths, TimeRange::SixMonths, TimeRange::YearToDate,
+            TimeRange::OneYear, TimeRange::TwoYears, TimeRange::FiveYears,
+            TimeRange::TenYears, TimeRange::All,
+        ]
+        .into_iter()
+        .find(|tr| tr.as_str() == label)
+    }
+
+    /// Bar size: minute bars for the day, five-minute bars for the
+    /// five-day week, hourly bars for the month, and longer bars beyond.
+    pub fn interval(&self) -> Interval {
+        match self {
+            TimeRange::OneDay => Interval::OneMinute,
+            TimeRange::OneWeek => Interval::FiveMinutes,
+            TimeRange::OneMonth => Interval::OneHour,
+            TimeRange::ThreeMonths
+            | TimeRange::SixMonths
+            | TimeRange::YearToDate
+            | TimeRange::OneYear
+            | TimeRange::TwoYears => Interval::OneDay,
+            TimeRange::FiveYears => Interval::OneWeek,
+            TimeRange::TenYears | TimeRange::All => Interval::OneMonth,
+        }
+    }
+
     /// Yahoo Finance v8 API (range, interval) pairs.
     pub fn yahoo_params(&self) -> (&'static str, &'static str) {
+        let range = match self {
+            TimeRange::OneDay => "1d",
+            TimeRange::OneWeek => "5d",
+            TimeRange::OneMonth => "1mo",
+            TimeRange::ThreeMonths => "3mo",
+            TimeRange::SixMonths => "6mo",
+            TimeRange::YearToDate => "ytd",
+            TimeRange::OneYear => "1y",
+            TimeRange::TwoYears => "2y",
+            TimeRange::FiveYears => "5y",
+            TimeRange::TenYears => "10y",
+            TimeRange::All => "max",
+        };
+        (range, self.interval().yahoo())
+    }
+
+    /// Yahoo range covering this range *and* the equal period before it, at
+    /// the same interval — what the prior-period overlay needs. `None` when
+    /// there is no earlier history to fetch.
+    pub fn doubled_yahoo_range(&self) -> Option<&'static str> {
         match self {
-            TimeRange::OneDay => ("1d", "1m"),
-            TimeRange::OneWeek => ("5d", "5m"),
-            TimeRange::OneMonth => ("1mo", "1h"),
-            TimeRange::ThreeMonths => ("3mo", "1d"),
-            TimeRange::SixMonths => ("6mo", "1d"),
-            TimeRange::YearToDate => ("ytd", "1d"),
-            TimeRange::OneYear => ("1y", "1d"),
-            TimeRange::TwoYears => ("2y", "1d"),
-            TimeRange::FiveYears => ("5y", "1wk"),
-            TimeRange::TenYears => ("10y", "1mo"),
-            TimeRange::All => ("max", "1mo"),
+            TimeRange::OneDay => Some("5d"),
+            TimeRange::OneWeek => Some("1mo"),
+            TimeRange::OneMonth => Some("3mo"),
+            TimeRange::ThreeMonths => Some("6mo"),
+            TimeRange::SixMonths => Some("1y"),
+            TimeRange::YearToDate | TimeRange::OneYear => Some("2y"),
+            TimeRange::TwoYears => Some("5y"),
+            TimeRange::FiveYears => Some("10y"),
+            TimeRange::TenYears => Some("max"),
+            TimeRange::All => None,
         }
     }
 
     /// Whether this is an intraday range (sub-hourly or sub-daily intervals).
-    #[allow(dead_code)]
     pub fn is_intraday(&self) -> bool {
-        matches!(self, TimeRange::OneDay | TimeRange::OneWeek | TimeRange::OneMonth)
+        self.interval().is_intraday()
+    }
+
+    /// Unix timestamp where the range ending at `end` starts; `None` for
+    /// All, which has no start.
+    pub fn start_before(&self, end: i64) -> Option<i64> {
+        let days = match self {
+            TimeRange::OneDay => 1,
+            TimeRange::OneWeek => 7,
+            TimeRange::OneMonth => 31,
+            TimeRange::ThreeMonths => 92,
+            TimeRange::SixMonths => 183,
+            TimeRange::YearToDate => {
+                let year = chrono::DateTime::from_timestamp(end, 0).map_or(1970, |dt| dt.year());
+                let start = chrono::NaiveDate::from_ymd_opt(year, 1, 1).unwrap_or_default();
+                return Some(start.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc().timestamp());
+            }
+            TimeRange::OneYear => 366,
+            TimeRange::TwoYears => 731,
+            TimeRange::FiveYears => 1827,
+            TimeRange::TenYears => 3653,
+            TimeRange::All => return None,
+        };
+        Some(end - days * 86_400)
     }
 }
 
@@ -97,6 +224,130 @@ pub fn filter_bars(stock_data: &StockData, _time_range: TimeRange) -> Vec<Filter
         .collect()
 }
 
+/// Split `history` into the current period — bars from `current_start` on —
+/// and the prior period right before it. For 1D the prior period is the
+/// whole session before, by trading day in `symbol`'s exchange zone, since
+/// the current session may still be under way; for longer ranges it is at
+/// most the same number of bars. Returns `(prior, current)` closes aligned
+/// by bar from the start.
+pub fn prior_period(history: &StockData, current_start: i64, symbol: &str, time_range: TimeRange) -> (Vec<f64>, Vec<f64>) {
+    let split = history.timestamps.partition_point(|&t| t < current_start);
+    let current = history.closes[split..].to_vec();
+    let start = match (time_range, split.checked_sub(1)) {
+        (TimeRange::OneDay, Some(last)) => {
+            let day = bar_date(symbol, history.timestamps[last]);
+            history.timestamps[..split].iter().rposition(|&t| bar_date(symbol, t) != day).map_or(0, |i| i + 1)
+        }
+        _ => split.saturating_sub(current.len()),
+    };
+    (history.closes[start..split].to_vec(), current)
+}
+
+/// Percent change over the latest session of intraday bars: from the close
+/// of the session before it, or from its first open when the bars start
+/// with it. Sessions are days in `symbol`'s exchange zone.
+pub fn session_change(stock_data: &StockData, symbol: &str) -> Option<f64> {
+    let last = *stock_data.closes.last()?;
+    let day = bar_date(symbol, *stock_data.timestamps.last()?);
+    let base = match stock_data.timestamps.iter().rposition(|&t| bar_date(symbol, t) != day) {
+        Some(i) => stock_data.closes[i],
+        None => *stock_data.opens.first()?,
+    };
+    (base != 0.0).then(|| (last - base) / base * 100.0)
+}
+
+/// The trading day of a bar at `timestamp` as a day number, in `symbol`'s
+/// exchange zone (UTC when unknown), for matching daily bars across markets.
+pub fn trading_day(symbol: &str, timestamp: i64) -> i64 {
+    bar_date(symbol, timestamp).map_or(timestamp.div_euclid(86_400), |d| d.num_days_from_ce() as i64)
+}
+
+/// Closes of `a` and `b` at the bar times both have, with `b`'s timestamps.
+/// Daily bars are matched by trading day in each symbol's own exchange
+/// zone, since two markets stamp the same day differently; intraday bars
+/// by their exact time.
+pub fn join_on_time(a: &StockData, symbol_a: &str, b: &StockData, symbol_b: &str, daily: bool) -> (Vec<i64>, Vec<f64>, Vec<f64>) {
+    let key = |symbol: &str, t: i64| if daily { trading_day(symbol, t) } else { t };
+    let (mut ts, mut ca, mut cb) = (Vec::new(), Vec::new(), Vec::new());
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        let (ka, kb) = (key(symbol_a, a.timestamps[i]), key(symbol_b, b.timestamps[j]));
+        if ka < kb {
+            i += 1;
+        } else if kb < ka {
+            j += 1;
+        } else {
+            ts.push(b.timestamps[j]);
+            ca.push(a.closes[i]);
+            cb.push(b.closes[j]);
+            i += 1;
+            j += 1;
+        }
+    }
+    (ts, ca, cb)
+}
+
+/// The part of `history` that `time_range` covers, counted back from its
+/// last bar rather than from today, since imported prices may end long ago.
+pub fn trailing_range(history: &StockData, time_range: TimeRange) -> StockData {
+    let Some(&last) = history.timestamps.last() else { return history.clone() };
+    match time_range.start_before(last) {
+        Some(start) => history.since(start),
+        None => history.clone(),
+    }
+}
+
+/// Smallest number of bars a zoomed chart may show.
+const MIN_WINDOW: usize = 5;
+
+/// A zoomed/panned slice `[start, end)` of a symbol's bars, independent of
+/// the `TimeRange` preset that was fetched.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ChartWindow {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl ChartWindow {
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// Scale the window by `factor` (< 1 zooms in) keeping `anchor` at the same
+    /// relative position. Returns `None` once the window covers every bar.
+    pub fn zoom(window: Option<ChartWindow>, total: usize, factor: f64, anchor: usize) -> Option<ChartWindow> {
+        let current = window.unwrap_or(ChartWindow { start: 0, end: total });
+        let len = current.len().max(1);
+        let new_len = ((len as f64 * factor).round() as usize).clamp(MIN_WINDOW.min(total), total);
+        if new_len >= total {
+            return None;
+        }
+        let anchor = anchor.clamp(current.start, current.end.saturating_sub(1));
+        let left = (anchor - current.start) * new_len / len;
+        let start = anchor.saturating_sub(left).min(total - new_len);
+        Some(ChartWindow { start, end: start + new_len })
+    }
+
+    /// Shift the window by `delta` bars, stopping at either end of the data.
+    pub fn pan(self, total: usize, delta: isize) -> ChartWindow {
+        let len = self.len();
+        let start = (self.start as isize + delta).clamp(0, total.saturating_sub(len) as isize) as usize;
+        ChartWindow { start, end: start + len }
+    }
+}
+
+/// Bars inside `window` (all bars when `None`), with the index of the first one.
+pub fn window_bars(stock_data: &StockData, time_range: TimeRange, window: Option<ChartWindow>) -> (usize, Vec<FilteredBar>) {
+    let mut bars = filter_bars(stock_data, time_range);
+    match window {
+        Some(w) if w.end <= bars.len() => {
+            bars.truncate(w.end);
+            (w.start, bars.split_off(w.start))
+        }
+        _ => (0, bars),
+    }
+}
+
 /// Calculate volatility (standard deviation of returns).
 pub fn calculate_volatility(prices: &[f64]) -> f64 {
     if prices.len() < 2 {
@@ -117,3 +368,122 @@ pub fn calculate_volatility(prices: &[f64]) -> f64 {
         / returns.len() as f64;
     variance.sqrt() * 100.0
 }
+
+/// Percent change of each price relative to the first one, for overlaying
+/// series with different price levels on a common scale.
+pub fn normalized_pct(prices: &[f64]) -> Vec<f64> {
+    match prices.first() {
+        Some(&base) if base != 0.0 => prices.iter().map(|p| (p - base) / base * 100.0).collect(),
+        _ => vec![0.0; prices.len()],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_ranges_cycle_through_intraday_bars() {
+        assert_eq!(TimeRange::OneDay.cycled(1), TimeRange::OneWeek);
+        assert_eq!(TimeRange::OneWeek.yahoo_params(), ("5d", "5m"));
+        assert!(TimeRange::OneMonth.is_intraday());
+        assert!(!TimeRange::ThreeMonths.is_intraday());
+    }
+
+    #[test]
+    fn test_prior_period() {
+        let mut sd = StockData::new();
+        for i in 0..10 {
+            sd.add_point(i * 100, 0.0, 0.0, 0.0, i as f64, 0);
+        }
+        let (prior, current) = prior_period(&sd, 650, "AAPL", TimeRange::OneMonth);
+        assert_eq!(current, vec![7.0, 8.0, 9.0]);
+        assert_eq!(prior, vec![4.0, 5.0, 6.0]);
+        // Not enough history: the prior period is just shorter
+        let (prior, current) = prior_period(&sd, 300, "AAPL", TimeRange::OneMonth);
+        assert_eq!((prior.len(), current.len()), (3, 7));
+    }
+
+    #[test]
+    fn test_prior_session_is_the_whole_day_before() {
+        let mut sd = StockData::new();
+        // All of 2024-03-04's New York session, then two hours of the 5th
+        for h in 0..7 {
+            sd.add_point(1_709_562_600 + h * 3_600, 0.0, 0.0, 0.0, h as f64, 0);
+        }
+        for h in 0..2 {
+            sd.add_point(1_709_649_000 + h * 3_600, 0.0, 0.0, 0.0, 10.0 + h as f64, 0);
+        }
+        let (prior, current) = prior_period(&sd, 1_709_649_000, "AAPL", TimeRange::OneDay);
+        assert_eq!(current, vec![10.0, 11.0]);
+        assert_eq!(prior.len(), 7);
+    }
+
+    #[test]
+    fn test_session_change_is_measured_from_the_previous_close() {
+        let mut sd = StockData::new();
+        // Two New York sessions of hourly bars: 2024-03-04 and 2024-03-05
+        for (day, base) in [(1_709_562_600, 100.0), (1_709_649_000, 110.0)] {
+            for h in 0..7 {
+                let price = base + h as f64;
+                sd.add_point(day + h * 3_600, price, price, price, price, 0);
+            }
+        }
+        // 116 against the first session's 106 close, not the second's 110 open
+        let change = session_change(&sd, "AAPL").unwrap();
+        assert!((change - 10.0 / 106.0 * 100.0).abs() < 1e-9);
+        // Only one session held: from its open
+        let one = sd.since(1_709_649_000);
+        assert!((session_change(&one, "AAPL").unwrap() - 6.0 / 110.0 * 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_join_on_time_skips_bars_only_one_side_has() {
+        let (mut a, mut b) = (StockData::new(), StockData::new());
+        // Daily bars, 2024-03-04 to 2024-03-08; London misses the 6th, New York the 8th
+        for day in 0..5 {
+            if day != 2 {
+                a.add_point(1_709_542_800 + day * 86_400, 0.0, 0.0, 0.0, day as f64, 0);
+            }
+            if day != 4 {
+                b.add_point(1_709_562_600 + day * 86_400, 0.0, 0.0, 0.0, 10.0 + day as f64, 0);
+            }
+        }
+        let (ts, ca, cb) = join_on_time(&a, "BARC.L", &b, "AAPL", true);
+        assert_eq!(ca, vec![0.0, 1.0, 3.0]);
+        assert_eq!(cb, vec![10.0, 11.0, 13.0]);
+        assert_eq!(ts[2], 1_709_562_600 + 3 * 86_400);
+        // Intraday: the London stamps match none of New York's
+        assert!(join_on_time(&a, "BARC.L", &b, "AAPL", false).0.is_empty());
+    }
+
+    #[test]
+    fn test_trailing_range_counts_back_from_the_last_bar() {
+        let mut sd = StockData::new();
+        // Daily bars through 2020-03-01
+        for day in 0..400 {
+            sd.add_point(1_548_892_800 + day * 86_400, 0.0, 0.0, 0.0, day as f64, 0);
+        }
+        assert_eq!(trailing_range(&sd, TimeRange::OneWeek).len(), 8);
+        assert_eq!(trailing_range(&sd, TimeRange::YearToDate).closes[0], 335.0);
+        assert_eq!(trailing_range(&sd, TimeRange::All).len(), 400);
+    }
+
+    #[test]
+    fn test_zoom_keeps_anchor_and_resets_when_full() {
+        let w = ChartWindow::zoom(None, 100, 0.5, 99).unwrap();
+        assert_eq!(w, ChartWindow { start: 50, end: 100 });
+        let w = ChartWindow::zoom(Some(w), 100, 0.5, 60).unwrap();
+        assert_eq!(w.len(), 25);
+        assert!(w.start <= 60 && 60 < w.end);
+        assert_eq!(ChartWindow::zoom(Some(w), 100, 8.0, 60), None);
+    }
+
+    #[test]
+    fn test_pan_clamps_to_data() {
+        let w = ChartWindow { start: 10, end: 30 };
+        assert_eq!(w.pan(100, -50), ChartWindow { start: 0, end: 20 });
+        assert_eq!(w.pan(100, 500), ChartWindow { start: 80, end: 100 });
+        assert_eq!(w.pan(100, 5), ChartWindow { start: 15, end: 35 });
+    }
+}
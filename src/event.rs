@@ -1,10 +1,62 @@
 use crate::lib::{
     analysis::StockAnalysis,
+    backtest::Backtest,
+    config::SymbolProfile,
+    dividends::Dividend,
+    edgar::Filing,
+    insiders::InsiderTrade,
     stock_data::StockData,
+    provider::{AnalystRatings, Fundamentals, NewsItem, Quote, SymbolMatch},
+    stream::Tick,
 };
 use crate::data::TimeRange;
 
 pub enum AppEvent {
-    Update(StockAnalysis, StockData, TimeRange),
-    Error(String),
+    /// Fetched bars, with the name of the provider that served them.
+    Update(StockAnalysis, StockData, TimeRange, &'static str),
+    /// Bars from the on-disk history cache, shown while the fetch that
+    /// tops them up runs, and the Unix time they were saved.
+    Cached(StockAnalysis, StockData, TimeRange, i64),
+    /// Extended history for the prior-period overlay: symbol, the range it
+    /// was requested for, and bars covering that range twice over.
+    History(String, TimeRange, StockData),
+    /// A failed fetch: symbol, the range asked for, and what went wrong.
+    Error(String, TimeRange, String),
+    /// A provider request for the symbol and range has gone out.
+    Started(String, TimeRange),
+    /// Symbol search results for the query typed in the edit view.
+    Suggestions(String, Vec<SymbolMatch>),
+    /// The latest quote of a symbol added in the edit view, or why it does
+    /// not resolve.
+    SymbolChecked(String, Result<Quote, String>),
+    /// Sector lookup for the symbol, or why it failed.
+    Profile(String, Result<SymbolProfile, String>),
+    /// Valuation figures for the symbol, or why they could not be fetched.
+    Fundamentals(String, Result<Fundamentals, String>),
+    /// Analyst recommendations and targets for the symbol, or why they
+    /// could not be fetched.
+    Ratings(String, Result<AnalystRatings, String>),
+    /// Insider trades in the symbol, or why they could not be fetched.
+    InsiderTrades(String, Result<Vec<InsiderTrade>, String>),
+    /// SEC filings of the symbol, or why they could not be fetched.
+    Filings(String, Result<Vec<Filing>, String>),
+    /// Dividend history for the symbol, or why it could not be fetched.
+    Dividends(String, Result<Vec<Dividend>, String>),
+    /// Bars of a symbol held in the portfolio over the range asked for, or
+    /// why they could not be fetched.
+    PortfolioHistory(String, TimeRange, Result<StockData, String>),
+    /// Backtests of the symbol's forecast models, and the time of the last
+    /// bar they ran to.
+    Backtest(String, i64, Vec<Backtest>),
+    /// Headlines about the symbol, or why they could not be fetched.
+    News(String, Result<Vec<NewsItem>, String>),
+    /// A live price from the quote stream.
+    Tick(Tick),
+    /// A symbol's bars analysed again after streamed prices changed them,
+    /// and the range they were loaded for.
+    Reanalyzed(StockAnalysis, TimeRange),
+    /// The quote stream connected (`true`) or dropped (`false`).
+    StreamState(bool),
+    /// That request has completed, after its Update/History/Error was sent.
+    Finished(String, TimeRange),
 }
@@ -0,0 +1,180 @@
+//! How good past forecasts turned out to be. Each step of a forecast made
+//! from daily bars is kept with the trading day it targeted; once the bars
+//! reach past that day, the close there is its actual. Per symbol and
+//! model this gives the mean absolute error, the mean absolute percentage
+//! error and how often the forecast called the direction right.
+
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, Days, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+
+use crate::lib::{analysis::StockAnalysis, forecast::Forecaster, stock_data::StockData, timezone::{bar_date, ymd}};
+
+/// Predictions kept per symbol; the oldest go first.
+const MAX_PER_SYMBOL: usize = 1000;
+
+/// A forecast step, as it was made.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct TrackedPrediction {
+    pub model: Forecaster,
+    /// Trading day of the last bar the forecast was made from, and its
+    /// close.
+    #[serde(with = "ymd")]
+    pub made_on: NaiveDate,
+    pub base_close: f64,
+    pub step: usize,
+    /// The `step`-th weekday after `made_on`; holidays are not skipped, so
+    /// one falling on a holiday is scored on the next trading day.
+    #[serde(with = "ymd")]
+    pub target: NaiveDate,
+    pub predicted: f64,
+    /// Close on the target day, once known.
+    #[serde(default)]
+    pub actual: Option<f64>,
+}
+
+/// Tracked predictions by symbol.
+pub type PredictionLog = BTreeMap<String, Vec<TrackedPrediction>>;
+
+/// Error measures over a model's scored predictions.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Accuracy {
+    pub scored: usize,
+    pub mae: f64,
+    /// In percent of the actual price.
+    pub mape: f64,
+    /// Share of predictions that moved the same way from the base close as
+    /// the price did, from 0 to 1.
+    pub hit_rate: f64,
+}
+
+/// The weekday `steps` weekdays after `date`.
+fn add_weekdays(date: NaiveDate, steps: usize) -> NaiveDate {
+    let mut date = date;
+    let mut left = steps;
+    while left > 0 {
+        date = date + Days::new(1);
+        if !matches!(date.weekday(), Weekday::Sat | Weekday::Sun) {
+            left -= 1;
+        }
+    }
+    date
+}
+
+/// Add `analysis`'s forecast to `log`, unless the same model already
+/// forecast from the same bar. Returns whether anything was added.
+pub fn record(log: &mut Vec<TrackedPrediction>, analysis: &StockAnalysis, stock_data: &StockData) -> bool {
+    let (Some(&timestamp), Some(&base_close)) = (stock_data.timestamps.last(), stock_data.closes.last()) else { return false };
+    let Some(made_on) = bar_date(&analysis.symbol, timestamp) else { return false };
+    if analysis.predictions.is_empty() || log.iter().any(|p| p.model == analysis.forecaster && p.made_on == made_on) {
+        return false;
+    }
+    log.extend(analysis.predictions.iter().enumerate().map(|(i, &predicted)| TrackedPrediction {
+        model: analysis.forecaster,
+        made_on,
+        base_close,
+        step: i + 1,
+        target: add_weekdays(made_on, i + 1),
+        predicted,
+        actual: None,
+    }));
+    let excess = log.len().saturating_sub(MAX_PER_SYMBOL);
+    log.drain(..excess);
+    true
+}
+
+/// Fill in the actual of each open prediction whose target day `stock_data`
+/// has a settled bar for: the first bar on or after it, when a later bar
+/// shows that one closed. Returns whether any was filled in.
+pub fn resolve(log: &mut [TrackedPrediction], symbol: &str, stock_data: &StockData) -> bool {
+    let days: Vec<(NaiveDate, f64)> = stock_data.timestamps.iter().zip(&stock_data.closes)
+        .filter_map(|(&t, &close)| Some((bar_date(symbol, t)?, close)))
+        .collect();
+    let settled = &days[..days.len().saturating_sub(1)];
+    let mut changed = false;
+    for prediction in log.iter_mut().filter(|p| p.actual.is_none()) {
+        if let Some(&(_, close)) = settled.iter().find(|(day, _)| *day >= prediction.target) {
+            prediction.actual = Some(close);
+            changed = true;
+        }
+    }
+    changed
+}
+
+/// Accuracy of each model that has scored predictions in `log`.
+pub fn accuracy(log: &[TrackedPrediction]) -> Vec<(Forecaster, Accuracy)> {
+    Forecaster::ALL.iter().copied()
+        .filter_map(|model| {
+            let scored = log.iter()
+                .filter(|p| p.model == model)
+                .filter_map(|p| Some((p.base_close, p.predicted, p.actual?)));
+            Some((model, score(scored)?))
+        })
+        .collect()
+}
+
+/// Accuracy of `(base close, predicted, actual)` outcomes; `None` when
+/// there are none.
+pub fn score(outcomes: impl IntoIterator<Item = (f64, f64, f64)>) -> Option<Accuracy> {
+    let (mut n, mut abs_error, mut pct_error, mut hits) = (0, 0.0, 0.0, 0);
+    for (base, predicted, actual) in outcomes {
+        n += 1;
+        abs_error += (predicted - actual).abs();
+        if actual != 0.0 {
+            pct_error += ((predicted - actual) / actual).abs() * 100.0;
+        }
+        if (predicted > base) == (actual > base) {
+            hits += 1;
+        }
+    }
+    (n > 0).then(|| Accuracy {
+        scored: n,
+        mae: abs_error / n as f64,
+        mape: pct_error / n as f64,
+        hit_rate: hits as f64 / n as f64,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lib::{analysis::analyze_stock, forecast::HORIZON};
+
+    fn bars(closes: &[f64]) -> StockData {
+        // Weekdays from Monday 2024-01-01, at 15:00 UTC (New York's session)
+        let mut sd = StockData::new();
+        let mut day = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        for &close in closes {
+            let t = day.and_hms_opt(15, 0, 0).unwrap().and_utc().timestamp();
+            sd.add_point(t, close, close, close, close, 0);
+            day = add_weekdays(day, 1);
+        }
+        sd
+    }
+
+    #[test]
+    fn test_predictions_are_scored_once_their_day_settles() {
+        let history: Vec<f64> = (0..30).map(|i| 100.0 + i as f64).collect();
+        let sd = bars(&history);
+        let analysis = analyze_stock(&sd, "AAA", Forecaster::Linear);
+        let mut log = Vec::new();
+        assert!(record(&mut log, &analysis, &sd));
+        assert!(!record(&mut log, &analysis, &sd));
+        assert_eq!(log.len(), HORIZON);
+        // Made on Friday 2024-02-09: the first step targets Monday
+        assert_eq!(log[0].target, NaiveDate::from_ymd_opt(2024, 2, 12).unwrap());
+
+        // Three more days: Monday and Tuesday have settled, Wednesday not yet
+        let later = bars(&[history.clone(), vec![130.0, 128.0, 140.0]].concat());
+        assert!(resolve(&mut log, "AAA", &later));
+        assert_eq!(log.iter().map(|p| p.actual).collect::<Vec<_>>()[..3], [Some(130.0), Some(128.0), None]);
+
+        let (model, acc) = accuracy(&log)[0];
+        assert_eq!((model, acc.scored), (Forecaster::Linear, 2));
+        let mae = ((log[0].predicted - 130.0).abs() + (log[1].predicted - 128.0).abs()) / 2.0;
+        assert!((acc.mae - mae).abs() < 1e-9, "{:?}", acc);
+        // The trend kept rising from 129: right on Monday, wrong on Tuesday
+        assert!((acc.hit_rate - 0.5).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,275 @@
+//! Alpha Vantage (<https://www.alphavantage.co>), an official API with a
+//! free key. The free tier allows 25 requests a day, so it suits small
+//! watchlists or standing in when Yahoo fails.
+
+use chrono::{NaiveDate, NaiveDateTime};
+
+use crate::data::{trailing_range, Interval, TimeRange};
+use crate::lib::{
+    config::SymbolProfile,
+    error::AppError,
+    market::new_york_to_utc,
+    provider::{AnalystRatings, DataProvider, Fundamentals, ProviderFuture, Quote, SymbolMatch},
+    stock_data::StockData,
+};
+
+const URL: &str = "https://www.alphavantage.co/query";
+
+pub struct AlphaVantageProvider {
+    api_key: String,
+    client: reqwest::Client,
+}
+
+impl AlphaVantageProvider {
+    pub fn new(api_key: String) -> Self {
+        AlphaVantageProvider { api_key, client: reqwest::Client::new() }
+    }
+
+    /// Call `function` with `params` and return the JSON, turning the
+    /// messages Alpha Vantage sends with a 200 status into errors.
+    async fn query(&self, function: &str, params: &[(&str, &str)]) -> Result<serde_json::Value, AppError> {
+        let json: serde_json::Value = self.client
+            .get(URL)
+            .query(&[("function", function), ("apikey", self.api_key.as_str())])
+            .query(params)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| AppError::from_request(format!("Alpha Vantage {function}"), e))?
+            .json()
+            .await
+            .map_err(|e| AppError::ApiError(format!("Parse Alpha Vantage {function}: {e}")))?;
+        // Bad symbols get "Error Message"; the per-minute limit "Note"; the
+        // daily limit and premium-only calls "Information"
+        if let Some(message) = json["Note"].as_str() {
+            return Err(AppError::Unavailable(format!("Alpha Vantage: {message}")));
+        }
+        for key in ["Error Message", "Information"] {
+            if let Some(message) = json[key].as_str() {
+                return Err(AppError::ApiError(format!("Alpha Vantage: {message}")));
+            }
+        }
+        Ok(json)
+    }
+
+    /// Every bar the series for `time_range` has, oldest first.
+    async fn fetch_series(&self, symbol: &str, time_range: TimeRange) -> Result<StockData, AppError> {
+        let (function, interval, size) = series_params(time_range);
+        let mut params = vec![("symbol", symbol), ("outputsize", size)];
+        if let Some(interval) = interval {
+            params.push(("interval", interval));
+        }
+        let json = self.query(function, &params).await?;
+        let data = parse_series(&json)?;
+        if data.is_empty() {
+            return Err(AppError::ApiError(format!("{symbol}: no data from Alpha Vantage")));
+        }
+        Ok(data)
+    }
+}
+
+/// Alpha Vantage function, intraday interval and output size for a range.
+/// Compact output is the latest 100 bars; daily full output needs a paid
+/// key, so only ranges longer than 100 trading days ask for it.
+fn series_params(time_range: TimeRange) -> (&'static str, Option<&'static str>, &'static str) {
+    match time_range.interval() {
+        Interval::OneMinute => ("TIME_SERIES_INTRADAY", Some("1min"), "full"),
+        Interval::FiveMinutes => ("TIME_SERIES_INTRADAY", Some("5min"), "full"),
+        Interval::OneHour => ("TIME_SERIES_INTRADAY", Some("60min"), "full"),
+        Interval::OneDay if time_range == TimeRange::ThreeMonths => ("TIME_SERIES_DAILY", None, "compact"),
+        Interval::OneDay => ("TIME_SERIES_DAILY", None, "full"),
+        Interval::OneWeek => ("TIME_SERIES_WEEKLY", None, "full"),
+        Interval::OneMonth => ("TIME_SERIES_MONTHLY", None, "full"),
+    }
+}
+
+/// Bars from a `TIME_SERIES_*` response. Intraday times are New York
+/// time; dates are taken as midnight UTC.
+fn parse_series(json: &serde_json::Value) -> Result<StockData, AppError> {
+    let series = json.as_object()
+        .and_then(|o| o.iter().find(|(key, _)| key.contains("Time Series")))
+        .and_then(|(_, series)| series.as_object())
+        .ok_or_else(|| AppError::ApiError("Alpha Vantage: no time series in the response".into()))?;
+    let mut rows: Vec<(i64, [f64; 4], u64)> = Vec::new();
+    for (time, bar) in series {
+        let timestamp = match NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M:%S") {
+            Ok(local) => new_york_to_utc(local).timestamp(),
+            Err(_) => match NaiveDate::parse_from_str(time, "%Y-%m-%d") {
+                Ok(date) => date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc().timestamp(),
+                Err(_) => continue,
+            },
+        };
+        let field = |name: &str| bar[name].as_str().and_then(|v| v.parse::<f64>().ok());
+        let (Some(open), Some(high), Some(low), Some(close)) =
+            (field("1. open"), field("2. high"), field("3. low"), field("4. close"))
+        else {
+            continue;
+        };
+        let volume = field("5. volume").unwrap_or(0.0) as u64;
+        rows.push((timestamp, [open, high, low, close], volume));
+    }
+    rows.sort_by_key(|r| r.0);
+    let mut data = StockData::new();
+    for (t, [o, h, l, c], v) in rows {
+        data.add_point(t, o, h, l, c, v);
+    }
+    Ok(data)
+}
+
+impl DataProvider for AlphaVantageProvider {
+    fn name(&self) -> &'static str {
+        "Alpha Vantage"
+    }
+
+    fn fetch_history<'a>(&'a self, symbol: &'a str, time_range: TimeRange) -> ProviderFuture<'a, StockData> {
+        Box::pin(async move {
+            let series = self.fetch_series(symbol, time_range).await?;
+            Ok(trailing_range(&series, time_range))
+        })
+    }
+
+    fn fetch_history_since<'a>(&'a self, symbol: &'a str, time_range: TimeRange, since: i64) -> ProviderFuture<'a, StockData> {
+        Box::pin(async move { Ok(self.fetch_series(symbol, time_range).await?.since(since)) })
+    }
+
+    fn fetch_doubled_history<'a>(&'a self, symbol: &'a str, time_range: TimeRange) -> ProviderFuture<'a, StockData> {
+        Box::pin(async move {
+            if time_range == TimeRange::All {
+                return Err(AppError::ApiError("no history before All".into()));
+            }
+            let series = self.fetch_series(symbol, time_range).await?;
+            let current = trailing_range(&series, time_range);
+            let (start, end) = (current.timestamps[0], current.timestamps[current.len() - 1]);
+            Ok(series.since(start - (end - start)))
+        })
+    }
+
+    fn fetch_quote<'a>(&'a self, symbol: &'a str) -> ProviderFuture<'a, Quote> {
+        Box::pin(async move {
+            let json = self.query("GLOBAL_QUOTE", &[("symbol", symbol)]).await?;
+            let quote = &json["Global Quote"];
+            let field = |name: &str| quote[name].as_str().filter(|s| !s.is_empty());
+            let price = field("05. price").and_then(|p| p.parse().ok())
+                .ok_or_else(|| AppError::ApiError(format!("{symbol}: unknown to Alpha Vantage")))?;
+            let timestamp = field("07. latest trading day")
+                .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+                .map_or(0, |dt| dt.and_utc().timestamp());
+            Ok(Quote {
+                timestamp,
+                price,
+                volume: field("06. volume").and_then(|v| v.parse().ok()).unwrap_or(0),
+            })
+        })
+    }
+
+    fn search_symbols<'a>(&'a self, query: &'a str) -> ProviderFuture<'a, Vec<SymbolMatch>> {
+        Box::pin(async move {
+            let json = self.query("SYMBOL_SEARCH", &[("keywords", query)]).await?;
+            let field = |m: &serde_json::Value, name: &str| m[name].as_str().unwrap_or_default().to_string();
+            Ok(json["bestMatches"].as_array().into_iter().flatten().map(|m| SymbolMatch {
+                symbol: field(m, "1. symbol"),
+                name: field(m, "2. name"),
+                kind: field(m, "3. type"),
+                exchange: field(m, "4. region"),
+            }).collect())
+        })
+    }
+
+    fn fetch_profile<'a>(&'a self, symbol: &'a str) -> ProviderFuture<'a, SymbolProfile> {
+        Box::pin(async move {
+            let json = self.query("OVERVIEW", &[("symbol", symbol)]).await?;
+            // Funds and unknown symbols come back as an empty object
+            if json.as_object().is_some_and(|o| o.is_empty()) {
+                return Ok(parse_etf_profile(&self.query("ETF_PROFILE", &[("symbol", symbol)]).await?));
+            }
+            let field = |name: &str| json[name].as_str().filter(|s| !s.is_empty() && *s != "None").map(str::to_string);
+            Ok(SymbolProfile {
+                sector: field("Sector"),
+                industry: field("Industry"),
+                kind: field("AssetType").map(|t| if t == "Common Stock" { "EQUITY".into() } else { t.to_ascii_uppercase() }),
+                ..SymbolProfile::default()
+            })
+        })
+    }
+
+    fn fetch_fundamentals<'a>(&'a self, symbol: &'a str) -> ProviderFuture<'a, Fundamentals> {
+        Box::pin(async move {
+            let json = self.query("OVERVIEW", &[("symbol", symbol)]).await?;
+            // Missing figures are "None" or "-", which do not parse
+            let number = |name: &str| json[name].as_str().and_then(|v| v.parse::<f64>().ok());
+            Ok(Fundamentals {
+                market_cap: number("MarketCapitalization"),
+                pe: number("PERatio"),
+                forward_pe: number("ForwardPE"),
+                eps: number("EPS"),
+                profit_margin: number("ProfitMargin"),
+                debt_to_equity: None,
+                ..Fundamentals::default()
+            })
+        })
+    }
+
+    fn fetch_ratings<'a>(&'a self, symbol: &'a str) -> ProviderFuture<'a, AnalystRatings> {
+        Box::pin(async move {
+            let json = self.query("OVERVIEW", &[("symbol", symbol)]).await?;
+            let count = |name: &str| json[name].as_str().and_then(|v| v.parse::<u32>().ok()).unwrap_or_default();
+            Ok(AnalystRatings {
+                strong_buy: count("AnalystRatingStrongBuy"),
+                buy: count("AnalystRatingBuy"),
+                hold: count("AnalystRatingHold"),
+                sell: count("AnalystRatingSell"),
+                strong_sell: count("AnalystRatingStrongSell"),
+                target_mean: json["AnalystTargetPrice"].as_str().and_then(|v| v.parse::<f64>().ok()),
+                ..AnalystRatings::default()
+            })
+        })
+    }
+}
+
+/// Fund figures from `ETF_PROFILE`; an empty profile for symbols that are
+/// not ETFs either. Numbers come as strings, the expense ratio a fraction.
+fn parse_etf_profile(json: &serde_json::Value) -> SymbolProfile {
+    let number = |name: &str| json[name].as_str().and_then(|s| s.parse::<f64>().ok());
+    let net_assets = number("net_assets");
+    let expense_ratio = number("net_expense_ratio");
+    if net_assets.is_none() && expense_ratio.is_none() {
+        return SymbolProfile::default();
+    }
+    SymbolProfile { kind: Some("ETF".into()), expense_ratio, net_assets, ..SymbolProfile::default() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_series_parses_daily_and_intraday_bars() {
+        let json = serde_json::json!({
+            "Meta Data": {"2. Symbol": "IBM"},
+            "Time Series (Daily)": {
+                "2024-01-03": {"1. open": "10", "2. high": "12", "3. low": "9", "4. close": "11", "5. volume": "500"},
+                "2024-01-02": {"1. open": "9", "2. high": "10", "3. low": "8", "4. close": "9.5", "5. volume": "400"},
+            }
+        });
+        let data = parse_series(&json).unwrap();
+        assert_eq!(data.timestamps, vec![1704153600, 1704240000]);
+        assert_eq!(data.closes, vec![9.5, 11.0]);
+
+        let json = serde_json::json!({"Time Series (5min)": {
+            "2024-07-01 09:30:00": {"1. open": "1", "2. high": "1", "3. low": "1", "4. close": "1", "5. volume": "1"},
+        }});
+        // 9:30 EDT is 13:30 UTC
+        assert_eq!(parse_series(&json).unwrap().timestamps, vec![1719840600]);
+        assert!(parse_series(&serde_json::json!({})).is_err());
+    }
+
+    #[test]
+    fn test_etf_profile_reads_fund_figures() {
+        let profile = parse_etf_profile(&serde_json::json!({"net_assets": "565700000000", "net_expense_ratio": "0.0945"}));
+        assert_eq!(profile.kind.as_deref(), Some("ETF"));
+        assert_eq!(profile.net_assets, Some(565.7e9));
+        assert_eq!(profile.expense_ratio, Some(0.0945));
+        assert_eq!(parse_etf_profile(&serde_json::json!({})), SymbolProfile::default());
+    }
+}
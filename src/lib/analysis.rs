@@ -1,5 +1,9 @@
+use crate::lib::forecast::{self, Forecaster};
+use crate::lib::garch::{Garch, Z_95};
 use crate::lib::stock_data::StockData;
+use serde::{Deserialize, Serialize};
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct StockAnalysis {
     pub symbol: String,
     pub current_price: f64,
@@ -12,11 +16,33 @@ pub struct StockAnalysis {
     pub sma50_values: Vec<f64>,
     /// Full EMA-20 series.
     pub ema20_values: Vec<f64>,
+    /// Kalman-filtered level at every bar.
+    #[serde(default)]
+    pub kalman_values: Vec<f64>,
+    /// Kalman-filtered slope at the last bar, in price per bar.
+    #[serde(default)]
+    pub kalman_slope: Option<f64>,
     pub predictions: Vec<f64>,
+    /// The model `predictions` came from.
+    #[serde(default)]
+    pub forecaster: Forecaster,
+    /// Next bar's volatility of returns from a GARCH(1,1) fit, as a
+    /// fraction of the price.
+    #[serde(default)]
+    pub garch_volatility: Option<f64>,
+    /// 95% band (low, high) around each prediction, from the forecast's
+    /// standard errors scaled by how the GARCH volatility compares to its
+    /// long-run level.
+    #[serde(default)]
+    pub prediction_bands: Vec<(f64, f64)>,
     pub recent_change: Option<f64>,
 }
 
-pub fn analyze_stock(stock_data: &StockData, symbol: &str) -> StockAnalysis {
+/// Price move a fully positive or negative news sentiment adds by the end
+/// of the forecast, as a fraction of the current price.
+const SENTIMENT_TILT: f64 = 0.02;
+
+pub fn analyze_stock(stock_data: &StockData, symbol: &str, forecaster: Forecaster) -> StockAnalysis {
     let current_price = stock_data.closes.last().copied().unwrap_or(0.0);
 
     let sma10_values = stock_data.sma(10).map(|a| a.to_vec()).unwrap_or_default();
@@ -27,7 +53,12 @@ pub fn analyze_stock(stock_data: &StockData, symbol: &str) -> StockAnalysis {
     let sma_50 = sma50_values.last().copied();
     let ema_20 = ema20_values.last().copied();
 
-    let predictions = stock_data.predict_next(20);
+    let kalman = stock_data.kalman_trend().unwrap_or_default();
+    let kalman_slope = kalman.last().map(|&(_, slope)| slope);
+    let kalman_values = kalman.into_iter().map(|(level, _)| level).collect();
+
+    let garch = Garch::fit(&stock_data.closes);
+    let (predictions, prediction_bands) = bands(forecast::forecast(stock_data, forecaster), garch);
 
     let recent_change = if stock_data.len() >= 2 {
         let last = stock_data.closes.last().unwrap();
@@ -46,7 +77,51 @@ pub fn analyze_stock(stock_data: &StockData, symbol: &str) -> StockAnalysis {
         sma10_values,
         sma50_values,
         ema20_values,
+        kalman_values,
+        kalman_slope,
         predictions,
+        forecaster,
+        garch_volatility: garch.map(|g| g.next_volatility()),
+        prediction_bands,
         recent_change,
     }
 }
+
+/// Forecast at the end of the prediction horizon vs the current price, in percent.
+pub fn prediction_upside(analysis: &StockAnalysis) -> Option<f64> {
+    let target = *analysis.predictions.last()?;
+    (analysis.current_price != 0.0).then(|| (target / analysis.current_price - 1.0) * 100.0)
+}
+
+/// Refit `analysis`'s forecast to `stock_data` and tilt it by a news
+/// `sentiment` from -1 to 1, growing to [`SENTIMENT_TILT`] of the price at
+/// the last step. A sentiment of 0 leaves the plain forecast of
+/// `analysis.forecaster`.
+pub fn apply_sentiment(analysis: &mut StockAnalysis, stock_data: &StockData, sentiment: f64) {
+    let mut forecast = forecast::forecast(stock_data, analysis.forecaster);
+    let steps = forecast.len() as f64;
+    for (i, (p, _)) in forecast.iter_mut().enumerate() {
+        *p += analysis.current_price * sentiment.clamp(-1.0, 1.0) * SENTIMENT_TILT * (i + 1) as f64 / steps;
+    }
+    (analysis.predictions, analysis.prediction_bands) = bands(forecast, Garch::fit(&stock_data.closes));
+}
+
+/// Half the width of the 95% band around prediction `step`.
+pub fn prediction_margin(analysis: &StockAnalysis, step: usize) -> Option<f64> {
+    analysis.prediction_bands.get(step).map(|(lo, hi)| (hi - lo) / 2.0)
+}
+
+/// Predictions and their 95% bands from a forecast of (value, standard
+/// error) pairs. The GARCH fit, when there is one, widens the bands while
+/// volatility runs above its long-run level and narrows them below it.
+fn bands(forecast: Vec<(f64, f64)>, garch: Option<Garch>) -> (Vec<f64>, Vec<(f64, f64)>) {
+    let scale: Vec<f64> = match garch {
+        Some(garch) => garch.cumulative_volatility(forecast.len()).into_iter().enumerate()
+            .map(|(h, sigma)| sigma / (garch.long_run_volatility() * ((h + 1) as f64).sqrt()))
+            .collect(),
+        None => vec![1.0; forecast.len()],
+    };
+    forecast.into_iter().zip(scale)
+        .map(|((p, std_error), k)| (p, (p - Z_95 * std_error * k, p + Z_95 * std_error * k)))
+        .unzip()
+}
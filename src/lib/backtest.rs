@@ -0,0 +1,135 @@
+//! Walk-forward backtests of the forecast models: fit a model to a rolling
+//! window of bars, forecast, and compare the forecast with the closes that
+//! actually followed, stepping the window through the history. Unlike the
+//! live track record in [`crate::lib::accuracy`], this needs no waiting.
+
+use serde::Serialize;
+
+use crate::lib::{
+    accuracy::{score, Accuracy},
+    forecast::{Forecaster, HORIZON},
+    stock_data::StockData,
+};
+
+/// Bars each forecast is fitted to unless asked otherwise.
+pub const DEFAULT_WINDOW: usize = 60;
+
+/// Windows shorter than this leave most models nothing to fit.
+pub const MIN_WINDOW: usize = 20;
+
+/// Forecasts made per model at most, from bars spread evenly over the
+/// history; the latest bar with a full horizon after it is always one.
+const MAX_ORIGINS: usize = 100;
+
+/// How one model did over a history.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Backtest {
+    pub model: Forecaster,
+    pub window: usize,
+    /// Bars a forecast was made from.
+    pub origins: usize,
+    /// Over every step of every forecast.
+    pub overall: Accuracy,
+    /// Over the first step of each forecast, and over the last.
+    pub first_step: Accuracy,
+    pub last_step: Accuracy,
+}
+
+/// A backtest as `bstock backtest --format json` writes it.
+#[derive(Serialize)]
+pub struct BacktestRow<'a> {
+    pub symbol: &'a str,
+    pub model: &'static str,
+    pub window: usize,
+    pub forecasts: usize,
+    pub mae: f64,
+    pub mape: f64,
+    pub hit_rate: f64,
+    pub first_step_mape: f64,
+    pub last_step_mape: f64,
+}
+
+impl Backtest {
+    pub fn row<'a>(&self, symbol: &'a str) -> BacktestRow<'a> {
+        BacktestRow {
+            symbol,
+            model: self.model.label(),
+            window: self.window,
+            forecasts: self.origins,
+            mae: self.overall.mae,
+            mape: self.overall.mape,
+            hit_rate: self.overall.hit_rate,
+            first_step_mape: self.first_step.mape,
+            last_step_mape: self.last_step.mape,
+        }
+    }
+}
+
+/// Walk `model` over `stock_data`, fitting each forecast to the `window`
+/// bars before it. `None` when the window is under [`MIN_WINDOW`], the
+/// history is too short for a forecast, or the model cannot fit that few
+/// bars.
+pub fn backtest(stock_data: &StockData, model: Forecaster, window: usize) -> Option<Backtest> {
+    let n = stock_data.len();
+    if window < MIN_WINDOW || n < window + HORIZON {
+        return None;
+    }
+    // Forecasting from bar `t` fits bars `t - window..t` and is scored on
+    // closes `t..t + HORIZON`
+    let latest = n - HORIZON;
+    let stride = (latest - window + 1).div_ceil(MAX_ORIGINS);
+    let mut outcomes: Vec<Vec<(f64, f64, f64)>> = vec![Vec::new(); HORIZON];
+    let mut origins = 0;
+    for t in (window..=latest).rev().step_by(stride) {
+        let forecast = model.predictor().predict(&stock_data.slice(t - window..t));
+        if forecast.is_empty() {
+            continue;
+        }
+        origins += 1;
+        let base = stock_data.closes[t - 1];
+        for (step, &(predicted, _)) in forecast.iter().take(HORIZON).enumerate() {
+            outcomes[step].push((base, predicted, stock_data.closes[t + step]));
+        }
+    }
+    Some(Backtest {
+        model,
+        window,
+        origins,
+        overall: score(outcomes.concat())?,
+        first_step: score(outcomes[0].iter().copied())?,
+        last_step: score(outcomes[HORIZON - 1].iter().copied())?,
+    })
+}
+
+/// Backtests of every model that can be fitted, most accurate (by mean
+/// absolute percentage error) first.
+pub fn backtest_all(stock_data: &StockData, window: usize) -> Vec<Backtest> {
+    let mut results: Vec<Backtest> = Forecaster::ALL.iter().copied()
+        .filter_map(|model| backtest(stock_data, model, window))
+        .collect();
+    results.sort_by(|a, b| a.overall.mape.total_cmp(&b.overall.mape));
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_a_straight_line_is_forecast_exactly() {
+        let mut sd = StockData::new();
+        for i in 0..200 {
+            let close = 50.0 + i as f64 * 0.5;
+            sd.add_point(1_700_000_000 + i * 86_400, close, close, close, close, 0);
+        }
+        let result = backtest(&sd, Forecaster::Kalman, DEFAULT_WINDOW).unwrap();
+        // 136 bars have a window before them and a horizon after: every other one
+        assert_eq!(result.origins, 68);
+        assert!(result.overall.mape < 0.5, "{:?}", result);
+        assert_eq!(result.overall.hit_rate, 1.0);
+        assert_eq!(result.overall.scored, result.origins * HORIZON);
+
+        assert!(backtest(&sd, Forecaster::Linear, MIN_WINDOW - 1).is_none());
+        assert!(backtest(&sd.slice(0..DEFAULT_WINDOW), Forecaster::Linear, DEFAULT_WINDOW).is_none());
+    }
+}
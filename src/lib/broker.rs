@@ -0,0 +1,271 @@
+//! Transactions from the CSV histories brokers export. Each format is a
+//! set of header names for the date, symbol, action, quantity, price and
+//! fee columns; the format is recognised from the header, which need not
+//! be the first line (Schwab and Fidelity put a title above it). Rows that
+//! are not buys or sells, such as dividends and transfers, are left out.
+
+use std::fs;
+use std::path::Path;
+
+use chrono::NaiveDate;
+
+use crate::lib::{error::AppError, paper::Side, portfolio::Transaction};
+
+/// A broker's export layout.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum BrokerFormat {
+    /// `date`, `symbol`, `action` (or a signed quantity), `quantity`,
+    /// `price` and optionally `fees` columns, in any order and case.
+    Generic,
+    Schwab,
+    Fidelity,
+    Robinhood,
+}
+
+/// Header names of each column, lower-cased; the first found is used.
+/// Every fee column found is added up.
+struct Columns {
+    date: &'static [&'static str],
+    symbol: &'static [&'static str],
+    action: &'static [&'static str],
+    quantity: &'static [&'static str],
+    price: &'static [&'static str],
+    fees: &'static [&'static str],
+}
+
+impl BrokerFormat {
+    /// The specific brokers first, as a generic header matches theirs too.
+    const ALL: [BrokerFormat; 4] = [BrokerFormat::Schwab, BrokerFormat::Fidelity, BrokerFormat::Robinhood, BrokerFormat::Generic];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            BrokerFormat::Generic => "generic",
+            BrokerFormat::Schwab => "Schwab",
+            BrokerFormat::Fidelity => "Fidelity",
+            BrokerFormat::Robinhood => "Robinhood",
+        }
+    }
+
+    fn columns(self) -> Columns {
+        match self {
+            BrokerFormat::Generic => Columns {
+                date: &["date", "trade date", "time"],
+                symbol: &["symbol", "ticker"],
+                action: &["action", "side", "type"],
+                quantity: &["quantity", "shares", "qty"],
+                price: &["price"],
+                fees: &["fees", "fee", "commission"],
+            },
+            BrokerFormat::Schwab => Columns {
+                date: &["date"],
+                symbol: &["symbol"],
+                action: &["action"],
+                quantity: &["quantity"],
+                price: &["price"],
+                fees: &["fees & comm"],
+            },
+            BrokerFormat::Fidelity => Columns {
+                date: &["run date"],
+                symbol: &["symbol"],
+                action: &["action"],
+                quantity: &["quantity"],
+                price: &["price ($)"],
+                fees: &["commission ($)", "fees ($)"],
+            },
+            BrokerFormat::Robinhood => Columns {
+                date: &["activity date"],
+                symbol: &["instrument"],
+                action: &["trans code"],
+                quantity: &["quantity"],
+                price: &["price"],
+                fees: &[],
+            },
+        }
+    }
+
+    /// Whether `names` is this format's header: every column but the fees
+    /// is there.
+    fn matches(self, names: &[String]) -> bool {
+        let has = |candidates: &[&str]| candidates.iter().any(|c| names.iter().any(|n| n == c));
+        let columns = self.columns();
+        let action = has(columns.action) || self == BrokerFormat::Generic;
+        // Schwab's other columns are generic ones
+        let own = self != BrokerFormat::Schwab || has(columns.fees);
+        own && action && has(columns.date) && has(columns.symbol) && has(columns.quantity) && has(columns.price)
+    }
+}
+
+/// The rows of a CSV, as cells. Commas and line breaks inside quotes stay
+/// in the cell, as in Robinhood's two-line descriptions.
+fn records(text: &str) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut cell = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                cell.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => record.push(std::mem::take(&mut cell).trim().to_string()),
+            '\n' if !quoted => {
+                record.push(std::mem::take(&mut cell).trim().to_string());
+                records.push(std::mem::take(&mut record));
+            }
+            '\r' if !quoted => {}
+            _ => cell.push(c),
+        }
+    }
+    if !cell.trim().is_empty() || !record.is_empty() {
+        record.push(cell.trim().to_string());
+        records.push(record);
+    }
+    records
+}
+
+/// A money or share amount as brokers write them: `$1,234.50`, `(12.00)`
+/// for a negative, `--` or blank for none.
+fn amount(cell: &str) -> Option<f64> {
+    let cell = cell.trim();
+    let (negative, cell) = match cell.strip_prefix('(').and_then(|c| c.strip_suffix(')')) {
+        Some(inner) => (true, inner),
+        None => (false, cell),
+    };
+    let digits: String = cell.chars().filter(|c| !matches!(c, '$' | ',' | ' ')).collect();
+    let value: f64 = digits.parse().ok().filter(|v: &f64| v.is_finite())?;
+    Some(if negative { -value } else { value })
+}
+
+/// The first date in `cell`: Schwab writes `01/02/2024 as of 12/29/2023`
+/// for trades booked late, and some exports add a time.
+fn date(cell: &str) -> Option<NaiveDate> {
+    let first = cell.split_whitespace().next()?.trim_end_matches(',');
+    ["%m/%d/%Y", "%Y-%m-%d", "%Y/%m/%d", "%d.%m.%Y"].iter()
+        .find_map(|format| NaiveDate::parse_from_str(first, format).ok())
+}
+
+/// Buy or sell from an action cell, such as `Buy`, `YOU BOUGHT APPLE INC`
+/// or `Reinvest Shares`; anything else is not a trade.
+fn side(action: &str) -> Option<Side> {
+    let action = action.to_ascii_lowercase();
+    if action.contains("sell") || action.contains("sold") {
+        Some(Side::Sell)
+    } else if action.contains("buy") || action.contains("bought") || action.contains("reinvest") {
+        Some(Side::Buy)
+    } else {
+        None
+    }
+}
+
+/// The buys and sells in `text`, in `format` or else the first format whose
+/// header is found, with the format used.
+pub fn parse_transactions(text: &str, format: Option<BrokerFormat>) -> Result<(BrokerFormat, Vec<Transaction>), AppError> {
+    let rows = records(text);
+    let names = |row: &[String]| -> Vec<String> { row.iter().map(|n| n.to_ascii_lowercase()).collect() };
+    let formats = match format {
+        Some(format) => vec![format],
+        None => BrokerFormat::ALL.to_vec(),
+    };
+    let (format, header) = rows.iter().enumerate()
+        .find_map(|(i, row)| {
+            let names = names(row);
+            formats.iter().find(|f| f.matches(&names)).map(|&f| (f, i))
+        })
+        .ok_or_else(|| AppError::Import(match format {
+            Some(format) => format!("no {} header with date, symbol, quantity and price columns", format.label()),
+            None => "no header with date, symbol, quantity and price columns".into(),
+        }))?;
+    let names = names(&rows[header]);
+    let columns = format.columns();
+    let column = |candidates: &[&str]| candidates.iter().find_map(|c| names.iter().position(|n| n == c));
+    let (Some(date_at), Some(symbol_at), Some(quantity_at), Some(price_at)) =
+        (column(columns.date), column(columns.symbol), column(columns.quantity), column(columns.price))
+    else {
+        return Err(AppError::Import("the header is missing a column".into()));
+    };
+    let action_at = column(columns.action);
+    let fees_at: Vec<usize> = columns.fees.iter().filter_map(|c| column(&[c])).collect();
+
+    let mut transactions = Vec::new();
+    for cells in &rows[header + 1..] {
+        let cell = |i: usize| cells.get(i).map_or("", |c| c.as_str());
+        let (Some(date), Some(quantity), Some(price)) = (date(cell(date_at)), amount(cell(quantity_at)), amount(cell(price_at))) else {
+            continue;
+        };
+        // Without an action column a negative quantity is a sell
+        let side = match action_at {
+            Some(i) => side(cell(i)),
+            None => Some(if quantity < 0.0 { Side::Sell } else { Side::Buy }),
+        };
+        let symbol = cell(symbol_at).to_uppercase();
+        let (Some(side), false, true) = (side, symbol.is_empty(), quantity != 0.0 && price >= 0.0) else { continue };
+        let fees = fees_at.iter().filter_map(|&i| amount(cell(i))).map(f64::abs).sum();
+        transactions.push(Transaction { date, symbol, side, quantity: quantity.abs(), price, fees });
+    }
+    if transactions.is_empty() {
+        return Err(AppError::Import("no buys or sells with a date, quantity and price".into()));
+    }
+    Ok((format, transactions))
+}
+
+/// Read and parse a broker's transaction history from `path`.
+pub fn read_transactions(path: &Path, format: Option<BrokerFormat>) -> Result<(BrokerFormat, Vec<Transaction>), AppError> {
+    parse_transactions(&fs::read_to_string(path)?, format)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_generic_with_signed_quantities() {
+        let text = "Date,Symbol,Quantity,Price,Commission\n2024-01-02,aapl,10,185.5,1\n2024-02-01,AAPL,-4,190,1\n2024-02-02,,1,1,0\n";
+        let (format, transactions) = parse_transactions(text, None).unwrap();
+        assert_eq!(format, BrokerFormat::Generic);
+        assert_eq!(transactions.len(), 2);
+        assert_eq!(transactions[0], Transaction { date: day(2024, 1, 2), symbol: "AAPL".into(), side: Side::Buy, quantity: 10.0, price: 185.5, fees: 1.0 });
+        assert_eq!((transactions[1].side, transactions[1].quantity), (Side::Sell, 4.0));
+    }
+
+    #[test]
+    fn test_broker_exports_are_recognised() {
+        let schwab = "\"Transactions  for account XXXX-1234 as of 03/01/2024\"\n\
+            \"Date\",\"Action\",\"Symbol\",\"Description\",\"Quantity\",\"Price\",\"Fees & Comm\",\"Amount\"\n\
+            \"02/01/2024 as of 01/31/2024\",\"Sell\",\"MSFT\",\"MICROSOFT CORP\",\"5\",\"$400.10\",\"$0.05\",\"$2,000.45\"\n\
+            \"01/15/2024\",\"Qualified Dividend\",\"MSFT\",\"MICROSOFT CORP\",\"\",\"\",\"\",\"$3.75\"\n\
+            \"01/02/2024\",\"Buy\",\"MSFT\",\"MICROSOFT CORP\",\"10\",\"$1,370.00\",\"\",\"-$13,700.00\"\n";
+        let (format, transactions) = parse_transactions(schwab, None).unwrap();
+        assert_eq!(format, BrokerFormat::Schwab);
+        assert_eq!(transactions.len(), 2);
+        assert_eq!((transactions[0].date, transactions[0].side, transactions[0].fees), (day(2024, 2, 1), Side::Sell, 0.05));
+        assert_eq!(transactions[1].price, 1370.0);
+
+        let fidelity = "\n\nRun Date,Action,Symbol,Security Description,Security Type,Quantity,Price ($),Commission ($),Fees ($),Amount ($)\n\
+            03/04/2024,\" YOU SOLD NVIDIA CORP (NVDA) (Cash)\", NVDA,NVIDIA CORP,Cash,-2,850.5,0,0.02,1700.98\n\
+            02/01/2024,\" YOU BOUGHT NVIDIA CORP (NVDA) (Cash)\", NVDA,NVIDIA CORP,Cash,3,600,1,,-1801\n\
+            \"The data and information in this spreadsheet is provided to you solely for your use.\"\n";
+        let (format, transactions) = parse_transactions(fidelity, None).unwrap();
+        assert_eq!(format, BrokerFormat::Fidelity);
+        assert_eq!(transactions.len(), 2);
+        assert_eq!((transactions[0].side, transactions[0].quantity, transactions[0].fees), (Side::Sell, 2.0, 0.02));
+        assert_eq!((transactions[1].symbol.as_str(), transactions[1].fees), ("NVDA", 1.0));
+
+        let robinhood = "\"Activity Date\",\"Process Date\",\"Settle Date\",\"Instrument\",\"Description\",\"Trans Code\",\"Quantity\",\"Price\",\"Amount\"\n\
+            \"1/5/2024\",\"1/5/2024\",\"1/9/2024\",\"TSLA\",\"Tesla\nCUSIP: 88160R101\",\"Buy\",\"2\",\"$237.49\",\"($474.98)\"\n\
+            \"1/8/2024\",\"1/8/2024\",\"1/8/2024\",\"\",\"ACH Deposit\",\"ACH\",\"\",\"\",\"$500.00\"\n";
+        let (format, transactions) = parse_transactions(robinhood, None).unwrap();
+        assert_eq!(format, BrokerFormat::Robinhood);
+        assert_eq!(transactions.len(), 1);
+        assert_eq!((transactions[0].date, transactions[0].price), (day(2024, 1, 5), 237.49));
+
+        assert!(parse_transactions(robinhood, Some(BrokerFormat::Schwab)).is_err());
+        assert!(parse_transactions("Name,Price\nApple,1\n", None).is_err());
+    }
+}
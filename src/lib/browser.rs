@@ -0,0 +1,34 @@
+//! Opening links in the user's web browser, through the platform's own
+//! opener: `open` on macOS, `start` on Windows and `xdg-open` elsewhere.
+
+use std::io;
+use std::process::{Command, Stdio};
+
+use crate::lib::error::AppError;
+
+/// Open `url` in the default browser without waiting for it. Only web
+/// links are opened, so a provider cannot have a local file or program run.
+pub fn open(url: &str) -> Result<(), AppError> {
+    if !(url.starts_with("https://") || url.starts_with("http://")) {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("not a web link: {url}")).into());
+    }
+    let mut command = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        // The empty argument is the window title `start` would take the URL for
+        command.args(["/C", "start", ""]);
+        command
+    } else {
+        Command::new("xdg-open")
+    };
+    // The opener writes to the terminal otherwise, over the interface
+    let mut child = command
+        .arg(url)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
@@ -0,0 +1,353 @@
+//! Exchange calendar: when each market a symbol trades on is open, from its
+//! local hours, daylight-saving rules and, for the larger exchanges, its
+//! holidays. Early closes (the US half days around Thanksgiving and
+//! Christmas) are not modelled; those days count as full sessions.
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Timelike, Utc, Weekday};
+
+use crate::lib::{instrument::AssetClass, market::Session};
+
+/// A time zone by its offset rules, enough for the exchanges below.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Zone {
+    /// US Eastern: daylight time from the second Sunday of March to the
+    /// first Sunday of November, 2am local both times.
+    NewYork,
+    /// UK: summer time from the last Sunday of March to the last Sunday of
+    /// October, 1:00 UTC both times, as in the rest of the EU rule.
+    London,
+    CentralEurope,
+    Tokyo,
+    HongKong,
+    India,
+    /// New South Wales: summer time from the first Sunday of October to
+    /// the first Sunday of April.
+    Sydney,
+}
+
+/// The `n`th (1-based) `weekday` of a month.
+fn nth_weekday(year: i32, month: u32, weekday: Weekday, n: u32) -> NaiveDate {
+    NaiveDate::from_weekday_of_month_opt(year, month, weekday, n as u8)
+        .expect("every month has a first to fourth of each weekday")
+}
+
+/// The last `weekday` of a month.
+fn last_weekday(year: i32, month: u32, weekday: Weekday) -> NaiveDate {
+    NaiveDate::from_weekday_of_month_opt(year, month, weekday, 5)
+        .unwrap_or_else(|| nth_weekday(year, month, weekday, 4))
+}
+
+impl Zone {
+    /// Offset from UTC in minutes at `utc`.
+    pub fn offset_minutes(self, utc: DateTime<Utc>) -> i64 {
+        let year = utc.year();
+        let at = |date: NaiveDate, hour| date.and_hms_opt(hour, 0, 0).unwrap().and_utc();
+        let eu_summer = || {
+            utc >= at(last_weekday(year, 3, Weekday::Sun), 1) && utc < at(last_weekday(year, 10, Weekday::Sun), 1)
+        };
+        match self {
+            // 2am EST is 7:00 UTC; 2am EDT is 6:00 UTC
+            Zone::NewYork => {
+                let summer = utc >= at(nth_weekday(year, 3, Weekday::Sun, 2), 7)
+                    && utc < at(nth_weekday(year, 11, Weekday::Sun, 1), 6);
+                if summer { -240 } else { -300 }
+            }
+            Zone::London => if eu_summer() { 60 } else { 0 },
+            Zone::CentralEurope => if eu_summer() { 120 } else { 60 },
+            Zone::Tokyo => 540,
+            Zone::HongKong => 480,
+            Zone::India => 330,
+            // 2am AEST and 3am AEDT are both 16:00 UTC the day before
+            Zone::Sydney => {
+                let summer_end = at(nth_weekday(year, 4, Weekday::Sun, 1), 16) - Duration::days(1);
+                let summer_start = at(nth_weekday(year, 10, Weekday::Sun, 1), 16) - Duration::days(1);
+                if utc < summer_end || utc >= summer_start { 660 } else { 600 }
+            }
+        }
+    }
+
+    /// Wall-clock time in this zone at `utc`, as a naive UTC value.
+    pub fn local(self, utc: DateTime<Utc>) -> DateTime<Utc> {
+        utc + Duration::minutes(self.offset_minutes(utc))
+    }
+}
+
+/// Easter Sunday, by the anonymous Gregorian algorithm.
+fn easter(year: i32) -> NaiveDate {
+    let (a, b, c) = (year % 19, year / 100, year % 100);
+    let (d, e) = (b / 4, b % 4);
+    let g = (b - (b + 8) / 25 + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let (i, k) = (c / 4, c % 4);
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32).expect("Easter is a real date")
+}
+
+fn is_weekend(date: NaiveDate) -> bool {
+    matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+}
+
+/// Whose holidays an exchange keeps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Holidays {
+    /// NYSE and Nasdaq.
+    Us,
+    /// The London Stock Exchange: England's bank holidays.
+    England,
+    /// Xetra and the Frankfurt exchange.
+    Germany,
+    /// Euronext Paris, Amsterdam and Brussels.
+    Euronext,
+    /// Holidays not known; only weekends are closed.
+    Unknown,
+}
+
+impl Holidays {
+    fn contains(self, date: NaiveDate) -> bool {
+        let year = date.year();
+        let day = |month, day| NaiveDate::from_ymd_opt(year, month, day).unwrap();
+        let good_friday = easter(year) - Duration::days(2);
+        let easter_monday = easter(year) + Duration::days(1);
+        match self {
+            Holidays::Us => {
+                // Saturdays are observed the Friday before and Sundays the
+                // Monday after, except New Year's Day, which is not moved
+                // back into the old year
+                let observed = |date: NaiveDate| match date.weekday() {
+                    Weekday::Sat => date - Duration::days(1),
+                    Weekday::Sun => date + Duration::days(1),
+                    _ => date,
+                };
+                let mut holidays = vec![
+                    nth_weekday(year, 1, Weekday::Mon, 3),
+                    nth_weekday(year, 2, Weekday::Mon, 3),
+                    good_friday,
+                    last_weekday(year, 5, Weekday::Mon),
+                    observed(day(7, 4)),
+                    nth_weekday(year, 9, Weekday::Mon, 1),
+                    nth_weekday(year, 11, Weekday::Thu, 4),
+                    observed(day(12, 25)),
+                ];
+                if day(1, 1).weekday() != Weekday::Sat {
+                    holidays.push(observed(day(1, 1)));
+                }
+                if year >= 2022 {
+                    holidays.push(observed(day(6, 19)));
+                }
+                holidays.contains(&date)
+            }
+            Holidays::England => {
+                // Holidays on a weekend move to the next free weekday
+                let mut holidays = vec![
+                    good_friday,
+                    easter_monday,
+                    nth_weekday(year, 5, Weekday::Mon, 1),
+                    last_weekday(year, 5, Weekday::Mon),
+                    last_weekday(year, 8, Weekday::Mon),
+                ];
+                for fixed in [day(1, 1), day(12, 25), day(12, 26)] {
+                    let mut date = fixed;
+                    while is_weekend(date) || holidays.contains(&date) {
+                        date += Duration::days(1);
+                    }
+                    holidays.push(date);
+                }
+                holidays.contains(&date)
+            }
+            Holidays::Germany => [day(1, 1), good_friday, easter_monday, day(5, 1), day(12, 24), day(12, 25), day(12, 26), day(12, 31)]
+                .contains(&date),
+            Holidays::Euronext => [day(1, 1), good_friday, easter_monday, day(5, 1), day(12, 25), day(12, 26)].contains(&date),
+            Holidays::Unknown => false,
+        }
+    }
+}
+
+/// A stock exchange's trading day. Times are local minutes after midnight.
+#[derive(Debug, PartialEq)]
+pub struct Exchange {
+    pub name: &'static str,
+    pub zone: Zone,
+    open: u32,
+    close: u32,
+    /// Start of the pre-market and end of after-hours trading, where the
+    /// exchange has them.
+    extended: Option<(u32, u32)>,
+    holidays: Holidays,
+}
+
+const fn hm(hour: u32, minute: u32) -> u32 {
+    hour * 60 + minute
+}
+
+pub const US: Exchange = Exchange {
+    name: "NYSE/Nasdaq", zone: Zone::NewYork, open: hm(9, 30), close: hm(16, 0),
+    extended: Some((hm(4, 0), hm(20, 0))), holidays: Holidays::Us,
+};
+const TORONTO: Exchange = Exchange {
+    name: "Toronto", zone: Zone::NewYork, open: hm(9, 30), close: hm(16, 0), extended: None, holidays: Holidays::Unknown,
+};
+const LONDON: Exchange = Exchange {
+    name: "London", zone: Zone::London, open: hm(8, 0), close: hm(16, 30), extended: None, holidays: Holidays::England,
+};
+const XETRA: Exchange = Exchange {
+    name: "Xetra", zone: Zone::CentralEurope, open: hm(9, 0), close: hm(17, 30), extended: None, holidays: Holidays::Germany,
+};
+const EURONEXT: Exchange = Exchange {
+    name: "Euronext", zone: Zone::CentralEurope, open: hm(9, 0), close: hm(17, 30), extended: None, holidays: Holidays::Euronext,
+};
+const TOKYO: Exchange = Exchange {
+    name: "Tokyo", zone: Zone::Tokyo, open: hm(9, 0), close: hm(15, 30), extended: None, holidays: Holidays::Unknown,
+};
+const HONG_KONG: Exchange = Exchange {
+    name: "Hong Kong", zone: Zone::HongKong, open: hm(9, 30), close: hm(16, 0), extended: None, holidays: Holidays::Unknown,
+};
+const INDIA: Exchange = Exchange {
+    name: "NSE/BSE", zone: Zone::India, open: hm(9, 15), close: hm(15, 30), extended: None, holidays: Holidays::Unknown,
+};
+const SYDNEY: Exchange = Exchange {
+    name: "ASX", zone: Zone::Sydney, open: hm(10, 0), close: hm(16, 0), extended: None, holidays: Holidays::Unknown,
+};
+
+/// Yahoo's exchange suffixes, and the index symbols listed on each
+/// exchange other than the US ones.
+const LISTINGS: [(&Exchange, &[&str], &[&str]); 8] = [
+    (&TORONTO, &["TO", "V", "NE"], &["^GSPTSE"]),
+    (&LONDON, &["L", "IL"], &["^FTSE", "^FTMC"]),
+    (&XETRA, &["DE", "F"], &["^GDAXI", "^MDAXI"]),
+    (&EURONEXT, &["PA", "AS", "BR"], &["^FCHI", "^AEX", "^BFX", "^N100"]),
+    (&TOKYO, &["T"], &["^N225"]),
+    (&HONG_KONG, &["HK"], &["^HSI"]),
+    (&INDIA, &["NS", "BO"], &["^NSEI", "^BSESN"]),
+    (&SYDNEY, &["AX"], &["^AXJO", "^AORD"]),
+];
+
+/// The exchange `symbol` trades on: by its suffix (`VOD.L`), by name for
+/// well-known indices, and the US exchanges for plain tickers and other
+/// indices. `None` for crypto, currencies and suffixes not listed here.
+pub fn exchange_of(symbol: &str) -> Option<&'static Exchange> {
+    let upper = symbol.to_ascii_uppercase();
+    match AssetClass::of(symbol) {
+        AssetClass::Crypto | AssetClass::Forex => None,
+        AssetClass::Index => Some(LISTINGS.iter().find(|(_, _, indices)| indices.contains(&upper.as_str())).map_or(&US, |l| l.0)),
+        AssetClass::Equity => match upper.rsplit_once('.') {
+            None => Some(&US),
+            Some((_, suffix)) => LISTINGS.iter().find(|(_, suffixes, _)| suffixes.contains(&suffix)).map(|l| l.0),
+        },
+    }
+}
+
+impl Exchange {
+    /// Whether `date` (local) is a trading day.
+    pub fn trades_on(&self, date: NaiveDate) -> bool {
+        !is_weekend(date) && !self.holidays.contains(date)
+    }
+
+    /// The exchange's session at `utc`.
+    pub fn session(&self, utc: DateTime<Utc>) -> Session {
+        let local = self.zone.local(utc);
+        if !self.trades_on(local.date_naive()) {
+            return Session::Closed;
+        }
+        let minute = local.hour() * 60 + local.minute();
+        let (pre, after) = self.extended.unwrap_or((self.open, self.close));
+        match minute {
+            m if m < pre => Session::Closed,
+            m if m < self.open => Session::PreMarket,
+            m if m < self.close => Session::Regular,
+            m if m < after => Session::AfterHours,
+            _ => Session::Closed,
+        }
+    }
+}
+
+/// Currencies trade from Sunday 17:00 to Friday 17:00 New York time.
+fn forex_session(utc: DateTime<Utc>) -> Session {
+    let local = Zone::NewYork.local(utc);
+    let open = match local.weekday() {
+        Weekday::Sat => false,
+        Weekday::Sun => local.hour() >= 17,
+        Weekday::Fri => local.hour() < 17,
+        _ => true,
+    };
+    if open { Session::Regular } else { Session::Closed }
+}
+
+/// `symbol`'s market session at `utc`. Crypto never closes; symbols on an
+/// exchange not listed here count as open on weekdays, as their hours are
+/// not known.
+pub fn session(symbol: &str, utc: DateTime<Utc>) -> Session {
+    match AssetClass::of(symbol) {
+        class if class.trades_24_7() => Session::Regular,
+        AssetClass::Forex => forex_session(utc),
+        _ => match exchange_of(symbol) {
+            Some(exchange) => exchange.session(utc),
+            None if is_weekend(utc.date_naive()) => Session::Closed,
+            None => Session::Regular,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_us_holidays() {
+        let holidays = [
+            "2025-01-01", "2025-01-20", "2025-02-17", "2025-04-18", "2025-05-26",
+            "2025-06-19", "2025-07-04", "2025-09-01", "2025-11-27", "2025-12-25",
+        ];
+        for day in holidays {
+            assert!(!US.trades_on(date(day)), "{day}");
+        }
+        assert!(US.trades_on(date("2025-04-21")));
+        // 4 July 2026 is a Saturday, so the Friday is off
+        assert!(!US.trades_on(date("2026-07-03")));
+        // New Year's Day 2022 was a Saturday and not observed
+        assert!(US.trades_on(date("2021-12-31")));
+        assert_eq!(session("AAPL", utc("2025-12-25T15:00:00Z")), Session::Closed);
+    }
+
+    #[test]
+    fn test_exchanges_by_symbol() {
+        assert_eq!(exchange_of("AAPL"), Some(&US));
+        assert_eq!(exchange_of("VOD.L").map(|e| e.name), Some("London"));
+        assert_eq!(exchange_of("^N225").map(|e| e.name), Some("Tokyo"));
+        assert_eq!(exchange_of("^GSPC"), Some(&US));
+        assert_eq!(exchange_of("BTC-USD"), None);
+        assert_eq!(exchange_of("NESN.SW"), None);
+    }
+
+    #[test]
+    fn test_sessions_in_local_time() {
+        // London opens at 8:00 BST, 7:00 UTC, in summer
+        assert_eq!(session("VOD.L", utc("2025-07-15T06:59:00Z")), Session::Closed);
+        assert_eq!(session("VOD.L", utc("2025-07-15T07:00:00Z")), Session::Regular);
+        assert_eq!(session("VOD.L", utc("2025-12-26T10:00:00Z")), Session::Closed);
+        assert_eq!(session("SAP.DE", utc("2025-01-15T16:29:00Z")), Session::Regular);
+        assert_eq!(session("7203.T", utc("2025-01-15T07:00:00Z")), Session::Closed);
+        // Sydney is on summer time in January: 10:00 AEDT is 23:00 UTC
+        assert_eq!(session("BHP.AX", utc("2025-01-14T23:00:00Z")), Session::Regular);
+        assert_eq!(easter(2025), date("2025-04-20"));
+    }
+
+    #[test]
+    fn test_round_the_clock_markets() {
+        assert_eq!(session("BTC-USD", utc("2025-07-19T03:00:00Z")), Session::Regular);
+        // Friday 17:00 EDT to Sunday 17:00 EDT
+        assert_eq!(session("EURUSD=X", utc("2025-07-18T20:59:00Z")), Session::Regular);
+        assert_eq!(session("EURUSD=X", utc("2025-07-19T12:00:00Z")), Session::Closed);
+        assert_eq!(session("EURUSD=X", utc("2025-07-20T21:00:00Z")), Session::Regular);
+    }
+}
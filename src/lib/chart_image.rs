@@ -0,0 +1,289 @@
+//! Small raster price charts, encoded as PNG for reports and chart exports
+//! or sent to the terminal over a graphics protocol. Self-contained: a plain
+//! RGB canvas plus a minimal PNG writer (stored deflate blocks), so no
+//! imaging dependency is needed.
+
+use crate::lib::{analysis::StockAnalysis, stock_data::StockData};
+
+pub type Rgb = [u8; 3];
+
+pub const BG: Rgb = [16, 18, 24];
+pub const GRID: Rgb = [48, 52, 62];
+pub const UP: Rgb = [48, 204, 90];
+pub const DOWN: Rgb = [246, 53, 56];
+pub const SMA10: Rgb = [230, 200, 60];
+pub const SMA50: Rgb = [80, 160, 255];
+pub const EMA20: Rgb = [200, 90, 220];
+pub const CROSSHAIR: Rgb = [255, 255, 160];
+pub const DIVIDEND: Rgb = [240, 160, 60];
+const MARGIN: usize = 6;
+
+/// 8-bit RGB pixels, row-major.
+pub struct RgbImage {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+
+impl RgbImage {
+    fn new(width: usize, height: usize) -> Self {
+        let mut pixels = Vec::with_capacity(width * height * 3);
+        for _ in 0..width * height {
+            pixels.extend_from_slice(&BG);
+        }
+        RgbImage { width, height, pixels }
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> Rgb {
+        let i = (y * self.width + x) * 3;
+        [self.pixels[i], self.pixels[i + 1], self.pixels[i + 2]]
+    }
+
+    fn set(&mut self, x: i64, y: i64, c: Rgb) {
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            return;
+        }
+        let i = (y as usize * self.width + x as usize) * 3;
+        self.pixels[i..i + 3].copy_from_slice(&c);
+    }
+
+    /// Bresenham line, `thick` pixels wide (vertically).
+    fn line(&mut self, (x0, y0): (i64, i64), (x1, y1): (i64, i64), c: Rgb, thick: i64) {
+        let (dx, dy) = ((x1 - x0).abs(), -(y1 - y0).abs());
+        let (sx, sy) = (if x0 < x1 { 1 } else { -1 }, if y0 < y1 { 1 } else { -1 });
+        let (mut x, mut y, mut err) = (x0, y0, dx + dy);
+        loop {
+            for t in 0..thick {
+                self.set(x, y + t, c);
+            }
+            if x == x1 && y == y1 {
+                break;
+            }
+            let e2 = 2 * err;
+            if e2 >= dy {
+                err += dy;
+                x += sx;
+            }
+            if e2 <= dx {
+                err += dx;
+                y += sy;
+            }
+        }
+    }
+
+    pub fn to_png(&self) -> Vec<u8> {
+        encode_png(self.width, self.height, &self.pixels)
+    }
+}
+
+/// Draws data-space primitives onto an [`RgbImage`], mapping `x_bounds` and
+/// `y_bounds` onto the image inset by `margin` pixels.
+pub struct Plot {
+    image: RgbImage,
+    x_bounds: (f64, f64),
+    y_bounds: (f64, f64),
+    margin: usize,
+}
+
+impl Plot {
+    pub fn new(width: usize, height: usize, x_bounds: (f64, f64), y_bounds: (f64, f64), margin: usize) -> Option<Plot> {
+        if width <= 2 * margin + 1 || height <= 2 * margin + 1 {
+            return None;
+        }
+        Some(Plot { image: RgbImage::new(width, height), x_bounds, y_bounds, margin })
+    }
+
+    fn to_px(&self, x: f64, y: f64) -> (i64, i64) {
+        let m = self.margin as f64;
+        let w = (self.image.width - 2 * self.margin - 1) as f64;
+        let h = (self.image.height - 2 * self.margin - 1) as f64;
+        let (x0, x1) = self.x_bounds;
+        let (y0, y1) = self.y_bounds;
+        let px = m + (x - x0) / (x1 - x0).max(f64::EPSILON) * w;
+        let py = m + (y1 - y) / (y1 - y0).max(f64::EPSILON) * h;
+        (px.round() as i64, py.round() as i64)
+    }
+
+    /// Pixels per unit along x, for sizing candle bodies.
+    pub fn x_scale(&self) -> f64 {
+        (self.image.width - 2 * self.margin) as f64 / (self.x_bounds.1 - self.x_bounds.0).max(f64::EPSILON)
+    }
+
+    /// `n` evenly spaced horizontal guides from the top edge to the bottom.
+    pub fn grid(&mut self, n: usize, c: Rgb) -> &mut Self {
+        let (y0, y1) = self.y_bounds;
+        for k in 0..n {
+            let y = y1 - (y1 - y0) * k as f64 / (n as f64 - 1.0).max(1.0);
+            self.hline(y, c);
+        }
+        self
+    }
+
+    pub fn hline(&mut self, y: f64, c: Rgb) -> &mut Self {
+        let (a, b) = (self.to_px(self.x_bounds.0, y), self.to_px(self.x_bounds.1, y));
+        self.image.line(a, b, c, 1);
+        self
+    }
+
+    pub fn vline(&mut self, x: f64, c: Rgb) -> &mut Self {
+        let (a, b) = (self.to_px(x, self.y_bounds.1), self.to_px(x, self.y_bounds.0));
+        self.image.line(a, b, c, 1);
+        self
+    }
+
+    /// Connect consecutive points.
+    pub fn series(&mut self, points: &[(f64, f64)], c: Rgb, thick: i64) -> &mut Self {
+        for pair in points.windows(2) {
+            let (a, b) = (self.to_px(pair[0].0, pair[0].1), self.to_px(pair[1].0, pair[1].1));
+            self.image.line(a, b, c, thick);
+        }
+        self
+    }
+
+    /// High-low wick plus a filled open-close body `half_width` pixels
+    /// either side of the wick.
+    pub fn candle(&mut self, x: f64, open: f64, high: f64, low: f64, close: f64, half_width: i64) -> &mut Self {
+        let c = if close >= open { UP } else { DOWN };
+        let (wick_top, wick_bottom) = (self.to_px(x, high), self.to_px(x, low));
+        self.image.line(wick_top, wick_bottom, c, 1);
+        let (top, bottom) = (self.to_px(x, open.max(close)).1, self.to_px(x, open.min(close)).1);
+        let cx = wick_top.0;
+        for y in top..=bottom {
+            self.image.line((cx - half_width, y), (cx + half_width, y), c, 1);
+        }
+        self
+    }
+
+    pub fn finish(self) -> RgbImage {
+        self.image
+    }
+}
+
+/// Render closes with SMA-10/SMA-50 overlays into a `width`×`height` PNG.
+/// The close line is green when the period ended up, red when down.
+pub fn render_chart_png(
+    stock_data: &StockData,
+    analysis: &StockAnalysis,
+    width: usize,
+    height: usize,
+) -> Option<Vec<u8>> {
+    let closes = &stock_data.closes;
+    let n = closes.len();
+    if n < 2 {
+        return None;
+    }
+    // Overlays are aligned so overlay[k] belongs to bar k + period - 1
+    let overlay = |vals: &[f64], period: usize| -> Vec<(f64, f64)> {
+        vals.iter().enumerate()
+            .map(|(k, &v)| (k + period - 1, v))
+            .filter(|(i, _)| *i < n)
+            .map(|(i, v)| (i as f64, v))
+            .collect()
+    };
+    let sma10 = overlay(&analysis.sma10_values, 10);
+    let sma50 = overlay(&analysis.sma50_values, 50);
+    let line: Vec<(f64, f64)> = closes.iter().enumerate().map(|(i, &c)| (i as f64, c)).collect();
+
+    let all = closes.iter().copied().chain(sma10.iter().map(|p| p.1)).chain(sma50.iter().map(|p| p.1));
+    let (lo, hi) = all.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| (lo.min(v), hi.max(v)));
+
+    let color = if closes[n - 1] >= closes[0] { UP } else { DOWN };
+    let mut plot = Plot::new(width, height, (0.0, (n - 1) as f64), (lo, hi), MARGIN)?;
+    plot.grid(5, GRID)
+        .series(&sma50, SMA50, 1)
+        .series(&sma10, SMA10, 1)
+        .series(&line, color, 2);
+    Some(plot.finish().to_png())
+}
+
+// ── PNG encoding ───────────────────────────────────────────────
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffffu32;
+    for &b in bytes {
+        crc ^= b as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xedb8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &x in bytes {
+        a = (a + x as u32) % 65_521;
+        b = (b + a) % 65_521;
+    }
+    (b << 16) | a
+}
+
+fn chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    let crc = crc32(&out[start..]);
+    out.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// 8-bit RGB PNG with uncompressed (stored) deflate blocks — larger than a
+/// compressed file, but charts this size stay well under typical limits.
+fn encode_png(width: usize, height: usize, rgb: &[u8]) -> Vec<u8> {
+    let mut raw = Vec::with_capacity(height * (width * 3 + 1));
+    for row in rgb.chunks(width * 3) {
+        raw.push(0); // filter: none
+        raw.extend_from_slice(row);
+    }
+
+    let mut zlib = vec![0x78, 0x01];
+    let mut blocks = raw.chunks(65_535).peekable();
+    while let Some(block) = blocks.next() {
+        zlib.push(u8::from(blocks.peek().is_none()));
+        let len = block.len() as u16;
+        zlib.extend_from_slice(&len.to_le_bytes());
+        zlib.extend_from_slice(&(!len).to_le_bytes());
+        zlib.extend_from_slice(block);
+    }
+    zlib.extend_from_slice(&adler32(&raw).to_be_bytes());
+
+    let mut ihdr = Vec::with_capacity(13);
+    ihdr.extend_from_slice(&(width as u32).to_be_bytes());
+    ihdr.extend_from_slice(&(height as u32).to_be_bytes());
+    ihdr.extend_from_slice(&[8, 2, 0, 0, 0]); // 8-bit, RGB, deflate, no filter, no interlace
+
+    let mut png = vec![0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+    chunk(&mut png, b"IHDR", &ihdr);
+    chunk(&mut png, b"IDAT", &zlib);
+    chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lib::analysis::analyze_stock;
+    use crate::lib::forecast::Forecaster;
+
+    #[test]
+    fn test_checksums() {
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    }
+
+    #[test]
+    fn test_render_png() {
+        let mut sd = StockData::new();
+        for i in 0..60 {
+            let p = 100.0 + (i as f64 / 5.0).sin() * 10.0;
+            sd.add_point(i * 86_400, p, p + 1.0, p - 1.0, p, 1_000);
+        }
+        let analysis = analyze_stock(&sd, "TEST", Forecaster::Linear);
+        let png = render_chart_png(&sd, &analysis, 320, 160).unwrap();
+        assert_eq!(&png[..8], &[0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a]);
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!(&png[png.len() - 8..png.len() - 4], b"IEND");
+        // Raw image data plus per-row filter bytes and deflate framing
+        assert!(png.len() > 320 * 160 * 3);
+    }
+}
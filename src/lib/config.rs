@@ -1,4 +1,8 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
+
+use crate::lib::forecast::Forecaster;
+use crate::lib::timezone::DisplayZone;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct StockConfig {
@@ -6,3 +10,443 @@ pub struct StockConfig {
     pub analysis_period_days: i64,
 }
 
+/// Sector and industry of a symbol as the provider classifies it. Funds,
+/// indices and crypto have neither.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct SymbolProfile {
+    #[serde(default)]
+    pub sector: Option<String>,
+    #[serde(default)]
+    pub industry: Option<String>,
+    /// What the symbol is, upper case: `EQUITY`, `ETF`, `INDEX`, ...
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    /// A fund's yearly costs as a fraction of its assets.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expense_ratio: Option<f64>,
+    /// A fund's assets under management, in dollars.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub net_assets: Option<f64>,
+}
+
+/// A user's display alias and free-text note for a symbol.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SymbolNote {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+impl SymbolNote {
+    pub fn is_empty(&self) -> bool {
+        self.alias.is_none() && self.note.is_none()
+    }
+}
+
+/// Display preferences, persisted alongside the watchlist.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UiSettings {
+    /// Linear text panels instead of charts, for screen readers.
+    #[serde(default)]
+    pub plain: bool,
+    /// Tiles per page in the main view, as rows × columns.
+    #[serde(default = "default_grid")]
+    pub grid: GridSize,
+    /// How the detail chart is drawn.
+    #[serde(default)]
+    pub graphics: GraphicsMode,
+    #[serde(default)]
+    pub theme: ThemeSettings,
+    /// hjkl, gg/G and count prefixes in the main view.
+    #[serde(default)]
+    pub vim: bool,
+    /// Key remaps, pressed key to the key it acts as, e.g. `{"J": "Down"}`.
+    #[serde(default)]
+    pub keys: BTreeMap<String, String>,
+    /// Zone dates and times are shown in: `local`, `exchange`, `utc` or an
+    /// offset such as `+05:30`.
+    #[serde(default = "default_timezone")]
+    pub timezone: String,
+    /// Prices adjusted for splits and dividends rather than as traded.
+    #[serde(default = "default_true")]
+    pub adjusted: bool,
+    /// Forecasts tilted by the sentiment of the symbol's news headlines.
+    #[serde(default)]
+    pub sentiment_forecast: bool,
+}
+
+fn default_timezone() -> String {
+    "local".into()
+}
+
+impl UiSettings {
+    /// The configured zone, or the local one when it cannot be read.
+    pub fn display_zone(&self) -> DisplayZone {
+        DisplayZone::parse(&self.timezone).unwrap_or_default()
+    }
+}
+
+impl Default for UiSettings {
+    fn default() -> Self {
+        Self {
+            plain: false,
+            grid: default_grid(),
+            graphics: GraphicsMode::default(),
+            theme: ThemeSettings::default(),
+            vim: false,
+            keys: BTreeMap::new(),
+            timezone: default_timezone(),
+            adjusted: true,
+            sentiment_forecast: false,
+        }
+    }
+}
+
+/// Colour scheme: a built-in preset plus per-colour overrides keyed by
+/// theme field name, e.g. `{"gain": "#00ff88"}`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ThemeSettings {
+    #[serde(default = "default_theme")]
+    pub preset: String,
+    #[serde(default)]
+    pub colors: BTreeMap<String, String>,
+}
+
+fn default_theme() -> String {
+    "default".into()
+}
+
+impl Default for ThemeSettings {
+    fn default() -> Self {
+        Self { preset: default_theme(), colors: BTreeMap::new() }
+    }
+}
+
+/// Chart backend: braille characters, or a raster image over a terminal
+/// graphics protocol.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphicsMode {
+    /// Raster when the terminal looks capable, braille otherwise.
+    #[default]
+    Auto,
+    Braille,
+    Kitty,
+    Sixel,
+}
+
+impl GraphicsMode {
+    pub fn parse(name: &str) -> Option<GraphicsMode> {
+        match name.to_ascii_lowercase().as_str() {
+            "auto" => Some(GraphicsMode::Auto),
+            "braille" => Some(GraphicsMode::Braille),
+            "kitty" => Some(GraphicsMode::Kitty),
+            "sixel" => Some(GraphicsMode::Sixel),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct GridSize {
+    pub rows: u16,
+    pub cols: u16,
+}
+
+/// Layouts cycled with 'L' in the main view.
+pub const GRID_PRESETS: [GridSize; 5] = [
+    GridSize { rows: 2, cols: 2 },
+    GridSize { rows: 3, cols: 3 },
+    GridSize { rows: 1, cols: 4 },
+    GridSize { rows: 2, cols: 3 },
+    GridSize { rows: 1, cols: 1 },
+];
+
+fn default_grid() -> GridSize {
+    GRID_PRESETS[0]
+}
+
+impl GridSize {
+    /// Smallest terminal that fits one tile per cell.
+    pub const TILE_MIN: (u16, u16) = (50, 16);
+
+    pub fn per_page(&self) -> usize {
+        self.rows as usize * self.cols as usize
+    }
+
+    pub fn min_terminal(&self) -> (u16, u16) {
+        // Plus three rows for the page title and the bottom bars
+        (self.cols * Self::TILE_MIN.0, self.rows * Self::TILE_MIN.1 + 3)
+    }
+
+    /// Parse `ROWSxCOLS`, e.g. `3x3`.
+    pub fn parse(spec: &str) -> Option<GridSize> {
+        let (r, c) = spec.split_once(['x', 'X'])?;
+        let (rows, cols) = (r.trim().parse().ok()?, c.trim().parse().ok()?);
+        (rows > 0 && cols > 0 && rows <= 6 && cols <= 6).then_some(GridSize { rows, cols })
+    }
+
+    /// The preset after this one, or the first if this is a custom size.
+    pub fn next_preset(&self) -> GridSize {
+        let i = GRID_PRESETS.iter().position(|g| g == self).map_or(0, |i| i + 1);
+        GRID_PRESETS[i % GRID_PRESETS.len()]
+    }
+}
+
+impl std::fmt::Display for GridSize {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}x{}", self.rows, self.cols)
+    }
+}
+
+
+/// Where the TUI was left, restored on the next start.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SessionState {
+    /// Symbol selected on exit.
+    #[serde(default)]
+    pub selected: Option<String>,
+    /// Time range label (e.g. `1Y`) by symbol, for symbols not on the default range.
+    #[serde(default)]
+    pub time_ranges: BTreeMap<String, String>,
+    /// Main view layout: `tiles`, `heatmap`, `table` or `sectors`.
+    #[serde(default)]
+    pub main_mode: Option<String>,
+    /// Main view order, e.g. `change`.
+    #[serde(default)]
+    pub sort: Option<String>,
+    /// Whether the selected symbol's detail view was open.
+    #[serde(default)]
+    pub detail: bool,
+}
+
+/// Background re-fetching of the quotes already loaded.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub struct RefreshSettings {
+    /// Minutes between refreshes; 0 turns it off.
+    #[serde(default = "default_refresh_minutes")]
+    pub interval_minutes: u64,
+    /// Only refresh symbols whose market is in its regular session, when
+    /// prices move.
+    #[serde(default = "default_true")]
+    pub market_hours_only: bool,
+    /// Stream live prices into loaded quotes between refreshes.
+    #[serde(default)]
+    pub stream: bool,
+}
+
+fn default_refresh_minutes() -> u64 {
+    5
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for RefreshSettings {
+    fn default() -> Self {
+        Self { interval_minutes: default_refresh_minutes(), market_hours_only: true, stream: false }
+    }
+}
+
+/// Where market data comes from. Providers are named `yahoo`,
+/// `alphavantage`, `finnhub` or `stooq`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProviderSettings {
+    /// Provider for watchlists without one of their own.
+    #[serde(default = "default_provider")]
+    pub default: String,
+    /// Provider by watchlist name.
+    #[serde(default)]
+    pub watchlists: BTreeMap<String, String>,
+    /// Providers to try in order when the watchlist's one fails.
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub fallback: Vec<String>,
+    /// API keys by provider name, for providers that need one.
+    #[serde(default)]
+    pub api_keys: BTreeMap<String, String>,
+    #[serde(default)]
+    pub retry: RetrySettings,
+    /// Requests per minute by provider name, replacing the built-in limit;
+    /// 0 for none.
+    #[serde(default)]
+    pub rate_limits: BTreeMap<String, u32>,
+    /// Requests each provider may have open at once; the rest wait.
+    #[serde(default = "default_max_concurrent")]
+    pub max_concurrent: usize,
+}
+
+fn default_max_concurrent() -> usize {
+    4
+}
+
+/// How often and how patiently failed provider requests are retried.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct RetrySettings {
+    /// Tries per request, the first included.
+    pub max_attempts: u32,
+    /// Pause before the first retry; it doubles for each one after.
+    pub base_delay_ms: u64,
+    pub max_delay_ms: u64,
+}
+
+impl Default for RetrySettings {
+    fn default() -> Self {
+        Self { max_attempts: 4, base_delay_ms: 500, max_delay_ms: 8_000 }
+    }
+}
+
+fn default_provider() -> String {
+    "yahoo".into()
+}
+
+impl Default for ProviderSettings {
+    fn default() -> Self {
+        Self {
+            default: default_provider(),
+            watchlists: BTreeMap::new(),
+            fallback: Vec::new(),
+            api_keys: BTreeMap::new(),
+            retry: RetrySettings::default(),
+            rate_limits: BTreeMap::new(),
+            max_concurrent: default_max_concurrent(),
+        }
+    }
+}
+
+/// A list of names, or a single name as `fallback` used to be; empty
+/// names (the TOML form of none) are dropped.
+fn one_or_many<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(Option<String>),
+        Many(Vec<String>),
+    }
+    let names = match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(name) => name.into_iter().collect(),
+        OneOrMany::Many(names) => names,
+    };
+    Ok(names.into_iter().filter(|n| !n.is_empty()).collect())
+}
+
+impl ProviderSettings {
+    /// Name of the provider watchlist `name` uses.
+    pub fn for_watchlist(&self, name: &str) -> &str {
+        self.watchlists.get(name).unwrap_or(&self.default)
+    }
+}
+
+/// The account strategies are backtested with, and strategies of the
+/// user's own.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct StrategySettings {
+    /// Cash at the start.
+    #[serde(default = "default_capital")]
+    pub capital: f64,
+    /// Share of the cash each entry puts into the position, in percent.
+    #[serde(default = "default_position_pct")]
+    pub position_pct: f64,
+    /// Flat fee per fill.
+    #[serde(default)]
+    pub commission: f64,
+    /// Fills are this much worse than the open, in percent.
+    #[serde(default = "default_slippage_pct")]
+    pub slippage_pct: f64,
+    /// Strategies by name, traded alongside the built-in ones.
+    #[serde(default)]
+    pub definitions: BTreeMap<String, StrategyDefinition>,
+}
+
+/// A strategy written out in words, e.g. `buy = "EMA20 crosses above
+/// SMA50"`, `sell = "RSI14 > 75"`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct StrategyDefinition {
+    pub buy: String,
+    pub sell: String,
+}
+
+fn default_capital() -> f64 {
+    10_000.0
+}
+
+fn default_position_pct() -> f64 {
+    100.0
+}
+
+fn default_slippage_pct() -> f64 {
+    0.05
+}
+
+impl Default for StrategySettings {
+    fn default() -> Self {
+        Self {
+            capital: default_capital(),
+            position_pct: default_position_pct(),
+            commission: 0.0,
+            slippage_pct: default_slippage_pct(),
+            definitions: BTreeMap::new(),
+        }
+    }
+}
+
+/// When the portfolio counts as concentrated, and what its risk is
+/// measured against.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PortfolioSettings {
+    /// Share of the market value one symbol may hold, in percent.
+    #[serde(default = "default_symbol_limit_pct")]
+    pub symbol_limit_pct: f64,
+    /// Share of the market value one sector may hold, in percent.
+    #[serde(default = "default_sector_limit_pct")]
+    pub sector_limit_pct: f64,
+    /// Symbol the portfolio's beta is taken against.
+    #[serde(default = "default_benchmark")]
+    pub benchmark: String,
+    /// Yearly return of cash, in percent, for the Sharpe ratio.
+    #[serde(default)]
+    pub risk_free_pct: f64,
+}
+
+fn default_symbol_limit_pct() -> f64 {
+    25.0
+}
+
+fn default_sector_limit_pct() -> f64 {
+    40.0
+}
+
+fn default_benchmark() -> String {
+    "SPY".into()
+}
+
+impl Default for PortfolioSettings {
+    fn default() -> Self {
+        Self {
+            symbol_limit_pct: default_symbol_limit_pct(),
+            sector_limit_pct: default_sector_limit_pct(),
+            benchmark: default_benchmark(),
+            risk_free_pct: 0.0,
+        }
+    }
+}
+
+/// Which model forecasts each symbol.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ForecastSettings {
+    /// Model for symbols without one of their own.
+    #[serde(default)]
+    pub default: Forecaster,
+    /// Model by symbol.
+    #[serde(default)]
+    pub symbols: BTreeMap<String, Forecaster>,
+}
+
+impl ForecastSettings {
+    /// The model `symbol` is forecast with.
+    pub fn for_symbol(&self, symbol: &str) -> Forecaster {
+        self.symbols.get(symbol).copied().unwrap_or(self.default)
+    }
+}
@@ -0,0 +1,145 @@
+//! Dividend history: what a symbol has paid, its trailing yield, and when
+//! its next payment is likely to go ex. Providers give ex-dates and
+//! amounts only, so the next ex-date is estimated from the usual gap
+//! between payments.
+
+use crate::lib::stock_data::{CorporateAction, StockData};
+
+const DAY: i64 = 86_400;
+const YEAR: i64 = 365 * DAY;
+
+/// A cash dividend per share, dated by its ex-date.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Dividend {
+    pub ex_date: i64,
+    pub amount: f64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Frequency {
+    Monthly,
+    Quarterly,
+    SemiAnnual,
+    Annual,
+}
+
+impl Frequency {
+    /// The schedule whose period is closest to `gap` seconds.
+    fn of_gap(gap: i64) -> Self {
+        match gap / DAY {
+            ..45 => Frequency::Monthly,
+            45..135 => Frequency::Quarterly,
+            135..270 => Frequency::SemiAnnual,
+            _ => Frequency::Annual,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Frequency::Monthly => "Monthly",
+            Frequency::Quarterly => "Quarterly",
+            Frequency::SemiAnnual => "Semi-annual",
+            Frequency::Annual => "Annual",
+        }
+    }
+}
+
+/// What a symbol's dividend history comes to at a point in time.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DividendSummary {
+    /// Dividends gone ex in the last year, per share.
+    pub trailing_amount: f64,
+    /// `trailing_amount` as a percentage of the price.
+    pub trailing_yield: f64,
+    pub last: Dividend,
+    /// `None` with a single payment to go on.
+    pub frequency: Option<Frequency>,
+    /// Estimated next ex-date: the last one plus the usual gap, moved on
+    /// by further gaps if that has passed.
+    pub next_ex_date: Option<i64>,
+}
+
+impl DividendSummary {
+    /// Whole days from `now` to the estimated next ex-date.
+    pub fn days_to_ex(&self, now: i64) -> Option<i64> {
+        self.next_ex_date.map(|t| (t - now).max(0) / DAY)
+    }
+}
+
+/// The dividends in `history` and `data`'s corporate actions together,
+/// oldest first, each ex-date once.
+pub fn merge(history: &[Dividend], data: &StockData) -> Vec<Dividend> {
+    let mut all: Vec<Dividend> = history.to_vec();
+    for action in &data.actions {
+        if let CorporateAction::Dividend { timestamp, amount } = *action
+            && !all.iter().any(|d| (d.ex_date - timestamp).abs() < DAY)
+        {
+            all.push(Dividend { ex_date: timestamp, amount });
+        }
+    }
+    all.sort_by_key(|d| d.ex_date);
+    all
+}
+
+/// Sum up `history` (oldest first) against `price` as of `now`; `None`
+/// when nothing has been paid.
+pub fn summarize(history: &[Dividend], price: f64, now: i64) -> Option<DividendSummary> {
+    let last = *history.iter().rfind(|d| d.ex_date <= now)?;
+    let trailing_amount: f64 = history.iter()
+        .filter(|d| d.ex_date > now - YEAR && d.ex_date <= now)
+        .map(|d| d.amount)
+        .sum();
+    let trailing_yield = if price > 0.0 { trailing_amount / price * 100.0 } else { 0.0 };
+
+    let mut gaps: Vec<i64> = history.windows(2).map(|w| w[1].ex_date - w[0].ex_date).filter(|&g| g > 0).collect();
+    gaps.sort_unstable();
+    let gap = gaps.get(gaps.len() / 2).copied();
+    let next_ex_date = gap.map(|gap| {
+        let mut next = last.ex_date + gap;
+        while next < now {
+            next += gap;
+        }
+        next
+    });
+    Some(DividendSummary {
+        trailing_amount,
+        trailing_yield,
+        last,
+        frequency: gap.map(Frequency::of_gap),
+        next_ex_date,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quarterly_payer() {
+        // 0.25 a quarter for two years, the last one 30 days ago
+        let now = 1_760_000_000;
+        let history: Vec<Dividend> = (0..8).rev()
+            .map(|q| Dividend { ex_date: now - 30 * DAY - q * 91 * DAY, amount: 0.25 })
+            .collect();
+        let summary = summarize(&history, 50.0, now).unwrap();
+        assert_eq!(summary.trailing_amount, 1.0);
+        assert!((summary.trailing_yield - 2.0).abs() < 1e-9);
+        assert_eq!(summary.frequency, Some(Frequency::Quarterly));
+        assert_eq!(summary.days_to_ex(now), Some(61));
+        assert_eq!(summary.last.ex_date, now - 30 * DAY);
+
+        assert!(summarize(&[], 50.0, now).is_none());
+        let once = summarize(&history[..1], 50.0, now).unwrap();
+        assert_eq!((once.trailing_amount, once.next_ex_date), (0.0, None));
+    }
+
+    #[test]
+    fn test_merge_takes_dividends_from_bars() {
+        let mut data = StockData::new();
+        data.add_action(CorporateAction::Dividend { timestamp: 10 * DAY, amount: 0.5 });
+        data.add_action(CorporateAction::Dividend { timestamp: 100 * DAY + 3600, amount: 0.5 });
+        data.add_action(CorporateAction::Split { timestamp: 50 * DAY, ratio: 2.0 });
+        let merged = merge(&[Dividend { ex_date: 100 * DAY, amount: 0.5 }], &data);
+        assert_eq!(merged.iter().map(|d| d.ex_date).collect::<Vec<_>>(), vec![10 * DAY, 100 * DAY]);
+    }
+}
@@ -0,0 +1,135 @@
+//! Company filings from SEC EDGAR: the annual (10-K), quarterly (10-Q)
+//! and current (8-K) reports of US-listed companies. EDGAR knows companies
+//! by CIK number, so tickers are looked up in its ticker list first; that
+//! list is fetched once per run.
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use chrono::NaiveDate;
+
+use crate::lib::error::AppError;
+
+const TICKERS_URL: &str = "https://www.sec.gov/files/company_tickers.json";
+const SUBMISSIONS_URL: &str = "https://data.sec.gov/submissions";
+const ARCHIVES_URL: &str = "https://www.sec.gov/Archives/edgar/data";
+
+/// The SEC turns away requests that do not say who is making them.
+const USER_AGENT: &str = concat!("bstock/", env!("CARGO_PKG_VERSION"), " (+https://github.com/Not-Buddy/bstock)");
+
+/// Forms listed, amendments included.
+const FORMS: &[&str] = &["10-K", "10-Q", "8-K"];
+
+/// Filings kept per company.
+const MAX_FILINGS: usize = 30;
+
+/// CIK numbers by upper-case ticker.
+static TICKERS: OnceLock<HashMap<String, u64>> = OnceLock::new();
+
+/// A report a company filed with the SEC.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Filing {
+    /// Form type, e.g. "10-K" or "8-K/A".
+    pub form: String,
+    pub filed: NaiveDate,
+    /// What the document is, as the filer put it; may be empty.
+    pub description: String,
+    /// The filing's main document.
+    pub link: String,
+}
+
+fn client() -> Result<reqwest::Client, AppError> {
+    reqwest::Client::builder()
+        .user_agent(USER_AGENT)
+        .build()
+        .map_err(|e| AppError::ApiError(format!("Connector: {e}")))
+}
+
+async fn get_json(client: &reqwest::Client, url: &str, what: &str) -> Result<serde_json::Value, AppError> {
+    client
+        .get(url)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| AppError::from_request(what, e))?
+        .json()
+        .await
+        .map_err(|e| AppError::ApiError(format!("Parse {what}: {e}")))
+}
+
+/// The most recent 10-K, 10-Q and 8-K filings of `symbol`, newest first.
+pub async fn fetch_filings(symbol: &str) -> Result<Vec<Filing>, AppError> {
+    let client = client()?;
+    let tickers = match TICKERS.get() {
+        Some(tickers) => tickers,
+        None => {
+            let json = get_json(&client, TICKERS_URL, "EDGAR tickers").await?;
+            TICKERS.get_or_init(|| parse_tickers(&json))
+        }
+    };
+    let cik = *tickers.get(&symbol.to_ascii_uppercase())
+        .ok_or_else(|| AppError::ApiError(format!("{symbol} is not in EDGAR")))?;
+    let json = get_json(&client, &format!("{SUBMISSIONS_URL}/CIK{cik:010}.json"), &format!("{symbol} filings")).await?;
+    Ok(parse_filings(cik, &json))
+}
+
+/// `company_tickers.json`: an object of `{"cik_str", "ticker", "title"}`
+/// entries keyed by row number.
+fn parse_tickers(json: &serde_json::Value) -> HashMap<String, u64> {
+    json.as_object().into_iter().flat_map(|rows| rows.values())
+        .filter_map(|row| Some((row["ticker"].as_str()?.to_ascii_uppercase(), row["cik_str"].as_u64()?)))
+        .collect()
+}
+
+/// The listed forms among a submissions file's recent filings, which come
+/// as parallel arrays, newest first.
+fn parse_filings(cik: u64, json: &serde_json::Value) -> Vec<Filing> {
+    let recent = &json["filings"]["recent"];
+    let column = |name: &str| recent[name].as_array().cloned().unwrap_or_default();
+    let (forms, dates, accessions, documents, descriptions) = (
+        column("form"), column("filingDate"), column("accessionNumber"), column("primaryDocument"), column("primaryDocDescription"),
+    );
+    let text = |values: &[serde_json::Value], i: usize| values.get(i).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+    (0..forms.len())
+        .filter_map(|i| {
+            let form = text(&forms, i);
+            if !FORMS.contains(&form.trim_end_matches("/A")) {
+                return None;
+            }
+            let filed = NaiveDate::parse_from_str(&text(&dates, i), "%Y-%m-%d").ok()?;
+            let accession = text(&accessions, i).replace('-', "");
+            let document = text(&documents, i);
+            let link = if document.is_empty() {
+                format!("{ARCHIVES_URL}/{cik}/{accession}/")
+            } else {
+                format!("{ARCHIVES_URL}/{cik}/{accession}/{document}")
+            };
+            Some(Filing { form, filed, description: text(&descriptions, i), link })
+        })
+        .take(MAX_FILINGS)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filings_are_the_listed_forms_with_links() {
+        let json = serde_json::json!({"filings": {"recent": {
+            "form": ["8-K", "4", "10-Q", "10-K/A"],
+            "filingDate": ["2024-11-01", "2024-10-30", "2024-08-02", "2024-03-15"],
+            "accessionNumber": ["0000320193-24-000120", "0000320193-24-000119", "0000320193-24-000081", "0000320193-24-000030"],
+            "primaryDocument": ["aapl-20241031.htm", "xslF345X05/wk-form4.xml", "aapl-20240629.htm", ""],
+            "primaryDocDescription": ["8-K", "FORM 4", "10-Q", ""],
+        }}});
+        let filings = parse_filings(320193, &json);
+        assert_eq!(filings.iter().map(|f| f.form.as_str()).collect::<Vec<_>>(), vec!["8-K", "10-Q", "10-K/A"]);
+        assert_eq!(filings[0].filed, NaiveDate::from_ymd_opt(2024, 11, 1).unwrap());
+        assert_eq!(filings[0].link, "https://www.sec.gov/Archives/edgar/data/320193/000032019324000120/aapl-20241031.htm");
+        assert!(filings[2].link.ends_with("/000032019324000030/"));
+
+        let tickers = parse_tickers(&serde_json::json!({"0": {"cik_str": 320193, "ticker": "AAPL", "title": "Apple Inc."}}));
+        assert_eq!(tickers.get("AAPL"), Some(&320193));
+    }
+}
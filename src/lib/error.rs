@@ -5,15 +5,78 @@ pub enum AppError {
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
-    #[error("Failed to parse config file")]
+    #[error("Failed to parse config file: {0}")]
     ConfigParseError(#[from] serde_json::Error),
 
-    #[error("Yahoo API error: {0}")]
+    #[error("Failed to parse TOML config: {0}")]
+    Toml(String),
+
+    #[error("Unsupported snapshot version {0}")]
+    SnapshotVersion(u32),
+
+
+    #[error("Watchlist error: {0}")]
+    Watchlist(String),
+
+    #[error("Profile error: {0}")]
+    Profile(String),
+
+    #[error("Cannot read data file {0}")]
+    DataFile(String),
+
+    #[error("CSV import error: {0}")]
+    Import(String),
+
+    #[error("Sync error: {0}")]
+    Sync(String),
+
+    #[error("Credential store error: {0}")]
+    Secrets(String),
+
+    #[error("Provider error: {0}")]
+    Provider(String),
+
+    #[error("API error: {0}")]
     ApiError(String),
+
+    /// A failure worth retrying: rate limiting, a timeout, a dropped
+    /// connection or a server error.
+    #[error("API error (temporary): {0}")]
+    Unavailable(String),
 }
 
 impl From<yahoo_finance_api::YahooError> for AppError {
     fn from(e: yahoo_finance_api::YahooError) -> Self {
-        AppError::ApiError(e.to_string())
+        AppError::ApiError(format!("Yahoo Finance: {}", e))
+    }
+}
+
+impl AppError {
+    /// `error` from an HTTP request, prefixed with `context`; temporary
+    /// failures become [`AppError::Unavailable`].
+    pub fn from_request(context: impl std::fmt::Display, error: reqwest::Error) -> Self {
+        let temporary = error.is_timeout()
+            || error.is_connect()
+            || error.status().is_some_and(|s| s.as_u16() == 429 || s.is_server_error());
+        let message = format!("{}: {}", context, error);
+        if temporary { AppError::Unavailable(message) } else { AppError::ApiError(message) }
+    }
+
+    /// Like [`from_request`](AppError::from_request) for the Yahoo
+    /// connector, which reports HTTP failures as a status string.
+    pub fn from_yahoo(context: impl std::fmt::Display, error: yahoo_finance_api::YahooError) -> Self {
+        use yahoo_finance_api::YahooError;
+        match error {
+            YahooError::ConnectionFailed(e) => AppError::from_request(context, e),
+            YahooError::FetchFailed(status) => {
+                let message = format!("{}: fetching from Yahoo Finance failed ({})", context, status);
+                if status.starts_with("429") || status.starts_with('5') {
+                    AppError::Unavailable(message)
+                } else {
+                    AppError::ApiError(message)
+                }
+            }
+            e => AppError::ApiError(format!("{}: {}", context, e)),
+        }
     }
 }
@@ -0,0 +1,69 @@
+use crate::lib::{analysis::StockAnalysis, error::AppError, stock_data::StockData, timezone::DisplayZone};
+use std::fs;
+use std::path::Path;
+
+/// `overlay[k]` belongs to bar `k + period - 1`, as on the chart; bars
+/// before the first full window have no value.
+fn overlay_at(overlay: &[f64], period: usize, bar: usize) -> Option<f64> {
+    bar.checked_sub(period - 1).and_then(|k| overlay.get(k)).copied()
+}
+
+fn cell(value: Option<f64>) -> String {
+    value.map(|v| format!("{:.4}", v)).unwrap_or_default()
+}
+
+/// One CSV row per bar: date (RFC 3339, in `zone`) and Unix timestamp, OHLCV, and the
+/// SMA-10, SMA-50, EMA-20 and RSI-14 the detail view shows. Indicator cells
+/// are empty until enough bars exist to compute them.
+pub fn to_csv(data: &StockData, analysis: &StockAnalysis, zone: DisplayZone) -> String {
+    let rsi = data.rsi(14).map(|r| r.to_vec()).unwrap_or_default();
+    let mut out = String::from("date,timestamp,open,high,low,close,volume,sma10,sma50,ema20,rsi14\n");
+    for i in 0..data.len() {
+        let date = zone.bar_time(&analysis.symbol, data.timestamps[i])
+            .map(|dt| dt.to_rfc3339())
+            .unwrap_or_default();
+        // rsi[k] corresponds to closes[k + 14]
+        let rsi14 = i.checked_sub(14).and_then(|k| rsi.get(k)).copied();
+        out.push_str(&format!(
+            "{},{},{},{},{},{},{},{},{},{},{}\n",
+            date, data.timestamps[i],
+            data.opens[i], data.highs[i], data.lows[i], data.closes[i], data.volumes[i],
+            cell(overlay_at(&analysis.sma10_values, 10, i)),
+            cell(overlay_at(&analysis.sma50_values, 50, i)),
+            cell(overlay_at(&analysis.ema20_values, 20, i)),
+            cell(rsi14),
+        ));
+    }
+    out
+}
+
+pub fn write_csv(path: &Path, data: &StockData, analysis: &StockAnalysis, zone: DisplayZone) -> Result<(), AppError> {
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, to_csv(data, analysis, zone))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lib::analysis::analyze_stock;
+    use crate::lib::forecast::Forecaster;
+
+    #[test]
+    fn test_indicators_line_up_with_their_bars() {
+        let mut data = StockData::new();
+        for i in 0..12 {
+            data.add_point(1_700_000_000 + i * 86_400, 1.0, 2.0, 0.5, 10.0 + i as f64, 100);
+        }
+        let analysis = analyze_stock(&data, "T", Forecaster::Linear);
+        let csv = to_csv(&data, &analysis, DisplayZone::Utc);
+        let rows: Vec<Vec<&str>> = csv.lines().skip(1).map(|l| l.split(',').collect()).collect();
+        assert_eq!(rows.len(), 12);
+        assert_eq!(rows[8][7], "");
+        assert_eq!(rows[9][7], format!("{:.4}", analysis.sma10_values[0]));
+        assert_eq!(rows[11][8], "");
+        assert_eq!(rows[0][0], "2023-11-14T22:13:20+00:00");
+    }
+}
@@ -0,0 +1,263 @@
+//! Finnhub (<https://finnhub.io>), keyed by an API token. Its quote
+//! endpoint is real time for US stocks, unlike the delayed bars the other
+//! providers serve; candle access depends on the account's plan.
+
+use chrono::{NaiveDate, Utc};
+
+use crate::data::{trailing_range, Interval, TimeRange};
+use crate::lib::{
+    config::SymbolProfile,
+    error::AppError,
+    insiders::{InsiderAction, InsiderTrade},
+    provider::{AnalystRatings, DataProvider, Fundamentals, ProviderFuture, Quote, SymbolMatch},
+    stock_data::StockData,
+};
+
+const URL: &str = "https://finnhub.io/api/v1";
+
+/// Days added before a range's start so the latest session is still
+/// covered over weekends and holidays; the extra bars are trimmed off.
+const PAD_DAYS: i64 = 4;
+
+pub struct FinnhubProvider {
+    api_key: String,
+    client: reqwest::Client,
+}
+
+impl FinnhubProvider {
+    pub fn new(api_key: String) -> Self {
+        FinnhubProvider { api_key, client: reqwest::Client::new() }
+    }
+
+    async fn get(&self, endpoint: &str, params: &[(&str, String)]) -> Result<serde_json::Value, AppError> {
+        let json: serde_json::Value = self.client
+            .get(format!("{URL}/{endpoint}"))
+            .header("X-Finnhub-Token", &self.api_key)
+            .query(params)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| AppError::from_request(format!("Finnhub {endpoint}"), e))?
+            .json()
+            .await
+            .map_err(|e| AppError::ApiError(format!("Parse Finnhub {endpoint}: {e}")))?;
+        if let Some(message) = json["error"].as_str() {
+            return Err(AppError::ApiError(format!("Finnhub: {message}")));
+        }
+        Ok(json)
+    }
+
+    /// Candles at `time_range`'s resolution from `from` (a Unix timestamp)
+    /// up to now. No candles is not an error.
+    async fn candles(&self, symbol: &str, time_range: TimeRange, from: i64) -> Result<StockData, AppError> {
+        let params = [
+            ("symbol", symbol.to_string()),
+            ("resolution", resolution(time_range).to_string()),
+            ("from", from.max(0).to_string()),
+            ("to", Utc::now().timestamp().to_string()),
+        ];
+        parse_candles(&self.get("stock/candle", &params).await?)
+    }
+
+    /// Candles covering `time_range` up to now, plus `periods - 1` equal
+    /// periods before it.
+    async fn periods(&self, symbol: &str, time_range: TimeRange, periods: i64) -> Result<StockData, AppError> {
+        let now = Utc::now().timestamp();
+        let from = time_range.start_before(now).map_or(0, |start| now - periods * (now - start) - PAD_DAYS * 86_400);
+        let data = self.candles(symbol, time_range, from).await?;
+        if data.is_empty() {
+            return Err(AppError::ApiError(format!("{symbol}: no data from Finnhub")));
+        }
+        Ok(data)
+    }
+}
+
+/// Finnhub resolution for the range's bar size.
+fn resolution(time_range: TimeRange) -> &'static str {
+    match time_range.interval() {
+        Interval::OneMinute => "1",
+        Interval::FiveMinutes => "5",
+        Interval::OneHour => "60",
+        Interval::OneDay => "D",
+        Interval::OneWeek => "W",
+        Interval::OneMonth => "M",
+    }
+}
+
+/// Bars from a `stock/candle` response: parallel `t`/`o`/`h`/`l`/`c`/`v`
+/// arrays, with `s` set to `no_data` when there are none.
+fn parse_candles(json: &serde_json::Value) -> Result<StockData, AppError> {
+    let mut data = StockData::new();
+    match json["s"].as_str() {
+        Some("no_data") => return Ok(data),
+        Some("ok") => {}
+        _ => return Err(AppError::ApiError("Finnhub: unexpected candle response".into())),
+    }
+    let column = |name: &str| -> Vec<f64> {
+        json[name].as_array().into_iter().flatten().map(|v| v.as_f64().unwrap_or(f64::NAN)).collect()
+    };
+    let (t, o, h, l, c, v) = (column("t"), column("o"), column("h"), column("l"), column("c"), column("v"));
+    for (i, &time) in t.iter().enumerate() {
+        let (Some(&o), Some(&h), Some(&l), Some(&c)) = (o.get(i), h.get(i), l.get(i), c.get(i)) else {
+            break;
+        };
+        if [o, h, l, c].iter().any(|p| p.is_nan()) {
+            continue;
+        }
+        let volume = v.get(i).copied().filter(|v| !v.is_nan()).unwrap_or(0.0) as u64;
+        data.add_point(time as i64, o, h, l, c, volume);
+    }
+    Ok(data)
+}
+
+impl DataProvider for FinnhubProvider {
+    fn name(&self) -> &'static str {
+        "Finnhub"
+    }
+
+    fn fetch_history<'a>(&'a self, symbol: &'a str, time_range: TimeRange) -> ProviderFuture<'a, StockData> {
+        Box::pin(async move { Ok(trailing_range(&self.periods(symbol, time_range, 1).await?, time_range)) })
+    }
+
+    fn fetch_history_since<'a>(&'a self, symbol: &'a str, time_range: TimeRange, since: i64) -> ProviderFuture<'a, StockData> {
+        Box::pin(async move { self.candles(symbol, time_range, since).await })
+    }
+
+    fn fetch_doubled_history<'a>(&'a self, symbol: &'a str, time_range: TimeRange) -> ProviderFuture<'a, StockData> {
+        Box::pin(async move {
+            if time_range == TimeRange::All {
+                return Err(AppError::ApiError("no history before All".into()));
+            }
+            let series = self.periods(symbol, time_range, 2).await?;
+            let current = trailing_range(&series, time_range);
+            let (start, end) = (current.timestamps[0], current.timestamps[current.len() - 1]);
+            Ok(series.since(start - (end - start)))
+        })
+    }
+
+    fn fetch_quote<'a>(&'a self, symbol: &'a str) -> ProviderFuture<'a, Quote> {
+        Box::pin(async move {
+            let json = self.get("quote", &[("symbol", symbol.to_string())]).await?;
+            // Unknown symbols get a quote of all zeros
+            let timestamp = json["t"].as_i64().unwrap_or(0);
+            let price = json["c"].as_f64().filter(|_| timestamp > 0)
+                .ok_or_else(|| AppError::ApiError(format!("{symbol}: unknown to Finnhub")))?;
+            // The quote has no volume
+            Ok(Quote { timestamp, price, volume: 0 })
+        })
+    }
+
+    fn search_symbols<'a>(&'a self, query: &'a str) -> ProviderFuture<'a, Vec<SymbolMatch>> {
+        Box::pin(async move {
+            let json = self.get("search", &[("q", query.to_string())]).await?;
+            let field = |m: &serde_json::Value, name: &str| m[name].as_str().unwrap_or_default().to_string();
+            Ok(json["result"].as_array().into_iter().flatten().map(|m| SymbolMatch {
+                symbol: field(m, "symbol"),
+                name: field(m, "description"),
+                kind: field(m, "type"),
+                exchange: String::new(),
+            }).collect())
+        })
+    }
+
+    fn fetch_profile<'a>(&'a self, symbol: &'a str) -> ProviderFuture<'a, SymbolProfile> {
+        Box::pin(async move {
+            let json = self.get("stock/profile2", &[("symbol", symbol.to_string())]).await?;
+            // Finnhub has one broad industry per company, which groups like a sector
+            let sector = json["finnhubIndustry"].as_str().filter(|s| !s.is_empty()).map(str::to_string);
+            Ok(SymbolProfile { sector, ..SymbolProfile::default() })
+        })
+    }
+
+    fn fetch_fundamentals<'a>(&'a self, symbol: &'a str) -> ProviderFuture<'a, Fundamentals> {
+        Box::pin(async move {
+            let params = [("symbol", symbol.to_string()), ("metric", "all".to_string())];
+            let json = self.get("stock/metric", &params).await?;
+            let metric = |name: &str| json["metric"][name].as_f64();
+            // Market cap is in millions and the margin a percentage
+            Ok(Fundamentals {
+                market_cap: metric("marketCapitalization").map(|m| m * 1e6),
+                pe: metric("peTTM").or_else(|| metric("peBasicExclExtraTTM")),
+                forward_pe: None,
+                eps: metric("epsTTM").or_else(|| metric("epsBasicExclExtraItemsTTM")),
+                profit_margin: metric("netProfitMarginTTM").map(|pct| pct / 100.0),
+                debt_to_equity: metric("totalDebt/totalEquityQuarterly"),
+                // Short interest is not in the free metrics
+                ..Fundamentals::default()
+            })
+        })
+    }
+
+    fn fetch_insider_trades<'a>(&'a self, symbol: &'a str) -> ProviderFuture<'a, Vec<InsiderTrade>> {
+        Box::pin(async move {
+            let json = self.get("stock/insider-transactions", &[("symbol", symbol.to_string())]).await?;
+            Ok(parse_insider_trades(&json))
+        })
+    }
+
+    fn fetch_ratings<'a>(&'a self, symbol: &'a str) -> ProviderFuture<'a, AnalystRatings> {
+        Box::pin(async move {
+            let json = self.get("stock/recommendation", &[("symbol", symbol.to_string())]).await?;
+            // Monthly tallies, newest first; price targets need a paid plan
+            let latest = &json[0];
+            let count = |name: &str| latest[name].as_u64().unwrap_or_default() as u32;
+            Ok(AnalystRatings {
+                strong_buy: count("strongBuy"),
+                buy: count("buy"),
+                hold: count("hold"),
+                sell: count("sell"),
+                strong_sell: count("strongSell"),
+                ..AnalystRatings::default()
+            })
+        })
+    }
+}
+
+/// Insider transactions, newest first. Form 4 codes tell open-market
+/// purchases (`P`) and sales (`S`) from everything else.
+fn parse_insider_trades(json: &serde_json::Value) -> Vec<InsiderTrade> {
+    let mut trades: Vec<InsiderTrade> = json["data"].as_array().into_iter().flatten()
+        .filter_map(|row| {
+            let date = NaiveDate::parse_from_str(row["transactionDate"].as_str()?, "%Y-%m-%d").ok()?;
+            let change = row["change"].as_f64().unwrap_or_default();
+            let action = match row["transactionCode"].as_str() {
+                Some("P") => InsiderAction::Buy,
+                Some("S") => InsiderAction::Sell,
+                _ => InsiderAction::Other,
+            };
+            Some(InsiderTrade {
+                insider: row["name"].as_str().unwrap_or_default().to_string(),
+                relation: String::new(),
+                date: date.and_hms_opt(0, 0, 0)?.and_utc().timestamp(),
+                action,
+                shares: change.abs() as u64,
+                value: row["transactionPrice"].as_f64().filter(|&p| p > 0.0).map(|p| p * change.abs()),
+            })
+        })
+        .collect();
+    trades.sort_by_key(|t| std::cmp::Reverse(t.date));
+    trades
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_candles_parse_and_skip_gaps() {
+        let json = serde_json::json!({
+            "s": "ok",
+            "t": [1704153600, 1704240000, 1704326400],
+            "o": [9.0, null, 11.0],
+            "h": [10.0, 12.0, 12.0],
+            "l": [8.0, 9.0, 10.0],
+            "c": [9.5, 11.0, 11.5],
+            "v": [400, 500, 600],
+        });
+        let data = parse_candles(&json).unwrap();
+        assert_eq!(data.timestamps, vec![1704153600, 1704326400]);
+        assert_eq!(data.volumes, vec![400, 600]);
+        assert!(parse_candles(&serde_json::json!({"s": "no_data"})).unwrap().is_empty());
+        assert!(parse_candles(&serde_json::json!({})).is_err());
+    }
+}
@@ -0,0 +1,444 @@
+//! Price forecasts. Each model is a [`Predictor`], found in the registry by
+//! the [`Forecaster`] config names it by: a straight line through the
+//! latest closes; Holt-Winters exponential smoothing, which follows a
+//! changing trend and, on daily bars, the pattern of the trading week; the
+//! level and slope of a Kalman filter; ARIMA(1,1,0); a Monte Carlo of
+//! resampled returns; with the `neural` feature, a GRU network over the
+//! log returns; or the average of all of them.
+
+use serde::{Deserialize, Serialize};
+
+use crate::lib::stock_data::StockData;
+
+/// Bars forecast ahead.
+pub const HORIZON: usize = 5;
+
+/// Bars the linear trend is fitted to.
+const LINEAR_BARS: usize = 20;
+
+/// Trading days in a week, the season of daily bars.
+const WEEK: usize = 5;
+
+/// Smoothing factors tried when fitting Holt-Winters.
+const FACTORS: [f64; 5] = [0.1, 0.3, 0.5, 0.7, 0.9];
+
+/// Price paths the Monte Carlo simulates, and the latest returns it draws
+/// their steps from.
+const PATHS: usize = 1000;
+const MONTE_CARLO_RETURNS: usize = 250;
+
+/// Latest returns the GRU network is trained on, and the fewest it needs.
+#[cfg(feature = "neural")]
+const NEURAL_RETURNS: usize = 250;
+#[cfg(feature = "neural")]
+const MIN_NEURAL_RETURNS: usize = 30;
+
+/// How a symbol's forecast is made.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum Forecaster {
+    /// Least-squares line through the last 20 closes.
+    #[default]
+    Linear,
+    /// Holt-Winters smoothing of level and trend, plus the day of the week
+    /// on daily bars.
+    HoltWinters,
+    /// The Kalman-filtered level carried on at its filtered slope.
+    Kalman,
+    /// Autoregression of the bar-to-bar changes, with drift.
+    Arima,
+    /// Simulated paths stepping by past returns drawn at random.
+    MonteCarlo,
+    /// The mean of every other model.
+    Ensemble,
+    /// A GRU network trained on the log returns. Needs the `neural`
+    /// feature; builds without it forecast with the linear trend.
+    Gru,
+}
+
+impl Forecaster {
+    /// Every model this build has.
+    pub const ALL: &[Forecaster] = &[
+        Forecaster::Linear,
+        Forecaster::HoltWinters,
+        Forecaster::Kalman,
+        Forecaster::Arima,
+        Forecaster::MonteCarlo,
+        Forecaster::Ensemble,
+        #[cfg(feature = "neural")]
+        Forecaster::Gru,
+    ];
+
+    /// The model this name stands for.
+    pub fn predictor(self) -> &'static dyn Predictor {
+        REGISTRY.iter().find(|(f, _)| *f == self).map(|&(_, p)| p).unwrap_or(&LinearTrend)
+    }
+
+    pub fn label(self) -> &'static str {
+        self.predictor().name()
+    }
+
+    /// The model named as in config, e.g. `holt-winters`.
+    pub fn parse(name: &str) -> Option<Forecaster> {
+        serde_json::from_value(serde_json::Value::String(name.to_ascii_lowercase())).ok()
+    }
+}
+
+/// A forecasting model.
+pub trait Predictor: Sync {
+    /// Shown next to the model's predictions.
+    fn name(&self) -> &'static str;
+
+    /// The next [`HORIZON`] closes of `stock_data`, each with its standard
+    /// error; empty when there are too few bars to fit.
+    fn predict(&self, stock_data: &StockData) -> Vec<(f64, f64)>;
+}
+
+/// Every model, by the name config chooses it with.
+const REGISTRY: &[(Forecaster, &dyn Predictor)] = &[
+    (Forecaster::Linear, &LinearTrend),
+    (Forecaster::HoltWinters, &HoltWinters),
+    (Forecaster::Kalman, &KalmanTrend),
+    (Forecaster::Arima, &Arima),
+    (Forecaster::MonteCarlo, &MonteCarlo),
+    (Forecaster::Ensemble, &Ensemble),
+    #[cfg(feature = "neural")]
+    (Forecaster::Gru, &Neural),
+];
+
+/// The next [`HORIZON`] closes of `stock_data` by `forecaster`, each with
+/// its standard error; empty with fewer than two bars. Models that need
+/// more bars than there are fall back to the linear trend.
+pub fn forecast(stock_data: &StockData, forecaster: Forecaster) -> Vec<(f64, f64)> {
+    match forecaster.predictor().predict(stock_data) {
+        forecast if forecast.is_empty() => LinearTrend.predict(stock_data),
+        forecast => forecast,
+    }
+}
+
+struct LinearTrend;
+
+impl Predictor for LinearTrend {
+    fn name(&self) -> &'static str {
+        "linear trend"
+    }
+
+    fn predict(&self, stock_data: &StockData) -> Vec<(f64, f64)> {
+        stock_data.predict_next(LINEAR_BARS)
+    }
+}
+
+struct HoltWinters;
+
+impl Predictor for HoltWinters {
+    fn name(&self) -> &'static str {
+        "Holt-Winters"
+    }
+
+    fn predict(&self, stock_data: &StockData) -> Vec<(f64, f64)> {
+        holt_winters(&stock_data.closes, weekly_season(&stock_data.timestamps), HORIZON)
+    }
+}
+
+/// The last filtered level carried on at its slope. The error is that of
+/// the filter's one-step predictions, growing like a random walk's.
+struct KalmanTrend;
+
+impl Predictor for KalmanTrend {
+    fn name(&self) -> &'static str {
+        "Kalman filter"
+    }
+
+    fn predict(&self, stock_data: &StockData) -> Vec<(f64, f64)> {
+        let Some(states) = stock_data.kalman_trend() else { return Vec::new() };
+        let closes = &stock_data.closes;
+        let Some(&(level, slope)) = states.last() else { return Vec::new() };
+        let sse: f64 = closes[1..].iter().zip(&states).map(|(y, (l, s))| (y - l - s).powi(2)).sum();
+        let sigma = (sse / (closes.len() - 1) as f64).sqrt();
+        (1..=HORIZON).map(|h| (level + h as f64 * slope, sigma * (h as f64).sqrt())).collect()
+    }
+}
+
+/// ARIMA(1,1,0) with drift: each bar's change is a constant plus a share
+/// of the change before it, fitted by least squares.
+struct Arima;
+
+impl Predictor for Arima {
+    fn name(&self) -> &'static str {
+        "ARIMA(1,1,0)"
+    }
+
+    fn predict(&self, stock_data: &StockData) -> Vec<(f64, f64)> {
+        let changes: Vec<f64> = stock_data.closes.windows(2).map(|w| w[1] - w[0]).collect();
+        if changes.len() < 4 {
+            return Vec::new();
+        }
+        let (xs, ys) = (&changes[..changes.len() - 1], &changes[1..]);
+        let n = xs.len() as f64;
+        let (mean_x, mean_y) = (xs.iter().sum::<f64>() / n, ys.iter().sum::<f64>() / n);
+        let sxx: f64 = xs.iter().map(|x| (x - mean_x).powi(2)).sum();
+        let sxy: f64 = xs.iter().zip(ys).map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+        // Keep it stationary; flat changes leave nothing to regress on
+        let phi = if sxx > 0.0 { (sxy / sxx).clamp(-0.99, 0.99) } else { 0.0 };
+        let drift = mean_y - phi * mean_x;
+        let sse: f64 = xs.iter().zip(ys).map(|(x, y)| (y - drift - phi * x).powi(2)).sum();
+        let sigma = (sse / (n - 2.0)).sqrt();
+
+        let (mut price, mut change) = (*stock_data.closes.last().unwrap(), *changes.last().unwrap());
+        // A shock h bars on moves the price by 1 + φ + … + φ^(h-1) of itself
+        let (mut weight, mut variance) = (0.0, 0.0);
+        (0..HORIZON)
+            .map(|_| {
+                change = drift + phi * change;
+                price += change;
+                weight = 1.0 + phi * weight;
+                variance += weight * weight;
+                (price, sigma * variance.sqrt())
+            })
+            .collect()
+    }
+}
+
+/// Paths that step by log returns resampled from the latest ones; the
+/// prediction is their mean and the error their spread. The draws are
+/// seeded so the same bars always give the same forecast.
+struct MonteCarlo;
+
+impl Predictor for MonteCarlo {
+    fn name(&self) -> &'static str {
+        "Monte Carlo"
+    }
+
+    fn predict(&self, stock_data: &StockData) -> Vec<(f64, f64)> {
+        let returns: Vec<f64> = stock_data.closes.windows(2)
+            .filter(|w| w[0] > 0.0 && w[1] > 0.0)
+            .map(|w| (w[1] / w[0]).ln())
+            .collect();
+        let returns = &returns[returns.len().saturating_sub(MONTE_CARLO_RETURNS)..];
+        let Some(&last) = stock_data.closes.last() else { return Vec::new() };
+        if returns.len() < 2 {
+            return Vec::new();
+        }
+        let mut seed = 0x9e37_79b9_7f4a_7c15u64;
+        let mut sums = [(0.0, 0.0); HORIZON];
+        for _ in 0..PATHS {
+            let mut price = last;
+            for sum in sums.iter_mut() {
+                price *= returns[(splitmix(&mut seed) % returns.len() as u64) as usize].exp();
+                sum.0 += price;
+                sum.1 += price * price;
+            }
+        }
+        let paths = PATHS as f64;
+        sums.iter().map(|&(sum, squares)| (sum / paths, (squares / paths - (sum / paths).powi(2)).max(0.0).sqrt())).collect()
+    }
+}
+
+/// The GRU network of [`crate::lib::neural`], trained on the latest log
+/// returns, standardized, then run on past the last one. The error is its
+/// one-step error on those returns, growing like a random walk's.
+#[cfg(feature = "neural")]
+struct Neural;
+
+#[cfg(feature = "neural")]
+impl Predictor for Neural {
+    fn name(&self) -> &'static str {
+        "GRU network"
+    }
+
+    fn predict(&self, stock_data: &StockData) -> Vec<(f64, f64)> {
+        let returns: Vec<f64> = stock_data.closes.windows(2)
+            .filter(|w| w[0] > 0.0 && w[1] > 0.0)
+            .map(|w| (w[1] / w[0]).ln())
+            .collect();
+        let returns = &returns[returns.len().saturating_sub(NEURAL_RETURNS)..];
+        let Some(&last) = stock_data.closes.last() else { return Vec::new() };
+        if returns.len() < MIN_NEURAL_RETURNS {
+            return Vec::new();
+        }
+        let n = returns.len() as f64;
+        let mean = returns.iter().sum::<f64>() / n;
+        let sd = (returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / n).sqrt();
+        if sd == 0.0 {
+            return Vec::new();
+        }
+        let series: Vec<f64> = returns.iter().map(|r| (r - mean) / sd).collect();
+        let (gru, rmse) = crate::lib::neural::Gru::train(&series);
+        let mut price = last;
+        gru.extend(&series, HORIZON)
+            .into_iter()
+            .enumerate()
+            .map(|(h, z)| {
+                price *= (mean + sd * z).exp();
+                (price, price * rmse * sd * ((h + 1) as f64).sqrt())
+            })
+            .collect()
+    }
+}
+
+/// SplitMix64: a small, well-mixed generator, plenty for resampling.
+pub(crate) fn splitmix(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// The mean of every other model's forecast. Its variance is the members'
+/// mean variance plus how far they disagree.
+struct Ensemble;
+
+impl Predictor for Ensemble {
+    fn name(&self) -> &'static str {
+        "ensemble"
+    }
+
+    fn predict(&self, stock_data: &StockData) -> Vec<(f64, f64)> {
+        let members: Vec<Vec<(f64, f64)>> = REGISTRY.iter()
+            .filter(|(f, _)| *f != Forecaster::Ensemble)
+            .map(|(_, p)| p.predict(stock_data))
+            .filter(|f| f.len() == HORIZON)
+            .collect();
+        if members.is_empty() {
+            return Vec::new();
+        }
+        let n = members.len() as f64;
+        (0..HORIZON)
+            .map(|h| {
+                let mean = members.iter().map(|m| m[h].0).sum::<f64>() / n;
+                let spread = members.iter().map(|m| (m[h].0 - mean).powi(2)).sum::<f64>() / n;
+                let variance = members.iter().map(|m| m[h].1.powi(2)).sum::<f64>() / n;
+                (mean, (variance + spread).sqrt())
+            })
+            .collect()
+    }
+}
+
+/// [`WEEK`] for daily bars, going by the usual gap between them; intraday
+/// and longer bars have no weekly season to speak of.
+fn weekly_season(timestamps: &[i64]) -> Option<usize> {
+    let mut gaps: Vec<i64> = timestamps.windows(2).map(|w| w[1] - w[0]).collect();
+    gaps.sort_unstable();
+    let gap = *gaps.get(gaps.len() / 2)?;
+    (18 * 3600..=4 * 86_400).contains(&gap).then_some(WEEK)
+}
+
+/// Forecast `horizon` values of `ys` with additive Holt-Winters smoothing,
+/// seasonal when `season` is given and there are three seasons of data to
+/// learn it from. The smoothing factors are the ones, from a coarse grid,
+/// with the smallest one-step-ahead squared error. Standard errors grow
+/// from the one-step error as for Holt's linear method, leaving out the
+/// seasonal term.
+pub fn holt_winters(ys: &[f64], season: Option<usize>, horizon: usize) -> Vec<(f64, f64)> {
+    if ys.len() < 2 {
+        return Vec::new();
+    }
+    let season = season.filter(|&m| m > 1 && ys.len() >= 3 * m);
+    let gammas: &[f64] = if season.is_some() { &FACTORS } else { &[0.0] };
+    let mut best: Option<(f64, f64, f64, Vec<f64>)> = None;
+    for &alpha in &FACTORS {
+        for &beta in &FACTORS {
+            for &gamma in gammas {
+                let (sse, forecast) = smooth(ys, season, alpha, beta, gamma, horizon);
+                if best.as_ref().is_none_or(|(b, ..)| sse < *b) {
+                    best = Some((sse, alpha, beta, forecast));
+                }
+            }
+        }
+    }
+    let Some((sse, alpha, beta, forecast)) = best else { return Vec::new() };
+    let sigma = (sse / (ys.len() - season.unwrap_or(1)) as f64).sqrt();
+    let mut variance = 0.0;
+    forecast.into_iter().enumerate()
+        .map(|(h, value)| {
+            variance += if h == 0 { 1.0 } else { (alpha * (1.0 + h as f64 * beta)).powi(2) };
+            (value, sigma * variance.sqrt())
+        })
+        .collect()
+}
+
+/// One Holt-Winters pass: the sum of squared one-step errors and the
+/// forecast from the end of `ys`.
+fn smooth(ys: &[f64], season: Option<usize>, alpha: f64, beta: f64, gamma: f64, horizon: usize) -> (f64, Vec<f64>) {
+    let mut sse = 0.0;
+    let Some(m) = season else {
+        let (mut level, mut trend) = (ys[0], ys[1] - ys[0]);
+        for &y in &ys[1..] {
+            sse += (y - (level + trend)).powi(2);
+            let previous = level;
+            level = alpha * y + (1.0 - alpha) * (level + trend);
+            trend = beta * (level - previous) + (1.0 - beta) * trend;
+        }
+        return (sse, (1..=horizon).map(|h| level + h as f64 * trend).collect());
+    };
+
+    // Level and trend from the first two seasons, the seasonal offsets
+    // from the first
+    let mean = |s: &[f64]| s.iter().sum::<f64>() / s.len() as f64;
+    let mut level = mean(&ys[..m]);
+    let mut trend = (mean(&ys[m..2 * m]) - level) / m as f64;
+    let mut seasonal: Vec<f64> = ys[..m].iter().map(|y| y - level).collect();
+    for (t, &y) in ys.iter().enumerate().skip(m) {
+        let offset = seasonal[t - m];
+        sse += (y - (level + trend + offset)).powi(2);
+        let previous = level;
+        level = alpha * (y - offset) + (1.0 - alpha) * (level + trend);
+        trend = beta * (level - previous) + (1.0 - beta) * trend;
+        seasonal.push(gamma * (y - level) + (1.0 - gamma) * offset);
+    }
+    let n = ys.len();
+    let forecast = (1..=horizon).map(|h| level + h as f64 * trend + seasonal[n - m + (h - 1) % m]).collect();
+    (sse, forecast)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_holt_winters_follows_trend_and_week() {
+        let pattern = [0.0, 2.0, 4.0, 2.0, 0.0];
+        let series = |t: usize| 100.0 + t as f64 + pattern[t % 5];
+        let ys: Vec<f64> = (0..60).map(series).collect();
+        let forecast = holt_winters(&ys, Some(5), 5);
+        for (h, (f, _)) in forecast.iter().enumerate() {
+            assert!((f - series(60 + h)).abs() < 0.5, "step {}: {} vs {}", h + 1, f, series(60 + h));
+        }
+        assert!(forecast.windows(2).all(|w| w[1].1 >= w[0].1));
+        // Too short for a season: a straight trend, fitted exactly
+        let line = holt_winters(&[1.0, 2.0, 3.0, 4.0], Some(5), 2);
+        assert!((line[0].0 - 5.0).abs() < 1e-9 && (line[1].0 - 6.0).abs() < 1e-9);
+        assert!(line[1].1 < 1e-9);
+        assert!(holt_winters(&[1.0], None, 3).is_empty());
+    }
+
+    #[test]
+    fn test_every_model_is_registered_and_forecasts() {
+        let mut sd = StockData::new();
+        for t in 0..60 {
+            let close = 100.0 + t as f64 + if t % 2 == 0 { 0.3 } else { -0.3 };
+            sd.add_point(t * 86_400, close, close, close, close, 0);
+        }
+        let mut names: Vec<&str> = Forecaster::ALL.iter().map(|f| f.label()).collect();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), Forecaster::ALL.len());
+        for &forecaster in Forecaster::ALL {
+            let forecast = forecast(&sd, forecaster);
+            assert_eq!(forecast.len(), HORIZON, "{}", forecaster.label());
+            // A rising series goes on rising, with a spread that grows
+            assert!(forecast[HORIZON - 1].0 > 160.0 && forecast[HORIZON - 1].0 < 170.0, "{}: {:?}", forecaster.label(), forecast);
+            assert!(forecast[HORIZON - 1].1 >= forecast[0].1, "{}", forecaster.label());
+        }
+        // Seeded draws: the same bars give the same forecast
+        assert_eq!(MonteCarlo.predict(&sd), MonteCarlo.predict(&sd));
+    }
+
+    #[test]
+    fn test_daily_bars_have_a_weekly_season() {
+        let daily: Vec<i64> = (0..10).map(|d| d * 86_400).collect();
+        let hourly: Vec<i64> = (0..10).map(|h| h * 3600).collect();
+        assert_eq!((weekly_season(&daily), weekly_season(&hourly)), (Some(5), None));
+    }
+}
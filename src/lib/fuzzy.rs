@@ -0,0 +1,57 @@
+/// Score `candidate` against `query` the way command palettes do: every
+/// query character must appear in order (case-insensitive), with runs of
+/// consecutive characters and matches at the start of a word scoring
+/// higher. `None` when the query is not a subsequence; an empty query
+/// matches everything with a score of 0.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i32> {
+    let mut query = query.chars().filter(|c| !c.is_whitespace()).flat_map(char::to_lowercase).peekable();
+    let mut score = 0;
+    let mut prev_matched = false;
+    let mut prev: Option<char> = None;
+    for c in candidate.chars() {
+        let Some(&q) = query.peek() else { break };
+        let word_start = prev.is_none_or(|p| !p.is_alphanumeric());
+        if c.to_lowercase().eq(std::iter::once(q)) {
+            query.next();
+            score += 1;
+            if prev_matched {
+                score += 4;
+            }
+            if word_start {
+                score += 6;
+            }
+            prev_matched = true;
+        } else {
+            prev_matched = false;
+            // Earlier matches beat ones buried at the end
+            score -= 1;
+        }
+        prev = Some(c);
+    }
+    query.peek().is_none().then_some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subsequence_required() {
+        assert!(fuzzy_score("tr3m", "Time range: 3M").is_some());
+        assert!(fuzzy_score("ADD", "add symbol").is_some());
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+        assert_eq!(fuzzy_score("xyz", "Time range: 3M"), None);
+        assert_eq!(fuzzy_score("mt", "Time"), None);
+    }
+
+    #[test]
+    fn test_word_starts_and_runs_rank_first() {
+        let heatmap = fuzzy_score("heat", "View: heatmap").unwrap();
+        let scattered = fuzzy_score("heat", "Show the chart").unwrap();
+        assert!(heatmap > scattered);
+
+        let initials = fuzzy_score("as", "Add symbol").unwrap();
+        let inside = fuzzy_score("as", "Toggle gridlines as").unwrap();
+        assert!(initials > inside);
+    }
+}
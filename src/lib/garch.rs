@@ -0,0 +1,112 @@
+//! GARCH(1,1) volatility: tomorrow's variance of returns is a blend of a
+//! long-run level, today's squared return and today's variance, so calm
+//! and turbulent spells persist and fade the way markets tend to.
+
+/// Returns the model is fitted to, the latest ones.
+const MAX_RETURNS: usize = 500;
+
+/// Fewer returns than this give no fit worth having.
+const MIN_RETURNS: usize = 30;
+
+/// Standard normal quantile of a two-sided 95% interval.
+pub const Z_95: f64 = 1.96;
+
+/// A fitted GARCH(1,1) model of per-bar log returns:
+/// `σ²[t+1] = ω + α·r²[t] + β·σ²[t]`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Garch {
+    pub omega: f64,
+    pub alpha: f64,
+    pub beta: f64,
+    /// Variance forecast for the bar after the last one.
+    pub next_variance: f64,
+}
+
+impl Garch {
+    /// Fit to the log returns of `closes` by maximum likelihood over a grid
+    /// of α and β, with ω set so the long-run variance is the sample one.
+    /// `None` with too few returns or none that vary.
+    pub fn fit(closes: &[f64]) -> Option<Garch> {
+        let returns: Vec<f64> = closes.windows(2)
+            .filter(|w| w[0] > 0.0 && w[1] > 0.0)
+            .map(|w| (w[1] / w[0]).ln())
+            .collect();
+        let returns = &returns[returns.len().saturating_sub(MAX_RETURNS)..];
+        if returns.len() < MIN_RETURNS {
+            return None;
+        }
+        let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+        let returns: Vec<f64> = returns.iter().map(|r| r - mean).collect();
+        let variance = returns.iter().map(|r| r * r).sum::<f64>() / returns.len() as f64;
+        if variance <= 0.0 {
+            return None;
+        }
+
+        let mut best: Option<(f64, Garch)> = None;
+        for a in 1..=15 {
+            for b in 30..=49 {
+                let (alpha, beta) = (a as f64 * 0.02, b as f64 * 0.02);
+                if alpha + beta >= 0.999 {
+                    continue;
+                }
+                let omega = variance * (1.0 - alpha - beta);
+                let (mut sigma2, mut log_likelihood) = (variance, 0.0);
+                for r in &returns {
+                    log_likelihood -= sigma2.ln() + r * r / sigma2;
+                    sigma2 = omega + alpha * r * r + beta * sigma2;
+                }
+                if best.as_ref().is_none_or(|(l, _)| log_likelihood > *l) {
+                    best = Some((log_likelihood, Garch { omega, alpha, beta, next_variance: sigma2 }));
+                }
+            }
+        }
+        best.map(|(_, garch)| garch)
+    }
+
+    /// Per-bar volatility expected over the next bar.
+    pub fn next_volatility(&self) -> f64 {
+        self.next_variance.sqrt()
+    }
+
+    /// Per-bar volatility the variance reverts to.
+    pub fn long_run_volatility(&self) -> f64 {
+        (self.omega / (1.0 - self.alpha - self.beta)).sqrt()
+    }
+
+    /// Volatility of the cumulative log return over each of the next
+    /// `steps` bars, the variance reverting to its long-run level.
+    pub fn cumulative_volatility(&self, steps: usize) -> Vec<f64> {
+        let persistence = self.alpha + self.beta;
+        let long_run = self.omega / (1.0 - persistence);
+        let mut total = 0.0;
+        (0..steps)
+            .map(|h| {
+                total += long_run + persistence.powi(h as i32) * (self.next_variance - long_run);
+                total.sqrt()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_garch_picks_up_a_turbulent_spell() {
+        // Alternating moves of 0.5%, then of 4% at the end
+        let mut closes = vec![100.0];
+        for i in 0..200 {
+            let step = if i < 180 { 0.005 } else { 0.04 };
+            let last = *closes.last().unwrap();
+            closes.push(last * if i % 2 == 0 { 1.0 + step } else { 1.0 - step });
+        }
+        let garch = Garch::fit(&closes).unwrap();
+        let long_run = garch.long_run_volatility();
+        assert!(garch.next_volatility() > long_run, "{} vs {}", garch.next_volatility(), long_run);
+        let cumulative = garch.cumulative_volatility(5);
+        assert!(cumulative.windows(2).all(|w| w[1] > w[0]));
+        assert!((cumulative[0] - garch.next_volatility()).abs() < 1e-12);
+        assert!(Garch::fit(&closes[..20]).is_none());
+    }
+}
@@ -0,0 +1,72 @@
+use crate::lib::error::AppError;
+use std::fs;
+use std::path::Path;
+
+/// Whether `token` could be a ticker: letters, digits and the `.`, `-`, `^`
+/// and `=` that exchange suffixes, indices and currencies use.
+fn looks_like_symbol(token: &str) -> bool {
+    !token.is_empty()
+        && token.len() <= 15
+        && token.chars().any(|c| c.is_ascii_alphabetic())
+        && token.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '^' | '='))
+}
+
+/// Tickers in a newline/comma-separated list or a CSV export, upper-cased,
+/// in file order and without duplicates. When the first row has a
+/// `symbol` or `ticker` column only that column is read, so other columns
+/// (names, prices, …) are not mistaken for tickers.
+pub fn parse_symbol_list(text: &str) -> Vec<String> {
+    let split = |line: &str| -> Vec<String> {
+        line.split([',', ';', '\t'])
+            .map(|cell| cell.trim().trim_matches('"').trim().to_string())
+            .collect()
+    };
+    let mut lines = text.lines().filter(|l| !l.trim().is_empty()).peekable();
+    let column = lines.peek().and_then(|header| {
+        split(header).iter().position(|cell| {
+            let cell = cell.to_ascii_lowercase();
+            cell == "symbol" || cell == "ticker"
+        })
+    });
+    if column.is_some() {
+        lines.next();
+    }
+
+    let mut symbols: Vec<String> = Vec::new();
+    for line in lines {
+        let cells = split(line);
+        let tokens: Vec<&str> = match column {
+            Some(c) => cells.get(c).map(|s| s.as_str()).into_iter().collect(),
+            None => cells.iter().flat_map(|cell| cell.split_whitespace()).collect(),
+        };
+        for token in tokens {
+            let symbol = token.to_ascii_uppercase();
+            if looks_like_symbol(&symbol) && !symbols.contains(&symbol) {
+                symbols.push(symbol);
+            }
+        }
+    }
+    symbols
+}
+
+/// Read and parse a ticker list from `path`.
+pub fn read_symbol_file(path: &Path) -> Result<Vec<String>, AppError> {
+    Ok(parse_symbol_list(&fs::read_to_string(path)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_list_is_deduplicated() {
+        let text = "aapl, msft\nNVDA\n\nAAPL;BRK-B  ^GSPC\n";
+        assert_eq!(parse_symbol_list(text), vec!["AAPL", "MSFT", "NVDA", "BRK-B", "^GSPC"]);
+    }
+
+    #[test]
+    fn test_csv_reads_the_symbol_column() {
+        let text = "Name,Symbol,Price\n\"Apple Inc.\",AAPL,190.1\nNvidia,\"NVDA\",900\nCash,,1\n";
+        assert_eq!(parse_symbol_list(text), vec!["AAPL", "NVDA"]);
+    }
+}
@@ -0,0 +1,98 @@
+//! Trades by company insiders — officers, directors and large holders —
+//! and what their buying and selling nets out to over recent months.
+
+const DAY: i64 = 86_400;
+
+/// Days the net activity figure looks back over.
+pub const WINDOW_DAYS: i64 = 91;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InsiderAction {
+    Buy,
+    Sell,
+    /// Option exercises, awards, gifts and the like, which are not a view
+    /// on the price.
+    Other,
+}
+
+impl InsiderAction {
+    pub fn label(self) -> &'static str {
+        match self {
+            InsiderAction::Buy => "Buy",
+            InsiderAction::Sell => "Sell",
+            InsiderAction::Other => "Other",
+        }
+    }
+}
+
+/// A transaction reported by an insider.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InsiderTrade {
+    pub insider: String,
+    /// Their role, e.g. "Chief Financial Officer"; may be empty.
+    pub relation: String,
+    /// Unix timestamp of the transaction.
+    pub date: i64,
+    pub action: InsiderAction,
+    pub shares: u64,
+    /// In the currency the stock trades in, where reported.
+    pub value: Option<f64>,
+}
+
+/// Insider buying less selling over the last [`WINDOW_DAYS`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NetActivity {
+    pub buys: usize,
+    pub sells: usize,
+    /// Shares bought less shares sold.
+    pub net_shares: i64,
+    /// Value bought less value sold, over the trades with a value.
+    pub net_value: f64,
+}
+
+/// Net activity of `trades` as of `now`; `None` when there was no buying
+/// or selling in the window.
+pub fn net_activity(trades: &[InsiderTrade], now: i64) -> Option<NetActivity> {
+    let mut net = NetActivity { buys: 0, sells: 0, net_shares: 0, net_value: 0.0 };
+    for trade in trades.iter().filter(|t| t.date > now - WINDOW_DAYS * DAY && t.date <= now) {
+        let sign = match trade.action {
+            InsiderAction::Buy => {
+                net.buys += 1;
+                1.0
+            }
+            InsiderAction::Sell => {
+                net.sells += 1;
+                -1.0
+            }
+            InsiderAction::Other => continue,
+        };
+        net.net_shares += sign as i64 * trade.shares as i64;
+        net.net_value += sign * trade.value.unwrap_or_default();
+    }
+    (net.buys + net.sells > 0).then_some(net)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(days_ago: i64, action: InsiderAction, shares: u64, value: Option<f64>) -> InsiderTrade {
+        let now = 1_760_000_000;
+        InsiderTrade { insider: "A".into(), relation: String::new(), date: now - days_ago * DAY, action, shares, value }
+    }
+
+    #[test]
+    fn test_net_activity_over_three_months() {
+        let now = 1_760_000_000;
+        let trades = [
+            trade(5, InsiderAction::Sell, 10_000, Some(2_000_000.0)),
+            trade(20, InsiderAction::Buy, 1_000, Some(150_000.0)),
+            trade(30, InsiderAction::Other, 50_000, None),
+            trade(120, InsiderAction::Buy, 99_000, Some(9_000_000.0)),
+        ];
+        let net = net_activity(&trades, now).unwrap();
+        assert_eq!((net.buys, net.sells, net.net_shares), (1, 1, -9_000));
+        assert_eq!(net.net_value, -1_850_000.0);
+        assert!(net_activity(&trades[2..], now).is_none());
+    }
+}
@@ -0,0 +1,164 @@
+//! What kind of instrument a symbol names, worked out from the symbol
+//! alone, and how its prices are written.
+
+/// Quote currencies a Yahoo-style crypto pair (`BTC-USD`) ends in. Share
+/// classes (`BRK-B`) end in a single letter, so they never match.
+const CRYPTO_QUOTES: [&str; 14] = [
+    "USD", "USDT", "USDC", "EUR", "GBP", "JPY", "CAD", "AUD", "CHF", "KRW", "INR", "BTC", "ETH", "BNB",
+];
+
+/// Quote currencies written with a leading `$`.
+const DOLLAR_QUOTES: [&str; 3] = ["USD", "USDT", "USDC"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssetClass {
+    Equity,
+    Crypto,
+    /// A currency pair, `EURUSD=X` style.
+    Forex,
+    /// A market index such as `^GSPC`, measured in points.
+    Index,
+}
+
+impl AssetClass {
+    pub fn of(symbol: &str) -> Self {
+        if symbol.starts_with('^') {
+            AssetClass::Index
+        } else if forex_pair(symbol).is_some() {
+            AssetClass::Forex
+        } else if crypto_pair(symbol).is_some() {
+            AssetClass::Crypto
+        } else {
+            AssetClass::Equity
+        }
+    }
+
+    /// Whether the instrument trades around the clock, every day.
+    pub fn trades_24_7(self) -> bool {
+        self == AssetClass::Crypto
+    }
+
+    /// Whether the instrument has a traded volume of its own. Currencies
+    /// trade over the counter, and Yahoo reports zero for them; an index's
+    /// volume is its members'.
+    pub fn has_volume(self) -> bool {
+        !matches!(self, AssetClass::Forex | AssetClass::Index)
+    }
+}
+
+/// `(base, quote)` for a currency pair. Yahoo writes `EURUSD=X`, and
+/// `JPY=X` for dollars in yen.
+fn forex_pair(symbol: &str) -> Option<(&str, &str)> {
+    let pair = symbol.strip_suffix("=X").or_else(|| symbol.strip_suffix("=x"))?;
+    if !pair.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    match pair.len() {
+        3 => Some(("USD", pair)),
+        6 => Some(pair.split_at(3)),
+        _ => None,
+    }
+}
+
+/// Size of one pip, the unit currency moves are counted in: a hundredth for
+/// yen pairs, a ten-thousandth otherwise. `None` for other instruments.
+pub fn pip_size(symbol: &str) -> Option<f64> {
+    let (_, quote) = forex_pair(symbol)?;
+    Some(if quote.eq_ignore_ascii_case("JPY") { 0.01 } else { 0.0001 })
+}
+
+/// `change` in price as pips (`+12.3 pips`), for currency pairs.
+pub fn format_pips(symbol: &str, change: f64) -> Option<String> {
+    pip_size(symbol).map(|pip| format!("{:+.1} pips", change / pip))
+}
+
+/// `(base, quote)` for a crypto pair such as `ETH-BTC`.
+fn crypto_pair(symbol: &str) -> Option<(&str, &str)> {
+    let (base, quote) = symbol.split_once('-')?;
+    let quote = CRYPTO_QUOTES.iter().find(|q| q.eq_ignore_ascii_case(quote))?;
+    (!base.is_empty() && base.chars().all(|c| c.is_ascii_alphanumeric())).then_some((base, quote))
+}
+
+/// Decimal places for `symbol`'s price. Equities keep cents; crypto trades
+/// from tens of thousands down to fractions of a cent, so smaller prices
+/// get enough places for four significant digits; currency pairs are quoted
+/// to a tenth of a pip.
+pub fn price_decimals(symbol: &str, price: f64) -> usize {
+    if let Some(pip) = pip_size(symbol) {
+        return (-pip.log10()).round() as usize + 1;
+    }
+    let price = price.abs();
+    match AssetClass::of(symbol) {
+        AssetClass::Equity | AssetClass::Forex | AssetClass::Index => 2,
+        AssetClass::Crypto if price >= 1000.0 || price == 0.0 || !price.is_finite() => 2,
+        AssetClass::Crypto if price >= 1.0 => 4,
+        AssetClass::Crypto => small_price_decimals(price),
+    }
+}
+
+/// Decimal places showing four significant digits of a price under 1,
+/// at least four and at most ten.
+pub fn small_price_decimals(price: f64) -> usize {
+    let price = price.abs();
+    if price == 0.0 || !price.is_finite() {
+        return 4;
+    }
+    ((-price.log10()).ceil().max(0.0) as usize + 3).clamp(4, 10)
+}
+
+/// `price` as `symbol` is quoted: `$` and cents for equities, more places
+/// for small crypto prices, the quote currency after crypto pairs not
+/// priced in dollars (`0.05123 BTC`), and bare numbers for currency pairs
+/// (`1.08432`) and index points (`5123.45`).
+pub fn format_price(symbol: &str, price: f64) -> String {
+    let decimals = price_decimals(symbol, price);
+    if matches!(AssetClass::of(symbol), AssetClass::Forex | AssetClass::Index) {
+        return format!("{:.*}", decimals, price);
+    }
+    match crypto_pair(symbol) {
+        Some((_, quote)) if !DOLLAR_QUOTES.contains(&quote) => format!("{:.*} {}", decimals, price, quote),
+        _ => format!("${:.*}", decimals, price),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crypto_pairs_are_told_from_share_classes() {
+        assert_eq!(AssetClass::of("BTC-USD"), AssetClass::Crypto);
+        assert_eq!(AssetClass::of("eth-btc"), AssetClass::Crypto);
+        assert_eq!(AssetClass::of("BRK-B"), AssetClass::Equity);
+        assert_eq!(AssetClass::of("AAPL"), AssetClass::Equity);
+        assert!(!AssetClass::of("AAPL").trades_24_7());
+    }
+
+    #[test]
+    fn test_prices_get_places_by_size() {
+        assert_eq!(format_price("AAPL", 0.5), "$0.50");
+        assert_eq!(format_price("BTC-USD", 64_123.456), "$64123.46");
+        assert_eq!(format_price("SOL-USD", 142.5), "$142.5000");
+        assert_eq!(format_price("ADA-USD", 0.45), "$0.4500");
+        assert_eq!(format_price("SHIB-USD", 0.00001234), "$0.00001234");
+        assert_eq!(format_price("ETH-BTC", 0.05123), "0.05123 BTC");
+    }
+
+    #[test]
+    fn test_currency_pairs_are_quoted_in_pips() {
+        assert_eq!(AssetClass::of("EURUSD=X"), AssetClass::Forex);
+        assert!(!AssetClass::of("EURUSD=X").has_volume());
+        assert_eq!(format_price("EURUSD=X", 1.084321), "1.08432");
+        assert_eq!(format_price("JPY=X", 151.2342), "151.234");
+        assert_eq!(format_pips("GBPUSD=X", 0.00123).as_deref(), Some("+12.3 pips"));
+        assert_eq!(format_pips("USDJPY=X", -0.5).as_deref(), Some("-50.0 pips"));
+        assert_eq!(format_pips("AAPL", 1.0), None);
+    }
+
+    #[test]
+    fn test_indices_are_in_points() {
+        assert_eq!(AssetClass::of("^GSPC"), AssetClass::Index);
+        assert!(!AssetClass::of("^GSPC").has_volume());
+        assert_eq!(format_price("^GSPC", 5123.456), "5123.46");
+    }
+}
@@ -1,6 +1,46 @@
 pub mod stock_data;
 pub mod yahooapi;
 pub mod config;
+pub mod accuracy;
+pub mod alphavantage;
 pub mod analysis;
+pub mod backtest;
+pub mod broker;
+pub mod browser;
+pub mod calendar;
+pub mod chart_image;
+pub mod dividends;
+pub mod edgar;
 pub mod error;
+pub mod export;
+pub mod finnhub;
+pub mod forecast;
+pub mod garch;
+pub mod fuzzy;
+pub mod import;
+pub mod insiders;
+pub mod instrument;
+pub mod market;
+#[cfg(feature = "neural")]
+pub mod neural;
+pub mod paper;
+pub mod optimize;
+pub mod parquet;
 pub mod persistence;
+pub mod portfolio;
+pub mod provider;
+pub mod report;
+pub mod retry;
+pub mod returns;
+pub mod risk;
+pub mod secrets;
+pub mod sentiment;
+pub mod signals;
+pub mod snapshot;
+pub mod stooq;
+pub mod strategy;
+pub mod stream;
+pub mod sync;
+pub mod timezone;
+pub mod toml;
+pub mod walk_forward;
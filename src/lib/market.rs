@@ -0,0 +1,135 @@
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+
+use crate::lib::{
+    calendar::{Zone, US},
+    instrument::AssetClass,
+    stock_data::StockData,
+};
+
+/// A New York wall-clock time as UTC. Times in the hour the clocks go back
+/// resolve to the later one.
+pub fn new_york_to_utc(local: NaiveDateTime) -> DateTime<Utc> {
+    // Standard time first; daylight time is an hour less behind UTC
+    let standard = local.and_utc() + Duration::hours(5);
+    standard - Duration::minutes(300 + Zone::NewYork.offset_minutes(standard))
+}
+
+/// Part of the trading day a time falls in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Session {
+    PreMarket,
+    Regular,
+    AfterHours,
+    Closed,
+}
+
+impl Session {
+    pub fn is_extended(self) -> bool {
+        matches!(self, Session::PreMarket | Session::AfterHours)
+    }
+
+    /// Short name for tile headers.
+    pub fn short_label(self) -> &'static str {
+        match self {
+            Session::PreMarket => "Pre",
+            Session::Regular => "Open",
+            Session::AfterHours => "After",
+            Session::Closed => "Closed",
+        }
+    }
+
+    /// Full name for headers and the status bar.
+    pub fn label(self) -> &'static str {
+        match self {
+            Session::PreMarket => "Pre-market",
+            Session::Regular => "Open",
+            Session::AfterHours => "After-hours",
+            Session::Closed => "Closed",
+        }
+    }
+}
+
+/// The US exchanges' session at `utc`: pre-market 4:00–9:30, regular
+/// 9:30–16:00 and after-hours 16:00–20:00 New York time, on trading days.
+pub fn us_session(utc: DateTime<Utc>) -> Session {
+    US.session(utc)
+}
+
+/// Whether `symbol` trades in the US sessions: stocks and funds without a
+/// foreign exchange suffix (`VOD.L`), as opposed to crypto, currencies and
+/// indices.
+pub fn follows_us_sessions(symbol: &str) -> bool {
+    AssetClass::of(symbol) == AssetClass::Equity && !symbol.contains('.')
+}
+
+/// When `data`'s latest bar is from the pre-market or after-hours session,
+/// that session and the percent move from the last regular-session close
+/// (or, before any, the close preceding the data).
+pub fn extended_change(data: &StockData) -> Option<(Session, f64)> {
+    let session = |t: i64| DateTime::from_timestamp(t, 0).map_or(Session::Closed, us_session);
+    let last = data.len().checked_sub(1)?;
+    let latest = session(data.timestamps[last]);
+    if !latest.is_extended() {
+        return None;
+    }
+    let reference = (0..last).rev()
+        .find(|&i| session(data.timestamps[i]) == Session::Regular)
+        .map(|i| data.closes[i])
+        .or(data.previous_close)
+        .filter(|&c| c > 0.0)?;
+    Some((latest, (data.closes[last] / reference - 1.0) * 100.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_session_follows_daylight_saving() {
+        // Summer: 9:30 EDT is 13:30 UTC
+        assert_ne!(us_session(utc("2025-07-15T13:29:00Z")), Session::Regular);
+        assert_eq!(us_session(utc("2025-07-15T13:30:00Z")), Session::Regular);
+        assert_ne!(us_session(utc("2025-07-15T20:00:00Z")), Session::Regular);
+        // Winter: 9:30 EST is 14:30 UTC
+        assert_ne!(us_session(utc("2025-01-15T14:00:00Z")), Session::Regular);
+        assert_eq!(us_session(utc("2025-01-15T20:59:00Z")), Session::Regular);
+    }
+
+    #[test]
+    fn test_new_york_wall_clock_to_utc() {
+        let local = |s: &str| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap();
+        assert_eq!(new_york_to_utc(local("2025-07-15 09:30:00")), utc("2025-07-15T13:30:00Z"));
+        assert_eq!(new_york_to_utc(local("2025-01-15 16:00:00")), utc("2025-01-15T21:00:00Z"));
+    }
+
+    #[test]
+    fn test_extended_sessions_and_their_move() {
+        assert_eq!(us_session(utc("2025-07-15T12:00:00Z")), Session::PreMarket);
+        assert_eq!(us_session(utc("2025-07-15T21:00:00Z")), Session::AfterHours);
+        assert_eq!(us_session(utc("2025-07-16T01:00:00Z")), Session::Closed);
+
+        let mut data = StockData::new();
+        data.add_point(utc("2025-07-15T19:59:00Z").timestamp(), 100.0, 100.0, 100.0, 100.0, 10);
+        data.add_point(utc("2025-07-15T21:00:00Z").timestamp(), 101.0, 101.0, 101.0, 101.0, 10);
+        let (session, change) = extended_change(&data).unwrap();
+        assert_eq!(session, Session::AfterHours);
+        assert!((change - 1.0).abs() < 1e-9);
+
+        // Pre-market with only the previous close to go on
+        let mut data = StockData::new();
+        data.add_point(utc("2025-07-16T12:00:00Z").timestamp(), 99.0, 99.0, 99.0, 99.0, 10);
+        assert!(extended_change(&data).is_none());
+        data.previous_close = Some(100.0);
+        assert_eq!(extended_change(&data).map(|(s, _)| s), Some(Session::PreMarket));
+    }
+
+    #[test]
+    fn test_closed_at_weekends() {
+        assert_ne!(us_session(utc("2025-07-19T15:00:00Z")), Session::Regular);
+        assert_ne!(us_session(utc("2025-07-20T15:00:00Z")), Session::Regular);
+    }
+}
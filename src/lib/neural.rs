@@ -0,0 +1,213 @@
+//! A small GRU network, trained on a symbol's own series each time it
+//! forecasts; built with the `neural` feature. Plain `f64` arithmetic
+//! rather than a tensor library: the network is tiny, and the feature adds
+//! no dependencies this way.
+
+use crate::lib::forecast::splitmix;
+
+/// Units in the hidden state.
+const HIDDEN: usize = 8;
+
+/// Passes of full backpropagation through time over the series.
+const EPOCHS: usize = 150;
+
+/// Adam's step size and moment decays.
+const LEARNING_RATE: f64 = 0.01;
+const BETA1: f64 = 0.9;
+const BETA2: f64 = 0.999;
+
+/// Gradients longer than this are scaled down to it, which keeps an
+/// occasional exploding step from wrecking the fit.
+const MAX_GRADIENT: f64 = 1.0;
+
+/// Parameters of one gate: input weights, recurrent weights (row `i` feeds
+/// unit `i`) and biases.
+const GATE: usize = HIDDEN + HIDDEN * HIDDEN + HIDDEN;
+/// The update, reset and candidate gates, then the output weights and bias.
+const OUTPUT: usize = 3 * GATE;
+const PARAMS: usize = OUTPUT + HIDDEN + 1;
+
+const UPDATE: usize = 0;
+const RESET: usize = 1;
+const CANDIDATE: usize = 2;
+
+type State = [f64; HIDDEN];
+
+/// What one step computed, kept for the backward pass.
+struct Step {
+    x: f64,
+    h_prev: State,
+    z: State,
+    r: State,
+    n: State,
+    h: State,
+}
+
+/// A single-layer GRU reading one value per step and predicting the next.
+pub struct Gru {
+    params: Vec<f64>,
+}
+
+fn sigmoid(x: f64) -> f64 {
+    1.0 / (1.0 + (-x).exp())
+}
+
+impl Gru {
+    /// Fit to `series`, predicting each value from the ones before it, and
+    /// return the network with its root-mean-square one-step error. The
+    /// weights start from a fixed seed, so the same series gives the same
+    /// network. `series` should be roughly standardized.
+    pub fn train(series: &[f64]) -> (Gru, f64) {
+        let mut gru = Gru::seeded();
+        if series.len() < 2 {
+            return (gru, 0.0);
+        }
+        let (mut m, mut v) = (vec![0.0; PARAMS], vec![0.0; PARAMS]);
+        for epoch in 1..=EPOCHS {
+            let mut grad = gru.gradient(series);
+            let norm = grad.iter().map(|g| g * g).sum::<f64>().sqrt();
+            if norm > MAX_GRADIENT {
+                grad.iter_mut().for_each(|g| *g *= MAX_GRADIENT / norm);
+            }
+            let (c1, c2) = (1.0 - BETA1.powi(epoch as i32), 1.0 - BETA2.powi(epoch as i32));
+            for (i, g) in grad.into_iter().enumerate() {
+                m[i] = BETA1 * m[i] + (1.0 - BETA1) * g;
+                v[i] = BETA2 * v[i] + (1.0 - BETA2) * g * g;
+                gru.params[i] -= LEARNING_RATE * (m[i] / c1) / ((v[i] / c2).sqrt() + 1e-8);
+            }
+        }
+        let steps = gru.forward(&series[..series.len() - 1]);
+        let sse: f64 = steps.iter().zip(&series[1..]).map(|(s, y)| (gru.output(&s.h) - y).powi(2)).sum();
+        let rmse = (sse / steps.len() as f64).sqrt();
+        (gru, rmse)
+    }
+
+    /// Weights drawn uniformly within ±1/√HIDDEN.
+    fn seeded() -> Gru {
+        let mut seed = 0x2545_f491_4f6c_dd1du64;
+        let scale = 1.0 / (HIDDEN as f64).sqrt();
+        let params = (0..PARAMS)
+            .map(|_| ((splitmix(&mut seed) >> 11) as f64 / (1u64 << 53) as f64 * 2.0 - 1.0) * scale)
+            .collect();
+        Gru { params }
+    }
+
+    /// The `count` values after `series`, each prediction fed back in as
+    /// the next input.
+    pub fn extend(&self, series: &[f64], count: usize) -> Vec<f64> {
+        let mut h = self.forward(series).last().map_or([0.0; HIDDEN], |s| s.h);
+        (0..count)
+            .map(|_| {
+                let y = self.output(&h);
+                h = self.step(y, &h).h;
+                y
+            })
+            .collect()
+    }
+
+    fn step(&self, x: f64, h_prev: &State) -> Step {
+        let p = &self.params;
+        let pre = |gate: usize, i: usize, input: &State| {
+            let base = gate * GATE;
+            let recurrent: f64 = (0..HIDDEN).map(|j| p[base + HIDDEN + i * HIDDEN + j] * input[j]).sum();
+            p[base + i] * x + recurrent + p[base + HIDDEN + HIDDEN * HIDDEN + i]
+        };
+        let z: State = std::array::from_fn(|i| sigmoid(pre(UPDATE, i, h_prev)));
+        let r: State = std::array::from_fn(|i| sigmoid(pre(RESET, i, h_prev)));
+        let reset: State = std::array::from_fn(|j| r[j] * h_prev[j]);
+        let n: State = std::array::from_fn(|i| pre(CANDIDATE, i, &reset).tanh());
+        let h = std::array::from_fn(|i| (1.0 - z[i]) * n[i] + z[i] * h_prev[i]);
+        Step { x, h_prev: *h_prev, z, r, n, h }
+    }
+
+    fn forward(&self, inputs: &[f64]) -> Vec<Step> {
+        let mut h = [0.0; HIDDEN];
+        inputs
+            .iter()
+            .map(|&x| {
+                let step = self.step(x, &h);
+                h = step.h;
+                step
+            })
+            .collect()
+    }
+
+    fn output(&self, h: &State) -> f64 {
+        (0..HIDDEN).map(|i| self.params[OUTPUT + i] * h[i]).sum::<f64>() + self.params[OUTPUT + HIDDEN]
+    }
+
+    /// Gradient of the mean squared one-step error over `series`, by
+    /// backpropagation through every step.
+    fn gradient(&self, series: &[f64]) -> Vec<f64> {
+        let p = &self.params;
+        let u = |gate: usize, i: usize, j: usize| gate * GATE + HIDDEN + i * HIDDEN + j;
+        let steps = self.forward(&series[..series.len() - 1]);
+        let mut grad = vec![0.0; PARAMS];
+        let mut dh_next = [0.0; HIDDEN];
+        for (step, &target) in steps.iter().zip(&series[1..]).rev() {
+            let dy = 2.0 * (self.output(&step.h) - target) / steps.len() as f64;
+            let mut dh = dh_next;
+            for i in 0..HIDDEN {
+                grad[OUTPUT + i] += dy * step.h[i];
+                dh[i] += dy * p[OUTPUT + i];
+            }
+            grad[OUTPUT + HIDDEN] += dy;
+
+            // Back through h = (1 - z)·n + z·h_prev to each gate's input
+            let mut dh_prev: State = std::array::from_fn(|i| dh[i] * step.z[i]);
+            let mut d_pre = [[0.0; HIDDEN]; 3];
+            for i in 0..HIDDEN {
+                d_pre[UPDATE][i] = dh[i] * (step.h_prev[i] - step.n[i]) * step.z[i] * (1.0 - step.z[i]);
+                d_pre[CANDIDATE][i] = dh[i] * (1.0 - step.z[i]) * (1.0 - step.n[i] * step.n[i]);
+            }
+            for j in 0..HIDDEN {
+                let d_reset: f64 = (0..HIDDEN).map(|i| p[u(CANDIDATE, i, j)] * d_pre[CANDIDATE][i]).sum();
+                d_pre[RESET][j] = d_reset * step.h_prev[j] * step.r[j] * (1.0 - step.r[j]);
+                dh_prev[j] += d_reset * step.r[j];
+            }
+
+            for (gate, d) in d_pre.iter().enumerate() {
+                let base = gate * GATE;
+                for i in 0..HIDDEN {
+                    grad[base + i] += d[i] * step.x;
+                    grad[base + HIDDEN + HIDDEN * HIDDEN + i] += d[i];
+                    for j in 0..HIDDEN {
+                        let input = if gate == CANDIDATE { step.r[j] * step.h_prev[j] } else { step.h_prev[j] };
+                        grad[u(gate, i, j)] += d[i] * input;
+                        if gate != CANDIDATE {
+                            dh_prev[j] += p[u(gate, i, j)] * d[i];
+                        }
+                    }
+                }
+            }
+            dh_next = dh_prev;
+        }
+        grad
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gradient_matches_finite_differences() {
+        let series: Vec<f64> = (0..12).map(|t| (t as f64 * 0.7).sin()).collect();
+        let mut gru = Gru::seeded();
+        let loss = |gru: &Gru| {
+            let steps = gru.forward(&series[..series.len() - 1]);
+            steps.iter().zip(&series[1..]).map(|(s, y)| (gru.output(&s.h) - y).powi(2)).sum::<f64>() / steps.len() as f64
+        };
+        let grad = gru.gradient(&series);
+        for i in (0..PARAMS).step_by(7) {
+            let eps = 1e-6;
+            gru.params[i] += eps;
+            let up = loss(&gru);
+            gru.params[i] -= 2.0 * eps;
+            let down = loss(&gru);
+            gru.params[i] += eps;
+            let numeric = (up - down) / (2.0 * eps);
+            assert!((grad[i] - numeric).abs() < 1e-6, "param {}: {} vs {}", i, grad[i], numeric);
+        }
+    }
+}
@@ -0,0 +1,278 @@
+//! Parameter sweeps: a strategy is backtested with every combination of
+//! lengths for its indicators, ranked by how it did over the first part of
+//! the history, and each combination is then checked on the part held out.
+//! The best of many tries is flattered by luck, so results that look like
+//! it are flagged.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::lib::{
+    config::StrategySettings,
+    stock_data::StockData,
+    strategy::{run, Series, Strategy, StrategyResult},
+};
+
+/// Combinations tried at most in one sweep.
+pub const MAX_COMBINATIONS: usize = 10_000;
+
+/// Fewer trades than this in the sample prove little either way.
+const MIN_TRADES: usize = 5;
+
+/// Lengths from `from` to `to` in steps of `step`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sweep {
+    pub from: usize,
+    pub to: usize,
+    pub step: usize,
+}
+
+impl Sweep {
+    pub fn lengths(&self) -> Vec<usize> {
+        (self.from.max(1)..=self.to).step_by(self.step.max(1)).collect()
+    }
+}
+
+/// Signs a combination's result owes more to the history it was picked on
+/// than to the rules.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Overfit {
+    /// Too few trades in the sample to tell skill from luck.
+    FewTrades,
+    /// The lengths next to it did far worse: a spike, not a plateau.
+    IsolatedPeak,
+    /// It earned less than half as fast, or lost, on the held-out bars.
+    FadesOutOfSample,
+}
+
+impl Overfit {
+    pub fn label(self) -> &'static str {
+        match self {
+            Overfit::FewTrades => "few trades",
+            Overfit::IsolatedPeak => "isolated peak",
+            Overfit::FadesOutOfSample => "fades out of sample",
+        }
+    }
+}
+
+/// One combination of lengths and how it did.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Trial {
+    pub strategy: Strategy,
+    /// Length of each swept indicator, in the order of
+    /// [`Optimization::parameters`].
+    pub lengths: Vec<usize>,
+    /// Returns in percent over the sample and over the held-out bars after
+    /// it; `None` when nothing was held out.
+    pub in_sample: f64,
+    pub out_of_sample: Option<f64>,
+    /// Trades opened in the sample, the share of those closed there that
+    /// made money, and the largest fall of the equity there, in percent.
+    pub trades: usize,
+    pub win_rate: Option<f64>,
+    pub max_drawdown: f64,
+    pub warnings: Vec<Overfit>,
+}
+
+/// Every combination of a sweep, best in the sample first.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Optimization {
+    /// The indicators swept.
+    pub parameters: Vec<Series>,
+    /// Bars in the sample; those after it were held out.
+    pub split: usize,
+    pub trials: Vec<Trial>,
+}
+
+/// A trial as `bstock optimize --format json` writes it.
+#[derive(Serialize)]
+pub struct OptimizeRow<'a> {
+    pub symbol: &'a str,
+    pub strategy: &'a str,
+    pub parameters: String,
+    pub in_sample_return: f64,
+    pub out_of_sample_return: Option<f64>,
+    pub trades: usize,
+    pub win_rate: Option<f64>,
+    pub max_drawdown: f64,
+    pub warnings: Vec<&'static str>,
+}
+
+impl Optimization {
+    /// The swept indicators at `trial`'s lengths, e.g. "SMA20 SMA120".
+    pub fn describe(&self, trial: &Trial) -> String {
+        self.parameters.iter().zip(&trial.lengths)
+            .map(|(s, &n)| s.with_length(n).to_string())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    pub fn row<'a>(&self, symbol: &'a str, trial: &'a Trial) -> OptimizeRow<'a> {
+        OptimizeRow {
+            symbol,
+            strategy: &trial.strategy.name,
+            parameters: self.describe(trial),
+            in_sample_return: trial.in_sample,
+            out_of_sample_return: trial.out_of_sample,
+            trades: trial.trades,
+            win_rate: trial.win_rate,
+            max_drawdown: trial.max_drawdown,
+            warnings: trial.warnings.iter().map(|w| w.label()).collect(),
+        }
+    }
+}
+
+/// How `result` did over the first `split` bars, and over the rest.
+fn measure(strategy: Strategy, lengths: Vec<usize>, result: &StrategyResult, capital: f64, split: usize) -> Trial {
+    let equity = &result.equity;
+    let at_split = equity[split - 1];
+    let pct = |from: f64, to: f64| if from != 0.0 { (to / from - 1.0) * 100.0 } else { 0.0 };
+    let closed: Vec<bool> = result.trades.iter()
+        .filter(|t| t.exit_bar.is_some_and(|bar| bar < split))
+        .map(|t| t.pnl > 0.0)
+        .collect();
+    let mut peak = f64::NEG_INFINITY;
+    let mut max_drawdown: f64 = 0.0;
+    for &value in &equity[..split] {
+        peak = peak.max(value);
+        if peak > 0.0 {
+            max_drawdown = max_drawdown.max((1.0 - value / peak) * 100.0);
+        }
+    }
+    Trial {
+        strategy,
+        lengths,
+        in_sample: pct(capital, at_split),
+        out_of_sample: (split < equity.len()).then(|| pct(at_split, equity[equity.len() - 1])),
+        trades: result.trades.iter().filter(|t| t.entry_bar < split).count(),
+        win_rate: (!closed.is_empty()).then(|| closed.iter().filter(|&&won| won).count() as f64 / closed.len() as f64),
+        max_drawdown,
+        warnings: Vec::new(),
+    }
+}
+
+/// Backtest `strategy` with every combination of `sweep`'s lengths for its
+/// SMAs, EMAs and RSIs, two of a kind never at the same length. The last
+/// `holdout_pct` percent of the bars are left out of the ranking. The
+/// combinations are spread over a thread per core.
+pub fn optimize(
+    strategy: &Strategy,
+    stock_data: &StockData,
+    settings: &StrategySettings,
+    sweep: Sweep,
+    holdout_pct: f64,
+) -> Result<Optimization, String> {
+    let parameters: Vec<Series> = strategy.series().into_iter().filter(|s| s.length().is_some()).collect();
+    if parameters.is_empty() {
+        return Err(format!("'{}' has no indicator lengths to sweep", strategy.name));
+    }
+    let lengths = sweep.lengths();
+    let total = lengths.len().checked_pow(parameters.len() as u32).unwrap_or(usize::MAX);
+    if lengths.is_empty() || total > MAX_COMBINATIONS {
+        return Err(format!(
+            "{} lengths for {} indicators make too many combinations (at most {}); narrow the range or take a larger step",
+            lengths.len(), parameters.len(), MAX_COMBINATIONS,
+        ));
+    }
+    let split = ((stock_data.len() as f64 * (1.0 - holdout_pct / 100.0)) as usize).min(stock_data.len());
+    if split < 2 {
+        return Err(format!("{} bars are too few to sweep", stock_data.len()));
+    }
+
+    // Every combination, as positions in `lengths`
+    let mut grid: Vec<Vec<usize>> = vec![Vec::new()];
+    for _ in &parameters {
+        grid = grid.into_iter()
+            .flat_map(|combo| (0..lengths.len()).map(move |i| [combo.clone(), vec![i]].concat()))
+            .collect();
+    }
+    grid.retain(|combo| {
+        let series: Vec<Series> = parameters.iter().zip(combo).map(|(s, &i)| s.with_length(lengths[i])).collect();
+        series.iter().enumerate().all(|(i, s)| !series[..i].contains(s))
+    });
+
+    let trial = |combo: &Vec<usize>| {
+        let chosen: Vec<usize> = combo.iter().map(|&i| lengths[i]).collect();
+        let swept = strategy.map_series(|s| parameters.iter().position(|&p| p == s).map_or(s, |i| s.with_length(chosen[i])));
+        let result = run(&swept, stock_data, settings);
+        measure(swept, chosen, &result, settings.capital, split)
+    };
+    let workers = std::thread::available_parallelism().map_or(1, |n| n.get()).clamp(1, grid.len().max(1));
+    let mut trials: Vec<Trial> = std::thread::scope(|scope| {
+        let handles: Vec<_> = grid.chunks(grid.len().div_ceil(workers).max(1))
+            .map(|part| scope.spawn(|| part.iter().map(trial).collect::<Vec<_>>()))
+            .collect();
+        handles.into_iter().flat_map(|h| h.join().expect("sweep thread panicked")).collect()
+    });
+
+    let by_combo: HashMap<&[usize], f64> = grid.iter().zip(&trials).map(|(c, t)| (c.as_slice(), t.in_sample)).collect();
+    let growth = |pct: f64, bars: usize| (1.0 + pct / 100.0).max(1e-9).ln() / bars as f64;
+    let flags: Vec<Vec<Overfit>> = grid.iter().zip(&trials)
+        .map(|(combo, t)| {
+            let mut warnings = Vec::new();
+            if t.trades < MIN_TRADES {
+                warnings.push(Overfit::FewTrades);
+            }
+            let neighbours: Vec<f64> = (0..combo.len())
+                .flat_map(|d| [combo[d].checked_sub(1), Some(combo[d] + 1)].map(move |i| (d, i)))
+                .filter_map(|(d, i)| {
+                    let mut next = combo.clone();
+                    next[d] = i?;
+                    by_combo.get(next.as_slice()).copied()
+                })
+                .collect();
+            if t.in_sample > 0.0
+                && !neighbours.is_empty()
+                && neighbours.iter().sum::<f64>() / (neighbours.len() as f64) < t.in_sample / 2.0
+            {
+                warnings.push(Overfit::IsolatedPeak);
+            }
+            let in_sample = growth(t.in_sample, split);
+            if let Some(after) = t.out_of_sample
+                && in_sample > 0.0
+                && growth(after, stock_data.len() - split) < in_sample / 2.0
+            {
+                warnings.push(Overfit::FadesOutOfSample);
+            }
+            warnings
+        })
+        .collect();
+    for (t, warnings) in trials.iter_mut().zip(flags) {
+        t.warnings = warnings;
+    }
+    trials.sort_by(|a, b| b.in_sample.total_cmp(&a.in_sample));
+    Ok(Optimization { parameters, split, trials })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lib::strategy::builtin;
+
+    #[test]
+    fn test_sweep_ranks_every_combination() {
+        // A slow wave on a rising trend: long averages ride it, short ones whipsaw
+        let mut sd = StockData::new();
+        for i in 0..300 {
+            let p = 100.0 + i as f64 * 0.2 + 10.0 * (i as f64 / 15.0).sin();
+            sd.add_point(i as i64 * 86_400, p, p, p, p, 0);
+        }
+        let golden = &builtin()[0];
+        let sweep = Sweep { from: 10, to: 50, step: 10 };
+        let result = optimize(golden, &sd, &StrategySettings::default(), sweep, 30.0).unwrap();
+        assert_eq!(result.parameters, [Series::Sma(10), Series::Sma(50)]);
+        assert_eq!(result.split, 210);
+        // 5 × 5 lengths, less the 5 with both averages the same
+        assert_eq!(result.trials.len(), 20);
+        assert!(result.trials.windows(2).all(|w| w[0].in_sample >= w[1].in_sample));
+        assert!(result.trials.iter().all(|t| t.lengths[0] != t.lengths[1] && t.out_of_sample.is_some()));
+        let best = &result.trials[0];
+        assert_eq!(best.strategy.series(), [Series::Sma(best.lengths[0]), Series::Sma(best.lengths[1])]);
+        assert_eq!(result.describe(best), format!("SMA{} SMA{}", best.lengths[0], best.lengths[1]));
+
+        let macd = builtin().into_iter().find(|s| s.name == "MACD cross").unwrap();
+        assert!(optimize(&macd, &sd, &StrategySettings::default(), sweep, 30.0).is_err());
+        assert!(optimize(golden, &sd, &StrategySettings::default(), Sweep { from: 1, to: 200, step: 1 }, 30.0).is_err());
+    }
+}
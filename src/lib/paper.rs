@@ -0,0 +1,163 @@
+//! Paper trading: a pretend account that buys and sells at the prices on
+//! screen, so signals can be tried without risking money. It only goes
+//! long, fills every order in full at the price given, and pays a flat
+//! commission per fill.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Fills kept in the account's history; the oldest go first.
+const MAX_FILLS: usize = 1000;
+
+/// Share amounts closer than this are the same, so selling a fractional
+/// holding in full leaves nothing behind.
+const EPSILON: f64 = 1e-9;
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+impl Side {
+    pub fn label(self) -> &'static str {
+        match self {
+            Side::Buy => "Buy",
+            Side::Sell => "Sell",
+        }
+    }
+}
+
+/// An order as it was filled.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Fill {
+    pub symbol: String,
+    pub side: Side,
+    pub shares: f64,
+    pub price: f64,
+    pub commission: f64,
+    /// Unix seconds.
+    pub timestamp: i64,
+    /// For sells, the gain over the average cost, net of this fill's
+    /// commission.
+    #[serde(default)]
+    pub realized: Option<f64>,
+}
+
+/// Shares held of a symbol.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub struct PaperPosition {
+    pub shares: f64,
+    /// Average price paid per share, buy commissions included.
+    pub cost: f64,
+}
+
+/// The account: cash, what it holds and every fill so far.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct PaperAccount {
+    /// Cash the account was opened with.
+    pub starting_cash: f64,
+    pub cash: f64,
+    #[serde(default)]
+    pub positions: BTreeMap<String, PaperPosition>,
+    #[serde(default)]
+    pub fills: Vec<Fill>,
+}
+
+impl PaperAccount {
+    pub fn new(cash: f64) -> PaperAccount {
+        PaperAccount { starting_cash: cash, cash, positions: BTreeMap::new(), fills: Vec::new() }
+    }
+
+    /// Whole shares the cash buys at `price` after `commission`.
+    pub fn affordable(&self, price: f64, commission: f64) -> f64 {
+        if price > 0.0 { ((self.cash - commission) / price).floor().max(0.0) } else { 0.0 }
+    }
+
+    /// Shares held of `symbol`.
+    pub fn held(&self, symbol: &str) -> f64 {
+        self.positions.get(symbol).map_or(0.0, |p| p.shares)
+    }
+
+    /// Buy `shares` of `symbol` at `price`, or say why not.
+    pub fn buy(&mut self, symbol: &str, shares: f64, price: f64, commission: f64, timestamp: i64) -> Result<&Fill, String> {
+        check(shares, price)?;
+        let total = shares * price + commission;
+        if total > self.cash + EPSILON {
+            return Err(format!("that costs ${:.2}, more than the ${:.2} cash", total, self.cash));
+        }
+        self.cash -= total;
+        let position = self.positions.entry(symbol.to_string()).or_insert(PaperPosition { shares: 0.0, cost: 0.0 });
+        position.cost = (position.shares * position.cost + total) / (position.shares + shares);
+        position.shares += shares;
+        Ok(self.record(Fill { symbol: symbol.to_string(), side: Side::Buy, shares, price, commission, timestamp, realized: None }))
+    }
+
+    /// Sell `shares` of `symbol` at `price`, or say why not.
+    pub fn sell(&mut self, symbol: &str, shares: f64, price: f64, commission: f64, timestamp: i64) -> Result<&Fill, String> {
+        check(shares, price)?;
+        let Some(position) = self.positions.get_mut(symbol).filter(|p| shares <= p.shares + EPSILON) else {
+            return Err(format!("only {} {} held", self.held(symbol), symbol));
+        };
+        let shares = shares.min(position.shares);
+        let realized = shares * (price - position.cost) - commission;
+        position.shares -= shares;
+        if position.shares <= EPSILON {
+            self.positions.remove(symbol);
+        }
+        self.cash += shares * price - commission;
+        Ok(self.record(Fill { symbol: symbol.to_string(), side: Side::Sell, shares, price, commission, timestamp, realized: Some(realized) }))
+    }
+
+    fn record(&mut self, fill: Fill) -> &Fill {
+        self.fills.push(fill);
+        let excess = self.fills.len().saturating_sub(MAX_FILLS);
+        self.fills.drain(..excess);
+        &self.fills[self.fills.len() - 1]
+    }
+
+    /// Cash plus every position at `price`, or at its cost where there is
+    /// no price.
+    pub fn equity(&self, price: impl Fn(&str) -> Option<f64>) -> f64 {
+        self.cash + self.positions.iter().map(|(symbol, p)| p.shares * price(symbol).unwrap_or(p.cost)).sum::<f64>()
+    }
+}
+
+fn check(shares: f64, price: f64) -> Result<(), String> {
+    if !(shares.is_finite() && shares > 0.0) {
+        return Err("the number of shares must be above zero".into());
+    }
+    if !(price.is_finite() && price > 0.0) {
+        return Err("there is no price to fill at".into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buys_and_sells_move_cash_and_cost() {
+        let mut account = PaperAccount::new(1000.0);
+        account.buy("AAA", 5.0, 100.0, 1.0, 0).unwrap();
+        account.buy("AAA", 3.0, 120.0, 1.0, 1).unwrap();
+        assert!((account.cash - (1000.0 - 501.0 - 361.0)).abs() < 1e-9);
+        let position = account.positions["AAA"];
+        assert_eq!(position.shares, 8.0);
+        assert!((position.cost - 862.0 / 8.0).abs() < 1e-9);
+        assert_eq!(account.affordable(100.0, 1.0), 1.0);
+        assert!(account.buy("AAA", 2.0, 100.0, 1.0, 2).is_err());
+
+        let realized = account.sell("AAA", 4.0, 110.0, 1.0, 3).unwrap().realized.unwrap();
+        assert!((realized - (4.0 * (110.0 - 862.0 / 8.0) - 1.0)).abs() < 1e-9);
+        assert!(account.sell("AAA", 5.0, 110.0, 1.0, 4).is_err());
+        account.sell("AAA", 4.0, 90.0, 0.0, 5).unwrap();
+        assert!(account.positions.is_empty());
+        assert_eq!(account.fills.len(), 4);
+        assert!((account.equity(|_| None) - account.cash).abs() < 1e-9);
+        assert!(account.buy("BBB", 0.0, 10.0, 0.0, 6).is_err());
+    }
+}
@@ -0,0 +1,254 @@
+//! Minimal Parquet writer: one row group, required columns, PLAIN encoding
+//! and no compression. Enough for tools such as polars, pandas and DuckDB
+//! to read tables of bars; the format is described at
+//! <https://parquet.apache.org/docs/file-format/>.
+
+const MAGIC: &[u8] = b"PAR1";
+
+/// Values of one column, all the same length.
+pub enum ColumnData {
+    /// UTF-8 strings.
+    Utf8(Vec<String>),
+    /// Milliseconds since the Unix epoch.
+    TimestampMillis(Vec<i64>),
+    Int64(Vec<i64>),
+    Double(Vec<f64>),
+}
+
+impl ColumnData {
+    fn len(&self) -> usize {
+        match self {
+            ColumnData::Utf8(v) => v.len(),
+            ColumnData::TimestampMillis(v) | ColumnData::Int64(v) => v.len(),
+            ColumnData::Double(v) => v.len(),
+        }
+    }
+
+    /// Parquet physical type: INT64 = 2, DOUBLE = 5, BYTE_ARRAY = 6.
+    fn physical_type(&self) -> i32 {
+        match self {
+            ColumnData::Utf8(_) => 6,
+            ColumnData::TimestampMillis(_) | ColumnData::Int64(_) => 2,
+            ColumnData::Double(_) => 5,
+        }
+    }
+
+    /// Parquet converted type: UTF8 = 0, TIMESTAMP_MILLIS = 9.
+    fn converted_type(&self) -> Option<i32> {
+        match self {
+            ColumnData::Utf8(_) => Some(0),
+            ColumnData::TimestampMillis(_) => Some(9),
+            _ => None,
+        }
+    }
+
+    fn plain_encoded(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        match self {
+            ColumnData::Utf8(values) => {
+                for v in values {
+                    out.extend_from_slice(&(v.len() as u32).to_le_bytes());
+                    out.extend_from_slice(v.as_bytes());
+                }
+            }
+            ColumnData::TimestampMillis(values) | ColumnData::Int64(values) => {
+                for v in values {
+                    out.extend_from_slice(&v.to_le_bytes());
+                }
+            }
+            ColumnData::Double(values) => {
+                for v in values {
+                    out.extend_from_slice(&v.to_le_bytes());
+                }
+            }
+        }
+        out
+    }
+}
+
+// Thrift compact protocol type ids.
+const T_I32: u8 = 5;
+const T_I64: u8 = 6;
+const T_BINARY: u8 = 8;
+const T_LIST: u8 = 9;
+const T_STRUCT: u8 = 12;
+
+/// Writes Thrift compact protocol structs, which is how Parquet encodes its
+/// page headers and footer.
+#[derive(Default)]
+struct Thrift {
+    out: Vec<u8>,
+    /// Last field id written in each open struct.
+    last_ids: Vec<i16>,
+}
+
+impl Thrift {
+    fn varint(&mut self, mut v: u64) {
+        while v >= 0x80 {
+            self.out.push((v as u8) | 0x80);
+            v >>= 7;
+        }
+        self.out.push(v as u8);
+    }
+
+    fn zigzag(&mut self, v: i64) {
+        self.varint(((v << 1) ^ (v >> 63)) as u64);
+    }
+
+    fn field(&mut self, id: i16, kind: u8) {
+        let last = self.last_ids.last_mut().expect("field written outside a struct");
+        let delta = id - *last;
+        *last = id;
+        if (1..=15).contains(&delta) {
+            self.out.push(((delta as u8) << 4) | kind);
+        } else {
+            self.out.push(kind);
+            self.zigzag(id as i64);
+        }
+    }
+
+    fn begin(&mut self) {
+        self.last_ids.push(0);
+    }
+
+    fn end(&mut self) {
+        self.out.push(0);
+        self.last_ids.pop();
+    }
+
+    fn i32(&mut self, id: i16, v: i32) {
+        self.field(id, T_I32);
+        self.zigzag(v as i64);
+    }
+
+    fn i64(&mut self, id: i16, v: i64) {
+        self.field(id, T_I64);
+        self.zigzag(v);
+    }
+
+    fn string(&mut self, id: i16, v: &str) {
+        self.field(id, T_BINARY);
+        self.varint(v.len() as u64);
+        self.out.extend_from_slice(v.as_bytes());
+    }
+
+    fn list(&mut self, id: i16, kind: u8, len: usize) {
+        self.field(id, T_LIST);
+        if len < 15 {
+            self.out.push(((len as u8) << 4) | kind);
+        } else {
+            self.out.push(0xf0 | kind);
+            self.varint(len as u64);
+        }
+    }
+
+    fn struct_field(&mut self, id: i16) {
+        self.field(id, T_STRUCT);
+        self.begin();
+    }
+}
+
+/// A Parquet file holding `columns` as named, all required and of equal
+/// length.
+pub fn write_table(columns: &[(&str, ColumnData)]) -> Vec<u8> {
+    let rows = columns.first().map_or(0, |(_, c)| c.len());
+    debug_assert!(columns.iter().all(|(_, c)| c.len() == rows));
+
+    let mut file = MAGIC.to_vec();
+    // Offset and total size (header included) of each column chunk
+    let mut chunks = Vec::new();
+    for (_, column) in columns {
+        let data = column.plain_encoded();
+        let mut header = Thrift::default();
+        header.begin();
+        header.i32(1, 0); // DATA_PAGE
+        header.i32(2, data.len() as i32);
+        header.i32(3, data.len() as i32);
+        header.struct_field(5);
+        header.i32(1, rows as i32);
+        header.i32(2, 0); // PLAIN
+        header.i32(3, 3); // RLE definition levels (none: the column is required)
+        header.i32(4, 3); // RLE repetition levels
+        header.end();
+        header.end();
+
+        let offset = file.len() as i64;
+        file.extend_from_slice(&header.out);
+        file.extend_from_slice(&data);
+        chunks.push((offset, (header.out.len() + data.len()) as i64));
+    }
+
+    let mut meta = Thrift::default();
+    meta.begin();
+    meta.i32(1, 1); // version
+    meta.list(2, T_STRUCT, columns.len() + 1);
+    meta.begin();
+    meta.string(4, "schema");
+    meta.i32(5, columns.len() as i32);
+    meta.end();
+    for (name, column) in columns {
+        meta.begin();
+        meta.i32(1, column.physical_type());
+        meta.i32(3, 0); // REQUIRED
+        meta.string(4, name);
+        if let Some(converted) = column.converted_type() {
+            meta.i32(6, converted);
+        }
+        meta.end();
+    }
+    meta.i64(3, rows as i64);
+    meta.list(4, T_STRUCT, 1);
+    meta.begin();
+    meta.list(1, T_STRUCT, columns.len());
+    for ((name, column), &(offset, size)) in columns.iter().zip(&chunks) {
+        meta.begin();
+        meta.i64(2, offset);
+        meta.struct_field(3);
+        meta.i32(1, column.physical_type());
+        meta.list(2, T_I32, 1);
+        meta.zigzag(0); // PLAIN
+        meta.list(3, T_BINARY, 1);
+        meta.varint(name.len() as u64);
+        meta.out.extend_from_slice(name.as_bytes());
+        meta.i32(4, 0); // UNCOMPRESSED
+        meta.i64(5, rows as i64);
+        meta.i64(6, size);
+        meta.i64(7, size);
+        meta.i64(9, offset);
+        meta.end();
+        meta.end();
+    }
+    meta.i64(2, chunks.iter().map(|&(_, size)| size).sum());
+    meta.i64(3, rows as i64);
+    meta.end();
+    meta.string(6, "bstock");
+    meta.end();
+
+    file.extend_from_slice(&meta.out);
+    file.extend_from_slice(&(meta.out.len() as u32).to_le_bytes());
+    file.extend_from_slice(MAGIC);
+    file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_footer_frames_the_metadata() {
+        let file = write_table(&[
+            ("symbol", ColumnData::Utf8(vec!["A".into(), "BB".into()])),
+            ("close", ColumnData::Double(vec![1.5, 2.5])),
+        ]);
+        assert_eq!(&file[..4], MAGIC);
+        assert_eq!(&file[file.len() - 4..], MAGIC);
+        let meta_len = u32::from_le_bytes(file[file.len() - 8..file.len() - 4].try_into().unwrap()) as usize;
+        // Pages: "PAR1", then each chunk's header and PLAIN values
+        let meta_start = file.len() - 8 - meta_len;
+        assert!(meta_start > 4 + 11 + 16);
+        // The footer struct ends with its stop byte
+        assert_eq!(file[file.len() - 9], 0);
+        // The BYTE_ARRAY values are length-prefixed
+        assert!(file.windows(6).any(|w| w == [2, 0, 0, 0, b'B', b'B']));
+    }
+}
@@ -1,13 +1,81 @@
-use crate::lib::{config::StockConfig, error::AppError};
+//! Everything kept on disk: the config file of each profile, and the data
+//! files shared by all of them (paper account, portfolio, prediction and
+//! signal logs, imported prices and the history cache).
+//!
+//! The history cache is plain JSON, one file per symbol and range, rather
+//! than an SQLite database: it needs no native library, and the files can
+//! be read or deleted by hand. The price is that every save rewrites the
+//! whole series, even when only the latest bars are new, and that nothing
+//! can be queried across files — a cached series is loaded whole or not at
+//! all. Series are at most a few thousand bars, so both stay cheap.
+
+use crate::lib::{
+    accuracy::PredictionLog,
+    signals::SignalHistory,
+    paper::PaperAccount,
+    portfolio::Portfolio,
+    config::{ForecastSettings, PortfolioSettings, ProviderSettings, RefreshSettings, SessionState, StockConfig, StrategySettings, SymbolNote, SymbolProfile, UiSettings},
+    error::AppError,
+    secrets,
+    stock_data::StockData,
+    sync::SyncConfig,
+    toml,
+};
 use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// Top of every `config.toml` bstock writes. Saving a setting from the app
+/// writes the whole file from the config it holds, so say so where a hand
+/// editor will see it.
+const TOML_HEADER: &str = "\
+# bstock config. Edit freely while bstock is closed; any setting changed
+# from within bstock rewrites this whole file, dropping comments (this one
+# comes back) and putting keys back in bstock's order.
+
+";
+
+/// Name of the watchlist in configs written before there were several.
+pub const DEFAULT_WATCHLIST: &str = "default";
+
+fn default_watchlist() -> String {
+    DEFAULT_WATCHLIST.into()
+}
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AppConfig {
+    /// The active watchlist.
     pub stock_config: StockConfig,
+    #[serde(default = "default_watchlist")]
+    pub active_watchlist: String,
+    /// Every other watchlist by name; switching swaps one with `stock_config`.
+    #[serde(default)]
+    pub watchlists: BTreeMap<String, StockConfig>,
     pub last_updated: Option<u64>, // Unix timestamp
+    #[serde(default)]
+    pub sync: Option<SyncConfig>,
+    #[serde(default)]
+    pub ui: UiSettings,
+    /// Aliases and notes by symbol, shared by every watchlist.
+    #[serde(default)]
+    pub notes: BTreeMap<String, SymbolNote>,
+    /// Sector metadata by symbol, kept so it is only looked up once.
+    #[serde(default)]
+    pub profiles: BTreeMap<String, SymbolProfile>,
+    #[serde(default)]
+    pub session: SessionState,
+    #[serde(default)]
+    pub refresh: RefreshSettings,
+    #[serde(default)]
+    pub providers: ProviderSettings,
+    #[serde(default)]
+    pub forecast: ForecastSettings,
+    #[serde(default)]
+    pub strategy: StrategySettings,
+    #[serde(default)]
+    pub portfolio: PortfolioSettings,
 }
 
 impl Default for AppConfig {
@@ -26,39 +94,127 @@ impl Default for AppConfig {
                 ],
                 analysis_period_days: 90,
             },
+            active_watchlist: default_watchlist(),
+            watchlists: BTreeMap::new(),
             last_updated: None,
+            sync: None,
+            ui: UiSettings::default(),
+            notes: BTreeMap::new(),
+            profiles: BTreeMap::new(),
+            session: SessionState::default(),
+            refresh: RefreshSettings::default(),
+            providers: ProviderSettings::default(),
+            forecast: ForecastSettings::default(),
+            strategy: StrategySettings::default(),
+            portfolio: PortfolioSettings::default(),
         }
     }
 }
 
 
+/// Name of the profile whose config lives directly in the config dir.
+pub const DEFAULT_PROFILE: &str = "default";
+
 pub struct PersistenceManager {
+    /// `config.json`, used until a `config.toml` exists beside it.
     config_file: PathBuf,
+    toml_file: PathBuf,
+    /// Where snapshots and cached history go, shared by every profile.
+    data_dir: PathBuf,
+    /// Project config dir; other profiles live under `profiles/<name>` in it.
+    root_dir: PathBuf,
+    profile: String,
 }
 
 impl PersistenceManager {
-    pub fn new() -> Result<Self, AppError> {
-        // Use ProjectDirs to get the appropriate config directory for the OS
-        let project_dirs = ProjectDirs::from("com", "bstock", "bstock")
-            .ok_or_else(|| AppError::Io(std::io::Error::new(
-                std::io::ErrorKind::NotFound,
-                "Could not determine project directories"
-            )))?;
-
-        let config_dir = project_dirs.config_dir().to_path_buf();
-        let config_file = config_dir.join("config.json");
+    /// Storage for the default profile in `config_dir`, or in the OS config
+    /// directory (e.g. `~/.config/bstock`) when that is `None`. Snapshots and
+    /// cached history go in an overridden directory too, so its state stays
+    /// self-contained.
+    pub fn new(config_dir: Option<PathBuf>) -> Result<Self, AppError> {
+        let (root_dir, data_dir) = match config_dir {
+            Some(dir) => (dir.clone(), dir),
+            None => {
+                // Use ProjectDirs to get the appropriate config directory for the OS
+                let project_dirs = ProjectDirs::from("com", "bstock", "bstock")
+                    .ok_or_else(|| AppError::Io(std::io::Error::new(
+                        std::io::ErrorKind::NotFound,
+                        "Could not determine project directories"
+                    )))?;
+                // Snapshots and history are user data rather than configuration;
+                // their directories are only created when first written.
+                (project_dirs.config_dir().to_path_buf(), project_dirs.data_dir().to_path_buf())
+            }
+        };
+        Self::in_dir(root_dir.clone(), root_dir, data_dir, DEFAULT_PROFILE)
+    }
 
+    fn in_dir(config_dir: PathBuf, root_dir: PathBuf, data_dir: PathBuf, profile: &str) -> Result<Self, AppError> {
         // Create config directory if it doesn't exist
         fs::create_dir_all(&config_dir)
             .map_err(AppError::Io)?;
 
         Ok(PersistenceManager {
-            config_file,
+            config_file: config_dir.join("config.json"),
+            toml_file: config_dir.join("config.toml"),
+            data_dir,
+            root_dir,
+            profile: profile.to_string(),
         })
     }
 
+    /// Storage for profile `name` in the same config directory, each with
+    /// its own watchlists and settings. The directory is created on first use.
+    pub fn open_profile(&self, name: &str) -> Result<Self, AppError> {
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            return Err(AppError::Profile(format!(
+                "'{}' is not a valid profile name (use letters, digits, - and _)", name
+            )));
+        }
+        let config_dir = if name == DEFAULT_PROFILE {
+            self.root_dir.clone()
+        } else {
+            self.root_dir.join("profiles").join(name)
+        };
+        Self::in_dir(config_dir, self.root_dir.clone(), self.data_dir.clone(), name)
+    }
+
+    pub fn profile(&self) -> &str {
+        &self.profile
+    }
+
+    /// Every profile, the default first and the rest by name.
+    pub fn profile_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(self.root_dir.join("profiles"))
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter(|entry| entry.path().is_dir())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .filter(|name| name != DEFAULT_PROFILE)
+            .collect();
+        names.sort();
+        names.insert(0, DEFAULT_PROFILE.to_string());
+        names
+    }
+
+    /// The config file in use: `config.toml` if there is one, otherwise
+    /// `config.json`.
+    pub fn config_path(&self) -> &Path {
+        if self.toml_file.exists() { &self.toml_file } else { &self.config_file }
+    }
+
+    /// The config, or the default where there is no file yet. A file that
+    /// does not parse is an error, never the default: the `save_*` helpers
+    /// load the whole config to change one section, and would otherwise
+    /// write a mistyped file over with defaults.
     pub fn load_config(&self) -> Result<AppConfig, AppError> {
-        if self.config_file.exists() {
+        if self.toml_file.exists() {
+            let config_content = fs::read_to_string(&self.toml_file)
+                .map_err(AppError::Io)?;
+            let value = toml::from_toml(&config_content)?;
+            Ok(serde_json::from_value(value)?)
+        } else if self.config_file.exists() {
             let config_content = fs::read_to_string(&self.config_file)
                 .map_err(AppError::Io)?;
             let app_config: AppConfig = serde_json::from_str(&config_content)
@@ -71,6 +227,11 @@ impl PersistenceManager {
     }
 
     pub fn save_config(&self, config: &AppConfig) -> Result<(), AppError> {
+        if self.toml_file.exists() {
+            let value = serde_json::to_value(config)?;
+            fs::write(&self.toml_file, format!("{}{}", TOML_HEADER, toml::to_toml(&value)?))?;
+            return Ok(());
+        }
         let config_content = serde_json::to_string_pretty(config)
             .map_err(AppError::ConfigParseError)?;
         fs::write(&self.config_file, config_content)
@@ -78,19 +239,412 @@ impl PersistenceManager {
         Ok(())
     }
 
+    /// Rewrite `config.json` as `config.toml`, which is used from then on.
+    /// The JSON file is kept as `config.json.bak`. Returns the new path.
+    pub fn migrate_to_toml(&self) -> Result<PathBuf, AppError> {
+        if self.toml_file.exists() {
+            return Err(AppError::Toml(format!("{} already exists", self.toml_file.display())));
+        }
+        let config = self.load_config()?;
+        let value = serde_json::to_value(&config)?;
+        fs::write(&self.toml_file, format!("{}{}", TOML_HEADER, toml::to_toml(&value)?))?;
+        if self.config_file.exists() {
+            fs::rename(&self.config_file, self.config_file.with_extension("json.bak"))?;
+        }
+        Ok(self.toml_file.clone())
+    }
+
     pub fn save_stock_config(&self, stock_config: &StockConfig) -> Result<(), AppError> {
-        let new_config = AppConfig {
-            stock_config: stock_config.clone(),
-            last_updated: Some(std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_secs()),
-        };
+        // Keep the other sections (e.g. sync settings) as they are
+        let mut new_config = self.load_config()?;
+        new_config.stock_config = stock_config.clone();
+        new_config.last_updated = Some(std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs());
         self.save_config(&new_config)
     }
 
-    pub fn get_stock_config(&self) -> Result<StockConfig, AppError> {
-        let config = self.load_config().unwrap_or_else(|_| AppConfig::default());
+    /// The active watchlist's name and every name, sorted.
+    pub fn watchlist_names(&self) -> (String, Vec<String>) {
+        let config = self.load_config().unwrap_or_default();
+        let mut names: Vec<String> = config.watchlists.into_keys().collect();
+        names.push(config.active_watchlist.clone());
+        names.sort();
+        (config.active_watchlist, names)
+    }
+
+    /// Make `name` the active watchlist, creating it empty if it does not
+    /// exist yet, and return its symbols.
+    pub fn switch_watchlist(&self, name: &str) -> Result<StockConfig, AppError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(AppError::Watchlist("the name is empty".into()));
+        }
+        let mut config = self.load_config()?;
+        if config.active_watchlist != name {
+            let target = config.watchlists.remove(name).unwrap_or_else(|| StockConfig {
+                symbols: Vec::new(),
+                analysis_period_days: config.stock_config.analysis_period_days,
+            });
+            let previous = std::mem::replace(&mut config.stock_config, target);
+            let previous_name = std::mem::replace(&mut config.active_watchlist, name.to_string());
+            config.watchlists.insert(previous_name, previous);
+            self.save_config(&config)?;
+        }
         Ok(config.stock_config)
     }
-}
\ No newline at end of file
+
+    /// Remove a watchlist other than the active one.
+    pub fn delete_watchlist(&self, name: &str) -> Result<(), AppError> {
+        let mut config = self.load_config()?;
+        if config.active_watchlist == name {
+            return Err(AppError::Watchlist(format!("{} is the active watchlist", name)));
+        }
+        if config.watchlists.remove(name).is_none() {
+            return Err(AppError::Watchlist(format!("no watchlist named {}", name)));
+        }
+        self.save_config(&config)
+    }
+
+    pub fn get_ui_settings(&self) -> UiSettings {
+        self.load_config().map(|c| c.ui).unwrap_or_default()
+    }
+
+    pub fn save_ui_settings(&self, ui: &UiSettings) -> Result<(), AppError> {
+        let mut config = self.load_config()?;
+        config.ui = ui.clone();
+        self.save_config(&config)
+    }
+
+    pub fn get_refresh_settings(&self) -> RefreshSettings {
+        self.load_config().map(|c| c.refresh).unwrap_or_default()
+    }
+
+    pub fn save_refresh_settings(&self, refresh: RefreshSettings) -> Result<(), AppError> {
+        let mut config = self.load_config()?;
+        config.refresh = refresh;
+        self.save_config(&config)
+    }
+
+    pub fn get_provider_settings(&self) -> ProviderSettings {
+        self.load_config().map(|c| c.providers).unwrap_or_default()
+    }
+
+    pub fn save_provider_settings(&self, providers: &ProviderSettings) -> Result<(), AppError> {
+        let mut config = self.load_config()?;
+        config.providers = providers.clone();
+        self.save_config(&config)
+    }
+
+    pub fn get_forecast_settings(&self) -> ForecastSettings {
+        self.load_config().map(|c| c.forecast).unwrap_or_default()
+    }
+
+    pub fn save_forecast_settings(&self, forecast: &ForecastSettings) -> Result<(), AppError> {
+        let mut config = self.load_config()?;
+        config.forecast = forecast.clone();
+        self.save_config(&config)
+    }
+
+    pub fn get_strategy_settings(&self) -> StrategySettings {
+        self.load_config().map(|c| c.strategy).unwrap_or_default()
+    }
+
+    pub fn get_portfolio_settings(&self) -> PortfolioSettings {
+        self.load_config().map(|c| c.portfolio).unwrap_or_default()
+    }
+
+    /// Save `provider`'s API key in the OS credential store, dropping any
+    /// copy in the config file, or in the config file where there is no
+    /// store. Returns why the store could not take it, if it could not.
+    pub fn save_api_key(&self, provider: &str, key: &str) -> Result<Option<AppError>, AppError> {
+        let mut providers = self.get_provider_settings();
+        let refused = match secrets::set(provider, key) {
+            Ok(()) => {
+                providers.api_keys.remove(provider);
+                None
+            }
+            Err(e) => {
+                providers.api_keys.insert(provider.to_string(), key.to_string());
+                Some(e)
+            }
+        };
+        self.save_provider_settings(&providers)?;
+        Ok(refused)
+    }
+
+    /// Forget `provider`'s API key, in the credential store and the config.
+    pub fn remove_api_key(&self, provider: &str) -> Result<(), AppError> {
+        let mut providers = self.get_provider_settings();
+        if providers.api_keys.remove(provider).is_some() {
+            self.save_provider_settings(&providers)?;
+        }
+        secrets::delete(provider)
+    }
+
+    pub fn get_session(&self) -> SessionState {
+        self.load_config().map(|c| c.session).unwrap_or_default()
+    }
+
+    pub fn save_session(&self, session: &SessionState) -> Result<(), AppError> {
+        let mut config = self.load_config()?;
+        config.session = session.clone();
+        self.save_config(&config)
+    }
+
+    pub fn get_notes(&self) -> BTreeMap<String, SymbolNote> {
+        self.load_config().map(|c| c.notes).unwrap_or_default()
+    }
+
+    /// Store `symbol`'s alias and note; an empty one removes the entry.
+    pub fn save_note(&self, symbol: &str, note: &SymbolNote) -> Result<(), AppError> {
+        let mut config = self.load_config()?;
+        if note.is_empty() {
+            config.notes.remove(symbol);
+        } else {
+            config.notes.insert(symbol.to_string(), note.clone());
+        }
+        self.save_config(&config)
+    }
+
+    pub fn get_profiles(&self) -> BTreeMap<String, SymbolProfile> {
+        self.load_config().map(|c| c.profiles).unwrap_or_default()
+    }
+
+    pub fn save_profile(&self, symbol: &str, profile: &SymbolProfile) -> Result<(), AppError> {
+        let mut config = self.load_config()?;
+        config.profiles.insert(symbol.to_string(), profile.clone());
+        self.save_config(&config)
+    }
+
+    pub fn snapshot_dir(&self) -> PathBuf {
+        self.data_dir.join("snapshots")
+    }
+
+    pub fn export_dir(&self) -> PathBuf {
+        self.data_dir.join("exports")
+    }
+
+    pub fn report_dir(&self) -> PathBuf {
+        self.data_dir.join("reports")
+    }
+
+    fn prediction_log_path(&self) -> PathBuf {
+        self.data_dir.join("predictions.json")
+    }
+
+    /// Forecasts made so far and how they turned out, shared by every
+    /// profile. A file that does not parse is an error, so the next save
+    /// does not replace the accuracy history with an empty one.
+    pub fn prediction_log(&self) -> Result<PredictionLog, AppError> {
+        Ok(read_data_file(&self.prediction_log_path())?.unwrap_or_default())
+    }
+
+    pub fn save_prediction_log(&self, log: &PredictionLog) -> Result<(), AppError> {
+        fs::create_dir_all(&self.data_dir)?;
+        fs::write(self.prediction_log_path(), serde_json::to_string(log)?)?;
+        Ok(())
+    }
+
+    fn signal_history_path(&self) -> PathBuf {
+        self.data_dir.join("signals.json")
+    }
+
+    /// Signals that have fired so far, shared by every profile. A file
+    /// that does not parse is an error, so the next save does not replace
+    /// the history with only the signals since.
+    pub fn signal_history(&self) -> Result<SignalHistory, AppError> {
+        Ok(read_data_file(&self.signal_history_path())?.unwrap_or_default())
+    }
+
+    pub fn save_signal_history(&self, history: &SignalHistory) -> Result<(), AppError> {
+        fs::create_dir_all(&self.data_dir)?;
+        fs::write(self.signal_history_path(), serde_json::to_string(history)?)?;
+        Ok(())
+    }
+
+    fn paper_account_path(&self) -> PathBuf {
+        self.data_dir.join("paper.json")
+    }
+
+    /// The paper trading account, shared by every profile; `None` until
+    /// the first order. A file that does not parse is an error, so the
+    /// next order does not replace it with a fresh account.
+    pub fn paper_account(&self) -> Result<Option<PaperAccount>, AppError> {
+        read_data_file(&self.paper_account_path())
+    }
+
+    pub fn save_paper_account(&self, account: &PaperAccount) -> Result<(), AppError> {
+        fs::create_dir_all(&self.data_dir)?;
+        fs::write(self.paper_account_path(), serde_json::to_string(account)?)?;
+        Ok(())
+    }
+
+    fn portfolio_path(&self) -> PathBuf {
+        self.data_dir.join("portfolio.json")
+    }
+
+    /// The lots held, shared by every profile. A file that does not parse
+    /// is an error, so the next save does not replace it with an empty
+    /// portfolio.
+    pub fn portfolio(&self) -> Result<Portfolio, AppError> {
+        Ok(read_data_file(&self.portfolio_path())?.unwrap_or_default())
+    }
+
+    pub fn save_portfolio(&self, portfolio: &Portfolio) -> Result<(), AppError> {
+        fs::create_dir_all(&self.data_dir)?;
+        fs::write(self.portfolio_path(), serde_json::to_string(portfolio)?)?;
+        Ok(())
+    }
+
+    pub fn history_store(&self) -> HistoryStore {
+        HistoryStore { dir: self.data_dir.join("history") }
+    }
+
+    pub fn get_stock_config(&self) -> Result<StockConfig, AppError> {
+        Ok(self.load_config()?.stock_config)
+    }
+
+    fn imported_path(&self, symbol: &str) -> PathBuf {
+        self.data_dir.join("imported").join(json_file_name(symbol))
+    }
+
+    /// Bars imported from a CSV for `symbol` and the Unix time of the
+    /// import. Such symbols are shown from these bars instead of fetched.
+    pub fn imported_prices(&self, symbol: &str) -> Option<(StockData, i64)> {
+        let content = fs::read_to_string(self.imported_path(symbol)).ok()?;
+        let imported: CachedHistory = serde_json::from_str(&content).ok()?;
+        (!imported.stock_data.is_empty()).then_some((imported.stock_data, imported.saved_at))
+    }
+
+    pub fn save_imported_prices(&self, symbol: &str, stock_data: &StockData) -> Result<(), AppError> {
+        let path = self.imported_path(symbol);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let imported = CachedHistory { saved_at: chrono::Utc::now().timestamp(), stock_data: stock_data.clone() };
+        fs::write(path, serde_json::to_string(&imported)?)?;
+        Ok(())
+    }
+}
+/// The JSON at `path`, or `None` where there is no file yet.
+fn read_data_file<T: serde::de::DeserializeOwned>(path: &Path) -> Result<Option<T>, AppError> {
+    match fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content)
+            .map(Some)
+            .map_err(|e| AppError::DataFile(format!("{}: {}", path.display(), e))),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// `<name>.json`, with characters that have no place in a file name
+/// replaced.
+fn json_file_name(name: &str) -> String {
+    let name: String = name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '^' | '=') { c } else { '_' })
+        .collect();
+    format!("{}.json", name)
+}
+
+/// A cached series and when it was fetched.
+#[derive(Serialize, Deserialize)]
+struct CachedHistory {
+    /// Unix timestamp.
+    saved_at: i64,
+    stock_data: StockData,
+}
+
+/// Bars fetched per symbol and range, kept on disk so a restart shows them
+/// at once and only the bars since need fetching. One JSON file per series,
+/// rewritten whole on each save (see the module docs).
+#[derive(Clone)]
+pub struct HistoryStore {
+    dir: PathBuf,
+}
+
+impl HistoryStore {
+    fn path(&self, symbol: &str, range: &str) -> PathBuf {
+        self.dir.join(json_file_name(&format!("{}-{}", symbol, range)))
+    }
+
+    /// The cached bars for `symbol` over `range` (a label such as "3M") and
+    /// the Unix time they were saved, if any were.
+    pub fn load(&self, symbol: &str, range: &str) -> Option<(StockData, i64)> {
+        let content = fs::read_to_string(self.path(symbol, range)).ok()?;
+        let cached: CachedHistory = serde_json::from_str(&content).ok()?;
+        (!cached.stock_data.is_empty()).then_some((cached.stock_data, cached.saved_at))
+    }
+
+    pub fn save(&self, symbol: &str, range: &str, stock_data: &StockData) -> Result<(), AppError> {
+        fs::create_dir_all(&self.dir)?;
+        let cached = CachedHistory { saved_at: chrono::Utc::now().timestamp(), stock_data: stock_data.clone() };
+        fs::write(self.path(symbol, range), serde_json::to_string(&cached)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profiles_are_isolated_in_the_config_dir() {
+        let dir = std::env::temp_dir().join(format!("bstock-test-{}", std::process::id()));
+        let pm = PersistenceManager::new(Some(dir.clone())).unwrap();
+        let crypto = pm.open_profile("crypto").unwrap();
+        let config = StockConfig { symbols: vec!["BTC-USD".into()], analysis_period_days: 30 };
+        crypto.save_stock_config(&config).unwrap();
+
+        assert!(dir.join("profiles/crypto/config.json").exists());
+        assert_eq!(crypto.get_stock_config().unwrap().symbols, vec!["BTC-USD"]);
+        assert_ne!(pm.get_stock_config().unwrap().symbols, vec!["BTC-USD"]);
+        assert_eq!(pm.profile_names(), vec!["default", "crypto"]);
+        assert!(pm.open_profile("../escape").is_err());
+
+        let store = crypto.history_store();
+        assert!(store.load("BTC-USD", "3M").is_none());
+        let mut bars = StockData::new();
+        bars.add_point(1_700_000_000, 1.0, 2.0, 0.5, 1.5, 10);
+        store.save("BTC-USD", "3M", &bars).unwrap();
+        assert_eq!(store.load("BTC-USD", "3M").unwrap().0.closes, vec![1.5]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_a_config_that_does_not_parse_is_never_overwritten() {
+        let dir = std::env::temp_dir().join(format!("bstock-test-broken-{}", std::process::id()));
+        let pm = PersistenceManager::new(Some(dir.clone())).unwrap();
+        let text = "active_watchlist = \"tech\"\n\n[watchlists.dividends]\nsymbols = [\"KO\"]\nanalysis_period_days = 90\n\n[ui]\nplain = tru\n";
+        fs::write(dir.join("config.toml"), text).unwrap();
+
+        assert!(pm.load_config().is_err());
+        assert!(pm.save_ui_settings(&UiSettings::default()).is_err());
+        assert!(pm.save_session(&SessionState::default()).is_err());
+        assert!(pm.save_stock_config(&StockConfig { symbols: Vec::new(), analysis_period_days: 90 }).is_err());
+        assert_eq!(fs::read_to_string(dir.join("config.toml")).unwrap(), text);
+
+        fs::write(dir.join("portfolio.json"), "{\"positions\": [").unwrap();
+        assert!(pm.portfolio().is_err());
+        assert!(pm.paper_account().unwrap().is_none());
+        fs::write(dir.join("predictions.json"), "{\"symbols\": 3}").unwrap();
+        assert!(pm.prediction_log().is_err());
+        fs::write(dir.join("signals.json"), "{\"AAPL\": [{\"rule\"").unwrap();
+        assert!(pm.signal_history().is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_written_toml_says_that_saving_rewrites_it() {
+        let dir = std::env::temp_dir().join(format!("bstock-test-toml-{}", std::process::id()));
+        let pm = PersistenceManager::new(Some(dir.clone())).unwrap();
+        let path = pm.migrate_to_toml().unwrap();
+        pm.save_stock_config(&StockConfig { symbols: vec!["KO".into()], analysis_period_days: 90 }).unwrap();
+        let text = fs::read_to_string(&path).unwrap();
+        assert!(text.starts_with(TOML_HEADER));
+        assert_eq!(text.matches("rewrites this whole file").count(), 1);
+        assert_eq!(pm.get_stock_config().unwrap().symbols, vec!["KO"]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
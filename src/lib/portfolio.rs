@@ -0,0 +1,553 @@
+//! The holdings actually owned, as lots: shares of a symbol bought on a
+//! day at a price. At the latest prices they give the market value and the
+//! gain not yet taken; with the purchase dates as deposits, the return on
+//! the money put in; and with each symbol's history, where the risk sits.
+//! Lots are typed in, or built from a broker's transactions: each buy
+//! opens one and each sell closes shares of the oldest (or newest) lots,
+//! whose gain is then realized and summed by tax year.
+
+use chrono::{Datelike, NaiveDate, NaiveTime};
+use serde::{Deserialize, Serialize};
+
+use crate::lib::{
+    paper::Side,
+    returns::{annualize, money_weighted_return, time_weighted_return, CashFlow, Valuation},
+    risk::{aligned_returns, periods_per_year, risk_contributions, risk_metrics, RiskContribution, RiskMetrics},
+    stock_data::StockData,
+    timezone::{bar_date, ymd},
+};
+
+/// A lot: shares of a symbol bought on a day.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Position {
+    pub symbol: String,
+    pub quantity: f64,
+    /// Price paid per share, fees included.
+    pub cost_basis: f64,
+    #[serde(with = "ymd")]
+    pub purchased: NaiveDate,
+}
+
+impl Position {
+    /// A lot from the fields of the portfolio form, or why they do not make
+    /// one. The date is `YYYY-MM-DD`.
+    pub fn parse(symbol: &str, quantity: &str, cost_basis: &str, purchased: &str) -> Result<Position, String> {
+        let symbol = symbol.trim().to_uppercase();
+        if symbol.is_empty() {
+            return Err("the symbol is missing".into());
+        }
+        let quantity: f64 = quantity.trim().parse().map_err(|_| format!("'{}' is not a quantity", quantity.trim()))?;
+        if !(quantity.is_finite() && quantity > 0.0) {
+            return Err("the quantity must be above zero".into());
+        }
+        let cost_basis: f64 = cost_basis.trim().parse().map_err(|_| format!("'{}' is not a price", cost_basis.trim()))?;
+        if !(cost_basis.is_finite() && cost_basis >= 0.0) {
+            return Err("the cost basis cannot be negative".into());
+        }
+        let purchased = NaiveDate::parse_from_str(purchased.trim(), "%Y-%m-%d")
+            .map_err(|_| format!("'{}' is not a date like 2024-03-15", purchased.trim()))?;
+        Ok(Position { symbol, quantity, cost_basis, purchased })
+    }
+
+    /// What the lot cost in all.
+    pub fn cost(&self) -> f64 {
+        self.quantity * self.cost_basis
+    }
+
+    /// The lot at `price`, or at cost where there is none.
+    pub fn value(&self, price: Option<f64>) -> f64 {
+        self.quantity * price.unwrap_or(self.cost_basis)
+    }
+}
+
+/// Share amounts closer than this are the same, so selling a lot in full
+/// leaves nothing behind.
+const EPSILON: f64 = 1e-9;
+
+/// A buy or sell from a broker's history.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Transaction {
+    #[serde(with = "ymd")]
+    pub date: NaiveDate,
+    pub symbol: String,
+    pub side: Side,
+    pub quantity: f64,
+    /// Price per share, fees not included.
+    pub price: f64,
+    /// Commission and fees paid on the whole trade.
+    pub fees: f64,
+}
+
+/// Which lots a sell closes first.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum LotMethod {
+    /// The oldest.
+    #[default]
+    Fifo,
+    /// The newest.
+    Lifo,
+}
+
+impl LotMethod {
+    pub fn label(self) -> &'static str {
+        match self {
+            LotMethod::Fifo => "FIFO",
+            LotMethod::Lifo => "LIFO",
+        }
+    }
+}
+
+/// Shares of a lot that were sold.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ClosedLot {
+    pub symbol: String,
+    pub quantity: f64,
+    /// Price paid per share, fees included.
+    pub cost_basis: f64,
+    #[serde(with = "ymd")]
+    pub purchased: NaiveDate,
+    /// Price received per share, less its share of the sell's fees.
+    pub proceeds: f64,
+    #[serde(with = "ymd")]
+    pub sold: NaiveDate,
+}
+
+impl ClosedLot {
+    pub fn gain(&self) -> f64 {
+        self.quantity * (self.proceeds - self.cost_basis)
+    }
+
+    /// Held for more than a year, which most tax codes treat as long-term.
+    pub fn long_term(&self) -> bool {
+        self.purchased.checked_add_months(chrono::Months::new(12)).is_some_and(|year| self.sold > year)
+    }
+}
+
+/// The lots closed in a calendar year.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct YearSummary {
+    pub year: i32,
+    pub proceeds: f64,
+    pub cost: f64,
+    /// Gain on lots held a year or less.
+    pub short_term: f64,
+    /// Gain on lots held more than a year.
+    pub long_term: f64,
+}
+
+impl YearSummary {
+    pub fn gain(&self) -> f64 {
+        self.short_term + self.long_term
+    }
+}
+
+/// Every lot held, the transactions imported and the lots they closed.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct Portfolio {
+    #[serde(default)]
+    pub positions: Vec<Position>,
+    #[serde(default)]
+    pub transactions: Vec<Transaction>,
+    #[serde(default)]
+    pub realized: Vec<ClosedLot>,
+    /// How sells pick lots; lots already closed stay as they were.
+    #[serde(default)]
+    pub lot_method: LotMethod,
+}
+
+/// What an import added to the portfolio.
+#[derive(Debug, Default, PartialEq)]
+pub struct ImportSummary {
+    pub added: usize,
+    /// Transactions already in the portfolio, left alone.
+    pub duplicates: usize,
+    /// Transactions that could not be applied, and why.
+    pub rejected: Vec<(Transaction, String)>,
+}
+
+/// The close of `symbol`'s last bar on or before `date`.
+pub fn close_on(symbol: &str, stock_data: &StockData, date: NaiveDate) -> Option<f64> {
+    stock_data.timestamps.iter().zip(&stock_data.closes)
+        .take_while(|&(&t, _)| bar_date(symbol, t).is_some_and(|d| d <= date))
+        .last()
+        .map(|(_, &close)| close)
+}
+
+/// Midnight UTC of `date`, in Unix seconds.
+fn timestamp(date: NaiveDate) -> i64 {
+    date.and_time(NaiveTime::MIN).and_utc().timestamp()
+}
+
+impl Portfolio {
+    /// Lots by symbol, oldest first within a symbol.
+    pub fn sort(&mut self) {
+        self.positions.sort_by(|a, b| a.symbol.cmp(&b.symbol).then(a.purchased.cmp(&b.purchased)));
+    }
+
+    /// Symbols held, A→Z.
+    pub fn symbols(&self) -> Vec<String> {
+        let mut symbols: Vec<String> = self.positions.iter().map(|p| p.symbol.clone()).collect();
+        symbols.sort();
+        symbols.dedup();
+        symbols
+    }
+
+    /// The first purchase.
+    pub fn since(&self) -> Option<NaiveDate> {
+        self.positions.iter().map(|p| p.purchased).min()
+    }
+
+    pub fn cost(&self) -> f64 {
+        self.positions.iter().map(Position::cost).sum()
+    }
+
+    /// Every lot at `price`, or at cost where there is no price.
+    pub fn value(&self, price: impl Fn(&str) -> Option<f64>) -> f64 {
+        self.positions.iter().map(|p| p.value(price(&p.symbol))).sum()
+    }
+
+    /// Each group's share of the market value, largest first, with the
+    /// symbols grouped by `group` (themselves, or their sector).
+    pub fn allocation(&self, price: impl Fn(&str) -> Option<f64>, group: impl Fn(&str) -> String) -> Vec<(String, f64)> {
+        let total = self.value(&price);
+        if total <= 0.0 {
+            return Vec::new();
+        }
+        let mut groups: Vec<(String, f64)> = Vec::new();
+        for p in &self.positions {
+            let name = group(&p.symbol);
+            let value = p.value(price(&p.symbol)) / total;
+            match groups.iter_mut().find(|(g, _)| *g == name) {
+                Some((_, share)) => *share += value,
+                None => groups.push((name, value)),
+            }
+        }
+        groups.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        groups
+    }
+
+    /// Apply `transaction` to the lots: a buy opens a lot with the fees in
+    /// its cost basis, a sell closes shares of the symbol's lots bought by
+    /// then, oldest or newest first by the lot method. A sell of more than
+    /// is held changes nothing.
+    pub fn apply(&mut self, transaction: &Transaction) -> Result<(), String> {
+        let t = transaction;
+        match t.side {
+            Side::Buy => self.positions.push(Position {
+                symbol: t.symbol.clone(),
+                quantity: t.quantity,
+                cost_basis: (t.quantity * t.price + t.fees) / t.quantity,
+                purchased: t.date,
+            }),
+            Side::Sell => {
+                let held: f64 = self.positions.iter()
+                    .filter(|p| p.symbol == t.symbol && p.purchased <= t.date)
+                    .map(|p| p.quantity)
+                    .sum();
+                if t.quantity > held + EPSILON {
+                    return Err(format!("sells {} {} but {} were held", t.quantity, t.symbol, held));
+                }
+                self.sort();
+                let proceeds = t.price - t.fees / t.quantity;
+                let mut lots: Vec<&mut Position> = self.positions.iter_mut()
+                    .filter(|p| p.symbol == t.symbol && p.purchased <= t.date)
+                    .collect();
+                if self.lot_method == LotMethod::Lifo {
+                    lots.reverse();
+                }
+                let mut left = t.quantity;
+                for lot in lots {
+                    let taken = left.min(lot.quantity);
+                    lot.quantity -= taken;
+                    left -= taken;
+                    self.realized.push(ClosedLot {
+                        symbol: lot.symbol.clone(),
+                        quantity: taken,
+                        cost_basis: lot.cost_basis,
+                        purchased: lot.purchased,
+                        proceeds,
+                        sold: t.date,
+                    });
+                    if left <= EPSILON {
+                        break;
+                    }
+                }
+                self.positions.retain(|p| p.quantity > EPSILON);
+            }
+        }
+        Ok(())
+    }
+
+    /// Add `transactions` to the history and apply them to the lots, oldest
+    /// first and buys before sells on a day. Ones already in the history
+    /// (from an earlier import of the same file) are skipped.
+    pub fn import(&mut self, mut transactions: Vec<Transaction>) -> ImportSummary {
+        let mut summary = ImportSummary::default();
+        let mut known = self.transactions.clone();
+        transactions.sort_by_key(|t| (t.date, t.side == Side::Sell));
+        for transaction in transactions {
+            if let Some(i) = known.iter().position(|k| *k == transaction) {
+                known.swap_remove(i);
+                summary.duplicates += 1;
+                continue;
+            }
+            match self.apply(&transaction) {
+                Ok(()) => {
+                    self.transactions.push(transaction);
+                    summary.added += 1;
+                }
+                Err(e) => summary.rejected.push((transaction, e)),
+            }
+        }
+        self.transactions.sort_by_key(|t| t.date);
+        self.realized.sort_by_key(|c| c.sold);
+        self.sort();
+        summary
+    }
+
+    /// Gain taken on every lot closed.
+    pub fn realized_gain(&self) -> f64 {
+        self.realized.iter().map(ClosedLot::gain).sum()
+    }
+
+    /// The closed lots' proceeds, cost and gain by the year they were
+    /// sold, newest first.
+    pub fn realized_by_year(&self) -> Vec<YearSummary> {
+        let mut years: Vec<YearSummary> = Vec::new();
+        for lot in &self.realized {
+            let year = lot.sold.year();
+            let i = match years.iter().position(|y| y.year == year) {
+                Some(i) => i,
+                None => {
+                    years.push(YearSummary { year, ..YearSummary::default() });
+                    years.len() - 1
+                }
+            };
+            let summary = &mut years[i];
+            summary.proceeds += lot.quantity * lot.proceeds;
+            summary.cost += lot.quantity * lot.cost_basis;
+            if lot.long_term() {
+                summary.long_term += lot.gain();
+            } else {
+                summary.short_term += lot.gain();
+            }
+        }
+        years.sort_by_key(|y| std::cmp::Reverse(y.year));
+        years
+    }
+
+    /// Each purchase as money put in, on its day.
+    fn deposits(&self) -> Vec<CashFlow> {
+        self.positions.iter().map(|p| CashFlow { timestamp: timestamp(p.purchased), amount: p.cost() }).collect()
+    }
+
+    /// Annual return on the money put in, each lot counted from its
+    /// purchase date up to `now` (Unix seconds).
+    pub fn money_weighted_return(&self, price: impl Fn(&str) -> Option<f64>, now: i64) -> Option<f64> {
+        money_weighted_return(&self.deposits(), Valuation { timestamp: now, value: self.value(price) })
+    }
+
+    /// Annual return of the holdings with the timing of the purchases taken
+    /// out: the value is taken on every purchase date from the closes in
+    /// `history`, and at `price` at `now`. `None` when a close is missing.
+    pub fn time_weighted_return<'a>(
+        &self,
+        price: impl Fn(&str) -> Option<f64>,
+        history: impl Fn(&str) -> Option<&'a StockData>,
+        now: i64,
+    ) -> Option<f64> {
+        let mut dates: Vec<NaiveDate> = self.positions.iter().map(|p| p.purchased).collect();
+        dates.sort();
+        dates.dedup();
+        let first = *dates.first()?;
+        let mut valuations = Vec::new();
+        for &date in &dates {
+            let value = self.positions.iter()
+                .filter(|p| p.purchased < date)
+                .map(|p| Some(p.quantity * close_on(&p.symbol, history(&p.symbol)?, date)?))
+                .sum::<Option<f64>>()?;
+            valuations.push(Valuation { timestamp: timestamp(date), value });
+        }
+        valuations.push(Valuation { timestamp: now, value: self.value(price) });
+        let total = time_weighted_return(&valuations, &self.deposits())?;
+        Some(annualize(total, now - timestamp(first)))
+    }
+
+    /// Each symbol's share of the holdings' volatility, from the returns of
+    /// the bars in `history` on the days they all traded, weighted by market
+    /// value. `None` until every symbol has a few bars.
+    pub fn risk<'a>(
+        &self,
+        price: impl Fn(&str) -> Option<f64>,
+        history: impl Fn(&str) -> Option<&'a StockData>,
+    ) -> Option<Vec<RiskContribution>> {
+        let (symbols, weights) = self.weights(&price)?;
+        let series: Vec<(&str, &StockData)> = symbols.iter().map(|s| Some((s.as_str(), history(s)?))).collect::<Option<_>>()?;
+        let (_, returns) = aligned_returns(&series)?;
+        risk_contributions(&symbols, &weights, &returns)
+    }
+
+    /// Symbols held and their shares of the market value.
+    fn weights(&self, price: impl Fn(&str) -> Option<f64>) -> Option<(Vec<String>, Vec<f64>)> {
+        let symbols = self.symbols();
+        let total = self.value(&price);
+        if total <= 0.0 {
+            return None;
+        }
+        let weights = symbols.iter()
+            .map(|s| self.positions.iter().filter(|p| &p.symbol == s).map(|p| p.value(price(s))).sum::<f64>() / total)
+            .collect();
+        Some((symbols, weights))
+    }
+
+    /// Volatility, Sharpe ratio over `risk_free` (a yearly rate), beta
+    /// against the `benchmark` bars and max drawdown of the holdings as
+    /// weighted now, over the days in `history` that they and the benchmark
+    /// all traded. `None` until every symbol has a few bars; no beta
+    /// without the benchmark's.
+    pub fn risk_metrics<'a>(
+        &self,
+        price: impl Fn(&str) -> Option<f64>,
+        history: impl Fn(&str) -> Option<&'a StockData>,
+        benchmark: Option<(&str, &StockData)>,
+        risk_free: f64,
+    ) -> Option<RiskMetrics> {
+        let (symbols, weights) = self.weights(&price)?;
+        let mut series: Vec<(&str, &StockData)> = symbols.iter().map(|s| Some((s.as_str(), history(s)?))).collect::<Option<_>>()?;
+        series.extend(benchmark);
+        let (timestamps, returns) = aligned_returns(&series)?;
+        let portfolio: Vec<f64> = returns.rows().into_iter()
+            .map(|row| row.iter().zip(&weights).map(|(r, w)| r * w).sum())
+            .collect();
+        let benchmark = benchmark.map(|_| returns.column(symbols.len()).to_vec());
+        let periods = periods_per_year(&timestamps)?;
+        risk_metrics(&portfolio, benchmark.as_deref(), periods, risk_free)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lots_value_and_return() {
+        let day = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let mut portfolio = Portfolio {
+            positions: vec![
+                Position::parse("bbb", "10", "50", "2024-01-01").unwrap(),
+                Position::parse("AAA", "4", "100", "2023-01-01").unwrap(),
+            ],
+            ..Portfolio::default()
+        };
+        portfolio.sort();
+        assert_eq!(portfolio.symbols(), ["AAA", "BBB"]);
+        assert_eq!(portfolio.since(), Some(day(2023, 1, 1)));
+        assert_eq!(portfolio.cost(), 900.0);
+        let price = |s: &str| (s == "AAA").then_some(121.0);
+        // BBB has no price, so it counts at cost
+        assert_eq!(portfolio.value(price), 984.0);
+        let by_symbol = portfolio.allocation(price, str::to_string);
+        assert_eq!(by_symbol.iter().map(|(s, _)| s.as_str()).collect::<Vec<_>>(), ["BBB", "AAA"]);
+        assert!((by_symbol[1].1 - 484.0 / 984.0).abs() < 1e-12);
+        assert_eq!(portfolio.allocation(price, |_| "Tech".into()), [("Tech".to_string(), 1.0)]);
+
+        // AAA alone, bought a year before it is valued
+        let aaa = Portfolio { positions: portfolio.positions[..1].to_vec(), ..Portfolio::default() };
+        let mwr = aaa.money_weighted_return(price, timestamp(day(2024, 1, 1))).unwrap();
+        assert!((mwr - 0.21).abs() < 1e-3, "{mwr}");
+
+        let mut history = StockData::new();
+        history.add_point(timestamp(day(2023, 12, 29)), 110.0, 110.0, 110.0, 110.0, 0);
+        let twr = portfolio.time_weighted_return(price, |_| Some(&history), timestamp(day(2025, 1, 1))).unwrap();
+        assert!(twr > 0.0);
+        assert!(portfolio.time_weighted_return(price, |_| None, timestamp(day(2025, 1, 1))).is_none());
+
+        // Both symbols move as the benchmark does, so the holdings do too
+        let mut index = StockData::new();
+        for (i, close) in [100.0, 102.0, 99.0, 104.0].into_iter().enumerate() {
+            index.add_point(timestamp(day(2024, 1, 1 + i as u32)), close, close, close, close, 0);
+        }
+        let metrics = portfolio.risk_metrics(price, |_| Some(&index), Some(("SPY", &index)), 0.0).unwrap();
+        assert!((metrics.beta.unwrap() - 1.0).abs() < 1e-9);
+        assert!((metrics.max_drawdown - (1.0 - 99.0 / 102.0)).abs() < 1e-9);
+        assert!(metrics.volatility > 0.0);
+        assert!(portfolio.risk_metrics(price, |_| Some(&index), None, 0.0).unwrap().beta.is_none());
+        // A holding that also trades on the weekend is measured against the
+        // benchmark on the days both traded: 2024-01-05 (Fri) to 01-08 (Mon)
+        let (mut coin, mut weekdays) = (StockData::new(), StockData::new());
+        for (i, close) in [100.0, 102.0, 150.0, 80.0, 99.0, 104.0].into_iter().enumerate() {
+            let t = timestamp(day(2024, 1, 3 + i as u32));
+            coin.add_point(t, close, close, close, close, 0);
+            if ![3, 4].contains(&i) {
+                weekdays.add_point(t, close, close, close, close, 0);
+            }
+        }
+        let metrics = portfolio.risk_metrics(price, |_| Some(&coin), Some(("SPY", &weekdays)), 0.0).unwrap();
+        assert!((metrics.beta.unwrap() - 1.0).abs() < 1e-9);
+
+        assert!(Position::parse("AAA", "0", "1", "2024-01-01").is_err());
+        assert!(Position::parse("AAA", "1", "1", "01/02/2024").is_err());
+        assert!(Position::parse(" ", "1", "1", "2024-01-01").is_err());
+    }
+
+    #[test]
+    fn test_import_builds_lots_oldest_first() {
+        let day = |d| NaiveDate::from_ymd_opt(2024, 1, d).unwrap();
+        let trade = |d, side, quantity, price, fees| Transaction { date: day(d), symbol: "AAA".into(), side, quantity, price, fees };
+        let mut portfolio = Portfolio::default();
+        // Newest first, as brokers list them
+        let summary = portfolio.import(vec![
+            trade(3, Side::Sell, 15.0, 30.0, 1.0),
+            trade(2, Side::Buy, 10.0, 20.0, 0.0),
+            trade(1, Side::Buy, 10.0, 10.0, 5.0),
+        ]);
+        assert_eq!((summary.added, summary.duplicates), (3, 0));
+        // The first lot is gone and half the second is left
+        assert_eq!(portfolio.positions.len(), 1);
+        assert_eq!((portfolio.positions[0].quantity, portfolio.positions[0].cost_basis), (5.0, 20.0));
+        assert_eq!(portfolio.transactions[0].date, day(1));
+
+        // The same file again adds nothing; a sell of more than is held is refused
+        let summary = portfolio.import(vec![trade(1, Side::Buy, 10.0, 10.0, 5.0), trade(4, Side::Sell, 6.0, 30.0, 0.0)]);
+        assert_eq!((summary.added, summary.duplicates, summary.rejected.len()), (0, 1, 1));
+        assert_eq!(portfolio.positions[0].quantity, 5.0);
+
+        let summary = portfolio.import(vec![trade(5, Side::Buy, 2.0, 50.0, 1.0)]);
+        assert_eq!(summary.added, 1);
+        assert_eq!(portfolio.positions[1].cost_basis, 50.5);
+    }
+
+    #[test]
+    fn test_realized_gains_by_lot_method_and_year() {
+        let trade = |date: &str, side, quantity, price| Transaction {
+            date: NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap(),
+            symbol: "AAA".into(),
+            side,
+            quantity,
+            price,
+            fees: 0.0,
+        };
+        let trades = vec![
+            trade("2022-01-03", Side::Buy, 10.0, 10.0),
+            trade("2023-06-01", Side::Buy, 10.0, 20.0),
+            trade("2023-09-01", Side::Sell, 5.0, 30.0),
+            trade("2024-02-01", Side::Sell, 10.0, 25.0),
+        ];
+        let mut fifo = Portfolio::default();
+        fifo.import(trades.clone());
+        // The first lot's shares go first, held over a year
+        assert_eq!(fifo.realized_gain(), 5.0 * 20.0 + 5.0 * 15.0 + 5.0 * 5.0);
+        let years = fifo.realized_by_year();
+        assert_eq!(years.iter().map(|y| y.year).collect::<Vec<_>>(), [2024, 2023]);
+        assert_eq!((years[1].short_term, years[1].long_term), (0.0, 100.0));
+        assert_eq!((years[0].short_term, years[0].long_term, years[0].proceeds), (25.0, 75.0, 250.0));
+        assert_eq!((fifo.positions[0].quantity, fifo.positions[0].cost_basis), (5.0, 20.0));
+
+        let mut lifo = Portfolio { lot_method: LotMethod::Lifo, ..Portfolio::default() };
+        lifo.import(trades);
+        assert_eq!(lifo.realized_gain(), 5.0 * 10.0 + 5.0 * 5.0 + 5.0 * 15.0);
+        assert_eq!(lifo.realized_by_year()[1].short_term, 50.0);
+        assert_eq!((lifo.positions[0].quantity, lifo.positions[0].cost_basis), (5.0, 10.0));
+    }
+}
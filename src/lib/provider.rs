@@ -0,0 +1,426 @@
+//! Where market data comes from. The app and the command-line tools fetch
+//! through [`DataProvider`], so another source, or a mock in tests, can be
+//! plugged in without changing them.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+use crate::data::TimeRange;
+use crate::lib::{
+    alphavantage::AlphaVantageProvider,
+    config::{ProviderSettings, SymbolProfile},
+    dividends::Dividend,
+    error::AppError,
+    finnhub::FinnhubProvider,
+    insiders::InsiderTrade,
+    retry::{default_rate_limit, Retrying},
+    secrets,
+    stock_data::StockData,
+    stooq::StooqProvider,
+    yahooapi::YahooProvider,
+};
+
+/// Provider names the config and `--provider` accept.
+pub const PROVIDER_NAMES: &[&str] = &["yahoo", "alphavantage", "finnhub", "stooq"];
+
+/// Providers that need an API key.
+pub const KEYED_PROVIDERS: &[&str] = &["alphavantage", "finnhub"];
+
+/// What provider methods return: a boxed future, so providers can be used
+/// as `dyn DataProvider` and moved onto the runtime.
+pub type ProviderFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, AppError>> + Send + 'a>>;
+
+/// The latest bar of a symbol.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Quote {
+    /// Unix timestamp of the bar.
+    pub timestamp: i64,
+    pub price: f64,
+    pub volume: u64,
+}
+
+/// Valuation figures for a stock; `None` where the provider has none.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Fundamentals {
+    /// In the currency the stock trades in.
+    pub market_cap: Option<f64>,
+    /// Trailing price/earnings.
+    pub pe: Option<f64>,
+    pub forward_pe: Option<f64>,
+    /// Trailing twelve-month earnings per share.
+    pub eps: Option<f64>,
+    /// Net income over revenue, as a fraction.
+    pub profit_margin: Option<f64>,
+    /// Total debt over shareholders' equity, as a ratio.
+    pub debt_to_equity: Option<f64>,
+    /// Shares sold short, as last reported.
+    pub shares_short: Option<f64>,
+    /// Shares sold short over the float, as a fraction.
+    pub short_percent_of_float: Option<f64>,
+    /// Days to cover: shares sold short over the average daily volume.
+    pub short_ratio: Option<f64>,
+}
+
+/// Short interest, as a fraction of the float, from which a stock counts
+/// as heavily shorted.
+const HEAVY_SHORT_INTEREST: f64 = 0.2;
+
+/// Days to cover from which a stock counts as heavily shorted.
+const HEAVY_DAYS_TO_COVER: f64 = 10.0;
+
+impl Fundamentals {
+    pub fn is_empty(&self) -> bool {
+        *self == Fundamentals::default()
+    }
+
+    /// Whether a fifth of the float is sold short, or covering it would
+    /// take ten days' volume or more.
+    pub fn heavily_shorted(&self) -> bool {
+        self.short_percent_of_float.is_some_and(|s| s >= HEAVY_SHORT_INTEREST)
+            || self.short_ratio.is_some_and(|d| d >= HEAVY_DAYS_TO_COVER)
+    }
+}
+
+/// What the analysts covering a stock recommend, and where they expect
+/// its price to be in a year.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AnalystRatings {
+    pub strong_buy: u32,
+    pub buy: u32,
+    pub hold: u32,
+    pub sell: u32,
+    pub strong_sell: u32,
+    /// Mean price target, in the currency the stock trades in.
+    pub target_mean: Option<f64>,
+    pub target_high: Option<f64>,
+    pub target_low: Option<f64>,
+}
+
+impl AnalystRatings {
+    pub fn is_empty(&self) -> bool {
+        *self == AnalystRatings::default()
+    }
+
+    /// Analysts with a recommendation.
+    pub fn analysts(&self) -> u32 {
+        self.strong_buy + self.buy + self.hold + self.sell + self.strong_sell
+    }
+
+    /// The mean recommendation, from 1 (strong buy) to 5 (strong sell).
+    pub fn mean_score(&self) -> Option<f64> {
+        let n = self.analysts();
+        let total = self.strong_buy + 2 * self.buy + 3 * self.hold + 4 * self.sell + 5 * self.strong_sell;
+        (n > 0).then(|| total as f64 / n as f64)
+    }
+
+    /// The mean recommendation in words, e.g. "Buy".
+    pub fn consensus(&self) -> Option<&'static str> {
+        Some(match self.mean_score()? {
+            s if s < 1.5 => "Strong buy",
+            s if s < 2.5 => "Buy",
+            s if s < 3.5 => "Hold",
+            s if s < 4.5 => "Sell",
+            _ => "Strong sell",
+        })
+    }
+
+    /// How far the mean target is above `price`, in percent; negative when
+    /// below.
+    pub fn upside(&self, price: f64) -> Option<f64> {
+        let target = self.target_mean?;
+        (price > 0.0).then(|| (target / price - 1.0) * 100.0)
+    }
+}
+
+/// A headline about a symbol.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NewsItem {
+    pub title: String,
+    /// Who published it, e.g. "Reuters"; empty when unknown.
+    pub publisher: String,
+    /// The article's URL.
+    pub link: String,
+    /// Unix timestamp of publication.
+    pub published: i64,
+}
+
+/// A ticker matching a search, for symbol autocomplete.
+#[derive(Debug, Clone)]
+pub struct SymbolMatch {
+    pub symbol: String,
+    /// Company or fund name; empty when the provider has none.
+    pub name: String,
+    pub exchange: String,
+    /// Kind of security as the provider words it, e.g. "Equity" or "ETF".
+    pub kind: String,
+}
+
+pub trait DataProvider: Send + Sync {
+    /// Name for messages, e.g. "Yahoo Finance".
+    fn name(&self) -> &'static str;
+
+    /// Bars covering `time_range` up to now, at the interval the range is
+    /// charted with.
+    fn fetch_history<'a>(&'a self, symbol: &'a str, time_range: TimeRange) -> ProviderFuture<'a, StockData>;
+
+    /// Bars at `time_range`'s interval from `since` (a Unix timestamp,
+    /// inclusive) up to now, for topping up loaded data. No bars is not an
+    /// error: there may be nothing new yet.
+    fn fetch_history_since<'a>(&'a self, symbol: &'a str, time_range: TimeRange, since: i64) -> ProviderFuture<'a, StockData>;
+
+    /// Bars covering `time_range` and the equal period right before it, at
+    /// the same interval, for the prior-period overlay. Ranges with nothing
+    /// before them are an error.
+    fn fetch_doubled_history<'a>(&'a self, symbol: &'a str, time_range: TimeRange) -> ProviderFuture<'a, StockData>;
+
+    fn fetch_quote<'a>(&'a self, symbol: &'a str) -> ProviderFuture<'a, Quote>;
+
+    /// Tickers matching a free-text `query` such as a company name.
+    fn search_symbols<'a>(&'a self, query: &'a str) -> ProviderFuture<'a, Vec<SymbolMatch>>;
+
+    /// Sector and industry of `symbol`; empty when the provider has none.
+    fn fetch_profile<'a>(&'a self, symbol: &'a str) -> ProviderFuture<'a, SymbolProfile>;
+
+    /// Market cap, earnings and balance-sheet ratios of `symbol`; empty
+    /// when the provider has none.
+    fn fetch_fundamentals<'a>(&'a self, _symbol: &'a str) -> ProviderFuture<'a, Fundamentals> {
+        Box::pin(async { Ok(Fundamentals::default()) })
+    }
+
+    /// Analyst recommendations and price targets for `symbol`; empty when
+    /// the provider has none.
+    fn fetch_ratings<'a>(&'a self, _symbol: &'a str) -> ProviderFuture<'a, AnalystRatings> {
+        Box::pin(async { Ok(AnalystRatings::default()) })
+    }
+
+    /// Recent trades by `symbol`'s insiders, newest first; empty when the
+    /// provider has none.
+    fn fetch_insider_trades<'a>(&'a self, _symbol: &'a str) -> ProviderFuture<'a, Vec<InsiderTrade>> {
+        Box::pin(async { Ok(Vec::new()) })
+    }
+
+    /// Dividends `symbol` has paid over the last few years, oldest first;
+    /// empty when the provider has no dividend data.
+    fn fetch_dividends<'a>(&'a self, _symbol: &'a str) -> ProviderFuture<'a, Vec<Dividend>> {
+        Box::pin(async { Ok(Vec::new()) })
+    }
+
+    /// Recent headlines about `symbol`, newest first; empty when the
+    /// provider has no news.
+    fn fetch_news<'a>(&'a self, _symbol: &'a str) -> ProviderFuture<'a, Vec<NewsItem>> {
+        Box::pin(async { Ok(Vec::new()) })
+    }
+
+    /// Whether `symbol` resolves to a quoted security.
+    fn validate_symbol<'a>(&'a self, symbol: &'a str) -> ProviderFuture<'a, ()> {
+        Box::pin(async move { self.fetch_quote(symbol).await.map(|_| ()) })
+    }
+
+    /// Name of the provider that last answered for `symbol`, which only
+    /// differs from [`name`](DataProvider::name) in a fallback chain.
+    fn served_by(&self, _symbol: &str) -> &'static str {
+        self.name()
+    }
+}
+
+/// The provider called `name`, with its API key from the OS credential
+/// store or else `settings`, with its concurrency, rate limit and retries
+/// as `settings` say.
+pub fn provider_named(name: &str, settings: &ProviderSettings) -> Result<Arc<dyn DataProvider>, AppError> {
+    let per_minute = settings.rate_limits.get(name).copied().or_else(|| default_rate_limit(name));
+    Ok(Arc::new(Retrying::new(bare_provider(name, settings)?, settings.retry, per_minute, settings.max_concurrent)))
+}
+
+fn bare_provider(name: &str, settings: &ProviderSettings) -> Result<Arc<dyn DataProvider>, AppError> {
+    let api_key = || {
+        secrets::get(name).or_else(|| settings.api_keys.get(name).filter(|k| !k.is_empty()).cloned()).ok_or_else(|| {
+            AppError::Provider(format!("{name} needs an API key; set it with `bstock config api-key {name} <KEY>`"))
+        })
+    };
+    match name {
+        "yahoo" => Ok(Arc::new(YahooProvider)),
+        "alphavantage" => Ok(Arc::new(AlphaVantageProvider::new(api_key()?))),
+        "finnhub" => Ok(Arc::new(FinnhubProvider::new(api_key()?))),
+        "stooq" => Ok(Arc::new(StooqProvider::new())),
+        _ => Err(AppError::Provider(format!("unknown provider {}; expected one of {}", name, PROVIDER_NAMES.join(", ")))),
+    }
+}
+
+/// The provider watchlist `watchlist` uses, followed by the configured
+/// fallbacks when there are any.
+pub fn provider_for_watchlist(settings: &ProviderSettings, watchlist: &str) -> Result<Arc<dyn DataProvider>, AppError> {
+    let name = settings.for_watchlist(watchlist);
+    let mut providers = vec![provider_named(name, settings)?];
+    for fallback in &settings.fallback {
+        if fallback != name {
+            providers.push(provider_named(fallback, settings)?);
+        }
+    }
+    match providers.len() {
+        1 => Ok(providers.remove(0)),
+        _ => Ok(Arc::new(Chain { providers, served: Mutex::new(HashMap::new()) })),
+    }
+}
+
+/// Asks each provider in turn until one answers, so a symbol the first one
+/// fails on (or a rate limit) falls through to the next.
+struct Chain {
+    providers: Vec<Arc<dyn DataProvider>>,
+    /// Which provider last answered for each symbol.
+    served: Mutex<HashMap<String, &'static str>>,
+}
+
+impl Chain {
+    /// The first answer to `call`, noting who gave it for `symbol` when
+    /// there is one; if every provider fails, all their errors.
+    fn first<'a, T: Send + 'a>(
+        &'a self,
+        symbol: Option<&'a str>,
+        call: impl Fn(&'a dyn DataProvider) -> ProviderFuture<'a, T> + Send + 'a,
+    ) -> ProviderFuture<'a, T> {
+        Box::pin(async move {
+            let mut errors = Vec::new();
+            for provider in &self.providers {
+                match call(provider.as_ref()).await {
+                    Ok(value) => {
+                        if let (Some(symbol), Ok(mut served)) = (symbol, self.served.lock()) {
+                            served.insert(symbol.to_string(), provider.served_by(symbol));
+                        }
+                        return Ok(value);
+                    }
+                    Err(e) => errors.push(format!("{}: {}", provider.name(), e)),
+                }
+            }
+            Err(AppError::Provider(errors.join("; ")))
+        })
+    }
+}
+
+impl DataProvider for Chain {
+    fn name(&self) -> &'static str {
+        self.providers[0].name()
+    }
+
+    fn fetch_history<'a>(&'a self, symbol: &'a str, time_range: TimeRange) -> ProviderFuture<'a, StockData> {
+        self.first(Some(symbol), move |p| p.fetch_history(symbol, time_range))
+    }
+
+    fn fetch_history_since<'a>(&'a self, symbol: &'a str, time_range: TimeRange, since: i64) -> ProviderFuture<'a, StockData> {
+        self.first(Some(symbol), move |p| p.fetch_history_since(symbol, time_range, since))
+    }
+
+    fn fetch_doubled_history<'a>(&'a self, symbol: &'a str, time_range: TimeRange) -> ProviderFuture<'a, StockData> {
+        self.first(None, move |p| p.fetch_doubled_history(symbol, time_range))
+    }
+
+    fn fetch_quote<'a>(&'a self, symbol: &'a str) -> ProviderFuture<'a, Quote> {
+        self.first(None, move |p| p.fetch_quote(symbol))
+    }
+
+    fn search_symbols<'a>(&'a self, query: &'a str) -> ProviderFuture<'a, Vec<SymbolMatch>> {
+        self.first(None, move |p| p.search_symbols(query))
+    }
+
+    fn fetch_profile<'a>(&'a self, symbol: &'a str) -> ProviderFuture<'a, SymbolProfile> {
+        self.first(None, move |p| p.fetch_profile(symbol))
+    }
+
+    fn fetch_fundamentals<'a>(&'a self, symbol: &'a str) -> ProviderFuture<'a, Fundamentals> {
+        self.first(None, move |p| p.fetch_fundamentals(symbol))
+    }
+
+    fn fetch_ratings<'a>(&'a self, symbol: &'a str) -> ProviderFuture<'a, AnalystRatings> {
+        self.first(None, move |p| p.fetch_ratings(symbol))
+    }
+
+    fn fetch_insider_trades<'a>(&'a self, symbol: &'a str) -> ProviderFuture<'a, Vec<InsiderTrade>> {
+        self.first(None, move |p| p.fetch_insider_trades(symbol))
+    }
+
+    fn fetch_dividends<'a>(&'a self, symbol: &'a str) -> ProviderFuture<'a, Vec<Dividend>> {
+        self.first(None, move |p| p.fetch_dividends(symbol))
+    }
+
+    fn fetch_news<'a>(&'a self, symbol: &'a str) -> ProviderFuture<'a, Vec<NewsItem>> {
+        self.first(None, move |p| p.fetch_news(symbol))
+    }
+
+    fn served_by(&self, symbol: &str) -> &'static str {
+        self.served.lock().ok()
+            .and_then(|served| served.get(symbol).copied())
+            .unwrap_or_else(|| self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Knows one symbol, with a single bar; the second field is its name.
+    struct Mock(&'static str, &'static str);
+
+    impl DataProvider for Mock {
+        fn name(&self) -> &'static str {
+            self.1
+        }
+
+        fn fetch_history<'a>(&'a self, symbol: &'a str, _: TimeRange) -> ProviderFuture<'a, StockData> {
+            Box::pin(async move {
+                if symbol != self.0 {
+                    return Err(AppError::ApiError(format!("{symbol}: no data")));
+                }
+                let mut data = StockData::new();
+                data.add_point(1_700_000_000, 1.0, 2.0, 0.5, 1.5, 10);
+                Ok(data)
+            })
+        }
+
+        fn fetch_history_since<'a>(&'a self, symbol: &'a str, time_range: TimeRange, _: i64) -> ProviderFuture<'a, StockData> {
+            self.fetch_history(symbol, time_range)
+        }
+
+        fn fetch_doubled_history<'a>(&'a self, symbol: &'a str, time_range: TimeRange) -> ProviderFuture<'a, StockData> {
+            self.fetch_history(symbol, time_range)
+        }
+
+        fn fetch_quote<'a>(&'a self, symbol: &'a str) -> ProviderFuture<'a, Quote> {
+            Box::pin(async move {
+                let data = self.fetch_history(symbol, TimeRange::OneDay).await?;
+                Ok(Quote { timestamp: data.timestamps[0], price: data.closes[0], volume: data.volumes[0] })
+            })
+        }
+
+        fn search_symbols<'a>(&'a self, _: &'a str) -> ProviderFuture<'a, Vec<SymbolMatch>> {
+            Box::pin(async { Ok(Vec::new()) })
+        }
+
+        fn fetch_profile<'a>(&'a self, _: &'a str) -> ProviderFuture<'a, SymbolProfile> {
+            Box::pin(async { Ok(SymbolProfile::default()) })
+        }
+    }
+
+    #[test]
+    fn test_validation_defaults_to_a_quote_lookup() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let provider: Box<dyn DataProvider> = Box::new(Mock("OK", "Mock"));
+        assert!(rt.block_on(provider.validate_symbol("OK")).is_ok());
+        assert!(rt.block_on(provider.validate_symbol("NOPE")).is_err());
+    }
+
+    #[test]
+    fn test_chain_falls_through_and_remembers_who_answered() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let chain = Chain {
+            providers: vec![Arc::new(Mock("A", "First")), Arc::new(Mock("B", "Second"))],
+            served: Mutex::new(HashMap::new()),
+        };
+        assert!(rt.block_on(chain.fetch_history("A", TimeRange::OneYear)).is_ok());
+        assert!(rt.block_on(chain.fetch_history("B", TimeRange::OneYear)).is_ok());
+        assert_eq!(chain.served_by("A"), "First");
+        assert_eq!(chain.served_by("B"), "Second");
+        let both = rt.block_on(chain.fetch_history("C", TimeRange::OneYear)).unwrap_err().to_string();
+        assert!(both.contains("First: ") && both.contains("; Second: "), "{both}");
+    }
+}
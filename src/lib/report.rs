@@ -0,0 +1,269 @@
+//! Daily summary documents for a watchlist: a table of every symbol, then
+//! a section per symbol with its indicators, active signals, forecast and
+//! chart. Markdown links the charts as PNG files beside the report; HTML
+//! embeds them so the file stands alone.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{DateTime, FixedOffset};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::lib::{
+    analysis::{prediction_upside, StockAnalysis},
+    chart_image::render_chart_png,
+    error::AppError,
+    instrument::format_price,
+    signals::{detect_signals, Signal},
+    stock_data::StockData,
+    timezone::bar_date,
+};
+
+const CHART_SIZE: (usize, usize) = (900, 300);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReportFormat {
+    Markdown,
+    Html,
+}
+
+impl ReportFormat {
+    /// From a format name or file extension: `md`/`markdown` or `html`/`htm`.
+    pub fn parse(name: &str) -> Option<ReportFormat> {
+        match name.to_ascii_lowercase().as_str() {
+            "md" | "markdown" => Some(ReportFormat::Markdown),
+            "html" | "htm" => Some(ReportFormat::Html),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ReportFormat::Markdown => "md",
+            ReportFormat::Html => "html",
+        }
+    }
+}
+
+pub struct ReportEntry<'a> {
+    pub stock_data: &'a StockData,
+    pub analysis: &'a StockAnalysis,
+}
+
+pub struct Report<'a> {
+    /// Usually the watchlist's name.
+    pub title: String,
+    /// Label of the time range the entries were fetched for.
+    pub range: String,
+    pub generated: DateTime<FixedOffset>,
+    pub entries: Vec<ReportEntry<'a>>,
+}
+
+fn money(symbol: &str, v: Option<f64>) -> String {
+    v.map_or("--".into(), |v| format_price(symbol, v))
+}
+
+fn percent(v: Option<f64>) -> String {
+    v.map_or("--".into(), |v| format!("{:+.2}%", v))
+}
+
+fn signal_date(signal: &Signal) -> String {
+    bar_date(&signal.symbol, signal.timestamp).map(|d| d.to_string()).unwrap_or_default()
+}
+
+fn signal_names(signals: &[Signal]) -> String {
+    if signals.is_empty() {
+        return "--".into();
+    }
+    signals.iter().map(|s| s.kind.label()).collect::<Vec<_>>().join(", ")
+}
+
+fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+impl Report<'_> {
+    fn heading(&self) -> String {
+        format!("{} — {}", self.title, self.generated.format("%Y-%m-%d"))
+    }
+
+    fn subtitle(&self) -> String {
+        format!(
+            "{} symbols, {} range, generated {}",
+            self.entries.len(), self.range, self.generated.format("%Y-%m-%d %H:%M"),
+        )
+    }
+
+    /// `chart(symbol)` gives the path (relative to the report) of each
+    /// symbol's chart, or `None` to leave it out.
+    pub fn to_markdown(&self, chart: impl Fn(&str) -> Option<String>) -> String {
+        let mut out = format!("# {}\n\n{}\n\n", self.heading(), self.subtitle());
+        out.push_str("| Symbol | Price | Change | Forecast | Upside | Signals |\n");
+        out.push_str("|---|---:|---:|---:|---:|---|\n");
+        let signals: Vec<Vec<Signal>> = self.entries.iter()
+            .map(|e| detect_signals(&e.analysis.symbol, e.stock_data, e.analysis))
+            .collect();
+        for (e, signals) in self.entries.iter().zip(&signals) {
+            let a = e.analysis;
+            out.push_str(&format!(
+                "| {} | {} | {} | {} | {} | {} |\n",
+                a.symbol, money(&a.symbol, Some(a.current_price)), percent(a.recent_change),
+                money(&a.symbol, a.predictions.last().copied()), percent(prediction_upside(a)), signal_names(signals),
+            ));
+        }
+
+        for (e, signals) in self.entries.iter().zip(&signals) {
+            let a = e.analysis;
+            out.push_str(&format!("\n## {}\n\n", a.symbol));
+            out.push_str(&format!(
+                "- Price: {} ({} on the day)\n- SMA-10: {}, SMA-50: {}, EMA-20: {}\n- Forecast in {} bars: {} ({})\n",
+                money(&a.symbol, Some(a.current_price)), percent(a.recent_change),
+                money(&a.symbol, a.sma_10), money(&a.symbol, a.sma_50), money(&a.symbol, a.ema_20),
+                a.predictions.len(), money(&a.symbol, a.predictions.last().copied()), percent(prediction_upside(a)),
+            ));
+            if signals.is_empty() {
+                out.push_str("- No active signals\n");
+            }
+            for s in signals {
+                out.push_str(&format!("- {} since {}: {}\n", s.kind.label(), signal_date(s), s.detail));
+            }
+            if let Some(path) = chart(&a.symbol) {
+                out.push_str(&format!("\n![{} chart]({})\n", a.symbol, path));
+            }
+        }
+        out
+    }
+
+    /// Self-contained HTML: styles inline, charts as data URIs.
+    pub fn to_html(&self) -> String {
+        let mut out = format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{0}</title>\n<style>\n\
+             body {{ font-family: sans-serif; max-width: 60em; margin: 2em auto; color: #222; }}\n\
+             table {{ border-collapse: collapse; }}\n\
+             th, td {{ padding: 0.3em 0.8em; border-bottom: 1px solid #ddd; text-align: right; }}\n\
+             th:first-child, td:first-child, td:last-child {{ text-align: left; }}\n\
+             .up {{ color: #1a8f3c; }} .down {{ color: #c62828; }}\n\
+             </style>\n</head>\n<body>\n<h1>{0}</h1>\n<p>{1}</p>\n",
+            html_escape(&self.heading()), html_escape(&self.subtitle()),
+        );
+        let class = |v: Option<f64>| match v {
+            Some(v) if v > 0.0 => " class=\"up\"",
+            Some(v) if v < 0.0 => " class=\"down\"",
+            _ => "",
+        };
+        let signals: Vec<Vec<Signal>> = self.entries.iter()
+            .map(|e| detect_signals(&e.analysis.symbol, e.stock_data, e.analysis))
+            .collect();
+
+        out.push_str("<table>\n<tr><th>Symbol</th><th>Price</th><th>Change</th><th>Forecast</th><th>Upside</th><th>Signals</th></tr>\n");
+        for (e, signals) in self.entries.iter().zip(&signals) {
+            let a = e.analysis;
+            let upside = prediction_upside(a);
+            out.push_str(&format!(
+                "<tr><td><a href=\"#{0}\">{0}</a></td><td>{1}</td><td{2}>{3}</td><td>{4}</td><td{5}>{6}</td><td>{7}</td></tr>\n",
+                html_escape(&a.symbol), money(&a.symbol, Some(a.current_price)),
+                class(a.recent_change), percent(a.recent_change),
+                money(&a.symbol, a.predictions.last().copied()), class(upside), percent(upside),
+                html_escape(&signal_names(signals)),
+            ));
+        }
+        out.push_str("</table>\n");
+
+        for (e, signals) in self.entries.iter().zip(&signals) {
+            let a = e.analysis;
+            let symbol = html_escape(&a.symbol);
+            out.push_str(&format!("<h2 id=\"{0}\">{0}</h2>\n<ul>\n", symbol));
+            out.push_str(&format!(
+                "<li>Price: {} ({} on the day)</li>\n<li>SMA-10: {}, SMA-50: {}, EMA-20: {}</li>\n<li>Forecast in {} bars: {} ({})</li>\n",
+                money(&a.symbol, Some(a.current_price)), percent(a.recent_change),
+                money(&a.symbol, a.sma_10), money(&a.symbol, a.sma_50), money(&a.symbol, a.ema_20),
+                a.predictions.len(), money(&a.symbol, a.predictions.last().copied()), percent(prediction_upside(a)),
+            ));
+            if signals.is_empty() {
+                out.push_str("<li>No active signals</li>\n");
+            }
+            for s in signals {
+                out.push_str(&format!(
+                    "<li>{} since {}: {}</li>\n",
+                    s.kind.label(), signal_date(s), html_escape(&s.detail),
+                ));
+            }
+            out.push_str("</ul>\n");
+            if let Some(png) = render_chart_png(e.stock_data, a, CHART_SIZE.0, CHART_SIZE.1) {
+                out.push_str(&format!(
+                    "<img alt=\"{} chart\" src=\"data:image/png;base64,{}\">\n",
+                    symbol, STANDARD.encode(png),
+                ));
+            }
+        }
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+}
+
+/// Directory the Markdown report at `path` keeps its charts in:
+/// `daily.md` gets `daily-charts/`.
+fn chart_dir(path: &Path) -> PathBuf {
+    let stem = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    path.with_file_name(format!("{}-charts", stem))
+}
+
+/// Write the report to `path`, plus a chart folder beside it for Markdown.
+pub fn write_report(path: &Path, format: ReportFormat, report: &Report) -> Result<(), AppError> {
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)?;
+    }
+    let text = match format {
+        ReportFormat::Html => report.to_html(),
+        ReportFormat::Markdown => {
+            let dir = chart_dir(path);
+            let dir_name = dir.file_name().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+            let mut charted = Vec::new();
+            for e in &report.entries {
+                if let Some(png) = render_chart_png(e.stock_data, e.analysis, CHART_SIZE.0, CHART_SIZE.1) {
+                    fs::create_dir_all(&dir)?;
+                    fs::write(dir.join(format!("{}.png", e.analysis.symbol)), png)?;
+                    charted.push(e.analysis.symbol.clone());
+                }
+            }
+            report.to_markdown(|symbol| {
+                charted.iter().any(|s| s == symbol).then(|| format!("{}/{}.png", dir_name, symbol))
+            })
+        }
+    };
+    fs::write(path, text)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lib::analysis::analyze_stock;
+    use crate::lib::forecast::Forecaster;
+
+    #[test]
+    fn test_report_lists_every_symbol() {
+        let mut data = StockData::new();
+        for i in 0..30 {
+            let p = 50.0 + i as f64;
+            data.add_point(1_700_000_000 + i * 86_400, p, p + 1.0, p - 1.0, p, 1_000);
+        }
+        let a = analyze_stock(&data, "AAA", Forecaster::Linear);
+        let b = analyze_stock(&data, "B<B", Forecaster::Linear);
+        let report = Report {
+            title: "Tech".into(),
+            range: "3M".into(),
+            generated: chrono::Local::now().fixed_offset(),
+            entries: vec![
+                ReportEntry { stock_data: &data, analysis: &a },
+                ReportEntry { stock_data: &data, analysis: &b },
+            ],
+        };
+        let md = report.to_markdown(|s| Some(format!("charts/{}.png", s)));
+        assert!(md.contains("| AAA | $79.00 |"));
+        assert!(md.contains("## B<B"));
+        assert!(md.contains("![AAA chart](charts/AAA.png)"));
+        let html = report.to_html();
+        assert!(html.contains("<h2 id=\"B&lt;B\">B&lt;B</h2>"));
+        assert_eq!(html.matches("data:image/png;base64,").count(), 2);
+    }
+}
@@ -0,0 +1,336 @@
+//! Retries and rate limiting around a [`DataProvider`]: only a few requests
+//! run at once, they are paced to stay under the provider's limit, and
+//! temporary failures (429s, timeouts, server errors) are tried again
+//! after a growing pause instead of leaving a hole in the watchlist.
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::sync::Semaphore;
+use tokio::time::Instant;
+
+use crate::data::TimeRange;
+use crate::lib::{
+    config::{RetrySettings, SymbolProfile},
+    dividends::Dividend,
+    error::AppError,
+    insiders::InsiderTrade,
+    provider::{AnalystRatings, DataProvider, Fundamentals, NewsItem, ProviderFuture, Quote, SymbolMatch},
+    stock_data::StockData,
+};
+
+/// Requests per minute each provider gets unless the config says otherwise.
+pub fn default_rate_limit(provider: &str) -> Option<u32> {
+    match provider {
+        "yahoo" => Some(240),
+        // The free tier's limit
+        "alphavantage" => Some(5),
+        "finnhub" => Some(60),
+        "stooq" => Some(60),
+        _ => None,
+    }
+}
+
+/// A random fraction in `[0, 1)`. `RandomState` is seeded afresh for each
+/// instance, which is plenty for spreading retries apart.
+fn jitter() -> f64 {
+    let bits = RandomState::new().build_hasher().finish();
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+impl RetrySettings {
+    /// Pause before retry number `retry` (1 for the first): the base delay
+    /// doubled each time up to the cap, of which the upper half is random
+    /// so clients that failed together do not all retry together.
+    pub fn delay(&self, retry: u32) -> Duration {
+        let doubled = self.base_delay_ms.saturating_mul(1 << retry.saturating_sub(1).min(20));
+        let ceiling = doubled.min(self.max_delay_ms) as f64;
+        Duration::from_millis((ceiling * (0.5 + jitter() / 2.0)) as u64)
+    }
+}
+
+/// Spaces requests evenly, at most `per_minute` a minute, across every task
+/// using the provider.
+pub struct RateLimiter {
+    interval: Duration,
+    /// Earliest time the next request may go out.
+    next: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    pub fn per_minute(per_minute: u32) -> Self {
+        RateLimiter {
+            interval: Duration::from_secs(60) / per_minute.max(1),
+            next: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Wait for this request's turn.
+    pub async fn wait(&self) {
+        let slot = {
+            let Ok(mut next) = self.next.lock() else { return };
+            let slot = (*next).max(Instant::now());
+            *next = slot + self.interval;
+            slot
+        };
+        tokio::time::sleep_until(slot).await;
+    }
+}
+
+/// `inner`, with each request waiting for a free slot and the rate
+/// limiter, and retried on temporary failures.
+pub struct Retrying {
+    inner: Arc<dyn DataProvider>,
+    settings: RetrySettings,
+    limiter: Option<RateLimiter>,
+    /// One permit per request allowed to be open at once.
+    slots: Semaphore,
+}
+
+impl Retrying {
+    /// `per_minute` of `None` or 0 leaves requests unpaced; at most
+    /// `max_concurrent` (at least one) run at a time.
+    pub fn new(inner: Arc<dyn DataProvider>, settings: RetrySettings, per_minute: Option<u32>, max_concurrent: usize) -> Self {
+        let limiter = per_minute.filter(|&n| n > 0).map(RateLimiter::per_minute);
+        Retrying { inner, settings, limiter, slots: Semaphore::new(max_concurrent.max(1)) }
+    }
+
+    fn call<'a, T: Send + 'a>(
+        &'a self,
+        call: impl Fn(&'a dyn DataProvider) -> ProviderFuture<'a, T> + Send + 'a,
+    ) -> ProviderFuture<'a, T> {
+        Box::pin(async move {
+            // Held through the retries, so a struggling provider gets fewer requests
+            let _slot = self.slots.acquire().await;
+            let mut retry = 0;
+            loop {
+                if let Some(limiter) = &self.limiter {
+                    limiter.wait().await;
+                }
+                match call(self.inner.as_ref()).await {
+                    Err(AppError::Unavailable(_)) if retry + 1 < self.settings.max_attempts => {
+                        retry += 1;
+                        tokio::time::sleep(self.settings.delay(retry)).await;
+                    }
+                    result => return result,
+                }
+            }
+        })
+    }
+}
+
+impl DataProvider for Retrying {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    fn fetch_history<'a>(&'a self, symbol: &'a str, time_range: TimeRange) -> ProviderFuture<'a, StockData> {
+        self.call(move |p| p.fetch_history(symbol, time_range))
+    }
+
+    fn fetch_history_since<'a>(&'a self, symbol: &'a str, time_range: TimeRange, since: i64) -> ProviderFuture<'a, StockData> {
+        self.call(move |p| p.fetch_history_since(symbol, time_range, since))
+    }
+
+    fn fetch_doubled_history<'a>(&'a self, symbol: &'a str, time_range: TimeRange) -> ProviderFuture<'a, StockData> {
+        self.call(move |p| p.fetch_doubled_history(symbol, time_range))
+    }
+
+    fn fetch_quote<'a>(&'a self, symbol: &'a str) -> ProviderFuture<'a, Quote> {
+        self.call(move |p| p.fetch_quote(symbol))
+    }
+
+    fn search_symbols<'a>(&'a self, query: &'a str) -> ProviderFuture<'a, Vec<SymbolMatch>> {
+        self.call(move |p| p.search_symbols(query))
+    }
+
+    fn fetch_profile<'a>(&'a self, symbol: &'a str) -> ProviderFuture<'a, SymbolProfile> {
+        self.call(move |p| p.fetch_profile(symbol))
+    }
+
+    fn fetch_fundamentals<'a>(&'a self, symbol: &'a str) -> ProviderFuture<'a, Fundamentals> {
+        self.call(move |p| p.fetch_fundamentals(symbol))
+    }
+
+    fn fetch_ratings<'a>(&'a self, symbol: &'a str) -> ProviderFuture<'a, AnalystRatings> {
+        self.call(move |p| p.fetch_ratings(symbol))
+    }
+
+    fn fetch_insider_trades<'a>(&'a self, symbol: &'a str) -> ProviderFuture<'a, Vec<InsiderTrade>> {
+        self.call(move |p| p.fetch_insider_trades(symbol))
+    }
+
+    fn fetch_dividends<'a>(&'a self, symbol: &'a str) -> ProviderFuture<'a, Vec<Dividend>> {
+        self.call(move |p| p.fetch_dividends(symbol))
+    }
+
+    fn fetch_news<'a>(&'a self, symbol: &'a str) -> ProviderFuture<'a, Vec<NewsItem>> {
+        self.call(move |p| p.fetch_news(symbol))
+    }
+
+    fn served_by(&self, symbol: &str) -> &'static str {
+        self.inner.served_by(symbol)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// Fails temporarily `failures` times, then answers; "BAD" always
+    /// fails for good.
+    struct Flaky {
+        failures: u32,
+        calls: AtomicU32,
+    }
+
+    impl DataProvider for Flaky {
+        fn name(&self) -> &'static str {
+            "Flaky"
+        }
+
+        fn fetch_history<'a>(&'a self, symbol: &'a str, _: TimeRange) -> ProviderFuture<'a, StockData> {
+            Box::pin(async move {
+                let call = self.calls.fetch_add(1, Ordering::SeqCst);
+                if symbol == "BAD" {
+                    return Err(AppError::ApiError("unknown symbol".into()));
+                }
+                if call < self.failures {
+                    return Err(AppError::Unavailable("429 Too Many Requests".into()));
+                }
+                Ok(StockData::new())
+            })
+        }
+
+        fn fetch_history_since<'a>(&'a self, symbol: &'a str, time_range: TimeRange, _: i64) -> ProviderFuture<'a, StockData> {
+            self.fetch_history(symbol, time_range)
+        }
+
+        fn fetch_doubled_history<'a>(&'a self, symbol: &'a str, time_range: TimeRange) -> ProviderFuture<'a, StockData> {
+            self.fetch_history(symbol, time_range)
+        }
+
+        fn fetch_quote<'a>(&'a self, _: &'a str) -> ProviderFuture<'a, Quote> {
+            Box::pin(async { Err(AppError::ApiError("no quotes".into())) })
+        }
+
+        fn search_symbols<'a>(&'a self, _: &'a str) -> ProviderFuture<'a, Vec<SymbolMatch>> {
+            Box::pin(async { Ok(Vec::new()) })
+        }
+
+        fn fetch_profile<'a>(&'a self, _: &'a str) -> ProviderFuture<'a, SymbolProfile> {
+            Box::pin(async { Ok(SymbolProfile::default()) })
+        }
+    }
+
+    fn retrying(failures: u32) -> (Arc<Flaky>, Retrying) {
+        let flaky = Arc::new(Flaky { failures, calls: AtomicU32::new(0) });
+        let settings = RetrySettings { max_attempts: 3, base_delay_ms: 1, max_delay_ms: 5 };
+        (flaky.clone(), Retrying::new(flaky, settings, None, 4))
+    }
+
+    #[test]
+    fn test_temporary_failures_are_retried_up_to_the_limit() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let (flaky, provider) = retrying(2);
+        assert!(rt.block_on(provider.fetch_history("OK", TimeRange::OneYear)).is_ok());
+        assert_eq!(flaky.calls.load(Ordering::SeqCst), 3);
+
+        let (flaky, provider) = retrying(5);
+        assert!(matches!(rt.block_on(provider.fetch_history("OK", TimeRange::OneYear)), Err(AppError::Unavailable(_))));
+        assert_eq!(flaky.calls.load(Ordering::SeqCst), 3);
+
+        let (flaky, provider) = retrying(0);
+        assert!(rt.block_on(provider.fetch_history("BAD", TimeRange::OneYear)).is_err());
+        assert_eq!(flaky.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_requests_beyond_the_cap_wait() {
+        /// Counts how many of its requests are open at once.
+        struct Slow {
+            open: AtomicU32,
+            most: AtomicU32,
+        }
+
+        impl DataProvider for Slow {
+            fn name(&self) -> &'static str {
+                "Slow"
+            }
+
+            fn fetch_history<'a>(&'a self, _: &'a str, _: TimeRange) -> ProviderFuture<'a, StockData> {
+                Box::pin(async move {
+                    let open = self.open.fetch_add(1, Ordering::SeqCst) + 1;
+                    self.most.fetch_max(open, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                    self.open.fetch_sub(1, Ordering::SeqCst);
+                    Ok(StockData::new())
+                })
+            }
+
+            fn fetch_history_since<'a>(&'a self, symbol: &'a str, time_range: TimeRange, _: i64) -> ProviderFuture<'a, StockData> {
+                self.fetch_history(symbol, time_range)
+            }
+
+            fn fetch_doubled_history<'a>(&'a self, symbol: &'a str, time_range: TimeRange) -> ProviderFuture<'a, StockData> {
+                self.fetch_history(symbol, time_range)
+            }
+
+            fn fetch_quote<'a>(&'a self, _: &'a str) -> ProviderFuture<'a, Quote> {
+                Box::pin(async { Err(AppError::ApiError("no quotes".into())) })
+            }
+
+            fn search_symbols<'a>(&'a self, _: &'a str) -> ProviderFuture<'a, Vec<SymbolMatch>> {
+                Box::pin(async { Ok(Vec::new()) })
+            }
+
+            fn fetch_profile<'a>(&'a self, _: &'a str) -> ProviderFuture<'a, SymbolProfile> {
+                Box::pin(async { Ok(SymbolProfile::default()) })
+            }
+        }
+
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let slow = Arc::new(Slow { open: AtomicU32::new(0), most: AtomicU32::new(0) });
+        let provider: Arc<dyn DataProvider> = Arc::new(Retrying::new(slow.clone(), RetrySettings::default(), None, 3));
+        rt.block_on(async {
+            let tasks: Vec<_> = (0..12).map(|_| {
+                let provider = provider.clone();
+                tokio::spawn(async move { provider.fetch_history("A", TimeRange::OneYear).await })
+            }).collect();
+            for task in tasks {
+                assert!(task.await.unwrap().is_ok());
+            }
+        });
+        assert_eq!(slow.most.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_the_cap() {
+        let settings = RetrySettings { max_attempts: 5, base_delay_ms: 100, max_delay_ms: 300 };
+        for _ in 0..20 {
+            let first = settings.delay(1).as_millis();
+            assert!((50..=100).contains(&first), "{first}");
+            let second = settings.delay(2).as_millis();
+            assert!((100..=200).contains(&second), "{second}");
+            let capped = settings.delay(10).as_millis();
+            assert!((150..=300).contains(&capped), "{capped}");
+        }
+    }
+
+    #[test]
+    fn test_rate_limiter_spaces_requests() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        // 3000 a minute is one every 20ms
+        let limiter = RateLimiter::per_minute(3000);
+        let started = std::time::Instant::now();
+        rt.block_on(async {
+            for _ in 0..4 {
+                limiter.wait().await;
+            }
+        });
+        assert!(started.elapsed() >= Duration::from_millis(60));
+    }
+}
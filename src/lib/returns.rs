@@ -0,0 +1,141 @@
+const SECS_PER_YEAR: f64 = 365.25 * 86_400.0;
+
+/// External cash moved into (positive) or out of (negative) the portfolio.
+#[derive(Clone, Copy, Debug)]
+pub struct CashFlow {
+    pub timestamp: i64,
+    pub amount: f64,
+}
+
+/// Market value of the portfolio at a point in time, measured *before* any
+/// cash flow with the same timestamp is applied.
+#[derive(Clone, Copy, Debug)]
+pub struct Valuation {
+    pub timestamp: i64,
+    pub value: f64,
+}
+
+/// Time-weighted return — performance with the effect of deposits and
+/// withdrawals removed. Sub-period returns between consecutive valuations
+/// are chained, each period starting from the value plus any flow made at
+/// its start. Returns the total (not annualized) return as a fraction.
+pub fn time_weighted_return(valuations: &[Valuation], flows: &[CashFlow]) -> Option<f64> {
+    if valuations.len() < 2 {
+        return None;
+    }
+    let flow_at = |ts: i64| flows.iter().filter(|f| f.timestamp == ts).map(|f| f.amount).sum::<f64>();
+    let mut growth = 1.0;
+    for w in valuations.windows(2) {
+        let start = w[0].value + flow_at(w[0].timestamp);
+        if start <= 0.0 {
+            // Nothing invested during this period — it contributes no return
+            continue;
+        }
+        growth *= w[1].value / start;
+    }
+    Some(growth - 1.0)
+}
+
+/// Net present value of the investor's cash flows at annual `rate`.
+/// Deposits are outflows for the investor, the final value an inflow.
+fn npv(rate: f64, t0: i64, flows: &[CashFlow], end: Valuation) -> (f64, f64) {
+    let mut value = 0.0;
+    let mut deriv = 0.0;
+    let items = flows.iter().map(|f| (f.timestamp, -f.amount)).chain([(end.timestamp, end.value)]);
+    for (ts, cf) in items {
+        let t = (ts - t0) as f64 / SECS_PER_YEAR;
+        let disc = (1.0 + rate).powf(-t);
+        value += cf * disc;
+        deriv += -t * cf * disc / (1.0 + rate);
+    }
+    (value, deriv)
+}
+
+/// Money-weighted return — what the investor actually earned given the
+/// timing of their flows: the annualized internal rate of return that makes
+/// the deposits/withdrawals and the final value `end` net to zero.
+pub fn money_weighted_return(flows: &[CashFlow], end: Valuation) -> Option<f64> {
+    let t0 = flows.iter().map(|f| f.timestamp).min()?;
+    if end.timestamp <= t0 {
+        return None;
+    }
+
+    // Newton's method from a neutral guess, falling back to bisection
+    let mut rate = 0.1;
+    for _ in 0..50 {
+        let (v, d) = npv(rate, t0, flows, end);
+        if v.abs() < 1e-9 {
+            return Some(rate);
+        }
+        if d == 0.0 || !d.is_finite() {
+            break;
+        }
+        let next = rate - v / d;
+        if !next.is_finite() || next <= -0.9999 {
+            break;
+        }
+        rate = next;
+    }
+
+    let (mut lo, mut hi) = (-0.9999, 100.0);
+    let (v_lo, v_hi) = (npv(lo, t0, flows, end).0, npv(hi, t0, flows, end).0);
+    if v_lo.signum() == v_hi.signum() {
+        return None;
+    }
+    for _ in 0..200 {
+        let mid = (lo + hi) / 2.0;
+        let v_mid = npv(mid, t0, flows, end).0;
+        if v_mid.signum() == v_lo.signum() { lo = mid } else { hi = mid }
+    }
+    Some((lo + hi) / 2.0)
+}
+
+/// Convert a total return over `secs` seconds into an annual rate.
+pub fn annualize(total: f64, secs: i64) -> f64 {
+    let years = secs as f64 / SECS_PER_YEAR;
+    if years <= 0.0 || total <= -1.0 {
+        return total;
+    }
+    (1.0 + total).powf(1.0 / years) - 1.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_abs_diff_eq;
+
+    const YEAR: i64 = 31_557_600;
+
+    #[test]
+    fn test_no_flows_twr_matches_mwr() {
+        let flows = [CashFlow { timestamp: 0, amount: 100.0 }];
+        let vals = [Valuation { timestamp: 0, value: 0.0 }, Valuation { timestamp: YEAR, value: 110.0 }];
+        let twr = time_weighted_return(&vals, &flows).unwrap();
+        let mwr = money_weighted_return(&flows, vals[1]).unwrap();
+        assert_abs_diff_eq!(twr, 0.10, epsilon = 1e-9);
+        assert_abs_diff_eq!(mwr, 0.10, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_badly_timed_deposit() {
+        // Doubles, then a large deposit right before a 50% drop
+        let flows = [
+            CashFlow { timestamp: 0, amount: 100.0 },
+            CashFlow { timestamp: YEAR, amount: 1000.0 },
+        ];
+        let vals = [
+            Valuation { timestamp: 0, value: 0.0 },
+            Valuation { timestamp: YEAR, value: 200.0 },
+            Valuation { timestamp: 2 * YEAR, value: 600.0 },
+        ];
+        let twr = time_weighted_return(&vals, &flows).unwrap();
+        let mwr = money_weighted_return(&flows, vals[2]).unwrap();
+        assert_abs_diff_eq!(twr, 0.0, epsilon = 1e-9);
+        assert!(mwr < -0.3, "investor lost money: {mwr}");
+    }
+
+    #[test]
+    fn test_annualize() {
+        assert_abs_diff_eq!(annualize(0.21, 2 * YEAR), 0.1, epsilon = 1e-9);
+    }
+}
@@ -0,0 +1,207 @@
+use std::collections::BTreeMap;
+
+use ndarray::{Array1, Array2};
+
+use crate::data::trading_day;
+use crate::lib::stock_data::StockData;
+
+/// A position's share of total portfolio volatility.
+#[derive(Clone, Debug)]
+pub struct RiskContribution {
+    pub symbol: String,
+    pub weight: f64,
+    /// Absolute contribution, in the same units as the portfolio volatility.
+    pub contribution: f64,
+    /// Fraction of total volatility; all positions sum to 1.
+    pub share: f64,
+}
+
+impl RiskContribution {
+    /// A position dominates risk when its share of volatility is well above
+    /// its share of capital.
+    pub fn dominates(&self) -> bool {
+        self.share > 0.25 && self.share > 1.5 * self.weight
+    }
+}
+
+/// How a series of returns behaved, annualized.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RiskMetrics {
+    /// Standard deviation of the returns over a year.
+    pub volatility: f64,
+    /// Return over the risk-free rate per unit of volatility; `None` when
+    /// the returns never varied.
+    pub sharpe: Option<f64>,
+    /// How far the returns move with the benchmark's.
+    pub beta: Option<f64>,
+    /// Deepest fall from a peak, as a fraction.
+    pub max_drawdown: f64,
+}
+
+/// Bars a year in `timestamps` (Unix seconds), from their span: about 252
+/// for daily bars, 52 for weekly.
+pub fn periods_per_year(timestamps: &[i64]) -> Option<f64> {
+    let (&first, &last) = (timestamps.first()?, timestamps.last()?);
+    let years = (last - first) as f64 / (365.25 * 86_400.0);
+    (years > 0.0).then(|| (timestamps.len() - 1) as f64 / years)
+}
+
+/// Volatility, Sharpe ratio over `risk_free` (a yearly rate), beta against
+/// `benchmark` (returns on the same bars) and max drawdown of `returns`,
+/// `periods` of them a year.
+pub fn risk_metrics(returns: &[f64], benchmark: Option<&[f64]>, periods: f64, risk_free: f64) -> Option<RiskMetrics> {
+    let n = returns.len();
+    if n < 2 {
+        return None;
+    }
+    let mean = |r: &[f64]| r.iter().sum::<f64>() / r.len() as f64;
+    let covariance = |a: &[f64], b: &[f64]| {
+        let (ma, mb) = (mean(a), mean(b));
+        a.iter().zip(b).map(|(x, y)| (x - ma) * (y - mb)).sum::<f64>() / (a.len() - 1) as f64
+    };
+    let volatility = covariance(returns, returns).sqrt() * periods.sqrt();
+    let sharpe = (volatility > 0.0).then(|| (mean(returns) * periods - risk_free) / volatility);
+    let beta = benchmark.filter(|b| b.len() == n).and_then(|b| {
+        let variance = covariance(b, b);
+        (variance > 0.0).then(|| covariance(returns, b) / variance)
+    });
+
+    let (mut value, mut peak, mut max_drawdown) = (1.0_f64, 1.0_f64, 0.0_f64);
+    for r in returns {
+        value *= 1.0 + r;
+        peak = peak.max(value);
+        max_drawdown = max_drawdown.max(1.0 - value / peak);
+    }
+    Some(RiskMetrics { volatility, sharpe, beta, max_drawdown })
+}
+
+/// Sample covariance of per-period returns; `returns` is bars × assets.
+pub fn covariance(returns: &Array2<f64>) -> Option<Array2<f64>> {
+    let n = returns.nrows();
+    if n < 2 {
+        return None;
+    }
+    let mean = returns.mean_axis(ndarray::Axis(0))?;
+    let centered = returns - &mean;
+    Some(centered.t().dot(&centered) / (n - 1) as f64)
+}
+
+/// Euler decomposition of portfolio volatility σ = √(wᵀΣw): position i
+/// contributes wᵢ(Σw)ᵢ / σ. Results are ranked, largest contribution first.
+///
+/// `returns` holds one column per symbol, aligned on the same bars.
+pub fn risk_contributions(symbols: &[String], weights: &[f64], returns: &Array2<f64>) -> Option<Vec<RiskContribution>> {
+    if symbols.len() != weights.len() || returns.ncols() != weights.len() {
+        return None;
+    }
+    let cov = covariance(returns)?;
+    let w = Array1::from(weights.to_vec());
+    let marginal = cov.dot(&w);
+    let variance = w.dot(&marginal);
+    if variance <= 0.0 {
+        return None;
+    }
+    let vol = variance.sqrt();
+
+    let mut out: Vec<RiskContribution> = symbols
+        .iter()
+        .zip(weights)
+        .zip(marginal.iter())
+        .map(|((symbol, &weight), &m)| {
+            let contribution = weight * m / vol;
+            RiskContribution { symbol: symbol.clone(), weight, contribution, share: contribution / vol }
+        })
+        .collect();
+    out.sort_by(|a, b| b.contribution.total_cmp(&a.contribution));
+    Some(out)
+}
+
+/// Simple returns of each `(symbol, bars)` series over the trading days
+/// they all have, one column per series, with the bar times of the rows'
+/// closes (one more than the returns). Days are matched in each symbol's
+/// own exchange zone, so a day missing from any series — a holiday abroad,
+/// a weekend only crypto trades, a gap in the data — is left out of all of
+/// them rather than pairing returns from different dates.
+pub fn aligned_returns(series: &[(&str, &StockData)]) -> Option<(Vec<i64>, Array2<f64>)> {
+    let days: Vec<BTreeMap<i64, (i64, f64)>> = series.iter()
+        .map(|(symbol, sd)| sd.timestamps.iter().zip(&sd.closes).map(|(&t, &c)| (trading_day(symbol, t), (t, c))).collect())
+        .collect();
+    let (first, rest) = days.split_first()?;
+    let common: Vec<i64> = first.keys().copied().filter(|day| rest.iter().all(|d| d.contains_key(day))).collect();
+    if common.len() < 3 {
+        return None;
+    }
+    let mut out = Array2::zeros((common.len() - 1, series.len()));
+    for (col, bars) in days.iter().enumerate() {
+        for (row, w) in common.windows(2).enumerate() {
+            let (from, to) = (bars[&w[0]].1, bars[&w[1]].1);
+            out[[row, col]] = if from != 0.0 { (to - from) / from } else { 0.0 };
+        }
+    }
+    Some((common.iter().map(|day| first[day].0).collect(), out))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_abs_diff_eq;
+    use ndarray::arr2;
+
+    #[test]
+    fn test_contributions_sum_to_volatility() {
+        let returns = arr2(&[
+            [0.01, 0.002, -0.03],
+            [-0.02, 0.001, 0.05],
+            [0.015, -0.001, -0.04],
+            [0.005, 0.003, 0.06],
+        ]);
+        let symbols: Vec<String> = ["A", "B", "C"].iter().map(|s| s.to_string()).collect();
+        let rc = risk_contributions(&symbols, &[0.4, 0.4, 0.2], &returns).unwrap();
+        let total: f64 = rc.iter().map(|r| r.share).sum();
+        assert_abs_diff_eq!(total, 1.0, epsilon = 1e-9);
+        // The volatile 20% position carries most of the risk
+        assert_eq!(rc[0].symbol, "C");
+        assert!(rc[0].dominates());
+        assert!(!rc.iter().find(|r| r.symbol == "B").unwrap().dominates());
+    }
+
+    #[test]
+    fn test_risk_metrics() {
+        let benchmark = [0.01, -0.02, 0.015, 0.005];
+        let doubled: Vec<f64> = benchmark.iter().map(|r| r * 2.0).collect();
+        let m = risk_metrics(&doubled, Some(&benchmark), 252.0, 0.0).unwrap();
+        assert_abs_diff_eq!(m.beta.unwrap(), 2.0, epsilon = 1e-12);
+        assert_abs_diff_eq!(m.volatility, risk_metrics(&benchmark, None, 252.0, 0.0).unwrap().volatility * 2.0, epsilon = 1e-12);
+        // Up 2%, then down 4%
+        assert_abs_diff_eq!(m.max_drawdown, 0.04, epsilon = 1e-12);
+        assert!(m.sharpe.unwrap() > 0.0);
+
+        let flat = risk_metrics(&[0.0, 0.0, 0.0], None, 52.0, 0.02).unwrap();
+        assert_eq!((flat.volatility, flat.sharpe, flat.beta), (0.0, None, None));
+        assert!(risk_metrics(&[0.01], None, 252.0, 0.0).is_none());
+
+        let week = 7 * 86_400;
+        let weekly: Vec<i64> = (0..53).map(|i| i * week).collect();
+        assert_abs_diff_eq!(periods_per_year(&weekly).unwrap(), 52.18, epsilon = 0.01);
+    }
+
+    #[test]
+    fn test_aligned_returns_skips_days_missing_from_any_series() {
+        let day = |d: i64| 1_709_562_600 + d * 86_400;
+        let (mut a, mut b) = (StockData::new(), StockData::new());
+        for (d, close) in [100.0, 110.0, 99.0, 120.0].into_iter().enumerate() {
+            a.add_point(day(d as i64), close, close, close, close, 0);
+        }
+        // B has no bar on the third day
+        for (d, close) in [(0, 1.0), (1, 50.0), (3, 100.0)] {
+            b.add_point(day(d), close, close, close, close, 0);
+        }
+        let (ts, r) = aligned_returns(&[("AAA", &a), ("BBB", &b)]).unwrap();
+        assert_eq!(ts, vec![day(0), day(1), day(3)]);
+        assert_eq!(r.shape(), &[2, 2]);
+        // The second return spans the gap in both columns: 110 to 120, 50 to 100
+        assert_abs_diff_eq!(r[[1, 0]], 10.0 / 110.0, epsilon = 1e-12);
+        assert_abs_diff_eq!(r[[1, 1]], 1.0, epsilon = 1e-12);
+        assert_abs_diff_eq!(r[[0, 1]], 49.0, epsilon = 1e-12);
+    }
+}
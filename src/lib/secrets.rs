@@ -0,0 +1,89 @@
+//! API keys in the OS credential store, so they stay out of the plain-text
+//! config: the login keychain on macOS (through `security`) and the Secret
+//! Service — GNOME Keyring, KWallet — elsewhere on Unix (through
+//! `secret-tool`). Other platforms have no store, and keys stay in the
+//! config file there.
+//!
+//! Each call runs the tool and waits for it, which takes a few tens of
+//! milliseconds. Keys are only read when a provider is set up (at start,
+//! on switching watchlist or provider) and written from `config api-key`
+//! or the command palette, so the wait falls on those key presses and never
+//! on drawing a frame. Keys never go on a command line, where other users
+//! could read them from the process list.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use crate::lib::error::AppError;
+
+/// Service the keys are filed under; the account is the provider name.
+const SERVICE: &str = "bstock";
+
+/// Run `program` with `args`, feeding it `input`, and return its output.
+#[cfg_attr(not(unix), allow(dead_code))]
+fn run(program: &str, args: &[&str], input: Option<&str>) -> Result<String, AppError> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| AppError::Secrets(format!("{}: {}", program, e)))?;
+    if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+        stdin.write_all(input.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        let message = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(AppError::Secrets(format!("{} failed: {}", program, message)));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// The key stored for `account`, if any.
+pub fn get(account: &str) -> Option<String> {
+    let found = if cfg!(target_os = "macos") {
+        run("security", &["find-generic-password", "-s", SERVICE, "-a", account, "-w"], None)
+    } else if cfg!(unix) {
+        run("secret-tool", &["lookup", "service", SERVICE, "account", account], None)
+    } else {
+        return None;
+    };
+    found.ok().filter(|key| !key.is_empty())
+}
+
+/// Store `key` for `account`, replacing any earlier one.
+pub fn set(account: &str, key: &str) -> Result<(), AppError> {
+    if cfg!(target_os = "macos") {
+        // `security` only takes the password as an argument, so the command
+        // goes to its interactive mode on stdin instead. That mode exits
+        // successfully whatever the command did, hence reading it back.
+        if key.chars().any(|c| c == '"' || c == '\\' || c.is_control()) {
+            return Err(AppError::Secrets("the keychain cannot take a key with quotes, backslashes or control characters".into()));
+        }
+        let command = format!("add-generic-password -U -s {} -a \"{}\" -w \"{}\"\n", SERVICE, account, key);
+        run("security", &["-i"], Some(&command))?;
+        if get(account).as_deref() != Some(key) {
+            return Err(AppError::Secrets("the keychain did not store the key".into()));
+        }
+    } else if cfg!(unix) {
+        let label = format!("bstock {} API key", account);
+        run("secret-tool", &["store", "--label", &label, "service", SERVICE, "account", account], Some(key))?;
+    } else {
+        return Err(AppError::Secrets("no credential store on this platform".into()));
+    }
+    Ok(())
+}
+
+/// Remove the key stored for `account`; having none is not an error.
+pub fn delete(account: &str) -> Result<(), AppError> {
+    if get(account).is_none() {
+        return Ok(());
+    }
+    if cfg!(target_os = "macos") {
+        run("security", &["delete-generic-password", "-s", SERVICE, "-a", account], None)?;
+    } else {
+        run("secret-tool", &["clear", "service", SERVICE, "account", account], None)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,120 @@
+//! Headline sentiment from a small finance lexicon: each headline scores
+//! by its positive and negative words, and a symbol's sentiment is the
+//! recency-weighted mean of the headlines that score at all. Crude next to
+//! a trained model, but it needs nothing beyond the headlines.
+
+use crate::lib::provider::NewsItem;
+
+const POSITIVE: &[&str] = &[
+    "beat", "beats", "boost", "boosts", "breakout", "bullish", "buy", "climb", "climbs", "gain", "gains",
+    "growth", "high", "higher", "jump", "jumps", "outperform", "outperforms", "profit", "profitable",
+    "rally", "rallies", "rebound", "rebounds", "record", "rise", "rises", "soar", "soars", "strong",
+    "surge", "surges", "top", "tops", "upgrade", "upgraded", "upgrades", "win", "wins",
+];
+
+const NEGATIVE: &[&str] = &[
+    "bankruptcy", "bearish", "concern", "concerns", "crash", "crashes", "cut", "cuts", "decline",
+    "declines", "default", "downgrade", "downgraded", "downgrades", "drop", "drops", "fall", "falls",
+    "fraud", "investigation", "lawsuit", "layoffs", "loss", "losses", "low", "lower", "miss", "misses",
+    "plunge", "plunges", "probe", "recall", "sell", "selloff", "slump", "slumps", "tumble", "tumbles",
+    "warning", "weak",
+];
+
+/// Words that flip the sense of the word after them.
+const NEGATORS: &[&str] = &["no", "not", "never", "without", "fails", "failed"];
+
+/// How long a headline takes to count half as much, in seconds.
+const HALF_LIFE: f64 = 2.0 * 86_400.0;
+
+/// Aggregate sentiment of a symbol's headlines.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sentiment {
+    /// From -1 (all negative) to 1 (all positive).
+    pub score: f64,
+    /// Headlines with any lexicon word, out of `total`.
+    pub scored: usize,
+    pub total: usize,
+}
+
+impl Sentiment {
+    pub fn label(&self) -> &'static str {
+        match self.score {
+            s if s >= 0.15 => "Bullish",
+            s if s <= -0.15 => "Bearish",
+            _ => "Neutral",
+        }
+    }
+}
+
+/// A headline's score from -1 to 1, or `None` when it has no lexicon word.
+pub fn score_headline(title: &str) -> Option<f64> {
+    let (mut positive, mut negative) = (0u32, 0u32);
+    let mut negated = false;
+    for word in title.split(|c: char| !c.is_alphanumeric() && c != '\'').filter(|w| !w.is_empty()) {
+        let word = word.to_lowercase();
+        let sign = if POSITIVE.contains(&word.as_str()) {
+            1
+        } else if NEGATIVE.contains(&word.as_str()) {
+            -1
+        } else {
+            negated = NEGATORS.contains(&word.as_str());
+            continue;
+        };
+        if (sign > 0) != negated {
+            positive += 1;
+        } else {
+            negative += 1;
+        }
+        negated = false;
+    }
+    let hits = positive + negative;
+    (hits > 0).then(|| (positive as f64 - negative as f64) / hits as f64)
+}
+
+/// Sentiment of `news` as of `now`, newer headlines weighing more; `None`
+/// when no headline scores.
+pub fn aggregate(news: &[NewsItem], now: i64) -> Option<Sentiment> {
+    let (mut sum, mut weights, mut scored) = (0.0, 0.0, 0);
+    for item in news {
+        let Some(score) = score_headline(&item.title) else { continue };
+        let age = (now - item.published).max(0) as f64;
+        let weight = 0.5f64.powf(age / HALF_LIFE);
+        sum += score * weight;
+        weights += weight;
+        scored += 1;
+    }
+    (scored > 0 && weights > 0.0).then(|| Sentiment { score: sum / weights, scored, total: news.len() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headline(title: &str, published: i64) -> NewsItem {
+        NewsItem { title: title.into(), publisher: String::new(), link: String::new(), published }
+    }
+
+    #[test]
+    fn test_headline_scores() {
+        assert_eq!(score_headline("Apple beats estimates, shares surge"), Some(1.0));
+        assert_eq!(score_headline("Regulators open probe after shares plunge"), Some(-1.0));
+        assert_eq!(score_headline("Results beat estimates but guidance cut"), Some(0.0));
+        assert_eq!(score_headline("Company does not beat estimates"), Some(-1.0));
+        assert_eq!(score_headline("Annual meeting scheduled for May"), None);
+    }
+
+    #[test]
+    fn test_newer_headlines_weigh_more() {
+        let now = 1_760_000_000;
+        let news = [
+            headline("Shares surge on record profit", now),
+            headline("Analysts downgrade the stock", now - 4 * 86_400),
+            headline("Board meets on Tuesday", now),
+        ];
+        let sentiment = aggregate(&news, now).unwrap();
+        // Weights 1 and 1/4: (1 - 0.25) / 1.25
+        assert!((sentiment.score - 0.6).abs() < 1e-9);
+        assert_eq!((sentiment.scored, sentiment.total, sentiment.label()), (2, 3, "Bullish"));
+        assert!(aggregate(&news[2..], now).is_none());
+    }
+}
@@ -0,0 +1,370 @@
+use crate::lib::{analysis::StockAnalysis, stock_data::StockData};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Signals older than this many bars are no longer considered active.
+const ACTIVE_LOOKBACK: usize = 5;
+/// Window used for the "normal" return and volume baselines.
+const BASELINE_WINDOW: usize = 20;
+/// Signals kept per symbol in the history; the oldest go first.
+const MAX_HISTORY: usize = 500;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum SignalKind {
+    GoldenCross,
+    DeathCross,
+    CrossAboveEma,
+    CrossBelowEma,
+    RsiOverbought,
+    RsiOversold,
+    MacdBullishCross,
+    MacdBearishCross,
+    BollingerBreakout,
+    BollingerBreakdown,
+    PriceAnomaly,
+    VolumeSpike,
+}
+
+impl SignalKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SignalKind::GoldenCross => "Golden cross",
+            SignalKind::DeathCross => "Death cross",
+            SignalKind::CrossAboveEma => "Above EMA20",
+            SignalKind::CrossBelowEma => "Below EMA20",
+            SignalKind::RsiOverbought => "RSI overbought",
+            SignalKind::RsiOversold => "RSI oversold",
+            SignalKind::MacdBullishCross => "MACD bullish cross",
+            SignalKind::MacdBearishCross => "MACD bearish cross",
+            SignalKind::BollingerBreakout => "Bollinger breakout",
+            SignalKind::BollingerBreakdown => "Bollinger breakdown",
+            SignalKind::PriceAnomaly => "Price anomaly",
+            SignalKind::VolumeSpike => "Volume spike",
+        }
+    }
+
+    /// Short form for the badges on tiles.
+    pub fn badge(&self) -> &'static str {
+        match self {
+            SignalKind::GoldenCross => "GOLD X",
+            SignalKind::DeathCross => "DEATH X",
+            SignalKind::CrossAboveEma => "EMA↑",
+            SignalKind::CrossBelowEma => "EMA↓",
+            SignalKind::RsiOverbought => "RSI>70",
+            SignalKind::RsiOversold => "RSI<30",
+            SignalKind::MacdBullishCross => "MACD↑",
+            SignalKind::MacdBearishCross => "MACD↓",
+            SignalKind::BollingerBreakout => "BB↑",
+            SignalKind::BollingerBreakdown => "BB↓",
+            SignalKind::PriceAnomaly => "MOVE",
+            SignalKind::VolumeSpike => "VOL",
+        }
+    }
+
+    /// `Some(true)` for bullish, `Some(false)` for bearish, `None` if neutral.
+    pub fn bias(&self) -> Option<bool> {
+        match self {
+            SignalKind::GoldenCross | SignalKind::CrossAboveEma | SignalKind::RsiOversold
+                | SignalKind::MacdBullishCross | SignalKind::BollingerBreakout => Some(true),
+            SignalKind::DeathCross | SignalKind::CrossBelowEma | SignalKind::RsiOverbought
+                | SignalKind::MacdBearishCross | SignalKind::BollingerBreakdown => Some(false),
+            SignalKind::PriceAnomaly | SignalKind::VolumeSpike => None,
+        }
+    }
+
+    /// Contribution to the composite score: trend changes on the slower
+    /// averages count most, neutral anomalies not at all.
+    pub fn weight(&self) -> i32 {
+        match self {
+            SignalKind::GoldenCross => 3,
+            SignalKind::DeathCross => -3,
+            SignalKind::CrossAboveEma => 2,
+            SignalKind::CrossBelowEma => -2,
+            SignalKind::MacdBullishCross => 2,
+            SignalKind::MacdBearishCross => -2,
+            SignalKind::RsiOversold | SignalKind::BollingerBreakout => 1,
+            SignalKind::RsiOverbought | SignalKind::BollingerBreakdown => -1,
+            SignalKind::PriceAnomaly | SignalKind::VolumeSpike => 0,
+        }
+    }
+}
+
+/// Composite score of a symbol's active signals — positive is bullish.
+/// The SMA-10/SMA-50 ordering adds ±1 so symbols without fresh crosses
+/// still rank by trend.
+pub fn composite_score(signals: &[Signal], analysis: &StockAnalysis) -> i32 {
+    let trend = match (analysis.sma_10, analysis.sma_50) {
+        (Some(fast), Some(slow)) if fast > slow => 1,
+        (Some(fast), Some(slow)) if fast < slow => -1,
+        _ => 0,
+    };
+    signals.iter().map(|s| s.kind.weight()).sum::<i32>() + trend
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Signal {
+    pub symbol: String,
+    pub kind: SignalKind,
+    /// Timestamp of the bar on which the signal fired.
+    pub timestamp: i64,
+    pub detail: String,
+}
+
+/// A signal as it fired, kept in the signal history.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SignalEvent {
+    pub kind: SignalKind,
+    pub timestamp: i64,
+    pub detail: String,
+    /// Close of the bar it fired on.
+    pub close: f64,
+}
+
+impl SignalEvent {
+    /// Percent change from the close it fired at to `price`, and whether
+    /// that went the way the signal pointed (`None` for neutral ones).
+    pub fn outcome(&self, price: f64) -> (f64, Option<bool>) {
+        let change = if self.close != 0.0 { (price / self.close - 1.0) * 100.0 } else { 0.0 };
+        (change, self.kind.bias().map(|bullish| if bullish { change > 0.0 } else { change < 0.0 }))
+    }
+}
+
+/// Signals that have fired, by symbol, oldest first.
+pub type SignalHistory = BTreeMap<String, Vec<SignalEvent>>;
+
+/// Add the `signals` not already in `history` — the same rule on the same
+/// bar — at the close of that bar. Returns whether any was added.
+pub fn record_history(history: &mut Vec<SignalEvent>, signals: &[Signal], stock_data: &StockData) -> bool {
+    let mut added = false;
+    for signal in signals {
+        if history.iter().any(|e| e.kind == signal.kind && e.timestamp == signal.timestamp) {
+            continue;
+        }
+        let Ok(j) = stock_data.timestamps.binary_search(&signal.timestamp) else { continue };
+        history.push(SignalEvent {
+            kind: signal.kind,
+            timestamp: signal.timestamp,
+            detail: signal.detail.clone(),
+            close: stock_data.closes[j],
+        });
+        added = true;
+    }
+    if added {
+        history.sort_by_key(|e| e.timestamp);
+        let excess = history.len().saturating_sub(MAX_HISTORY);
+        history.drain(..excess);
+    }
+    added
+}
+
+/// Value of an overlay series at bar `j`, where `series[0]` belongs to
+/// bar `period - 1` (the layout produced by `StockData::sma`/`ema`).
+fn overlay_at(series: &[f64], period: usize, j: usize) -> Option<f64> {
+    series.get(j.checked_sub(period - 1)?).copied()
+}
+
+/// Most recent bar within the lookback where `fast` crossed `slow`.
+/// Returns the bar index and whether the cross was upward.
+fn last_cross(
+    len: usize,
+    fast: impl Fn(usize) -> Option<f64>,
+    slow: impl Fn(usize) -> Option<f64>,
+) -> Option<(usize, bool)> {
+    let first = len.saturating_sub(ACTIVE_LOOKBACK).max(1);
+    (first..len).rev().find_map(|j| {
+        let (f0, s0, f1, s1) = (fast(j - 1)?, slow(j - 1)?, fast(j)?, slow(j)?);
+        if f0 <= s0 && f1 > s1 {
+            Some((j, true))
+        } else if f0 >= s0 && f1 < s1 {
+            Some((j, false))
+        } else {
+            None
+        }
+    })
+}
+
+/// Evaluate every rule against the latest bars and return the signals that
+/// are still active.
+pub fn detect_signals(symbol: &str, stock_data: &StockData, analysis: &StockAnalysis) -> Vec<Signal> {
+    let len = stock_data.len();
+    let mut signals = Vec::new();
+    if len < 2 {
+        return signals;
+    }
+    let mut push = |kind: SignalKind, j: usize, detail: String| {
+        signals.push(Signal {
+            symbol: symbol.to_string(),
+            kind,
+            timestamp: stock_data.timestamps[j],
+            detail,
+        });
+    };
+
+    // ── SMA-10 / SMA-50 crossover ───────────────────────────
+    if let Some((j, up)) = last_cross(
+        len,
+        |j| overlay_at(&analysis.sma10_values, 10, j),
+        |j| overlay_at(&analysis.sma50_values, 50, j),
+    ) {
+        let kind = if up { SignalKind::GoldenCross } else { SignalKind::DeathCross };
+        push(kind, j, format!("SMA10 crossed {} SMA50", if up { "above" } else { "below" }));
+    }
+
+    // ── price / EMA-20 crossover ────────────────────────────
+    if let Some((j, up)) = last_cross(
+        len,
+        |j| stock_data.closes.get(j).copied(),
+        |j| overlay_at(&analysis.ema20_values, 20, j),
+    ) {
+        let kind = if up { SignalKind::CrossAboveEma } else { SignalKind::CrossBelowEma };
+        push(kind, j, format!("Close ${:.2} vs EMA20", stock_data.closes[j]));
+    }
+
+    // ── RSI extremes: active while RSI stays in the zone ────
+    if let Some(rsi) = stock_data.rsi(14) {
+        let rsi = rsi.to_vec();
+        let offset = len - rsi.len();
+        let latest = rsi[rsi.len() - 1];
+        let overbought = latest > 70.0;
+        if overbought || latest < 30.0 {
+            let kind = if overbought { SignalKind::RsiOverbought } else { SignalKind::RsiOversold };
+            let in_zone = |v: f64| if overbought { v > 70.0 } else { v < 30.0 };
+            // Date the signal from when RSI entered the zone
+            let entered = rsi.iter().rposition(|v| !in_zone(*v)).map_or(0, |k| k + 1);
+            push(kind, entered + offset, format!("RSI(14) {:.1}", latest));
+        }
+    }
+
+    // ── MACD(12, 26, 9) / signal-line crossover ─────────────
+    if let Some(macd) = stock_data.macd(12, 26, 9) {
+        let offset = len - macd.len();
+        let at = |j: usize| macd.get(j.checked_sub(offset)?).copied();
+        if let Some((j, up)) = last_cross(len, |j| Some(at(j)?.0), |j| Some(at(j)?.1)) {
+            let kind = if up { SignalKind::MacdBullishCross } else { SignalKind::MacdBearishCross };
+            push(kind, j, format!("MACD {:.2} crossed {} signal", macd[j - offset].0, if up { "above" } else { "below" }));
+        }
+    }
+
+    // ── Bollinger(20, 2) breakouts: active while outside ────
+    if let Some(bands) = stock_data.bollinger(20, 2.0) {
+        let offset = len - bands.len();
+        let (lower, _, upper) = bands[bands.len() - 1];
+        let latest = stock_data.closes[len - 1];
+        let above = latest > upper;
+        if above || latest < lower {
+            let kind = if above { SignalKind::BollingerBreakout } else { SignalKind::BollingerBreakdown };
+            let outside = |k: usize| {
+                let close = stock_data.closes[k + offset];
+                if above { close > bands[k].2 } else { close < bands[k].0 }
+            };
+            // Date the signal from when the close left the bands
+            let left = (0..bands.len()).rev().find(|&k| !outside(k)).map_or(0, |k| k + 1);
+            let band = if above { ("above", upper) } else { ("below", lower) };
+            push(kind, left + offset, format!("Close ${:.2} {} band ${:.2}", latest, band.0, band.1));
+        }
+    }
+
+    // ── anomalies vs the trailing baseline ──────────────────
+    let returns: Vec<f64> = stock_data.closes.windows(2)
+        .map(|w| if w[0] != 0.0 { (w[1] - w[0]) / w[0] } else { 0.0 })
+        .collect();
+    let first = len.saturating_sub(ACTIVE_LOOKBACK).max(BASELINE_WINDOW + 1);
+    for j in (first..len).rev() {
+        let base = &returns[j - 1 - BASELINE_WINDOW..j - 1];
+        let mean = base.iter().sum::<f64>() / base.len() as f64;
+        let sd = (base.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / base.len() as f64).sqrt();
+        let r = returns[j - 1];
+        if sd > 0.0 && ((r - mean) / sd).abs() > 3.0 {
+            push(SignalKind::PriceAnomaly, j, format!("{:+.2}% move ({:.1}σ)", r * 100.0, (r - mean) / sd));
+            break;
+        }
+    }
+    for j in (first..len).rev() {
+        let base = &stock_data.volumes[j - BASELINE_WINDOW..j];
+        let avg = base.iter().sum::<u64>() as f64 / base.len() as f64;
+        if avg > 0.0 && stock_data.volumes[j] as f64 > 3.0 * avg {
+            push(SignalKind::VolumeSpike, j, format!("{:.1}× avg volume", stock_data.volumes[j] as f64 / avg));
+            break;
+        }
+    }
+
+    signals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lib::analysis::analyze_stock;
+    use crate::lib::forecast::Forecaster;
+
+    fn rising(n: usize) -> StockData {
+        let mut sd = StockData::new();
+        for i in 0..n {
+            let p = 100.0 + i as f64;
+            sd.add_point(i as i64 * 86_400, p, p + 1.0, p - 1.0, p, 1_000);
+        }
+        sd
+    }
+
+    #[test]
+    fn test_steady_rise_is_overbought() {
+        let sd = rising(30);
+        let analysis = analyze_stock(&sd, "TEST", Forecaster::Linear);
+        let signals = detect_signals("TEST", &sd, &analysis);
+        let rsi = signals.iter().find(|s| s.kind == SignalKind::RsiOverbought).unwrap();
+        // RSI has been pinned at 100 since its first value (bar 14)
+        assert_eq!(rsi.timestamp, 14 * 86_400);
+        assert!(signals.iter().all(|s| s.kind != SignalKind::VolumeSpike));
+    }
+
+    #[test]
+    fn test_composite_score() {
+        let sd = rising(60);
+        let analysis = analyze_stock(&sd, "TEST", Forecaster::Linear);
+        let signals = detect_signals("TEST", &sd, &analysis);
+        // Overbought (-1) but SMA10 above SMA50 (+1)
+        assert_eq!(composite_score(&signals, &analysis), 0);
+    }
+
+    #[test]
+    fn test_macd_cross_and_bollinger_breakout() {
+        // Flat, a slide, then a jump well clear of the bands
+        let mut sd = StockData::new();
+        for i in 0..60 {
+            let p = if i < 40 { 100.0 } else if i < 59 { 100.0 - (i - 39) as f64 * 0.5 } else { 110.0 };
+            sd.add_point(i as i64 * 86_400, p, p, p, p, 1_000);
+        }
+        let analysis = analyze_stock(&sd, "TEST", Forecaster::Linear);
+        let signals = detect_signals("TEST", &sd, &analysis);
+        let kinds: Vec<SignalKind> = signals.iter().map(|s| s.kind).collect();
+        assert!(kinds.contains(&SignalKind::MacdBullishCross), "{:?}", kinds);
+        let breakout = signals.iter().find(|s| s.kind == SignalKind::BollingerBreakout).unwrap();
+        assert_eq!(breakout.timestamp, 59 * 86_400);
+    }
+
+    #[test]
+    fn test_history_keeps_each_signal_once_with_its_close() {
+        let sd = rising(30);
+        let analysis = analyze_stock(&sd, "TEST", Forecaster::Linear);
+        let signals = detect_signals("TEST", &sd, &analysis);
+        let mut history = Vec::new();
+        assert!(record_history(&mut history, &signals, &sd));
+        assert!(!record_history(&mut history, &signals, &sd));
+        let rsi = history.iter().find(|e| e.kind == SignalKind::RsiOverbought).unwrap();
+        assert_eq!(rsi.close, 114.0);
+        // Overbought, then the price kept rising: it did not play out
+        let (change, played_out) = rsi.outcome(120.0);
+        assert!((change - 600.0 / 114.0).abs() < 1e-9);
+        assert_eq!(played_out, Some(false));
+    }
+
+    #[test]
+    fn test_volume_spike() {
+        let mut sd = rising(30);
+        *sd.volumes.last_mut().unwrap() = 10_000;
+        let analysis = analyze_stock(&sd, "TEST", Forecaster::Linear);
+        let signals = detect_signals("TEST", &sd, &analysis);
+        let spike = signals.iter().find(|s| s.kind == SignalKind::VolumeSpike).unwrap();
+        assert_eq!(spike.timestamp, 29 * 86_400);
+    }
+}
@@ -0,0 +1,91 @@
+use crate::lib::{analysis::StockAnalysis, error::AppError, signals::Signal, stock_data::StockData};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Bumped whenever a field changes meaning; older readers refuse newer files.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// Frozen copy of one symbol's detail view, written so it can be shared and
+/// reopened read-only with `bstock open-snapshot`.
+#[derive(Serialize, Deserialize)]
+pub struct Snapshot {
+    pub version: u32,
+    /// Unix timestamp of when the snapshot was taken.
+    pub created_at: i64,
+    pub symbol: String,
+    /// Time range label as shown in the UI (e.g. "3M").
+    pub time_range: String,
+    /// Zoomed slice `[start, end)` of the series, if the chart was zoomed.
+    pub window: Option<(usize, usize)>,
+    pub stock_data: StockData,
+    pub analysis: StockAnalysis,
+    pub signals: Vec<Signal>,
+    #[serde(default)]
+    pub notes: Vec<String>,
+}
+
+impl Snapshot {
+    /// Write to `dir` as `<SYMBOL>-<YYYYmmdd-HHMMSS>.json` and return the path.
+    pub fn save(&self, dir: &Path) -> Result<PathBuf, AppError> {
+        fs::create_dir_all(dir).map_err(AppError::Io)?;
+        let stamp = chrono::DateTime::from_timestamp(self.created_at, 0)
+            .map(|dt| dt.format("%Y%m%d-%H%M%S").to_string())
+            .unwrap_or_else(|| self.created_at.to_string());
+        let file_name: String = self.symbol.chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '.' { c } else { '_' })
+            .collect();
+        let path = dir.join(format!("{}-{}.json", file_name, stamp));
+        let content = serde_json::to_string_pretty(self).map_err(AppError::ConfigParseError)?;
+        fs::write(&path, content).map_err(AppError::Io)?;
+        Ok(path)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, AppError> {
+        let content = fs::read_to_string(path).map_err(AppError::Io)?;
+        let snapshot: Snapshot = serde_json::from_str(&content).map_err(AppError::ConfigParseError)?;
+        if snapshot.version > SNAPSHOT_VERSION {
+            return Err(AppError::SnapshotVersion(snapshot.version));
+        }
+        Ok(snapshot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lib::analysis::analyze_stock;
+    use crate::lib::forecast::Forecaster;
+
+    #[test]
+    fn test_round_trip() {
+        let mut sd = StockData::new();
+        for i in 0..30 {
+            let p = 50.0 + i as f64;
+            sd.add_point(i * 86_400, p, p + 1.0, p - 1.0, p, 500);
+        }
+        let analysis = analyze_stock(&sd, "BRK.B", Forecaster::Linear);
+        let snapshot = Snapshot {
+            version: SNAPSHOT_VERSION,
+            created_at: 1_700_000_000,
+            symbol: "BRK.B".into(),
+            time_range: "3M".into(),
+            window: Some((5, 25)),
+            stock_data: sd,
+            analysis,
+            signals: vec![],
+            notes: vec!["watch earnings".into()],
+        };
+
+        let dir = std::env::temp_dir().join(format!("bstock-snapshot-test-{}", std::process::id()));
+        let path = snapshot.save(&dir).unwrap();
+        assert!(path.file_name().unwrap().to_string_lossy().starts_with("BRK.B-2023"));
+        let loaded = Snapshot::load(&path).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(loaded.window, Some((5, 25)));
+        assert_eq!(loaded.stock_data.closes, snapshot.stock_data.closes);
+        assert_eq!(loaded.analysis.predictions, snapshot.analysis.predictions);
+        assert_eq!(loaded.notes, snapshot.notes);
+    }
+}
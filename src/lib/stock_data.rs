@@ -1,6 +1,12 @@
+use crate::lib::{
+    error::AppError,
+    parquet::{write_table, ColumnData},
+};
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
 use ndarray::Array1;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct StockData {
     pub timestamps: Vec<i64>,
     pub opens: Vec<f64>,
@@ -8,6 +14,33 @@ pub struct StockData {
     pub lows: Vec<f64>,
     pub closes: Vec<f64>,
     pub volumes: Vec<u64>,
+    /// Close of the session before the first bar, when the provider gives
+    /// it; a reference for moves before the first regular session.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_close: Option<f64>,
+    /// Splits and dividends, oldest first. Bars are kept as traded;
+    /// [`StockData::adjusted`] applies these to the bars before them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub actions: Vec<CorporateAction>,
+}
+
+/// A split or dividend, dated by its ex-date: the first bar it no longer
+/// applies to.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum CorporateAction {
+    /// `ratio` new shares for each old one; below 1 for a reverse split.
+    Split { timestamp: i64, ratio: f64 },
+    /// Cash paid per share, in the prices of the day.
+    Dividend { timestamp: i64, amount: f64 },
+}
+
+impl CorporateAction {
+    pub fn timestamp(&self) -> i64 {
+        match *self {
+            CorporateAction::Split { timestamp, .. } | CorporateAction::Dividend { timestamp, .. } => timestamp,
+        }
+    }
 }
 
 impl Default for StockData {
@@ -25,6 +58,8 @@ impl StockData {
             lows: Vec::new(),
             closes: Vec::new(),
             volumes: Vec::new(),
+            previous_close: None,
+            actions: Vec::new(),
         }
     }
 
@@ -37,6 +72,193 @@ impl StockData {
         self.volumes.push(volume);
     }
 
+    /// Record `action`, keeping the list in date order; one already known
+    /// is not added twice.
+    pub fn add_action(&mut self, action: CorporateAction) {
+        if self.actions.contains(&action) {
+            return;
+        }
+        let at = self.actions.partition_point(|a| a.timestamp() <= action.timestamp());
+        self.actions.insert(at, action);
+    }
+
+    /// The bars back-adjusted for splits and dividends, so a series runs
+    /// on without jumps: bars before a split are divided by its ratio
+    /// (volumes multiplied), and bars before an ex-date are scaled down by
+    /// the dividend's share of the close before it.
+    pub fn adjusted(&self) -> StockData {
+        let mut data = self.clone();
+        let (mut price, mut shares) = (1.0, 1.0);
+        let mut end = self.len();
+        for action in self.actions.iter().rev() {
+            let at = self.timestamps.partition_point(|&t| t < action.timestamp()).min(end);
+            data.scale(at..end, price, shares);
+            end = at;
+            match *action {
+                CorporateAction::Split { ratio, .. } if ratio > 0.0 => {
+                    price /= ratio;
+                    shares *= ratio;
+                }
+                CorporateAction::Dividend { amount, .. } => {
+                    let close = at.checked_sub(1).map(|i| self.closes[i]).or(self.previous_close);
+                    if let Some(close) = close.filter(|&c| c > amount) {
+                        price *= 1.0 - amount / close;
+                    }
+                }
+                _ => {}
+            }
+        }
+        data.scale(0..end, price, shares);
+        data.previous_close = self.previous_close.map(|c| c * price);
+        data
+    }
+
+    fn scale(&mut self, bars: std::ops::Range<usize>, price: f64, shares: f64) {
+        if price == 1.0 && shares == 1.0 {
+            return;
+        }
+        for series in [&mut self.opens, &mut self.highs, &mut self.lows, &mut self.closes] {
+            series[bars.clone()].iter_mut().for_each(|p| *p *= price);
+        }
+        self.volumes[bars].iter_mut().for_each(|v| *v = (*v as f64 * shares).round() as u64);
+    }
+
+    /// Merge `newer` bars from an incremental fetch: bars at or after its
+    /// first timestamp are replaced (the last one may have been still
+    /// forming), and as many of the oldest bars are dropped as were added,
+    /// so the series keeps covering the same span. Into an empty series,
+    /// `newer` is taken whole, as a full load.
+    pub fn merge_newer(&mut self, newer: &StockData) {
+        if self.is_empty() {
+            *self = newer.clone();
+            return;
+        }
+        for &action in &newer.actions {
+            self.add_action(action);
+        }
+        let Some(&first) = newer.timestamps.first() else { return };
+        let before = self.len();
+        let keep = self.timestamps.iter().position(|&t| t >= first).unwrap_or(before);
+        self.truncate(keep);
+        for i in 0..newer.len() {
+            self.add_point(
+                newer.timestamps[i], newer.opens[i], newer.highs[i],
+                newer.lows[i], newer.closes[i], newer.volumes[i],
+            );
+        }
+        let excess = self.len().saturating_sub(before);
+        for series in [&mut self.opens, &mut self.highs, &mut self.lows, &mut self.closes] {
+            series.drain(..excess);
+        }
+        self.timestamps.drain(..excess);
+        self.volumes.drain(..excess);
+    }
+
+    /// The bars in `range`, with the corporate actions between them.
+    pub fn slice(&self, range: std::ops::Range<usize>) -> StockData {
+        let span = self.timestamps[range.clone()].first().zip(self.timestamps[range.clone()].last());
+        StockData {
+            timestamps: self.timestamps[range.clone()].to_vec(),
+            opens: self.opens[range.clone()].to_vec(),
+            highs: self.highs[range.clone()].to_vec(),
+            lows: self.lows[range.clone()].to_vec(),
+            closes: self.closes[range.clone()].to_vec(),
+            volumes: self.volumes[range].to_vec(),
+            previous_close: None,
+            actions: self.actions.iter()
+                .filter(|a| span.is_some_and(|(&first, &last)| (first..=last).contains(&a.timestamp())))
+                .copied()
+                .collect(),
+        }
+    }
+
+    fn truncate(&mut self, len: usize) {
+        self.timestamps.truncate(len);
+        self.opens.truncate(len);
+        self.highs.truncate(len);
+        self.lows.truncate(len);
+        self.closes.truncate(len);
+        self.volumes.truncate(len);
+    }
+
+    /// Bars from an OHLCV CSV, such as a broker or Stooq export. The
+    /// header names the columns, in any order and case: `date` (or `time`,
+    /// `datetime`, `timestamp`), `open`, `high`, `low`, `close` and
+    /// optionally `volume`; Stooq's `<DATE>` style works too. Cells may be
+    /// separated by commas, semicolons (decimal commas allowed) or tabs.
+    /// Dates can be ISO, `YYYYMMDD`, `MM/DD/YYYY`, `DD.MM.YYYY` or Unix
+    /// seconds/milliseconds. Rows with missing prices (e.g. `null`) are
+    /// skipped; bars come out sorted with duplicate times merged.
+    pub fn from_csv(text: &str) -> Result<StockData, AppError> {
+        let mut lines = text.lines().filter(|l| !l.trim().is_empty());
+        let header = lines.next().ok_or_else(|| AppError::Import("the file is empty".into()))?;
+        let sep = [',', ';', '\t'].into_iter()
+            .max_by_key(|&c| header.matches(c).count())
+            .unwrap_or(',');
+        let names: Vec<String> = header.split(sep)
+            .map(|cell| cell.trim().trim_matches(['"', '<', '>']).to_ascii_lowercase())
+            .collect();
+        let column = |candidates: &[&str]| {
+            candidates.iter().find_map(|c| names.iter().position(|n| n == c))
+        };
+        let date = column(&["date", "datetime", "timestamp", "time"])
+            .ok_or_else(|| AppError::Import("no date column in the header".into()))?;
+        let mut prices = [0; 4];
+        for (slot, name) in prices.iter_mut().zip(["open", "high", "low", "close"]) {
+            *slot = column(&[name]).ok_or_else(|| AppError::Import(format!("no {} column in the header", name)))?;
+        }
+        let volume = column(&["volume", "vol"]);
+
+        let number = |cell: &str| -> Option<f64> {
+            let cell = cell.trim().trim_matches('"');
+            let value = if sep == ';' { cell.replace(',', ".").parse() } else { cell.parse() };
+            value.ok().filter(|v: &f64| v.is_finite())
+        };
+        let mut rows = Vec::new();
+        for line in lines {
+            let cells: Vec<&str> = line.split(sep).collect();
+            let Some(timestamp) = cells.get(date).and_then(|c| parse_timestamp(c)) else { continue };
+            let Some(ohlc) = prices.iter()
+                .map(|&i| cells.get(i).and_then(|c| number(c)))
+                .collect::<Option<Vec<f64>>>()
+            else {
+                continue;
+            };
+            let [open, high, low, close] = ohlc[..] else { continue };
+            let volume = volume.and_then(|i| cells.get(i)).and_then(|c| number(c)).unwrap_or(0.0);
+            rows.push((timestamp, open, high, low, close, volume.max(0.0) as u64));
+        }
+        // Stable sort, then the last row for a time wins
+        rows.sort_by_key(|r| r.0);
+        rows.reverse();
+        rows.dedup_by_key(|r| r.0);
+        rows.reverse();
+
+        let mut data = StockData::new();
+        for (t, o, h, l, c, v) in rows {
+            data.add_point(t, o, h, l, c, v);
+        }
+        if data.is_empty() {
+            return Err(AppError::Import("no rows with a date and prices".into()));
+        }
+        Ok(data)
+    }
+
+    /// The bars from `start` (a Unix timestamp, inclusive) on.
+    pub fn since(&self, start: i64) -> StockData {
+        let from = self.timestamps.partition_point(|&t| t < start);
+        StockData {
+            timestamps: self.timestamps[from..].to_vec(),
+            opens: self.opens[from..].to_vec(),
+            highs: self.highs[from..].to_vec(),
+            lows: self.lows[from..].to_vec(),
+            closes: self.closes[from..].to_vec(),
+            volumes: self.volumes[from..].to_vec(),
+            previous_close: if from == 0 { self.previous_close } else { None },
+            actions: self.actions.iter().copied().filter(|a| a.timestamp() >= start).collect(),
+        }
+    }
+
     pub fn len(&self) -> usize {
         self.closes.len()
     }
@@ -81,8 +303,109 @@ impl StockData {
         Some(Array1::from(ema_values))
     }
 
-    // Simple prediction based on trend
-    pub fn predict_next(&self, periods: usize) -> Vec<f64> {
+    // Calculate Relative Strength Index (Wilder's smoothing).
+    // rsi[k] corresponds to closes[k + period].
+    pub fn rsi(&self, period: usize) -> Option<Array1<f64>> {
+        if period == 0 || self.len() <= period {
+            return None;
+        }
+
+        let changes: Vec<f64> = self.closes.windows(2).map(|w| w[1] - w[0]).collect();
+        let mut avg_gain = changes[..period].iter().filter(|c| **c > 0.0).sum::<f64>() / period as f64;
+        let mut avg_loss = -changes[..period].iter().filter(|c| **c < 0.0).sum::<f64>() / period as f64;
+
+        let to_rsi = |gain: f64, loss: f64| {
+            if loss == 0.0 { 100.0 } else { 100.0 - 100.0 / (1.0 + gain / loss) }
+        };
+
+        let mut rsi_values = vec![to_rsi(avg_gain, avg_loss)];
+        for change in &changes[period..] {
+            avg_gain = (avg_gain * (period as f64 - 1.0) + change.max(0.0)) / period as f64;
+            avg_loss = (avg_loss * (period as f64 - 1.0) + (-change).max(0.0)) / period as f64;
+            rsi_values.push(to_rsi(avg_gain, avg_loss));
+        }
+
+        Some(Array1::from(rsi_values))
+    }
+
+    // Calculate MACD: the EMA-`fast` of closes less the EMA-`slow`, and
+    // the EMA-`signal` of that difference, as (MACD, signal) pairs.
+    // macd[k] corresponds to closes[k + slow + signal - 2].
+    pub fn macd(&self, fast: usize, slow: usize, signal: usize) -> Option<Vec<(f64, f64)>> {
+        if fast == 0 || fast >= slow || signal == 0 || self.len() < slow + signal - 1 {
+            return None;
+        }
+        let (fast_ema, slow_ema) = (self.ema(fast)?, self.ema(slow)?);
+        let line: Vec<f64> = slow_ema.iter().enumerate().map(|(k, s)| fast_ema[k + slow - fast] - s).collect();
+
+        let multiplier = 2.0 / (signal as f64 + 1.0);
+        let mut signal_line = line[..signal].iter().sum::<f64>() / signal as f64;
+        let mut macd_values = vec![(line[signal - 1], signal_line)];
+        for &value in &line[signal..] {
+            signal_line += (value - signal_line) * multiplier;
+            macd_values.push((value, signal_line));
+        }
+        Some(macd_values)
+    }
+
+    // Calculate Bollinger Bands: the SMA-`period` of closes `width`
+    // standard deviations either side, as (lower, middle, upper).
+    // bands[k] corresponds to closes[k + period - 1].
+    pub fn bollinger(&self, period: usize, width: f64) -> Option<Vec<(f64, f64, f64)>> {
+        if period == 0 || self.len() < period {
+            return None;
+        }
+        Some(self.closes.windows(period).map(|w| {
+            let mean = w.iter().sum::<f64>() / period as f64;
+            let sd = (w.iter().map(|c| (c - mean).powi(2)).sum::<f64>() / period as f64).sqrt();
+            (mean - width * sd, mean, mean + width * sd)
+        }).collect())
+    }
+
+    // Kalman filter over a local linear trend: each close is a level plus
+    // noise, the level moving by a slope that itself drifts slowly. Returns
+    // the filtered (level, slope per bar) at every bar. The noise ratio is
+    // picked from a few by the likelihood of the closes.
+    pub fn kalman_trend(&self) -> Option<Vec<(f64, f64)>> {
+        if self.len() < 3 {
+            return None;
+        }
+        let changes: Vec<f64> = self.closes.windows(2).map(|w| w[1] - w[0]).collect();
+        let mean = changes.iter().sum::<f64>() / changes.len() as f64;
+        let noise = (changes.iter().map(|c| (c - mean).powi(2)).sum::<f64>() / changes.len() as f64).max(1e-12);
+
+        let filter = |ratio: f64| {
+            let (q_level, q_slope) = (noise * ratio, noise * ratio * 0.01);
+            let (mut level, mut slope) = (self.closes[0], 0.0);
+            let (mut p00, mut p01, mut p11) = (noise * 10.0, 0.0, noise);
+            let mut states = vec![(level, slope)];
+            let mut log_likelihood = 0.0;
+            for &y in &self.closes[1..] {
+                // Predict
+                let (l, s) = (level + slope, slope);
+                let (a00, a01, a11) = (p00 + 2.0 * p01 + p11 + q_level, p01 + p11, p11 + q_slope);
+                // Update
+                let innovation = y - l;
+                let variance = a00 + noise;
+                let (k0, k1) = (a00 / variance, a01 / variance);
+                level = l + k0 * innovation;
+                slope = s + k1 * innovation;
+                (p00, p01, p11) = ((1.0 - k0) * a00, (1.0 - k0) * a01, a11 - k1 * a01);
+                log_likelihood -= variance.ln() + innovation * innovation / variance;
+                states.push((level, slope));
+            }
+            (log_likelihood, states)
+        };
+        [0.01, 0.03, 0.1, 0.3, 1.0].into_iter()
+            .map(filter)
+            .max_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, states)| states)
+    }
+
+    // Simple prediction based on trend: a least-squares line through the
+    // last `periods` closes, extended 5 bars. Each prediction comes with its
+    // standard error, 0 when two closes leave nothing to estimate it from.
+    pub fn predict_next(&self, periods: usize) -> Vec<(f64, f64)> {
         if self.len() < 2 {
             return vec![];
         }
@@ -102,15 +425,74 @@ impl StockData {
         let slope = (n * sum_xy - sum_x * sum_y) / (n * sum_x2 - sum_x * sum_x);
         let intercept = (sum_y - slope * sum_x) / n;
 
+        let sse: f64 = x.iter().zip(y.iter()).map(|(xi, yi)| (yi - slope * xi - intercept).powi(2)).sum();
+        let s = if recent_period > 2 { (sse / (n - 2.0)).sqrt() } else { 0.0 };
+        let mean_x = sum_x / n;
+        let sxx = sum_x2 - n * mean_x * mean_x;
+
         for i in 1..=5 {
             let next_x = (recent_period + i) as f64;
-            predictions.push(slope * next_x + intercept);
+            let std_error = s * (1.0 + 1.0 / n + (next_x - mean_x).powi(2) / sxx).sqrt();
+            predictions.push((slope * next_x + intercept, std_error));
         }
 
         predictions
     }
 }
 
+/// A CSV date cell as a Unix timestamp; dates without a time are midnight
+/// UTC.
+fn parse_timestamp(cell: &str) -> Option<i64> {
+    let cell = cell.trim().trim_matches('"');
+    if !cell.is_empty() && cell.bytes().all(|b| b.is_ascii_digit()) {
+        let n: i64 = cell.parse().ok()?;
+        return Some(match cell.len() {
+            8 => NaiveDate::parse_from_str(cell, "%Y%m%d").ok()?.and_hms_opt(0, 0, 0)?.and_utc().timestamp(),
+            // Milliseconds, as JavaScript and many APIs write them
+            13.. => n / 1000,
+            _ => n,
+        });
+    }
+    if let Ok(dt) = DateTime::parse_from_rfc3339(cell) {
+        return Some(dt.timestamp());
+    }
+    for format in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M"] {
+        if let Ok(dt) = NaiveDateTime::parse_from_str(cell, format) {
+            return Some(dt.and_utc().timestamp());
+        }
+    }
+    ["%Y-%m-%d", "%m/%d/%Y", "%Y/%m/%d", "%d.%m.%Y"].iter()
+        .find_map(|format| NaiveDate::parse_from_str(cell, format).ok())
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .map(|dt| dt.and_utc().timestamp())
+}
+
+/// Several symbols' bars as one Parquet table, a row per bar with
+/// `symbol`, `timestamp` (UTC milliseconds), `open`, `high`, `low`,
+/// `close` and `volume` columns, in the order given.
+pub fn to_parquet(series: &[(&str, &StockData)]) -> Vec<u8> {
+    let mut symbols = Vec::new();
+    let mut data = StockData::new();
+    for (symbol, sd) in series {
+        symbols.extend(std::iter::repeat_n(symbol.to_string(), sd.len()));
+        data.timestamps.extend(&sd.timestamps);
+        data.opens.extend(&sd.opens);
+        data.highs.extend(&sd.highs);
+        data.lows.extend(&sd.lows);
+        data.closes.extend(&sd.closes);
+        data.volumes.extend(&sd.volumes);
+    }
+    write_table(&[
+        ("symbol", ColumnData::Utf8(symbols)),
+        ("timestamp", ColumnData::TimestampMillis(data.timestamps.iter().map(|t| t * 1000).collect())),
+        ("open", ColumnData::Double(data.opens)),
+        ("high", ColumnData::Double(data.highs)),
+        ("low", ColumnData::Double(data.lows)),
+        ("close", ColumnData::Double(data.closes)),
+        ("volume", ColumnData::Int64(data.volumes.iter().map(|&v| v as i64).collect())),
+    ])
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -144,11 +526,109 @@ mod tests {
         assert_abs_diff_eq!(ema, expected, epsilon = 1e-10);
     }
 
+    #[test]
+    fn test_rsi() {
+        let sd = create_stock_data();
+        let rsi = sd.rsi(3).unwrap();
+        // changes: +2 +3 -2 +3 +2 → seed gain 5/3, loss 2/3
+        let expected = arr1(&[71.42857142857143, 82.6086956521739, 87.5]);
+        assert_abs_diff_eq!(rsi, expected, epsilon = 1e-10);
+        assert!(sd.rsi(6).is_none());
+    }
+
+    #[test]
+    fn test_macd_and_bollinger() {
+        let sd = create_stock_data();
+        let macd = sd.macd(2, 3, 2).unwrap();
+        // MACD 1.333, 0.556, 0.741, 0.858 from bar 2; signal seeded on bar 3
+        assert_eq!(macd.len(), 3);
+        assert_abs_diff_eq!(macd[0].0, 0.5555555555555556, epsilon = 1e-10);
+        assert_abs_diff_eq!(macd[0].1, 0.9444444444444444, epsilon = 1e-10);
+        assert_abs_diff_eq!(macd[2].1, 0.8415637860082305, epsilon = 1e-10);
+        assert!(sd.macd(3, 5, 3).is_none());
+
+        let bands = sd.bollinger(3, 2.0).unwrap();
+        assert_eq!(bands.len(), 4);
+        let (lower, middle, upper) = bands[0];
+        assert_abs_diff_eq!(middle, 102.33333333333333, epsilon = 1e-10);
+        assert_abs_diff_eq!(upper - middle, 2.0 * (38.0f64 / 9.0).sqrt(), epsilon = 1e-10);
+        assert_abs_diff_eq!(middle - lower, upper - middle, epsilon = 1e-10);
+    }
+
+    #[test]
+    fn test_merge_newer_replaces_the_last_bar_and_slides() {
+        let mut sd = create_stock_data();
+        let mut newer = StockData::new();
+        newer.add_point(1672963200, 105.0, 111.0, 104.0, 109.0, 1600);
+        newer.add_point(1673049600, 109.0, 112.0, 108.0, 111.0, 900);
+        sd.merge_newer(&newer);
+        assert_eq!(sd.len(), 6);
+        assert_eq!(sd.timestamps[0], 1672617600);
+        assert_eq!(&sd.closes[4..], &[109.0, 111.0]);
+        assert_eq!(sd.volumes.len(), 6);
+
+        // Nothing loaded yet: all of it, not slid away to nothing
+        let mut empty = StockData::new();
+        empty.merge_newer(&newer);
+        assert_eq!(empty.timestamps, newer.timestamps);
+        assert_eq!(empty.closes, vec![109.0, 111.0]);
+    }
+
+    #[test]
+    fn test_from_csv_reads_stooq_and_broker_exports() {
+        let stooq = "Date,Open,High,Low,Close,Volume\n2024-01-03,10,12,9,11,500\n2024-01-02,9,10,8,9.5,400\n";
+        let sd = StockData::from_csv(stooq).unwrap();
+        assert_eq!(sd.timestamps, vec![1704153600, 1704240000]);
+        assert_eq!(sd.closes, vec![9.5, 11.0]);
+
+        let broker = "\"Time\";\"Close\";\"High\";\"Low\";\"Open\"\n01/02/2024;9,5;10;8;9\n01/03/2024;null;12;9;10\n";
+        let sd = StockData::from_csv(broker).unwrap();
+        assert_eq!(sd.len(), 1);
+        assert_eq!((sd.opens[0], sd.closes[0], sd.volumes[0]), (9.0, 9.5, 0));
+
+        let old_stooq = "<TICKER>,<PER>,<DATE>,<TIME>,<OPEN>,<HIGH>,<LOW>,<CLOSE>,<VOL>\nX,D,20240102,000000,1,2,0.5,1.5,10\n";
+        assert_eq!(StockData::from_csv(old_stooq).unwrap().timestamps, vec![1704153600]);
+        assert!(StockData::from_csv("Date,Close\n2024-01-02,1\n").is_err());
+    }
+
+    #[test]
+    fn test_adjusted_runs_on_across_splits_and_dividends() {
+        let mut sd = create_stock_data();
+        // A 2-for-1 split on the fourth bar, and a dividend of 2 going ex
+        // on the second, 2% of the close of 100 before it
+        sd.closes[3] /= 2.0;
+        sd.add_action(CorporateAction::Split { timestamp: 1672790400, ratio: 2.0 });
+        sd.add_action(CorporateAction::Dividend { timestamp: 1672617600, amount: 2.0 });
+        sd.add_action(CorporateAction::Split { timestamp: 1672790400, ratio: 2.0 });
+        assert_eq!(sd.actions.len(), 2);
+
+        let adjusted = sd.adjusted();
+        assert_eq!(&adjusted.closes[3..], &sd.closes[3..]);
+        assert_abs_diff_eq!(adjusted.closes[2], 52.5);
+        assert_eq!(adjusted.volumes[1], 2400);
+        assert_abs_diff_eq!(adjusted.closes[0], 50.0 * 0.98);
+        assert_eq!(sd.since(1672790400).actions.len(), 1);
+    }
+
+    #[test]
+    fn test_kalman_trend_finds_the_slope() {
+        let mut sd = StockData::new();
+        for t in 0..100 {
+            let close = 10.0 + 2.0 * t as f64 + if t % 2 == 0 { 0.5 } else { -0.5 };
+            sd.add_point(t * 86_400, close, close, close, close, 0);
+        }
+        let (level, slope) = *sd.kalman_trend().unwrap().last().unwrap();
+        assert_abs_diff_eq!(slope, 2.0, epsilon = 0.1);
+        assert_abs_diff_eq!(level, 208.0, epsilon = 1.0);
+        assert!(create_stock_data().since(1672876800).kalman_trend().is_none());
+    }
+
     #[test]
     fn test_predict_next() {
         let sd = create_stock_data();
         let predictions = sd.predict_next(5);
         assert_eq!(predictions.len(), 5);
-        assert!(predictions[0] > 100.0);
+        assert!(predictions[0].0 > 100.0);
+        assert!(predictions[0].1 > 0.0 && predictions[4].1 > predictions[0].1);
     }
 }
@@ -0,0 +1,177 @@
+//! Stooq (<https://stooq.com>), free CSV downloads with no key. Bars are
+//! daily at the finest, so 1D has nothing to show, and Stooq offers no
+//! symbol search or company profiles.
+
+use chrono::{DateTime, Utc};
+
+use crate::data::{trailing_range, Interval, TimeRange};
+use crate::lib::{
+    config::SymbolProfile,
+    error::AppError,
+    provider::{DataProvider, ProviderFuture, Quote, SymbolMatch},
+    stock_data::StockData,
+};
+
+const URL: &str = "https://stooq.com";
+
+#[derive(Default)]
+pub struct StooqProvider {
+    client: reqwest::Client,
+}
+
+impl StooqProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    async fn get(&self, path: &str, params: &[(&str, String)]) -> Result<String, AppError> {
+        self.client
+            .get(format!("{URL}/{path}"))
+            .query(params)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| AppError::from_request("Stooq", e))?
+            .text()
+            .await
+            .map_err(|e| AppError::ApiError(format!("Stooq: {e}")))
+    }
+
+    /// Bars at `time_range`'s interval from `from` (a Unix timestamp, or
+    /// the first bar when `None`) up to today. No bars is not an error.
+    async fn bars(&self, symbol: &str, time_range: TimeRange, from: Option<i64>) -> Result<StockData, AppError> {
+        let interval = interval(time_range)
+            .ok_or_else(|| AppError::ApiError("Stooq has no intraday bars for 1D".into()))?;
+        let mut params = vec![("s", stooq_symbol(symbol)), ("i", interval.to_string())];
+        if let Some(from) = from {
+            let date = |t: i64| DateTime::from_timestamp(t, 0).unwrap_or_default().format("%Y%m%d").to_string();
+            params.push(("d1", date(from.max(0))));
+            params.push(("d2", date(Utc::now().timestamp())));
+        }
+        let body = self.get("q/d/l/", &params).await?;
+        // Unknown symbols and empty ranges come back as the text "No data"
+        if body.trim().eq_ignore_ascii_case("no data") {
+            return Ok(StockData::new());
+        }
+        StockData::from_csv(&body).map_err(|e| AppError::ApiError(format!("Stooq {symbol}: {e}")))
+    }
+
+    /// Bars covering `time_range` up to now, plus `periods - 1` equal
+    /// periods before it.
+    async fn periods(&self, symbol: &str, time_range: TimeRange, periods: i64) -> Result<StockData, AppError> {
+        let now = Utc::now().timestamp();
+        // A few days' margin keeps the latest session in over weekends
+        let from = time_range.start_before(now).map(|start| now - periods * (now - start) - 4 * 86_400);
+        let data = self.bars(symbol, time_range, from).await?;
+        if data.is_empty() {
+            return Err(AppError::ApiError(format!("{symbol}: no data from Stooq")));
+        }
+        Ok(data)
+    }
+}
+
+/// Stooq writes US tickers lower case with a `.us` suffix; symbols that
+/// already name a market (`vod.uk`) are passed through.
+fn stooq_symbol(symbol: &str) -> String {
+    let symbol = symbol.to_ascii_lowercase();
+    if symbol.contains('.') || symbol.starts_with('^') {
+        symbol
+    } else {
+        format!("{symbol}.us")
+    }
+}
+
+/// Stooq interval for a range; daily bars stand in for the intraday bars
+/// of 1W and 1M.
+fn interval(time_range: TimeRange) -> Option<&'static str> {
+    match time_range.interval() {
+        Interval::OneMinute => None,
+        Interval::FiveMinutes | Interval::OneHour | Interval::OneDay => Some("d"),
+        Interval::OneWeek => Some("w"),
+        Interval::OneMonth => Some("m"),
+    }
+}
+
+/// The latest bar from Stooq's quote CSV (`Symbol,Date,Time,Open,High,Low,
+/// Close,Volume`); unknown symbols have `N/D` in every field. The time is
+/// Stooq's local time, so only the date is kept.
+fn parse_quote(symbol: &str, body: &str) -> Result<Quote, AppError> {
+    let unknown = || AppError::ApiError(format!("{symbol}: unknown to Stooq"));
+    let row: Vec<&str> = body.lines().nth(1).ok_or_else(unknown)?.split(',').map(str::trim).collect();
+    let [_, date, _, _, _, _, close, volume] = row[..] else { return Err(unknown()) };
+    let price = close.parse::<f64>().map_err(|_| unknown())?;
+    let timestamp = chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_or(0, |d| d.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc().timestamp());
+    Ok(Quote {
+        timestamp,
+        price,
+        volume: volume.parse().unwrap_or(0),
+    })
+}
+
+impl DataProvider for StooqProvider {
+    fn name(&self) -> &'static str {
+        "Stooq"
+    }
+
+    fn fetch_history<'a>(&'a self, symbol: &'a str, time_range: TimeRange) -> ProviderFuture<'a, StockData> {
+        Box::pin(async move { Ok(trailing_range(&self.periods(symbol, time_range, 1).await?, time_range)) })
+    }
+
+    fn fetch_history_since<'a>(&'a self, symbol: &'a str, time_range: TimeRange, since: i64) -> ProviderFuture<'a, StockData> {
+        Box::pin(async move { Ok(self.bars(symbol, time_range, Some(since)).await?.since(since)) })
+    }
+
+    fn fetch_doubled_history<'a>(&'a self, symbol: &'a str, time_range: TimeRange) -> ProviderFuture<'a, StockData> {
+        Box::pin(async move {
+            if time_range == TimeRange::All {
+                return Err(AppError::ApiError("no history before All".into()));
+            }
+            let series = self.periods(symbol, time_range, 2).await?;
+            let current = trailing_range(&series, time_range);
+            let (start, end) = (current.timestamps[0], current.timestamps[current.len() - 1]);
+            Ok(series.since(start - (end - start)))
+        })
+    }
+
+    fn fetch_quote<'a>(&'a self, symbol: &'a str) -> ProviderFuture<'a, Quote> {
+        Box::pin(async move {
+            let params = [
+                ("s", stooq_symbol(symbol)),
+                ("f", "sd2t2ohlcv".to_string()),
+                ("h", String::new()),
+                ("e", "csv".to_string()),
+            ];
+            parse_quote(symbol, &self.get("q/l/", &params).await?)
+        })
+    }
+
+    fn search_symbols<'a>(&'a self, _query: &'a str) -> ProviderFuture<'a, Vec<SymbolMatch>> {
+        Box::pin(async { Ok(Vec::new()) })
+    }
+
+    fn fetch_profile<'a>(&'a self, _symbol: &'a str) -> ProviderFuture<'a, SymbolProfile> {
+        Box::pin(async { Ok(SymbolProfile::default()) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_symbols_and_quotes() {
+        assert_eq!(stooq_symbol("AAPL"), "aapl.us");
+        assert_eq!(stooq_symbol("VOD.UK"), "vod.uk");
+
+        let quote = parse_quote(
+            "AAPL",
+            "Symbol,Date,Time,Open,High,Low,Close,Volume\nAAPL.US,2024-01-02,22:00:09,187.15,188.44,183.885,185.64,82488674\n",
+        ).unwrap();
+        assert_eq!(quote.price, 185.64);
+        assert_eq!(quote.volume, 82_488_674);
+        assert_eq!(quote.timestamp, 1704153600);
+        let unknown = "Symbol,Date,Time,Open,High,Low,Close,Volume\nNOPE.US,N/D,N/D,N/D,N/D,N/D,N/D,N/D\n";
+        assert!(parse_quote("NOPE", unknown).is_err());
+    }
+}
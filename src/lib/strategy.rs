@@ -0,0 +1,462 @@
+//! Trading strategies and their backtests: rules saying when to buy and
+//! when to sell, traded bar by bar over a symbol's history the way a
+//! long-only account would, paying commission and slippage on every fill.
+//! A rule is judged on a bar's close and filled at the next bar's open.
+
+use std::{collections::BTreeMap, fmt};
+
+use crate::lib::{
+    config::{StrategyDefinition, StrategySettings},
+    stock_data::StockData,
+};
+
+/// An indicator a rule looks at, valued at each bar.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Series {
+    Close,
+    Sma(usize),
+    Ema(usize),
+    Rsi(usize),
+    /// MACD(12, 26, 9) and its signal line.
+    Macd,
+    MacdSignal,
+}
+
+impl fmt::Display for Series {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Series::Close => write!(f, "close"),
+            Series::Sma(n) => write!(f, "SMA{}", n),
+            Series::Ema(n) => write!(f, "EMA{}", n),
+            Series::Rsi(n) => write!(f, "RSI{}", n),
+            Series::Macd => write!(f, "MACD"),
+            Series::MacdSignal => write!(f, "MACD signal"),
+        }
+    }
+}
+
+impl Series {
+    /// Bars the indicator looks back over, for those with a length.
+    pub fn length(self) -> Option<usize> {
+        match self {
+            Series::Sma(n) | Series::Ema(n) | Series::Rsi(n) => Some(n),
+            Series::Close | Series::Macd | Series::MacdSignal => None,
+        }
+    }
+
+    /// The same indicator over `n` bars; those without a length stay as
+    /// they are.
+    pub fn with_length(self, n: usize) -> Series {
+        match self {
+            Series::Sma(_) => Series::Sma(n),
+            Series::Ema(_) => Series::Ema(n),
+            Series::Rsi(_) => Series::Rsi(n),
+            other => other,
+        }
+    }
+
+    /// The series at every bar of `stock_data`; `None` before it has
+    /// enough bars.
+    fn values(self, stock_data: &StockData) -> Vec<Option<f64>> {
+        let len = stock_data.len();
+        let aligned = |values: Vec<f64>| {
+            let mut out = vec![None; len.saturating_sub(values.len())];
+            out.extend(values.into_iter().map(Some));
+            out
+        };
+        match self {
+            Series::Close => stock_data.closes.iter().map(|&c| Some(c)).collect(),
+            Series::Sma(n) if n > 0 => aligned(stock_data.closes.windows(n).map(|w| w.iter().sum::<f64>() / n as f64).collect()),
+            Series::Ema(n) => aligned(stock_data.ema(n).map_or_else(Vec::new, |e| e.to_vec())),
+            Series::Rsi(n) => aligned(stock_data.rsi(n).map_or_else(Vec::new, |r| r.to_vec())),
+            Series::Macd => aligned(stock_data.macd(12, 26, 9).map_or_else(Vec::new, |m| m.iter().map(|p| p.0).collect())),
+            Series::MacdSignal => aligned(stock_data.macd(12, 26, 9).map_or_else(Vec::new, |m| m.iter().map(|p| p.1).collect())),
+            Series::Sma(_) => vec![None; len],
+        }
+    }
+}
+
+/// One side of a comparison.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Operand {
+    Series(Series),
+    Value(f64),
+}
+
+impl Operand {
+    /// An indicator such as `SMA50`, `EMA(20)`, `RSI14` (`RSI` alone is
+    /// RSI14), `MACD`, `MACD signal` or `close`, or a number.
+    fn parse(text: &str) -> Result<Operand, String> {
+        let name: String = text.chars().filter(|c| !c.is_whitespace() && !matches!(c, '(' | ')')).collect::<String>().to_lowercase();
+        let length = |prefix: &str| name.strip_prefix(prefix)?.parse::<usize>().ok().filter(|&n| n > 0);
+        let series = match name.as_str() {
+            "close" | "price" => Series::Close,
+            "rsi" => Series::Rsi(14),
+            "macd" => Series::Macd,
+            "macdsignal" | "signal" => Series::MacdSignal,
+            _ => match (length("sma"), length("ema"), length("rsi")) {
+                (Some(n), _, _) => Series::Sma(n),
+                (_, Some(n), _) => Series::Ema(n),
+                (_, _, Some(n)) => Series::Rsi(n),
+                _ => {
+                    return name.parse::<f64>().ok().filter(|v| v.is_finite()).map(Operand::Value)
+                        .ok_or_else(|| format!("unknown indicator '{}'", text.trim()));
+                }
+            },
+        };
+        Ok(Operand::Series(series))
+    }
+}
+
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operand::Series(series) => series.fmt(f),
+            Operand::Value(v) => write!(f, "{}", v),
+        }
+    }
+}
+
+/// A rule that holds, or not, at each bar.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Condition {
+    /// `left` went from at or below `right` to above it on this bar, or
+    /// from at or above to below.
+    Crosses { left: Operand, right: Operand, above: bool },
+    /// `left` is above, or below, `right`.
+    Compare { left: Operand, right: Operand, above: bool },
+}
+
+impl Condition {
+    /// `A crosses above B` (or `over`, `below`, `under`), `A > B` or
+    /// `A < B` (or `above`, `below`), in any case.
+    fn parse(text: &str) -> Result<Condition, String> {
+        let text = text.trim().to_lowercase();
+        let sides = |at: usize, word: &str| Ok::<_, String>((Operand::parse(&text[..at])?, Operand::parse(&text[at + word.len()..])?));
+        for (word, above) in [(" crosses above ", true), (" crosses over ", true), (" crosses below ", false), (" crosses under ", false)] {
+            if let Some(at) = text.find(word) {
+                let (left, right) = sides(at, word)?;
+                return Ok(Condition::Crosses { left, right, above });
+            }
+        }
+        for (word, above) in [(">", true), ("<", false), (" above ", true), (" below ", false)] {
+            if let Some(at) = text.find(word) {
+                let (left, right) = sides(at, word)?;
+                return Ok(Condition::Compare { left, right, above });
+            }
+        }
+        Err(format!("cannot read '{}'", text))
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Condition::Crosses { left, right, above } => {
+                write!(f, "{} crosses {} {}", left, if *above { "above" } else { "below" }, right)
+            }
+            Condition::Compare { left, right, above } => write!(f, "{} {} {}", left, if *above { ">" } else { "<" }, right),
+        }
+    }
+}
+
+/// When to buy and when to sell; each fires when all its conditions hold.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Strategy {
+    pub name: String,
+    pub entry: Vec<Condition>,
+    pub exit: Vec<Condition>,
+}
+
+impl Strategy {
+    /// Every indicator the rules look at, each once, in the order they
+    /// first appear.
+    pub fn series(&self) -> Vec<Series> {
+        let mut series = Vec::new();
+        for condition in self.entry.iter().chain(&self.exit) {
+            let (Condition::Crosses { left, right, .. } | Condition::Compare { left, right, .. }) = condition;
+            for operand in [left, right] {
+                if let Operand::Series(s) = *operand
+                    && !series.contains(&s)
+                {
+                    series.push(s);
+                }
+            }
+        }
+        series
+    }
+
+    /// The strategy with every indicator passed through `f`.
+    pub fn map_series(&self, f: impl Fn(Series) -> Series) -> Strategy {
+        let operand = |o: Operand| match o {
+            Operand::Series(s) => Operand::Series(f(s)),
+            value => value,
+        };
+        let condition = |c: &Condition| match *c {
+            Condition::Crosses { left, right, above } => Condition::Crosses { left: operand(left), right: operand(right), above },
+            Condition::Compare { left, right, above } => Condition::Compare { left: operand(left), right: operand(right), above },
+        };
+        Strategy {
+            name: self.name.clone(),
+            entry: self.entry.iter().map(condition).collect(),
+            exit: self.exit.iter().map(condition).collect(),
+        }
+    }
+}
+
+/// `conditions` joined with "and".
+pub fn describe(conditions: &[Condition]) -> String {
+    conditions.iter().map(|c| c.to_string()).collect::<Vec<_>>().join(" and ")
+}
+
+/// Conditions joined with "and".
+pub fn parse_conditions(text: &str) -> Result<Vec<Condition>, String> {
+    if text.trim().is_empty() {
+        return Err("no conditions".into());
+    }
+    text.to_lowercase().split(" and ").map(Condition::parse).collect()
+}
+
+/// The strategies in the `strategy.definitions` config, or the first one
+/// that cannot be read.
+pub fn parse_definitions(definitions: &BTreeMap<String, StrategyDefinition>) -> Result<Vec<Strategy>, String> {
+    definitions.iter()
+        .map(|(name, definition)| {
+            let parse = |side: &str, text: &str| parse_conditions(text).map_err(|e| format!("Strategy '{}' {}: {}", name, side, e));
+            Ok(Strategy { name: name.clone(), entry: parse("buy", &definition.buy)?, exit: parse("sell", &definition.sell)? })
+        })
+        .collect()
+}
+
+/// The strategies that come with bstock.
+pub fn builtin() -> Vec<Strategy> {
+    let (sma10, sma50) = (Operand::Series(Series::Sma(10)), Operand::Series(Series::Sma(50)));
+    let (macd, signal) = (Operand::Series(Series::Macd), Operand::Series(Series::MacdSignal));
+    let (close, ema20) = (Operand::Series(Series::Close), Operand::Series(Series::Ema(20)));
+    let rsi = Operand::Series(Series::Rsi(14));
+    vec![
+        Strategy {
+            name: "Golden cross".into(),
+            entry: vec![Condition::Crosses { left: sma10, right: sma50, above: true }],
+            exit: vec![Condition::Crosses { left: sma10, right: sma50, above: false }],
+        },
+        Strategy {
+            name: "EMA trend".into(),
+            entry: vec![Condition::Crosses { left: close, right: ema20, above: true }],
+            exit: vec![Condition::Crosses { left: close, right: ema20, above: false }],
+        },
+        Strategy {
+            name: "RSI reversion".into(),
+            entry: vec![Condition::Compare { left: rsi, right: Operand::Value(30.0), above: false }],
+            exit: vec![Condition::Compare { left: rsi, right: Operand::Value(70.0), above: true }],
+        },
+        Strategy {
+            name: "MACD cross".into(),
+            entry: vec![Condition::Crosses { left: macd, right: signal, above: true }],
+            exit: vec![Condition::Crosses { left: macd, right: signal, above: false }],
+        },
+    ]
+}
+
+/// A round trip, or the position still open at the last bar.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Trade {
+    /// Bars the position was bought and sold on.
+    pub entry_bar: usize,
+    pub exit_bar: Option<usize>,
+    pub entry_time: i64,
+    /// Fill prices, slippage included.
+    pub entry_price: f64,
+    /// `None` while still open, when the exit price is the last close.
+    pub exit_time: Option<i64>,
+    pub exit_price: f64,
+    pub shares: f64,
+    /// Net of commission both ways.
+    pub pnl: f64,
+}
+
+/// How a strategy did over a history.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StrategyResult {
+    pub trades: Vec<Trade>,
+    /// Account value at each bar's close.
+    pub equity: Vec<f64>,
+    /// In percent of the starting capital.
+    pub total_return: f64,
+    /// Holding from the first close to the last, in percent.
+    pub buy_and_hold: f64,
+    /// Share of closed trades that made money, from 0 to 1; `None` before
+    /// any has closed.
+    pub win_rate: Option<f64>,
+    /// Largest fall of the equity from a high, in percent.
+    pub max_drawdown: f64,
+}
+
+/// Evaluates conditions against precomputed series.
+struct Evaluator {
+    series: Vec<(Series, Vec<Option<f64>>)>,
+}
+
+impl Evaluator {
+    fn new(stock_data: &StockData, strategy: &Strategy) -> Evaluator {
+        Evaluator { series: strategy.series().into_iter().map(|s| (s, s.values(stock_data))).collect() }
+    }
+
+    fn value(&self, operand: Operand, t: usize) -> Option<f64> {
+        match operand {
+            Operand::Value(v) => Some(v),
+            Operand::Series(s) => self.series.iter().find(|(known, _)| *known == s)?.1.get(t).copied().flatten(),
+        }
+    }
+
+    fn holds(&self, condition: &Condition, t: usize) -> bool {
+        let side = |above: bool, l: f64, r: f64| if above { l > r } else { l < r };
+        match *condition {
+            Condition::Compare { left, right, above } => {
+                matches!((self.value(left, t), self.value(right, t)), (Some(l), Some(r)) if side(above, l, r))
+            }
+            Condition::Crosses { left, right, above } => {
+                let Some(prev) = t.checked_sub(1) else { return false };
+                match (self.value(left, prev), self.value(right, prev), self.value(left, t), self.value(right, t)) {
+                    (Some(l0), Some(r0), Some(l1), Some(r1)) => !side(above, l0, r0) && side(above, l1, r1),
+                    _ => false,
+                }
+            }
+        }
+    }
+
+    fn all_hold(&self, conditions: &[Condition], t: usize) -> bool {
+        !conditions.is_empty() && conditions.iter().all(|c| self.holds(c, t))
+    }
+}
+
+/// Trade `strategy` over `stock_data` with the account in `settings`.
+pub fn run(strategy: &Strategy, stock_data: &StockData, settings: &StrategySettings) -> StrategyResult {
+    let eval = Evaluator::new(stock_data, strategy);
+    let closes = &stock_data.closes;
+    let slippage = settings.slippage_pct / 100.0;
+    let mut cash = settings.capital;
+    let mut open: Option<Trade> = None;
+    let mut trades = Vec::new();
+    let mut equity = Vec::with_capacity(closes.len());
+
+    for (t, &close) in closes.iter().enumerate() {
+        // Orders from the last bar's close fill at this bar's open
+        if t > 0 {
+            let fill = stock_data.opens[t];
+            match open.take() {
+                None if eval.all_hold(&strategy.entry, t - 1) => {
+                    let price = fill * (1.0 + slippage);
+                    let budget = cash * settings.position_pct / 100.0 - settings.commission;
+                    if price > 0.0 && budget > 0.0 {
+                        let shares = budget / price;
+                        cash -= shares * price + settings.commission;
+                        open = Some(Trade {
+                            entry_bar: t,
+                            exit_bar: None,
+                            entry_time: stock_data.timestamps[t],
+                            entry_price: price,
+                            exit_time: None,
+                            exit_price: price,
+                            shares,
+                            pnl: -settings.commission,
+                        });
+                    }
+                }
+                Some(mut trade) if eval.all_hold(&strategy.exit, t - 1) => {
+                    let price = fill * (1.0 - slippage);
+                    cash += trade.shares * price - settings.commission;
+                    trade.exit_bar = Some(t);
+                    trade.exit_time = Some(stock_data.timestamps[t]);
+                    trade.exit_price = price;
+                    trade.pnl = trade.shares * (price - trade.entry_price) - 2.0 * settings.commission;
+                    trades.push(trade);
+                }
+                still => open = still,
+            }
+        }
+        equity.push(cash + open.map_or(0.0, |o| o.shares * close));
+    }
+    if let (Some(mut trade), Some(&last)) = (open, closes.last()) {
+        trade.exit_price = last;
+        trade.pnl = trade.shares * (last - trade.entry_price) - settings.commission;
+        trades.push(trade);
+    }
+
+    let closed: Vec<&Trade> = trades.iter().filter(|t| t.exit_time.is_some()).collect();
+    let mut peak = f64::NEG_INFINITY;
+    let mut max_drawdown: f64 = 0.0;
+    for &value in &equity {
+        peak = peak.max(value);
+        if peak > 0.0 {
+            max_drawdown = max_drawdown.max((1.0 - value / peak) * 100.0);
+        }
+    }
+    let pct = |from: f64, to: f64| if from != 0.0 { (to / from - 1.0) * 100.0 } else { 0.0 };
+    StrategyResult {
+        total_return: pct(settings.capital, equity.last().copied().unwrap_or(settings.capital)),
+        buy_and_hold: match (closes.first(), closes.last()) {
+            (Some(&first), Some(&last)) => pct(first, last),
+            _ => 0.0,
+        },
+        win_rate: (!closed.is_empty()).then(|| closed.iter().filter(|t| t.pnl > 0.0).count() as f64 / closed.len() as f64),
+        max_drawdown,
+        trades,
+        equity,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_a_round_trip_with_and_without_costs() {
+        let mut sd = StockData::new();
+        for (i, &p) in [100.0, 106.0, 110.0, 120.0, 102.0, 100.0].iter().enumerate() {
+            sd.add_point(i as i64 * 86_400, p, p, p, p, 0);
+        }
+        let close = Operand::Series(Series::Close);
+        let strategy = Strategy {
+            name: "test".into(),
+            entry: vec![Condition::Compare { left: close, right: Operand::Value(105.0), above: true }],
+            exit: vec![Condition::Compare { left: close, right: Operand::Value(103.0), above: false }],
+        };
+        let free = StrategySettings { capital: 1000.0, position_pct: 100.0, commission: 0.0, slippage_pct: 0.0, ..Default::default() };
+        let result = run(&strategy, &sd, &free);
+        // Bought at 110 on the third bar, sold at 100 on the last
+        assert_eq!(result.trades.len(), 1);
+        assert_eq!((result.trades[0].entry_price, result.trades[0].exit_price), (110.0, 100.0));
+        assert!((result.total_return - (100.0 / 110.0 - 1.0) * 100.0).abs() < 1e-9);
+        assert_eq!(result.win_rate, Some(0.0));
+        assert!((result.max_drawdown - (1.0 - 100.0 / 120.0) * 100.0).abs() < 1e-9);
+        assert_eq!(result.equity.len(), 6);
+
+        let costly = StrategySettings { commission: 5.0, slippage_pct: 1.0, ..free };
+        assert!(run(&strategy, &sd, &costly).total_return < result.total_return);
+        assert_eq!(describe(&strategy.entry), "close > 105");
+    }
+
+    #[test]
+    fn test_strategies_are_read_from_words() {
+        let (ema20, sma50) = (Operand::Series(Series::Ema(20)), Operand::Series(Series::Sma(50)));
+        assert_eq!(
+            parse_conditions("EMA(20) crosses above SMA50").unwrap(),
+            [Condition::Crosses { left: ema20, right: sma50, above: true }],
+        );
+        let exit = parse_conditions("RSI > 75 AND close below sma50").unwrap();
+        assert_eq!(exit, [
+            Condition::Compare { left: Operand::Series(Series::Rsi(14)), right: Operand::Value(75.0), above: true },
+            Condition::Compare { left: Operand::Series(Series::Close), right: sma50, above: false },
+        ]);
+        // What is shown reads back the same
+        assert_eq!(parse_conditions(&describe(&exit)).unwrap(), exit);
+        assert_eq!(parse_conditions("macd crosses under macd signal").unwrap()[0].to_string(), "MACD crosses below MACD signal");
+
+        let mut definitions = BTreeMap::new();
+        definitions.insert("Mine".to_string(), StrategyDefinition { buy: "vwap > 3".into(), sell: "rsi > 70".into() });
+        assert_eq!(parse_definitions(&definitions).unwrap_err(), "Strategy 'Mine' buy: unknown indicator 'vwap'");
+        assert!(parse_conditions("sma50 is high").is_err());
+        assert!(parse_conditions(" ").is_err());
+    }
+}
@@ -0,0 +1,315 @@
+//! Live price ticks from Yahoo Finance's streamer, a WebSocket that pushes
+//! each subscribed symbol's price as it trades. Messages are base64
+//! protobuf `PricingData`; only the few fields shown are decoded.
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Arc;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use tokio::io::{AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio_rustls::{
+    client::TlsStream,
+    rustls::{self, pki_types::ServerName},
+    TlsConnector,
+};
+
+use crate::lib::{error::AppError, stock_data::StockData};
+
+const HOST: &str = "streamer.finance.yahoo.com";
+const PATH: &str = "/?version=2";
+const USER_AGENT: &str =
+    "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Safari/537.36";
+
+/// Frames larger than this are refused rather than buffered.
+const MAX_FRAME: u64 = 1 << 20;
+
+/// One price update.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tick {
+    pub symbol: String,
+    pub price: f64,
+    /// Unix seconds.
+    pub timestamp: i64,
+    /// Volume traded so far today, where the exchange reports it.
+    pub day_volume: Option<u64>,
+}
+
+/// Random bytes for the handshake key and frame masks; they only need to
+/// be unpredictable to proxies, not secret.
+fn random_bytes<const N: usize>() -> [u8; N] {
+    let mut bytes = [0; N];
+    for chunk in bytes.chunks_mut(8) {
+        let word = RandomState::new().build_hasher().finish().to_le_bytes();
+        chunk.copy_from_slice(&word[..chunk.len()]);
+    }
+    bytes
+}
+
+/// Read a protobuf varint from the front of `buf`.
+fn varint(buf: &mut &[u8]) -> Option<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = buf.split_first()?;
+        *buf = rest;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+/// A `PricingData` message: field 1 is the symbol, 2 the price (float),
+/// 3 the time in milliseconds and 9 the day's volume (both zigzag sint64).
+/// Other fields are skipped by wire type.
+fn decode_pricing(mut buf: &[u8]) -> Option<Tick> {
+    let zigzag = |v: u64| (v >> 1) as i64 ^ -((v & 1) as i64);
+    let (mut symbol, mut price, mut timestamp, mut day_volume) = (None, None, 0, None);
+    while !buf.is_empty() {
+        let key = varint(&mut buf)?;
+        match (key >> 3, key & 7) {
+            (field, 0) => {
+                let value = varint(&mut buf)?;
+                match field {
+                    3 => timestamp = zigzag(value) / 1000,
+                    9 => day_volume = u64::try_from(zigzag(value)).ok(),
+                    _ => {}
+                }
+            }
+            (_, 1) => buf = buf.get(8..)?,
+            (field, 2) => {
+                let len = varint(&mut buf)? as usize;
+                let bytes = buf.get(..len)?;
+                if field == 1 {
+                    symbol = Some(String::from_utf8_lossy(bytes).into_owned());
+                }
+                buf = &buf[len..];
+            }
+            (field, 5) => {
+                let bytes: [u8; 4] = buf.get(..4)?.try_into().ok()?;
+                if field == 2 {
+                    price = Some(f64::from(f32::from_le_bytes(bytes)));
+                }
+                buf = &buf[4..];
+            }
+            _ => return None,
+        }
+    }
+    Some(Tick { symbol: symbol?, price: price.filter(|p| *p > 0.0)?, timestamp, day_volume })
+}
+
+/// The tick in a text message: `{"type":"pricing","message":"<base64>"}`,
+/// or the bare base64 the first version of the streamer sends.
+pub fn parse_message(text: &str) -> Option<Tick> {
+    let json = serde_json::from_str::<serde_json::Value>(text).ok();
+    let encoded = match &json {
+        Some(json) => json["message"].as_str()?,
+        None => text.trim(),
+    };
+    decode_pricing(&STANDARD.decode(encoded).ok()?)
+}
+
+/// A WebSocket client connection, just enough of RFC 6455 for the
+/// streamer: masked frames out, unfragmented or continued frames in.
+struct Socket {
+    stream: BufReader<TlsStream<TcpStream>>,
+}
+
+impl Socket {
+    async fn connect() -> Result<Self, AppError> {
+        let unavailable = |e: std::io::Error| AppError::Unavailable(format!("Yahoo streamer: {e}"));
+        let mut roots = rustls::RootCertStore::empty();
+        roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+        let config = rustls::ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .map_err(|e| AppError::ApiError(format!("TLS: {e}")))?
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        let name = ServerName::try_from(HOST).map_err(|e| AppError::ApiError(format!("TLS: {e}")))?;
+        let tcp = TcpStream::connect((HOST, 443)).await.map_err(unavailable)?;
+        let tls = TlsConnector::from(Arc::new(config)).connect(name, tcp).await.map_err(unavailable)?;
+        let mut stream = BufReader::new(tls);
+
+        let key = STANDARD.encode(random_bytes::<16>());
+        let request = format!(
+            "GET {PATH} HTTP/1.1\r\nHost: {HOST}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+             Sec-WebSocket-Key: {key}\r\nSec-WebSocket-Version: 13\r\n\
+             Origin: https://finance.yahoo.com\r\nUser-Agent: {USER_AGENT}\r\n\r\n"
+        );
+        stream.get_mut().write_all(request.as_bytes()).await.map_err(unavailable)?;
+
+        // The response head, up to the blank line; frames follow it
+        let mut head = Vec::new();
+        while !head.ends_with(b"\r\n\r\n") {
+            head.push(stream.read_u8().await.map_err(unavailable)?);
+            if head.len() > 16 * 1024 {
+                return Err(AppError::ApiError("Yahoo streamer: oversized handshake".into()));
+            }
+        }
+        let head = String::from_utf8_lossy(&head);
+        let status = head.lines().next().unwrap_or_default();
+        if status.split_whitespace().nth(1) != Some("101") {
+            return Err(AppError::Unavailable(format!("Yahoo streamer refused the connection: {status}")));
+        }
+        Ok(Socket { stream })
+    }
+
+    async fn send(&mut self, opcode: u8, payload: &[u8]) -> std::io::Result<()> {
+        let mut frame = vec![0x80 | opcode];
+        match payload.len() {
+            len if len < 126 => frame.push(0x80 | len as u8),
+            len if len <= u16::MAX as usize => {
+                frame.push(0x80 | 126);
+                frame.extend((len as u16).to_be_bytes());
+            }
+            len => {
+                frame.push(0x80 | 127);
+                frame.extend((len as u64).to_be_bytes());
+            }
+        }
+        let mask = random_bytes::<4>();
+        frame.extend(mask);
+        frame.extend(payload.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+        self.stream.get_mut().write_all(&frame).await
+    }
+
+    /// The next complete text message, answering pings on the way. `None`
+    /// once the server closes the connection.
+    async fn next_text(&mut self) -> std::io::Result<Option<String>> {
+        let mut message = Vec::new();
+        loop {
+            let [first, second] = [self.stream.read_u8().await?, self.stream.read_u8().await?];
+            let (fin, opcode) = (first & 0x80 != 0, first & 0x0f);
+            let len = match u64::from(second & 0x7f) {
+                126 => u64::from(self.stream.read_u16().await?),
+                127 => self.stream.read_u64().await?,
+                len => len,
+            };
+            if len > MAX_FRAME {
+                return Err(std::io::Error::other("frame too large"));
+            }
+            let mask = if second & 0x80 != 0 {
+                let mut mask = [0; 4];
+                self.stream.read_exact(&mut mask).await?;
+                Some(mask)
+            } else {
+                None
+            };
+            let mut payload = vec![0; len as usize];
+            self.stream.read_exact(&mut payload).await?;
+            if let Some(mask) = mask {
+                payload.iter_mut().enumerate().for_each(|(i, b)| *b ^= mask[i % 4]);
+            }
+            match opcode {
+                0x8 => return Ok(None),
+                0x9 => self.send(0xA, &payload).await?,
+                0xA => {}
+                _ => {
+                    message.extend(payload);
+                    if fin {
+                        return Ok(Some(String::from_utf8_lossy(&message).into_owned()));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Fold `tick` into `data`, whose bars are `bar_seconds` long: the last bar
+/// moves with the price until the tick falls past its end, when a new bar
+/// starts at the boundary. Ticks older than the last bar are dropped.
+pub fn apply_tick(data: &mut StockData, tick: &Tick, bar_seconds: i64) {
+    let Some(&last) = data.timestamps.last() else { return };
+    if tick.timestamp < last {
+        return;
+    }
+    if tick.timestamp >= last + bar_seconds {
+        let start = last + (tick.timestamp - last) / bar_seconds * bar_seconds;
+        data.add_point(start, tick.price, tick.price, tick.price, tick.price, 0);
+    } else {
+        let i = data.len() - 1;
+        data.closes[i] = tick.price;
+        data.highs[i] = data.highs[i].max(tick.price);
+        data.lows[i] = data.lows[i].min(tick.price);
+    }
+    // A day's volume is the last daily bar's
+    if let (Some(volume), 86_400, Some(last)) = (tick.day_volume, bar_seconds, data.volumes.last_mut()) {
+        *last = volume;
+    }
+}
+
+/// Subscribe to `symbols` and call `on_tick` with each price update until
+/// the connection drops, which always ends in an error to retry on.
+pub async fn stream_quotes(symbols: &[String], mut on_tick: impl FnMut(Tick)) -> Result<(), AppError> {
+    let mut socket = Socket::connect().await?;
+    let subscribe = serde_json::json!({ "subscribe": symbols }).to_string();
+    let lost = |e: std::io::Error| AppError::Unavailable(format!("Yahoo streamer: {e}"));
+    socket.send(0x1, subscribe.as_bytes()).await.map_err(lost)?;
+    while let Some(text) = socket.next_text().await.map_err(lost)? {
+        if let Some(tick) = parse_message(&text) {
+            on_tick(tick);
+        }
+    }
+    Err(AppError::Unavailable("Yahoo streamer closed the connection".into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A `PricingData` message as the streamer encodes it.
+    fn pricing(symbol: &str, price: f32, millis: i64, volume: i64) -> Vec<u8> {
+        let zigzag = |v: i64| ((v << 1) ^ (v >> 63)) as u64;
+        let varint = |mut v: u64, out: &mut Vec<u8>| loop {
+            if v < 0x80 {
+                out.push(v as u8);
+                break;
+            }
+            out.push((v as u8 & 0x7f) | 0x80);
+            v >>= 7;
+        };
+        let mut out = vec![0x0a, symbol.len() as u8];
+        out.extend(symbol.as_bytes());
+        out.push(0x15);
+        out.extend(price.to_le_bytes());
+        out.push(0x18);
+        varint(zigzag(millis), &mut out);
+        // Currency, a string field that is skipped
+        out.extend([0x22, 3]);
+        out.extend(b"USD");
+        out.push(0x48);
+        varint(zigzag(volume), &mut out);
+        out
+    }
+
+    #[test]
+    fn test_pricing_messages_decode() {
+        let bytes = pricing("AAPL", 187.25, 1_704_213_000_123, 48_000_000);
+        let tick = decode_pricing(&bytes).unwrap();
+        assert_eq!(tick, Tick { symbol: "AAPL".into(), price: 187.25, timestamp: 1_704_213_000, day_volume: Some(48_000_000) });
+
+        let wrapped = format!(r#"{{"type":"pricing","message":"{}"}}"#, STANDARD.encode(&bytes));
+        assert_eq!(parse_message(&wrapped), Some(tick.clone()));
+        assert_eq!(parse_message(&STANDARD.encode(&bytes)), Some(tick));
+        assert_eq!(parse_message(r#"{"type":"heartbeat"}"#), None);
+        assert_eq!(decode_pricing(&bytes[..5]), None);
+    }
+
+    #[test]
+    fn test_ticks_move_the_last_bar_then_start_the_next() {
+        let mut data = StockData::new();
+        data.add_point(600, 10.0, 10.5, 9.5, 10.0, 100);
+        let tick = |timestamp, price| Tick { symbol: "X".into(), price, timestamp, day_volume: None };
+
+        apply_tick(&mut data, &tick(650, 11.0), 300);
+        assert_eq!((data.len(), data.highs[0], data.closes[0]), (1, 11.0, 11.0));
+        apply_tick(&mut data, &tick(500, 1.0), 300);
+        assert_eq!((data.len(), data.lows[0]), (1, 9.5));
+
+        apply_tick(&mut data, &tick(1_250, 10.8), 300);
+        assert_eq!(data.len(), 2);
+        assert_eq!((data.timestamps[1], data.opens[1], data.volumes[1]), (1_200, 10.8, 0));
+    }
+}
@@ -0,0 +1,510 @@
+use crate::lib::{
+    config::{ForecastSettings, PortfolioSettings, ProviderSettings, RefreshSettings, StockConfig, StrategySettings, SymbolNote, UiSettings},
+    error::AppError,
+    persistence::{AppConfig, PersistenceManager},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// File name used inside sync folders and git repositories.
+pub const SYNC_FILE: &str = "bstock-watchlist.json";
+
+/// Where the watchlist is mirrored.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum SyncTarget {
+    /// A directory kept in sync by something else (Dropbox, Syncthing, …).
+    Folder { path: PathBuf },
+    /// A local clone; pulls before reading and commits + pushes after writing.
+    Git { repo: PathBuf },
+    /// A URL accepting GET and PUT of the raw file (WebDAV, pre-signed S3, …).
+    Http { url: String },
+}
+
+impl SyncTarget {
+    /// `git:<repo>`, an `http(s)://` URL, or a plain folder path.
+    pub fn parse(spec: &str) -> SyncTarget {
+        if let Some(repo) = spec.strip_prefix("git:") {
+            SyncTarget::Git { repo: PathBuf::from(repo) }
+        } else if spec.starts_with("http://") || spec.starts_with("https://") {
+            SyncTarget::Http { url: spec.to_string() }
+        } else {
+            SyncTarget::Folder { path: PathBuf::from(spec) }
+        }
+    }
+}
+
+impl std::fmt::Display for SyncTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SyncTarget::Folder { path } => write!(f, "folder {}", path.display()),
+            SyncTarget::Git { repo } => write!(f, "git repo {}", repo.display()),
+            SyncTarget::Http { url } => write!(f, "{}", url),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncConfig {
+    pub target: SyncTarget,
+    /// Fingerprint of each watchlist at the last successful push/pull; the
+    /// common ancestor used to tell which side changed since.
+    #[serde(default)]
+    pub watchlists: BTreeMap<String, u64>,
+    /// Fingerprint of the settings at the last successful push/pull.
+    #[serde(default)]
+    pub settings: Option<u64>,
+    /// The single fingerprint kept before every watchlist was synced; it
+    /// stands for the active watchlist until the next sync.
+    #[serde(default, skip_serializing)]
+    pub last_synced: Option<u64>,
+}
+
+/// The settings shared between machines. Machine-local state (the session,
+/// the sync target) and API keys stay where they are.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncedSettings {
+    pub ui: UiSettings,
+    pub refresh: RefreshSettings,
+    pub providers: ProviderSettings,
+    pub forecast: ForecastSettings,
+    pub strategy: StrategySettings,
+    pub portfolio: PortfolioSettings,
+    pub notes: BTreeMap<String, SymbolNote>,
+}
+
+impl SyncedSettings {
+    fn of(config: &AppConfig) -> Self {
+        let mut providers = config.providers.clone();
+        providers.api_keys.clear();
+        Self {
+            ui: config.ui.clone(),
+            refresh: config.refresh,
+            providers,
+            forecast: config.forecast.clone(),
+            strategy: config.strategy.clone(),
+            portfolio: config.portfolio.clone(),
+            notes: config.notes.clone(),
+        }
+    }
+
+    fn apply(self, config: &mut AppConfig) {
+        let api_keys = std::mem::take(&mut config.providers.api_keys);
+        config.ui = self.ui;
+        config.refresh = self.refresh;
+        config.providers = self.providers;
+        config.providers.api_keys = api_keys;
+        config.forecast = self.forecast;
+        config.strategy = self.strategy;
+        config.portfolio = self.portfolio;
+        config.notes = self.notes;
+    }
+}
+
+/// What is stored at the sync target.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SyncDocument {
+    pub version: u32,
+    /// Unix timestamp of the push that wrote it.
+    pub updated_at: u64,
+    /// Every watchlist by name, the active one included.
+    #[serde(default)]
+    pub watchlists: BTreeMap<String, StockConfig>,
+    /// Absent in version 1 documents, which held only a watchlist.
+    #[serde(default)]
+    pub settings: Option<SyncedSettings>,
+    /// The version 1 watchlist, read as the default one.
+    #[serde(default, skip_serializing)]
+    stock_config: Option<StockConfig>,
+}
+
+impl SyncDocument {
+    fn of(config: &AppConfig) -> Self {
+        let mut watchlists = config.watchlists.clone();
+        watchlists.insert(config.active_watchlist.clone(), config.stock_config.clone());
+        Self {
+            version: 2,
+            updated_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            watchlists,
+            settings: Some(SyncedSettings::of(config)),
+            stock_config: None,
+        }
+    }
+}
+
+/// FNV-1a over the value's JSON — stable across runs and platforms,
+/// unlike `std`'s hasher.
+pub fn fingerprint<T: Serialize>(value: &T) -> u64 {
+    let json = serde_json::to_string(value).unwrap_or_default();
+    json.bytes().fold(0xcbf2_9ce4_8422_2325, |h, b| (h ^ b as u64).wrapping_mul(0x0100_0000_01b3))
+}
+
+/// Stands in for the fingerprint of a watchlist one side does not have,
+/// so creating or deleting one is a change like any other.
+const ABSENT: u64 = 0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SyncState {
+    InSync,
+    /// Nothing has been pushed to the target yet.
+    NoRemote,
+    /// Only this machine changed since the last sync: safe to push.
+    LocalAhead,
+    /// Only the target changed: safe to pull.
+    RemoteAhead,
+    /// Both sides changed — pushing or pulling would lose edits.
+    Conflict,
+}
+
+pub fn classify(local: u64, remote: Option<u64>, base: Option<u64>) -> SyncState {
+    match remote {
+        None => SyncState::NoRemote,
+        Some(r) if r == local => SyncState::InSync,
+        Some(r) if Some(r) == base => SyncState::LocalAhead,
+        Some(_) if Some(local) == base => SyncState::RemoteAhead,
+        Some(_) => SyncState::Conflict,
+    }
+}
+
+fn git(repo: &Path, args: &[&str]) -> Result<(), AppError> {
+    let out = Command::new("git").arg("-C").arg(repo).args(args).output().map_err(AppError::Io)?;
+    if out.status.success() {
+        Ok(())
+    } else {
+        Err(AppError::Sync(format!(
+            "git {}: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&out.stderr).trim()
+        )))
+    }
+}
+
+fn read_file(path: &Path) -> Result<Option<String>, AppError> {
+    match fs::read_to_string(path) {
+        Ok(s) => Ok(Some(s)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(AppError::Io(e)),
+    }
+}
+
+/// Fetch the document currently stored at `target`, if any.
+pub async fn fetch_remote(target: &SyncTarget) -> Result<Option<SyncDocument>, AppError> {
+    let content = match target {
+        SyncTarget::Folder { path } => read_file(&path.join(SYNC_FILE))?,
+        SyncTarget::Git { repo } => {
+            git(repo, &["pull", "--ff-only", "--quiet"])?;
+            read_file(&repo.join(SYNC_FILE))?
+        }
+        SyncTarget::Http { url } => {
+            let resp = reqwest::get(url).await.map_err(|e| AppError::Sync(e.to_string()))?;
+            if resp.status() == reqwest::StatusCode::NOT_FOUND {
+                None
+            } else {
+                let resp = resp.error_for_status().map_err(|e| AppError::Sync(e.to_string()))?;
+                Some(resp.text().await.map_err(|e| AppError::Sync(e.to_string()))?)
+            }
+        }
+    };
+    content
+        .map(|c| serde_json::from_str(&c).map_err(AppError::ConfigParseError))
+        .transpose()
+}
+
+/// Replace the document stored at `target`.
+pub async fn store_remote(target: &SyncTarget, doc: &SyncDocument) -> Result<(), AppError> {
+    let content = serde_json::to_string_pretty(doc).map_err(AppError::ConfigParseError)?;
+    match target {
+        SyncTarget::Folder { path } => {
+            fs::create_dir_all(path).map_err(AppError::Io)?;
+            fs::write(path.join(SYNC_FILE), content).map_err(AppError::Io)?;
+        }
+        SyncTarget::Git { repo } => {
+            fs::write(repo.join(SYNC_FILE), content).map_err(AppError::Io)?;
+            git(repo, &["add", SYNC_FILE])?;
+            git(repo, &["commit", "--quiet", "-m", "Update bstock watchlist"])?;
+            git(repo, &["push", "--quiet"])?;
+        }
+        SyncTarget::Http { url } => {
+            reqwest::Client::new()
+                .put(url)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(content)
+                .send()
+                .await
+                .and_then(|r| r.error_for_status())
+                .map_err(|e| AppError::Sync(e.to_string()))?;
+        }
+    }
+    Ok(())
+}
+
+fn configured(config: &AppConfig) -> Result<SyncConfig, AppError> {
+    config.sync.clone().ok_or_else(|| {
+        AppError::Sync("no sync target configured (use `bstock sync set <target>`)".into())
+    })
+}
+
+/// Point sync at a new target. The previous base no longer applies.
+pub fn set_target(pm: &PersistenceManager, target: SyncTarget) -> Result<(), AppError> {
+    let mut config = pm.load_config()?;
+    config.sync = Some(SyncConfig { target, watchlists: BTreeMap::new(), settings: None, last_synced: None });
+    pm.save_config(&config)
+}
+
+/// What is compared and synced on its own: one watchlist, or the settings.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SyncItem {
+    Watchlist(String),
+    Settings,
+}
+
+impl std::fmt::Display for SyncItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SyncItem::Watchlist(name) => write!(f, "watchlist {}", name),
+            SyncItem::Settings => write!(f, "settings"),
+        }
+    }
+}
+
+fn fingerprints(doc: &SyncDocument) -> BTreeMap<SyncItem, u64> {
+    doc.watchlists
+        .iter()
+        .map(|(name, config)| (SyncItem::Watchlist(name.clone()), fingerprint(config)))
+        .chain(doc.settings.as_ref().map(|s| (SyncItem::Settings, fingerprint(s))))
+        .collect()
+}
+
+fn bases(sync: &SyncConfig, active: &str) -> BTreeMap<SyncItem, u64> {
+    let mut bases: BTreeMap<SyncItem, u64> = sync
+        .watchlists
+        .iter()
+        .map(|(name, fp)| (SyncItem::Watchlist(name.clone()), *fp))
+        .chain(sync.settings.map(|fp| (SyncItem::Settings, fp)))
+        .collect();
+    if let (true, Some(fp)) = (bases.is_empty(), sync.last_synced) {
+        bases.insert(SyncItem::Watchlist(active.to_string()), fp);
+    }
+    bases
+}
+
+/// Classify every item either side has. An item missing from a side, or
+/// from the last sync, compares as `ABSENT`, so a watchlist only one
+/// machine has is simply ahead there.
+fn compare(
+    local: &BTreeMap<SyncItem, u64>,
+    remote: Option<&BTreeMap<SyncItem, u64>>,
+    bases: &BTreeMap<SyncItem, u64>,
+) -> Vec<(SyncItem, SyncState)> {
+    let mut items: Vec<&SyncItem> = local.keys().chain(remote.into_iter().flat_map(|r| r.keys())).collect();
+    items.sort();
+    items.dedup();
+    items
+        .into_iter()
+        .map(|item| {
+            let fp = |side: &BTreeMap<SyncItem, u64>| side.get(item).copied().unwrap_or(ABSENT);
+            let state = classify(fp(local), remote.map(fp), Some(fp(bases)));
+            (item.clone(), state)
+        })
+        .collect()
+}
+
+/// The target's document, with a version 1 watchlist read as `active`.
+async fn fetch_document(target: &SyncTarget, active: &str) -> Result<Option<SyncDocument>, AppError> {
+    Ok(fetch_remote(target).await?.map(|mut doc| {
+        if let Some(stock_config) = doc.stock_config.take() {
+            doc.watchlists.entry(active.to_string()).or_insert(stock_config);
+        }
+        doc
+    }))
+}
+
+fn blocked(states: &[(SyncItem, SyncState)], blocking: &[SyncState]) -> Vec<String> {
+    states.iter().filter(|(_, s)| blocking.contains(s)).map(|(item, _)| item.to_string()).collect()
+}
+
+/// Record `fingerprints` as the state both sides agreed on.
+fn remember(config: &mut AppConfig, fingerprints: BTreeMap<SyncItem, u64>) {
+    if let Some(sync) = config.sync.as_mut() {
+        sync.settings = fingerprints.get(&SyncItem::Settings).copied();
+        sync.watchlists = fingerprints
+            .into_iter()
+            .filter_map(|(item, fp)| match item {
+                SyncItem::Watchlist(name) => Some((name, fp)),
+                SyncItem::Settings => None,
+            })
+            .collect();
+        sync.last_synced = None;
+    }
+}
+
+/// Compare each local watchlist, and the settings, with the target's.
+pub async fn status(pm: &PersistenceManager) -> Result<(SyncTarget, Vec<(SyncItem, SyncState)>), AppError> {
+    let config = pm.load_config()?;
+    let sync = configured(&config)?;
+    let local = fingerprints(&SyncDocument::of(&config));
+    let remote = fetch_document(&sync.target, &config.active_watchlist).await?.map(|d| fingerprints(&d));
+    let states = compare(&local, remote.as_ref(), &bases(&sync, &config.active_watchlist));
+    Ok((sync.target, states))
+}
+
+/// Upload every watchlist and the settings. Refuses to overwrite changes
+/// made elsewhere unless `force` is set.
+pub async fn push(pm: &PersistenceManager, force: bool) -> Result<Vec<(SyncItem, SyncState)>, AppError> {
+    let mut config = pm.load_config()?;
+    let sync = configured(&config)?;
+    let doc = SyncDocument::of(&config);
+    let local = fingerprints(&doc);
+    let remote = fetch_document(&sync.target, &config.active_watchlist).await?.map(|d| fingerprints(&d));
+    let states = compare(&local, remote.as_ref(), &bases(&sync, &config.active_watchlist));
+    let changed_there = blocked(&states, &[SyncState::RemoteAhead, SyncState::Conflict]);
+    if !changed_there.is_empty() && !force {
+        return Err(AppError::Sync(format!(
+            "{} changed at {} since the last sync; pull first or push with --force",
+            changed_there.join(", "),
+            sync.target
+        )));
+    }
+    if states.iter().any(|(_, s)| *s != SyncState::InSync) {
+        store_remote(&sync.target, &doc).await?;
+    }
+    remember(&mut config, local);
+    pm.save_config(&config)?;
+    Ok(states)
+}
+
+/// Bring in every watchlist, and the settings, that changed at the target.
+/// Ones changed only here are kept for the next push; ones changed on both
+/// sides are an error unless `force` is set, which takes the target's.
+pub async fn pull(pm: &PersistenceManager, force: bool) -> Result<Vec<(SyncItem, SyncState)>, AppError> {
+    let mut config = pm.load_config()?;
+    let sync = configured(&config)?;
+    let local = fingerprints(&SyncDocument::of(&config));
+    let Some(mut doc) = fetch_document(&sync.target, &config.active_watchlist).await? else {
+        return Err(AppError::Sync(format!("nothing stored at {} yet", sync.target)));
+    };
+    let remote = fingerprints(&doc);
+    let mut bases = bases(&sync, &config.active_watchlist);
+    let states = compare(&local, Some(&remote), &bases);
+    let conflicts = blocked(&states, &[SyncState::Conflict]);
+    if !conflicts.is_empty() && !force {
+        return Err(AppError::Sync(format!(
+            "{} changed both here and at {} since the last sync; pull with --force to take theirs",
+            conflicts.join(", "),
+            sync.target
+        )));
+    }
+    for (item, state) in &states {
+        let take = match state {
+            SyncState::InSync | SyncState::RemoteAhead => true,
+            SyncState::Conflict | SyncState::LocalAhead => force,
+            SyncState::NoRemote => false,
+        };
+        if !take {
+            continue;
+        }
+        match item {
+            SyncItem::Settings => {
+                if let Some(settings) = doc.settings.take() {
+                    settings.apply(&mut config);
+                }
+            }
+            SyncItem::Watchlist(name) => match doc.watchlists.remove(name) {
+                Some(watchlist) if *name == config.active_watchlist => config.stock_config = watchlist,
+                Some(watchlist) => {
+                    config.watchlists.insert(name.clone(), watchlist);
+                }
+                // Deleted at the target; the active watchlist is kept, and
+                // goes back up with the next push
+                None => {
+                    config.watchlists.remove(name);
+                }
+            },
+        }
+        match remote.get(item) {
+            Some(fp) => bases.insert(item.clone(), *fp),
+            None => bases.remove(item),
+        };
+    }
+    remember(&mut config, bases);
+    pm.save_config(&config)?;
+    Ok(states)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(classify(1, None, None), SyncState::NoRemote);
+        assert_eq!(classify(1, Some(1), None), SyncState::InSync);
+        assert_eq!(classify(2, Some(1), Some(1)), SyncState::LocalAhead);
+        assert_eq!(classify(1, Some(2), Some(1)), SyncState::RemoteAhead);
+        assert_eq!(classify(2, Some(3), Some(1)), SyncState::Conflict);
+        // Never synced and both sides exist with different content
+        assert_eq!(classify(2, Some(3), None), SyncState::Conflict);
+    }
+
+    #[test]
+    fn test_parse_target() {
+        assert_eq!(SyncTarget::parse("git:/tmp/r"), SyncTarget::Git { repo: "/tmp/r".into() });
+        assert_eq!(SyncTarget::parse("https://x/y.json"), SyncTarget::Http { url: "https://x/y.json".into() });
+        assert_eq!(SyncTarget::parse("/mnt/sync"), SyncTarget::Folder { path: "/mnt/sync".into() });
+    }
+
+    #[test]
+    fn test_pull_updates_the_named_watchlist_not_the_active_one() {
+        let dir = std::env::temp_dir().join(format!("bstock-test-sync-{}", std::process::id()));
+        let laptop = PersistenceManager::new(Some(dir.join("laptop"))).unwrap();
+        let desktop = PersistenceManager::new(Some(dir.join("desktop"))).unwrap();
+        let target = SyncTarget::Folder { path: dir.join("shared") };
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let watchlist = |symbols: &[&str]| StockConfig {
+            symbols: symbols.iter().map(|s| s.to_string()).collect(),
+            analysis_period_days: 90,
+        };
+
+        set_target(&laptop, target.clone()).unwrap();
+        laptop.switch_watchlist("tech").unwrap();
+        laptop.save_stock_config(&watchlist(&["AAPL"])).unwrap();
+        rt.block_on(push(&laptop, false)).unwrap();
+
+        set_target(&desktop, target).unwrap();
+        rt.block_on(pull(&desktop, false)).unwrap();
+        assert_eq!(desktop.watchlist_names().1, vec!["default", "tech"]);
+
+        // Both machines change a different watchlist; the desktop keeps
+        // "default" active while pulling "tech"
+        laptop.save_stock_config(&watchlist(&["AAPL", "MSFT"])).unwrap();
+        rt.block_on(push(&laptop, false)).unwrap();
+        desktop.save_stock_config(&watchlist(&["KO"])).unwrap();
+        let states = rt.block_on(pull(&desktop, false)).unwrap();
+        assert!(states.contains(&(SyncItem::Watchlist("default".into()), SyncState::LocalAhead)));
+        assert!(states.contains(&(SyncItem::Watchlist("tech".into()), SyncState::RemoteAhead)));
+        let config = desktop.load_config().unwrap();
+        assert_eq!(config.active_watchlist, "default");
+        assert_eq!(config.stock_config.symbols, vec!["KO"]);
+        assert_eq!(config.watchlists["tech"].symbols, vec!["AAPL", "MSFT"]);
+
+        rt.block_on(push(&desktop, false)).unwrap();
+        rt.block_on(pull(&laptop, false)).unwrap();
+        let config = laptop.load_config().unwrap();
+        assert_eq!(config.active_watchlist, "tech");
+        assert_eq!(config.watchlists["default"].symbols, vec!["KO"]);
+
+        // A watchlist changed on both sides since the last sync blocks the pull
+        laptop.save_stock_config(&watchlist(&["NVDA"])).unwrap();
+        rt.block_on(push(&laptop, false)).unwrap();
+        desktop.switch_watchlist("tech").unwrap();
+        desktop.save_stock_config(&watchlist(&["AMD"])).unwrap();
+        assert!(rt.block_on(pull(&desktop, false)).is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,150 @@
+//! Time zones for showing times. Bars are stored as Unix seconds; a bar's
+//! time belongs to the exchange it traded on, and is shown in the zone the
+//! user picks: their own, the exchange's, UTC or a fixed offset.
+
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, TimeZone, Utc};
+
+use crate::lib::{
+    calendar::{exchange_of, Zone},
+    instrument::AssetClass,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DisplayZone {
+    /// The computer's own zone.
+    #[default]
+    Local,
+    /// Each symbol's exchange zone; times not tied to a symbol are local.
+    Exchange,
+    Utc,
+    Fixed(FixedOffset),
+}
+
+impl DisplayZone {
+    /// `local`, `exchange`, `utc` or an offset such as `+05:30` or `-4`.
+    pub fn parse(name: &str) -> Option<Self> {
+        let name = name.trim();
+        match name.to_ascii_lowercase().as_str() {
+            "local" => return Some(DisplayZone::Local),
+            "exchange" => return Some(DisplayZone::Exchange),
+            "utc" | "gmt" | "z" => return Some(DisplayZone::Utc),
+            _ => {}
+        }
+        let sign = match name.chars().next()? {
+            '+' => 1,
+            '-' => -1,
+            _ => return None,
+        };
+        let (hours, minutes) = name[1..].split_once(':').unwrap_or((&name[1..], "0"));
+        let (hours, minutes): (i32, i32) = (hours.parse().ok()?, minutes.parse().ok()?);
+        if hours > 14 || minutes >= 60 {
+            return None;
+        }
+        FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60)).map(DisplayZone::Fixed)
+    }
+
+    /// The zone as `parse` reads it back.
+    pub fn name(self) -> String {
+        match self {
+            DisplayZone::Local => "local".into(),
+            DisplayZone::Exchange => "exchange".into(),
+            DisplayZone::Utc => "utc".into(),
+            DisplayZone::Fixed(offset) => offset.to_string(),
+        }
+    }
+
+    /// An instant not tied to a symbol, such as when data arrived.
+    pub fn instant<Tz: TimeZone>(self, at: &DateTime<Tz>) -> DateTime<FixedOffset> {
+        match self {
+            DisplayZone::Local | DisplayZone::Exchange => at.with_timezone(&Local).fixed_offset(),
+            DisplayZone::Utc => at.with_timezone(&Utc).fixed_offset(),
+            DisplayZone::Fixed(offset) => at.with_timezone(&offset),
+        }
+    }
+
+    /// Now, in this zone.
+    pub fn now(self) -> DateTime<FixedOffset> {
+        self.instant(&Utc::now())
+    }
+
+    /// The bar of `symbol` at `timestamp`, in this zone.
+    pub fn bar_time(self, symbol: &str, timestamp: i64) -> Option<DateTime<FixedOffset>> {
+        match self {
+            DisplayZone::Exchange => exchange_time(symbol, timestamp),
+            _ => DateTime::from_timestamp(timestamp, 0).map(|t| self.instant(&t)),
+        }
+    }
+
+    /// `timestamp` of a bar of `symbol` written with `fmt`. Formats with a
+    /// time of day use this zone; date-only ones give the exchange's
+    /// trading day, which a zone far from the exchange would shift.
+    pub fn format_bar(self, symbol: &str, timestamp: i64, fmt: &str) -> String {
+        let time = if fmt.contains("%H") { self.bar_time(symbol, timestamp) } else { exchange_time(symbol, timestamp) };
+        time.map_or_else(|| "?".into(), |t| t.format(fmt).to_string())
+    }
+}
+
+/// Zone of the market `symbol` trades in: its exchange's, New York for
+/// currencies, whose trading day turns over there, and UTC for crypto and
+/// exchanges the calendar does not know.
+pub fn exchange_zone(symbol: &str) -> Option<Zone> {
+    match AssetClass::of(symbol) {
+        AssetClass::Forex => Some(Zone::NewYork),
+        _ => exchange_of(symbol).map(|exchange| exchange.zone),
+    }
+}
+
+/// `timestamp` in the zone of `symbol`'s market.
+pub fn exchange_time(symbol: &str, timestamp: i64) -> Option<DateTime<FixedOffset>> {
+    let utc = DateTime::from_timestamp(timestamp, 0)?;
+    let offset = exchange_zone(symbol).map_or(0, |zone| zone.offset_minutes(utc));
+    Some(utc.with_timezone(&FixedOffset::east_opt(offset as i32 * 60)?))
+}
+
+/// The trading day of `symbol`'s bar at `timestamp`.
+pub fn bar_date(symbol: &str, timestamp: i64) -> Option<NaiveDate> {
+    exchange_time(symbol, timestamp).map(|t| t.date_naive())
+}
+
+/// Dates as `YYYY-MM-DD`.
+pub mod ymd {
+    use chrono::NaiveDate;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(date: &NaiveDate, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&date.format("%Y-%m-%d"))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<NaiveDate, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        NaiveDate::parse_from_str(&text, "%Y-%m-%d").map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zones_parse_and_round_trip() {
+        for name in ["local", "exchange", "utc", "+05:30", "-04:00"] {
+            assert_eq!(DisplayZone::parse(name).map(|z| z.name()).as_deref(), Some(name));
+        }
+        assert_eq!(DisplayZone::parse("-4"), DisplayZone::parse("-04:00"));
+        assert_eq!(DisplayZone::parse("UTC"), Some(DisplayZone::Utc));
+        assert_eq!(DisplayZone::parse("Mars/Olympus"), None);
+        assert_eq!(DisplayZone::parse("+15"), None);
+    }
+
+    #[test]
+    fn test_bars_keep_their_exchange_day() {
+        // 9:00 in Tokyo on 15 January is 0:00 UTC; 9:30 in New York is 14:30 UTC
+        let tokyo = 1_736_899_200;
+        assert_eq!(bar_date("7203.T", tokyo).unwrap().to_string(), "2025-01-15");
+        let fixed = DisplayZone::parse("-08:00").unwrap();
+        assert_eq!(fixed.format_bar("7203.T", tokyo, "%Y-%m-%d"), "2025-01-15");
+        assert_eq!(fixed.format_bar("7203.T", tokyo, "%d %H:%M"), "14 16:00");
+        assert_eq!(DisplayZone::Exchange.format_bar("AAPL", 1_736_951_400, "%H:%M %:z"), "09:30 -05:00");
+        assert_eq!(DisplayZone::Utc.format_bar("AAPL", 1_736_951_400, "%H:%M"), "14:30");
+    }
+}
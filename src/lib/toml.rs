@@ -0,0 +1,518 @@
+//! Just enough TOML to keep the config file in it: conversion between TOML
+//! documents and `serde_json::Value`, so the config types serialize the same
+//! way whichever format is on disk. Dates and times are not supported, and
+//! nulls are left out when writing since TOML has none.
+
+use serde_json::{Map, Number, Value};
+
+use crate::lib::error::AppError;
+
+// ── writing ────────────────────────────────────────────────────────
+
+fn key(k: &str) -> String {
+    if !k.is_empty() && k.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+        k.to_string()
+    } else {
+        string(k)
+    }
+}
+
+fn string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04X}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn inline(v: &Value) -> String {
+    match v {
+        Value::Null => "\"\"".into(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => string(s),
+        Value::Array(items) => {
+            let items: Vec<String> = items.iter().filter(|v| !v.is_null()).map(inline).collect();
+            format!("[{}]", items.join(", "))
+        }
+        Value::Object(map) => {
+            let items: Vec<String> = map.iter()
+                .filter(|(_, v)| !v.is_null())
+                .map(|(k, v)| format!("{} = {}", key(k), inline(v)))
+                .collect();
+            if items.is_empty() { "{}".into() } else { format!("{{ {} }}", items.join(", ")) }
+        }
+    }
+}
+
+fn is_table_array(v: &Value) -> bool {
+    matches!(v, Value::Array(items) if !items.is_empty() && items.iter().all(Value::is_object))
+}
+
+fn write_table(out: &mut String, path: &[String], map: &Map<String, Value>) {
+    for (k, v) in map {
+        if !v.is_null() && !v.is_object() && !is_table_array(v) {
+            out.push_str(&format!("{} = {}\n", key(k), inline(v)));
+        }
+    }
+    for (k, v) in map {
+        let mut sub = path.to_vec();
+        sub.push(key(k));
+        match v {
+            Value::Object(inner) => {
+                // A header is only needed when the table has keys of its own
+                let has_values = inner.is_empty()
+                    || inner.values().any(|v| !v.is_null() && !v.is_object() && !is_table_array(v));
+                if has_values {
+                    out.push_str(&format!("\n[{}]\n", sub.join(".")));
+                }
+                write_table(out, &sub, inner);
+            }
+            Value::Array(items) if is_table_array(v) => {
+                for item in items {
+                    out.push_str(&format!("\n[[{}]]\n", sub.join(".")));
+                    if let Value::Object(inner) = item {
+                        write_table(out, &sub, inner);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Render a JSON object as a TOML document.
+pub fn to_toml(value: &Value) -> Result<String, AppError> {
+    let Value::Object(map) = value else {
+        return Err(AppError::Toml("the top level must be a table".into()));
+    };
+    let mut out = String::new();
+    write_table(&mut out, &[], map);
+    Ok(out.trim_start().to_string())
+}
+
+// ── reading ────────────────────────────────────────────────────────
+
+struct Parser<'a> {
+    chars: Vec<char>,
+    pos: usize,
+    text: &'a str,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: impl std::fmt::Display) -> AppError {
+        let line = self.text.chars().take(self.pos).filter(|&c| c == '\n').count() + 1;
+        AppError::Toml(format!("line {}: {}", line, message))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn starts_with(&self, s: &str) -> bool {
+        s.chars().enumerate().all(|(i, c)| self.chars.get(self.pos + i) == Some(&c))
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        let found = self.peek() == Some(c);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), AppError> {
+        if self.eat(c) { Ok(()) } else { Err(self.error(format!("expected `{}`", c))) }
+    }
+
+    /// Spaces and tabs within a line.
+    fn skip_blank(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t')) {
+            self.pos += 1;
+        }
+    }
+
+    fn skip_comment(&mut self) {
+        if self.peek() == Some('#') {
+            while !matches!(self.peek(), None | Some('\n')) {
+                self.pos += 1;
+            }
+        }
+    }
+
+    /// Whitespace, newlines and comments, as allowed inside arrays.
+    fn skip_all(&mut self) {
+        loop {
+            self.skip_blank();
+            self.skip_comment();
+            match self.peek() {
+                Some('\n' | '\r') => self.pos += 1,
+                _ => break,
+            }
+        }
+    }
+
+    /// The rest of the line must be empty or a comment.
+    fn end_of_line(&mut self) -> Result<(), AppError> {
+        self.skip_blank();
+        self.skip_comment();
+        self.eat('\r');
+        match self.peek() {
+            None => Ok(()),
+            Some('\n') => {
+                self.pos += 1;
+                Ok(())
+            }
+            Some(c) => Err(self.error(format!("unexpected `{}`", c))),
+        }
+    }
+
+    fn key_part(&mut self) -> Result<String, AppError> {
+        self.skip_blank();
+        match self.peek() {
+            Some('"') => self.basic_string(),
+            Some('\'') => self.literal_string(),
+            _ => {
+                let start = self.pos;
+                while matches!(self.peek(), Some(c) if c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+                    self.pos += 1;
+                }
+                if start == self.pos {
+                    return Err(self.error("expected a key"));
+                }
+                Ok(self.chars[start..self.pos].iter().collect())
+            }
+        }
+    }
+
+    /// A possibly dotted key such as `ui.theme."my key"`.
+    fn key(&mut self) -> Result<Vec<String>, AppError> {
+        let mut parts = vec![self.key_part()?];
+        loop {
+            self.skip_blank();
+            if !self.eat('.') {
+                return Ok(parts);
+            }
+            parts.push(self.key_part()?);
+        }
+    }
+
+    fn escape(&mut self) -> Result<char, AppError> {
+        let c = self.peek().ok_or_else(|| self.error("unterminated string"))?;
+        self.pos += 1;
+        Ok(match c {
+            'n' => '\n',
+            't' => '\t',
+            'r' => '\r',
+            'b' => '\u{8}',
+            'f' => '\u{c}',
+            'e' => '\u{1b}',
+            '"' => '"',
+            '\\' => '\\',
+            'u' | 'U' => {
+                let len = if c == 'u' { 4 } else { 8 };
+                let hex: String = self.chars.iter().skip(self.pos).take(len).collect();
+                self.pos += len;
+                u32::from_str_radix(&hex, 16).ok()
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| self.error(format!("bad unicode escape `{}`", hex)))?
+            }
+            c => return Err(self.error(format!("unknown escape `\\{}`", c))),
+        })
+    }
+
+    fn basic_string(&mut self) -> Result<String, AppError> {
+        if self.starts_with("\"\"\"") {
+            self.pos += 3;
+            // A newline right after the opening quotes is not part of the string
+            self.eat('\r');
+            self.eat('\n');
+            let mut s = String::new();
+            loop {
+                if self.starts_with("\"\"\"") {
+                    self.pos += 3;
+                    return Ok(s);
+                }
+                match self.peek() {
+                    None => return Err(self.error("unterminated string")),
+                    Some('\\') => {
+                        self.pos += 1;
+                        // Line-ending backslash trims up to the next text
+                        if matches!(self.peek(), Some(' ' | '\t' | '\r' | '\n')) {
+                            while matches!(self.peek(), Some(' ' | '\t' | '\r' | '\n')) {
+                                self.pos += 1;
+                            }
+                        } else {
+                            s.push(self.escape()?);
+                        }
+                    }
+                    Some(c) => {
+                        s.push(c);
+                        self.pos += 1;
+                    }
+                }
+            }
+        }
+        self.expect('"')?;
+        let mut s = String::new();
+        loop {
+            match self.peek() {
+                None | Some('\n') => return Err(self.error("unterminated string")),
+                Some('"') => {
+                    self.pos += 1;
+                    return Ok(s);
+                }
+                Some('\\') => {
+                    self.pos += 1;
+                    s.push(self.escape()?);
+                }
+                Some(c) => {
+                    s.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+
+    fn literal_string(&mut self) -> Result<String, AppError> {
+        let multiline = self.starts_with("'''");
+        let close = if multiline { "'''" } else { "'" };
+        self.pos += close.len();
+        if multiline {
+            self.eat('\r');
+            self.eat('\n');
+        }
+        let start = self.pos;
+        while !self.starts_with(close) {
+            match self.peek() {
+                None => return Err(self.error("unterminated string")),
+                Some('\n') if !multiline => return Err(self.error("unterminated string")),
+                _ => self.pos += 1,
+            }
+        }
+        let s = self.chars[start..self.pos].iter().collect();
+        self.pos += close.len();
+        Ok(s)
+    }
+
+    fn number_or_word(&mut self) -> Result<Value, AppError> {
+        let start = self.pos;
+        while matches!(self.peek(), Some(c) if c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '_' | '.')) {
+            self.pos += 1;
+        }
+        let word: String = self.chars[start..self.pos].iter().collect();
+        match word.as_str() {
+            "true" => return Ok(Value::Bool(true)),
+            "false" => return Ok(Value::Bool(false)),
+            "" => return Err(self.error("expected a value")),
+            _ => {}
+        }
+        if matches!(self.peek(), Some(':')) || (word.len() >= 10 && word.as_bytes().get(4) == Some(&b'-')) {
+            return Err(self.error("dates and times are not supported"));
+        }
+        let digits = word.replace('_', "");
+        let radix = [("0x", 16), ("0o", 8), ("0b", 2)].into_iter()
+            .find(|(prefix, _)| digits.starts_with(prefix));
+        if let Some((prefix, radix)) = radix {
+            return i64::from_str_radix(&digits[prefix.len()..], radix)
+                .map(Value::from)
+                .map_err(|_| self.error(format!("bad number `{}`", word)));
+        }
+        if let Ok(i) = digits.parse::<i64>() {
+            return Ok(Value::from(i));
+        }
+        match digits.trim_start_matches('+').parse::<f64>() {
+            Ok(f) => Number::from_f64(f)
+                .map(Value::Number)
+                .ok_or_else(|| self.error("inf and nan are not supported")),
+            Err(_) => Err(self.error(format!("bad value `{}`", word))),
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, AppError> {
+        self.skip_blank();
+        match self.peek() {
+            Some('"') => self.basic_string().map(Value::String),
+            Some('\'') => self.literal_string().map(Value::String),
+            Some('[') => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                loop {
+                    self.skip_all();
+                    if self.eat(']') {
+                        return Ok(Value::Array(items));
+                    }
+                    items.push(self.value()?);
+                    self.skip_all();
+                    if !self.eat(',') {
+                        self.skip_all();
+                        self.expect(']')?;
+                        return Ok(Value::Array(items));
+                    }
+                }
+            }
+            Some('{') => {
+                self.pos += 1;
+                let mut map = Map::new();
+                self.skip_blank();
+                if self.eat('}') {
+                    return Ok(Value::Object(map));
+                }
+                loop {
+                    let path = self.key()?;
+                    self.skip_blank();
+                    self.expect('=')?;
+                    let value = self.value()?;
+                    insert(&mut map, &path, value).map_err(|e| self.error(e))?;
+                    self.skip_blank();
+                    if self.eat('}') {
+                        return Ok(Value::Object(map));
+                    }
+                    self.expect(',')?;
+                }
+            }
+            _ => self.number_or_word(),
+        }
+    }
+}
+
+/// The table at `path` under `root`, created as needed; for an array of
+/// tables, its last element.
+fn table_at<'m>(root: &'m mut Map<String, Value>, path: &[String]) -> Result<&'m mut Map<String, Value>, String> {
+    let mut table = root;
+    for part in path {
+        let entry = table.entry(part.clone()).or_insert_with(|| Value::Object(Map::new()));
+        let next = match entry {
+            Value::Array(items) => items.last_mut(),
+            other => Some(other),
+        };
+        table = match next {
+            Some(Value::Object(map)) => map,
+            _ => return Err(format!("`{}` is not a table", part)),
+        };
+    }
+    Ok(table)
+}
+
+fn insert(table: &mut Map<String, Value>, path: &[String], value: Value) -> Result<(), String> {
+    let (last, parents) = path.split_last().ok_or("empty key")?;
+    let table = table_at(table, parents)?;
+    if table.contains_key(last) {
+        return Err(format!("`{}` is defined twice", last));
+    }
+    table.insert(last.clone(), value);
+    Ok(())
+}
+
+/// Parse a TOML document into a JSON object.
+pub fn from_toml(text: &str) -> Result<Value, AppError> {
+    let mut p = Parser { chars: text.chars().collect(), pos: 0, text };
+    let mut root = Map::new();
+    let mut current: Vec<String> = Vec::new();
+    loop {
+        p.skip_all();
+        let Some(c) = p.peek() else { break };
+        if c == '[' {
+            p.pos += 1;
+            let array = p.eat('[');
+            let path = p.key()?;
+            p.skip_blank();
+            p.expect(']')?;
+            if array {
+                p.expect(']')?;
+                let (last, parents) = path.split_last().ok_or_else(|| p.error("empty table name"))?;
+                let parent = table_at(&mut root, parents).map_err(|e| p.error(e))?;
+                let entry = parent.entry(last.clone()).or_insert_with(|| Value::Array(Vec::new()));
+                match entry {
+                    Value::Array(items) => items.push(Value::Object(Map::new())),
+                    _ => return Err(p.error(format!("`{}` is not an array of tables", last))),
+                }
+            } else {
+                table_at(&mut root, &path).map_err(|e| p.error(e))?;
+            }
+            current = path;
+        } else {
+            let path = p.key()?;
+            p.skip_blank();
+            p.expect('=')?;
+            let value = p.value()?;
+            let table = table_at(&mut root, &current).map_err(|e| p.error(e))?;
+            insert(table, &path, value).map_err(|e| p.error(e))?;
+        }
+        p.end_of_line()?;
+    }
+    Ok(Value::Object(root))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_round_trip() {
+        let value = json!({
+            "name": "tech \"growth\"\n",
+            "count": 3,
+            "ratio": 0.5,
+            "missing": null,
+            "symbols": ["AAPL", "BRK-B"],
+            "nested": {"flag": true, "deeper": {"x": -1}},
+            "profiles": {"^GSPC": {"sector": "Index"}},
+            "empty": {},
+            "rows": [{"a": 1}, {"a": 2, "sub": {"b": "c"}}],
+        });
+        let text = to_toml(&value).unwrap();
+        let mut expected = value.clone();
+        expected.as_object_mut().unwrap().remove("missing");
+        assert_eq!(from_toml(&text).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_reads_hand_written_toml() {
+        let text = r##"
+# watchlists
+title = 'literal \n'  # trailing comment
+big = 1_000
+hex = 0xff
+point.x = 1.5e2
+
+[ui]
+theme = { preset = "gruvbox", colors = { gain = "#00ff88" } }
+grid = [
+    2, 3,  # rows, cols
+]
+
+[[alerts]]
+symbol = "NVDA"
+[[alerts]]
+symbol = """
+MSFT"""
+"##;
+        assert_eq!(from_toml(text).unwrap(), json!({
+            "title": "literal \\n",
+            "big": 1000,
+            "hex": 255,
+            "point": {"x": 150.0},
+            "ui": {"theme": {"preset": "gruvbox", "colors": {"gain": "#00ff88"}}, "grid": [2, 3]},
+            "alerts": [{"symbol": "NVDA"}, {"symbol": "MSFT"}],
+        }));
+    }
+
+    #[test]
+    fn test_errors_name_the_line() {
+        let err = from_toml("a = 1\na = 2\n").unwrap_err().to_string();
+        assert!(err.contains("line 2"), "{}", err);
+        assert!(from_toml("when = 2024-01-01").is_err());
+    }
+}
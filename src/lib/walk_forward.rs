@@ -0,0 +1,295 @@
+//! Walk-forward analysis: the history is cut into rolling folds, each a
+//! stretch of bars to choose on followed by the stretch after it to test
+//! on. Whatever looked best on the first — a strategy's indicator lengths,
+//! or the forecast model — is judged only on the second, so the figures
+//! stitched together from the test stretches are all out of sample.
+
+use std::ops::Range;
+
+use serde::Serialize;
+
+use crate::lib::{
+    backtest::backtest,
+    config::StrategySettings,
+    forecast::{Forecaster, HORIZON},
+    optimize::{optimize, Sweep},
+    stock_data::StockData,
+    strategy::{run, Series, Strategy},
+    timezone::bar_date,
+};
+
+/// Bars chosen on and bars tested on, in a fold.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Fold {
+    pub train: Range<usize>,
+    pub test: Range<usize>,
+}
+
+/// Rolling folds over `len` bars: `train` bars, then the `test` after
+/// them, moving on by `test` bars each time until a full test no longer
+/// fits.
+pub fn folds(len: usize, train: usize, test: usize) -> Vec<Fold> {
+    if train == 0 || test == 0 {
+        return Vec::new();
+    }
+    (0..)
+        .map(|i| i * test)
+        .take_while(|start| start + train + test <= len)
+        .map(|start| Fold { train: start..start + train, test: start + train..start + train + test })
+        .collect()
+}
+
+impl Fold {
+    /// Trading days of the first and last test bar of `symbol`'s
+    /// `stock_data`, as `YYYY-MM-DD`.
+    pub fn test_dates(&self, symbol: &str, stock_data: &StockData) -> (String, String) {
+        let date = |bar: usize| {
+            stock_data.timestamps.get(bar)
+                .and_then(|&t| bar_date(symbol, t))
+                .map_or_else(|| "?".into(), |d| d.format("%Y-%m-%d").to_string())
+        };
+        (date(self.test.start), date(self.test.end - 1))
+    }
+}
+
+/// A strategy fold as `bstock optimize --walk-forward --format json`
+/// writes it.
+#[derive(Serialize)]
+pub struct StrategyFoldRow<'a> {
+    pub symbol: &'a str,
+    pub fold: usize,
+    pub test_from: String,
+    pub test_to: String,
+    pub parameters: String,
+    pub train_return: f64,
+    pub test_return: f64,
+    pub test_trades: usize,
+}
+
+/// A model fold as `bstock backtest --walk-forward --format json` writes
+/// it.
+#[derive(Serialize)]
+pub struct ModelFoldRow<'a> {
+    pub symbol: &'a str,
+    pub fold: usize,
+    pub test_from: String,
+    pub test_to: String,
+    pub picked: &'static str,
+    pub train_mape: f64,
+    pub test_mape: f64,
+    pub best: &'static str,
+}
+
+/// A strategy's lengths as chosen on a fold's train bars, and how they
+/// then did.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StrategyFold {
+    pub fold: Fold,
+    pub lengths: Vec<usize>,
+    /// Returns in percent over the train bars and over the test bars.
+    pub train_return: f64,
+    pub test_return: f64,
+    /// Trades opened in the test bars.
+    pub test_trades: usize,
+}
+
+/// A strategy walked forward.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StrategyWalk {
+    /// The indicators swept on each fold.
+    pub parameters: Vec<Series>,
+    pub folds: Vec<StrategyFold>,
+    /// Every fold's test return compounded, in percent.
+    pub test_return: f64,
+    /// How fast the test bars earned against the train bars, per bar: 1
+    /// keeps up, 0 or below earns nothing out of sample. `None` when the
+    /// train bars did not make money either.
+    pub efficiency: Option<f64>,
+}
+
+impl StrategyWalk {
+    /// The swept indicators at `fold`'s lengths, e.g. "SMA20 SMA120".
+    pub fn describe(&self, fold: &StrategyFold) -> String {
+        self.parameters.iter().zip(&fold.lengths)
+            .map(|(s, &n)| s.with_length(n).to_string())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Every fold, numbered from 1.
+    pub fn rows<'a>(&self, symbol: &'a str, stock_data: &StockData) -> Vec<StrategyFoldRow<'a>> {
+        self.folds.iter().enumerate()
+            .map(|(i, f)| {
+                let (test_from, test_to) = f.fold.test_dates(symbol, stock_data);
+                StrategyFoldRow {
+                    symbol,
+                    fold: i + 1,
+                    test_from,
+                    test_to,
+                    parameters: self.describe(f),
+                    train_return: f.train_return,
+                    test_return: f.test_return,
+                    test_trades: f.test_trades,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Log growth per bar of a `pct` return over `bars` bars.
+fn growth(pct: f64, bars: usize) -> f64 {
+    (1.0 + pct / 100.0).max(1e-9).ln() / bars.max(1) as f64
+}
+
+/// On each fold, sweep `strategy`'s lengths over the train bars and trade
+/// the best combination over the test bars. The test bars are traded with
+/// everything before them as history, so the indicators are warmed up and
+/// a position taken before the test carries into it.
+pub fn walk_strategy(
+    strategy: &Strategy,
+    stock_data: &StockData,
+    settings: &StrategySettings,
+    sweep: Sweep,
+    train: usize,
+    test: usize,
+) -> Result<StrategyWalk, String> {
+    let folds = folds(stock_data.len(), train, test);
+    if folds.is_empty() {
+        return Err(format!("{} bars are too few for {} to choose on and {} to test on", stock_data.len(), train, test));
+    }
+    let mut parameters = Vec::new();
+    let mut walked = Vec::new();
+    for fold in folds {
+        let chosen = optimize(strategy, &stock_data.slice(fold.train.clone()), settings, sweep, 0.0)?;
+        let Some(best) = chosen.trials.into_iter().next() else { continue };
+        parameters = chosen.parameters;
+        let result = run(&best.strategy, &stock_data.slice(0..fold.test.end), settings);
+        let before = result.equity[fold.test.start - 1];
+        walked.push(StrategyFold {
+            test_return: if before != 0.0 { (result.equity[fold.test.end - 1] / before - 1.0) * 100.0 } else { 0.0 },
+            test_trades: result.trades.iter().filter(|t| fold.test.contains(&t.entry_bar)).count(),
+            train_return: best.in_sample,
+            lengths: best.lengths,
+            fold,
+        });
+    }
+    let test_return = (walked.iter().map(|f| 1.0 + f.test_return / 100.0).product::<f64>() - 1.0) * 100.0;
+    let train_growth = walked.iter().map(|f| growth(f.train_return, train)).sum::<f64>() / walked.len().max(1) as f64;
+    let test_growth = growth(test_return, walked.len() * test);
+    Ok(StrategyWalk {
+        parameters,
+        efficiency: (train_growth > 0.0).then(|| test_growth / train_growth),
+        test_return,
+        folds: walked,
+    })
+}
+
+/// The forecast model that was most accurate on a fold's train bars, and
+/// how it did on the test bars after.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ModelFold {
+    pub fold: Fold,
+    pub picked: Forecaster,
+    /// Mean absolute percentage errors of the picked model.
+    pub train_mape: f64,
+    pub test_mape: f64,
+    /// The model that turned out most accurate on the test bars.
+    pub best: Forecaster,
+}
+
+/// Forecast models walked forward.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ModelWalk {
+    pub folds: Vec<ModelFold>,
+    /// Means over the folds of the picked models' errors.
+    pub train_mape: f64,
+    pub test_mape: f64,
+}
+
+impl ModelWalk {
+    /// Every fold, numbered from 1.
+    pub fn rows<'a>(&self, symbol: &'a str, stock_data: &StockData) -> Vec<ModelFoldRow<'a>> {
+        self.folds.iter().enumerate()
+            .map(|(i, f)| {
+                let (test_from, test_to) = f.fold.test_dates(symbol, stock_data);
+                ModelFoldRow {
+                    symbol,
+                    fold: i + 1,
+                    test_from,
+                    test_to,
+                    picked: f.picked.label(),
+                    train_mape: f.train_mape,
+                    test_mape: f.test_mape,
+                    best: f.best.label(),
+                }
+            })
+            .collect()
+    }
+}
+
+/// On each fold, backtest `models` over the train bars as
+/// [`crate::lib::backtest`] does, pick the most accurate, and score every
+/// model on forecasts made from the test bars. Those forecasts are fitted
+/// to the `window` bars before them, which may reach back into the train
+/// bars.
+pub fn walk_models(stock_data: &StockData, models: &[Forecaster], window: usize, train: usize, test: usize) -> Result<ModelWalk, String> {
+    let folds = folds(stock_data.len(), train, test);
+    if folds.is_empty() || train < window + HORIZON || test < HORIZON {
+        return Err(format!(
+            "{} bars are too few for {} to choose on and {} to test on with a {}-bar window",
+            stock_data.len(), train, test, window,
+        ));
+    }
+    let mut walked = Vec::new();
+    for fold in folds {
+        let train_bars = stock_data.slice(fold.train.clone());
+        // Forecasts from every test bar with a full horizon left in the test
+        let test_bars = stock_data.slice(fold.test.start - window..fold.test.end);
+        let mape = |sd: &StockData, model: Forecaster| backtest(sd, model, window).map(|b| b.overall.mape);
+        let picked = models.iter()
+            .filter_map(|&m| Some((m, mape(&train_bars, m)?)))
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+        let tested: Vec<(Forecaster, f64)> = models.iter().filter_map(|&m| Some((m, mape(&test_bars, m)?))).collect();
+        let (Some((picked, train_mape)), Some(&(best, _))) = (picked, tested.iter().min_by(|a, b| a.1.total_cmp(&b.1))) else { continue };
+        let Some(&(_, test_mape)) = tested.iter().find(|(m, _)| *m == picked) else { continue };
+        walked.push(ModelFold { fold, picked, train_mape, test_mape, best });
+    }
+    if walked.is_empty() {
+        return Err("no model could be fitted on any fold".into());
+    }
+    let mean = |f: fn(&ModelFold) -> f64| walked.iter().map(f).sum::<f64>() / walked.len() as f64;
+    Ok(ModelWalk { train_mape: mean(|f| f.train_mape), test_mape: mean(|f| f.test_mape), folds: walked })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lib::strategy::builtin;
+
+    #[test]
+    fn test_folds_roll_forward_by_the_test_length() {
+        assert_eq!(folds(100, 50, 20), [
+            Fold { train: 0..50, test: 50..70 },
+            Fold { train: 20..70, test: 70..90 },
+        ]);
+        assert!(folds(60, 50, 20).is_empty());
+
+        let mut sd = StockData::new();
+        for i in 0..400 {
+            let p = 100.0 + i as f64 * 0.2 + 10.0 * (i as f64 / 15.0).sin();
+            sd.add_point(i as i64 * 86_400, p, p, p, p, 0);
+        }
+        let sweep = Sweep { from: 10, to: 40, step: 10 };
+        let walk = walk_strategy(&builtin()[0], &sd, &StrategySettings::default(), sweep, 200, 50).unwrap();
+        assert_eq!(walk.folds.len(), 4);
+        assert_eq!(walk.folds[3].fold.test, 350..400);
+        let compounded = walk.folds.iter().map(|f| 1.0 + f.test_return / 100.0).product::<f64>();
+        assert!((walk.test_return - (compounded - 1.0) * 100.0).abs() < 1e-9);
+        assert!(walk.folds.iter().all(|f| f.lengths.len() == 2));
+
+        let models = walk_models(&sd, &[Forecaster::Linear, Forecaster::Kalman], 30, 200, 50).unwrap();
+        assert_eq!(models.folds.len(), 4);
+        assert!(models.test_mape > 0.0);
+        assert!(walk_models(&sd, &[Forecaster::Linear], 300, 200, 50).is_err());
+    }
+}
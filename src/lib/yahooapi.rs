@@ -1,41 +1,527 @@
-use crate::data::TimeRange;
+use std::sync::Mutex;
+
+use crate::data::{Interval, TimeRange};
+use crate::lib::config::SymbolProfile;
+use crate::lib::dividends::Dividend;
 use crate::lib::error::AppError;
-use crate::lib::stock_data::StockData;
-use yahoo_finance_api::YahooConnector;
+use crate::lib::insiders::{InsiderAction, InsiderTrade};
+use crate::lib::provider::{AnalystRatings, DataProvider, Fundamentals, NewsItem, ProviderFuture, Quote, SymbolMatch};
+use crate::lib::stock_data::{CorporateAction, StockData};
+use yahoo_finance_api::{time::OffsetDateTime, YResponse, YahooConnector};
 
 const USER_AGENT: &str =
     "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Safari/537.36";
 
-pub async fn fetch_stock_data(symbol: &str, time_range: TimeRange) -> Result<StockData, AppError> {
-    let provider = YahooConnector::builder()
-        .build_with_agent(USER_AGENT)
-        .map_err(|e| AppError::ApiError(format!("Connector: {e}")))?;
+async fn fetch_stock_data(symbol: &str, time_range: TimeRange) -> Result<StockData, AppError> {
+    let (range, _) = time_range.yahoo_params();
+    fetch_stock_data_range(symbol, range, time_range.interval()).await
+}
 
-    let (range, interval) = time_range.yahoo_params();
+fn connector() -> Result<YahooConnector, AppError> {
+    YahooConnector::builder()
+        .build_with_agent(USER_AGENT)
+        .map_err(|e| AppError::ApiError(format!("Connector: {e}")))
+}
 
-    let response = provider
-        .get_quote_range(symbol, interval, range)
+/// A chart request with pre- and after-market bars included, which the
+/// connector has no way to ask for. `span` is either a `range` or a
+/// `period1`/`period2` pair.
+async fn fetch_extended_chart(symbol: &str, interval: &str, span: &[(&str, String)]) -> Result<YResponse, AppError> {
+    let client = reqwest::Client::builder()
+        .user_agent(USER_AGENT)
+        .build()
+        .map_err(|e| AppError::ApiError(format!("Connector: {e}")))?;
+    let json: serde_json::Value = client
+        .get(format!("{CHART_URL}/{symbol}"))
+        .query(&[("interval", interval), ("includePrePost", "true"), ("events", "div|split")])
+        .query(span)
+        .send()
         .await
-        .map_err(|e| AppError::ApiError(format!(
-            "{symbol} (range={range} interval={interval}): {e}"
-        )))?;
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| AppError::from_request(format!("{symbol} (interval={interval})"), e))?
+        .json()
+        .await
+        .map_err(|e| AppError::ApiError(format!("Parse {symbol}: {e}")))?;
+    YResponse::from_json(json).map_err(|e| AppError::ApiError(format!("Parse {symbol}: {e}")))
+}
 
-    let mut stock_data = StockData::new();
+/// The bars in `response` from `since` on, with the close before the
+/// first bar when Yahoo gives it, and its splits and dividends. Yahoo
+/// scales bars and dividends before a split to the shares after it; that is
+/// undone here, so bars are as traded and [`StockData::adjusted`] applies
+/// the splits along with the dividends.
+fn response_bars(symbol: &str, response: &YResponse, since: i64) -> Result<StockData, AppError> {
     let quotes = response
         .quotes()
         .map_err(|e| AppError::ApiError(format!("Parse {symbol}: {e}")))?;
+    let splits: Vec<(i64, f64)> = response.splits().unwrap_or_default().into_iter()
+        .map(|s| (s.date as i64, s.numerator / s.denominator))
+        .filter(|(_, ratio)| ratio.is_finite() && *ratio > 0.0)
+        .collect();
+    // Shares after the splits following `t` for each share at `t`
+    let later_splits = |t: i64| splits.iter().filter(|(date, _)| *date > t).map(|(_, ratio)| ratio).product::<f64>();
+
+    let mut stock_data = StockData::new();
+    for bar in quotes.into_iter().filter(|bar| bar.timestamp as i64 >= since) {
+        let f = later_splits(bar.timestamp as i64);
+        stock_data.add_point(
+            bar.timestamp as i64, bar.open * f, bar.high * f, bar.low * f, bar.close * f,
+            (bar.volume as f64 / f).round() as u64,
+        );
+    }
+    for &(timestamp, ratio) in splits.iter().filter(|(date, _)| *date >= since) {
+        stock_data.add_action(CorporateAction::Split { timestamp, ratio });
+    }
+    for dividend in response.dividends().unwrap_or_default().into_iter().filter(|d| d.date as i64 >= since) {
+        let timestamp = dividend.date as i64;
+        stock_data.add_action(CorporateAction::Dividend { timestamp, amount: dividend.amount * later_splits(timestamp) });
+    }
+    stock_data.previous_close = response.metadata().ok().map(|m| m.chart_previous_close).filter(|&c| c > 0.0)
+        .map(|c| c * later_splits(stock_data.timestamps.first().copied().unwrap_or(since) - 1));
+    Ok(stock_data)
+}
 
-    if quotes.is_empty() {
+/// Fetch an explicit Yahoo `range` at `interval`, for requests that do not
+/// map onto one of the [`TimeRange`] presets. Intraday bars include the
+/// extended sessions.
+async fn fetch_stock_data_range(symbol: &str, range: &str, interval: Interval) -> Result<StockData, AppError> {
+    let response = if interval.is_intraday() {
+        fetch_extended_chart(symbol, interval.yahoo(), &[("range", range.to_string())]).await?
+    } else {
+        connector()?
+            .get_quote_range(symbol, interval.yahoo(), range)
+            .await
+            .map_err(|e| AppError::from_yahoo(format!("{symbol} (range={range} interval={})", interval.yahoo()), e))?
+    };
+    let stock_data = response_bars(symbol, &response, i64::MIN)?;
+    if stock_data.is_empty() {
         return Err(AppError::ApiError(format!(
-            "{symbol}: no data (range={range} interval={interval})"
+            "{symbol}: no data (range={range} interval={})", interval.yahoo()
         )));
     }
+    Ok(stock_data)
+}
 
-    for bar in quotes {
-        stock_data.add_point(
-            bar.timestamp as i64, bar.open, bar.high, bar.low, bar.close, bar.volume,
-        );
+/// Bars at `interval` from `since` (a Unix timestamp, inclusive) up to now,
+/// for topping up data already loaded. No bars is not an error here: there
+/// may be nothing new yet.
+async fn fetch_stock_data_since(symbol: &str, interval: Interval, since: i64) -> Result<StockData, AppError> {
+    let now = OffsetDateTime::now_utc();
+    let response = if interval.is_intraday() {
+        let span = [("period1", since.to_string()), ("period2", now.unix_timestamp().to_string())];
+        fetch_extended_chart(symbol, interval.yahoo(), &span).await?
+    } else {
+        let start = OffsetDateTime::from_unix_timestamp(since)
+            .map_err(|e| AppError::ApiError(format!("{symbol}: bad start time {since}: {e}")))?;
+        connector()?
+            .get_quote_history_interval(symbol, start, now, interval.yahoo())
+            .await
+            .map_err(|e| AppError::from_yahoo(format!("{symbol} (since={since} interval={})", interval.yahoo()), e))?
+    };
+    response_bars(symbol, &response, since)
+}
+
+/// The last of `symbol`'s recent daily bars.
+async fn fetch_quote(symbol: &str) -> Result<Quote, AppError> {
+    let data = fetch_stock_data_range(symbol, "5d", Interval::OneDay).await?;
+    let last = data.len() - 1;
+    Ok(Quote {
+        timestamp: data.timestamps[last],
+        price: data.closes[last],
+        volume: data.volumes[last],
+    })
+}
+
+/// Tickers matching a free-text `query` such as a company name.
+async fn search_symbols(query: &str) -> Result<Vec<SymbolMatch>, AppError> {
+    let result = connector()?
+        .search_ticker_opt(query)
+        .await
+        .map_err(|e| AppError::from_yahoo(format!("Search {query}"), e))?;
+    Ok(result.quotes.into_iter().map(|q| SymbolMatch {
+        name: q.long_name.or(q.short_name).unwrap_or_default(),
+        symbol: q.symbol,
+        exchange: q.exchange,
+        kind: q.type_display,
+    }).collect())
+}
+
+/// Five years of `symbol`'s dividends, read from the events of a monthly
+/// chart. Yahoo scales amounts to today's shares, which is what a yield
+/// on today's price wants.
+async fn fetch_dividends(symbol: &str) -> Result<Vec<Dividend>, AppError> {
+    let response = connector()?
+        .get_quote_range(symbol, "1mo", "5y")
+        .await
+        .map_err(|e| AppError::from_yahoo(format!("{symbol} dividends"), e))?;
+    Ok(response.dividends().unwrap_or_default().into_iter()
+        .map(|d| Dividend { ex_date: d.date as i64, amount: d.amount })
+        .collect())
+}
+
+const CHART_URL: &str = "https://query1.finance.yahoo.com/v8/finance/chart";
+const SEARCH_URL: &str = "https://query2.finance.yahoo.com/v1/finance/search";
+const COOKIE_URL: &str = "https://fc.yahoo.com";
+const CRUMB_URL: &str = "https://query1.finance.yahoo.com/v1/test/getcrumb";
+const SUMMARY_URL: &str = "https://query2.finance.yahoo.com/v10/finance/quoteSummary";
+
+/// Look up `symbol`'s sector and industry. The connector's search results
+/// drop those fields, so this reads the search endpoint directly.
+async fn fetch_profile(symbol: &str) -> Result<SymbolProfile, AppError> {
+    let client = reqwest::Client::builder()
+        .user_agent(USER_AGENT)
+        .build()
+        .map_err(|e| AppError::ApiError(format!("Connector: {e}")))?;
+    let json: serde_json::Value = client
+        .get(SEARCH_URL)
+        .query(&[("q", symbol), ("quotesCount", "5"), ("newsCount", "0")])
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| AppError::from_request(format!("{symbol} profile"), e))?
+        .json()
+        .await
+        .map_err(|e| AppError::ApiError(format!("Parse {symbol} profile: {e}")))?;
+    Ok(parse_profile(symbol, &json))
+}
+
+/// Recent headlines about `symbol`, which the search endpoint returns
+/// alongside its matches.
+async fn fetch_news(symbol: &str) -> Result<Vec<NewsItem>, AppError> {
+    let client = reqwest::Client::builder()
+        .user_agent(USER_AGENT)
+        .build()
+        .map_err(|e| AppError::ApiError(format!("Connector: {e}")))?;
+    let json: serde_json::Value = client
+        .get(SEARCH_URL)
+        .query(&[("q", symbol), ("quotesCount", "0"), ("newsCount", "20")])
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| AppError::from_request(format!("{symbol} news"), e))?
+        .json()
+        .await
+        .map_err(|e| AppError::ApiError(format!("Parse {symbol} news: {e}")))?;
+    Ok(parse_news(&json))
+}
+
+/// Yahoo Finance, through its public chart and search endpoints.
+pub struct YahooProvider;
+
+impl DataProvider for YahooProvider {
+    fn name(&self) -> &'static str {
+        "Yahoo Finance"
     }
 
-    Ok(stock_data)
+    fn fetch_history<'a>(&'a self, symbol: &'a str, time_range: TimeRange) -> ProviderFuture<'a, StockData> {
+        Box::pin(fetch_stock_data(symbol, time_range))
+    }
+
+    fn fetch_history_since<'a>(&'a self, symbol: &'a str, time_range: TimeRange, since: i64) -> ProviderFuture<'a, StockData> {
+        Box::pin(fetch_stock_data_since(symbol, time_range.interval(), since))
+    }
+
+    fn fetch_doubled_history<'a>(&'a self, symbol: &'a str, time_range: TimeRange) -> ProviderFuture<'a, StockData> {
+        Box::pin(async move {
+            let range = time_range.doubled_yahoo_range()
+                .ok_or_else(|| AppError::ApiError(format!("no history before {}", time_range.as_str())))?;
+            fetch_stock_data_range(symbol, range, time_range.interval()).await
+        })
+    }
+
+    fn fetch_quote<'a>(&'a self, symbol: &'a str) -> ProviderFuture<'a, Quote> {
+        Box::pin(fetch_quote(symbol))
+    }
+
+    fn search_symbols<'a>(&'a self, query: &'a str) -> ProviderFuture<'a, Vec<SymbolMatch>> {
+        Box::pin(search_symbols(query))
+    }
+
+    fn fetch_profile<'a>(&'a self, symbol: &'a str) -> ProviderFuture<'a, SymbolProfile> {
+        Box::pin(fetch_profile(symbol))
+    }
+
+    fn fetch_fundamentals<'a>(&'a self, symbol: &'a str) -> ProviderFuture<'a, Fundamentals> {
+        Box::pin(fetch_fundamentals(symbol))
+    }
+
+    fn fetch_ratings<'a>(&'a self, symbol: &'a str) -> ProviderFuture<'a, AnalystRatings> {
+        Box::pin(fetch_ratings(symbol))
+    }
+
+    fn fetch_insider_trades<'a>(&'a self, symbol: &'a str) -> ProviderFuture<'a, Vec<InsiderTrade>> {
+        Box::pin(fetch_insider_trades(symbol))
+    }
+
+    fn fetch_dividends<'a>(&'a self, symbol: &'a str) -> ProviderFuture<'a, Vec<Dividend>> {
+        Box::pin(fetch_dividends(symbol))
+    }
+
+    fn fetch_news<'a>(&'a self, symbol: &'a str) -> ProviderFuture<'a, Vec<NewsItem>> {
+        Box::pin(fetch_news(symbol))
+    }
+}
+
+/// Cookie and crumb quote summaries are requested with, kept for the run
+/// once Yahoo has issued them.
+static SESSION: Mutex<Option<(String, String)>> = Mutex::new(None);
+
+/// A new session cookie and the crumb issued for it.
+async fn new_session(client: &reqwest::Client) -> Result<(String, String), reqwest::Error> {
+    // The cookie comes with an error page, so the status is not checked
+    let session = client.get(COOKIE_URL).send().await?;
+    let cookie: Vec<&str> = session.headers().get_all(reqwest::header::SET_COOKIE).iter()
+        .filter_map(|v| v.to_str().ok()?.split(';').next())
+        .collect();
+    let cookie = cookie.join("; ");
+    let crumb = client
+        .get(CRUMB_URL)
+        .header(reqwest::header::COOKIE, &cookie)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())?
+        .text()
+        .await?;
+    Ok((cookie, crumb.trim().to_string()))
+}
+
+/// `modules` of `symbol`'s quote summary, which wants a session cookie
+/// and the crumb issued for it. `what` names the request in errors.
+async fn quote_summary(symbol: &str, modules: &str, what: &str) -> Result<serde_json::Value, AppError> {
+    let client = reqwest::Client::builder()
+        .user_agent(USER_AGENT)
+        .build()
+        .map_err(|e| AppError::ApiError(format!("Connector: {e}")))?;
+    let request_error = |e| AppError::from_request(format!("{symbol} {what}"), e);
+    let cached = SESSION.lock().ok().and_then(|s| s.clone());
+    let mut session = match cached.clone() {
+        Some(session) => session,
+        None => new_session(&client).await.map_err(request_error)?,
+    };
+    let request = |(cookie, crumb): &(String, String)| {
+        client
+            .get(format!("{SUMMARY_URL}/{symbol}"))
+            .header(reqwest::header::COOKIE, cookie)
+            .query(&[("modules", modules), ("crumb", crumb)])
+            .send()
+    };
+    let mut response = request(&session).await.map_err(request_error)?;
+    // A crumb from an expired session is refused; start a new one once
+    if response.status() == reqwest::StatusCode::UNAUTHORIZED && cached.is_some() {
+        session = new_session(&client).await.map_err(request_error)?;
+        response = request(&session).await.map_err(request_error)?;
+    }
+    let json = response
+        .error_for_status()
+        .map_err(request_error)?
+        .json()
+        .await
+        .map_err(|e| AppError::ApiError(format!("Parse {symbol} {what}: {e}")))?;
+    if let Ok(mut cache) = SESSION.lock() {
+        *cache = Some(session);
+    }
+    Ok(json)
+}
+
+/// Valuation figures from the quote summary.
+async fn fetch_fundamentals(symbol: &str) -> Result<Fundamentals, AppError> {
+    let json = quote_summary(symbol, "summaryDetail,defaultKeyStatistics,financialData", "fundamentals").await?;
+    Ok(parse_fundamentals(&json))
+}
+
+/// Insider transactions from the quote summary.
+async fn fetch_insider_trades(symbol: &str) -> Result<Vec<InsiderTrade>, AppError> {
+    let json = quote_summary(symbol, "insiderTransactions", "insider trades").await?;
+    Ok(parse_insider_trades(&json))
+}
+
+/// Recommendations and price targets from the quote summary.
+async fn fetch_ratings(symbol: &str) -> Result<AnalystRatings, AppError> {
+    let json = quote_summary(symbol, "recommendationTrend,financialData", "analyst ratings").await?;
+    Ok(parse_ratings(&json))
+}
+
+/// Figures from a quote summary, each under a module as `{"raw": …}`.
+/// Yahoo gives debt/equity as a percentage.
+fn parse_fundamentals(json: &serde_json::Value) -> Fundamentals {
+    let result = &json["quoteSummary"]["result"][0];
+    let raw = |module: &str, name: &str| result[module][name]["raw"].as_f64();
+    Fundamentals {
+        market_cap: raw("summaryDetail", "marketCap"),
+        pe: raw("summaryDetail", "trailingPE"),
+        forward_pe: raw("summaryDetail", "forwardPE").or_else(|| raw("defaultKeyStatistics", "forwardPE")),
+        eps: raw("defaultKeyStatistics", "trailingEps"),
+        profit_margin: raw("financialData", "profitMargins").or_else(|| raw("defaultKeyStatistics", "profitMargins")),
+        debt_to_equity: raw("financialData", "debtToEquity").map(|pct| pct / 100.0),
+        shares_short: raw("defaultKeyStatistics", "sharesShort"),
+        short_percent_of_float: raw("defaultKeyStatistics", "shortPercentOfFloat"),
+        short_ratio: raw("defaultKeyStatistics", "shortRatio"),
+    }
+}
+
+/// This month's recommendation tally (period `0m`) and the targets in
+/// the financial data.
+fn parse_ratings(json: &serde_json::Value) -> AnalystRatings {
+    let result = &json["quoteSummary"]["result"][0];
+    let trend = result["recommendationTrend"]["trend"].as_array()
+        .and_then(|t| t.iter().find(|p| p["period"] == "0m").or(t.first()));
+    let count = |name: &str| trend.and_then(|t| t[name].as_u64()).unwrap_or_default() as u32;
+    let target = |name: &str| result["financialData"][name]["raw"].as_f64();
+    AnalystRatings {
+        strong_buy: count("strongBuy"),
+        buy: count("buy"),
+        hold: count("hold"),
+        sell: count("sell"),
+        strong_sell: count("strongSell"),
+        target_mean: target("targetMeanPrice"),
+        target_high: target("targetHighPrice"),
+        target_low: target("targetLowPrice"),
+    }
+}
+
+/// Insider transactions, newest first. Yahoo words what each was, so
+/// sales and purchases are told from awards and exercises by that text.
+fn parse_insider_trades(json: &serde_json::Value) -> Vec<InsiderTrade> {
+    let rows = json["quoteSummary"]["result"][0]["insiderTransactions"]["transactions"].as_array();
+    let mut trades: Vec<InsiderTrade> = rows.into_iter().flatten()
+        .filter_map(|row| {
+            let text = |name: &str| row[name].as_str().unwrap_or_default().trim().to_string();
+            let description = text("transactionText");
+            let action = if description.starts_with("Sale") {
+                InsiderAction::Sell
+            } else if description.starts_with("Purchase") || description.starts_with("Buy") {
+                InsiderAction::Buy
+            } else {
+                InsiderAction::Other
+            };
+            Some(InsiderTrade {
+                insider: text("filerName"),
+                relation: text("filerRelation"),
+                date: row["startDate"]["raw"].as_i64()?,
+                action,
+                shares: row["shares"]["raw"].as_f64().unwrap_or_default().abs() as u64,
+                value: row["value"]["raw"].as_f64().filter(|&v| v > 0.0),
+            })
+        })
+        .collect();
+    trades.sort_by_key(|t| std::cmp::Reverse(t.date));
+    trades
+}
+
+/// The news of a search, newest first, skipping items with no title or
+/// link.
+fn parse_news(json: &serde_json::Value) -> Vec<NewsItem> {
+    let mut news: Vec<NewsItem> = json["news"].as_array().into_iter().flatten()
+        .filter_map(|item| {
+            let field = |name: &str| item[name].as_str().unwrap_or_default().trim().to_string();
+            let (title, link) = (field("title"), field("link"));
+            if title.is_empty() || link.is_empty() {
+                return None;
+            }
+            Some(NewsItem {
+                title,
+                publisher: field("publisher"),
+                link,
+                published: item["providerPublishTime"].as_i64().unwrap_or_default(),
+            })
+        })
+        .collect();
+    news.sort_by_key(|n| std::cmp::Reverse(n.published));
+    news
+}
+
+/// The profile of the search result whose symbol is exactly `symbol`; an
+/// empty one when there is no such result or it has no sector.
+fn parse_profile(symbol: &str, json: &serde_json::Value) -> SymbolProfile {
+    let quote = json["quotes"].as_array()
+        .and_then(|quotes| quotes.iter().find(|q| q["symbol"].as_str().is_some_and(|s| s.eq_ignore_ascii_case(symbol))));
+    let field = |name: &str| {
+        quote.and_then(|q| q[name].as_str()).filter(|s| !s.is_empty()).map(String::from)
+    };
+    SymbolProfile {
+        sector: field("sectorDisp").or_else(|| field("sector")),
+        industry: field("industryDisp").or_else(|| field("industry")),
+        kind: field("quoteType").map(|k| k.to_ascii_uppercase()),
+        // Search results carry no fund figures
+        ..SymbolProfile::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profile_comes_from_the_exact_symbol_match() {
+        let json = serde_json::json!({"quotes": [
+            {"symbol": "MSFTX", "sector": "Financial Services"},
+            {"symbol": "MSFT", "sector": "Technology", "sectorDisp": "Technology", "industry": "Software—Infrastructure", "quoteType": "EQUITY"},
+            {"symbol": "QQQ", "quoteType": "ETF"},
+        ]});
+        let profile = parse_profile("msft", &json);
+        assert_eq!(profile.sector.as_deref(), Some("Technology"));
+        assert_eq!(profile.industry.as_deref(), Some("Software—Infrastructure"));
+        assert_eq!(parse_profile("QQQ", &json).kind.as_deref(), Some("ETF"));
+        assert_eq!(parse_profile("SPY", &json), SymbolProfile::default());
+    }
+
+    #[test]
+    fn fundamentals_come_from_the_summary_modules() {
+        let json = serde_json::json!({"quoteSummary": {"result": [{
+            "summaryDetail": {"marketCap": {"raw": 3.1e12, "fmt": "3.1T"}, "trailingPE": {"raw": 35.2}, "forwardPE": {}},
+            "defaultKeyStatistics": {"forwardPE": {"raw": 29.8}, "trailingEps": {"raw": 11.8}, "shortPercentOfFloat": {"raw": 0.008}, "shortRatio": {"raw": 1.9}},
+            "financialData": {"profitMargins": {"raw": 0.36}, "debtToEquity": {"raw": 33.7}},
+        }]}});
+        let f = parse_fundamentals(&json);
+        assert_eq!((f.market_cap, f.pe, f.forward_pe, f.eps), (Some(3.1e12), Some(35.2), Some(29.8), Some(11.8)));
+        assert_eq!(f.profit_margin, Some(0.36));
+        assert!((f.debt_to_equity.unwrap() - 0.337).abs() < 1e-9);
+        assert_eq!((f.short_percent_of_float, f.short_ratio, f.shares_short), (Some(0.008), Some(1.9), None));
+        assert!(!f.heavily_shorted());
+        assert!(Fundamentals { short_ratio: Some(12.0), ..f }.heavily_shorted());
+        assert!(parse_fundamentals(&serde_json::json!({"quoteSummary": {"result": null}})).is_empty());
+    }
+
+    #[test]
+    fn ratings_come_from_this_months_trend() {
+        let json = serde_json::json!({"quoteSummary": {"result": [{
+            "recommendationTrend": {"trend": [
+                {"period": "-1m", "strongBuy": 1, "buy": 1, "hold": 9},
+                {"period": "0m", "strongBuy": 10, "buy": 20, "hold": 8, "sell": 2, "strongSell": 0},
+            ]},
+            "financialData": {"targetMeanPrice": {"raw": 240.0}, "targetHighPrice": {"raw": 300.0}},
+        }]}});
+        let r = parse_ratings(&json);
+        assert_eq!((r.strong_buy, r.buy, r.hold, r.sell, r.analysts()), (10, 20, 8, 2, 40));
+        assert_eq!(r.consensus(), Some("Buy"));
+        assert_eq!((r.target_high, r.target_low), (Some(300.0), None));
+        assert!((r.upside(200.0).unwrap() - 20.0).abs() < 1e-9);
+        assert!(parse_ratings(&serde_json::json!({})).is_empty());
+    }
+
+    #[test]
+    fn insider_trades_are_told_apart_by_their_text() {
+        let json = serde_json::json!({"quoteSummary": {"result": [{"insiderTransactions": {"transactions": [
+            {"filerName": "A", "transactionText": "Stock Award(Grant) at price 0.00 per share.", "startDate": {"raw": 100}, "shares": {"raw": 500}},
+            {"filerName": "B", "filerRelation": "Director", "transactionText": "Sale at price 180.00 per share.", "startDate": {"raw": 300}, "shares": {"raw": 1000}, "value": {"raw": 180000}},
+            {"filerName": "C", "transactionText": "Purchase at price 170.00 per share.", "startDate": {"raw": 200}, "shares": {"raw": 10}, "value": {"raw": 1700}},
+        ]}}]}});
+        let trades = parse_insider_trades(&json);
+        let actions: Vec<_> = trades.iter().map(|t| (t.insider.as_str(), t.action)).collect();
+        assert_eq!(actions, vec![("B", InsiderAction::Sell), ("C", InsiderAction::Buy), ("A", InsiderAction::Other)]);
+        assert_eq!((trades[0].shares, trades[0].value, trades[2].value), (1000, Some(180_000.0), None));
+    }
+
+    #[test]
+    fn news_is_newest_first_and_complete() {
+        let json = serde_json::json!({"news": [
+            {"title": "Older", "publisher": "Reuters", "link": "https://example.com/a", "providerPublishTime": 100},
+            {"title": "No link", "providerPublishTime": 300},
+            {"title": "Newer", "link": "https://example.com/b", "providerPublishTime": 200},
+        ]});
+        let news = parse_news(&json);
+        assert_eq!(news.iter().map(|n| n.title.as_str()).collect::<Vec<_>>(), vec!["Newer", "Older"]);
+        assert_eq!((news[1].publisher.as_str(), news[0].publisher.as_str()), ("Reuters", ""));
+        assert!(parse_news(&serde_json::json!({})).is_empty());
+    }
 }
@@ -1,25 +1,93 @@
 use anyhow::Result;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use crossterm::{
     event::{DisableMouseCapture, EnableMouseCapture},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::{prelude::*, backend::CrosstermBackend};
-use crate::lib::{config::StockConfig, persistence::PersistenceManager};
+use crate::lib::{
+    analysis::analyze_stock,
+    backtest::DEFAULT_WINDOW,
+    broker::{self, BrokerFormat},
+    config::{GraphicsMode, GridSize, StockConfig},
+    export,
+    forecast::Forecaster,
+    import,
+    optimize::Sweep,
+    persistence::{PersistenceManager, DEFAULT_PROFILE},
+    portfolio::LotMethod,
+    provider::{provider_for_watchlist, DataProvider, KEYED_PROVIDERS, PROVIDER_NAMES},
+    report::{write_report, Report, ReportEntry, ReportFormat},
+    snapshot::Snapshot,
+    stock_data::{to_parquet, StockData},
+    sync::{self, SyncState, SyncTarget},
+    timezone::DisplayZone,
+};
 use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::backtest::{run_backtest, BacktestFormat};
+use crate::data::TimeRange;
+use crate::optimize::{run_optimize, OptimizeFormat};
+use crate::rank::{run_rank, RankFormat};
+use crate::ui::theme::{Theme, PRESETS};
 
 mod app;
+mod backtest;
 mod data;
 mod event;
 mod lib {
+    pub mod accuracy;
+    pub mod alphavantage;
     pub mod analysis;
+    pub mod backtest;
+    pub mod broker;
+    pub mod browser;
+    pub mod calendar;
+    pub mod chart_image;
     pub mod config;
+    pub mod dividends;
+    pub mod edgar;
     pub mod error;
+    pub mod export;
+    pub mod finnhub;
+    pub mod forecast;
+    pub mod garch;
+    pub mod fuzzy;
+    pub mod import;
+    pub mod insiders;
+    pub mod instrument;
+    pub mod market;
+    #[cfg(feature = "neural")]
+    pub mod neural;
+    pub mod paper;
+    pub mod optimize;
+    pub mod portfolio;
     pub mod stock_data;
     pub mod yahooapi;
+    pub mod parquet;
     pub mod persistence;
+    pub mod provider;
+    pub mod report;
+    pub mod retry;
+    pub mod returns;
+    pub mod risk;
+    pub mod secrets;
+    pub mod sentiment;
+    pub mod signals;
+    pub mod snapshot;
+    pub mod stooq;
+    pub mod strategy;
+    pub mod stream;
+    pub mod sync;
+    pub mod timezone;
+    pub mod toml;
+    pub mod walk_forward;
 }
+mod optimize;
+mod rank;
 mod ui;
 
 use app::App;
@@ -27,18 +95,701 @@ use app::App;
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Stock symbols to analyze
     #[arg(short, long, num_args = 1..)]
     symbols: Option<Vec<String>>,
 
+    /// Directory for the config and snapshots instead of the OS config directory
+    #[arg(long, global = true, env = "BSTOCK_CONFIG_DIR", value_name = "DIR")]
+    config_dir: Option<PathBuf>,
+
+    /// Named profile with its own watchlists and settings, created if new
+    #[arg(long, global = true)]
+    profile: Option<String>,
+
+    /// Named watchlist to show, created empty if new (remembered as the active one)
+    #[arg(short, long, global = true)]
+    watchlist: Option<String>,
+
+    /// Data provider for the active watchlist: yahoo, alphavantage, finnhub or stooq (remembered)
+    #[arg(long, global = true, value_parser = clap::builder::PossibleValuesParser::new(PROVIDER_NAMES))]
+    provider: Option<String>,
+
+    /// Add the tickers in a text or CSV file to the watchlist, skipping duplicates and unknown symbols
+    #[arg(long, value_name = "FILE")]
+    import: Option<PathBuf>,
+
+    /// Show only cached price history and make no network requests
+    #[arg(long, global = true)]
+    offline: bool,
+
     /// Analysis period in days
     #[arg(short, long)]
     period: Option<i64>,
+
+    /// Screen-reader friendly text output instead of charts (remembered; `--plain false` to turn off)
+    #[arg(long, num_args = 0..=1, default_missing_value = "true")]
+    plain: Option<bool>,
+
+    /// Main-view tile grid as ROWSxCOLS, e.g. 3x3 (remembered)
+    #[arg(long, value_parser = parse_grid)]
+    grid: Option<GridSize>,
+
+    /// Detail chart backend: auto, braille, kitty or sixel (remembered)
+    #[arg(long, value_parser = parse_graphics)]
+    graphics: Option<GraphicsMode>,
+
+    /// Colour scheme: default, solarized, gruvbox or high-contrast (remembered)
+    #[arg(long, value_parser = clap::builder::PossibleValuesParser::new(PRESETS))]
+    theme: Option<String>,
+
+    /// Zone for dates and times: local, exchange, utc or an offset such as +05:30 (remembered)
+    #[arg(long, value_parser = parse_timezone)]
+    timezone: Option<DisplayZone>,
+
+    /// Re-fetch loaded quotes every MINUTES during US market hours; 0 turns it off (remembered)
+    #[arg(long, value_name = "MINUTES")]
+    refresh: Option<u64>,
+
+    /// Vim-style keys in the main view: hjkl, gg/G, count prefixes (remembered; `--vim false` to turn off)
+    #[arg(long, num_args = 0..=1, default_missing_value = "true")]
+    vim: Option<bool>,
+
+    /// Adjust prices for splits and dividends; `--adjusted false` shows them as traded (remembered)
+    #[arg(long, num_args = 0..=1, default_missing_value = "true")]
+    adjusted: Option<bool>,
+
+    /// Tilt forecasts by the sentiment of each symbol's news headlines (remembered; `--sentiment-forecast false` to turn off)
+    #[arg(long, num_args = 0..=1, default_missing_value = "true")]
+    sentiment_forecast: Option<bool>,
+
+    /// Stream live Yahoo prices into loaded quotes between refreshes (remembered; `--stream false` to turn off)
+    #[arg(long, num_args = 0..=1, default_missing_value = "true")]
+    stream: Option<bool>,
+}
+
+fn parse_range(label: &str) -> Result<TimeRange, String> {
+    TimeRange::from_label(label).ok_or_else(|| format!("unknown time range `{label}`"))
+}
+
+fn parse_timezone(name: &str) -> Result<DisplayZone, String> {
+    DisplayZone::parse(name).ok_or_else(|| format!("expected local, exchange, utc or an offset like +05:30, got `{name}`"))
+}
+
+fn parse_report_format(name: &str) -> Result<ReportFormat, String> {
+    ReportFormat::parse(name).ok_or_else(|| format!("expected markdown or html, got `{name}`"))
+}
+
+fn parse_forecaster(name: &str) -> Result<Forecaster, String> {
+    match Forecaster::parse(name) {
+        Some(model) if Forecaster::ALL.contains(&model) => Ok(model),
+        Some(_) => Err(format!("`{name}` needs bstock built with the `neural` feature")),
+        None => Err(format!("expected linear, holt-winters, kalman, arima, monte-carlo, ensemble or gru, got `{name}`")),
+    }
+}
+
+fn parse_lengths(spec: &str) -> Result<(usize, usize), String> {
+    spec.split_once('-')
+        .and_then(|(from, to)| Some((from.trim().parse().ok()?, to.trim().parse().ok()?)))
+        .filter(|&(from, to): &(usize, usize)| from >= 1 && from <= to)
+        .ok_or_else(|| format!("expected FROM-TO like 10-200, got `{spec}`"))
+}
+
+fn parse_split(spec: &str) -> Result<(usize, usize), String> {
+    spec.split_once(':')
+        .and_then(|(train, test)| Some((train.trim().parse().ok()?, test.trim().parse().ok()?)))
+        .filter(|&(train, test): &(usize, usize)| train > 0 && test > 0)
+        .ok_or_else(|| format!("expected TRAIN:TEST bars like 500:125, got `{spec}`"))
+}
+
+fn parse_grid(spec: &str) -> Result<GridSize, String> {
+    GridSize::parse(spec).ok_or_else(|| format!("expected ROWSxCOLS with 1-6 each, got `{spec}`"))
+}
+
+fn parse_graphics(name: &str) -> Result<GraphicsMode, String> {
+    GraphicsMode::parse(name).ok_or_else(|| format!("expected auto, braille, kitty or sixel, got `{name}`"))
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Open a saved analysis snapshot (read-only)
+    OpenSnapshot {
+        /// Path to the snapshot JSON file
+        path: PathBuf,
+    },
+    /// Fetch the watchlist headlessly and print it ranked by composite signal score
+    Rank {
+        /// Symbols to rank instead of the watchlist
+        #[arg(short, long, num_args = 1..)]
+        symbols: Option<Vec<String>>,
+        /// Time range to analyze (1D, 1W, 1M, 3M, 6M, YTD, 1Y, 2Y, 5Y, 10Y, All)
+        #[arg(short, long, default_value = "3M", value_parser = parse_range)]
+        range: TimeRange,
+        /// Output format
+        #[arg(short, long, value_enum, default_value_t = RankFormat::Table)]
+        format: RankFormat,
+    },
+    /// Walk the forecast models over the watchlist's history and print how far off they were
+    Backtest {
+        /// Symbols to test instead of the watchlist
+        #[arg(short, long, num_args = 1..)]
+        symbols: Option<Vec<String>>,
+        /// Time range to test over (1D, 1W, 1M, 3M, 6M, YTD, 1Y, 2Y, 5Y, 10Y, All)
+        #[arg(short, long, default_value = "2Y", value_parser = parse_range)]
+        range: TimeRange,
+        /// Model to test, repeatable (default: all of them)
+        #[arg(short, long, value_parser = parse_forecaster)]
+        model: Vec<Forecaster>,
+        /// Bars each forecast is fitted to
+        #[arg(long, default_value_t = DEFAULT_WINDOW)]
+        window: usize,
+        /// Pick the most accurate model on each TRAIN bars and score it on the TEST bars after, rolling forward
+        #[arg(long, value_name = "TRAIN:TEST", num_args = 0..=1, default_missing_value = "250:60", value_parser = parse_split)]
+        walk_forward: Option<(usize, usize)>,
+        /// Output format
+        #[arg(short, long, value_enum, default_value_t = BacktestFormat::Table)]
+        format: BacktestFormat,
+    },
+    /// Sweep a strategy's indicator lengths over the watchlist's history and print the best combinations
+    Optimize {
+        /// Symbols to sweep instead of the watchlist
+        #[arg(short, long, num_args = 1..)]
+        symbols: Option<Vec<String>>,
+        /// Strategy to sweep, built in or from `strategy.definitions`
+        #[arg(long, default_value = "Golden cross")]
+        strategy: String,
+        /// Time range to sweep over (1D, 1W, 1M, 3M, 6M, YTD, 1Y, 2Y, 5Y, 10Y, All)
+        #[arg(short, long, default_value = "5Y", value_parser = parse_range)]
+        range: TimeRange,
+        /// Lengths to try for each SMA, EMA and RSI
+        #[arg(long, default_value = "10-200", value_parser = parse_lengths)]
+        lengths: (usize, usize),
+        /// Step between lengths
+        #[arg(long, default_value_t = 10)]
+        step: usize,
+        /// Percent of the bars, at the end, left out of the ranking to check the results on
+        #[arg(long, default_value_t = 30.0)]
+        holdout: f64,
+        /// Choose the lengths on each TRAIN bars and trade them on the TEST bars after, rolling forward
+        #[arg(long, value_name = "TRAIN:TEST", num_args = 0..=1, default_missing_value = "500:125", value_parser = parse_split)]
+        walk_forward: Option<(usize, usize)>,
+        /// Combinations to print per symbol
+        #[arg(long, default_value_t = 10)]
+        top: usize,
+        /// Output format
+        #[arg(short, long, value_enum, default_value_t = OptimizeFormat::Table)]
+        format: OptimizeFormat,
+    },
+    /// Write symbols' bars as CSV or Parquet, to a file or stdout
+    Export {
+        #[arg(required = true)]
+        symbols: Vec<String>,
+        /// Time range to export (1D, 1W, 1M, 3M, 6M, YTD, 1Y, 2Y, 5Y, 10Y, All)
+        #[arg(short, long, default_value = "1Y", value_parser = parse_range)]
+        range: TimeRange,
+        /// CSV (one symbol, with indicators) or Parquet (any number, with a symbol column)
+        #[arg(short, long, value_enum, default_value_t = ExportFormat::Csv)]
+        format: ExportFormat,
+        /// File to write instead of stdout
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Load a symbol's prices from an OHLCV CSV (e.g. a broker or Stooq export) and add it to the watchlist
+    ImportPrices {
+        /// CSV with date, open, high, low, close and optional volume columns
+        file: PathBuf,
+        /// Symbol to file the prices under (default: the file name, e.g. `aapl.us.csv` is AAPL.US)
+        #[arg(long)]
+        symbol: Option<String>,
+    },
+    /// Add the buys and sells in a broker's CSV export to the portfolio, opening and closing lots
+    ImportTransactions {
+        /// CSV transaction history from Schwab, Fidelity, Robinhood, or with date, symbol, action, quantity, price and fees columns
+        file: PathBuf,
+        /// Export layout (default: recognised from the header)
+        #[arg(long, value_enum)]
+        format: Option<BrokerFormat>,
+        /// Close the oldest (fifo) or newest (lifo) lots first on sells from now on (remembered)
+        #[arg(long, value_enum)]
+        lots: Option<LotMethod>,
+    },
+    /// Write a daily summary of a watchlist as Markdown or HTML
+    Report {
+        /// Symbols to report on instead of the watchlist
+        #[arg(short, long, num_args = 1..)]
+        symbols: Option<Vec<String>>,
+        /// Time range to analyze (1D, 1W, 1M, 3M, 6M, YTD, 1Y, 2Y, 5Y, 10Y, All)
+        #[arg(short, long, default_value = "3M", value_parser = parse_range)]
+        range: TimeRange,
+        /// markdown or html (default: from the output file's extension, else markdown)
+        #[arg(short, long, value_parser = parse_report_format)]
+        format: Option<ReportFormat>,
+        /// File to write (default: the reports folder in the data directory)
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Share the watchlists and settings between machines via a folder, git repo or URL
+    Sync {
+        #[command(subcommand)]
+        action: SyncAction,
+    },
+    /// Show or convert the config file
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ConfigAction {
+    /// Print the path of the config file in use
+    Path,
+    /// Convert config.json to config.toml, keeping the JSON as config.json.bak
+    Migrate,
+    /// Save a data provider's API key in the OS credential store, read from stdin when KEY is left out
+    ApiKey {
+        // Not `provider`, which would merge with the global --provider
+        #[arg(value_name = "PROVIDER", value_parser = clap::builder::PossibleValuesParser::new(KEYED_PROVIDERS))]
+        name: String,
+        key: Option<String>,
+        /// Forget the key instead
+        #[arg(long, conflicts_with = "key")]
+        remove: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum SyncAction {
+    /// Set the sync target: a folder path, `git:<local clone>`, or an http(s) URL
+    Set { target: String },
+    /// Show which watchlists and settings differ from the target's
+    Status,
+    /// Upload every watchlist and the settings
+    Push {
+        /// Overwrite changes made on other machines
+        #[arg(long)]
+        force: bool,
+    },
+    /// Bring in the watchlists and settings changed on other machines
+    Pull {
+        /// Take the target's copy of anything changed on both sides
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+/// Runs a `sync` subcommand without starting the TUI.
+fn run_sync(pm: &PersistenceManager, action: SyncAction) -> Result<()> {
+    let rt = tokio::runtime::Runtime::new()?;
+    let describe = |state: SyncState| match state {
+        SyncState::InSync => "in sync",
+        SyncState::NoRemote => "nothing pushed yet",
+        SyncState::LocalAhead => "local changes not pushed",
+        SyncState::RemoteAhead => "remote changes not pulled",
+        SyncState::Conflict => "conflict: both sides changed since the last sync",
+    };
+    match action {
+        SyncAction::Set { target } => {
+            let target = SyncTarget::parse(&target);
+            sync::set_target(pm, target.clone())?;
+            println!("Sync target set to {}", target);
+        }
+        SyncAction::Status => {
+            let (target, states) = rt.block_on(sync::status(pm))?;
+            println!("{}:", target);
+            for (item, state) in states {
+                println!("  {}: {}", item, describe(state));
+            }
+        }
+        SyncAction::Push { force } => {
+            let states = rt.block_on(sync::push(pm, force))?;
+            for (item, state) in states.iter().filter(|(_, s)| *s != SyncState::InSync) {
+                println!("Pushed {} ({})", item, describe(*state));
+            }
+        }
+        SyncAction::Pull { force } => {
+            let states = rt.block_on(sync::pull(pm, force))?;
+            for (item, state) in states.iter().filter(|(_, s)| *s != SyncState::InSync) {
+                let verb = if *state == SyncState::LocalAhead && !force { "Kept" } else { "Pulled" };
+                println!("{} {} ({})", verb, item, describe(*state));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Output format of `bstock export`.
+#[derive(Clone, Copy, Debug, clap::ValueEnum)]
+enum ExportFormat {
+    Csv,
+    Parquet,
+}
+
+/// Fetches each symbol (or reads it from the history cache when offline)
+/// and writes them to `output`, or stdout for CSV.
+fn run_export(
+    pm: &PersistenceManager,
+    provider: &dyn DataProvider,
+    symbols: &[String],
+    range: TimeRange,
+    format: ExportFormat,
+    output: Option<PathBuf>,
+    offline: bool,
+) -> Result<()> {
+    let rt = tokio::runtime::Runtime::new()?;
+    let adjusted = pm.get_ui_settings().adjusted;
+    let mut series = Vec::new();
+    for symbol in symbols {
+        let symbol = symbol.to_uppercase();
+        let data = if offline {
+            pm.history_store().load(&symbol, range.as_str())
+                .map(|(data, _)| data)
+                .ok_or_else(|| anyhow::anyhow!("{} {} is not in the cache", symbol, range.as_str()))?
+        } else {
+            rt.block_on(provider.fetch_history(&symbol, range))?
+        };
+        let data = if adjusted { data.adjusted() } else { data };
+        series.push((symbol, data));
+    }
+
+    let bars: usize = series.iter().map(|(_, data)| data.len()).sum();
+    match (format, output) {
+        (ExportFormat::Csv, output) => {
+            let [(symbol, data)] = series.as_slice() else {
+                anyhow::bail!("CSV export takes one symbol; use --format parquet for several");
+            };
+            let analysis = analyze_stock(data, symbol, pm.get_forecast_settings().for_symbol(symbol));
+            let zone = pm.get_ui_settings().display_zone();
+            match output {
+                Some(path) => export::write_csv(&path, data, &analysis, zone)?,
+                None => {
+                    print!("{}", export::to_csv(data, &analysis, zone));
+                    return Ok(());
+                }
+            }
+        }
+        (ExportFormat::Parquet, Some(path)) => {
+            let tables: Vec<(&str, &StockData)> = series.iter().map(|(s, d)| (s.as_str(), d)).collect();
+            std::fs::write(&path, to_parquet(&tables))?;
+        }
+        (ExportFormat::Parquet, None) => anyhow::bail!("Parquet export needs an output file (-o)"),
+    }
+    eprintln!("Wrote {} bars of {} symbol{}", bars, series.len(), if series.len() == 1 { "" } else { "s" });
+    Ok(())
+}
+
+/// Fetches the active watchlist (or `symbols`) and writes its report.
+/// Symbols that fail to load are reported on stderr and left out.
+fn run_report(
+    pm: &PersistenceManager,
+    provider: &dyn DataProvider,
+    symbols: Option<Vec<String>>,
+    range: TimeRange,
+    format: Option<ReportFormat>,
+    output: Option<PathBuf>,
+    offline: bool,
+) -> Result<()> {
+    let title = pm.watchlist_names().0;
+    let symbols = match symbols {
+        Some(symbols) => symbols,
+        None => pm.get_stock_config()?.symbols,
+    };
+    let format = format
+        .or_else(|| output.as_ref().and_then(|p| p.extension()).and_then(|e| ReportFormat::parse(&e.to_string_lossy())))
+        .unwrap_or(ReportFormat::Markdown);
+
+    let rt = tokio::runtime::Runtime::new()?;
+    let adjusted = pm.get_ui_settings().adjusted;
+    let forecast = pm.get_forecast_settings();
+    let mut loaded = Vec::new();
+    for symbol in &symbols {
+        let data = if offline {
+            pm.history_store().load(symbol, range.as_str()).map(|(data, _)| data)
+                .ok_or_else(|| anyhow::anyhow!("not in the cache"))
+        } else {
+            rt.block_on(provider.fetch_history(symbol, range)).map_err(anyhow::Error::from)
+        };
+        match data {
+            Ok(data) if !data.is_empty() => {
+                let data = if adjusted { data.adjusted() } else { data };
+                let analysis = analyze_stock(&data, symbol, forecast.for_symbol(symbol));
+                loaded.push((data, analysis));
+            }
+            Ok(_) => eprintln!("{symbol}: no data"),
+            Err(e) => eprintln!("{symbol}: {e}"),
+        }
+    }
+    if loaded.is_empty() {
+        anyhow::bail!("no symbols could be loaded");
+    }
+
+    let generated = pm.get_ui_settings().display_zone().now();
+    let path = output.unwrap_or_else(|| {
+        pm.report_dir().join(format!("{}-{}.{}", title, generated.format("%Y%m%d"), format.extension()))
+    });
+    let report = Report {
+        title,
+        range: range.as_str().to_string(),
+        generated,
+        entries: loaded.iter().map(|(stock_data, analysis)| ReportEntry { stock_data, analysis }).collect(),
+    };
+    write_report(&path, format, &report)?;
+    eprintln!("Report of {} symbols written to {}", report.entries.len(), path.display());
+    Ok(())
+}
+
+/// Stores the bars in the CSV at `path` as `symbol`'s prices, which the TUI
+/// then shows instead of fetching, and adds the symbol to the watchlist.
+fn run_import_prices(pm: &PersistenceManager, path: &std::path::Path, symbol: Option<String>) -> Result<()> {
+    let symbol = match symbol {
+        Some(symbol) => symbol,
+        None => path.file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .ok_or_else(|| anyhow::anyhow!("give the symbol with --symbol"))?,
+    }
+    .trim()
+    .to_uppercase();
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("reading {}: {}", path.display(), e))?;
+    let data = StockData::from_csv(&text)?;
+    pm.save_imported_prices(&symbol, &data)?;
+
+    let mut config = pm.get_stock_config()?;
+    let added = !config.symbols.contains(&symbol);
+    if added {
+        config.symbols.push(symbol.clone());
+        pm.save_stock_config(&config)?;
+    }
+    let day = |t: i64| chrono::DateTime::from_timestamp(t, 0).map(|dt| dt.format("%Y-%m-%d").to_string()).unwrap_or_default();
+    eprintln!(
+        "Imported {} bars of {} ({} to {}){}",
+        data.len(), symbol, day(data.timestamps[0]), day(data.timestamps[data.len() - 1]),
+        if added { ", added to the watchlist" } else { "" },
+    );
+    Ok(())
+}
+
+/// Adds the transactions in the broker export at `path` to the portfolio.
+/// Ones imported before are skipped, so an export can be imported again
+/// as it grows; sells of more than is held are reported and left out.
+fn run_import_transactions(
+    pm: &PersistenceManager,
+    path: &std::path::Path,
+    format: Option<BrokerFormat>,
+    lots: Option<LotMethod>,
+) -> Result<()> {
+    let (format, transactions) = broker::read_transactions(path, format)
+        .map_err(|e| anyhow::anyhow!("reading {}: {}", path.display(), e))?;
+    let mut portfolio = pm.portfolio()?;
+    if let Some(lots) = lots {
+        portfolio.lot_method = lots;
+    }
+    let summary = portfolio.import(transactions);
+    for (t, reason) in &summary.rejected {
+        eprintln!("Skipped {} {} {} on {}: {}", t.side.label(), t.quantity, t.symbol, t.date.format("%Y-%m-%d"), reason);
+    }
+    pm.save_portfolio(&portfolio)?;
+    eprintln!(
+        "Imported {} {} transaction{} from {} ({} already imported); {} lots held, ${:.2} realized ({})",
+        summary.added, format.label(), if summary.added == 1 { "" } else { "s" }, path.display(),
+        summary.duplicates, portfolio.positions.len(), portfolio.realized_gain(), portfolio.lot_method.label(),
+    );
+    Ok(())
+}
+
+/// Merges the tickers in `path` into the active watchlist. New symbols are
+/// checked against the provider in parallel and only those that resolve are
+/// saved; a summary goes to stderr, where it shows after the TUI exits.
+fn run_import(pm: &PersistenceManager, provider: &Arc<dyn DataProvider>, path: &std::path::Path) -> Result<()> {
+    let symbols = import::read_symbol_file(path)
+        .map_err(|e| anyhow::anyhow!("reading {}: {}", path.display(), e))?;
+    let mut config = pm.get_stock_config()?;
+    let (new, duplicates): (Vec<String>, Vec<String>) = symbols.into_iter()
+        .partition(|s| !config.symbols.contains(s));
+
+    let rt = tokio::runtime::Runtime::new()?;
+    let checks = rt.block_on(async {
+        let handles: Vec<_> = new.into_iter()
+            .map(|s| {
+                let provider = provider.clone();
+                tokio::spawn(async move {
+                    let result = provider.validate_symbol(&s).await;
+                    (s, result)
+                })
+            })
+            .collect();
+        let mut checks = Vec::new();
+        for handle in handles {
+            checks.push(handle.await?);
+        }
+        Ok::<_, tokio::task::JoinError>(checks)
+    })?;
+
+    let mut added = 0;
+    for (symbol, result) in checks {
+        match result {
+            Ok(()) => {
+                config.symbols.push(symbol);
+                added += 1;
+            }
+            Err(e) => eprintln!("Skipped {}: {}", symbol, e),
+        }
+    }
+    pm.save_stock_config(&config)?;
+    eprintln!(
+        "Imported {} symbol{} from {} ({} already in the watchlist)",
+        added, if added == 1 { "" } else { "s" }, path.display(), duplicates.len(),
+    );
+    Ok(())
 }
 
 fn main() -> Result<()> {
     let args = Args::parse();
 
+    let pm = PersistenceManager::new(args.config_dir.clone())?
+        .open_profile(args.profile.as_deref().unwrap_or(DEFAULT_PROFILE))?;
+    // Saving loads the whole config first, so a file that does not parse
+    // is reported here rather than saved over with defaults
+    pm.load_config().map_err(|e| anyhow::anyhow!("{}; fix {} and try again", e, pm.config_path().display()))?;
+    let mut ui = pm.get_ui_settings();
+    if args.plain.is_some() || args.grid.is_some() || args.graphics.is_some() || args.theme.is_some() || args.vim.is_some()
+        || args.timezone.is_some() || args.adjusted.is_some() || args.sentiment_forecast.is_some()
+    {
+        if let Some(plain) = args.plain {
+            ui.plain = plain;
+        }
+        if let Some(grid) = args.grid {
+            ui.grid = grid;
+        }
+        if let Some(graphics) = args.graphics {
+            ui.graphics = graphics;
+        }
+        if let Some(theme) = args.theme {
+            ui.theme.preset = theme;
+        }
+        if let Some(vim) = args.vim {
+            ui.vim = vim;
+        }
+        if let Some(zone) = args.timezone {
+            ui.timezone = zone.name();
+        }
+        if let Some(adjusted) = args.adjusted {
+            ui.adjusted = adjusted;
+        }
+        if let Some(sentiment_forecast) = args.sentiment_forecast {
+            ui.sentiment_forecast = sentiment_forecast;
+        }
+        pm.save_ui_settings(&ui)?;
+    }
+    if args.refresh.is_some() || args.stream.is_some() {
+        let mut refresh = pm.get_refresh_settings();
+        if let Some(minutes) = args.refresh {
+            refresh.interval_minutes = minutes;
+        }
+        if let Some(stream) = args.stream {
+            refresh.stream = stream;
+        }
+        pm.save_refresh_settings(refresh)?;
+    }
+    if let Some(name) = &args.watchlist {
+        pm.switch_watchlist(name)?;
+    }
+    let mut providers = pm.get_provider_settings();
+    let (active_watchlist, _) = pm.watchlist_names();
+    if let Some(name) = args.provider {
+        providers.watchlists.insert(active_watchlist.clone(), name);
+        pm.save_provider_settings(&providers)?;
+    }
+    // Set up on first use, so `config api-key` works while a key is missing
+    let provider = || provider_for_watchlist(&providers, &active_watchlist);
+    if let Some(path) = &args.import {
+        run_import(&pm, &provider()?, path)?;
+    }
+
+    // Load before touching the terminal so a bad file reports plainly
+    let snapshot = match args.command {
+        Some(Command::OpenSnapshot { path }) => Some(Snapshot::load(&path)?),
+        Some(Command::Sync { action }) => return run_sync(&pm, action),
+        Some(Command::Export { symbols, range, format, output }) => {
+            return run_export(&pm, provider()?.as_ref(), &symbols, range, format, output, args.offline);
+        }
+        Some(Command::ImportPrices { file, symbol }) => return run_import_prices(&pm, &file, symbol),
+        Some(Command::ImportTransactions { file, format, lots }) => {
+            return run_import_transactions(&pm, &file, format, lots);
+        }
+        Some(Command::Report { symbols, range, format, output }) => {
+            return run_report(&pm, provider()?.as_ref(), symbols, range, format, output, args.offline);
+        }
+        Some(Command::Config { action }) => {
+            match action {
+                ConfigAction::Path => println!("{}", pm.config_path().display()),
+                ConfigAction::Migrate => println!("Config written to {}", pm.migrate_to_toml()?.display()),
+                ConfigAction::ApiKey { name, remove: true, .. } => {
+                    pm.remove_api_key(&name)?;
+                    println!("Removed the {} key", name);
+                }
+                ConfigAction::ApiKey { name, key, .. } => {
+                    let key = match key {
+                        Some(key) => key,
+                        None => {
+                            eprint!("{} API key: ", name);
+                            let mut line = String::new();
+                            io::stdin().read_line(&mut line)?;
+                            line.trim().to_string()
+                        }
+                    };
+                    anyhow::ensure!(!key.is_empty(), "no key given");
+                    match pm.save_api_key(&name, &key)? {
+                        None => println!("Saved the {} key in the OS credential store", name),
+                        Some(e) => println!("{}; saved the {} key in {} instead", e, name, pm.config_path().display()),
+                    }
+                }
+            }
+            return Ok(());
+        }
+        Some(Command::Rank { symbols, range, format }) => {
+            // Rank the given symbols without saving them as the watchlist
+            let symbols = match symbols {
+                Some(symbols) => symbols,
+                None => pm.get_stock_config()?.symbols,
+            };
+            return run_rank(provider()?, &symbols, range, format, ui.adjusted, &pm.get_forecast_settings());
+        }
+        Some(Command::Backtest { symbols, range, model, window, walk_forward, format }) => {
+            let symbols = match symbols {
+                Some(symbols) => symbols,
+                None => pm.get_stock_config()?.symbols,
+            };
+            return run_backtest(provider()?, &symbols, range, &model, window, walk_forward, format, ui.adjusted);
+        }
+        Some(Command::Optimize { symbols, strategy, range, lengths: (from, to), step, holdout, walk_forward, top, format }) => {
+            let symbols = match symbols {
+                Some(symbols) => symbols,
+                None => pm.get_stock_config()?.symbols,
+            };
+            let settings = pm.get_strategy_settings();
+            let mut strategies = lib::strategy::builtin();
+            strategies.extend(
+                lib::strategy::parse_definitions(&settings.definitions)
+                    .map_err(|e| anyhow::anyhow!("{} in the strategy.definitions config", e))?,
+            );
+            let Some(chosen) = strategies.iter().find(|s| s.name.eq_ignore_ascii_case(&strategy)) else {
+                let names: Vec<&str> = strategies.iter().map(|s| s.name.as_str()).collect();
+                anyhow::bail!("unknown strategy `{}`; expected one of: {}", strategy, names.join(", "));
+            };
+            let sweep = Sweep { from, to, step };
+            return run_optimize(provider()?, &symbols, range, chosen, sweep, holdout, walk_forward, top, format, &settings, ui.adjusted);
+        }
+        None => None,
+    };
+    Theme::from_settings(&ui.theme).map_err(|e| anyhow::anyhow!("{} in the ui.theme config", e))?;
+    app::keymap::parse_remaps(&ui.keys).map_err(|e| anyhow::anyhow!("{} in the ui.keys config", e))?;
+    lib::strategy::parse_definitions(&pm.get_strategy_settings().definitions)
+        .map_err(|e| anyhow::anyhow!("{} in the strategy.definitions config", e))?;
+
     // setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -46,13 +797,34 @@ fn main() -> Result<()> {
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    let mut app = App::new()?;
+    let res = match snapshot {
+        Some(snapshot) => App::from_snapshot(pm, snapshot).and_then(|mut app| app.view_snapshot(&mut terminal)),
+        None => run_watchlist(&mut terminal, pm, args.symbols, args.period, args.offline),
+    };
+
+    // restore terminal
+    disable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        LeaveAlternateScreen,
+        DisableMouseCapture
+    )?;
+    terminal.show_cursor()?;
 
-    // Initialize persistence manager
-    let persistence_manager = PersistenceManager::new()?;
+    res
+}
 
-    let config = if let Some(symbols) = args.symbols {
-        let period = args.period.unwrap_or(90);
+fn run_watchlist(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    persistence_manager: PersistenceManager,
+    symbols: Option<Vec<String>>,
+    period: Option<i64>,
+    offline: bool,
+) -> Result<()> {
+    let config = if let Some(symbols) = symbols {
+        let period = period.unwrap_or_else(|| {
+            persistence_manager.get_stock_config().map_or(90, |c| c.analysis_period_days)
+        });
         let stock_config = StockConfig {
             symbols,
             analysis_period_days: period,
@@ -62,22 +834,19 @@ fn main() -> Result<()> {
         stock_config
     } else {
         // Load config from persistent storage
-        persistence_manager.get_stock_config()?
+        let mut stock_config = persistence_manager.get_stock_config()?;
+        if let Some(period) = period {
+            stock_config.analysis_period_days = period;
+            persistence_manager.save_stock_config(&stock_config)?;
+        }
+        stock_config
     };
 
+    let mut app = App::new(persistence_manager)?;
+    app.offline = offline;
+
     // Use a fixed config file path that represents the persistent storage
     let config_file_path = "persistent_config"; // Placeholder string, won't be used for file operations
 
-    let res = app.run(&mut terminal, &config, config_file_path);
-
-    // restore terminal
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
-
-    res
+    app.run(terminal, &config, config_file_path)
 }
\ No newline at end of file
@@ -0,0 +1,224 @@
+use anyhow::Result;
+use std::sync::Arc;
+
+use crate::data::TimeRange;
+use crate::lib::{
+    config::StrategySettings,
+    optimize::{optimize, Optimization, OptimizeRow, Sweep},
+    provider::DataProvider,
+    stock_data::StockData,
+    strategy::Strategy,
+    walk_forward::{walk_strategy, StrategyFoldRow, StrategyWalk},
+};
+
+/// Output format of `bstock optimize`.
+#[derive(Clone, Copy, Debug, clap::ValueEnum)]
+pub enum OptimizeFormat {
+    Table,
+    Json,
+    Csv,
+}
+
+/// Fetch every symbol concurrently, split- and dividend-`adjusted` or as
+/// traded. Symbols that fail to load are reported on stderr and left out.
+pub async fn fetch_symbols(
+    provider: Arc<dyn DataProvider>,
+    symbols: &[String],
+    time_range: TimeRange,
+    adjusted: bool,
+) -> Vec<(String, StockData)> {
+    let mut tasks = tokio::task::JoinSet::new();
+    for symbol in symbols {
+        let symbol = symbol.clone();
+        let provider = provider.clone();
+        tasks.spawn(async move {
+            let data = provider.fetch_history(&symbol, time_range).await;
+            (symbol, data)
+        });
+    }
+
+    let mut results = Vec::new();
+    while let Some(joined) = tasks.join_next().await {
+        let Ok((symbol, data)) = joined else { continue };
+        match data {
+            Ok(sd) if !sd.is_empty() => results.push((symbol, if adjusted { sd.adjusted() } else { sd })),
+            Ok(_) => eprintln!("{symbol}: no data"),
+            Err(e) => eprintln!("{symbol}: {e}"),
+        }
+    }
+    results.sort_by(|a, b| a.0.cmp(&b.0));
+    results
+}
+
+fn print_table(symbol: &str, bars: usize, optimization: &Optimization, top: usize) {
+    let first = &optimization.trials[0];
+    println!(
+        "{symbol}: {} over {} combinations of {}, ranked on the first {} of {bars} bars",
+        first.strategy.name,
+        optimization.trials.len(),
+        optimization.parameters.iter().map(|s| s.to_string()).collect::<Vec<_>>().join(" "),
+        optimization.split,
+    );
+    println!(
+        "{:<16} {:>9} {:>9} {:>7} {:>6} {:>9}  Warnings",
+        "Lengths", "Sample", "Held out", "Trades", "Win", "Drawdown",
+    );
+    for trial in optimization.trials.iter().take(top) {
+        println!(
+            "{:<16} {:>8.2}% {:>9} {:>7} {:>6} {:>8.2}%  {}",
+            optimization.describe(trial),
+            trial.in_sample,
+            trial.out_of_sample.map_or("--".into(), |r| format!("{:.2}%", r)),
+            trial.trades,
+            trial.win_rate.map_or("--".into(), |w| format!("{:.0}%", w * 100.0)),
+            trial.max_drawdown,
+            trial.warnings.iter().map(|w| w.label()).collect::<Vec<_>>().join(", "),
+        );
+    }
+    println!();
+}
+
+fn print_walk_table(symbol: &str, strategy: &Strategy, walk: &StrategyWalk, rows: &[StrategyFoldRow]) {
+    let (train, test) = walk.folds.first().map_or((0, 0), |f| (f.fold.train.len(), f.fold.test.len()));
+    println!(
+        "{symbol}: {} walked forward over {} folds, lengths chosen on {train} bars and traded on the next {test}",
+        strategy.name,
+        walk.folds.len(),
+    );
+    println!("{:>4} {:<23} {:<16} {:>9} {:>9} {:>7}", "Fold", "Test bars", "Lengths", "Choosing", "Testing", "Trades");
+    for r in rows {
+        println!(
+            "{:>4} {:<23} {:<16} {:>8.2}% {:>8.2}% {:>7}",
+            r.fold, format!("{}–{}", r.test_from, r.test_to), r.parameters, r.train_return, r.test_return, r.test_trades,
+        );
+    }
+    println!(
+        "Out of sample: {:+.2}% compounded, efficiency {}",
+        walk.test_return,
+        walk.efficiency.map_or("-- (no gain in sample)".into(), |e| format!("{:.2}", e)),
+    );
+    println!();
+}
+
+fn print_walk_csv(rows: &[StrategyFoldRow]) {
+    println!("symbol,fold,test_from,test_to,parameters,train_return,test_return,test_trades");
+    for r in rows {
+        println!(
+            "{},{},{},{},{},{:.4},{:.4},{}",
+            r.symbol, r.fold, r.test_from, r.test_to, r.parameters, r.train_return, r.test_return, r.test_trades,
+        );
+    }
+}
+
+/// Runs `bstock optimize --walk-forward`: on each fold the lengths are
+/// chosen on `train` bars and traded on the `test` bars after.
+fn run_walk_forward(
+    data: &[(String, StockData)],
+    strategy: &Strategy,
+    sweep: Sweep,
+    (train, test): (usize, usize),
+    format: OptimizeFormat,
+    settings: &StrategySettings,
+) -> Result<()> {
+    let mut walks = Vec::new();
+    for (symbol, sd) in data {
+        match walk_strategy(strategy, sd, settings, sweep, train, test) {
+            Ok(walk) => walks.push((symbol, sd, walk)),
+            Err(e) => eprintln!("{symbol}: {e}"),
+        }
+    }
+    match format {
+        OptimizeFormat::Table => {
+            for (symbol, sd, walk) in &walks {
+                print_walk_table(symbol, strategy, walk, &walk.rows(symbol, sd));
+            }
+        }
+        OptimizeFormat::Json => {
+            let rows: Vec<StrategyFoldRow> = walks.iter().flat_map(|(symbol, sd, walk)| walk.rows(symbol, sd)).collect();
+            println!("{}", serde_json::to_string_pretty(&rows)?);
+        }
+        OptimizeFormat::Csv => {
+            let rows: Vec<StrategyFoldRow> = walks.iter().flat_map(|(symbol, sd, walk)| walk.rows(symbol, sd)).collect();
+            print_walk_csv(&rows);
+        }
+    }
+    if walks.is_empty() && !data.is_empty() {
+        anyhow::bail!("no symbols could be walked forward");
+    }
+    Ok(())
+}
+
+fn print_csv(rows: &[OptimizeRow]) {
+    println!("symbol,strategy,parameters,in_sample_return,out_of_sample_return,trades,win_rate,max_drawdown,warnings");
+    for r in rows {
+        println!(
+            "{},{},{},{:.4},{},{},{},{:.4},{}",
+            r.symbol,
+            r.strategy,
+            r.parameters,
+            r.in_sample_return,
+            r.out_of_sample_return.map_or(String::new(), |v| format!("{:.4}", v)),
+            r.trades,
+            r.win_rate.map_or(String::new(), |v| format!("{:.4}", v)),
+            r.max_drawdown,
+            r.warnings.join(";"),
+        );
+    }
+}
+
+/// Runs `bstock optimize` without starting the TUI, printing the `top`
+/// combinations per symbol, or with `walk_forward` the lengths chosen on
+/// each fold and how they did after.
+#[allow(clippy::too_many_arguments)]
+pub fn run_optimize(
+    provider: Arc<dyn DataProvider>,
+    symbols: &[String],
+    time_range: TimeRange,
+    strategy: &Strategy,
+    sweep: Sweep,
+    holdout_pct: f64,
+    walk_forward: Option<(usize, usize)>,
+    top: usize,
+    format: OptimizeFormat,
+    settings: &StrategySettings,
+    adjusted: bool,
+) -> Result<()> {
+    if !(0.0..=90.0).contains(&holdout_pct) {
+        anyhow::bail!("the holdout must be between 0 and 90 percent");
+    }
+    let rt = tokio::runtime::Runtime::new()?;
+    let data = rt.block_on(fetch_symbols(provider, symbols, time_range, adjusted));
+    if let Some(split) = walk_forward {
+        return run_walk_forward(&data, strategy, sweep, split, format, settings);
+    }
+    let mut results = Vec::new();
+    for (symbol, sd) in &data {
+        match optimize(strategy, sd, settings, sweep, holdout_pct) {
+            Ok(optimization) if !optimization.trials.is_empty() => results.push((symbol, sd.len(), optimization)),
+            Ok(_) => eprintln!("{symbol}: no combinations to try"),
+            Err(e) => anyhow::bail!(e),
+        }
+    }
+    let rows: Vec<OptimizeRow> = results.iter()
+        .flat_map(|(symbol, _, o)| o.trials.iter().take(top).map(|t| o.row(symbol, t)))
+        .collect();
+    match format {
+        OptimizeFormat::Table => {
+            for (symbol, bars, optimization) in &results {
+                print_table(symbol, *bars, optimization, top);
+            }
+            if let Some((_, _, optimization)) = results.first() {
+                println!(
+                    "The best of {} tries is flattered by luck: prefer lengths whose neighbours do nearly as well and that hold up on the held-out bars.",
+                    optimization.trials.len(),
+                );
+            }
+        }
+        OptimizeFormat::Json => println!("{}", serde_json::to_string_pretty(&rows)?),
+        OptimizeFormat::Csv => print_csv(&rows),
+    }
+    if results.is_empty() && !symbols.is_empty() {
+        anyhow::bail!("no symbols could be optimized");
+    }
+    Ok(())
+}
@@ -0,0 +1,135 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::sync::Arc;
+
+use crate::data::{calculate_volatility, TimeRange};
+use crate::lib::{
+    analysis::analyze_stock,
+    config::ForecastSettings,
+    provider::DataProvider,
+    signals::{composite_score, detect_signals},
+};
+
+/// Output format of `bstock rank`.
+#[derive(Clone, Copy, Debug, clap::ValueEnum)]
+pub enum RankFormat {
+    Table,
+    Json,
+    Csv,
+}
+
+#[derive(Serialize)]
+pub struct RankRow {
+    pub rank: usize,
+    pub symbol: String,
+    pub score: i32,
+    pub price: f64,
+    pub change_pct: Option<f64>,
+    pub volatility_pct: f64,
+    pub signals: Vec<String>,
+}
+
+/// Fetch every symbol concurrently, score it and sort best first, on
+/// split- and dividend-`adjusted` prices or as traded, each forecast with
+/// its model from `forecast`. Symbols that fail to load are reported on
+/// stderr and left out.
+async fn rank_symbols(
+    provider: Arc<dyn DataProvider>,
+    symbols: &[String],
+    time_range: TimeRange,
+    adjusted: bool,
+    forecast: &ForecastSettings,
+) -> Vec<RankRow> {
+    let mut tasks = tokio::task::JoinSet::new();
+    for symbol in symbols {
+        let symbol = symbol.clone();
+        let provider = provider.clone();
+        tasks.spawn(async move {
+            let data = provider.fetch_history(&symbol, time_range).await;
+            (symbol, data)
+        });
+    }
+
+    let mut rows = Vec::new();
+    while let Some(joined) = tasks.join_next().await {
+        let Ok((symbol, data)) = joined else { continue };
+        match data {
+            Ok(sd) if !sd.is_empty() => {
+                let sd = if adjusted { sd.adjusted() } else { sd };
+                let analysis = analyze_stock(&sd, &symbol, forecast.for_symbol(&symbol));
+                let signals = detect_signals(&symbol, &sd, &analysis);
+                rows.push(RankRow {
+                    rank: 0,
+                    score: composite_score(&signals, &analysis),
+                    price: analysis.current_price,
+                    change_pct: analysis.recent_change,
+                    volatility_pct: calculate_volatility(&sd.closes),
+                    signals: signals.iter().map(|s| s.kind.label().to_string()).collect(),
+                    symbol,
+                });
+            }
+            Ok(_) => eprintln!("{symbol}: no data"),
+            Err(e) => eprintln!("{symbol}: {e}"),
+        }
+    }
+
+    rows.sort_by(|a, b| b.score.cmp(&a.score).then(a.symbol.cmp(&b.symbol)));
+    for (i, row) in rows.iter_mut().enumerate() {
+        row.rank = i + 1;
+    }
+    rows
+}
+
+fn print_table(rows: &[RankRow]) {
+    println!("{:>4}  {:<10} {:>5} {:>12} {:>9} {:>8}  Signals", "Rank", "Symbol", "Score", "Price", "Change", "Vol");
+    for r in rows {
+        println!(
+            "{:>4}  {:<10} {:>+5} {:>12.2} {:>9} {:>7.2}%  {}",
+            r.rank,
+            r.symbol,
+            r.score,
+            r.price,
+            r.change_pct.map_or("--".into(), |c| format!("{:+.2}%", c)),
+            r.volatility_pct,
+            r.signals.join(", "),
+        );
+    }
+}
+
+fn print_csv(rows: &[RankRow]) {
+    println!("rank,symbol,score,price,change_pct,volatility_pct,signals");
+    for r in rows {
+        println!(
+            "{},{},{},{:.4},{},{:.4},\"{}\"",
+            r.rank,
+            r.symbol,
+            r.score,
+            r.price,
+            r.change_pct.map_or(String::new(), |c| format!("{:.4}", c)),
+            r.volatility_pct,
+            r.signals.join(";"),
+        );
+    }
+}
+
+/// Runs `bstock rank` without starting the TUI.
+pub fn run_rank(
+    provider: Arc<dyn DataProvider>,
+    symbols: &[String],
+    time_range: TimeRange,
+    format: RankFormat,
+    adjusted: bool,
+    forecast: &ForecastSettings,
+) -> Result<()> {
+    let rt = tokio::runtime::Runtime::new()?;
+    let rows = rt.block_on(rank_symbols(provider, symbols, time_range, adjusted, forecast));
+    match format {
+        RankFormat::Table => print_table(&rows),
+        RankFormat::Json => println!("{}", serde_json::to_string_pretty(&rows)?),
+        RankFormat::Csv => print_csv(&rows),
+    }
+    if rows.is_empty() && !symbols.is_empty() {
+        anyhow::bail!("no symbols could be ranked");
+    }
+    Ok(())
+}
@@ -2,26 +2,21 @@ use ratatui::{
     prelude::*,
     text::{Line as TextLine, Span},
     widgets::{
-        Block, Borders, Paragraph,
+        Block, Borders, Paragraph, Sparkline,
         canvas::{Canvas, Line},
     },
 };
-use crate::lib::analysis::StockAnalysis;
+use crate::lib::{
+    analysis::StockAnalysis,
+    instrument::{format_price, small_price_decimals, AssetClass},
+    market::{follows_us_sessions, us_session},
+    signals::SignalEvent,
+    timezone::DisplayZone,
+};
 
-type CanvasFn<'a> = Box<dyn Fn(&mut ratatui::widgets::canvas::Context<'_>) + 'a>;
+use super::theme::Theme;
 
-// ── colours ────────────────────────────────────────────────────
-const GRID_C: Color = Color::DarkGray;
-const PRED_C: Color = Color::Gray;
-const SMA10_C: Color = Color::Yellow;
-const SMA50_C: Color = Color::Cyan;
-const EMA20_C: Color = Color::Magenta;
-const XHAIR_C: Color = Color::LightYellow;
-const CANDLE_UP: Color = Color::Green;
-const CANDLE_DOWN: Color = Color::Red;
-const VOL_UP: Color = Color::Green;
-const VOL_DOWN: Color = Color::Red;
-const PREV_CLOSE_C: Color = Color::LightBlue;
+type CanvasFn<'a> = Box<dyn Fn(&mut ratatui::widgets::canvas::Context<'_>) + 'a>;
 
 // ── nice-number axis ───────────────────────────────────────────
 
@@ -49,14 +44,96 @@ pub fn nice_y_bounds(min_val: f64, max_val: f64) -> (f64, f64, f64) {
     (lo - pad, hi + pad, step)
 }
 
-pub fn y_axis_labels(lo: f64, hi: f64, n: usize) -> Vec<String> {
+/// Number of price labels on the y-axis; gridlines are drawn at the same values.
+pub const Y_LABELS: usize = 5;
+
+/// Evenly spaced values from `hi` (top) down to `lo` (bottom).
+pub fn y_axis_values(lo: f64, hi: f64, n: usize) -> Vec<f64> {
     let step = (hi - lo) / (n as f64 - 1.0).max(1.0);
-    (0..n).map(|i| {
-        let v = hi - step * i as f64;
-        if v >= 1000.0 { format!("${:.0}", v) }
-        else if v >= 1.0 { format!("${:.2}", v) }
-        else { format!("${:.4}", v) }
-    }).collect()
+    (0..n).map(|i| hi - step * i as f64).collect()
+}
+
+/// Compact price label for the y-axis; currency pairs keep their full
+/// precision since their moves are in the last digits, and index points
+/// have no `$`.
+pub fn fmt_axis_price(symbol: &str, v: f64) -> String {
+    let label = match AssetClass::of(symbol) {
+        AssetClass::Forex => return format_price(symbol, v),
+        _ if v >= 1000.0 => format!("${:.0}", v),
+        _ if v >= 1.0 => format!("${:.2}", v),
+        _ => format!("${:.*}", small_price_decimals(v), v),
+    };
+    if AssetClass::of(symbol) == AssetClass::Index { label[1..].to_string() } else { label }
+}
+
+pub fn y_axis_labels(symbol: &str, lo: f64, hi: f64, n: usize) -> Vec<String> {
+    y_axis_values(lo, hi, n).into_iter().map(|v| fmt_axis_price(symbol, v)).collect()
+}
+
+/// Terminal row (within a Braille canvas `rows` cells tall) on which a value
+/// at `frac` of the way down from the top bound is painted — mirrors the
+/// canvas's own dot mapping so text labels land exactly on drawn lines.
+pub fn braille_row(frac: f64, rows: u16) -> u16 {
+    let dots = rows as f64 * 4.0;
+    let dot = (frac.clamp(0.0, 1.0) * (dots - 1.0)) as u16;
+    dot / 4
+}
+
+pub fn pct_axis_labels(lo: f64, hi: f64, n: usize) -> Vec<String> {
+    let step = (hi - lo) / (n as f64 - 1.0).max(1.0);
+    (0..n).map(|i| format!("{:+.1}%", hi - step * i as f64)).collect()
+}
+
+// ── time axis ──────────────────────────────────────────────────
+
+/// Tick format for the visible span: clock time within a day, weekday + time
+/// over a few days, day of month up to a quarter, month + year beyond that.
+fn x_tick_format(span_secs: i64) -> &'static str {
+    const DAY: i64 = 86_400;
+    if span_secs <= DAY + DAY / 2 { "%H:%M" }
+    else if span_secs <= 10 * DAY { "%a %H:%M" }
+    else if span_secs <= 120 * DAY { "%b %d" }
+    else { "%b %Y" }
+}
+
+/// Date ticks for a chart whose bars are the timestamps `tss`, drawn at
+/// `x = index` on an x-axis spanning `[0, x_max]` and `width` cells wide.
+/// Labels are centred under their bar and dropped when they would overlap.
+/// Times are `symbol`'s bars shown in `zone`.
+pub fn create_x_axis(tss: &[i64], symbol: &str, zone: DisplayZone, width: u16, x_max: f64, theme: &Theme) -> Paragraph<'static> {
+    let n = tss.len();
+    let w = width as usize;
+    if n == 0 || w == 0 {
+        return Paragraph::new("");
+    }
+    let fmt = x_tick_format(tss[n - 1] - tss[0]);
+    let label_at = |i: usize| zone.format_bar(symbol, tss[i], fmt);
+    let label_len = label_at(0).chars().count().max(1);
+    let max_labels = (w / (label_len + 3)).clamp(1, 6).min(n);
+    let positions: Vec<usize> = if max_labels == 1 {
+        vec![0]
+    } else {
+        (0..max_labels).map(|i| i * (n - 1) / (max_labels - 1)).collect()
+    };
+
+    let mut line = vec![' '; w];
+    let mut next_free = 0;
+    for pos in positions {
+        let label: Vec<char> = label_at(pos).chars().collect();
+        if label.len() > w { continue; }
+        let center = (pos as f64 / x_max.max(1.0) * (w - 1) as f64).round() as usize;
+        let start = center.saturating_sub(label.len() / 2).min(w - label.len());
+        if start < next_free { continue; }
+        line[start..start + label.len()].copy_from_slice(&label);
+        next_free = start + label.len() + 1;
+    }
+    Paragraph::new(line.into_iter().collect::<String>())
+        .style(Style::default().fg(theme.muted))
+}
+
+/// The part of an axis row that sits under a bordered canvas's content.
+pub fn axis_row_inner(area: Rect) -> Rect {
+    Rect::new(area.x + 1, area.y, area.width.saturating_sub(2), area.height)
 }
 
 // ── helpers ────────────────────────────────────────────────────
@@ -88,6 +165,25 @@ fn draw_series(
     }
 }
 
+/// Draw a dotted horizontal line across `[0, x_max]`.
+fn draw_hline_dotted(
+    ctx: &mut ratatui::widgets::canvas::Context<'_>,
+    y: f64,
+    x_max: f64,
+    dash: f64,
+    color: Color,
+) {
+    let mut sx = 0.0;
+    let mut on = true;
+    while sx < x_max {
+        if on {
+            ctx.draw(&Line { x1: sx, y1: y, x2: (sx + dash).min(x_max), y2: y, color });
+        }
+        sx += dash;
+        on = !on;
+    }
+}
+
 /// Draw a dashed series (alternating on/off segments).
 fn draw_dashed(
     ctx: &mut ratatui::widgets::canvas::Context<'_>,
@@ -115,6 +211,21 @@ fn draw_dashed(
     }
 }
 
+/// Shade between the low and high of `band`, given as (x, low, high)
+/// points, with a vertical line every braille dot column of width `dot`.
+fn draw_band(ctx: &mut ratatui::widgets::canvas::Context<'_>, band: &[(f64, f64, f64)], dot: f64, color: Color) {
+    for w in band.windows(2) {
+        let ((x0, lo0, hi0), (x1, lo1, hi1)) = (w[0], w[1]);
+        let mut x = x0;
+        while x <= x1 {
+            let t = if x1 > x0 { (x - x0) / (x1 - x0) } else { 0.0 };
+            let (lo, hi) = (lo0 + t * (lo1 - lo0), hi0 + t * (hi1 - hi0));
+            ctx.draw(&Line { x1: x, y1: lo, x2: x, y2: hi, color });
+            x += dot.max(1e-6);
+        }
+    }
+}
+
 /// Draw a single OHLC candle with dynamically-scaled body width.
 ///
 /// `dot_x` is the coordinate width of one Braille dot  (= x_range / (char_cells * 2)).
@@ -125,9 +236,9 @@ fn draw_candle(
     x: f64, open: f64, high: f64, low: f64, close: f64,
     dot_x: f64,
     gap_x: f64,
+    color: Color,
 ) {
     let up = close >= open;
-    let color = if up { CANDLE_UP } else { CANDLE_DOWN };
     let top = if up { close } else { open };
     let bot = if up { open } else { close };
 
@@ -149,11 +260,11 @@ fn draw_candle(
 /// Draw a solid volume bar with dynamically-scaled width.
 fn draw_vol_bar(
     ctx: &mut ratatui::widgets::canvas::Context<'_>,
-    x: f64, h: f64, up: bool,
+    x: f64, h: f64,
     dot_x: f64,
     gap_x: f64,
+    color: Color,
 ) {
-    let color = if up { VOL_UP } else { VOL_DOWN };
     let half_width = gap_x.max(dot_x) * 0.4;
     let step = (dot_x * 0.5).max(gap_x * 0.01).max(0.01);
     let mut dx = -half_width;
@@ -167,17 +278,74 @@ fn draw_vol_bar(
 
 // ── price chart ────────────────────────────────────────────────
 
+/// Right edge of the price chart's x-axis: the last bar, or the last
+/// prediction when a forecast is appended after the bars.
+pub fn price_x_max(n: usize, n_preds: usize) -> f64 {
+    if n_preds == 0 {
+        (n as f64 - 1.0).max(0.0)
+    } else {
+        n as f64 + n_preds as f64
+    }
+}
+
+/// Whether `symbol`'s chart over `time_range` marks the extended sessions.
+pub fn marks_extended_hours(symbol: &str, time_range: crate::data::TimeRange) -> bool {
+    time_range.is_intraday() && follows_us_sessions(symbol)
+}
+
+/// Indices into `bars` of the bars whose period holds one of `ex_dates`;
+/// a bar lasts `bar_seconds`.
+pub fn ex_dividend_bars(bars: &[crate::data::FilteredBar], ex_dates: impl IntoIterator<Item = i64>, bar_seconds: i64) -> Vec<usize> {
+    let (Some(first), Some(last)) = (bars.first(), bars.last()) else { return Vec::new() };
+    ex_dates.into_iter()
+        .filter(|&t| t >= first.timestamp && t < last.timestamp + bar_seconds)
+        .map(|t| bars.partition_point(|b| b.timestamp <= t) - 1)
+        .collect()
+}
+
+/// Indices into `bars` of the bars `events` fired on, with their bias.
+pub fn signal_bars(bars: &[crate::data::FilteredBar], events: &[SignalEvent]) -> Vec<(usize, Option<bool>)> {
+    events.iter()
+        .filter_map(|e| Some((bars.binary_search_by_key(&e.timestamp, |b| b.timestamp).ok()?, e.kind.bias())))
+        .collect()
+}
+
+/// Optional decorations for [`create_price_chart`].
+#[derive(Clone, Copy, Default)]
+pub struct PriceChartOptions<'a> {
+    pub crosshair_x: Option<f64>,
+    pub prev_close: Option<f64>,
+    /// Dotted guides at the y-axis label values (and matching verticals).
+    pub gridlines: bool,
+    /// Highlighted horizontal guide at the latest price.
+    pub current_price: Option<f64>,
+    /// Append the forecast after the last bar (only meaningful when the
+    /// visible bars end at the latest data point).
+    pub show_predictions: bool,
+    /// Grey out bars from the US pre-market and after-hours sessions.
+    pub extended_hours: bool,
+    /// Bars on which a dividend went ex, marked with a `D` at the foot.
+    pub ex_dividends: &'a [usize],
+    /// Bars on which a signal fired, with its bias: `▲` under bullish
+    /// ones, `▼` over bearish ones and `◆` over neutral ones.
+    pub signals: &'a [(usize, Option<bool>)],
+}
+
+/// `bars` is a contiguous slice of the full series starting at `full_start`,
+/// which is used to line the cached overlays up with the visible bars.
 pub fn create_price_chart<'a>(
     bars: &'a [crate::data::FilteredBar],
-    full_data_len: usize,
+    full_start: usize,
     analysis: &'a StockAnalysis,
-    crosshair_x: Option<f64>,
     title: &'a str,
     canvas_char_width: u16,
-    prev_close: Option<f64>,
+    opts: PriceChartOptions<'a>,
+    theme: &'a Theme,
 ) -> Canvas<'a, CanvasFn<'a>> {
     let n = bars.len();
-    let full_start = full_data_len.saturating_sub(n);
+    let PriceChartOptions {
+        crosshair_x, prev_close, gridlines, current_price, show_predictions, extended_hours, ex_dividends, signals,
+    } = opts;
 
     // Use cached SMA/EMA series (computed once at fetch time)
     let sma10_pts = align_overlay(&analysis.sma10_values, full_start, n, 10);
@@ -185,7 +353,8 @@ pub fn create_price_chart<'a>(
     let ema20_pts = align_overlay(&analysis.ema20_values, full_start, n, 20);
 
     // Predictions
-    let pred_pts: Vec<(f64, f64)> = analysis.predictions.iter().enumerate()
+    let predictions: &[f64] = if show_predictions { &analysis.predictions } else { &[] };
+    let pred_pts: Vec<(f64, f64)> = predictions.iter().enumerate()
         .map(|(i, &p)| ((n as f64) + i as f64, p)).collect();
     let mut pred_full = vec![];
     if !pred_pts.is_empty() {
@@ -194,22 +363,24 @@ pub fn create_price_chart<'a>(
         }
         pred_full.extend(&pred_pts);
     }
+    // 95% band around the forecast, fanning out from the last close
+    let bands: &[(f64, f64)] = if pred_pts.is_empty() { &[] } else { &analysis.prediction_bands };
+    let band: Vec<(f64, f64, f64)> = pred_full.first().map(|&(x, y)| (x, y, y)).into_iter()
+        .chain(bands.iter().enumerate().map(|(i, &(lo, hi))| ((n + i) as f64, lo, hi)))
+        .collect();
 
     // Y range
     let mut all_y: Vec<f64> = bars.iter().flat_map(|b| [b.high, b.low]).collect();
     all_y.extend(sma10_pts.iter().map(|(_, y)| *y));
     all_y.extend(sma50_pts.iter().map(|(_, y)| *y));
     all_y.extend(ema20_pts.iter().map(|(_, y)| *y));
-    all_y.extend(analysis.predictions.iter().copied());
+    all_y.extend(predictions.iter().copied());
+    all_y.extend(bands.iter().flat_map(|&(lo, hi)| [lo, hi]));
     let y_max = all_y.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
     let y_min = all_y.iter().cloned().fold(f64::INFINITY, f64::min);
     let (y_lo, y_hi, _step) = nice_y_bounds(y_min, y_max);
 
-    let x_max = if pred_pts.is_empty() {
-        (n as f64 - 1.0).max(0.0)
-    } else {
-        n as f64 + pred_pts.len() as f64
-    };
+    let x_max = price_x_max(n, pred_pts.len());
 
     Canvas::default()
         .block(Block::default().borders(Borders::ALL).title(title))
@@ -219,64 +390,129 @@ pub fn create_price_chart<'a>(
         .paint(Box::new(move |ctx: &mut ratatui::widgets::canvas::Context<'_>| {
             if n == 0 { return; }
 
-            // ── grid ──────────────────────────────────────
-            for i in 0..=5 {
-                let gy = y_lo + (y_hi - y_lo) * (i as f64 / 5.0);
-                ctx.draw(&Line { x1: 0.0, y1: gy, x2: x_max, y2: gy, color: GRID_C });
-                let gx = i as f64 / 5.0 * x_max;
-                ctx.draw(&Line { x1: gx, y1: y_lo, x2: gx, y2: y_hi, color: GRID_C });
+            // ── grid (dotted, on the y-axis label values) ──
+            if gridlines {
+                let dot = x_max / (canvas_char_width as f64 * 2.0).max(1.0);
+                for gy in y_axis_values(y_lo, y_hi, Y_LABELS) {
+                    draw_hline_dotted(ctx, gy, x_max, dot, theme.grid);
+                }
+                let dot_y = (y_hi - y_lo) / 40.0;
+                for i in 1..5 {
+                    let gx = i as f64 / 5.0 * x_max;
+                    let mut sy = y_lo;
+                    while sy < y_hi {
+                        ctx.draw(&Line { x1: gx, y1: sy, x2: gx, y2: (sy + dot_y).min(y_hi), color: theme.grid });
+                        sy += dot_y * 2.0;
+                    }
+                }
             }
 
             // ── previous close ────────────────────────────
             if let Some(pc) = prev_close
                 && pc >= y_lo && pc <= y_hi
             {
-                let dash = x_max / 60.0;
-                let mut sx = 0.0;
-                let mut on = false;
-                while sx < x_max {
-                    if on {
-                        ctx.draw(&Line {
-                            x1: sx, y1: pc,
-                            x2: (sx + dash).min(x_max), y2: pc,
-                            color: PREV_CLOSE_C,
-                        });
-                    }
-                    sx += dash;
-                    on = !on;
-                }
+                draw_hline_dotted(ctx, pc, x_max, x_max / 60.0, theme.prev_close);
+            }
+
+            // ── current price guide ───────────────────────
+            if let Some(cp) = current_price
+                && cp >= y_lo && cp <= y_hi
+            {
+                ctx.draw(&Line { x1: 0.0, y1: cp, x2: x_max, y2: cp, color: theme.last_price });
             }
 
             // ── SMA-50 ────────────────────────────────────
             if sma50_pts.len() > 1 {
-                draw_series(ctx, &sma50_pts, SMA50_C);
+                draw_series(ctx, &sma50_pts, theme.sma50);
             }
             // ── SMA-10 ────────────────────────────────────
             if sma10_pts.len() > 1 {
-                draw_series(ctx, &sma10_pts, SMA10_C);
+                draw_series(ctx, &sma10_pts, theme.sma10);
             }
             // ── EMA-20 ────────────────────────────────────
             if ema20_pts.len() > 1 {
-                draw_series(ctx, &ema20_pts, EMA20_C);
+                draw_series(ctx, &ema20_pts, theme.ema20);
             }
 
             // ── OHLC candles ──────────────────────────────
             let dot_x = x_max / (canvas_char_width as f64 * 2.0).max(1.0);
             let gap_x = if n > 1 { x_max / (n - 1) as f64 } else { 1.0 };
             for (i, bar) in bars.iter().enumerate() {
-                draw_candle(ctx, i as f64, bar.open, bar.high, bar.low, bar.close, dot_x, gap_x);
+                let extended = extended_hours
+                    && chrono::DateTime::from_timestamp(bar.timestamp, 0).is_some_and(|t| us_session(t).is_extended());
+                let color = if extended { theme.muted } else { theme.signed(bar.close - bar.open) };
+                draw_candle(ctx, i as f64, bar.open, bar.high, bar.low, bar.close, dot_x, gap_x, color);
+            }
+
+            // ── ex-dividend dates ─────────────────────────
+            for &i in ex_dividends {
+                ctx.print(i as f64, y_lo, Span::styled("D", Style::default().fg(theme.neutral)));
+            }
+
+            // ── signals ───────────────────────────────────
+            for &(i, bias) in signals {
+                let Some(bar) = bars.get(i) else { continue };
+                let (y, mark, color) = match bias {
+                    Some(true) => (bar.low, "▲", theme.gain),
+                    Some(false) => (bar.high, "▼", theme.loss),
+                    None => (bar.high, "◆", theme.neutral),
+                };
+                ctx.print(i as f64, y.clamp(y_lo, y_hi), Span::styled(mark, Style::default().fg(color)));
             }
 
             // ── predictions ───────────────────────────────
             if pred_full.len() > 1 {
                 let sep_x = n as f64 - 0.5;
-                ctx.draw(&Line { x1: sep_x, y1: y_lo, x2: sep_x, y2: y_hi, color: GRID_C });
-                draw_dashed(ctx, &pred_full, PRED_C, 0.3);
+                ctx.draw(&Line { x1: sep_x, y1: y_lo, x2: sep_x, y2: y_hi, color: theme.grid });
+                draw_band(ctx, &band, dot_x, theme.grid);
+                draw_dashed(ctx, &pred_full, theme.prediction, 0.3);
             }
 
             // ── crosshair ─────────────────────────────────
             if let Some(cx) = crosshair_x {
-                ctx.draw(&Line { x1: cx, y1: y_lo, x2: cx, y2: y_hi, color: XHAIR_C });
+                ctx.draw(&Line { x1: cx, y1: y_lo, x2: cx, y2: y_hi, color: theme.crosshair });
+            }
+        }) as CanvasFn<'a>)
+}
+
+// ── comparison chart ───────────────────────────────────────────
+
+/// A labelled series of percent changes for the comparison chart.
+pub struct PctSeries {
+    pub label: String,
+    pub color: Color,
+    pub values: Vec<f64>,
+}
+
+/// Y-bounds shared by the comparison canvas and its axis labels.
+pub fn pct_y_bounds(series: &[PctSeries]) -> (f64, f64) {
+    let (min, max) = series.iter()
+        .flat_map(|s| s.values.iter().copied())
+        .chain([0.0])
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| (lo.min(v), hi.max(v)));
+    let (lo, hi, _step) = nice_y_bounds(min, max);
+    (lo, hi)
+}
+
+/// Overlay of percent-change lines aligned by bar index, with a 0% baseline.
+pub fn create_comparison_chart<'a>(series: &'a [PctSeries], title: &'a str, theme: &'a Theme) -> Canvas<'a, CanvasFn<'a>> {
+    let n = series.iter().map(|s| s.values.len()).max().unwrap_or(0);
+    let x_max = (n as f64 - 1.0).max(1.0);
+    let (y_lo, y_hi) = pct_y_bounds(series);
+
+    Canvas::default()
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .marker(Marker::Braille)
+        .x_bounds([0.0, x_max])
+        .y_bounds([y_lo, y_hi])
+        .paint(Box::new(move |ctx: &mut ratatui::widgets::canvas::Context<'_>| {
+            if n == 0 { return; }
+            ctx.draw(&Line { x1: 0.0, y1: 0.0, x2: x_max, y2: 0.0, color: theme.grid });
+            for s in series {
+                let pts: Vec<(f64, f64)> = s.values.iter().enumerate()
+                    .map(|(i, &v)| (i as f64, v))
+                    .collect();
+                draw_series(ctx, &pts, s.color);
             }
         }) as CanvasFn<'a>)
 }
@@ -286,6 +522,7 @@ pub fn create_price_chart<'a>(
 pub fn create_volume_chart<'a>(
     bars: &'a [crate::data::FilteredBar],
     canvas_char_width: u16,
+    theme: &'a Theme,
 ) -> Canvas<'a, CanvasFn<'a>> {
     let n = bars.len();
     let max_vol = bars.iter().map(|b| b.volume).max().unwrap_or(1);
@@ -300,37 +537,72 @@ pub fn create_volume_chart<'a>(
             let dot_x = x_max / (canvas_char_width as f64).max(1.0);
             let gap_x = if n > 1 { x_max / (n - 1) as f64 } else { 1.0 };
             for (i, bar) in bars.iter().enumerate() {
-                draw_vol_bar(ctx, i as f64, bar.volume as f64, bar.close >= bar.open, dot_x, gap_x);
+                draw_vol_bar(ctx, i as f64, bar.volume as f64, dot_x, gap_x, theme.signed(bar.close - bar.open));
             }
         }) as CanvasFn<'a>)
 }
 
+// ── sparkline (crowded tiles) ──────────────────────────────────
+
+/// Tiles whose chart area is smaller than this (borders included) show a
+/// sparkline instead of the full canvas chart.
+pub const SPARKLINE_BELOW: (u16, u16) = (24, 8);
+
+/// Index of the first of the most recent closes that fit one per column
+/// inside a bordered area `area_width` wide.
+pub fn sparkline_start(n: usize, area_width: u16) -> usize {
+    n.saturating_sub(area_width.saturating_sub(2) as usize)
+}
+
+/// Closes rescaled so the lowest one still shows as a sliver: a sparkline
+/// always starts at zero, which would flatten typical price moves.
+pub fn sparkline_levels(closes: &[f64]) -> Vec<u64> {
+    let lo = closes.iter().copied().fold(f64::INFINITY, f64::min);
+    let hi = closes.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let span = (hi - lo).max(f64::EPSILON);
+    closes.iter().map(|c| 1 + ((c - lo) / span * 99.0).round() as u64).collect()
+}
+
+pub fn create_sparkline<'a>(levels: &'a [u64], title: &'a str, rising: bool, theme: &Theme) -> Sparkline<'a> {
+    Sparkline::default()
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .data(levels)
+        .max(100)
+        .style(Style::default().fg(if rising { theme.gain } else { theme.loss }))
+}
+
 // ── legend ─────────────────────────────────────────────────────
 
-pub fn create_legend_line() -> Paragraph<'static> {
+pub fn create_legend_line(theme: &Theme) -> Paragraph<'static> {
     let items: Vec<(&str, Color)> = vec![
-        ("│ OHLC ", Color::White),
-        ("─ SMA10 ", SMA10_C),
-        ("─ SMA50 ", SMA50_C),
-        ("─ EMA20 ", EMA20_C),
-        ("╌ Pred ", PRED_C),
-        ("╌ Prev ", PREV_CLOSE_C),
+        ("│ OHLC ", theme.text),
+        ("─ SMA10 ", theme.sma10),
+        ("─ SMA50 ", theme.sma50),
+        ("─ EMA20 ", theme.ema20),
+        ("╌ Pred ", theme.prediction),
+        ("░ 95% ", theme.grid),
+        ("╌ Prev ", theme.prev_close),
+        ("─ Last ", theme.last_price),
+        ("▲▼ Signal ", theme.neutral),
         ("│", Color::Reset),
-        (" ▲ Vol ", VOL_UP),
-        (" ▼ Vol ", VOL_DOWN),
+        (" ▲ Vol ", theme.gain),
+        (" ▼ Vol ", theme.loss),
     ];
     let spans: Vec<Span<'static>> = items.into_iter()
         .map(|(l, c)| Span::styled(l.to_string(), Style::default().fg(c)))
         .collect();
     Paragraph::new(TextLine::from(spans))
         .alignment(Alignment::Center)
-        .style(Style::default().fg(Color::DarkGray))
+        .style(Style::default().fg(theme.muted))
 }
 
 // ── crosshair info ─────────────────────────────────────────────
 
 pub struct CrosshairSnapshot {
     pub date: String,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
     pub price: f64,
     pub sma10: Option<f64>,
     pub sma50: Option<f64>,
@@ -342,20 +614,27 @@ pub struct CrosshairSnapshot {
 
 pub fn crosshair_info(
     bars: &[crate::data::FilteredBar],
-    full_data_len: usize,
+    full_start: usize,
     analysis: &StockAnalysis,
     index: usize,
+    time_range: crate::data::TimeRange,
+    zone: DisplayZone,
 ) -> Option<CrosshairSnapshot> {
     let n = bars.len();
     if n == 0 || index >= n { return None; }
     let bar = &bars[index];
-    let date = chrono::DateTime::from_timestamp(bar.timestamp, 0)
-        .map(|dt| dt.format("%Y-%m-%d").to_string())
-        .unwrap_or_else(|| "?".into());
-    let full_idx = full_data_len.saturating_sub(n) + index;
+    let fmt = if time_range.is_intraday() { "%Y-%m-%d %H:%M" } else { "%Y-%m-%d" };
+    let date = zone.format_bar(&analysis.symbol, bar.timestamp, fmt);
+    let full_idx = full_start + index;
     // Use cached SMA/EMA from analysis instead of recomputing
     let sma10 = analysis.sma10_values.get(full_idx.saturating_sub(9)).copied();
     let sma50 = analysis.sma50_values.get(full_idx.saturating_sub(49)).copied();
     let ema20 = analysis.ema20_values.get(full_idx.saturating_sub(19)).copied();
-    Some(CrosshairSnapshot { date, price: bar.close, sma10, sma50, ema20, volume: bar.volume, index, total: n })
+    Some(CrosshairSnapshot {
+        date,
+        open: bar.open, high: bar.high, low: bar.low, price: bar.close,
+        sma10, sma50, ema20,
+        volume: bar.volume,
+        index, total: n,
+    })
 }
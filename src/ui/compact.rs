@@ -0,0 +1,94 @@
+use ratatui::{
+    prelude::*,
+    text::Line as TextLine,
+    widgets::Paragraph,
+};
+
+use crate::app::{App, LoadState};
+
+use super::{
+    chart::sparkline_levels,
+    layout::{spinner, MainHits, TileHit},
+};
+
+const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// One-row sparkline of the last `width` closes as block characters.
+fn text_sparkline(closes: &[f64], width: usize) -> String {
+    let recent = &closes[closes.len().saturating_sub(width)..];
+    sparkline_levels(recent)
+        .into_iter()
+        .map(|l| BLOCKS[((l - 1) as usize * BLOCKS.len() / 100).min(BLOCKS.len() - 1)])
+        .collect()
+}
+
+/// Degraded main view for terminals smaller than the tile grid needs: one
+/// line per symbol with price, % change and a sparkline.
+pub fn draw_compact_ui(f: &mut Frame, app: &App, area: Rect, needed: (u16, u16)) -> MainHits {
+    let v = Layout::default().direction(Direction::Vertical).constraints([
+        Constraint::Length(1), Constraint::Min(0), Constraint::Length(1),
+    ]).split(area);
+
+    let theme = &app.theme;
+    let loading = app.loading_total > 0 && app.loading_done < app.loading_total;
+    let title = if loading {
+        format!("Bstock - compact  {}/{}", app.loading_done, app.loading_total)
+    } else {
+        format!("Bstock - compact ({}x{} for charts)", needed.0, needed.1)
+    };
+    f.render_widget(Paragraph::new(title).style(Style::default().fg(theme.accent)), v[0]);
+
+    // Scroll so the selected row stays visible
+    let rows = v[1].height as usize;
+    let order = app.main_order();
+    let selected_pos = order.iter().position(|&i| i == app.selected_index).unwrap_or(0);
+    let offset = selected_pos.saturating_sub(rows.saturating_sub(1));
+    let spark_w = (v[1].width as usize).saturating_sub(30);
+    let lines: Vec<TextLine> = order.iter().skip(offset).take(rows)
+        .map(|&i| {
+            let a = &app.analyses[i];
+            let selected = i == app.selected_index;
+            let base = if selected {
+                Style::default().fg(theme.on_accent).bg(theme.accent)
+            } else {
+                Style::default()
+            };
+            match app.load_state(i) {
+                LoadState::Loaded => {}
+                LoadState::Fetching => {
+                    return TextLine::from(Span::styled(format!("{:<8} {} fetching", a.analysis.symbol, spinner()), base));
+                }
+                LoadState::Failed(message) => {
+                    let style = if selected { base } else { Style::default().fg(theme.loss) };
+                    return TextLine::from(Span::styled(format!("{:<8} failed: {}", a.analysis.symbol, message), style));
+                }
+                LoadState::NotLoaded => {
+                    return TextLine::from(Span::styled(format!("{:<8} …", a.analysis.symbol), base));
+                }
+            }
+            let change = a.analysis.recent_change.unwrap_or(0.0);
+            let colored = if selected { base } else { Style::default().fg(theme.signed(change)) };
+            TextLine::from(vec![
+                Span::styled(format!("{:<8}", a.analysis.symbol), base.add_modifier(Modifier::BOLD)),
+                Span::styled(format!("{:>10.2} ", a.analysis.current_price), base),
+                Span::styled(format!("{:>+7.2}% ", change), colored),
+                Span::styled(format!(" {}", text_sparkline(&a.stock_data.closes, spark_w)), colored),
+            ])
+        })
+        .collect();
+    let tiles = order.iter().skip(offset).take(rows).enumerate()
+        .map(|(row, &index)| TileHit {
+            index,
+            area: Rect::new(v[1].x, v[1].y + row as u16, v[1].width, 1),
+            selector: None,
+        })
+        .collect();
+    f.render_widget(Paragraph::new(lines), v[1]);
+
+    f.render_widget(
+        Paragraph::new("←→ select  Enter details  L layout  e edit  ? help  q quit")
+            .style(Style::default().fg(theme.muted)),
+        v[2],
+    );
+    MainHits { per_page: rows.max(1), tiles }
+}
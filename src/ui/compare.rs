@@ -0,0 +1,150 @@
+use ratatui::{
+    prelude::*,
+    text::{Line as TextLine, Span},
+    widgets::Paragraph,
+};
+
+use crate::app::AnalysisWithChartData;
+use crate::data::{join_on_time, normalized_pct, prior_period};
+use crate::lib::{stock_data::StockData, timezone::DisplayZone};
+
+use super::{chart, detail, theme::Theme};
+
+/// Renders two symbols as percent-change lines over the same window,
+/// over the bar times both have.
+#[allow(clippy::too_many_arguments)]
+pub fn draw_compare_ui(
+    f: &mut Frame,
+    first: &AnalysisWithChartData,
+    second: &AnalysisWithChartData,
+    area: Rect,
+    loading_total: usize,
+    loading_done: usize,
+    zone: DisplayZone,
+    theme: &Theme,
+) {
+    let sym_a = first.analysis.symbol.as_str();
+    let sym_b = second.analysis.symbol.as_str();
+    let header = format!(
+        " {} vs {}  |  {}  |  ↑↓ range  Esc back ",
+        sym_a, sym_b, second.time_range.as_str(),
+    );
+
+    let daily = !second.time_range.is_intraday();
+    let (ts, closes_a, closes_b) = join_on_time(&first.stock_data, sym_a, &second.stock_data, sym_b, daily);
+    let series = [
+        chart::PctSeries {
+            label: sym_a.to_string(),
+            color: theme.compare_a,
+            values: normalized_pct(&closes_a),
+        },
+        chart::PctSeries {
+            label: sym_b.to_string(),
+            color: theme.compare_b,
+            values: normalized_pct(&closes_b),
+        },
+    ];
+    let title = format!(" % change | {} ", second.time_range.as_str());
+    let loading = loading_total > 0 && loading_done < loading_total;
+    draw_pct_overlay(f, area, &header, &title, &series, &ts, sym_b, zone, loading, theme);
+}
+
+/// Renders the current period of a symbol against the equal period right
+/// before it, both as percent change from their first bar and aligned by
+/// trading day.
+pub fn draw_prior_period_ui(
+    f: &mut Frame,
+    data: &AnalysisWithChartData,
+    history: Option<&StockData>,
+    area: Rect,
+    loading: bool,
+    zone: DisplayZone,
+    theme: &Theme,
+) {
+    let sym = data.analysis.symbol.as_str();
+    let range = data.time_range.as_str();
+    let header = format!(" {}  |  {} vs prior {}  |  ↑↓ range  Esc back ", sym, range, range);
+
+    let first_ts = data.stock_data.timestamps.first().copied();
+    let (prior, current) = match (history, first_ts) {
+        (Some(h), Some(t0)) => prior_period(h, t0, sym, data.time_range),
+        _ => (Vec::new(), Vec::new()),
+    };
+    // Plot against the current period's dates; the prior one is offset
+    let ts = history.map_or(&[][..], |h| &h.timestamps[h.timestamps.len() - current.len()..]);
+    let m = if prior.is_empty() { 0 } else { current.len() };
+    let series = [
+        chart::PctSeries {
+            label: format!("{} {}", sym, range),
+            color: theme.compare_a,
+            values: normalized_pct(&current[..m]),
+        },
+        chart::PctSeries {
+            label: format!("prior {}", range),
+            color: theme.compare_b,
+            values: normalized_pct(&prior),
+        },
+    ];
+    let title = format!(" % change | {} vs prior {} ", range, range);
+    draw_pct_overlay(f, area, &header, &title, &series, &ts[..m], sym, zone, loading, theme);
+}
+
+/// Shared layout of the percent-change overlays: header, y-axis, chart,
+/// date axis, and a summary of each series' total change and their spread.
+/// `ts` are `symbol`'s bar times, shown in `zone`.
+#[allow(clippy::too_many_arguments)]
+fn draw_pct_overlay(
+    f: &mut Frame,
+    area: Rect,
+    header: &str,
+    title: &str,
+    series: &[chart::PctSeries; 2],
+    ts: &[i64],
+    symbol: &str,
+    zone: DisplayZone,
+    loading: bool,
+    theme: &Theme,
+) {
+    let v = Layout::default().direction(Direction::Vertical).constraints([
+        Constraint::Length(1), Constraint::Min(0), Constraint::Length(1), Constraint::Length(1),
+    ]).split(area);
+    f.render_widget(Paragraph::new(header.to_string()).style(Style::default().fg(theme.accent)), v[0]);
+
+    let body = Layout::default().direction(Direction::Horizontal).constraints([
+        Constraint::Length(8), Constraint::Min(0),
+    ]).split(v[1]);
+
+    if ts.is_empty() {
+        let msg = if loading { "Fetching…" } else { "No overlapping data to compare" };
+        f.render_widget(Paragraph::new(msg).alignment(Alignment::Center), body[1]);
+        return;
+    }
+
+    // Same scale as the chart, which spans the longer series
+    let x_max = (series.iter().map(|s| s.values.len()).max().unwrap_or(0) as f64 - 1.0).max(1.0);
+    let (y_lo, y_hi) = chart::pct_y_bounds(series);
+    detail::draw_y_axis_labels(f, body[0], &chart::pct_axis_labels(y_lo, y_hi, 5), theme);
+    f.render_widget(chart::create_comparison_chart(series, title, theme), body[1]);
+
+    let x_axis = Layout::default().direction(Direction::Horizontal).constraints([
+        Constraint::Length(8), Constraint::Min(0),
+    ]).split(v[2]);
+    let x_area = chart::axis_row_inner(x_axis[1]);
+    f.render_widget(
+        chart::create_x_axis(ts, symbol, zone, x_area.width, x_max, theme),
+        x_area,
+    );
+
+    // ── summary: total change of each leg and the spread ────
+    let last = |s: &chart::PctSeries| s.values.last().copied().unwrap_or(0.0);
+    let mut spans: Vec<Span> = Vec::new();
+    for s in series {
+        spans.push(Span::styled(format!("─ {} ", s.label), Style::default().fg(s.color)));
+        spans.push(Span::styled(
+            format!("{:+.2}%   ", last(s)),
+            Style::default().fg(theme.signed(last(s))),
+        ));
+    }
+    spans.push(Span::raw(format!("Spread {:+.2}pp", last(&series[0]) - last(&series[1]))));
+    f.render_widget(Paragraph::new(TextLine::from(spans)).alignment(Alignment::Center), v[3]);
+}
@@ -1,117 +1,718 @@
 use ratatui::{
-    prelude::{Constraint, Direction, Layout, Rect, Alignment, Style, Color, Modifier, text::Span},
-    widgets::{Paragraph},
+    prelude::{Constraint, Direction, Layout, Line, Rect, Alignment, Span, Style, Modifier},
+    widgets::{Block, Borders, Cell, Clear, List, ListItem, ListState, Paragraph, Row, Table, Tabs, Wrap},
     Frame,
 };
 
-use crate::app::AnalysisWithChartData;
-use crate::data::{filter_bars, TimeRange};
+use crate::app::{AnalysisWithChartData, App, DetailTab};
+use crate::data::{window_bars, ChartWindow, FilteredBar};
+use crate::lib::{
+    calendar,
+    chart_image::{self, Plot, RgbImage},
+    dividends::{self, Dividend},
+    edgar::Filing,
+    forecast::HORIZON,
+    insiders::{InsiderAction, NetActivity},
+    instrument::{format_price, AssetClass},
+    market::{follows_us_sessions, Session},
+    provider::AnalystRatings,
+    sentiment::Sentiment,
+    signals::SignalEvent,
+    timezone::DisplayZone,
+};
 
-use super::{chart, metrics};
+use super::{chart, indicators::draw_indicators_tab, strategy::draw_strategies_tab, metrics, selector::render_time_range_selector, status::{age_span, age_text}, theme::Theme};
 
-/// Y-axis price labels (ratatui text — always sharp & readable).
-fn draw_y_axis(f: &mut Frame, area: Rect, y_lo: f64, y_hi: f64) {
-    let labels = chart::y_axis_labels(y_lo, y_hi, 5);
-    let chunks = Layout::default().direction(Direction::Vertical).constraints([
-        Constraint::Percentage(20), Constraint::Percentage(20),
-        Constraint::Percentage(20), Constraint::Percentage(20),
-        Constraint::Min(0),
-    ]).split(area);
-    for (label, chunk) in labels.iter().zip(chunks.iter()) {
+/// Y-axis price labels (ratatui text — always sharp & readable), with the
+/// latest price highlighted on the row of its guide line.
+fn draw_y_axis(f: &mut Frame, area: Rect, symbol: &str, y_lo: f64, y_hi: f64, current_price: Option<f64>, theme: &Theme) {
+    draw_y_axis_labels(f, area, &chart::y_axis_labels(symbol, y_lo, y_hi, chart::Y_LABELS), theme);
+    if let Some(cp) = current_price
+        && cp >= y_lo && cp <= y_hi && y_hi > y_lo
+    {
+        let row = label_row(area, (y_hi - cp) / (y_hi - y_lo));
+        f.render_widget(
+            Paragraph::new(chart::fmt_axis_price(symbol, cp))
+                .style(Style::default().fg(theme.on_accent).bg(theme.last_price))
+                .alignment(Alignment::Right),
+            row,
+        );
+    }
+}
+
+/// The one-line strip of `area` level with a value `frac` of the way down
+/// the bordered chart beside it.
+fn label_row(area: Rect, frac: f64) -> Rect {
+    let rows = area.height.saturating_sub(2);
+    Rect::new(area.x, area.y + 1 + chart::braille_row(frac, rows.max(1)), area.width, 1)
+}
+
+/// Lay out evenly spaced pre-formatted axis labels top to bottom, each on the
+/// row where the chart in the adjacent, equally tall `area` draws that value.
+pub(super) fn draw_y_axis_labels(f: &mut Frame, area: Rect, labels: &[String], theme: &Theme) {
+    if area.height < 3 { return; }
+    let steps = (labels.len() as f64 - 1.0).max(1.0);
+    for (i, label) in labels.iter().enumerate() {
         f.render_widget(
-            Paragraph::new(label.as_str()).style(Style::default().fg(Color::Cyan)).alignment(Alignment::Right),
-            *chunk,
+            Paragraph::new(label.as_str()).style(Style::default().fg(theme.axis)).alignment(Alignment::Right),
+            label_row(area, i as f64 / steps),
         );
     }
 }
 
-/// X-axis date labels with context-aware formatting.
-fn draw_x_axis(f: &mut Frame, area: Rect, ts: &[i64], n: usize, time_range: TimeRange) {
-    if n == 0 || ts.is_empty() { return; }
-    let start = ts.len().saturating_sub(n);
-    let tss = &ts[start..];
-    let max_labels = 5usize;
-    let positions: Vec<usize> = if n <= max_labels {
-        (0..n).collect()
+/// Small value readout box pinned to the chart corner opposite the crosshair,
+/// so it never hides the bar being inspected.
+fn draw_crosshair_box(f: &mut Frame, chart_area: Rect, symbol: &str, snap: &chart::CrosshairSnapshot, theme: &Theme) {
+    let fmt = |v: f64| format_price(symbol, v);
+    let fmt_opt = |v: Option<f64>| v.map_or("--".into(), fmt);
+    let mut lines = vec![
+        format!("Open   {}", fmt(snap.open)),
+        format!("High   {}", fmt(snap.high)),
+        format!("Low    {}", fmt(snap.low)),
+        format!("Close  {}", fmt(snap.price)),
+    ];
+    if AssetClass::of(symbol).has_volume() {
+        lines.push(format!("Vol    {}", metrics::fmt_volume(snap.volume)));
+    }
+    lines.extend([
+        format!("SMA10  {}", fmt_opt(snap.sma10)),
+        format!("SMA50  {}", fmt_opt(snap.sma50)),
+        format!("EMA20  {}", fmt_opt(snap.ema20)),
+        format!("Bar    {}/{}", snap.index + 1, snap.total),
+    ]);
+    let inner_w = lines.iter().map(|l| l.len()).chain([snap.date.len() + 2]).max().unwrap_or(0) as u16;
+    let w = (inner_w + 2).min(chart_area.width.saturating_sub(2));
+    let h = (lines.len() as u16 + 2).min(chart_area.height.saturating_sub(2));
+    if w < 10 || h < 3 { return; }
+
+    let on_left_half = snap.index * 2 < snap.total;
+    let x = if on_left_half {
+        chart_area.x + chart_area.width.saturating_sub(w + 1)
     } else {
-        (0..max_labels)
-            .map(|i| (i as f64 * (n - 1) as f64 / (max_labels - 1) as f64).round() as usize)
-            .collect()
+        chart_area.x + 1
     };
-    let labels: Vec<String> = positions.iter().filter_map(|&pos| {
-        let ts_val = *tss.get(pos)?;
-        let dt = chrono::DateTime::from_timestamp(ts_val, 0)?;
-        Some(match time_range {
-            // Intraday: show hours:minutes
-            TimeRange::OneDay => dt.format("%H:%M").to_string(),
-            // Weekly: show abbreviated weekday + time
-            TimeRange::OneWeek => dt.format("%a %H:%M").to_string(),
-            // Monthly to yearly: show month + day
-            TimeRange::OneMonth
-            | TimeRange::ThreeMonths
-            | TimeRange::SixMonths
-            | TimeRange::YearToDate
-            | TimeRange::OneYear => dt.format("%b %d").to_string(),
-            // Multi-year: show month + year
-            TimeRange::TwoYears
-            | TimeRange::FiveYears
-            | TimeRange::TenYears
-            | TimeRange::All => dt.format("%b %Y").to_string(),
-        })
-    }).collect();
-    let w = area.width as usize;
-    let gap_count = labels.len().saturating_sub(1).max(1);
-    let total_label_width: usize = labels.iter().map(|s| s.len()).sum();
-    let space_per_gap = w.saturating_sub(total_label_width) / gap_count;
-    let spacer = " ".repeat(space_per_gap.max(1));
-    let spans: Vec<Span> = labels.iter().enumerate().flat_map(|(i, l)| {
-        let mut v = vec![];
-        if i > 0 { v.push(Span::raw(spacer.clone())); }
-        v.push(Span::styled(l.clone(), Style::default().fg(Color::DarkGray)));
-        v
-    }).collect();
-    f.render_widget(Paragraph::new(ratatui::text::Line::from(spans)).alignment(Alignment::Center), area);
+    let area = Rect::new(x, chart_area.y + 1, w, h);
+
+    let text: Vec<ratatui::text::Line> = lines.iter().map(|l| ratatui::text::Line::from(l.as_str())).collect();
+    f.render_widget(Clear, area);
+    f.render_widget(
+        Paragraph::new(text)
+            .block(Block::default().borders(Borders::ALL).title(format!(" {} ", snap.date)))
+            .style(Style::default().fg(theme.crosshair)),
+        area,
+    );
 }
 
-/// Renders the detail view: header, chart, volume, crosshair info, metrics.
-pub fn draw_detail_ui(
-    f: &mut Frame,
-    data: &AnalysisWithChartData,
-    area: Rect,
-    crosshair_index: Option<usize>,
-    loading_total: usize,
-    loading_done: usize,
-) {
-    let bars = filter_bars(&data.stock_data, data.time_range);
+/// Price-chart y-bounds over bars, overlays and any forecast and its band, matching the
+/// braille canvas so the y-axis labels fit either backend.
+fn price_y_bounds(data: &AnalysisWithChartData, full_start: usize, bars: &[FilteredBar], predictions: &[f64]) -> (f64, f64) {
     let n_bars = bars.len();
-    // Compute unified y-bounds including SMA/EMA/predictions (same as chart does)
-    let full_start = data.stock_data.closes.len().saturating_sub(n_bars);
     let sma10_pts = super::chart::align_overlay_for_bounds(&data.analysis.sma10_values, full_start, n_bars, 10);
     let sma50_pts = super::chart::align_overlay_for_bounds(&data.analysis.sma50_values, full_start, n_bars, 50);
     let ema20_pts = super::chart::align_overlay_for_bounds(&data.analysis.ema20_values, full_start, n_bars, 20);
+    let bands: &[(f64, f64)] = if predictions.is_empty() { &[] } else { &data.analysis.prediction_bands };
     let y_max = bars.iter().flat_map(|b| [b.high, b.low])
         .chain(sma10_pts.iter().map(|(_, y)| *y))
         .chain(sma50_pts.iter().map(|(_, y)| *y))
         .chain(ema20_pts.iter().map(|(_, y)| *y))
-        .chain(data.analysis.predictions.iter().copied())
+        .chain(predictions.iter().copied())
+        .chain(bands.iter().flat_map(|&(lo, hi)| [lo, hi]))
         .fold(f64::NEG_INFINITY, f64::max);
     let y_min = bars.iter().flat_map(|b| [b.high, b.low])
         .chain(sma10_pts.iter().map(|(_, y)| *y))
         .chain(sma50_pts.iter().map(|(_, y)| *y))
         .chain(ema20_pts.iter().map(|(_, y)| *y))
-        .chain(data.analysis.predictions.iter().copied())
+        .chain(predictions.iter().copied())
+        .chain(bands.iter().flat_map(|&(lo, hi)| [lo, hi]))
         .fold(f64::INFINITY, f64::min);
     let (y_lo, y_hi, _step) = chart::nice_y_bounds(y_min, y_max);
+    (y_lo, y_hi)
+}
 
-    // ── title ───────────────────────────────────────────
+/// Raster twin of the braille price chart, `width`×`height` pixels, for
+/// terminals with a graphics protocol. Forecast dashes are left out; the
+/// x-axis still reserves their room so the labels line up. Ex-dividend
+/// bars get a vertical line.
+pub fn detail_chart_image(
+    data: &AnalysisWithChartData,
+    window: Option<ChartWindow>,
+    crosshair_index: Option<usize>,
+    gridlines: bool,
+    dividends: &[Dividend],
+    width: usize,
+    height: usize,
+) -> Option<RgbImage> {
+    let (full_start, bars) = window_bars(&data.stock_data, data.time_range, window);
+    let n = bars.len();
+    if n == 0 {
+        return None;
+    }
+    let show_predictions = full_start + n == data.stock_data.len();
+    let predictions: &[f64] = if show_predictions { &data.analysis.predictions } else { &[] };
+    let (y_lo, y_hi) = price_y_bounds(data, full_start, &bars, predictions);
+    let x_max = chart::price_x_max(n, predictions.len()).max(1.0);
+
+    let mut plot = Plot::new(width, height, (0.0, x_max), (y_lo, y_hi), 0)?;
+    if gridlines {
+        plot.grid(chart::Y_LABELS, chart_image::GRID);
+    }
+    if let Some(last) = bars.last() {
+        plot.hline(last.close, chart_image::CROSSHAIR);
+    }
+    for i in ex_dividend_bars(data, &bars, dividends) {
+        plot.vline(i as f64, chart_image::DIVIDEND);
+    }
+    for (values, period, color) in [
+        (&data.analysis.sma50_values, 50, chart_image::SMA50),
+        (&data.analysis.sma10_values, 10, chart_image::SMA10),
+        (&data.analysis.ema20_values, 20, chart_image::EMA20),
+    ] {
+        plot.series(&chart::align_overlay_for_bounds(values, full_start, n, period), color, 1);
+    }
+    let half_width = ((plot.x_scale() * 0.4) as i64).clamp(0, 6);
+    for (i, b) in bars.iter().enumerate() {
+        plot.candle(i as f64, b.open, b.high, b.low, b.close, half_width);
+    }
+    if let Some(x) = crosshair_index {
+        plot.vline(x as f64, chart_image::CROSSHAIR);
+    }
+    Some(plot.finish())
+}
+
+/// Visible bars on which one of `dividends` went ex.
+fn ex_dividend_bars(data: &AnalysisWithChartData, bars: &[FilteredBar], dividends: &[Dividend]) -> Vec<usize> {
+    chart::ex_dividend_bars(bars, dividends.iter().map(|d| d.ex_date), data.time_range.interval().seconds())
+}
+
+/// Where the detail view put its interactive parts, for mouse hit-testing.
+pub struct DetailAreas {
+    pub chart: Rect,
+    /// The time range selector; snapshots have none.
+    pub selector: Option<Rect>,
+}
+
+/// Renders the detail view: the tab bar, then the selected tab. Only the
+/// chart tab has areas for the mouse.
+pub fn draw_detail_view(f: &mut Frame, app: &App, data: &AnalysisWithChartData, area: Rect) -> Option<DetailAreas> {
+    let theme = &app.theme;
     let v = Layout::default().direction(Direction::Vertical).constraints([
         Constraint::Length(1), Constraint::Min(0),
     ]).split(area);
+    let titles: Vec<Line> = DetailTab::ALL.iter().enumerate()
+        .map(|(i, t)| Line::from(format!("{} {}", i + 1, t.title())))
+        .collect();
+    let selected = DetailTab::ALL.iter().position(|&t| t == app.detail_tab).unwrap_or(0);
+    let tab_row = Layout::default().direction(Direction::Horizontal).constraints([
+        Constraint::Length(50), Constraint::Min(0),
+    ]).split(v[0]);
     f.render_widget(
-        Paragraph::new(format!(" {}  |  {}  |  ←→ crosshair  ↑↓ range  Esc back ", data.analysis.symbol, data.time_range.as_str()))
-            .style(Style::default().fg(Color::Yellow)),
-        v[0],
+        Tabs::new(titles)
+            .select(selected)
+            .style(Style::default().fg(theme.muted))
+            .highlight_style(Style::default().fg(theme.accent).add_modifier(Modifier::BOLD))
+            .divider("│"),
+        tab_row[0],
     );
+    // The symbol's alias and note, right-aligned beside the tabs
+    let symbol = &data.analysis.symbol;
+    let mut label = app.symbol_label(symbol);
+    if let Some(note) = app.note_of(symbol) {
+        label.push_str(&format!(" — {}", note));
+    }
+    let mut right = Vec::new();
+    let session = calendar::session(symbol, chrono::Utc::now());
+    let session_color = match session {
+        Session::Regular => theme.gain,
+        Session::PreMarket | Session::AfterHours => theme.neutral,
+        Session::Closed => theme.muted,
+    };
+    right.push(Span::styled(format!("{}  ", session.label()), Style::default().fg(session_color)));
+    if let Some(kind) = kind_label(app, symbol).filter(|k| *k != "Stock") {
+        right.push(Span::styled(format!("{}  ", kind), Style::default().fg(theme.neutral)));
+    }
+    if label != *symbol {
+        right.push(Span::styled(format!("{}  ", label), Style::default().fg(theme.text)));
+    }
+    if let Some(at) = data.fetched_at {
+        if data.cached {
+            right.push(Span::styled("cached, ", Style::default().fg(theme.neutral)));
+        }
+        right.push(Span::styled("updated ", Style::default().fg(theme.muted)));
+        right.push(age_span(app, at));
+        right.push(Span::raw(" "));
+    }
+    f.render_widget(Paragraph::new(Line::from(right)).alignment(Alignment::Right), tab_row[1]);
+
+    match app.detail_tab {
+        DetailTab::Chart => Some(draw_detail_ui(
+            f, data, v[1], app.crosshair_index,
+            app.loading_total, app.loading_done,
+            app.chart_window, app.show_gridlines,
+            app.read_only, app.status_message.as_deref(),
+            app.graphics.is_some(), &app.dividends_of(data), app.ratings.get(symbol),
+            app.insider_activity(symbol).as_ref(),
+            app.signal_history.get(symbol).map_or(&[][..], Vec::as_slice), app.zone, theme,
+        )),
+        DetailTab::Indicators => {
+            let history = app.signal_history.get(symbol).map_or(&[][..], Vec::as_slice);
+            draw_indicators_tab(f, data, &app.forecast_accuracy(symbol), history, v[1], theme);
+            None
+        }
+        DetailTab::Fundamentals => {
+            draw_fundamentals_tab(f, app, symbol, v[1], theme);
+            None
+        }
+        DetailTab::News => {
+            draw_news_tab(f, app, symbol, v[1], theme);
+            None
+        }
+        DetailTab::Filings => {
+            draw_filings_tab(f, app, symbol, v[1], theme);
+            None
+        }
+        DetailTab::Insiders => {
+            draw_insiders_tab(f, app, symbol, v[1], theme);
+            None
+        }
+        DetailTab::Backtest => {
+            draw_backtest_tab(f, app, data, v[1], theme);
+            None
+        }
+        DetailTab::Strategies => {
+            draw_strategies_tab(f, app, data, v[1], theme);
+            None
+        }
+    }
+}
+
+/// What `symbol` is, from its profile or, for indices, its symbol.
+fn kind_label(app: &App, symbol: &str) -> Option<&'static str> {
+    let kind = app.profiles.get(symbol).and_then(|p| p.kind.as_deref());
+    Some(match kind {
+        Some("EQUITY") => "Stock",
+        Some("ETF") => "ETF",
+        Some("MUTUALFUND") => "Mutual fund",
+        Some("CRYPTOCURRENCY") => "Crypto",
+        Some("CURRENCY") => "Currency",
+        Some("INDEX") => "Index",
+        _ if AssetClass::of(symbol) == AssetClass::Index => "Index",
+        _ => return None,
+    })
+}
+
+/// Dollar amount in thousands, millions, billions or trillions: `$565.7B`.
+pub(super) fn fmt_assets(v: f64) -> String {
+    match v {
+        v if v >= 1e12 => format!("${:.1}T", v / 1e12),
+        v if v >= 1e9 => format!("${:.1}B", v / 1e9),
+        v if v >= 1e6 => format!("${:.1}M", v / 1e6),
+        v => format!("${:.0}", v),
+    }
+}
+
+/// [`fmt_assets`] with a sign, for net flows: `-$1.9M`.
+pub(super) fn fmt_signed_assets(v: f64) -> String {
+    format!("{}{}", if v < 0.0 { "-" } else { "+" }, fmt_assets(v.abs()))
+}
+
+/// What the symbol's profile says about it: its type and sector, for ETFs
+/// their costs and size, and for stocks their valuation, short interest
+/// and analyst ratings, where the provider has them.
+fn draw_fundamentals_tab(f: &mut Frame, app: &App, symbol: &str, area: Rect, theme: &Theme) {
+    let profile = app.profiles.get(symbol);
+    let mut lines = Vec::new();
+    if let Some(kind) = kind_label(app, symbol) {
+        lines.push(format!("Type           {}", kind));
+    }
+    if let Some(p) = profile {
+        for (name, value) in [("Sector", &p.sector), ("Industry", &p.industry)] {
+            if let Some(value) = value {
+                lines.push(format!("{:<15}{}", name, value));
+            }
+        }
+        if let Some(ratio) = p.expense_ratio {
+            lines.push(format!("Expense ratio  {:.2}%", ratio * 100.0));
+        }
+        if let Some(assets) = p.net_assets {
+            lines.push(format!("Net assets     {}", fmt_assets(assets)));
+        }
+    }
+    if let Some(f) = app.fundamentals.get(symbol).filter(|f| !f.is_empty()) {
+        if !lines.is_empty() {
+            lines.push(String::new());
+        }
+        let figures = [
+            ("Market cap", f.market_cap.map(fmt_assets)),
+            ("P/E", f.pe.map(|v| format!("{:.2}", v))),
+            ("Forward P/E", f.forward_pe.map(|v| format!("{:.2}", v))),
+            ("EPS (TTM)", f.eps.map(|v| format_price(symbol, v))),
+            ("Profit margin", f.profit_margin.map(|v| format!("{:.2}%", v * 100.0))),
+            ("Debt/equity", f.debt_to_equity.map(|v| format!("{:.2}", v))),
+        ];
+        for (name, value) in figures {
+            lines.push(format!("{:<15}{}", name, value.unwrap_or_else(|| "--".into())));
+        }
+        if f.short_percent_of_float.is_some() || f.short_ratio.is_some() {
+            let mut short = f.short_percent_of_float.map_or_else(|| "--".into(), |v| format!("{:.2}% of float", v * 100.0));
+            if let Some(shares) = f.shares_short {
+                short.push_str(&format!(" ({} shares)", metrics::fmt_volume(shares as u64)));
+            }
+            if f.heavily_shorted() {
+                short.push_str("  ⚠ heavily shorted");
+            }
+            lines.push(format!("{:<15}{}", "Short interest", short));
+            lines.push(format!("{:<15}{}", "Days to cover", f.short_ratio.map_or_else(|| "--".into(), |v| format!("{:.1}", v))));
+        }
+    }
+    if let Some(r) = app.ratings.get(symbol).filter(|r| !r.is_empty()) {
+        if !lines.is_empty() {
+            lines.push(String::new());
+        }
+        lines.extend(analyst_lines(r, symbol, app.analyses.get(app.selected_index).map(|a| a.analysis.current_price)));
+    }
+    if lines.is_empty() {
+        draw_empty_tab(f, area, " Fundamentals ", "No fundamentals for this symbol.", theme);
+        return;
+    }
+    let text: Vec<Line> = lines.into_iter().map(Line::from).collect();
+    f.render_widget(
+        Paragraph::new(text)
+            .style(Style::default().fg(theme.text))
+            .block(Block::default().borders(Borders::ALL).title(" Fundamentals ")),
+        area,
+    );
+}
+
+/// The analysts' recommendation tally as bars, their consensus, and the
+/// price target against `price`.
+fn analyst_lines(r: &AnalystRatings, symbol: &str, price: Option<f64>) -> Vec<String> {
+    let mut lines = Vec::new();
+    if let (Some(consensus), Some(mean)) = (r.consensus(), r.mean_score()) {
+        lines.push(format!("{:<15}{} ({} analysts, mean {:.2} of 5)", "Analysts", consensus, r.analysts(), mean));
+        let counts = [("Strong buy", r.strong_buy), ("Buy", r.buy), ("Hold", r.hold), ("Sell", r.sell), ("Strong sell", r.strong_sell)];
+        let most = counts.iter().map(|&(_, n)| n).max().unwrap_or(1).max(1);
+        for (name, n) in counts {
+            lines.push(format!("  {:<13}{:>3} {}", name, n, "█".repeat((n * 20).div_ceil(most) as usize)));
+        }
+    }
+    if let Some(target) = r.target_mean {
+        let mut line = format!("{:<15}{}", "Price target", format_price(symbol, target));
+        if let Some(upside) = price.and_then(|p| r.upside(p)) {
+            line.push_str(&format!(" ({:+.1}%)", upside));
+        }
+        if let (Some(low), Some(high)) = (r.target_low, r.target_high) {
+            line.push_str(&format!(", range {} – {}", format_price(symbol, low), format_price(symbol, high)));
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// Headlines about the symbol, newest first, each with when and by whom
+/// it was published, under a gauge of their sentiment; Enter opens the
+/// highlighted one.
+fn draw_news_tab(f: &mut Frame, app: &App, symbol: &str, area: Rect, theme: &Theme) {
+    let news = app.news.get(symbol).map_or(&[][..], Vec::as_slice);
+    if news.is_empty() {
+        let message = if app.offline { "News is not fetched offline." } else { "No news for this symbol." };
+        draw_empty_tab(f, area, " News ", message, theme);
+        return;
+    }
+    let v = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(3)])
+        .split(area);
+    draw_sentiment_gauge(f, app.sentiment(symbol), app.sentiment_forecast, v[0], theme);
+    let area = v[1];
+    let items: Vec<ListItem> = news.iter().map(|n| {
+        let published = app.zone.bar_time(symbol, n.published);
+        let age = chrono::DateTime::from_timestamp(n.published, 0).map(|t| age_text(t.with_timezone(&chrono::Local)));
+        ListItem::new(Line::from(vec![
+            Span::styled(
+                format!(" {} ", published.map_or_else(|| "?".into(), |t| t.format("%b %d %H:%M").to_string())),
+                Style::default().fg(theme.muted),
+            ),
+            Span::styled(format!("{:>9} ", age.unwrap_or_default()), Style::default().fg(theme.muted)),
+            Span::styled(format!("{:<16.16} ", n.publisher), Style::default().fg(theme.accent)),
+            Span::styled(n.title.clone(), Style::default().fg(theme.text)),
+        ]))
+    }).collect();
+    let mut state = ListState::default();
+    state.select(Some(app.list_selected.min(news.len() - 1)));
+    f.render_stateful_widget(
+        List::new(items)
+            .highlight_style(Style::default().fg(theme.selection_fg).bg(theme.selection_bg))
+            .block(Block::default().borders(Borders::ALL).title(format!(" News ({}) — ↑↓ select, Enter open ", news.len()))),
+        area,
+        &mut state,
+    );
+}
+
+/// Insider buying less selling over the last three months, over every
+/// reported insider trade, newest first.
+fn draw_insiders_tab(f: &mut Frame, app: &App, symbol: &str, area: Rect, theme: &Theme) {
+    let trades = app.insider_trades.get(symbol).map_or(&[][..], Vec::as_slice);
+    if trades.is_empty() {
+        let message = if app.offline { "Insider trades are not fetched offline." } else { "No insider trades for this symbol." };
+        draw_empty_tab(f, area, " Insiders ", message, theme);
+        return;
+    }
+    let block = Block::default().borders(Borders::ALL).title(format!(" Insider trades ({}) ", trades.len()));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    let v = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(2), Constraint::Min(1)])
+        .split(inner);
+
+    let summary = match app.insider_activity(symbol) {
+        Some(net) => {
+            let shares = format!("{}{}", if net.net_shares < 0 { "-" } else { "+" }, metrics::fmt_volume(net.net_shares.unsigned_abs()));
+            let mut text = format!(
+                " Last 3 months: {} buy{}, {} sell{}, net {} shares",
+                net.buys, if net.buys == 1 { "" } else { "s" }, net.sells, if net.sells == 1 { "" } else { "s" }, shares,
+            );
+            if net.net_value != 0.0 {
+                text.push_str(&format!(" ({})", fmt_signed_assets(net.net_value)));
+            }
+            Span::styled(text, Style::default().fg(theme.signed(net.net_shares as f64)))
+        }
+        None => Span::styled(" No insider buying or selling in the last 3 months.", Style::default().fg(theme.muted)),
+    };
+    f.render_widget(Paragraph::new(Line::from(summary)), v[0]);
+
+    let header = Row::new(["Date", "Insider", "Role", "Action", "Shares", "Value"])
+        .style(Style::default().fg(theme.accent).add_modifier(Modifier::BOLD));
+    let rows: Vec<Row> = trades.iter().map(|t| {
+        let color = match t.action {
+            InsiderAction::Buy => theme.gain,
+            InsiderAction::Sell => theme.loss,
+            InsiderAction::Other => theme.muted,
+        };
+        Row::new(vec![
+            Cell::from(app.zone.format_bar(symbol, t.date, "%Y-%m-%d")),
+            Cell::from(t.insider.clone()),
+            Cell::from(t.relation.clone()),
+            Cell::from(t.action.label()).style(Style::default().fg(color)),
+            Cell::from(metrics::fmt_volume(t.shares)),
+            Cell::from(t.value.map_or_else(|| "--".into(), fmt_assets)),
+        ])
+    }).collect();
+    let widths = [
+        Constraint::Length(11),
+        Constraint::Min(16),
+        Constraint::Min(12),
+        Constraint::Length(7),
+        Constraint::Length(8),
+        Constraint::Length(9),
+    ];
+    f.render_widget(Table::new(rows).header(header).widths(&widths).style(Style::default().fg(theme.text)), v[1]);
+}
+
+/// How each forecast model would have done on the loaded bars, most
+/// accurate first, with the symbol's own model marked.
+fn draw_backtest_tab(f: &mut Frame, app: &App, data: &AnalysisWithChartData, area: Rect, theme: &Theme) {
+    let symbol = &data.analysis.symbol;
+    let results = match app.backtests.get(symbol) {
+        Some(Some(results)) => results,
+        Some(None) => return draw_empty_tab(f, area, " Backtest ", "Backtesting the forecast models…", theme),
+        None => return draw_empty_tab(f, area, " Backtest ", "No bars to backtest yet.", theme),
+    };
+    if results.is_empty() {
+        let message = format!(
+            "{} bars are too few to backtest; pick a longer range.",
+            data.stock_data.len(),
+        );
+        return draw_empty_tab(f, area, " Backtest ", &message, theme);
+    }
+    let block = Block::default().borders(Borders::ALL).title(" Backtest ");
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    let v = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(2), Constraint::Min(1)])
+        .split(inner);
+    let summary = format!(
+        " Each model fitted to {} bars and forecasting {} ahead, from up to {} points in the range. Errors are against the closes that followed.",
+        results[0].window, HORIZON, results.iter().map(|r| r.origins).max().unwrap_or(0),
+    );
+    f.render_widget(Paragraph::new(summary).style(Style::default().fg(theme.muted)).wrap(Wrap { trim: false }), v[0]);
+
+    let header = Row::new(["Model", "Forecasts", "MAE", "MAPE", "Direction", "Step 1", "Last step"])
+        .style(Style::default().fg(theme.accent).add_modifier(Modifier::BOLD));
+    let rows: Vec<Row> = results.iter().enumerate().map(|(i, r)| {
+        let mut name = r.model.label().to_string();
+        if r.model == data.analysis.forecaster {
+            name.push_str(" •");
+        }
+        let style = if i == 0 { Style::default().fg(theme.gain) } else { Style::default().fg(theme.text) };
+        Row::new(vec![
+            Cell::from(name),
+            Cell::from(r.origins.to_string()),
+            Cell::from(format_price(symbol, r.overall.mae)),
+            Cell::from(format!("{:.2}%", r.overall.mape)),
+            Cell::from(format!("{:.0}%", r.overall.hit_rate * 100.0)),
+            Cell::from(format!("{:.2}%", r.first_step.mape)),
+            Cell::from(format!("{:.2}%", r.last_step.mape)),
+        ]).style(style)
+    }).collect();
+    let widths = [
+        Constraint::Min(16),
+        Constraint::Length(10),
+        Constraint::Length(10),
+        Constraint::Length(8),
+        Constraint::Length(10),
+        Constraint::Length(8),
+        Constraint::Length(10),
+    ];
+    f.render_widget(Table::new(rows).header(header).widths(&widths), v[1]);
+}
+
+/// What `filing` is: the filer's description, or the form's name where
+/// that only repeats the form type.
+pub(super) fn filing_description(filing: &Filing) -> String {
+    if !filing.description.is_empty() && !filing.description.eq_ignore_ascii_case(&filing.form) {
+        return filing.description.clone();
+    }
+    let kind = match filing.form.trim_end_matches("/A") {
+        "10-K" => "Annual report",
+        "10-Q" => "Quarterly report",
+        _ => "Current report",
+    };
+    if filing.form.ends_with("/A") { format!("{} (amended)", kind) } else { kind.to_string() }
+}
+
+/// The symbol's recent 10-K, 10-Q and 8-K filings from EDGAR, newest
+/// first; Enter opens the highlighted one's document.
+fn draw_filings_tab(f: &mut Frame, app: &App, symbol: &str, area: Rect, theme: &Theme) {
+    let filings = app.filings.get(symbol).map_or(&[][..], Vec::as_slice);
+    if filings.is_empty() {
+        let message = if !follows_us_sessions(symbol) {
+            "EDGAR only has filings of US-listed companies."
+        } else if app.offline {
+            "Filings are not fetched offline."
+        } else {
+            "No filings for this symbol."
+        };
+        draw_empty_tab(f, area, " Filings ", message, theme);
+        return;
+    }
+    let items: Vec<ListItem> = filings.iter().map(|filing| {
+        let color = if filing.form.starts_with("8-K") { theme.text } else { theme.accent };
+        ListItem::new(Line::from(vec![
+            Span::styled(format!(" {}  ", filing.filed), Style::default().fg(theme.muted)),
+            Span::styled(format!("{:<8}", filing.form), Style::default().fg(color).add_modifier(Modifier::BOLD)),
+            Span::styled(filing_description(filing), Style::default().fg(theme.text)),
+        ]))
+    }).collect();
+    let mut state = ListState::default();
+    state.select(Some(app.list_selected.min(filings.len() - 1)));
+    f.render_stateful_widget(
+        List::new(items)
+            .highlight_style(Style::default().fg(theme.selection_fg).bg(theme.selection_bg))
+            .block(Block::default().borders(Borders::ALL).title(format!(" SEC filings ({}) — ↑↓ select, Enter open ", filings.len()))),
+        area,
+        &mut state,
+    );
+}
+
+/// A Bearish–Bullish scale with a marker at the headlines' sentiment.
+fn draw_sentiment_gauge(f: &mut Frame, sentiment: Option<Sentiment>, in_forecast: bool, area: Rect, theme: &Theme) {
+    let title = if in_forecast { " Sentiment (tilts the forecast) " } else { " Sentiment " };
+    let block = Block::default().borders(Borders::ALL).title(title);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    let Some(s) = sentiment else {
+        f.render_widget(
+            Paragraph::new(" No headline says anything clearly good or bad.").style(Style::default().fg(theme.muted)),
+            inner,
+        );
+        return;
+    };
+    let color = match s.label() {
+        "Bullish" => theme.gain,
+        "Bearish" => theme.loss,
+        _ => theme.neutral,
+    };
+    let summary = format!("  {:+.2} {} ({} of {} headlines)", s.score, s.label(), s.scored, s.total);
+    let width = (inner.width as usize).saturating_sub(summary.chars().count() + " Bearish  Bullish".len()).clamp(5, 40);
+    let marker = (((s.score + 1.0) / 2.0) * (width - 1) as f64).round() as usize;
+    let scale = |range: std::ops::Range<usize>| "─".repeat(range.len());
+    f.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::styled(" Bearish ", Style::default().fg(theme.loss)),
+            Span::styled(scale(0..marker), Style::default().fg(theme.muted)),
+            Span::styled("●", Style::default().fg(color).add_modifier(Modifier::BOLD)),
+            Span::styled(scale(marker + 1..width), Style::default().fg(theme.muted)),
+            Span::styled(" Bullish", Style::default().fg(theme.gain)),
+            Span::styled(summary, Style::default().fg(color)),
+        ])),
+        inner,
+    );
+}
+
+fn draw_empty_tab(f: &mut Frame, area: Rect, title: &str, message: &str, theme: &Theme) {
+    f.render_widget(
+        Paragraph::new(message)
+            .style(Style::default().fg(theme.muted))
+            .alignment(Alignment::Center)
+            .block(Block::default().borders(Borders::ALL).title(title)),
+        area,
+    );
+}
+
+/// Renders the chart tab: header, chart, volume, crosshair info, range
+/// selector, metrics.
+///
+/// Returns the price chart's and selector's areas so mouse events can be
+/// mapped onto bars and ranges. With `raster`, the chart interior is left
+/// empty for [`detail_chart_image`] to be drawn over.
+#[allow(clippy::too_many_arguments)]
+fn draw_detail_ui(
+    f: &mut Frame,
+    data: &AnalysisWithChartData,
+    area: Rect,
+    crosshair_index: Option<usize>,
+    loading_total: usize,
+    loading_done: usize,
+    window: Option<ChartWindow>,
+    gridlines: bool,
+    read_only: bool,
+    status: Option<&str>,
+    raster: bool,
+    dividends: &[Dividend],
+    ratings: Option<&AnalystRatings>,
+    insiders: Option<&NetActivity>,
+    signal_history: &[SignalEvent],
+    zone: DisplayZone,
+    theme: &Theme,
+) -> DetailAreas {
+    let (full_start, bars) = window_bars(&data.stock_data, data.time_range, window);
+    let n_bars = bars.len();
+    // Forecast only makes sense when the view ends at the latest bar
+    let show_predictions = full_start + n_bars == data.stock_data.len();
+    let predictions: &[f64] = if show_predictions { &data.analysis.predictions } else { &[] };
+    // Compute unified y-bounds including SMA/EMA/predictions (same as chart does)
+    let (y_lo, y_hi) = price_y_bounds(data, full_start, &bars, predictions);
+
+    // ── title ───────────────────────────────────────────
+    let v = Layout::default().direction(Direction::Vertical).constraints([
+        Constraint::Length(1), Constraint::Min(0),
+    ]).split(area);
+    let zoom = match window {
+        Some(w) => format!("  |  bars {}-{} of {}", w.start + 1, w.end, data.stock_data.len()),
+        None => String::new(),
+    };
+    let keys = if read_only {
+        "←→/mouse crosshair  +/- zoom  h/l pan  0 reset  g grid  Esc quit"
+    } else {
+        "←→/mouse crosshair  ↑↓ range  +/- zoom  h/l pan  0 reset  g grid  p prior period  w snapshot  n note  b/s paper trade  r refresh  E csv  P png  Esc back"
+    };
+    let mut header = format!(" {}  |  {}{}  |  {} ", data.analysis.symbol, data.time_range.as_str(), zoom, keys);
+    if let Some(status) = status {
+        header.push_str(&format!("|  {} ", status));
+    }
+    f.render_widget(Paragraph::new(header).style(Style::default().fg(theme.accent)), v[0]);
 
     // ── body ────────────────────────────────────────────
     let body = Layout::default().direction(Direction::Horizontal).constraints([
@@ -119,63 +720,84 @@ pub fn draw_detail_ui(
     ]).split(v[1]);
 
     let has_loading = loading_total > 0 && loading_done < loading_total;
-    let mut cc: Vec<Constraint> = vec![Constraint::Min(8), Constraint::Percentage(18)]; // chart + volume
+    let has_volume = AssetClass::of(&data.analysis.symbol).has_volume();
+    let volume_rows = if has_volume { Constraint::Percentage(18) } else { Constraint::Length(0) };
+    let mut cc: Vec<Constraint> = vec![Constraint::Min(8), volume_rows]; // chart + volume
     cc.push(Constraint::Length(1)); // x-axis
     cc.push(Constraint::Length(1)); // legend
-    if crosshair_index.is_some() { cc.push(Constraint::Length(1)); }
+    if !read_only { cc.push(Constraint::Length(1)); } // range selector
     if has_loading { cc.push(Constraint::Length(1)); }
     let chart_col = Layout::default().direction(Direction::Vertical).constraints(cc).split(body[1]);
 
     // ── Y-axis ──────────────────────────────────────────
-    draw_y_axis(f, body[0], y_lo, y_hi);
+    let current_price = bars.last().map(|b| b.close);
+    let y_area = Rect::new(body[0].x, chart_col[0].y, body[0].width, chart_col[0].height);
+    draw_y_axis(f, y_area, &data.analysis.symbol, y_lo, y_hi, current_price, theme);
 
     // ── Price chart ─────────────────────────────────────
     let title = format!(" {} | {} ", data.analysis.symbol, data.time_range.as_str());
-    let xhair_x = crosshair_index.map(|i| i as f64);
-    let full_len = data.stock_data.closes.len();
+    let ex_dividends = ex_dividend_bars(data, &bars, dividends);
+    let signals = chart::signal_bars(&bars, signal_history);
     let prev_close = if bars.len() >= 2 {
         Some(bars[bars.len() - 2].close)
     } else {
         None
     };
     let price_canvas = chart::create_price_chart(
-        &bars, full_len, &data.analysis, xhair_x, &title,
-        chart_col[0].width, prev_close,
+        &bars, full_start, &data.analysis, &title,
+        chart_col[0].width,
+        chart::PriceChartOptions {
+            crosshair_x: crosshair_index.map(|i| i as f64),
+            prev_close,
+            gridlines,
+            current_price,
+            show_predictions,
+            extended_hours: chart::marks_extended_hours(&data.analysis.symbol, data.time_range),
+            ex_dividends: &ex_dividends,
+            signals: &signals,
+        },
+        theme,
     );
-    f.render_widget(price_canvas, chart_col[0]);
+    if raster {
+        // The image is placed after the frame is drawn; leave the cells blank
+        f.render_widget(Block::default().borders(Borders::ALL).title(title.as_str()), chart_col[0]);
+    } else {
+        f.render_widget(price_canvas, chart_col[0]);
+    }
 
     // ── Volume chart ────────────────────────────────────
-    f.render_widget(
-        chart::create_volume_chart(&bars, chart_col[1].width),
-        chart_col[1],
-    );
+    if has_volume {
+        f.render_widget(
+            chart::create_volume_chart(&bars, chart_col[1].width, theme),
+            chart_col[1],
+        );
+    }
 
     // ── X-axis ──────────────────────────────────────────
-    draw_x_axis(f, chart_col[2], &data.stock_data.timestamps, n_bars, data.time_range);
+    let visible_ts: Vec<i64> = bars.iter().map(|b| b.timestamp).collect();
+    let x_max = chart::price_x_max(n_bars, predictions.len());
+    let x_area = chart::axis_row_inner(chart_col[2]);
+    f.render_widget(chart::create_x_axis(&visible_ts, &data.analysis.symbol, zone, x_area.width, x_max, theme), x_area);
 
     // ── Legend ──────────────────────────────────────────
-    f.render_widget(chart::create_legend_line(), chart_col[3]);
+    f.render_widget(chart::create_legend_line(theme), chart_col[3]);
 
     // ── Crosshair info ──────────────────────────────────
     if let Some(idx) = crosshair_index
-        && let Some(snap) = chart::crosshair_info(&bars, full_len, &data.analysis, idx)
+        && let Some(snap) = chart::crosshair_info(&bars, full_start, &data.analysis, idx, data.time_range, zone)
     {
-        let info = Paragraph::new(format!(
-            " {} │ ${:.2} │ O:${:.2} H:${:.2} L:${:.2} C:${:.2} │ Vol: {} │ SMA10: {} SMA50: {} EMA20: {} │ {}/{} ",
-            snap.date, snap.price,
-            bars[idx].open, bars[idx].high, bars[idx].low, bars[idx].close,
-            metrics::fmt_volume(snap.volume),
-            snap.sma10.map_or("--".into(), |v| format!("${:.2}", v)),
-            snap.sma50.map_or("--".into(), |v| format!("${:.2}", v)),
-            snap.ema20.map_or("--".into(), |v| format!("${:.2}", v)),
-            snap.index + 1, snap.total,
-        )).style(Style::default().fg(Color::LightYellow)).alignment(Alignment::Center);
-        f.render_widget(info, chart_col[4]);
+        draw_crosshair_box(f, chart_col[0], &data.analysis.symbol, &snap, theme);
+    }
+
+    // ── Range selector ──────────────────────────────────
+    let selector = (!read_only).then(|| chart_col[4]);
+    if let Some(area) = selector {
+        f.render_widget(render_time_range_selector(data.time_range, true), area);
     }
 
     // ── Loading indicator ────────────────────────────────
     if has_loading {
-        let load_idx = if crosshair_index.is_some() { 5 } else { 4 };
+        let load_idx = if read_only { 4 } else { 5 };
         let bar_w = 20usize;
         let filled = bar_w * loading_done / loading_total.max(1);
         let spinner = ['◐', '◓', '◑', '◒'][(loading_done * 2) % 4];
@@ -189,12 +811,15 @@ pub fn draw_detail_ui(
         );
         f.render_widget(
             Paragraph::new(load_text)
-                .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+                .style(Style::default().fg(theme.accent).add_modifier(Modifier::BOLD))
                 .alignment(Alignment::Center),
             chart_col[load_idx],
         );
     }
 
     // ── Metrics ─────────────────────────────────────────
-    metrics::draw_metrics(f, &data.analysis, &data.stock_data, body[2], data.time_range);
+    let summary = dividends::summarize(dividends, data.analysis.current_price, chrono::Utc::now().timestamp());
+    metrics::draw_metrics(f, &data.analysis, &data.stock_data, body[2], data.time_range, summary.as_ref(), ratings, insiders, theme);
+
+    DetailAreas { chart: chart_col[0], selector }
 }
@@ -1,18 +1,39 @@
 use ratatui::{
     prelude::*,
-    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, BorderType},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, BorderType},
 };
 
-use crate::app::App;
+use crate::app::{App, SymbolCheck};
+use crate::lib::{instrument::format_price, provider::Quote};
+
+use super::{metrics::fmt_symbol_volume, theme::Theme};
+
+/// Most search matches shown under the input at once.
+const MAX_SUGGESTIONS: usize = 8;
+
+/// The quote a symbol was checked with: its price, volume where it trades
+/// any and the day of the bar.
+fn quote_summary(symbol: &str, quote: &Quote) -> String {
+    let mut summary = format_price(symbol, quote.price);
+    if quote.volume > 0 {
+        summary.push_str(&format!("  vol {}", fmt_symbol_volume(symbol, quote.volume)));
+    }
+    if let Some(at) = chrono::DateTime::from_timestamp(quote.timestamp, 0).filter(|_| quote.timestamp > 0) {
+        summary.push_str(&format!("  as of {}", at.format("%Y-%m-%d")));
+    }
+    summary
+}
 
 /// Renders the user interface for the edit view where users can add/remove stocks
 pub fn draw_edit_ui(f: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
             Constraint::Length(3),        // Title
             Constraint::Length(3),        // New symbol input
             Constraint::Min(10),          // Stock list
+            Constraint::Length(1),        // Unknown symbol warning
             Constraint::Length(3),        // Instructions
         ])
         .split(area);
@@ -23,18 +44,30 @@ pub fn draw_edit_ui(f: &mut Frame, app: &App, area: Rect) {
         .border_type(BorderType::Plain);
     f.render_widget(title_block, chunks[0]);
     
-    let title = Paragraph::new("Edit Stocks - Add or Remove Symbols")
-        .style(Style::default().fg(Color::Yellow))
+    let title = Paragraph::new(format!("Edit Stocks - Add or Remove Symbols ({})", app.active_watchlist))
+        .style(Style::default().fg(theme.accent))
         .alignment(Alignment::Center);
     f.render_widget(title, chunks[0]);
 
-    // Input field for new symbols
+    // Input field for new symbols, and the watchlist's analysis period;
+    // Tab moves between them
+    let inputs = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Min(20), Constraint::Length(26)])
+        .split(chunks[1]);
+    let focused = |on: bool| Style::default().fg(if on { theme.accent } else { theme.text });
     let input_block = Block::default()
         .borders(Borders::ALL)
-        .title("Add New Symbol (Press Enter to add)");
+        .border_style(focused(!app.editing_period_focus))
+        .title("Add New Symbol or search by name (Press Enter to add)");
     let input_text = Paragraph::new(app.new_symbol_input.as_str())
         .block(input_block);
-    f.render_widget(input_text, chunks[1]);
+    f.render_widget(input_text, inputs[0]);
+    let period_block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(focused(app.editing_period_focus))
+        .title("Analysis period (Tab)");
+    f.render_widget(Paragraph::new(format!("{} days", app.editing_period)).block(period_block), inputs[1]);
 
     // Stock list with selection
     let mut list_state = ListState::default();
@@ -45,30 +78,93 @@ pub fn draw_edit_ui(f: &mut Frame, app: &App, area: Rect) {
         .iter()
         .enumerate()
         .map(|(i, symbol)| {
-            let content = if i == app.editing_selected_index {
+            let mut spans = if i == app.editing_selected_index {
                 // Highlight selected item
-                Line::from(vec![
-                    Span::styled(">", Style::default().fg(Color::Yellow)),
-                    Span::raw(format!(" {}", symbol)),
-                ])
+                vec![
+                    Span::styled(">", Style::default().fg(theme.accent)),
+                    Span::raw(format!(" {:<10}", symbol)),
+                ]
             } else {
-                Line::from(format!("  {}", symbol))
+                vec![Span::raw(format!("  {:<10}", symbol))]
             };
-            ListItem::new(content)
+            match app.symbol_checks.get(symbol) {
+                Some(SymbolCheck::Checking) => spans.push(Span::styled("checking…", Style::default().fg(theme.muted))),
+                Some(SymbolCheck::Valid) => spans.push(Span::styled("✓", Style::default().fg(theme.gain))),
+                Some(SymbolCheck::Quoted(quote)) => {
+                    spans.push(Span::styled("✓ ", Style::default().fg(theme.gain)));
+                    spans.push(Span::raw(quote_summary(symbol, quote)));
+                }
+                Some(SymbolCheck::Unknown(message)) => spans.push(Span::styled(
+                    format!("⚠ unknown ticker: {}", message),
+                    Style::default().fg(theme.loss),
+                )),
+                None => {}
+            }
+            ListItem::new(Line::from(spans))
         })
         .collect();
 
     let list = List::new(items)
-        .block(Block::default().borders(Borders::ALL).title("Current Symbols (Delete to remove)"))
-        .highlight_style(Style::default().bg(Color::DarkGray));
+        .block(Block::default().borders(Borders::ALL).title("Current Symbols in tile order (Delete to remove, Shift+Up/Down to move)"))
+        .highlight_style(Style::default().fg(theme.selection_fg).bg(theme.selection_bg));
     
     f.render_stateful_widget(list, chunks[2], &mut list_state);
 
     // Instructions
     let instructions = Paragraph::new(
-        "Up/Down: Navigate | Delete: Remove selected | Enter: Add new symbol | Ctrl+S: Save & Exit | Esc: Cancel"
+        "Tab: Symbol/period field | Up/Down: Navigate | Shift+Up/Down: Move | Delete: Remove selected | Enter: Add new symbol | Ctrl+O: Import file at typed path | Ctrl+S: Save & Exit | Esc: Cancel"
     )
-    .style(Style::default().fg(Color::Gray))
+    .style(Style::default().fg(theme.muted))
     .alignment(Alignment::Center);
-    f.render_widget(instructions, chunks[3]);
+    f.render_widget(instructions, chunks[4]);
+
+    let unknown = app.editing_symbols.iter()
+        .filter(|s| matches!(app.symbol_checks.get(*s), Some(SymbolCheck::Unknown(_))))
+        .count();
+    let checking = app.editing_symbols.iter()
+        .filter(|s| matches!(app.symbol_checks.get(*s), Some(SymbolCheck::Checking)))
+        .count();
+    let warning = match (unknown, checking, app.save_warned) {
+        (0, 0, _) => None,
+        (0, n, true) => Some(format!("{} symbol(s) still being checked — Ctrl+S again to save anyway", n)),
+        (0, _, false) => None,
+        (n, _, true) => Some(format!("{} unknown symbol(s) would fail to load — Ctrl+S again to save anyway", n)),
+        (n, _, false) => Some(format!("{} unknown symbol(s) — remove with Delete", n)),
+    };
+    if let Some(message) = warning.as_ref().or(app.edit_message.as_ref()) {
+        f.render_widget(
+            Paragraph::new(message.as_str())
+                .style(Style::default().fg(if warning.is_some() { theme.loss } else { theme.accent }))
+                .alignment(Alignment::Center),
+            chunks[3],
+        );
+    }
+
+    draw_suggestions(f, app, inputs[0], chunks[2], theme);
+}
+
+/// Dropdown of tickers matching the input, over the top of the symbol list.
+fn draw_suggestions(f: &mut Frame, app: &App, input: Rect, below: Rect, theme: &Theme) {
+    if app.suggestions.is_empty() || app.new_symbol_input.trim().is_empty() {
+        return;
+    }
+    let rows = app.suggestions.len().min(MAX_SUGGESTIONS) as u16;
+    let popup = Rect::new(input.x + 1, below.y, input.width.saturating_sub(2).min(80), rows + 2).intersection(below);
+    f.render_widget(Clear, popup);
+    let items: Vec<ListItem> = app.suggestions.iter().take(MAX_SUGGESTIONS).map(|m| {
+        ListItem::new(Line::from(vec![
+            Span::styled(format!(" {:<10}", m.symbol), Style::default().fg(theme.text).add_modifier(Modifier::BOLD)),
+            Span::styled(m.name.clone(), Style::default().fg(theme.text)),
+            Span::styled(format!("  {} · {}", m.exchange, m.kind), Style::default().fg(theme.muted)),
+        ]))
+    }).collect();
+    let mut state = ListState::default();
+    state.select(app.suggestion_selected);
+    let list = List::new(items)
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.accent))
+            .title(" Matches — ↑↓ choose, Enter add, Esc close "))
+        .highlight_style(Style::default().fg(theme.selection_fg).bg(theme.selection_bg));
+    f.render_stateful_widget(list, popup, &mut state);
 }
\ No newline at end of file
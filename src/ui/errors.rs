@@ -0,0 +1,63 @@
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
+};
+
+use crate::app::App;
+
+/// The 'x' panel of failed fetches, newest last, with the selected one's
+/// full message underneath.
+pub fn draw_error_panel(f: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
+    let width = area.width.saturating_sub(4).min(90);
+    let height = area.height.saturating_sub(2).min(app.error_log.len() as u16 + 7).max(8.min(area.height));
+    let popup = Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    );
+    f.render_widget(Clear, popup);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.loss))
+        .title(format!(" Fetch errors ({}) — r retry, R retry all, d dismiss, x close ", app.error_log.len()));
+    let inner = block.inner(popup);
+    f.render_widget(block, popup);
+
+    if app.error_log.is_empty() {
+        f.render_widget(
+            Paragraph::new("No fetch errors.").style(Style::default().fg(theme.muted)),
+            inner,
+        );
+        return;
+    }
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(3)])
+        .split(inner);
+    let items: Vec<ListItem> = app.error_log.iter().map(|e| {
+        ListItem::new(Line::from(vec![
+            Span::styled(format!(" {} ", app.zone.instant(&e.at).format("%H:%M:%S")), Style::default().fg(theme.muted)),
+            Span::styled(format!("{:<8} {:<4} ", e.symbol, e.time_range.as_str()), Style::default().fg(theme.text).add_modifier(Modifier::BOLD)),
+            Span::styled(e.message.lines().next().unwrap_or_default().to_string(), Style::default().fg(theme.loss)),
+        ]))
+    }).collect();
+    let selected = app.errors_selected.min(app.error_log.len() - 1);
+    let mut state = ListState::default();
+    state.select(Some(selected));
+    f.render_stateful_widget(
+        List::new(items).highlight_style(Style::default().fg(theme.selection_fg).bg(theme.selection_bg)),
+        chunks[0],
+        &mut state,
+    );
+
+    // Long provider messages do not fit on a row; show the selected one whole
+    let detail = Paragraph::new(app.error_log[selected].message.as_str())
+        .wrap(Wrap { trim: true })
+        .style(Style::default().fg(theme.muted))
+        .block(Block::default().borders(Borders::TOP));
+    f.render_widget(detail, chunks[1]);
+}
@@ -0,0 +1,149 @@
+//! Raster detail charts over the kitty graphics protocol or sixel. Braille
+//! characters top out at 2×4 dots per cell, which smears dense series; a
+//! pixel image of the same area does not. Terminals without either protocol
+//! keep the braille canvas.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use std::collections::HashMap;
+
+use crate::lib::{chart_image::RgbImage, config::GraphicsMode};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Protocol {
+    Kitty,
+    Sixel,
+}
+
+/// The protocol to use for `mode`; for `Auto`, guessed from the environment
+/// since a terminal query would race the event loop for stdin.
+pub fn detect(mode: GraphicsMode) -> Option<Protocol> {
+    match mode {
+        GraphicsMode::Braille => None,
+        GraphicsMode::Kitty => Some(Protocol::Kitty),
+        GraphicsMode::Sixel => Some(Protocol::Sixel),
+        GraphicsMode::Auto => {
+            let var = |k: &str| std::env::var(k).unwrap_or_default();
+            let (term, program) = (var("TERM"), var("TERM_PROGRAM"));
+            // Multiplexers swallow or garble the escapes
+            if !var("TMUX").is_empty() || term.starts_with("screen") {
+                None
+            } else if std::env::var_os("KITTY_WINDOW_ID").is_some()
+                || term == "xterm-kitty"
+                || term == "xterm-ghostty"
+                || matches!(program.as_str(), "WezTerm" | "ghostty")
+            {
+                Some(Protocol::Kitty)
+            } else if term.starts_with("foot") || term.starts_with("mlterm") || term.contains("sixel") {
+                Some(Protocol::Sixel)
+            } else {
+                None
+            }
+        }
+    }
+}
+
+/// Pixel size of one terminal cell, falling back to a common 8×16 when the
+/// terminal does not report its pixel dimensions.
+pub fn cell_pixels() -> (u16, u16) {
+    match crossterm::terminal::window_size() {
+        Ok(ws) if ws.width > 0 && ws.height > 0 && ws.columns > 0 && ws.rows > 0 => {
+            (ws.width / ws.columns, ws.height / ws.rows)
+        }
+        _ => (8, 16),
+    }
+}
+
+/// Removes every image this program placed (kitty only; sixel pixels are
+/// plain cell contents and go away on redraw).
+pub const KITTY_DELETE_ALL: &str = "\x1b_Ga=d,q=2\x1b\\";
+
+/// Transmit and display `png`, scaled to `cols`×`rows` cells at the cursor,
+/// beneath any text so popups drawn over the chart stay readable.
+pub fn kitty_sequence(png: &[u8], cols: u16, rows: u16) -> String {
+    let data = STANDARD.encode(png);
+    let chunks: Vec<&[u8]> = data.as_bytes().chunks(4096).collect();
+    let mut out = String::with_capacity(data.len() + chunks.len() * 16);
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = u8::from(i + 1 < chunks.len());
+        if i == 0 {
+            out.push_str(&format!("\x1b_Ga=T,f=100,q=2,C=1,z=-1,c={cols},r={rows},m={more};"));
+        } else {
+            out.push_str(&format!("\x1b_Gm={more};"));
+        }
+        // Base64 is ASCII, so the chunk is valid UTF-8
+        out.push_str(std::str::from_utf8(chunk).unwrap_or_default());
+        out.push_str("\x1b\\");
+    }
+    out
+}
+
+/// Encode `image` as sixel. Chart images use a handful of colours, so each
+/// distinct one gets its own register; past 256 the rest share the last.
+pub fn sixel_sequence(image: &RgbImage) -> String {
+    let mut palette: HashMap<[u8; 3], usize> = HashMap::new();
+    let mut index = Vec::with_capacity(image.width * image.height);
+    for y in 0..image.height {
+        for x in 0..image.width {
+            let next = palette.len().min(255);
+            index.push(*palette.entry(image.pixel(x, y)).or_insert(next));
+        }
+    }
+
+    let mut out = format!("\x1bP0;1q\"1;1;{};{}", image.width, image.height);
+    let mut colors: Vec<(&[u8; 3], &usize)> = palette.iter().collect();
+    colors.sort_by_key(|&(_, &i)| i);
+    for (rgb, i) in colors {
+        let pct = |c: u8| c as u32 * 100 / 255;
+        out.push_str(&format!("#{};2;{};{};{}", i, pct(rgb[0]), pct(rgb[1]), pct(rgb[2])));
+    }
+
+    for band in (0..image.height).step_by(6) {
+        let rows = (image.height - band).min(6);
+        let mut used: Vec<usize> = (band..band + rows)
+            .flat_map(|y| index[y * image.width..(y + 1) * image.width].iter().copied())
+            .collect();
+        used.sort_unstable();
+        used.dedup();
+        for (n, &color) in used.iter().enumerate() {
+            if n > 0 {
+                out.push('$');
+            }
+            out.push_str(&format!("#{}", color));
+            let sixels = (0..image.width).map(|x| {
+                (0..rows).fold(0u8, |bits, r| {
+                    if index[(band + r) * image.width + x] == color { bits | 1 << r } else { bits }
+                })
+            });
+            push_run_length(&mut out, sixels);
+        }
+        out.push('-');
+    }
+    out.push_str("\x1b\\");
+    out
+}
+
+/// Append sixel characters, collapsing runs with the `!count` repeat.
+fn push_run_length(out: &mut String, sixels: impl Iterator<Item = u8>) {
+    let flush = |out: &mut String, bits: u8, count: usize| {
+        let ch = (63 + bits) as char;
+        if count > 3 {
+            out.push_str(&format!("!{}{}", count, ch));
+        } else {
+            (0..count).for_each(|_| out.push(ch));
+        }
+    };
+    let mut run: Option<(u8, usize)> = None;
+    for bits in sixels {
+        run = match run {
+            Some((b, n)) if b == bits => Some((b, n + 1)),
+            Some((b, n)) => {
+                flush(out, b, n);
+                Some((bits, 1))
+            }
+            None => Some((bits, 1)),
+        };
+    }
+    if let Some((b, n)) = run {
+        flush(out, b, n);
+    }
+}
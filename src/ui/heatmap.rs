@@ -0,0 +1,135 @@
+use ratatui::{
+    prelude::*,
+    text::Line as TextLine,
+    widgets::{Block, Borders, Paragraph},
+};
+
+use crate::app::{AnalysisWithChartData, App, LoadState};
+use crate::data::session_change;
+use crate::lib::instrument::format_price;
+
+use super::{
+    layout::{spinner, MainHits, TileHit},
+    theme::Theme,
+};
+
+pub(super) const CELL_W: u16 = 16;
+pub(super) const CELL_H: u16 = 5;
+
+/// Bucket boundaries of the colour scale, worst (≤ -3%) to best (≥ +3%).
+const BUCKETS: [f64; 7] = [-3.0, -2.0, -1.0, 0.0, 1.0, 2.0, 3.0];
+
+/// Percent change over the latest session. Intraday ranges may hold
+/// several sessions, so it is measured from the close of the one before
+/// rather than from the previous bar.
+pub(super) fn daily_change(data: &AnalysisWithChartData) -> Option<f64> {
+    if data.time_range.is_intraday() {
+        session_change(&data.stock_data, &data.analysis.symbol)
+    } else {
+        data.analysis.recent_change
+    }
+}
+
+fn change_color(pct: f64, theme: &Theme) -> Color {
+    let bucket = (pct.round().clamp(-3.0, 3.0) + 3.0) as usize;
+    theme.heatmap[bucket]
+}
+
+/// Renders the watchlist as a grid of equally sized cells coloured by daily
+/// % change. Returns the number of columns so ↑↓ can move a whole row, and
+/// where each cell went.
+pub fn draw_heatmap_ui(f: &mut Frame, app: &App, area: Rect) -> (usize, MainHits) {
+    let v = Layout::default().direction(Direction::Vertical).constraints([
+        Constraint::Length(1), Constraint::Min(0), Constraint::Length(1), Constraint::Length(1),
+    ]).split(area);
+
+    let cols = (v[1].width / CELL_W).max(1) as usize;
+    let rows = (v[1].height / CELL_H).max(1) as usize;
+    let per_page = cols * rows;
+    let order = app.main_order();
+    let n = order.len();
+    let page = order.iter().position(|&i| i == app.selected_index).unwrap_or(0) / per_page;
+    let pages = n.div_ceil(per_page).max(1);
+    let loaded = order.iter().filter(|&&i| !app.analyses[i].stock_data.is_empty()).count();
+    let theme = &app.theme;
+
+    f.render_widget(
+        Paragraph::new(format!(
+            "Bstock - Heatmap  |  {}/{} loaded  |  Page {}/{}  |  by {}",
+            loaded, n, page + 1, pages, app.main_sort.label(),
+        )).alignment(Alignment::Center),
+        v[0],
+    );
+
+    // Spread leftover width evenly so the grid fills the screen
+    let cell_w = v[1].width / cols as u16;
+    let mut hits = MainHits { per_page, tiles: Vec::new() };
+    for (slot, &index) in order.iter().skip(page * per_page).take(per_page).enumerate() {
+        let cell = Rect::new(
+            v[1].x + (slot % cols) as u16 * cell_w,
+            v[1].y + (slot / cols) as u16 * CELL_H,
+            cell_w,
+            CELL_H,
+        );
+        hits.tiles.push(TileHit { index, area: cell, selector: None });
+        draw_cell(f, app, index, cell);
+    }
+
+    // ── colour key ──────────────────────────────────────
+    let mut key: Vec<Span> = BUCKETS.iter().zip(theme.heatmap)
+        .map(|(v, c)| Span::styled(format!(" {:+.0}% ", v), Style::default().fg(theme.text).bg(c)))
+        .collect();
+    key.push(Span::styled("  … not loaded ", Style::default().fg(theme.muted)));
+    f.render_widget(Paragraph::new(TextLine::from(key)).alignment(Alignment::Center), v[2]);
+
+    f.render_widget(
+        Paragraph::new("←→↑↓ select │ Enter details │ v switch view │ S signals │ e edit │ ? help │ q quit")
+            .style(Style::default().fg(theme.muted)),
+        v[3],
+    );
+
+    (cols, hits)
+}
+
+/// One cell: symbol, % change (or its load state) and price on a
+/// background coloured by the change.
+pub(super) fn draw_cell(f: &mut Frame, app: &App, index: usize, cell: Rect) {
+    let data = &app.analyses[index];
+    let theme = &app.theme;
+    let change = daily_change(data).filter(|_| !data.stock_data.is_empty());
+    let state = app.load_state(index);
+    let bg = match (change, state) {
+        (Some(c), _) => change_color(c, theme),
+        (None, LoadState::Failed(_)) => theme.loss,
+        (None, _) => theme.muted,
+    };
+    let placeholder = match state {
+        LoadState::Fetching => spinner().to_string(),
+        LoadState::Failed(_) => "failed".to_string(),
+        _ => "…".to_string(),
+    };
+    let lines = vec![
+        TextLine::from(Span::styled(
+            data.analysis.symbol.clone(),
+            Style::default().add_modifier(Modifier::BOLD),
+        )),
+        TextLine::from(change.map_or(placeholder, |c| format!("{:+.2}%", c))),
+        TextLine::from(if data.stock_data.is_empty() {
+            String::new()
+        } else {
+            format_price(&data.analysis.symbol, data.analysis.current_price)
+        }),
+    ];
+    let border = if index == app.selected_index {
+        Style::default().fg(theme.accent).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(theme.on_accent)
+    };
+    f.render_widget(
+        Paragraph::new(lines)
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(theme.text).bg(bg))
+            .block(Block::default().borders(Borders::ALL).border_style(border)),
+        cell,
+    );
+}
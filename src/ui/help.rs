@@ -0,0 +1,50 @@
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+use crate::app::keymap::KEYMAP;
+use crate::app::App;
+
+/// The key binding overlay, listed from the keymap table with the key
+/// remaps applied.
+pub fn draw_help_overlay(f: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
+    let mut lines = Vec::new();
+    for section in KEYMAP.iter().filter(|s| app.vim || !s.vim_only) {
+        if !lines.is_empty() {
+            lines.push(Line::from(""));
+        }
+        lines.push(Line::from(Span::styled(
+            section.title,
+            Style::default().fg(theme.accent).add_modifier(Modifier::BOLD),
+        )));
+        for binding in section.bindings {
+            lines.push(Line::from(vec![
+                Span::styled(format!("  {:<14}", binding.keys_with(&app.key_remap)), Style::default().fg(theme.text)),
+                Span::styled(binding.description, Style::default().fg(theme.muted)),
+            ]));
+        }
+    }
+
+    let width = area.width.saturating_sub(4).min(72);
+    let height = area.height.saturating_sub(2).min(lines.len() as u16 + 2);
+    let popup = Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    );
+    // Stop scrolling once the last line is in view
+    let max_scroll = (lines.len() as u16).saturating_sub(height.saturating_sub(2));
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.accent))
+        .title(" Keys — ↑↓ scroll, ? or Esc to close ");
+
+    f.render_widget(Clear, popup);
+    f.render_widget(
+        Paragraph::new(lines).block(block).scroll((app.help_scroll.min(max_scroll), 0)),
+        popup,
+    );
+}
@@ -0,0 +1,191 @@
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table},
+};
+
+use crate::app::{sma_spread, AnalysisWithChartData};
+use crate::data::calculate_volatility;
+use crate::lib::{
+    accuracy::Accuracy,
+    analysis::prediction_margin,
+    forecast::Forecaster,
+    instrument::{format_pips, format_price},
+    signals::{detect_signals, SignalEvent},
+    timezone::bar_date,
+};
+
+use super::{metrics::fmt_symbol_volume, theme::Theme};
+
+fn money(symbol: &str, v: Option<f64>) -> String {
+    v.map_or("--".into(), |v| format_price(symbol, v))
+}
+
+/// Detail-view tab with every indicator value for the loaded range, and
+/// how each model's past forecasts have done, on the left and the symbol's
+/// active signals over the timeline of past ones on the right.
+pub fn draw_indicators_tab(
+    f: &mut Frame,
+    data: &AnalysisWithChartData,
+    accuracy: &[(Forecaster, Accuracy)],
+    history: &[SignalEvent],
+    area: Rect,
+    theme: &Theme,
+) {
+    let a = &data.analysis;
+    let sd = &data.stock_data;
+    let cols = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(area);
+
+    if sd.is_empty() {
+        f.render_widget(
+            Paragraph::new("No data loaded yet.")
+                .style(Style::default().fg(theme.muted))
+                .block(Block::default().borders(Borders::ALL).title(" Indicators ")),
+            area,
+        );
+        return;
+    }
+
+    let signed = |v: Option<f64>, unit: &str| match v {
+        Some(v) => Cell::from(format!("{:+.2}{}", v, unit)).style(Style::default().fg(theme.signed(v))),
+        None => Cell::from("--"),
+    };
+    let high = sd.highs.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let low = sd.lows.iter().copied().fold(f64::INFINITY, f64::min);
+    let avg_vol = sd.volumes.iter().sum::<u64>() / sd.volumes.len().max(1) as u64;
+    let rsi = sd.rsi(14).and_then(|r| r.last().copied());
+
+    let mut rows = vec![
+        Row::new(vec![Cell::from("Close"), Cell::from(format_price(&a.symbol, a.current_price))]),
+        Row::new(vec![Cell::from("Change"), signed(a.recent_change, "%")]),
+    ];
+    // Currency moves are read in pips
+    if let [.., prev, last] = sd.closes[..]
+        && let Some(pips) = format_pips(&a.symbol, last - prev)
+    {
+        let style = Style::default().fg(theme.signed(last - prev));
+        rows.push(Row::new(vec![Cell::from("Change (pips)"), Cell::from(pips).style(style)]));
+    }
+    rows.extend([
+        Row::new(vec![Cell::from("SMA-10"), Cell::from(money(&a.symbol, a.sma_10))]),
+        Row::new(vec![Cell::from("SMA-50"), Cell::from(money(&a.symbol, a.sma_50))]),
+        Row::new(vec![Cell::from("EMA-20"), Cell::from(money(&a.symbol, a.ema_20))]),
+        Row::new(vec![Cell::from("Kalman level"), Cell::from(money(&a.symbol, a.kalman_values.last().copied()))]),
+        Row::new(vec![
+            Cell::from("Kalman trend/bar"),
+            signed(a.kalman_slope.filter(|_| a.current_price != 0.0).map(|s| s / a.current_price * 100.0), "%"),
+        ]),
+        Row::new(vec![Cell::from("SMA-10 vs SMA-50"), signed(sma_spread(a), "%")]),
+        Row::new(vec![
+            Cell::from("RSI(14)"),
+            Cell::from(rsi.map_or("--".into(), |v| format!("{:.1}", v))),
+        ]),
+        Row::new(vec![Cell::from("Volatility"), Cell::from(format!("{:.2}%", calculate_volatility(&sd.closes)))]),
+        Row::new(vec![
+            Cell::from("GARCH next bar"),
+            Cell::from(a.garch_volatility.map_or("--".into(), |v| format!("{:.2}%", v * 100.0))),
+        ]),
+        Row::new(vec![Cell::from(format!("{} high", data.time_range.as_str())), Cell::from(format_price(&a.symbol, high))]),
+        Row::new(vec![Cell::from(format!("{} low", data.time_range.as_str())), Cell::from(format_price(&a.symbol, low))]),
+        Row::new(vec![Cell::from("Avg volume"), Cell::from(fmt_symbol_volume(&a.symbol, avg_vol))]),
+    ]);
+    if !a.predictions.is_empty() {
+        rows.push(Row::new(vec![Cell::from("Forecast model"), Cell::from(a.forecaster.label())]));
+    }
+    for (i, p) in a.predictions.iter().enumerate() {
+        let forecast = match prediction_margin(a, i) {
+            Some(m) => format!("{} ± {}", format_price(&a.symbol, *p), format_price(&a.symbol, m)),
+            None => format_price(&a.symbol, *p),
+        };
+        rows.push(Row::new(vec![Cell::from(format!("Forecast day {}", i + 1)), Cell::from(forecast)]));
+    }
+    for (model, acc) in accuracy {
+        rows.push(Row::new(vec![
+            Cell::from(format!("{} record", model.label())),
+            Cell::from(format!(
+                "MAE {}  MAPE {:.1}%  hit {:.0}% of {}",
+                format_price(&a.symbol, acc.mae), acc.mape, acc.hit_rate * 100.0, acc.scored,
+            )),
+        ]));
+    }
+    let table = Table::new(rows)
+        .widths(&[Constraint::Length(20), Constraint::Min(10)])
+        .style(Style::default().fg(theme.text))
+        .block(Block::default().borders(Borders::ALL).title(format!(" Indicators | {} ", data.time_range.as_str())));
+    f.render_widget(table, cols[0]);
+
+    let signals = detect_signals(&a.symbol, sd, a);
+    let right = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(signals.len().max(1) as u16 + 2), Constraint::Min(3)])
+        .split(cols[1]);
+    let rows: Vec<Row> = signals.iter().map(|s| {
+        let date = bar_date(&a.symbol, s.timestamp).map_or("?".into(), |d| d.to_string());
+        let style = match s.kind.bias() {
+            Some(bullish) => Style::default().fg(if bullish { theme.gain } else { theme.loss }),
+            None => Style::default().fg(theme.text),
+        };
+        Row::new(vec![
+            Cell::from(date),
+            Cell::from(s.kind.label()).style(style),
+            Cell::from(s.detail.clone()),
+        ])
+    }).collect();
+    let block = Block::default().borders(Borders::ALL).title(" Active signals ");
+    if rows.is_empty() {
+        f.render_widget(
+            Paragraph::new("None").style(Style::default().fg(theme.muted)).block(block),
+            right[0],
+        );
+    } else {
+        let table = Table::new(rows)
+            .widths(&[Constraint::Length(11), Constraint::Length(20), Constraint::Min(10)])
+            .style(Style::default().fg(theme.text))
+            .block(block);
+        f.render_widget(table, right[0]);
+    }
+    draw_signal_history(f, &a.symbol, history, a.current_price, right[1], theme);
+}
+
+/// Past signals, newest first, each with the price move since and whether
+/// that went its way: ✓ it did, ✗ it did not.
+fn draw_signal_history(f: &mut Frame, symbol: &str, history: &[SignalEvent], price: f64, area: Rect, theme: &Theme) {
+    let block = Block::default().borders(Borders::ALL).title(format!(" Signal history ({}) ", history.len()));
+    if history.is_empty() {
+        f.render_widget(
+            Paragraph::new("No signals recorded yet.").style(Style::default().fg(theme.muted)).block(block),
+            area,
+        );
+        return;
+    }
+    let rows: Vec<Row> = history.iter().rev().map(|e| {
+        let date = bar_date(symbol, e.timestamp).map_or("?".into(), |d| d.to_string());
+        let style = match e.kind.bias() {
+            Some(bullish) => Style::default().fg(if bullish { theme.gain } else { theme.loss }),
+            None => Style::default().fg(theme.text),
+        };
+        let (change, played_out) = e.outcome(price);
+        let verdict = match played_out {
+            Some(true) => Cell::from("✓").style(Style::default().fg(theme.gain)),
+            Some(false) => Cell::from("✗").style(Style::default().fg(theme.loss)),
+            None => Cell::from(""),
+        };
+        Row::new(vec![
+            Cell::from(date),
+            Cell::from(e.kind.label()).style(style),
+            Cell::from(format_price(symbol, e.close)),
+            Cell::from(format!("{:+.2}%", change)).style(Style::default().fg(theme.signed(change))),
+            verdict,
+        ])
+    }).collect();
+    let header = Row::new(["Date", "Signal", "At", "Since", ""])
+        .style(Style::default().fg(theme.accent).add_modifier(Modifier::BOLD));
+    let table = Table::new(rows)
+        .header(header)
+        .widths(&[Constraint::Length(11), Constraint::Length(20), Constraint::Length(11), Constraint::Length(9), Constraint::Length(2)])
+        .style(Style::default().fg(theme.text))
+        .block(block);
+    f.render_widget(table, area);
+}
@@ -1,61 +1,61 @@
 use ratatui::{
     prelude::*,
-    widgets::{Block, Borders, Paragraph, Clear},
+    widgets::{block::Title, Block, Borders, Paragraph},
 };
 use crate::{
-    app::AnalysisWithChartData,
+    app::{App, LoadState, MainSort},
+    lib::{
+        analysis::prediction_margin,
+        instrument::format_price,
+        market::{extended_change, follows_us_sessions},
+        signals::Signal,
+    },
     ui::{
+        compact::draw_compact_ui,
         metrics::render_metrics,
         selector::render_time_range_selector,
+        status::age_span,
+        theme::Theme,
     },
 };
 
-pub fn draw_ui(
-    f: &mut Frame,
-    analyses: &[AnalysisWithChartData],
-    selected_index: usize,
-    loading_total: usize,
-    loading_done: usize,
-    loading_errors: &[String],
-) {
-    let size = f.size();
-
-    // Check if terminal is too small and display overlay if needed
-    if size.width < 100 || size.height < 35 {
-        // Create overlay for small terminal message
-        let overlay_area = Rect::new(
-            size.width.saturating_sub(50) / 2,
-            size.height.saturating_sub(10) / 2,
-            50.min(size.width),
-            10.min(size.height),
-        );
-
-        let block = Block::default()
-            .borders(Borders::ALL)
-            .title("Terminal Size Warning");
-
-        // Determine colors based on whether dimensions meet requirements
-        let width_color = if size.width >= 100 { Color::Green } else { Color::Red };
-        let height_color = if size.height >= 35 { Color::Green } else { Color::Red };
-
-        // Create colored text for dimensions
-        let text = vec![
-            ratatui::text::Line::from("Terminal size:"),
-            ratatui::text::Line::from(vec![
-                Span::raw("  Width = "),
-                Span::styled(size.width.to_string(), Style::default().fg(width_color)),
-                Span::raw("    Height = "),
-                Span::styled(size.height.to_string(), Style::default().fg(height_color)),
-            ]),
-            ratatui::text::Line::from(""),
-            ratatui::text::Line::from("Needed for current config:"),
-            ratatui::text::Line::from("  Width = 100  Height = 35"),
-        ];
-
-        let paragraph = Paragraph::new(text).block(block).alignment(Alignment::Center);
-
-        f.render_widget(Clear, overlay_area); // Clear the area to create the modal effect
-        f.render_widget(paragraph, overlay_area);
+/// Screen area of one symbol in the main view.
+pub struct TileHit {
+    pub index: usize,
+    pub area: Rect,
+    /// The tile's time range selector, if it has one.
+    pub selector: Option<Rect>,
+}
+
+/// Where the main view put each symbol, for mouse hit-testing.
+#[derive(Default)]
+pub struct MainHits {
+    /// Symbols per page, so the scroll wheel can move a page at a time.
+    pub per_page: usize,
+    pub tiles: Vec<TileHit>,
+}
+
+/// Spinner frame for the current moment; the main loop redraws often
+/// enough for it to turn.
+pub(super) fn spinner() -> char {
+    let frame = chrono::Utc::now().timestamp_millis() / 150;
+    ['◐', '◓', '◑', '◒'][frame.rem_euclid(4) as usize]
+}
+
+pub fn draw_ui(f: &mut Frame, app: &App, size: Rect) -> MainHits {
+    let analyses = &app.analyses;
+    let selected_index = app.selected_index;
+    let loading_total = app.loading_total;
+    let loading_done = app.loading_done;
+    let loading_errors = &app.loading_errors;
+    let compare_mark = app.compare_mark.as_deref();
+    let grid = app.grid;
+    let theme = &app.theme;
+    let (min_w, min_h) = grid.min_terminal();
+
+    // Too small for the tile grid: fall back to the compact list
+    if size.width < min_w || size.height < min_h {
+        draw_compact_ui(f, app, size, (min_w, min_h))
     } else {
         let chunks = Layout::default()
             .direction(Direction::Vertical)
@@ -70,24 +70,36 @@ pub fn draw_ui(
             )
             .split(size);
 
-        let num_stocks = analyses.len();
-        let num_pages = (num_stocks as f32 / 4.0).ceil() as usize;
-        let current_page = selected_index / 4 + 1;
+        // A search narrows the grid to its matches
+        let order = app.main_order();
+        let num_stocks = order.len();
+        let per_page = grid.per_page();
+        let num_pages = num_stocks.div_ceil(per_page);
+        let selected_pos = order.iter().position(|&i| i == selected_index).unwrap_or(0);
+        let current_page = selected_pos / per_page + 1;
 
+        let sorted = if app.main_sort == MainSort::Watchlist {
+            String::new()
+        } else {
+            format!("  sorted by {}", app.main_sort.label())
+        };
         let title =
-            Paragraph::new(format!("Bstock - Page {}/{}", current_page, num_pages))
+            Paragraph::new(format!("Bstock - Page {}/{}  ({} grid){}", current_page, num_pages, grid, sorted))
                 .alignment(Alignment::Center);
         f.render_widget(title, chunks[0]);
 
+        if num_stocks == 0 && app.search.is_some() {
+            f.render_widget(
+                Paragraph::new("No matching symbols").alignment(Alignment::Center).style(Style::default().fg(theme.muted)),
+                chunks[1],
+            );
+            return MainHits { per_page, tiles: Vec::new() };
+        }
         if num_stocks == 0 {
             let loading = loading_total > 0;
             let done = loading_done >= loading_total && loading_total > 0;
             let msg = if loading {
-                let pct = if loading_total > 0 {
-                    loading_done * 100 / loading_total
-                } else {
-                    0
-                };
+                let pct = (loading_done * 100).checked_div(loading_total).unwrap_or(0);
                 let bar_width = 40usize;
                 let filled = bar_width * loading_done / loading_total.max(1);
                 let bar = format!(
@@ -120,11 +132,12 @@ pub fn draw_ui(
             };
             let text = Paragraph::new(msg).alignment(Alignment::Center);
             f.render_widget(text, chunks[1]);
-            return;
+            return MainHits { per_page, tiles: Vec::new() };
         }
+        let mut hits = MainHits { per_page, tiles: Vec::new() };
 
-        let num_cols = 2;
-        let num_rows = 2;
+        let num_cols = grid.cols as usize;
+        let num_rows = grid.rows as usize;
 
         let stock_chunks = Layout::default()
             .direction(Direction::Vertical)
@@ -146,19 +159,49 @@ pub fn draw_ui(
                 .split(stock_chunks[i]);
 
             for j in 0..num_cols {
-                let index = (current_page - 1) * 4 + i * num_cols + j;
-                if index < num_stocks {
+                let slot = (current_page - 1) * per_page + i * num_cols + j;
+                if let Some(&index) = order.get(slot) {
                     let analysis_with_data = &analyses[index];
                     let analysis = &analysis_with_data.analysis;
                     let stock_data = &analysis_with_data.stock_data;
 
                     // Create a detailed block with a chart
+                    let mut title = app.symbol_label(&analysis.symbol);
+                    if let Some(note) = app.note_of(&analysis.symbol) {
+                        title.push_str(&format!(" — {}", note));
+                    }
+                    if compare_mark == Some(analysis.symbol.as_str()) {
+                        title.push_str(" ◆ compare");
+                    }
+                    let mut title = Line::from(title);
+                    // Pre-market and after-hours moves, apart from the day's
+                    if let Some((session, change)) = extended_change(stock_data)
+                        .filter(|_| follows_us_sessions(&analysis.symbol))
+                    {
+                        title.spans.push(Span::styled(
+                            format!(" {} {:+.2}%", session.short_label(), change),
+                            Style::default().fg(theme.signed(change)),
+                        ));
+                    }
+                    if app.heavily_shorted(&analysis.symbol) {
+                        title.spans.push(Span::styled(" ⚠ shorted", Style::default().fg(theme.loss)));
+                    }
                     let mut block = Block::default()
-                        .title(analysis.symbol.as_str())
+                        .title(title)
                         .borders(Borders::ALL);
+                    if let Some(at) = analysis_with_data.fetched_at {
+                        let mut age = Line::from(vec![Span::raw(" "), age_span(app, at), Span::raw(" ")]);
+                        if analysis_with_data.cached {
+                            age.spans.insert(1, Span::styled("cached ", Style::default().fg(theme.neutral)));
+                        }
+                        block = block.title(Title::from(age).alignment(Alignment::Right));
+                    }
 
+                    let state = app.load_state(index);
                     if index == selected_index {
-                        block = block.border_style(Style::default().fg(Color::Yellow));
+                        block = block.border_style(Style::default().fg(theme.accent));
+                    } else if matches!(state, LoadState::Failed(_)) {
+                        block = block.border_style(Style::default().fg(theme.loss));
                     }
 
                     // Draw the border first
@@ -176,6 +219,20 @@ pub fn draw_ui(
                         ])
                         .split(inner_area);
 
+                    if state != LoadState::Loaded {
+                        draw_tile_placeholder(f, app, index, state, content_with_selector[0]);
+                        f.render_widget(
+                            render_time_range_selector(analysis_with_data.time_range, selected_index == index),
+                            content_with_selector[1],
+                        );
+                        hits.tiles.push(TileHit {
+                            index,
+                            area: row_chunks[j],
+                            selector: Some(content_with_selector[1]),
+                        });
+                        continue;
+                    }
+
                     // Split the main content area for text, metrics and chart
                     let main_content_chunks = Layout::default()
                         .direction(Direction::Horizontal)
@@ -187,52 +244,50 @@ pub fn draw_ui(
                         .split(content_with_selector[0]);
 
                     // Render the text details
-                    let text = vec![
+                    let mut text = vec![
                         ratatui::text::Line::from(vec![
                             Span::raw("Price: "),
                             Span::styled(
-                                format!("${:.2}", analysis.current_price),
-                                Style::default().fg(Color::Green),
+                                format_price(&analysis.symbol, analysis.current_price),
+                                Style::default().fg(theme.gain),
                             ),
                         ]),
                         ratatui::text::Line::from(format!(
-                            "10-day SMA: ${:.2}",
-                            analysis.sma_10.unwrap_or(0.0)
+                            "10-day SMA: {}",
+                            format_price(&analysis.symbol, analysis.sma_10.unwrap_or(0.0))
                         )),
                         ratatui::text::Line::from(format!(
-                            "50-day SMA: ${:.2}",
-                            analysis.sma_50.unwrap_or(0.0)
+                            "50-day SMA: {}",
+                            format_price(&analysis.symbol, analysis.sma_50.unwrap_or(0.0))
                         )),
                         ratatui::text::Line::from(format!(
-                            "20-day EMA: ${:.2}",
-                            analysis.ema_20.unwrap_or(0.0)
+                            "20-day EMA: {}",
+                            format_price(&analysis.symbol, analysis.ema_20.unwrap_or(0.0))
                         )),
                         ratatui::text::Line::from(vec![
                             Span::raw("Trend: "),
                             Span::styled(
                                 format!("{:.2}%", analysis.recent_change.unwrap_or(0.0)),
                                 if analysis.recent_change.unwrap_or(0.0) > 0.0 {
-                                    Style::default().fg(Color::Green)
+                                    Style::default().fg(theme.gain)
                                 } else {
-                                    Style::default().fg(Color::Red)
+                                    Style::default().fg(theme.loss)
                                 },
                             ),
                         ]),
-                        ratatui::text::Line::from(""),
-                        ratatui::text::Line::from("Predictions:"),
-                        ratatui::text::Line::from(format!(
-                            "Day 1: ${:.2}",
-                            analysis.predictions.first().copied().unwrap_or(0.0)
-                        )),
-                        ratatui::text::Line::from(format!(
-                            "Day 2: ${:.2}",
-                            analysis.predictions.get(1).copied().unwrap_or(0.0)
-                        )),
-                        ratatui::text::Line::from(format!(
-                            "Day 3: ${:.2}",
-                            analysis.predictions.get(2).copied().unwrap_or(0.0)
-                        )),
+                        signal_badges(app.signals.get(&analysis.symbol).map_or(&[][..], Vec::as_slice), theme),
+                        ratatui::text::Line::from(format!("Predictions ({}):", analysis.forecaster.label())),
                     ];
+                    text.extend((0..3).map(|i| {
+                        let margin = prediction_margin(analysis, i)
+                            .map_or(String::new(), |m| format!(" ± {}", format_price(&analysis.symbol, m)));
+                        ratatui::text::Line::from(format!(
+                            "Day {}: {}{}",
+                            i + 1,
+                            format_price(&analysis.symbol, analysis.predictions.get(i).copied().unwrap_or(0.0)),
+                            margin,
+                        ))
+                    }));
 
                     // Render the text details
                     let paragraph = Paragraph::new(text);
@@ -243,23 +298,64 @@ pub fn draw_ui(
                         analysis,
                         stock_data,
                         analysis_with_data.time_range,
+                        app.dividend_summary(analysis_with_data).as_ref(),
+                        app.ratings.get(&analysis.symbol),
+                        app.insider_activity(&analysis.symbol).as_ref(),
+                        theme,
                     );
                     f.render_widget(metrics, main_content_chunks[1]);
 
                     // Render the chart with the selected time range (Braille Canvas)
                     let bars = crate::data::filter_bars(stock_data, analysis_with_data.time_range);
-                    let full_len = stock_data.closes.len();
                     let prev_close = if bars.len() >= 2 {
                         Some(bars[bars.len() - 2].close)
                     } else {
                         None
                     };
-                    let chart = crate::ui::chart::create_price_chart(
-                        &bars, full_len, analysis,
-                        None, analysis.symbol.as_str(),
-                        main_content_chunks[2].width, prev_close,
-                    );
-                    f.render_widget(chart, main_content_chunks[2]);
+                    let chart_rows = Layout::default()
+                        .direction(Direction::Vertical)
+                        .constraints([Constraint::Min(3), Constraint::Length(1)])
+                        .split(main_content_chunks[2]);
+                    let (min_w, min_h) = crate::ui::chart::SPARKLINE_BELOW;
+                    let crowded = chart_rows[0].width < min_w || chart_rows[0].height < min_h;
+                    let (tss, x_max): (Vec<i64>, f64) = if crowded {
+                        // Not enough room for a readable canvas: recent closes only
+                        let start = crate::ui::chart::sparkline_start(bars.len(), chart_rows[0].width);
+                        let closes: Vec<f64> = bars[start..].iter().map(|b| b.close).collect();
+                        let levels = crate::ui::chart::sparkline_levels(&closes);
+                        let rising = closes.last() >= closes.first();
+                        f.render_widget(
+                            crate::ui::chart::create_sparkline(&levels, analysis.symbol.as_str(), rising, theme),
+                            chart_rows[0],
+                        );
+                        let tss = bars[start..].iter().map(|b| b.timestamp).collect();
+                        (tss, (closes.len() as f64 - 1.0).max(1.0))
+                    } else {
+                        let chart = crate::ui::chart::create_price_chart(
+                            &bars, 0, analysis,
+                            analysis.symbol.as_str(),
+                            chart_rows[0].width,
+                            crate::ui::chart::PriceChartOptions {
+                                prev_close,
+                                gridlines: app.show_gridlines,
+                                current_price: Some(analysis.current_price),
+                                show_predictions: true,
+                                extended_hours: crate::ui::chart::marks_extended_hours(
+                                    &analysis.symbol,
+                                    analysis_with_data.time_range,
+                                ),
+                                ..Default::default()
+                            },
+                            theme,
+                        );
+                        f.render_widget(chart, chart_rows[0]);
+                        let tss = bars.iter().map(|b| b.timestamp).collect();
+                        (tss, crate::ui::chart::price_x_max(bars.len(), analysis.predictions.len()))
+                    };
+
+                    // Date ticks under the chart
+                    let x_area = crate::ui::chart::axis_row_inner(chart_rows[1]);
+                    f.render_widget(crate::ui::chart::create_x_axis(&tss, &analysis.symbol, app.zone, x_area.width, x_max, theme), x_area);
 
                     // Render the time range selector below the chart
                     let time_range_selector = render_time_range_selector(
@@ -267,6 +363,11 @@ pub fn draw_ui(
                         selected_index == index,
                     );
                     f.render_widget(time_range_selector, content_with_selector[1]);
+                    hits.tiles.push(TileHit {
+                        index,
+                        area: row_chunks[j],
+                        selector: Some(content_with_selector[1]),
+                    });
                 }
             }
         }
@@ -280,7 +381,7 @@ pub fn draw_ui(
             ])
             .split(chunks[2]);
 
-        let legend = crate::ui::chart::create_legend_line();
+        let legend = crate::ui::chart::create_legend_line(theme);
         f.render_widget(legend, bottom[0]);
 
         // Help row: left-aligned help text, right-aligned loading indicator
@@ -289,11 +390,13 @@ pub fn draw_ui(
             .constraints([Constraint::Min(0), Constraint::Length(30)])
             .split(bottom[1]);
 
-        let help = Paragraph::new(
-            "←→ select stock │ ↑↓ time range │ Enter details │ e edit │ q quit",
-        )
+        let help = Paragraph::new(if compare_mark.is_some() {
+            "←→ pick second stock │ c compare with marked │ c on marked tile to cancel │ q quit"
+        } else {
+            "←→ select stock │ ↑↓ time range │ Enter details │ v switch view │ L layout │ c compare │ s sort │ / search │ S signals │ $ paper │ P portfolio │ e edit │ ? help │ q quit"
+        })
         .alignment(Alignment::Left)
-        .style(Style::default().fg(Color::DarkGray));
+        .style(Style::default().fg(theme.muted));
         f.render_widget(help, help_row[0]);
 
         if loading_total > 0 && loading_done < loading_total {
@@ -310,8 +413,72 @@ pub fn draw_ui(
             );
             let load_widget = Paragraph::new(load_text)
                 .alignment(Alignment::Right)
-                .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD));
+                .style(Style::default().fg(theme.accent).add_modifier(Modifier::BOLD));
             f.render_widget(load_widget, help_row[1]);
+        } else if app.vim_count.is_some() || app.vim_pending_g {
+            // Vim motion being typed, as vim shows it in the corner
+            let pending = format!(
+                "{}{} ",
+                app.vim_count.map_or(String::new(), |c| c.to_string()),
+                if app.vim_pending_g { "g" } else { "" },
+            );
+            f.render_widget(
+                Paragraph::new(pending).alignment(Alignment::Right).style(Style::default().fg(theme.accent)),
+                help_row[1],
+            );
         }
+        hits
     }
 }
+
+/// Body of a tile with no data: a spinner while it is fetched, the error
+/// when the fetch failed.
+/// The active signals as badges, bullish in the gain colour and bearish in
+/// the loss colour; blank when there are none.
+fn signal_badges(signals: &[Signal], theme: &Theme) -> Line<'static> {
+    let mut spans = Vec::new();
+    for signal in signals {
+        let color = match signal.kind.bias() {
+            Some(true) => theme.gain,
+            Some(false) => theme.loss,
+            None => theme.neutral,
+        };
+        if !spans.is_empty() {
+            spans.push(Span::raw(" "));
+        }
+        spans.push(Span::styled(
+            format!("[{}]", signal.kind.badge()),
+            Style::default().fg(color).add_modifier(Modifier::BOLD),
+        ));
+    }
+    Line::from(spans)
+}
+
+fn draw_tile_placeholder(f: &mut Frame, app: &App, index: usize, state: LoadState, area: Rect) {
+    let theme = &app.theme;
+    let entry = &app.analyses[index];
+    let lines = match state {
+        LoadState::Fetching => vec![
+            Line::from(""),
+            Line::from(Span::styled(
+                format!("{} Fetching {} {}…", spinner(), entry.analysis.symbol, entry.time_range.as_str()),
+                Style::default().fg(theme.accent),
+            )),
+        ],
+        LoadState::Failed(message) => vec![
+            Line::from(""),
+            Line::from(Span::styled("failed", Style::default().fg(theme.loss).add_modifier(Modifier::BOLD))),
+            Line::from(Span::styled(message.to_string(), Style::default().fg(theme.loss))),
+            Line::from(""),
+            Line::from(Span::styled("x: errors and retry", Style::default().fg(theme.muted))),
+        ],
+        LoadState::Loaded | LoadState::NotLoaded => vec![
+            Line::from(""),
+            Line::from(Span::styled("Not loaded", Style::default().fg(theme.muted))),
+        ],
+    };
+    f.render_widget(
+        Paragraph::new(lines).alignment(Alignment::Center).wrap(ratatui::widgets::Wrap { trim: true }),
+        area,
+    );
+}
@@ -4,26 +4,43 @@ use ratatui::{
 };
 use crate::lib::{
     analysis::StockAnalysis,
+    dividends::DividendSummary,
+    insiders::NetActivity,
+    provider::AnalystRatings,
+    instrument::{format_price, AssetClass},
     stock_data::StockData,
+    timezone::bar_date,
 };
 use crate::data::{calculate_volatility, TimeRange};
 
+use super::{detail::fmt_signed_assets, theme::Theme};
+
 /// Render the metrics panel with real analysis data.
+#[allow(clippy::too_many_arguments)]
 pub fn draw_metrics(
     f: &mut Frame,
     analysis: &StockAnalysis,
     stock_data: &StockData,
     area: Rect,
     time_range: TimeRange,
+    dividends: Option<&DividendSummary>,
+    ratings: Option<&AnalystRatings>,
+    insiders: Option<&NetActivity>,
+    theme: &Theme,
 ) {
-    let widget = render_metrics(analysis, stock_data, time_range);
+    let widget = render_metrics(analysis, stock_data, time_range, dividends, ratings, insiders, theme);
     f.render_widget(widget, area);
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn render_metrics(
     analysis: &StockAnalysis,
     stock_data: &StockData,
     time_range: TimeRange,
+    dividends: Option<&DividendSummary>,
+    ratings: Option<&AnalystRatings>,
+    insiders: Option<&NetActivity>,
+    theme: &Theme,
 ) -> Paragraph<'static> {
     let high = stock_data.closes.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
     let low = stock_data.closes.iter().cloned().fold(f64::INFINITY, f64::min);
@@ -45,23 +62,59 @@ pub fn render_metrics(
         .recent_change
         .map_or_else(|| String::from("--"), |c| format!("{:+.2}%", c));
 
-    let sma10_str = analysis.sma_10.map_or_else(|| "--".into(), |v| format!("${:.2}", v));
-    let sma50_str = analysis.sma_50.map_or_else(|| "--".into(), |v| format!("${:.2}", v));
-    let ema20_str = analysis.ema_20.map_or_else(|| "--".into(), |v| format!("${:.2}", v));
+    let price = |v: f64| format_price(&analysis.symbol, v);
+    let sma10_str = analysis.sma_10.map_or_else(|| "--".into(), price);
+    let sma50_str = analysis.sma_50.map_or_else(|| "--".into(), price);
+    let ema20_str = analysis.ema_20.map_or_else(|| "--".into(), price);
+
+    let dividend = dividends.map_or_else(String::new, |d| {
+        let last = bar_date(&analysis.symbol, d.last.ex_date).map_or_else(String::new, |t| t.format("%m-%d").to_string());
+        let next = d.days_to_ex(chrono::Utc::now().timestamp()).map_or_else(|| "--".into(), |days| format!("~{}d", days));
+        format!(
+            "\n──────────────────\n\
+             Yield:  {:.2}%\n\
+             Div:    {} {}\n\
+             Ex-div: {}",
+            d.trailing_yield, price(d.last.amount), last, next,
+        )
+    });
+
+    let analysts = ratings.filter(|r| r.target_mean.is_some() || r.analysts() > 0).map_or_else(String::new, |r| {
+        let target = r.target_mean.map_or_else(|| "--".into(), price);
+        let upside = r.upside(current).map_or_else(|| "--".into(), |u| format!("{:+.1}%", u));
+        let rating = r.consensus().map_or_else(|| "--".into(), |c| format!("{} ({})", c, r.analysts()));
+        format!(
+            "\n──────────────────\n\
+             Target: {}\n\
+             Upside: {}\n\
+             Rating: {}",
+            target, upside, rating,
+        )
+    });
+
+    // Net value where the trades report one, else net shares
+    let insider = insiders.map_or_else(String::new, |net| {
+        let figure = if net.net_value != 0.0 {
+            fmt_signed_assets(net.net_value)
+        } else {
+            format!("{}{} sh", if net.net_shares < 0 { "-" } else { "+" }, fmt_volume(net.net_shares.unsigned_abs()))
+        };
+        format!("\nInsider: {} 3m", figure)
+    });
 
     // Colour-coded legend line
     let legend = "\n  ■Price  ■SMA10  ■SMA50  ■EMA20  ◆Pred";
 
     let text = format!(
-        " Price:  ${:.2}\n\
+        " Price:  {}\n\
          Change: {}\n\
          ──────────────────\n\
          SMA-10: {}\n\
          SMA-50: {}\n\
          EMA-20: {}\n\
          ──────────────────\n\
-         Hi:     ${:.2}\n\
-         Lo:     ${:.2}\n\
+         Hi:     {}\n\
+         Lo:     {}\n\
          Hi%:    {:+.2}%\n\
          Lo%:    {:+.2}%\n\
          ──────────────────\n\
@@ -69,25 +122,31 @@ pub fn render_metrics(
          AvgVol: {}\n\
          ──────────────────\n\
          Range:  {}\
+         {}\
+         {}\
+         {}\
          {}",
-        current,
+        price(current),
         change_str,
         sma10_str,
         sma50_str,
         ema20_str,
-        high,
-        low,
+        price(high),
+        price(low),
         from_high_pct,
         from_low_pct,
         volatility,
-        fmt_volume(avg_vol),
+        fmt_symbol_volume(&analysis.symbol, avg_vol),
         time_range.as_str(),
+        dividend,
+        analysts,
+        insider,
         legend,
     );
 
     Paragraph::new(text)
         .block(Block::default().borders(Borders::ALL).title(" Metrics "))
-        .style(Style::default().fg(Color::White))
+        .style(Style::default().fg(theme.text))
 }
 
 /// Compact volume formatting: 1.2M, 345K, etc.
@@ -100,3 +159,8 @@ pub fn fmt_volume(v: u64) -> String {
         v.to_string()
     }
 }
+
+/// `fmt_volume` for `symbol`, or `--` when it has no traded volume.
+pub fn fmt_symbol_volume(symbol: &str, v: u64) -> String {
+    if AssetClass::of(symbol).has_volume() { fmt_volume(v) } else { "--".into() }
+}
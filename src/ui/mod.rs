@@ -1,6 +1,25 @@
 pub mod chart;
+pub mod compact;
+pub mod compare;
 pub mod detail;
 pub mod edit;
+pub mod errors;
+pub mod graphics;
+pub mod heatmap;
+pub mod help;
+pub mod indicators;
 pub mod layout;
 pub mod metrics;
+pub mod note;
+pub mod palette;
+pub mod paper;
+pub mod plain;
+pub mod portfolio;
+pub mod sectors;
 pub mod selector;
+pub mod signals;
+pub mod status;
+pub mod strategy;
+pub mod table;
+pub mod theme;
+pub mod watchlists;
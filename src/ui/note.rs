@@ -0,0 +1,41 @@
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+use crate::app::App;
+
+/// The 'n' editor for the selected symbol's alias and note.
+pub fn draw_note_editor(f: &mut Frame, app: &App, area: Rect) {
+    let Some(edit) = &app.note_edit else { return };
+    let theme = &app.theme;
+    let width = area.width.saturating_sub(4).min(70);
+    let popup = Rect::new(area.x + (area.width - width) / 2, area.y + area.height / 4, width, 4);
+    let popup = popup.intersection(area);
+    f.render_widget(Clear, popup);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.accent))
+        .title(format!(" {} — Tab next field, Enter save, Esc cancel ", edit.symbol));
+    let inner = block.inner(popup);
+    f.render_widget(block, popup);
+
+    let field = |label: &str, value: &str, active: bool| {
+        let mut spans = vec![
+            Span::styled(format!("{:<7}", label), Style::default().fg(if active { theme.accent } else { theme.muted })),
+            Span::styled(value.to_string(), Style::default().fg(theme.text)),
+        ];
+        if active {
+            spans.push(Span::styled("█", Style::default().fg(theme.muted)));
+        }
+        Line::from(spans)
+    };
+    f.render_widget(
+        Paragraph::new(vec![
+            field("Alias", &edit.alias, !edit.on_note),
+            field("Note", &edit.note, edit.on_note),
+        ]),
+        inner,
+    );
+}
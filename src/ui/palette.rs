@@ -0,0 +1,69 @@
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+};
+
+use crate::app::App;
+
+/// Most matches listed at once; the list scrolls past this.
+const MAX_ROWS: u16 = 12;
+
+/// The ':' command palette: an input line with the matching commands below,
+/// or just the input while a command asks for its argument.
+pub fn draw_palette(f: &mut Frame, app: &App, area: Rect) {
+    let Some(palette) = &app.palette else { return };
+    let theme = &app.theme;
+    let matches = app.palette_matches();
+    let prompt = palette.pending.and_then(|c| c.prompt());
+    let secret = palette.pending.is_some_and(|c| c.is_secret());
+
+    let rows = if prompt.is_some() { 0 } else { (matches.len() as u16).clamp(1, MAX_ROWS) };
+    let width = area.width.saturating_sub(4).min(60);
+    let height = (rows + 4).min(area.height);
+    let popup = Rect::new(area.x + (area.width - width) / 2, area.y + area.height / 6, width, height);
+    let popup = popup.intersection(area);
+    f.render_widget(Clear, popup);
+
+    let title = match prompt {
+        Some(p) => format!(" {} — Enter to confirm, Esc to cancel ", p),
+        None => " Command — type to filter, Enter to run ".to_string(),
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.accent))
+        .title(title);
+    let inner = block.inner(popup);
+    f.render_widget(block, popup);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(2), Constraint::Min(0)])
+        .split(inner);
+    let input = Line::from(vec![
+        Span::styled(": ", Style::default().fg(theme.accent)),
+        Span::styled(
+            if secret { "•".repeat(palette.query.chars().count()) } else { palette.query.clone() },
+            Style::default().fg(theme.text),
+        ),
+        Span::styled("█", Style::default().fg(theme.muted)),
+    ]);
+    f.render_widget(Paragraph::new(input), chunks[0]);
+
+    if prompt.is_some() {
+        return;
+    }
+    if matches.is_empty() {
+        f.render_widget(
+            Paragraph::new("No matching command").style(Style::default().fg(theme.muted)),
+            chunks[1],
+        );
+        return;
+    }
+    let items: Vec<ListItem> = matches.iter().map(|(label, _)| ListItem::new(format!(" {}", label))).collect();
+    let mut state = ListState::default();
+    state.select(Some(palette.selected.min(matches.len() - 1)));
+    let list = List::new(items)
+        .style(Style::default().fg(theme.text))
+        .highlight_style(Style::default().fg(theme.selection_fg).bg(theme.selection_bg));
+    f.render_stateful_widget(list, chunks[1], &mut state);
+}
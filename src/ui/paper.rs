@@ -0,0 +1,187 @@
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table, TableState},
+};
+
+use crate::app::App;
+use crate::lib::{instrument::format_price, paper::Side};
+
+/// Shares without trailing zeros: `10`, `0.25`.
+pub(super) fn fmt_shares(shares: f64) -> String {
+    let text = format!("{:.4}", shares);
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+pub(super) fn fmt_money(v: f64) -> String {
+    format!("{}${:.2}", if v < 0.0 { "-" } else { "" }, v.abs())
+}
+
+/// The paper trading account: cash and equity, each position at the
+/// latest price with its unrealized gain, and the fills so far.
+pub fn draw_paper_ui(f: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
+    let account = &app.paper;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(5), Constraint::Length(10), Constraint::Length(1)])
+        .split(area);
+
+    let equity = account.equity(|s| app.live_price(s));
+    let invested = equity - account.cash;
+    let total = if account.starting_cash > 0.0 { (equity / account.starting_cash - 1.0) * 100.0 } else { 0.0 };
+    let realized: f64 = account.fills.iter().filter_map(|fill| fill.realized).sum();
+    let summary = Line::from(vec![
+        Span::styled(" Cash ", Style::default().fg(theme.muted)),
+        Span::raw(fmt_money(account.cash)),
+        Span::styled("  Positions ", Style::default().fg(theme.muted)),
+        Span::raw(fmt_money(invested)),
+        Span::styled("  Equity ", Style::default().fg(theme.muted)),
+        Span::styled(fmt_money(equity), Style::default().add_modifier(Modifier::BOLD)),
+        Span::styled(format!(" {:+.2}%", total), Style::default().fg(theme.signed(total))),
+        Span::styled(format!(" since {}", fmt_money(account.starting_cash)), Style::default().fg(theme.muted)),
+        Span::styled("  Realized ", Style::default().fg(theme.muted)),
+        Span::styled(fmt_money(realized), Style::default().fg(theme.signed(realized))),
+    ]);
+    f.render_widget(
+        Paragraph::new(summary).block(Block::default().borders(Borders::ALL).title(" Paper trading ")),
+        chunks[0],
+    );
+
+    let header = Row::new(["Symbol", "Shares", "Avg cost", "Price", "Value", "Unrealized", "%"])
+        .style(Style::default().fg(theme.accent).add_modifier(Modifier::BOLD));
+    let rows: Vec<Row> = account.positions.iter()
+        .map(|(symbol, position)| {
+            let price = app.live_price(symbol);
+            let value = position.shares * price.unwrap_or(position.cost);
+            let gain = value - position.shares * position.cost;
+            let pct = if position.cost > 0.0 { gain / (position.shares * position.cost) * 100.0 } else { 0.0 };
+            let signed = |text: String| Cell::from(text).style(Style::default().fg(theme.signed(gain)));
+            Row::new(vec![
+                Cell::from(symbol.clone()),
+                Cell::from(fmt_shares(position.shares)),
+                Cell::from(format_price(symbol, position.cost)),
+                match price {
+                    Some(p) => Cell::from(format_price(symbol, p)),
+                    None => Cell::from("not loaded").style(Style::default().fg(theme.muted)),
+                },
+                Cell::from(fmt_money(value)),
+                signed(fmt_money(gain)),
+                signed(format!("{:+.2}%", pct)),
+            ])
+        })
+        .collect();
+    let table = Table::new(rows)
+        .header(header)
+        .widths(&[
+            Constraint::Length(10),
+            Constraint::Length(10),
+            Constraint::Length(12),
+            Constraint::Length(12),
+            Constraint::Length(14),
+            Constraint::Length(13),
+            Constraint::Length(9),
+        ])
+        .style(Style::default().fg(theme.text))
+        .highlight_style(Style::default().fg(theme.selection_fg).bg(theme.selection_bg))
+        .block(Block::default().borders(Borders::ALL).title(format!(" {} positions ", account.positions.len())));
+    let mut state = TableState::default();
+    if !account.positions.is_empty() {
+        state.select(Some(app.paper_selected.min(account.positions.len() - 1)));
+    }
+    f.render_stateful_widget(table, chunks[1], &mut state);
+
+    let header = Row::new(["Time", "Side", "Symbol", "Shares", "Price", "Commission", "Realized"])
+        .style(Style::default().fg(theme.accent).add_modifier(Modifier::BOLD));
+    let rows: Vec<Row> = account.fills.iter().rev()
+        .map(|fill| {
+            let time = chrono::DateTime::from_timestamp(fill.timestamp, 0)
+                .map(|t| app.zone.instant(&t).format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_default();
+            let side = match fill.side {
+                Side::Buy => Cell::from("Buy").style(Style::default().fg(theme.gain)),
+                Side::Sell => Cell::from("Sell").style(Style::default().fg(theme.loss)),
+            };
+            Row::new(vec![
+                Cell::from(time),
+                side,
+                Cell::from(fill.symbol.clone()),
+                Cell::from(fmt_shares(fill.shares)),
+                Cell::from(format_price(&fill.symbol, fill.price)),
+                Cell::from(fmt_money(fill.commission)),
+                fill.realized.map_or(Cell::from(""), |r| Cell::from(fmt_money(r)).style(Style::default().fg(theme.signed(r)))),
+            ])
+        })
+        .collect();
+    let fills = Table::new(rows)
+        .header(header)
+        .widths(&[
+            Constraint::Length(17),
+            Constraint::Length(5),
+            Constraint::Length(10),
+            Constraint::Length(10),
+            Constraint::Length(12),
+            Constraint::Length(11),
+            Constraint::Length(12),
+        ])
+        .style(Style::default().fg(theme.text))
+        .block(Block::default().borders(Borders::ALL).title(format!(" Fills ({}) ", account.fills.len())));
+    f.render_widget(fills, chunks[2]);
+
+    let help = Paragraph::new("↑↓ select │ b buy more │ s sell │ Enter open details │ b/s in a symbol's details trade it │ Esc back")
+        .style(Style::default().fg(theme.muted));
+    f.render_widget(help, chunks[3]);
+}
+
+/// The 'b' / 's' paper order for a symbol: shares, what it comes to at the
+/// latest price, and why it did not fill.
+pub fn draw_order_ticket(f: &mut Frame, app: &App, area: Rect) {
+    let Some(ticket) = &app.order else { return };
+    let theme = &app.theme;
+    let width = area.width.saturating_sub(4).min(70);
+    let popup = Rect::new(area.x + (area.width - width) / 2, area.y + area.height / 4, width, 5);
+    let popup = popup.intersection(area);
+    f.render_widget(Clear, popup);
+
+    let color = if ticket.side == Side::Buy { theme.gain } else { theme.loss };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(color))
+        .title(format!(" Paper {} {} — Tab max, Enter fill, Esc cancel ", ticket.side.label().to_lowercase(), ticket.symbol));
+    let inner = block.inner(popup);
+    f.render_widget(block, popup);
+
+    let price = app.live_price(&ticket.symbol);
+    let shares = ticket.shares.trim().parse::<f64>().unwrap_or(0.0);
+    let commission = app.strategy.commission;
+    let muted = Style::default().fg(theme.muted);
+    let input = Line::from(vec![
+        Span::styled("Shares ", Style::default().fg(color)),
+        Span::styled(ticket.shares.clone(), Style::default().fg(theme.text)),
+        Span::styled("█", muted),
+    ]);
+    let amounts = match price {
+        Some(price) => {
+            let (label, total, cash_after) = match ticket.side {
+                Side::Buy => ("Cost", shares * price + commission, app.paper.cash - shares * price - commission),
+                Side::Sell => ("Proceeds", shares * price - commission, app.paper.cash + shares * price - commission),
+            };
+            Line::from(vec![
+                Span::styled("at ", muted),
+                Span::raw(format_price(&ticket.symbol, price)),
+                Span::styled(format!("  {} ", label), muted),
+                Span::raw(fmt_money(total)),
+                Span::styled("  cash after ", muted),
+                Span::raw(fmt_money(cash_after)),
+            ])
+        }
+        None => Line::styled("No price loaded", muted),
+    };
+    let note = match &ticket.error {
+        Some(e) => Line::styled(e.clone(), Style::default().fg(theme.loss)),
+        None => Line::styled(
+            format!("Held {}, cash {}", fmt_shares(app.paper.held(&ticket.symbol)), fmt_money(app.paper.cash)),
+            muted,
+        ),
+    };
+    f.render_widget(Paragraph::new(vec![input, amounts, note]), inner);
+}
@@ -0,0 +1,320 @@
+//! Plain output mode: linear, labelled text with no canvas graphics, box
+//! drawing or colour-only cues, so terminal screen readers can follow it.
+
+use ratatui::{
+    prelude::*,
+    widgets::{Paragraph, Wrap},
+};
+
+use crate::app::{AnalysisWithChartData, App, MainMode};
+use crate::data::{calculate_volatility, join_on_time, normalized_pct, prior_period, window_bars};
+use crate::lib::{analysis::prediction_margin, calendar, instrument::format_price, signals::detect_signals, stock_data::StockData, timezone::bar_date};
+
+use super::status::age_text;
+
+use super::{chart, detail, metrics};
+
+fn money(symbol: &str, v: Option<f64>) -> String {
+    v.map_or("not available".into(), |v| format_price(symbol, v))
+}
+
+fn percent(v: Option<f64>) -> String {
+    v.map_or("not available".into(), |v| format!("{:+.2}%", v))
+}
+
+fn loading_line(app: &App) -> Option<String> {
+    (app.loading_total > 0 && app.loading_done < app.loading_total)
+        .then(|| format!("Loading: {} of {} fetched.", app.loading_done, app.loading_total))
+}
+
+fn render(f: &mut Frame, area: Rect, lines: Vec<String>, scroll: u16) {
+    let text: Vec<Line> = lines.into_iter().map(Line::from).collect();
+    f.render_widget(Paragraph::new(text).wrap(Wrap { trim: false }).scroll((scroll, 0)), area);
+}
+
+/// Watchlist as one line per symbol, the selected one marked in words.
+pub fn draw_plain_main(f: &mut Frame, app: &App, area: Rect) {
+    let n = app.analyses.len();
+    let mut lines = vec![format!("bstock watchlist {}, {} symbols.", app.active_watchlist, n)];
+    if app.watchlists.len() > 1 {
+        let i = app.watchlists.iter().position(|w| *w == app.active_watchlist).unwrap_or(0);
+        lines.push(format!("Watchlist {} of {}. Tab switches to the next one.", i + 1, app.watchlists.len()));
+    }
+    lines.extend(loading_line(app));
+    let sectors = app.main_mode == MainMode::Sectors;
+    let order = if sectors { app.sector_order() } else { app.main_order() };
+    if let Some(query) = &app.search {
+        lines.push(format!("Search: {}, {} matching. Enter keeps the selection, Escape cancels.", query, order.len()));
+    }
+    lines.push(String::new());
+
+    let mut selected_row = lines.len();
+    for (pos, &i) in order.iter().enumerate() {
+        let a = &app.analyses[i];
+        if sectors && (pos == 0 || app.sector_of(order[pos - 1]) != app.sector_of(i)) {
+            lines.push(format!("Sector {}:", app.sector_of(i)));
+        }
+        if i == app.selected_index {
+            selected_row = lines.len();
+        }
+        let marker = if i == app.selected_index { "Selected: " } else { "" };
+        let body = if a.stock_data.is_empty() {
+            "not loaded".to_string()
+        } else {
+            format!(
+                "price {}, change {}{}",
+                format_price(&a.analysis.symbol, a.analysis.current_price),
+                percent(a.analysis.recent_change),
+                a.fetched_at.map(|at| format!(
+                    ", {}updated {}", if a.cached { "cached, " } else { "" }, age_text(at),
+                )).unwrap_or_default(),
+            )
+        };
+        lines.push(format!(
+            "{}{} of {}: {}, {}, range {}.",
+            marker, i + 1, n, app.symbol_label(&a.analysis.symbol), body, a.time_range.as_str(),
+        ));
+    }
+    if n == 0 {
+        lines.push("No symbols. Press e to edit the watchlist.".into());
+    }
+
+    lines.push(String::new());
+    lines.push("Keys: left and right select, up and down change range, Enter details, \
+                c compare, slash search, S signals, e edit, question mark help, q quit.".into());
+
+    // Keep the selected row on screen
+    let scroll = selected_row.saturating_sub(area.height as usize / 2) as u16;
+    render(f, area, lines, scroll);
+}
+
+/// Detail view as labelled lines: values, indicators, forecast, signals and
+/// the bar under the cursor.
+pub fn draw_plain_detail(f: &mut Frame, app: &App, data: &AnalysisWithChartData, area: Rect) {
+    let sd = &data.stock_data;
+    let a = &data.analysis;
+    let mut lines = vec![format!("{} details, range {}.", app.symbol_label(&a.symbol), data.time_range.as_str())];
+    if let Some(at) = data.fetched_at {
+        let source = if data.cached { "From the cache, updated" } else { "Updated" };
+        lines.push(format!("{} {}.", source, age_text(at)));
+    }
+    if let Some(note) = app.note_of(&a.symbol) {
+        lines.push(format!("Note: {}", note));
+    }
+    lines.push(format!("Market: {}.", calendar::session(&a.symbol, chrono::Utc::now()).label()));
+    lines.extend(loading_line(app));
+    if let Some(status) = &app.status_message {
+        lines.push(format!("{}.", status));
+    }
+
+    if sd.is_empty() {
+        lines.push("No data loaded yet.".into());
+    } else {
+        let (start, bars) = window_bars(sd, data.time_range, app.chart_window);
+        lines.push(match app.chart_window {
+            Some(w) => format!("Showing bars {} to {} of {}.", w.start + 1, w.end, sd.len()),
+            None => format!("Showing all {} bars.", sd.len()),
+        });
+        let hi = sd.highs.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let lo = sd.lows.iter().copied().fold(f64::INFINITY, f64::min);
+        let avg_vol = sd.volumes.iter().sum::<u64>() / sd.volumes.len().max(1) as u64;
+        lines.extend([
+            String::new(),
+            format!("Last price: {}.", format_price(&a.symbol, a.current_price)),
+            format!("Change from previous bar: {}.", percent(a.recent_change)),
+            format!("Range high: {}. Range low: {}.", format_price(&a.symbol, hi), format_price(&a.symbol, lo)),
+            format!("SMA 10: {}. SMA 50: {}. EMA 20: {}.", money(&a.symbol, a.sma_10), money(&a.symbol, a.sma_50), money(&a.symbol, a.ema_20)),
+            format!(
+                "RSI 14: {}.",
+                sd.rsi(14).and_then(|r| r.last().copied()).map_or("not available".into(), |v| format!("{:.1}", v)),
+            ),
+            format!("Volatility: {:.2}%. Average volume: {}.", calculate_volatility(&sd.closes), metrics::fmt_symbol_volume(&a.symbol, avg_vol)),
+        ]);
+        if let Some(v) = a.garch_volatility {
+            lines.push(format!("GARCH volatility for the next bar: {:.2}%.", v * 100.0));
+        }
+
+        let preds: Vec<String> = a.predictions.iter().take(3).enumerate()
+            .map(|(i, p)| match prediction_margin(a, i) {
+                Some(m) => format!("step {} {} plus or minus {}", i + 1, format_price(&a.symbol, *p), format_price(&a.symbol, m)),
+                None => format!("step {} {}", i + 1, format_price(&a.symbol, *p)),
+            })
+            .collect();
+        lines.push(if preds.is_empty() {
+            "Forecast: not available.".into()
+        } else {
+            format!("Forecast ({}): {}.", a.forecaster.label(), preds.join(", "))
+        });
+        for (model, acc) in app.forecast_accuracy(&a.symbol) {
+            lines.push(format!(
+                "Past {} forecasts: {} scored, average error {} or {:.1}%, direction right {:.0}% of the time.",
+                model.label(), acc.scored, format_price(&a.symbol, acc.mae), acc.mape, acc.hit_rate * 100.0,
+            ));
+        }
+        if let Some(f) = app.fundamentals.get(&a.symbol).filter(|f| !f.is_empty()) {
+            let figures: Vec<String> = [
+                ("market cap", f.market_cap.map(detail::fmt_assets)),
+                ("P/E", f.pe.map(|v| format!("{:.2}", v))),
+                ("forward P/E", f.forward_pe.map(|v| format!("{:.2}", v))),
+                ("EPS", f.eps.map(|v| format_price(&a.symbol, v))),
+                ("profit margin", f.profit_margin.map(|v| format!("{:.2}%", v * 100.0))),
+                ("debt to equity", f.debt_to_equity.map(|v| format!("{:.2}", v))),
+                ("short interest", f.short_percent_of_float.map(|v| format!("{:.2}% of float", v * 100.0))),
+                ("days to cover", f.short_ratio.map(|v| format!("{:.1}", v))),
+            ].into_iter().filter_map(|(name, value)| Some(format!("{} {}", name, value?))).collect();
+            lines.push(format!("Fundamentals: {}.", figures.join(", ")));
+            if f.heavily_shorted() {
+                lines.push("Heavily shorted.".into());
+            }
+        }
+        if let Some(r) = app.ratings.get(&a.symbol).filter(|r| !r.is_empty()) {
+            let mut parts = Vec::new();
+            if let Some(consensus) = r.consensus() {
+                parts.push(format!(
+                    "{} from {} analysts ({} strong buy, {} buy, {} hold, {} sell, {} strong sell)",
+                    consensus.to_lowercase(), r.analysts(), r.strong_buy, r.buy, r.hold, r.sell, r.strong_sell,
+                ));
+            }
+            if let Some(target) = r.target_mean {
+                let upside = r.upside(a.current_price).map_or_else(String::new, |u| format!(", {:+.1}% from the last price", u));
+                parts.push(format!("mean price target {}{}", format_price(&a.symbol, target), upside));
+            }
+            lines.push(format!("Analysts: {}.", parts.join("; ")));
+        }
+        if let Some(d) = app.dividend_summary(data) {
+            let date = |t: i64| bar_date(&a.symbol, t).map(|d| d.to_string()).unwrap_or_default();
+            let mut line = format!(
+                "Dividends: trailing yield {:.2}% ({} over the past year); last {} went ex on {}",
+                d.trailing_yield, format_price(&a.symbol, d.trailing_amount),
+                format_price(&a.symbol, d.last.amount), date(d.last.ex_date),
+            );
+            if let (Some(frequency), Some(next)) = (d.frequency, d.next_ex_date) {
+                line.push_str(&format!(
+                    "; paid {}, next ex-date expected around {}",
+                    frequency.label().to_lowercase(), date(next),
+                ));
+            }
+            lines.push(format!("{}.", line));
+        }
+        if let Some(net) = app.insider_activity(&a.symbol) {
+            let mut line = format!(
+                "Insiders over the last 3 months: {} buys, {} sells, net {:+} shares",
+                net.buys, net.sells, net.net_shares,
+            );
+            if net.net_value != 0.0 {
+                line.push_str(&format!(" ({})", detail::fmt_signed_assets(net.net_value)));
+            }
+            lines.push(format!("{}.", line));
+        }
+        if let Some(filings) = app.filings.get(&a.symbol).filter(|f| !f.is_empty()) {
+            lines.push("SEC filings:".into());
+            for filing in filings.iter().take(5) {
+                lines.push(format!("  {} {}, filed {}.", filing.form, detail::filing_description(filing), filing.filed));
+            }
+        }
+        if let Some(news) = app.news.get(&a.symbol).filter(|n| !n.is_empty()) {
+            if let Some(s) = app.sentiment(&a.symbol) {
+                lines.push(format!(
+                    "News sentiment: {} ({:+.2}, from {} of {} headlines).",
+                    s.label(), s.score, s.scored, s.total,
+                ));
+            }
+            lines.push("Headlines:".into());
+            for n in news.iter().take(5) {
+                let date = bar_date(&a.symbol, n.published).map(|d| d.to_string()).unwrap_or_default();
+                let source = if n.publisher.is_empty() { String::new() } else { format!(", {}", n.publisher) };
+                lines.push(format!("  {} ({}{}).", n.title.trim_end_matches('.'), date, source));
+            }
+        }
+
+        let signals = detect_signals(&a.symbol, sd, a);
+        if signals.is_empty() {
+            lines.push("Active signals: none.".into());
+        } else {
+            lines.push(format!("Active signals: {}.", signals.len()));
+            for s in &signals {
+                let date = bar_date(&a.symbol, s.timestamp).map(|d| d.to_string()).unwrap_or_default();
+                lines.push(format!("Signal: {}, since {}, {}.", s.kind.label(), date, s.detail));
+            }
+        }
+
+        if let Some(idx) = app.crosshair_index
+            && let Some(snap) = chart::crosshair_info(&bars, start, a, idx, data.time_range, app.zone)
+        {
+            lines.extend([
+                String::new(),
+                format!("Cursor: bar {} of {}, {}.", snap.index + 1, snap.total, snap.date),
+                format!(
+                    "Open {}. High {}. Low {}. Close {}. Volume {}.",
+                    format_price(&a.symbol, snap.open),
+                    format_price(&a.symbol, snap.high),
+                    format_price(&a.symbol, snap.low),
+                    format_price(&a.symbol, snap.price),
+                    metrics::fmt_symbol_volume(&a.symbol, snap.volume),
+                ),
+                format!("SMA 10: {}. SMA 50: {}. EMA 20: {}.", money(&a.symbol, snap.sma10), money(&a.symbol, snap.sma50), money(&a.symbol, snap.ema20)),
+            ]);
+        }
+    }
+
+    lines.push(String::new());
+    lines.push(if app.read_only {
+        "Keys: left and right move the cursor, plus and minus zoom, h and l pan, 0 reset, Escape quit.".into()
+    } else {
+        "Keys: left and right move the cursor, up and down change range, plus and minus zoom, \
+         h and l pan, 0 reset, p prior period, w snapshot, Escape back.".into()
+    });
+    render(f, area, lines, 0);
+}
+
+/// Comparison as the total change of each symbol and the spread between them.
+pub fn draw_plain_compare(f: &mut Frame, app: &App, first: &AnalysisWithChartData, second: &AnalysisWithChartData, area: Rect) {
+    let daily = !second.time_range.is_intraday();
+    let (ts, ca, cb) = join_on_time(&first.stock_data, &first.analysis.symbol, &second.stock_data, &second.analysis.symbol, daily);
+    let m = ts.len();
+    let mut lines = vec![format!(
+        "Comparing {} with {}, range {}.",
+        first.analysis.symbol, second.analysis.symbol, second.time_range.as_str(),
+    )];
+    lines.extend(loading_line(app));
+    if m == 0 {
+        lines.push("No overlapping data to compare yet.".into());
+    } else {
+        let last = |c: &[f64]| normalized_pct(c).last().copied().unwrap_or(0.0);
+        let (pa, pb) = (last(&ca), last(&cb));
+        lines.extend([
+            format!("Over the {} bars both have:", m),
+            format!("{} changed {:+.2}%.", first.analysis.symbol, pa),
+            format!("{} changed {:+.2}%.", second.analysis.symbol, pb),
+            format!("Spread: {:+.2} percentage points.", pa - pb),
+        ]);
+    }
+    lines.push(String::new());
+    lines.push("Keys: up and down change range, Escape back.".into());
+    render(f, area, lines, 0);
+}
+
+/// Prior-period comparison as the total change over each period.
+pub fn draw_plain_prior(f: &mut Frame, app: &App, data: &AnalysisWithChartData, history: Option<&StockData>, area: Rect) {
+    let range = data.time_range.as_str();
+    let mut lines = vec![format!("{}: {} compared with the prior {}.", data.analysis.symbol, range, range)];
+    lines.extend(loading_line(app));
+    let split = history.zip(data.stock_data.timestamps.first())
+        .map(|(h, &t0)| prior_period(h, t0, &data.analysis.symbol, data.time_range))
+        .filter(|(prior, _)| !prior.is_empty());
+    match split {
+        Some((prior, current)) => {
+            let total = |c: &[f64]| normalized_pct(c).last().copied().unwrap_or(0.0);
+            let (pc, pp) = (total(&current), total(&prior));
+            lines.extend([
+                format!("Current period, {} bars: changed {:+.2}%.", current.len(), pc),
+                format!("Prior period, {} bars: changed {:+.2}%.", prior.len(), pp),
+                format!("Difference: {:+.2} percentage points.", pc - pp),
+            ]);
+        }
+        None => lines.push("No prior-period data yet.".into()),
+    }
+    lines.push(String::new());
+    lines.push("Keys: up and down change range, Escape back.".into());
+    render(f, area, lines, 0);
+}
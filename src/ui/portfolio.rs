@@ -0,0 +1,306 @@
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table, TableState},
+};
+
+use crate::app::App;
+use crate::lib::instrument::format_price;
+use crate::ui::theme::Theme;
+
+use super::paper::{fmt_money, fmt_shares};
+
+/// Each group's share as a bar across `area`, over `limit` (a fraction)
+/// in the loss colour.
+fn draw_allocation(f: &mut Frame, theme: &Theme, title: &str, groups: &[(String, f64)], limit: f64, area: Rect) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(" {}, over {:.0}% flagged ", title, limit * 100.0));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    let label_w = groups.iter().map(|(g, _)| g.chars().count()).max().unwrap_or(0).min(16);
+    let bar_w = (inner.width as usize).saturating_sub(label_w + 9);
+    let lines: Vec<Line> = groups.iter()
+        .map(|(group, share)| {
+            let over = *share > limit;
+            let filled = ((share * bar_w as f64).round() as usize).min(bar_w);
+            let label: String = group.chars().take(label_w).collect();
+            let color = if over { theme.loss } else { theme.accent };
+            let pct = Style::default().fg(if over { theme.loss } else { theme.text });
+            Line::from(vec![
+                Span::styled(format!("{:<w$} ", label, w = label_w), Style::default().fg(theme.text)),
+                Span::styled("█".repeat(filled), Style::default().fg(color)),
+                Span::styled("░".repeat(bar_w - filled), Style::default().fg(theme.muted)),
+                Span::styled(format!(" {:>5.1}%", share * 100.0), if over { pct.add_modifier(Modifier::BOLD) } else { pct }),
+                Span::styled(if over { "!" } else { "" }, pct),
+            ])
+        })
+        .collect();
+    f.render_widget(Paragraph::new(lines), inner);
+}
+
+/// The lots held: what they are worth at the latest prices and the gain
+/// not yet taken, the return on the money put in and the risk taken for
+/// it, how the value is split
+/// by symbol and sector, each symbol's share of the risk, and the gain
+/// taken on lots sold, by year.
+pub fn draw_portfolio_ui(f: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
+    let portfolio = &app.portfolio;
+    let symbols = portfolio.symbols();
+    let years = portfolio.realized_by_year();
+    let price = |s: &str| app.portfolio_price(s);
+    let by_symbol = portfolio.allocation(price, str::to_string);
+    let by_sector = portfolio.allocation(price, |s| app.sector_of_symbol(s).to_string());
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(4),
+            Constraint::Min(5),
+            Constraint::Length(if by_symbol.is_empty() { 0 } else { by_symbol.len().clamp(1, 8) as u16 + 2 }),
+            Constraint::Length(symbols.len().max(years.len()).clamp(1, 8) as u16 + 3),
+            Constraint::Length(1),
+        ])
+        .split(area);
+
+    let history = |s: &str| app.portfolio_bars(s);
+    let now = chrono::Utc::now().timestamp();
+    let value = portfolio.value(price);
+    let cost = portfolio.cost();
+    let gain = value - cost;
+    let muted = Style::default().fg(theme.muted);
+    let rate = |label: &str, r: Option<f64>| match r {
+        Some(r) => vec![
+            Span::styled(format!("  {} ", label), muted),
+            Span::styled(format!("{:+.2}%", r * 100.0), Style::default().fg(theme.signed(r))),
+            Span::styled(" a year", muted),
+        ],
+        None => vec![Span::styled(format!("  {} --", label), muted)],
+    };
+    let mut summary = vec![
+        Span::styled(" Value ", muted),
+        Span::styled(fmt_money(value), Style::default().add_modifier(Modifier::BOLD)),
+        Span::styled("  Cost ", muted),
+        Span::raw(fmt_money(cost)),
+        Span::styled("  Unrealized ", muted),
+        Span::styled(fmt_money(gain), Style::default().fg(theme.signed(gain))),
+    ];
+    if cost > 0.0 {
+        summary.push(Span::styled(format!(" {:+.2}%", gain / cost * 100.0), Style::default().fg(theme.signed(gain))));
+    }
+    if !portfolio.realized.is_empty() {
+        let realized = portfolio.realized_gain();
+        summary.push(Span::styled("  Realized ", muted));
+        summary.push(Span::styled(fmt_money(realized), Style::default().fg(theme.signed(realized))));
+    }
+    summary.extend(rate("Money-weighted", portfolio.money_weighted_return(price, now)));
+    summary.extend(rate("Time-weighted", portfolio.time_weighted_return(price, history, now)));
+    let settings = &app.portfolio_settings;
+    let benchmark = settings.benchmark.trim().to_uppercase();
+    let metrics = portfolio.risk_metrics(price, history, app.portfolio_bars(&benchmark).map(|sd| (benchmark.as_str(), sd)), settings.risk_free_pct / 100.0);
+    let risk = match metrics {
+        Some(m) => {
+            let mut spans = vec![
+                Span::styled(" Volatility ", muted),
+                Span::raw(format!("{:.2}%", m.volatility * 100.0)),
+                Span::styled(" a year  Sharpe ", muted),
+                Span::raw(m.sharpe.map_or("--".into(), |s| format!("{:.2}", s))),
+                Span::styled("  Beta ", muted),
+                Span::raw(m.beta.map_or("--".into(), |b| format!("{:.2}", b))),
+                Span::styled(format!(" vs {}", benchmark), muted),
+                Span::styled("  Max drawdown ", muted),
+                Span::styled(format!("{:.2}%", -m.max_drawdown * 100.0), Style::default().fg(theme.signed(-m.max_drawdown))),
+            ];
+            if settings.risk_free_pct != 0.0 {
+                spans.push(Span::styled(format!("  (cash at {:.2}%)", settings.risk_free_pct), muted));
+            }
+            Line::from(spans)
+        }
+        None => Line::styled(" Volatility, Sharpe, beta and drawdown need each symbol's history", muted),
+    };
+    let title = match portfolio.since() {
+        Some(since) => format!(" Portfolio since {} ", since.format("%Y-%m-%d")),
+        None => " Portfolio ".into(),
+    };
+    f.render_widget(
+        Paragraph::new(vec![Line::from(summary), risk]).block(Block::default().borders(Borders::ALL).title(title)),
+        chunks[0],
+    );
+
+    let header = Row::new(["Symbol", "Quantity", "Cost basis", "Purchased", "Price", "Value", "Unrealized", "%"])
+        .style(Style::default().fg(theme.accent).add_modifier(Modifier::BOLD));
+    let rows: Vec<Row> = portfolio.positions.iter()
+        .map(|position| {
+            let symbol = &position.symbol;
+            let price = app.portfolio_price(symbol);
+            let value = position.value(price);
+            let gain = value - position.cost();
+            let pct = if position.cost() > 0.0 { gain / position.cost() * 100.0 } else { 0.0 };
+            let signed = |text: String| Cell::from(text).style(Style::default().fg(theme.signed(gain)));
+            Row::new(vec![
+                Cell::from(symbol.clone()),
+                Cell::from(fmt_shares(position.quantity)),
+                Cell::from(format_price(symbol, position.cost_basis)),
+                Cell::from(position.purchased.format("%Y-%m-%d").to_string()),
+                match price {
+                    Some(p) => Cell::from(format_price(symbol, p)),
+                    None => Cell::from("no price").style(muted),
+                },
+                Cell::from(fmt_money(value)),
+                signed(fmt_money(gain)),
+                signed(format!("{:+.2}%", pct)),
+            ])
+        })
+        .collect();
+    let table = Table::new(rows)
+        .header(header)
+        .widths(&[
+            Constraint::Length(10),
+            Constraint::Length(10),
+            Constraint::Length(12),
+            Constraint::Length(11),
+            Constraint::Length(12),
+            Constraint::Length(14),
+            Constraint::Length(13),
+            Constraint::Length(9),
+        ])
+        .style(Style::default().fg(theme.text))
+        .highlight_style(Style::default().fg(theme.selection_fg).bg(theme.selection_bg))
+        .block(Block::default().borders(Borders::ALL).title(match portfolio.transactions.len() {
+            0 => format!(" {} lots ", portfolio.positions.len()),
+            n => format!(" {} lots, {} transactions imported ", portfolio.positions.len(), n),
+        }));
+    let mut state = TableState::default();
+    if !portfolio.positions.is_empty() {
+        state.select(Some(app.portfolio_selected.min(portfolio.positions.len() - 1)));
+    }
+    f.render_stateful_widget(table, chunks[1], &mut state);
+
+    if !by_symbol.is_empty() {
+        let halves = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(chunks[2]);
+        let limits = &app.portfolio_settings;
+        draw_allocation(f, theme, "By symbol", &by_symbol, limits.symbol_limit_pct / 100.0, halves[0]);
+        draw_allocation(f, theme, "By sector", &by_sector, limits.sector_limit_pct / 100.0, halves[1]);
+    }
+
+    let bottom = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(chunks[3]);
+    let risk_block = Block::default().borders(Borders::ALL).title(" Risk: share of the volatility ");
+    match portfolio.risk(price, history) {
+        Some(contributions) => {
+            let header = Row::new(["Symbol", "Weight", "Risk share", ""])
+                .style(Style::default().fg(theme.accent).add_modifier(Modifier::BOLD));
+            let rows: Vec<Row> = contributions.iter()
+                .map(|c| {
+                    let flag = if c.dominates() {
+                        Cell::from("carries more risk than its weight").style(Style::default().fg(theme.loss))
+                    } else {
+                        Cell::from("")
+                    };
+                    Row::new(vec![
+                        Cell::from(c.symbol.clone()),
+                        Cell::from(format!("{:.1}%", c.weight * 100.0)),
+                        Cell::from(format!("{:.1}%", c.share * 100.0)),
+                        flag,
+                    ])
+                })
+                .collect();
+            let table = Table::new(rows)
+                .header(header)
+                .widths(&[Constraint::Length(10), Constraint::Length(8), Constraint::Length(11), Constraint::Min(10)])
+                .style(Style::default().fg(theme.text))
+                .block(risk_block);
+            f.render_widget(table, bottom[0]);
+        }
+        None => {
+            let text = if symbols.is_empty() {
+                "Press a to add a lot."
+            } else if app.offline {
+                "No history offline."
+            } else {
+                "Waiting for each symbol's history…"
+            };
+            f.render_widget(Paragraph::new(text).style(muted).block(risk_block), bottom[0]);
+        }
+    }
+
+    let realized_block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(" Realized by year, {} lots ", portfolio.lot_method.label()));
+    if years.is_empty() {
+        let text = "Lots closed by imported sells show here.";
+        f.render_widget(Paragraph::new(text).style(muted).block(realized_block), bottom[1]);
+    } else {
+        let header = Row::new(["Year", "Proceeds", "Cost", "Short-term", "Long-term", "Total"])
+            .style(Style::default().fg(theme.accent).add_modifier(Modifier::BOLD));
+        let signed = |v: f64| Cell::from(fmt_money(v)).style(Style::default().fg(theme.signed(v)));
+        let rows: Vec<Row> = years.iter()
+            .map(|y| Row::new(vec![
+                Cell::from(y.year.to_string()),
+                Cell::from(fmt_money(y.proceeds)),
+                Cell::from(fmt_money(y.cost)),
+                signed(y.short_term),
+                signed(y.long_term),
+                signed(y.gain()),
+            ]))
+            .collect();
+        let table = Table::new(rows)
+            .header(header)
+            .widths(&[
+                Constraint::Length(5),
+                Constraint::Length(12),
+                Constraint::Length(12),
+                Constraint::Length(11),
+                Constraint::Length(11),
+                Constraint::Length(11),
+            ])
+            .style(Style::default().fg(theme.text))
+            .block(realized_block);
+        f.render_widget(table, bottom[1]);
+    }
+
+    let help = Paragraph::new("↑↓ select │ a add │ e edit │ d remove │ Enter open details │ Esc back")
+        .style(muted);
+    f.render_widget(help, chunks[4]);
+}
+
+/// The 'a' / 'e' form for a lot.
+pub fn draw_position_form(f: &mut Frame, app: &App, area: Rect) {
+    let Some(form) = &app.position_form else { return };
+    let theme = &app.theme;
+    let width = area.width.saturating_sub(4).min(70);
+    let popup = Rect::new(area.x + (area.width - width) / 2, area.y + area.height / 4, width, 7);
+    let popup = popup.intersection(area);
+    f.render_widget(Clear, popup);
+
+    let action = if form.editing.is_some() { "Edit lot" } else { "Add lot" };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.accent))
+        .title(format!(" {} — Tab next field, Enter save, Esc cancel ", action));
+    let inner = block.inner(popup);
+    f.render_widget(block, popup);
+
+    let mut lines: Vec<Line> = ["Symbol", "Quantity", "Cost/share", "Purchased"].iter().zip(&form.fields).enumerate()
+        .map(|(i, (label, value))| {
+            let active = i == form.field;
+            let mut spans = vec![
+                Span::styled(format!("{:<11}", label), Style::default().fg(if active { theme.accent } else { theme.muted })),
+                Span::styled(value.clone(), Style::default().fg(theme.text)),
+            ];
+            if active {
+                spans.push(Span::styled("█", Style::default().fg(theme.muted)));
+            }
+            Line::from(spans)
+        })
+        .collect();
+    lines.push(match &form.error {
+        Some(e) => Line::styled(e.clone(), Style::default().fg(theme.loss)),
+        None => Line::styled("Cost per share with fees; date as YYYY-MM-DD", Style::default().fg(theme.muted)),
+    });
+    f.render_widget(Paragraph::new(lines), inner);
+}
@@ -0,0 +1,113 @@
+use ratatui::{
+    prelude::*,
+    widgets::Paragraph,
+};
+
+use crate::app::{App, UNCLASSIFIED_SECTOR};
+
+use super::{
+    heatmap::{daily_change, draw_cell, CELL_H, CELL_W},
+    layout::{MainHits, TileHit},
+};
+
+/// One sector's heading and the symbols under it, in display order.
+struct Group<'a> {
+    sector: &'a str,
+    members: Vec<usize>,
+}
+
+fn groups(app: &App) -> Vec<Group<'_>> {
+    let mut groups: Vec<Group> = Vec::new();
+    for index in app.sector_order() {
+        let sector = app.sector_of(index);
+        match groups.last_mut() {
+            Some(g) if g.sector == sector => g.members.push(index),
+            _ => groups.push(Group { sector, members: vec![index] }),
+        }
+    }
+    groups
+}
+
+/// Renders the watchlist as heatmap cells clustered under one heading per
+/// sector, each with the sector's average daily change. Scrolls to keep the
+/// selected cell on screen. Returns the columns per row, like the heatmap,
+/// and where each visible cell went.
+pub fn draw_sectors_ui(f: &mut Frame, app: &App, area: Rect) -> (usize, MainHits) {
+    let v = Layout::default().direction(Direction::Vertical).constraints([
+        Constraint::Length(1), Constraint::Min(0), Constraint::Length(1),
+    ]).split(area);
+    let theme = &app.theme;
+    let body = v[1];
+    let cols = (body.width / CELL_W).max(1) as usize;
+    let cell_w = body.width / cols as u16;
+    let groups = groups(app);
+
+    let classified = app.analyses.iter().filter(|a| app.profiles.contains_key(&a.analysis.symbol)).count();
+    f.render_widget(
+        Paragraph::new(format!(
+            "Bstock - Sectors  |  {} sectors  |  {}/{} classified  |  by {}",
+            groups.iter().filter(|g| g.sector != UNCLASSIFIED_SECTOR).count(),
+            classified, app.analyses.len(), app.main_sort.label(),
+        )).alignment(Alignment::Center),
+        v[0],
+    );
+
+    // Lay everything out top to bottom, then scroll so the selection shows
+    let mut y = 0u16;
+    let mut headings = Vec::new();
+    let mut cells = Vec::new();
+    for g in &groups {
+        headings.push((y, g));
+        y += 1;
+        for (slot, &index) in g.members.iter().enumerate() {
+            let row = (slot / cols) as u16;
+            cells.push((index, y + row * CELL_H, (slot % cols) as u16 * cell_w));
+        }
+        y += (g.members.len().div_ceil(cols) as u16) * CELL_H;
+    }
+    let selected_y = cells.iter().find(|c| c.0 == app.selected_index).map_or(0, |c| c.1);
+    let scroll = (selected_y + CELL_H).saturating_sub(body.height);
+    let visible = |top: u16, height: u16| top >= scroll && top + height <= scroll + body.height;
+
+    for (top, g) in headings {
+        if !visible(top, 1) {
+            continue;
+        }
+        let changes: Vec<f64> = g.members.iter()
+            .filter(|&&i| !app.analyses[i].stock_data.is_empty())
+            .filter_map(|&i| daily_change(&app.analyses[i]))
+            .collect();
+        let mut spans = vec![
+            Span::styled(format!(" {} ", g.sector), Style::default().fg(theme.accent).add_modifier(Modifier::BOLD)),
+            Span::styled(
+                format!("{} symbol{}", g.members.len(), if g.members.len() == 1 { "" } else { "s" }),
+                Style::default().fg(theme.muted),
+            ),
+        ];
+        if !changes.is_empty() {
+            let avg = changes.iter().sum::<f64>() / changes.len() as f64;
+            spans.push(Span::styled("  avg ", Style::default().fg(theme.muted)));
+            spans.push(Span::styled(format!("{:+.2}%", avg), Style::default().fg(theme.signed(avg))));
+        }
+        f.render_widget(Paragraph::new(Line::from(spans)), Rect::new(body.x, body.y + top - scroll, body.width, 1));
+    }
+
+    let mut hits = MainHits { per_page: 0, tiles: Vec::new() };
+    for (index, top, x) in cells {
+        if !visible(top, CELL_H) {
+            continue;
+        }
+        let cell = Rect::new(body.x + x, body.y + top - scroll, cell_w, CELL_H);
+        hits.tiles.push(TileHit { index, area: cell, selector: None });
+        draw_cell(f, app, index, cell);
+    }
+    hits.per_page = hits.tiles.len();
+
+    f.render_widget(
+        Paragraph::new("←→ select │ j/k row │ Enter details │ v switch view │ S signals │ e edit │ ? help │ q quit")
+            .style(Style::default().fg(theme.muted)),
+        v[2],
+    );
+
+    (cols, hits)
+}
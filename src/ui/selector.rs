@@ -4,28 +4,46 @@ use ratatui::{
 };
 use crate::data::TimeRange;
 
-// Function to render the time range selector
-pub fn render_time_range_selector(current_time_range: TimeRange, is_selected: bool) -> Paragraph<'static> {
-    let mut text = String::new();
-    let time_ranges = TimeRange::all();
-
-    for (i, tr) in time_ranges.iter().enumerate() {
-        if i > 0 {
-            text.push(' ');
-        }
-
-        if *tr == current_time_range {
+/// Each range's label in the selector, in order.
+fn selector_labels(current_time_range: TimeRange, is_selected: bool) -> Vec<(TimeRange, String)> {
+    TimeRange::all().iter().map(|tr| {
+        let label = if *tr == current_time_range {
             if is_selected {
-                text.push_str(&format!(" [{}] ", tr.as_str()));
+                format!(" [{}] ", tr.as_str())
             } else {
-                text.push_str(&format!(" ({}) ", tr.as_str()));
+                format!(" ({}) ", tr.as_str())
             }
         } else {
-            text.push_str(&format!(" {} ", tr.as_str()));
-        }
-    }
+            format!(" {} ", tr.as_str())
+        };
+        (*tr, label)
+    }).collect()
+}
+
+// Function to render the time range selector
+pub fn render_time_range_selector(current_time_range: TimeRange, is_selected: bool) -> Paragraph<'static> {
+    let text = selector_labels(current_time_range, is_selected)
+        .into_iter()
+        .map(|(_, label)| label)
+        .collect::<Vec<_>>()
+        .join(" ");
 
     Paragraph::new(text)
         .alignment(Alignment::Center)
         .block(Block::default())
 }
+
+/// The range whose label is at column `x` of a selector rendered in `area`.
+pub fn time_range_at(area: Rect, current_time_range: TimeRange, is_selected: bool, x: u16) -> Option<TimeRange> {
+    let labels = selector_labels(current_time_range, is_selected);
+    let total: usize = labels.iter().map(|(_, l)| l.len() + 1).sum::<usize>() - 1;
+    // Centred the way ratatui centres a line
+    let mut start = area.x as usize + (area.width as usize / 2).saturating_sub(total / 2);
+    for (tr, label) in labels {
+        if (start..start + label.len()).contains(&(x as usize)) {
+            return Some(tr);
+        }
+        start += label.len() + 1;
+    }
+    None
+}
@@ -0,0 +1,77 @@
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState},
+};
+
+use crate::app::{App, SignalSort};
+
+const WIDTHS: [Constraint; 4] = [
+    Constraint::Length(17),
+    Constraint::Length(8),
+    Constraint::Length(16),
+    Constraint::Min(10),
+];
+
+/// Renders every active signal across the watchlist as one sortable table.
+pub fn draw_signals_ui(f: &mut Frame, app: &App, area: Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(1)])
+        .split(area);
+
+    let signals = app.collect_signals();
+    let loaded = app.analyses.iter().filter(|a| !a.stock_data.is_empty()).count();
+
+    let theme = &app.theme;
+    let arrow = if app.signals_sort_desc { "▼" } else { "▲" };
+    let heading = |name: &str, col: SignalSort| {
+        if app.signals_sort == col { format!("{} {}", name, arrow) } else { name.to_string() }
+    };
+    let header = Row::new(vec![
+        heading("1 Time", SignalSort::Time),
+        heading("2 Symbol", SignalSort::Symbol),
+        heading("3 Signal", SignalSort::Kind),
+        "Detail".to_string(),
+    ])
+    .style(Style::default().fg(theme.accent).add_modifier(Modifier::BOLD));
+
+    let rows: Vec<Row> = signals
+        .iter()
+        .map(|sig| {
+            let time = app.zone.format_bar(&sig.symbol, sig.timestamp, "%Y-%m-%d %H:%M");
+            let color = match sig.kind.bias() {
+                Some(true) => theme.gain,
+                Some(false) => theme.loss,
+                None => theme.neutral,
+            };
+            Row::new(vec![
+                Cell::from(time),
+                Cell::from(sig.symbol.clone()),
+                Cell::from(sig.kind.label()).style(Style::default().fg(color)),
+                Cell::from(sig.detail.clone()),
+            ])
+        })
+        .collect();
+
+    let title = format!(
+        " Signals — {} active across {}/{} loaded symbols ",
+        signals.len(),
+        loaded,
+        app.analyses.len(),
+    );
+    let table = Table::new(rows)
+        .header(header)
+        .widths(&WIDTHS)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .highlight_style(Style::default().fg(theme.selection_fg).bg(theme.selection_bg));
+
+    let mut state = TableState::default();
+    if !signals.is_empty() {
+        state.select(Some(app.signals_selected.min(signals.len() - 1)));
+    }
+    f.render_stateful_widget(table, chunks[0], &mut state);
+
+    let help = Paragraph::new("↑↓ select │ 1/2/3 sort by time/symbol/signal (again to reverse) │ Enter open details │ Esc back")
+        .style(Style::default().fg(theme.muted));
+    f.render_widget(help, chunks[1]);
+}
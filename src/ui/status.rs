@@ -0,0 +1,134 @@
+use chrono::{DateTime, Local};
+use ratatui::{prelude::*, widgets::Paragraph};
+
+use crate::app::App;
+use crate::lib::persistence::DEFAULT_PROFILE;
+
+/// Minutes after which a symbol's data is shown as stale, and as old.
+const STALE_AFTER_MINUTES: i64 = 15;
+const OLD_AFTER_MINUTES: i64 = 60;
+
+/// How long ago data was fetched, e.g. "5m ago".
+pub fn age_text(fetched_at: DateTime<Local>) -> String {
+    let minutes = (Local::now() - fetched_at).num_minutes();
+    match minutes {
+        ..1 => "just now".to_string(),
+        1..60 => format!("{}m ago", minutes),
+        60..1440 => format!("{}h ago", minutes / 60),
+        _ => format!("{}d ago", minutes / 1440),
+    }
+}
+
+/// [`age_text`] muted while fresh, then in the neutral colour once stale
+/// and the loss colour once old.
+pub fn age_span(app: &App, fetched_at: DateTime<Local>) -> Span<'static> {
+    let minutes = (Local::now() - fetched_at).num_minutes();
+    let color = if minutes >= OLD_AFTER_MINUTES {
+        app.theme.loss
+    } else if minutes >= STALE_AFTER_MINUTES {
+        app.theme.neutral
+    } else {
+        app.theme.muted
+    };
+    Span::styled(age_text(fetched_at), Style::default().fg(color))
+}
+
+/// One-line summary of the watchlist's data: how much has loaded, when it
+/// last refreshed, what is being fetched and how many fetches failed.
+pub fn status_text(app: &App) -> String {
+    if app.read_only {
+        return "Read-only snapshot".into();
+    }
+    let loaded = app.analyses.iter().filter(|a| !a.stock_data.is_empty()).count();
+    let mut parts = Vec::new();
+    if app.offline {
+        parts.push("offline: cached data".into());
+    }
+    if app.profile() != DEFAULT_PROFILE {
+        parts.push(format!("profile {}", app.profile()));
+    }
+    parts.push(format!("{}/{} loaded", loaded, app.analyses.len()));
+    parts.push(match app.last_refresh {
+        Some(t) => format!("refreshed {}", app.zone.instant(&t).format("%H:%M:%S")),
+        None => "not refreshed yet".into(),
+    });
+    if app.auto_refresh_active() {
+        parts.push(format!("auto-refresh {}m", app.refresh.interval_minutes));
+    } else if app.auto_refresh_enabled() && !app.analyses.is_empty() {
+        parts.push("auto-refresh paused: markets closed".into());
+    }
+    if app.stream_live {
+        parts.push("● live".into());
+    } else if app.refresh.stream && !app.offline {
+        parts.push("live quotes connecting".into());
+    }
+    if !app.in_flight.is_empty() {
+        let requests: Vec<String> = app.in_flight.iter()
+            .map(|(sym, tr)| format!("{} {}", sym, tr.as_str()))
+            .collect();
+        parts.push(format!("fetching {}", requests.join(", ")));
+    }
+    match app.error_log.len() {
+        0 => {}
+        1 => parts.push("1 error (x to view)".into()),
+        n => parts.push(format!("{} errors (x to view)", n)),
+    }
+    parts.join(" │ ")
+}
+
+/// The watchlist's provider, plus how many loaded symbols a fallback
+/// served instead, e.g. "Yahoo Finance · 2 via Stooq".
+pub fn provider_text(app: &App) -> String {
+    let primary = app.provider.name();
+    let mut fallbacks: Vec<(&str, usize)> = Vec::new();
+    for source in app.analyses.iter().filter_map(|a| a.source).filter(|&s| s != primary) {
+        match fallbacks.iter_mut().find(|(name, _)| *name == source) {
+            Some((_, count)) => *count += 1,
+            None => fallbacks.push((source, 1)),
+        }
+    }
+    let mut text = primary.to_string();
+    for (name, count) in fallbacks {
+        text.push_str(&format!(" · {} via {}", count, name));
+    }
+    text
+}
+
+/// Bottom status bar shown under every view. While '/' search is open it
+/// holds the search box instead, as in vim.
+pub fn draw_status_bar(f: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
+    if let Some(query) = &app.search {
+        let matches = app.main_order().len();
+        let line = Line::from(vec![
+            Span::styled("/", Style::default().fg(theme.accent)),
+            Span::styled(query.as_str(), Style::default().fg(theme.text)),
+            Span::styled("█", Style::default().fg(theme.muted)),
+            Span::styled(
+                format!("  {} match{} — ←→ step, Enter select, Esc cancel", matches, if matches == 1 { "" } else { "es" }),
+                Style::default().fg(theme.muted),
+            ),
+        ]);
+        f.render_widget(Paragraph::new(line), area);
+        return;
+    }
+    let style = if !app.error_log.is_empty() {
+        Style::default().fg(theme.loss)
+    } else if app.in_flight.is_empty() {
+        Style::default().fg(theme.muted)
+    } else {
+        Style::default().fg(theme.accent)
+    };
+    let provider = provider_text(app);
+    let cols = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Min(0), Constraint::Length(provider.chars().count() as u16 + 2)])
+        .split(area);
+    f.render_widget(Paragraph::new(format!(" {}", status_text(app))).style(style), cols[0]);
+    f.render_widget(
+        Paragraph::new(format!("{} ", provider))
+            .alignment(Alignment::Right)
+            .style(Style::default().fg(theme.muted)),
+        cols[1],
+    );
+}
@@ -0,0 +1,119 @@
+use ratatui::{
+    prelude::*,
+    widgets::{
+        canvas::{Canvas, Line as CanvasLine},
+        Block, Borders, Cell, Paragraph, Row, Table, TableState, Wrap,
+    },
+};
+
+use crate::app::{AnalysisWithChartData, App};
+use crate::lib::strategy::{describe, run, StrategyResult};
+
+use super::{chart::nice_y_bounds, detail::fmt_assets, theme::Theme};
+
+/// Detail-view tab backtesting every strategy on the loaded bars: their
+/// results side by side, then the rules and equity curve of the
+/// highlighted one against buying and holding.
+pub fn draw_strategies_tab(f: &mut Frame, app: &App, data: &AnalysisWithChartData, area: Rect, theme: &Theme) {
+    let sd = &data.stock_data;
+    if sd.len() < 2 {
+        f.render_widget(
+            Paragraph::new("No data loaded yet.")
+                .style(Style::default().fg(theme.muted))
+                .block(Block::default().borders(Borders::ALL).title(" Strategies ")),
+            area,
+        );
+        return;
+    }
+    let strategies = app.strategies();
+    let results: Vec<StrategyResult> = strategies.iter().map(|s| run(s, sd, &app.strategy)).collect();
+    let v = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(strategies.len() as u16 + 4), Constraint::Length(3), Constraint::Min(6)])
+        .split(area);
+
+    let pct = |v: f64| Cell::from(format!("{:+.2}%", v)).style(Style::default().fg(theme.signed(v)));
+    let header = Row::new(["Strategy", "Trades", "Win rate", "Return", "Max drawdown", "vs buy & hold"])
+        .style(Style::default().fg(theme.accent).add_modifier(Modifier::BOLD));
+    let rows: Vec<Row> = strategies.iter().zip(&results).map(|(s, r)| {
+        Row::new(vec![
+            Cell::from(s.name.clone()),
+            Cell::from(r.trades.len().to_string()),
+            Cell::from(r.win_rate.map_or("--".into(), |w| format!("{:.0}%", w * 100.0))),
+            pct(r.total_return),
+            Cell::from(format!("-{:.2}%", r.max_drawdown)).style(Style::default().fg(theme.loss)),
+            pct(r.total_return - r.buy_and_hold),
+        ])
+    }).collect();
+    let account = &app.strategy;
+    let title = format!(
+        " Strategies | {} | {} capital, {:.0}% per trade, {} commission, {}% slippage ",
+        data.time_range.as_str(), fmt_assets(account.capital), account.position_pct, account.commission, account.slippage_pct,
+    );
+    let table = Table::new(rows)
+        .header(header)
+        .widths(&[
+            Constraint::Min(16),
+            Constraint::Length(7),
+            Constraint::Length(9),
+            Constraint::Length(10),
+            Constraint::Length(13),
+            Constraint::Length(14),
+        ])
+        .style(Style::default().fg(theme.text))
+        .highlight_style(Style::default().fg(theme.selection_fg).bg(theme.selection_bg))
+        .block(Block::default().borders(Borders::ALL).title(title));
+    let selected = app.list_selected.min(strategies.len().saturating_sub(1));
+    let mut state = TableState::default();
+    state.select(Some(selected));
+    f.render_stateful_widget(table, v[0], &mut state);
+
+    let (Some(strategy), Some(result)) = (strategies.get(selected), results.get(selected)) else { return };
+    let rules = vec![
+        Line::from(vec![Span::styled(" Buy when ", Style::default().fg(theme.gain)), Span::raw(describe(&strategy.entry))]),
+        Line::from(vec![Span::styled(" Sell when ", Style::default().fg(theme.loss)), Span::raw(describe(&strategy.exit))]),
+    ];
+    f.render_widget(Paragraph::new(rules).wrap(Wrap { trim: false }), v[1]);
+
+    draw_equity_curve(f, &strategy.name, result, &sd.closes, account.capital, v[2], theme);
+}
+
+/// The strategy's account value at each bar, and in grey what the capital
+/// would have been worth bought and held; entries are marked `▲` and exits
+/// `▼`.
+fn draw_equity_curve(f: &mut Frame, name: &str, result: &StrategyResult, closes: &[f64], capital: f64, area: Rect, theme: &Theme) {
+    let first = closes.first().copied().filter(|&c| c > 0.0).unwrap_or(1.0);
+    let hold: Vec<f64> = closes.iter().map(|c| capital * c / first).collect();
+    let (lo, hi) = result.equity.iter().chain(&hold)
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &v| (lo.min(v), hi.max(v)));
+    let (y_lo, y_hi, _) = nice_y_bounds(lo, hi);
+    let x_max = (result.equity.len() as f64 - 1.0).max(1.0);
+    let equity = &result.equity;
+    let title = format!(
+        " {} equity {} → {}  (buy & hold {}) ",
+        name,
+        fmt_assets(capital),
+        fmt_assets(equity.last().copied().unwrap_or(capital)),
+        fmt_assets(hold.last().copied().unwrap_or(capital)),
+    );
+    let canvas = Canvas::default()
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .marker(symbols::Marker::Braille)
+        .x_bounds([0.0, x_max])
+        .y_bounds([y_lo, y_hi])
+        .paint(|ctx| {
+            for (series, color) in [(&hold, theme.muted), (equity, theme.accent)] {
+                for (i, w) in series.windows(2).enumerate() {
+                    ctx.draw(&CanvasLine { x1: i as f64, y1: w[0], x2: (i + 1) as f64, y2: w[1], color });
+                }
+            }
+            for trade in &result.trades {
+                for (bar, mark, color) in [(Some(trade.entry_bar), "▲", theme.gain), (trade.exit_bar, "▼", theme.loss)] {
+                    if let Some(value) = bar.and_then(|i| equity.get(i)) {
+                        ctx.print(bar.unwrap_or(0) as f64, *value, Span::styled(mark, Style::default().fg(color)));
+                    }
+                }
+            }
+        });
+    f.render_widget(canvas, area);
+}
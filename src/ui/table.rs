@@ -0,0 +1,96 @@
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState},
+};
+
+use crate::app::{sma_spread, App, TableSort};
+use crate::data::calculate_volatility;
+use crate::lib::instrument::format_price;
+
+use super::metrics::fmt_symbol_volume;
+
+const WIDTHS: [Constraint; 7] = [
+    Constraint::Length(12),
+    Constraint::Length(12),
+    Constraint::Length(12),
+    Constraint::Length(14),
+    Constraint::Length(14),
+    Constraint::Length(12),
+    Constraint::Min(5),
+];
+
+/// Renders the watchlist as one sortable row per symbol.
+pub fn draw_table_ui(f: &mut Frame, app: &App, area: Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(1)])
+        .split(area);
+
+    let theme = &app.theme;
+    let arrow = if app.table_sort_desc { "▼" } else { "▲" };
+    let heading = |name: &str, col: TableSort| {
+        if app.table_sort == col { format!("{} {}", name, arrow) } else { name.to_string() }
+    };
+    let header = Row::new(vec![
+        heading("1 Symbol", TableSort::Symbol),
+        heading("2 Price", TableSort::Price),
+        heading("3 Change", TableSort::Change),
+        heading("4 SMA10/50", TableSort::Trend),
+        heading("5 Volatility", TableSort::Volatility),
+        heading("6 Volume", TableSort::Volume),
+        "Range".to_string(),
+    ])
+    .style(Style::default().fg(theme.accent).add_modifier(Modifier::BOLD));
+
+    let order = app.table_order();
+    let rows: Vec<Row> = order
+        .iter()
+        .map(|&i| {
+            let a = &app.analyses[i];
+            if a.stock_data.is_empty() {
+                return Row::new(vec![
+                    Cell::from(a.analysis.symbol.clone()),
+                    Cell::from("…").style(Style::default().fg(theme.muted)),
+                ]);
+            }
+            let change = a.analysis.recent_change.unwrap_or(0.0);
+            let trend = match sma_spread(&a.analysis) {
+                Some(s) => Cell::from(format!("{} {:+.2}%", if s >= 0.0 { "▲" } else { "▼" }, s))
+                    .style(Style::default().fg(theme.signed(s))),
+                None => Cell::from("--"),
+            };
+            let symbol = if app.heavily_shorted(&a.analysis.symbol) {
+                Cell::from(Line::from(vec![
+                    Span::raw(a.analysis.symbol.clone()),
+                    Span::styled(" ⚠", Style::default().fg(theme.loss)),
+                ]))
+            } else {
+                Cell::from(a.analysis.symbol.clone())
+            };
+            Row::new(vec![
+                symbol,
+                Cell::from(format_price(&a.analysis.symbol, a.analysis.current_price)),
+                Cell::from(format!("{:+.2}%", change)).style(Style::default().fg(theme.signed(change))),
+                trend,
+                Cell::from(format!("{:.2}%", calculate_volatility(&a.stock_data.closes))),
+                Cell::from(fmt_symbol_volume(&a.analysis.symbol, a.stock_data.volumes.last().copied().unwrap_or(0))),
+                Cell::from(a.time_range.as_str().to_string()),
+            ])
+        })
+        .collect();
+
+    let title = format!(" Bstock - Table ({} symbols) ", app.analyses.len());
+    let table = Table::new(rows)
+        .header(header)
+        .widths(&WIDTHS)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .highlight_style(Style::default().fg(theme.selection_fg).bg(theme.selection_bg));
+
+    let mut state = TableState::default();
+    state.select(order.iter().position(|&i| i == app.selected_index));
+    f.render_stateful_widget(table, chunks[0], &mut state);
+
+    let help = Paragraph::new("↑↓ select │ 1-6 sort by column (again to reverse) │ Enter details │ v switch view │ S signals │ e edit │ ? help │ q quit")
+        .style(Style::default().fg(theme.muted));
+    f.render_widget(help, chunks[1]);
+}
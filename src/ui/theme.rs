@@ -0,0 +1,231 @@
+//! Colour schemes. Every `ui/*` module takes its colours from a [`Theme`]
+//! rather than naming them, so a preset or a config override restyles the
+//! whole app.
+
+use ratatui::style::Color;
+use std::str::FromStr;
+
+use crate::lib::config::ThemeSettings;
+
+/// Built-in presets, selectable with `--theme` or `ui.theme.preset`.
+pub const PRESETS: [&str; 4] = ["default", "solarized", "gruvbox", "high-contrast"];
+
+#[derive(Clone, Debug)]
+pub struct Theme {
+    /// Rising prices, up candles and volume bars.
+    pub gain: Color,
+    /// Falling prices, down candles and volume bars.
+    pub loss: Color,
+    /// Neither up nor down (e.g. anomaly signals).
+    pub neutral: Color,
+    /// Headers, titles and the selected tile's border.
+    pub accent: Color,
+    /// Text drawn on an `accent` background.
+    pub on_accent: Color,
+    /// Key hints, axes and placeholders.
+    pub muted: Color,
+    pub text: Color,
+    /// Highlighted table/list row.
+    pub selection_bg: Color,
+    pub selection_fg: Color,
+    /// Y-axis price labels.
+    pub axis: Color,
+    pub grid: Color,
+    pub prediction: Color,
+    pub sma10: Color,
+    pub sma50: Color,
+    pub ema20: Color,
+    pub crosshair: Color,
+    pub prev_close: Color,
+    pub last_price: Color,
+    /// The two legs of a comparison overlay.
+    pub compare_a: Color,
+    pub compare_b: Color,
+    /// Heatmap buckets from ≤ -3% to ≥ +3%.
+    pub heatmap: [Color; 7],
+}
+
+fn rgb(hex: u32) -> Color {
+    Color::Rgb((hex >> 16) as u8, (hex >> 8) as u8, hex as u8)
+}
+
+/// Seven steps from `loss` through `mid` to `gain`.
+fn ramp(loss: u32, mid: u32, gain: u32) -> [Color; 7] {
+    let mix = |a: u32, b: u32, t: f64| {
+        let ch = |shift: u32| {
+            let (x, y) = ((a >> shift) & 0xff, (b >> shift) & 0xff);
+            (x as f64 + (y as f64 - x as f64) * t).round() as u32
+        };
+        (ch(16) << 16) | (ch(8) << 8) | ch(0)
+    };
+    let mut out = [Color::Reset; 7];
+    for (i, slot) in out.iter_mut().enumerate() {
+        *slot = match i {
+            0..=2 => rgb(mix(loss, mid, i as f64 / 3.0)),
+            _ => rgb(mix(mid, gain, (i - 3) as f64 / 3.0)),
+        };
+    }
+    out
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            gain: Color::Green,
+            loss: Color::Red,
+            neutral: Color::LightYellow,
+            accent: Color::Yellow,
+            on_accent: Color::Black,
+            muted: Color::DarkGray,
+            text: Color::White,
+            selection_bg: Color::DarkGray,
+            selection_fg: Color::Reset,
+            axis: Color::Cyan,
+            grid: Color::DarkGray,
+            prediction: Color::Gray,
+            sma10: Color::Yellow,
+            sma50: Color::Cyan,
+            ema20: Color::Magenta,
+            crosshair: Color::LightYellow,
+            prev_close: Color::LightBlue,
+            last_price: Color::LightGreen,
+            compare_a: Color::Cyan,
+            compare_b: Color::LightMagenta,
+            // Finviz-style scale
+            heatmap: [
+                Color::Rgb(246, 53, 56),
+                Color::Rgb(191, 64, 69),
+                Color::Rgb(139, 68, 78),
+                Color::Rgb(65, 69, 84),
+                Color::Rgb(53, 118, 78),
+                Color::Rgb(47, 158, 79),
+                Color::Rgb(48, 204, 90),
+            ],
+        }
+    }
+}
+
+impl Theme {
+    pub fn preset(name: &str) -> Option<Theme> {
+        let base = Theme::default();
+        match name {
+            "default" => Some(base),
+            "solarized" => Some(Theme {
+                gain: rgb(0x859900),
+                loss: rgb(0xdc322f),
+                neutral: rgb(0xb58900),
+                accent: rgb(0xb58900),
+                on_accent: rgb(0x002b36),
+                muted: rgb(0x586e75),
+                text: rgb(0x93a1a1),
+                selection_bg: rgb(0x073642),
+                selection_fg: rgb(0xeee8d5),
+                axis: rgb(0x2aa198),
+                grid: rgb(0x073642),
+                prediction: rgb(0x839496),
+                sma10: rgb(0xb58900),
+                sma50: rgb(0x268bd2),
+                ema20: rgb(0xd33682),
+                crosshair: rgb(0xeee8d5),
+                prev_close: rgb(0x6c71c4),
+                last_price: rgb(0x2aa198),
+                compare_a: rgb(0x268bd2),
+                compare_b: rgb(0xcb4b16),
+                heatmap: ramp(0xdc322f, 0x073642, 0x859900),
+            }),
+            "gruvbox" => Some(Theme {
+                gain: rgb(0xb8bb26),
+                loss: rgb(0xfb4934),
+                neutral: rgb(0xfabd2f),
+                accent: rgb(0xfabd2f),
+                on_accent: rgb(0x282828),
+                muted: rgb(0x928374),
+                text: rgb(0xebdbb2),
+                selection_bg: rgb(0x504945),
+                selection_fg: rgb(0xfbf1c7),
+                axis: rgb(0x8ec07c),
+                grid: rgb(0x504945),
+                prediction: rgb(0xa89984),
+                sma10: rgb(0xfabd2f),
+                sma50: rgb(0x83a598),
+                ema20: rgb(0xd3869b),
+                crosshair: rgb(0xfbf1c7),
+                prev_close: rgb(0x83a598),
+                last_price: rgb(0x8ec07c),
+                compare_a: rgb(0x83a598),
+                compare_b: rgb(0xfe8019),
+                heatmap: ramp(0xfb4934, 0x3c3836, 0xb8bb26),
+            }),
+            // Bright, fully saturated colours only; no dim greys for text
+            "high-contrast" => Some(Theme {
+                gain: Color::LightGreen,
+                loss: Color::LightRed,
+                neutral: Color::LightYellow,
+                accent: Color::LightYellow,
+                on_accent: Color::Black,
+                muted: Color::White,
+                text: Color::White,
+                selection_bg: Color::White,
+                selection_fg: Color::Black,
+                axis: Color::LightCyan,
+                grid: Color::Gray,
+                prediction: Color::White,
+                sma10: Color::LightYellow,
+                sma50: Color::LightCyan,
+                ema20: Color::LightMagenta,
+                crosshair: Color::White,
+                prev_close: Color::LightBlue,
+                last_price: Color::LightGreen,
+                compare_a: Color::LightCyan,
+                compare_b: Color::LightMagenta,
+                heatmap: ramp(0xff0000, 0x000000, 0x00ff00),
+            }),
+            _ => None,
+        }
+    }
+
+    /// The configured preset with any per-colour overrides applied. Colours
+    /// are ratatui names (`lightred`), `#rrggbb`, or a 0-255 palette index.
+    pub fn from_settings(settings: &ThemeSettings) -> Result<Theme, String> {
+        let mut theme = Theme::preset(&settings.preset).ok_or_else(|| {
+            format!("unknown theme `{}` (expected one of {})", settings.preset, PRESETS.join(", "))
+        })?;
+        for (key, value) in &settings.colors {
+            let color = Color::from_str(value)
+                .map_err(|_| format!("invalid colour `{}` for theme key `{}`", value, key))?;
+            *theme.slot(key).ok_or_else(|| format!("unknown theme key `{}`", key))? = color;
+        }
+        Ok(theme)
+    }
+
+    fn slot(&mut self, key: &str) -> Option<&mut Color> {
+        Some(match key {
+            "gain" => &mut self.gain,
+            "loss" => &mut self.loss,
+            "neutral" => &mut self.neutral,
+            "accent" => &mut self.accent,
+            "on_accent" => &mut self.on_accent,
+            "muted" => &mut self.muted,
+            "text" => &mut self.text,
+            "selection_bg" => &mut self.selection_bg,
+            "selection_fg" => &mut self.selection_fg,
+            "axis" => &mut self.axis,
+            "grid" => &mut self.grid,
+            "prediction" => &mut self.prediction,
+            "sma10" => &mut self.sma10,
+            "sma50" => &mut self.sma50,
+            "ema20" => &mut self.ema20,
+            "crosshair" => &mut self.crosshair,
+            "prev_close" => &mut self.prev_close,
+            "last_price" => &mut self.last_price,
+            "compare_a" => &mut self.compare_a,
+            "compare_b" => &mut self.compare_b,
+            _ => return None,
+        })
+    }
+
+    /// `gain` or `loss` by the sign of `v`.
+    pub fn signed(&self, v: f64) -> Color {
+        if v >= 0.0 { self.gain } else { self.loss }
+    }
+}
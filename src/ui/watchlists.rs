@@ -0,0 +1,18 @@
+use ratatui::{prelude::*, widgets::Tabs};
+
+use crate::app::App;
+
+/// One tab per named watchlist over the main view, the shown one selected.
+pub fn draw_watchlist_tabs(f: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
+    let titles: Vec<Line> = app.watchlists.iter().map(|w| Line::from(w.as_str())).collect();
+    let selected = app.watchlists.iter().position(|w| *w == app.active_watchlist).unwrap_or(0);
+    f.render_widget(
+        Tabs::new(titles)
+            .select(selected)
+            .style(Style::default().fg(theme.muted))
+            .highlight_style(Style::default().fg(theme.accent).add_modifier(Modifier::BOLD))
+            .divider("│"),
+        area,
+    );
+}